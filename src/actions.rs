@@ -0,0 +1,368 @@
+use anyhow::{Context, Result};
+use std::io::Write;
+use std::process::{Command, Stdio};
+
+use crate::config::{ActionItem, Config};
+use crate::hooks;
+
+/// What to do with the repositories picked in the picker.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Action {
+    /// Print the path for the shell wrapper to `cd` into (Enter)
+    Navigate,
+    /// Open the repositories in the editor (`keybindings.open_editor`)
+    OpenEditor,
+    /// Open the repositories' remotes in the browser (`keybindings.open_browser`)
+    OpenBrowser,
+    /// Create or attach to a tmux session per repository (`gitnav tmux`)
+    Tmux,
+    /// Pick a file in the repository and open it in the editor (`gitnav files`)
+    Files,
+    /// Pick a branch of the repository and change to it (`gitnav branch`)
+    Branch,
+    /// Pick a worktree of the repository and change to it (`gitnav worktree`)
+    Worktree,
+    /// Copy the paths to the clipboard (`keybindings.copy_path`)
+    CopyPath,
+    /// Rescan and reopen the picker (`keybindings.refresh`)
+    Refresh,
+}
+
+/// The picker's result: the chosen repositories and what to do with them.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Selection {
+    pub action: Action,
+    pub paths: Vec<String>,
+}
+
+impl Selection {
+    /// A selection made with Enter.
+    pub fn navigate(paths: Vec<String>) -> Self {
+        Self {
+            action: Action::Navigate,
+            paths,
+        }
+    }
+}
+
+/// An entry in the action menu (`actions.menu`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MenuEntry<'a> {
+    /// Change to the repository, as Enter does without the menu
+    Cd,
+    OpenEditor,
+    /// A command from `actions.items`
+    Command(&'a ActionItem),
+    OpenBrowser,
+    CopyPath,
+}
+
+impl MenuEntry<'_> {
+    pub fn label(&self) -> &str {
+        match self {
+            MenuEntry::Cd => "cd",
+            MenuEntry::OpenEditor => "open in editor",
+            MenuEntry::Command(item) => &item.name,
+            MenuEntry::OpenBrowser => "open remote",
+            MenuEntry::CopyPath => "copy path",
+        }
+    }
+}
+
+/// The action menu: cd first, then the editor, the configured commands, the
+/// remote, and copying the path.
+pub fn menu(config: &Config) -> Vec<MenuEntry<'_>> {
+    let mut entries = vec![MenuEntry::Cd, MenuEntry::OpenEditor];
+    entries.extend(config.actions.items.iter().map(MenuEntry::Command));
+    entries.extend([MenuEntry::OpenBrowser, MenuEntry::CopyPath]);
+    entries
+}
+
+/// Run a menu command through the shell in `repo`, and wait for it to exit.
+///
+/// Like the editor, it keeps the terminal on stdin and stderr and has its
+/// stdout sent to stderr, so terminal programs such as lazygit work.
+/// `GITNAV_REPO_PATH` and `GITNAV_REPO_NAME` are set.
+///
+/// # Errors
+///
+/// Returns an error if the command cannot be started or exits unsuccessfully
+pub fn run_command(command: &str, repo: &std::path::Path, name: &str) -> Result<()> {
+    let status = hooks::shell_command(command)
+        .current_dir(repo)
+        .env("GITNAV_REPO_PATH", repo)
+        .env("GITNAV_REPO_NAME", name)
+        .stdin(Stdio::inherit())
+        .stdout(std::io::stderr())
+        .status()
+        .with_context(|| format!("Failed to run: {}", command))?;
+    if !status.success() {
+        anyhow::bail!("Command exited with {}: {}", status, command);
+    }
+    Ok(())
+}
+
+/// Keys bound to actions, in fzf's key syntax (`ctrl-e`). Blank keys are left
+/// unbound, and Enter is left out since it always navigates.
+pub fn bindings(config: &Config) -> Vec<(&str, Action)> {
+    let keys = &config.keybindings;
+    [
+        (keys.select.as_str(), Action::Navigate),
+        (keys.open_editor.as_str(), Action::OpenEditor),
+        (keys.open_browser.as_str(), Action::OpenBrowser),
+        (keys.copy_path.as_str(), Action::CopyPath),
+        (keys.refresh.as_str(), Action::Refresh),
+    ]
+    .into_iter()
+    .map(|(key, action)| (key.trim(), action))
+    .filter(|(key, _)| !key.is_empty() && *key != "enter")
+    .collect()
+}
+
+/// The editor command: `ui.editor`, then `$VISUAL`, then `$EDITOR`.
+pub fn editor_command(config: &Config) -> Option<String> {
+    config
+        .ui
+        .editor
+        .clone()
+        .or_else(|| std::env::var("VISUAL").ok())
+        .or_else(|| std::env::var("EDITOR").ok())
+        .filter(|editor| !editor.trim().is_empty())
+}
+
+/// Open `paths` in `editor` and wait for it to exit.
+///
+/// The editor runs through the shell, so it may carry arguments (`code -n`).
+/// The paths go where `{path}` appears in it (`code {path} --wait`), or at the
+/// end when it has no placeholder. Its stdout goes to stderr: gitnav's stdout is read by the shell wrapper,
+/// while stderr is still the terminal, so terminal editors work too.
+///
+/// With `dir`, the editor starts in that directory (`gitnav files` opens
+/// files relative to the repository root).
+///
+/// # Errors
+///
+/// Returns an error if the editor cannot be started or exits unsuccessfully
+pub fn open_in_editor(editor: &str, paths: &[String], dir: Option<&std::path::Path>) -> Result<()> {
+    let mut command = hooks::shell_command(&editor_invocation(editor, paths));
+    #[cfg(not(windows))]
+    command.arg("gitnav").args(paths);
+    if let Some(dir) = dir {
+        command.current_dir(dir);
+    }
+
+    let status = command
+        .stdin(Stdio::inherit())
+        .stdout(std::io::stderr())
+        .status()
+        .with_context(|| format!("Failed to run editor: {}", editor))?;
+    if !status.success() {
+        anyhow::bail!("Editor exited with {}: {}", status, editor);
+    }
+    Ok(())
+}
+
+/// Open `url` in the default browser (`open` on macOS, `start` on Windows,
+/// `xdg-open` elsewhere).
+///
+/// # Errors
+///
+/// Returns an error if the opener cannot be started or exits unsuccessfully
+pub fn open_url(url: &str) -> Result<()> {
+    let mut command = if cfg!(target_os = "macos") {
+        Command::new("open")
+    } else if cfg!(windows) {
+        let mut command = Command::new("cmd");
+        command.args(["/C", "start", ""]);
+        command
+    } else {
+        Command::new("xdg-open")
+    };
+    let status = command
+        .arg(url)
+        .stdin(Stdio::null())
+        .stdout(std::io::stderr())
+        .status()
+        .with_context(|| format!("Failed to open {} in a browser", url))?;
+    if !status.success() {
+        anyhow::bail!("Opening {} in a browser failed with {}", url, status);
+    }
+    Ok(())
+}
+
+/// Copy `text` to the system clipboard with `pbcopy` on macOS, `clip` on
+/// Windows, and `wl-copy`, `xclip`, or `xsel` elsewhere (the first one found).
+///
+/// # Errors
+///
+/// Returns an error if no clipboard tool can be started or it fails
+pub fn copy_to_clipboard(text: &str) -> Result<()> {
+    let candidates: &[&[&str]] = if cfg!(target_os = "macos") {
+        &[&["pbcopy"]]
+    } else if cfg!(windows) {
+        &[&["clip"]]
+    } else {
+        &[
+            &["wl-copy"],
+            &["xclip", "-selection", "clipboard"],
+            &["xsel", "--clipboard", "--input"],
+        ]
+    };
+
+    for candidate in candidates {
+        let Ok(mut child) = Command::new(candidate[0])
+            .args(&candidate[1..])
+            .stdin(Stdio::piped())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn()
+        else {
+            continue;
+        };
+        if let Some(mut stdin) = child.stdin.take() {
+            stdin
+                .write_all(text.as_bytes())
+                .with_context(|| format!("Failed to write to {}", candidate[0]))?;
+        }
+        let status = child.wait()?;
+        if !status.success() {
+            anyhow::bail!("{} failed with {}", candidate[0], status);
+        }
+        return Ok(());
+    }
+    let names: Vec<&str> = candidates.iter().map(|c| c[0]).collect();
+    anyhow::bail!("No clipboard tool found (tried {})", names.join(", "))
+}
+
+/// The shell command line that opens `paths` in `editor`.
+///
+/// `sh -c` gets the paths as positional arguments, so they are never
+/// re-parsed by the shell; `cmd /C` has no such mechanism, so they are quoted.
+#[cfg(not(windows))]
+fn editor_invocation(editor: &str, _paths: &[String]) -> String {
+    place_paths(editor, "\"$@\"")
+}
+
+#[cfg(windows)]
+fn editor_invocation(editor: &str, paths: &[String]) -> String {
+    let quoted: Vec<String> = paths.iter().map(|p| format!("\"{}\"", p)).collect();
+    place_paths(editor, &quoted.join(" "))
+}
+
+/// Put `paths` where `{path}` appears in `editor`, or after it.
+fn place_paths(editor: &str, paths: &str) -> String {
+    if editor.contains("{path}") {
+        editor.replace("{path}", paths)
+    } else {
+        format!("{} {}", editor, paths)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bindings_skip_blank_keys() {
+        let mut config = Config::default();
+        assert_eq!(
+            bindings(&config),
+            vec![
+                ("ctrl-e", Action::OpenEditor),
+                ("ctrl-o", Action::OpenBrowser),
+                ("ctrl-y", Action::CopyPath),
+                ("ctrl-r", Action::Refresh)
+            ]
+        );
+
+        config.keybindings.select = "ctrl-j".to_string();
+        config.keybindings.open_editor = " ".to_string();
+        config.keybindings.open_browser = String::new();
+        config.keybindings.copy_path = String::new();
+        config.keybindings.refresh = String::new();
+        assert_eq!(bindings(&config), vec![("ctrl-j", Action::Navigate)]);
+    }
+
+    #[test]
+    fn test_menu_lists_builtins_around_commands() {
+        let mut config = Config::default();
+        config.actions.items.push(ActionItem {
+            name: "tig".to_string(),
+            command: "tig".to_string(),
+        });
+        let entries = menu(&config);
+        let labels: Vec<&str> = entries.iter().map(|e| e.label()).collect();
+        assert_eq!(
+            labels,
+            vec![
+                "cd",
+                "open in editor",
+                "lazygit",
+                "tig",
+                "open remote",
+                "copy path"
+            ]
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_run_command_in_repo() {
+        let tmp = tempfile::tempdir().unwrap();
+        let out = tmp.path().join("out");
+        let command = format!(
+            "printf '%s %s' \"$GITNAV_REPO_NAME\" \"$(pwd -P)\" > '{}'",
+            out.display()
+        );
+        run_command(&command, tmp.path(), "api").unwrap();
+        let expected = format!("api {}", tmp.path().canonicalize().unwrap().display());
+        assert_eq!(std::fs::read_to_string(&out).unwrap(), expected);
+        assert!(run_command("exit 2", tmp.path(), "api").is_err());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_open_in_editor_passes_paths_as_arguments() {
+        let tmp = tempfile::tempdir().unwrap();
+        let out = tmp.path().join("out");
+        let editor = format!("printf '%s|' > '{}'", out.display());
+        let paths = vec!["/src/my repo".to_string(), "/src/$HOME".to_string()];
+
+        open_in_editor(&editor, &paths, None).unwrap();
+        assert_eq!(
+            std::fs::read_to_string(&out).unwrap(),
+            "/src/my repo|/src/$HOME|"
+        );
+        assert!(open_in_editor("false", &paths, None).is_err());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_open_in_editor_places_paths_at_placeholder() {
+        let tmp = tempfile::tempdir().unwrap();
+        let out = tmp.path().join("out");
+        let editor = format!("printf '%s|' {{path}} --wait > '{}'", out.display());
+        let paths = vec!["/src/my repo".to_string()];
+
+        open_in_editor(&editor, &paths, None).unwrap();
+        assert_eq!(
+            std::fs::read_to_string(&out).unwrap(),
+            "/src/my repo|--wait|"
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_open_in_editor_starts_in_dir() {
+        let tmp = tempfile::tempdir().unwrap();
+        let out = tmp.path().join("out");
+        let editor = format!("printf '%s %s' \"$(pwd -P)\" > '{}'", out.display());
+
+        open_in_editor(&editor, &["src/main.rs".to_string()], Some(tmp.path())).unwrap();
+        let expected = format!(
+            "{} src/main.rs",
+            tmp.path().canonicalize().unwrap().display()
+        );
+        assert_eq!(std::fs::read_to_string(&out).unwrap(), expected);
+    }
+}
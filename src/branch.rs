@@ -0,0 +1,165 @@
+use anyhow::{Context, Result};
+use git2::{BranchType, Repository};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+
+use crate::scanner;
+use crate::worktree;
+
+/// A branch offered by `gitnav branch`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Branch {
+    /// Short name: `feature` for a local branch, `origin/feature` for a remote one
+    pub name: String,
+    /// A remote-tracking branch with no local branch of the same name
+    pub remote: bool,
+    /// Working tree that has the branch checked out, if any
+    pub worktree: Option<PathBuf>,
+    /// Commit time of the branch tip (Unix seconds)
+    time: i64,
+}
+
+impl Branch {
+    /// Picker line: the name padded to `width`, then where the branch is
+    /// checked out or that it only exists on a remote.
+    pub fn label(&self, width: usize) -> String {
+        let note = match &self.worktree {
+            Some(path) => scanner::shorten_home(path, dirs::home_dir().as_deref()),
+            None if self.remote => "remote".to_string(),
+            None => String::new(),
+        };
+        format!("{:<w$}  {}", self.name, note, w = width)
+            .trim_end()
+            .to_string()
+    }
+}
+
+/// Branches of the repository at `path`, for `gitnav branch`.
+///
+/// Local branches come first, then remote-tracking branches that have no
+/// local branch of the same name; each group is ordered by most recent commit.
+/// Branches checked out in a working tree carry its path.
+///
+/// # Errors
+///
+/// Returns an error if the repository or its branches cannot be read
+pub fn list(path: &Path) -> Result<Vec<Branch>> {
+    let repo = Repository::open(path)
+        .with_context(|| format!("Failed to open repository: {}", path.display()))?;
+    let checked_out: HashMap<String, PathBuf> = worktree::list(path)
+        .unwrap_or_default()
+        .into_iter()
+        .filter(|w| w.path.is_dir())
+        .filter_map(|w| Some((w.branch?, w.path)))
+        .collect();
+
+    let mut local = Vec::new();
+    let mut remote = Vec::new();
+    for (branch, kind) in repo.branches(None)?.flatten() {
+        let Some(name) = branch.name().ok().flatten().map(String::from) else {
+            continue;
+        };
+        let time = branch
+            .get()
+            .peel_to_commit()
+            .map(|c| c.time().seconds())
+            .unwrap_or_default();
+        match kind {
+            BranchType::Local => local.push(Branch {
+                worktree: checked_out.get(&name).cloned(),
+                name,
+                remote: false,
+                time,
+            }),
+            BranchType::Remote if !name.ends_with("/HEAD") => remote.push(Branch {
+                name,
+                remote: true,
+                worktree: None,
+                time,
+            }),
+            BranchType::Remote => {}
+        }
+    }
+    remote.retain(|r| {
+        let short = r.name.split_once('/').map_or(r.name.as_str(), |(_, b)| b);
+        !local.iter().any(|l| l.name == short)
+    });
+    local.sort_by(|a, b| b.time.cmp(&a.time).then_with(|| a.name.cmp(&b.name)));
+    remote.sort_by(|a, b| b.time.cmp(&a.time).then_with(|| a.name.cmp(&b.name)));
+    local.extend(remote);
+    Ok(local)
+}
+
+/// Check out `branch` in the working tree at `path` with `git checkout`.
+///
+/// A remote branch gets a local branch tracking it. Uncommitted changes are
+/// carried over as git carries them; git refuses when they would be
+/// overwritten. git's output goes to stderr, keeping stdout for the path.
+///
+/// # Errors
+///
+/// Returns an error if git cannot be started or the checkout fails
+pub fn checkout(path: &Path, branch: &Branch) -> Result<()> {
+    let mut cmd = Command::new("git");
+    cmd.arg("-C").arg(path).arg("checkout");
+    if branch.remote {
+        cmd.arg("--track");
+    }
+    let status = cmd
+        .arg(&branch.name)
+        .arg("--")
+        .stdin(Stdio::null())
+        .stdout(std::io::stderr())
+        .status()
+        .context("Failed to run git")?;
+    if !status.success() {
+        anyhow::bail!("git checkout {} failed with {}", branch.name, status);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::commit;
+
+    #[test]
+    fn test_list_orders_and_marks_branches() {
+        let tmp = tempfile::tempdir().unwrap();
+        let path = tmp.path().join("api");
+        let repo = Repository::init(&path).unwrap();
+        commit(&repo, "first", Some(1_000));
+        let main = repo.head().unwrap().shorthand().unwrap().to_string();
+        let first = repo.head().unwrap().peel_to_commit().unwrap();
+        repo.branch("old", &first, false).unwrap();
+        commit(&repo, "second", Some(2_000));
+        let second = repo.head().unwrap().peel_to_commit().unwrap();
+        repo.branch("fix", &second, false).unwrap();
+        repo.reference("refs/remotes/origin/fix", second.id(), false, "")
+            .unwrap();
+        repo.reference("refs/remotes/origin/feature", first.id(), false, "")
+            .unwrap();
+        let fix_tree = tmp.path().join("api-fix");
+        let fix = repo.find_branch("fix", BranchType::Local).unwrap();
+        let mut opts = git2::WorktreeAddOptions::new();
+        opts.reference(Some(fix.get()));
+        repo.worktree("fix", &fix_tree, Some(&opts)).unwrap();
+
+        let branches = list(&path).unwrap();
+        let names: Vec<&str> = branches.iter().map(|b| b.name.as_str()).collect();
+        assert_eq!(names, vec!["fix", main.as_str(), "old", "origin/feature"]);
+        assert_eq!(
+            branches[0]
+                .worktree
+                .as_deref()
+                .map(|p| p.canonicalize().unwrap()),
+            Some(fix_tree.canonicalize().unwrap())
+        );
+        assert!(branches[1].worktree.is_some());
+        assert_eq!(branches[2].worktree, None);
+        assert!(branches[3].remote);
+        assert_eq!(branches[3].label(14), "origin/feature  remote");
+        assert_eq!(branches[2].label(14), "old");
+    }
+}
@@ -0,0 +1,232 @@
+use anyhow::{Context, Result};
+use std::io::{self, BufRead, Write};
+
+use crate::config::ConfirmMode;
+use crate::scanner::GitRepo;
+
+/// Maximum number of repositories listed in a confirmation summary.
+const SUMMARY_LIMIT: usize = 10;
+
+/// Decide whether a bulk operation must be confirmed under the configured policy.
+pub fn needs_confirmation(mode: &ConfirmMode, destructive: bool) -> bool {
+    match mode {
+        ConfirmMode::Always => true,
+        ConfirmMode::Destructive => destructive,
+        ConfirmMode::Never => false,
+    }
+}
+
+/// Heuristically decide whether a command run by `exec` can discard work or delete data.
+///
+/// Matches plain `rm`/`rmdir` and git subcommands such as `reset --hard`, `clean`,
+/// forced pushes, branch deletion, and `stash drop`/`clear`.
+pub fn looks_destructive(command: &[String]) -> bool {
+    let Some(program) = command.first() else {
+        return false;
+    };
+    let program = program.rsplit(['/', '\\']).next().unwrap_or(program);
+    let args: Vec<&str> = command[1..].iter().map(String::as_str).collect();
+    let has = |flag: &str| args.contains(&flag);
+
+    match program {
+        "rm" | "rmdir" | "del" | "rd" | "shred" => true,
+        "git" | "git.exe" => match args.first().copied() {
+            Some("reset") => has("--hard") || has("--merge") || has("--keep"),
+            Some("clean") => true,
+            Some("push") => {
+                has("--force")
+                    || has("-f")
+                    || has("--delete")
+                    || has("-d")
+                    || args
+                        .iter()
+                        .any(|a| a.starts_with("--force-with-lease") || a.starts_with("--mirror"))
+            }
+            Some("branch") => has("-d") || has("-D") || has("--delete"),
+            Some("tag") => has("-d") || has("--delete"),
+            Some("checkout") => has("--") || has(".") || has("-f") || has("--force"),
+            Some("restore") | Some("rm") => true,
+            Some("stash") => matches!(args.get(1).copied(), Some("drop") | Some("clear")),
+            Some("worktree") => matches!(args.get(1).copied(), Some("remove") | Some("prune")),
+            Some("gc") => args.iter().any(|a| a.starts_with("--prune")),
+            Some("filter-branch") | Some("filter-repo") => true,
+            _ => false,
+        },
+        _ => false,
+    }
+}
+
+/// Ask the user to confirm a bulk operation when the configured policy requires it.
+///
+/// Prints a summary of the affected repositories to stderr and reads the answer
+/// from stdin. Returns `Ok(true)` when the operation should proceed.
+///
+/// # Arguments
+///
+/// * `action` - Short description of the operation (e.g. "Delete 3 gone branches")
+/// * `repos` - Repositories the operation will touch
+/// * `mode` - Configured confirmation policy (`[bulk] confirm`)
+/// * `destructive` - Whether the operation can discard work or delete data
+/// * `assume_yes` - Skip the prompt (`--yes`)
+///
+/// # Errors
+///
+/// Returns an error if confirmation is required but stdin is not interactive
+pub fn confirm(
+    action: &str,
+    repos: &[GitRepo],
+    mode: &ConfirmMode,
+    destructive: bool,
+    assume_yes: bool,
+) -> Result<bool> {
+    if assume_yes || repos.is_empty() || !needs_confirmation(mode, destructive) {
+        return Ok(true);
+    }
+
+    if !atty::is(atty::Stream::Stdin) {
+        anyhow::bail!(
+            "{} needs confirmation but stdin is not a terminal; pass --yes to proceed",
+            action
+        );
+    }
+
+    let stdin = io::stdin();
+    confirm_with(action, repos, &mut stdin.lock(), &mut io::stderr())
+}
+
+/// Ask a yes/no `question` on stderr and read the answer from stdin, for
+/// single-repository operations such as checking out over uncommitted changes.
+///
+/// Returns `None` without asking when stdin is not a terminal, so the caller
+/// can explain how to proceed instead.
+///
+/// # Errors
+///
+/// Returns an error if the prompt cannot be written or the answer read
+pub fn ask(question: &str) -> Result<Option<bool>> {
+    if !atty::is(atty::Stream::Stdin) {
+        return Ok(None);
+    }
+    let stdin = io::stdin();
+    ask_with(question, &mut stdin.lock(), &mut io::stderr()).map(Some)
+}
+
+/// Prompt implementation with injectable streams.
+fn confirm_with<R: BufRead, W: Write>(
+    action: &str,
+    repos: &[GitRepo],
+    input: &mut R,
+    out: &mut W,
+) -> Result<bool> {
+    writeln!(out, "{} in {} repositories:", action, repos.len())?;
+    for repo in repos.iter().take(SUMMARY_LIMIT) {
+        writeln!(out, "  {}  {}", repo.name, repo.path.display())?;
+    }
+    if repos.len() > SUMMARY_LIMIT {
+        writeln!(out, "  ... and {} more", repos.len() - SUMMARY_LIMIT)?;
+    }
+    ask_with("Proceed?", input, out)
+}
+
+/// Write `question` with a `[y/N]` hint and read the answer; anything but
+/// `y` or `yes` is a no.
+fn ask_with<R: BufRead, W: Write>(question: &str, input: &mut R, out: &mut W) -> Result<bool> {
+    write!(out, "{} [y/N] ", question)?;
+    out.flush()?;
+
+    let mut answer = String::new();
+    input
+        .read_line(&mut answer)
+        .context("Failed to read confirmation")?;
+
+    Ok(matches!(answer.trim().to_lowercase().as_str(), "y" | "yes"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    fn cmd(parts: &[&str]) -> Vec<String> {
+        parts.iter().map(|s| s.to_string()).collect()
+    }
+
+    fn repos(n: usize) -> Vec<GitRepo> {
+        (0..n)
+            .map(|i| GitRepo::new(PathBuf::from(format!("/src/repo{}", i))))
+            .collect()
+    }
+
+    #[test]
+    fn test_needs_confirmation_policy() {
+        assert!(needs_confirmation(&ConfirmMode::Always, false));
+        assert!(needs_confirmation(&ConfirmMode::Destructive, true));
+        assert!(!needs_confirmation(&ConfirmMode::Destructive, false));
+        assert!(!needs_confirmation(&ConfirmMode::Never, true));
+    }
+
+    #[test]
+    fn test_looks_destructive() {
+        assert!(looks_destructive(&cmd(&["rm", "-rf", "target"])));
+        assert!(looks_destructive(&cmd(&["git", "reset", "--hard"])));
+        assert!(looks_destructive(&cmd(&["git", "clean", "-fdx"])));
+        assert!(looks_destructive(&cmd(&[
+            "git",
+            "push",
+            "--force-with-lease"
+        ])));
+        assert!(looks_destructive(&cmd(&["git", "branch", "-D", "old"])));
+        assert!(looks_destructive(&cmd(&["/usr/bin/git", "stash", "drop"])));
+
+        assert!(!looks_destructive(&cmd(&["git", "status"])));
+        assert!(!looks_destructive(&cmd(&["git", "fetch", "--all"])));
+        assert!(!looks_destructive(&cmd(&["git", "push"])));
+        assert!(!looks_destructive(&cmd(&["cargo", "build"])));
+        assert!(!looks_destructive(&[]));
+    }
+
+    #[test]
+    fn test_confirm_with_accepts_yes() {
+        let mut out = Vec::new();
+        let ok = confirm_with("Run", &repos(2), &mut "y\n".as_bytes(), &mut out).unwrap();
+        assert!(ok);
+        let text = String::from_utf8(out).unwrap();
+        assert!(text.contains("Run in 2 repositories"));
+        assert!(text.contains("repo1"));
+    }
+
+    #[test]
+    fn test_confirm_with_defaults_to_no() {
+        let mut out = Vec::new();
+        assert!(!confirm_with("Run", &repos(1), &mut "\n".as_bytes(), &mut out).unwrap());
+        assert!(!confirm_with("Run", &repos(1), &mut "nope\n".as_bytes(), &mut out).unwrap());
+    }
+
+    #[test]
+    fn test_confirm_with_truncates_summary() {
+        let mut out = Vec::new();
+        confirm_with("Run", &repos(15), &mut "n\n".as_bytes(), &mut out).unwrap();
+        let text = String::from_utf8(out).unwrap();
+        assert!(text.contains("... and 5 more"));
+        assert!(!text.contains("repo12"));
+    }
+
+    #[test]
+    fn test_ask_with() {
+        let mut out = Vec::new();
+        let question = "api has uncommitted changes. Check out main anyway?";
+        assert!(ask_with(question, &mut "YES\n".as_bytes(), &mut out).unwrap());
+        assert!(!ask_with(question, &mut "".as_bytes(), &mut out).unwrap());
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            format!("{} [y/N] {} [y/N] ", question, question)
+        );
+    }
+
+    #[test]
+    fn test_confirm_skipped_when_not_required() {
+        assert!(confirm("Run", &repos(3), &ConfirmMode::Never, true, false).unwrap());
+        assert!(confirm("Run", &repos(3), &ConfirmMode::Always, true, true).unwrap());
+        assert!(confirm("Run", &[], &ConfirmMode::Always, true, false).unwrap());
+    }
+}
@@ -1,18 +1,73 @@
 use anyhow::{Context, Result};
-use sha2::{Digest, Sha256};
+use rusqlite::params;
+use std::collections::HashMap;
 use std::fs;
 use std::path::{Path, PathBuf};
-use std::time::SystemTime;
 
-use crate::scanner::GitRepo;
+use crate::parallel;
+use crate::profile;
+use crate::scanner::{self, GitRepo};
+use crate::state::{self, State};
+
+/// A cached repository and the metadata read from it when the cache was written.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct CachedRepo {
+    name: String,
+    path: PathBuf,
+    branch: Option<String>,
+    /// Unix timestamp of the HEAD commit
+    last_commit: Option<i64>,
+    /// Tracked files had uncommitted changes
+    dirty: bool,
+}
+
+/// Branch and dirty state of a repository as recorded when the cache was written.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CachedMeta {
+    pub branch: Option<String>,
+    pub dirty: bool,
+}
+
+impl CachedRepo {
+    fn from_repo(repo: &GitRepo) -> Self {
+        let (branch, last_commit, dirty) = git2::Repository::open(&repo.path)
+            .ok()
+            .and_then(|r| {
+                let head = r.head().ok()?;
+                let branch = head
+                    .is_branch()
+                    .then(|| head.shorthand().map(String::from))
+                    .flatten();
+                Some((
+                    branch,
+                    head.peel_to_commit().ok().map(|c| c.time().seconds()),
+                    scanner::has_tracked_changes(&r),
+                ))
+            })
+            .unwrap_or_default();
+        Self {
+            name: repo.name.clone(),
+            path: repo.path.clone(),
+            branch,
+            last_commit,
+            dirty,
+        }
+    }
+}
 
 /// Manages caching of repository lists with TTL (time-to-live) validation.
 ///
-/// Uses SHA256 hashing to generate deterministic cache keys for search paths
-/// and stores repositories as tab-separated values with a configurable TTL.
+/// Scan results live in the `scans` and `repos` tables of the cache directory's
+/// database (`scans.db`, see [`State::open_cache`]), keyed by profile and
+/// search paths, so dropping a few repositories touches only their rows. The
+/// cache directory also holds the other caches (remote descriptions, GitHub
+/// stats, previews).
 #[derive(Debug)]
 pub struct Cache {
     cache_dir: PathBuf,
+    state: State,
+    /// Active profile, or empty for none
+    profile: String,
     ttl_seconds: u64,
 }
 
@@ -21,7 +76,9 @@ impl Cache {
     ///
     /// # Arguments
     ///
+    /// * `base_dir` - Cache directory, usually from [`Cache::resolve_dir`]
     /// * `ttl_seconds` - Time-to-live in seconds for cached data
+    /// * `profile` - Active profile; its scans and cache files are kept apart
     ///
     /// # Returns
     ///
@@ -29,20 +86,40 @@ impl Cache {
     ///
     /// # Errors
     ///
-    /// Returns an error if the cache directory cannot be determined or created.
-    pub fn new(ttl_seconds: u64) -> Result<Self> {
-        let cache_dir = Self::get_cache_dir()?;
+    /// Returns an error if the cache directory cannot be created or its scan
+    /// database cannot be opened.
+    pub fn new(base_dir: &Path, ttl_seconds: u64, profile: Option<&str>) -> Result<Self> {
+        let cache_dir = profile::scoped_dir(base_dir, profile);
         fs::create_dir_all(&cache_dir).with_context(|| {
             format!("Failed to create cache directory: {}", cache_dir.display())
         })?;
+        // One database for every profile, in the unscoped directory
+        let state = State::open_cache(base_dir)?;
 
         Ok(Self {
             cache_dir,
+            state,
+            profile: profile.unwrap_or_default().to_string(),
             ttl_seconds,
         })
     }
 
-    /// Get the cache directory path
+    /// Resolve the cache directory from `cache.directory` (or `GITNAV_CACHE_DIR`).
+    ///
+    /// Falls back to the platform cache directory (`~/.cache/gitnav` on Linux).
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if no directory is configured and the platform cache
+    /// directory cannot be determined.
+    pub fn resolve_dir(directory: Option<&str>) -> Result<PathBuf> {
+        match directory {
+            Some(dir) => Ok(PathBuf::from(shellexpand::tilde(dir).to_string())),
+            None => Self::get_cache_dir(),
+        }
+    }
+
+    /// Get the default cache directory path
     fn get_cache_dir() -> Result<PathBuf> {
         dirs::cache_dir()
             .map(|p| p.join("gitnav"))
@@ -56,34 +133,56 @@ impl Cache {
 
     /// List all cache files in the cache directory
     ///
+    /// Without a profile this includes the cache files of every profile, the
+    /// cached remote descriptions and GitHub stats, and the cached previews.
+    /// Scan caches written by earlier versions are listed too, so
+    /// `gitnav clear-cache` removes them.
+    ///
     /// # Returns
     ///
     /// A vector of paths to cache files, or an error if the directory cannot be read
     pub fn list_cache_files(&self) -> Result<Vec<PathBuf>> {
+        let mut files = Self::cache_files_in(&self.cache_dir)?;
+
+        let profiles_dir = self.cache_dir.join("profiles");
+        if profiles_dir.is_dir() {
+            for entry in fs::read_dir(&profiles_dir).with_context(|| {
+                format!("Failed to read cache directory: {}", profiles_dir.display())
+            })? {
+                let entry = entry.with_context(|| {
+                    format!("Failed to read cache entry in {}", profiles_dir.display())
+                })?;
+                files.extend(Self::cache_files_in(&entry.path())?);
+            }
+        }
+        files.extend(Self::cache_files_in(&self.cache_dir.join("descriptions"))?);
+        files.extend(Self::cache_files_in(&self.cache_dir.join("github"))?);
+        files.extend(Self::cache_files_in(&self.cache_dir.join("previews"))?);
+
+        files.sort();
+        Ok(files)
+    }
+
+    /// List the `.cache` files directly inside `dir`
+    fn cache_files_in(dir: &Path) -> Result<Vec<PathBuf>> {
         let mut files = Vec::new();
 
-        if !self.cache_dir.exists() {
+        if !dir.is_dir() {
             return Ok(files);
         }
 
-        let entries = fs::read_dir(&self.cache_dir).with_context(|| {
-            format!(
-                "Failed to read cache directory: {}",
-                self.cache_dir.display()
-            )
-        })?;
+        let entries = fs::read_dir(dir)
+            .with_context(|| format!("Failed to read cache directory: {}", dir.display()))?;
 
         for entry in entries {
-            let entry = entry.with_context(|| {
-                format!("Failed to read cache entry in {}", self.cache_dir.display())
-            })?;
+            let entry = entry
+                .with_context(|| format!("Failed to read cache entry in {}", dir.display()))?;
             let path = entry.path();
             if path.is_file() && path.extension().is_some_and(|ext| ext == "cache") {
                 files.push(path);
             }
         }
 
-        files.sort();
         Ok(files)
     }
 
@@ -105,13 +204,29 @@ impl Cache {
         Ok(total_size)
     }
 
-    /// Generate cache file path for a given search path
-    fn cache_file_path<P: AsRef<Path>>(&self, search_path: P) -> PathBuf {
-        let mut hasher = Sha256::new();
-        hasher.update(search_path.as_ref().to_string_lossy().as_bytes());
-        let hash = format!("{:x}", hasher.finalize());
+    /// Number of cached scans: this profile's, or every profile's without one.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the scan database cannot be read
+    pub fn scan_count(&self) -> Result<usize> {
+        let count: i64 = if self.profile.is_empty() {
+            self.state
+                .conn()
+                .query_row("SELECT COUNT(*) FROM scans", [], |row| row.get(0))?
+        } else {
+            self.state.conn().query_row(
+                "SELECT COUNT(*) FROM scans WHERE profile = ?1",
+                [&self.profile],
+                |row| row.get(0),
+            )?
+        };
+        Ok(count as usize)
+    }
 
-        self.cache_dir.join(format!("repos_{}.cache", &hash[..16]))
+    /// The key a search path is stored under.
+    fn scan_key<P: AsRef<Path>>(search_path: P) -> String {
+        state::path_text(search_path.as_ref())
     }
 
     /// Check if cached data exists and is still valid (within TTL).
@@ -122,30 +237,23 @@ impl Cache {
     ///
     /// # Returns
     ///
-    /// `true` if a valid cache file exists and hasn't expired, `false` otherwise
+    /// `true` if a scan is cached and hasn't expired, `false` otherwise
     pub fn is_valid<P: AsRef<Path>>(&self, search_path: P) -> bool {
-        let cache_path = self.cache_file_path(search_path);
+        let scanned_at: Option<i64> = self
+            .state
+            .conn()
+            .query_row(
+                "SELECT scanned_at FROM scans WHERE profile = ?1 AND scan_key = ?2",
+                params![self.profile, Self::scan_key(search_path)],
+                |row| row.get(0),
+            )
+            .ok();
 
-        if !cache_path.exists() {
+        let Some(scanned_at) = scanned_at else {
             return false;
-        }
-
-        let metadata = match fs::metadata(&cache_path) {
-            Ok(m) => m,
-            Err(_) => return false,
         };
-
-        let modified = match metadata.modified() {
-            Ok(m) => m,
-            Err(_) => return false,
-        };
-
-        let age = match SystemTime::now().duration_since(modified) {
-            Ok(d) => d.as_secs(),
-            Err(_) => return false,
-        };
-
-        age < self.ttl_seconds
+        let age = chrono::Utc::now().timestamp() - scanned_at;
+        (0..self.ttl_seconds as i64).contains(&age)
     }
 
     /// Load repository list from cache.
@@ -156,35 +264,87 @@ impl Cache {
     ///
     /// # Returns
     ///
-    /// A vector of `GitRepo` instances parsed from the cache file
+    /// The cached repositories in scan order, or `None` if nothing is cached
     ///
     /// # Errors
     ///
-    /// Returns an error if the cache file cannot be read or parsed
-    pub fn load<P: AsRef<Path>>(&self, search_path: P) -> Result<Vec<GitRepo>> {
-        let cache_path = self.cache_file_path(search_path);
-        let contents = fs::read_to_string(&cache_path)
-            .with_context(|| format!("Failed to read cache file: {}", cache_path.display()))?;
-
-        let repos: Vec<GitRepo> = contents
-            .lines()
-            .filter_map(|line| {
-                let parts: Vec<&str> = line.split('\t').collect();
-                if parts.len() == 2 {
-                    Some(GitRepo {
-                        name: parts[0].to_string(),
-                        path: PathBuf::from(parts[1]),
+    /// Returns an error if the scan database cannot be read
+    pub fn load<P: AsRef<Path>>(&self, search_path: P) -> Result<Option<Vec<GitRepo>>> {
+        Ok(self.read(search_path)?.map(|repos| {
+            repos
+                .into_iter()
+                .map(|repo| GitRepo {
+                    name: repo.name,
+                    path: repo.path,
+                })
+                .collect()
+        }))
+    }
+
+    /// Load the branch and dirty state recorded for each cached repository,
+    /// keyed by path. Empty if nothing is cached.
+    ///
+    /// The state is as of the last scan, so it can lag behind the repositories.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the scan database cannot be read
+    pub fn load_meta<P: AsRef<Path>>(
+        &self,
+        search_path: P,
+    ) -> Result<HashMap<PathBuf, CachedMeta>> {
+        Ok(self
+            .read(search_path)?
+            .map(|repos| {
+                repos
+                    .into_iter()
+                    .map(|repo| {
+                        let meta = CachedMeta {
+                            branch: repo.branch,
+                            dirty: repo.dirty,
+                        };
+                        (repo.path, meta)
                     })
-                } else {
-                    None
-                }
+                    .collect()
             })
-            .collect();
+            .unwrap_or_default())
+    }
+
+    /// Read the cached repositories for a search path, or `None` if it was
+    /// never scanned (or was invalidated).
+    fn read<P: AsRef<Path>>(&self, search_path: P) -> Result<Option<Vec<CachedRepo>>> {
+        let key = Self::scan_key(search_path);
+        let conn = self.state.conn();
+        let scanned: i64 = conn.query_row(
+            "SELECT COUNT(*) FROM scans WHERE profile = ?1 AND scan_key = ?2",
+            params![self.profile, key],
+            |row| row.get(0),
+        )?;
+        if scanned == 0 {
+            return Ok(None);
+        }
 
-        Ok(repos)
+        let repos = conn
+            .prepare(
+                "SELECT name, path, branch, last_commit, dirty FROM repos
+                 WHERE profile = ?1 AND scan_key = ?2 ORDER BY position",
+            )?
+            .query_map(params![self.profile, key], |row| {
+                Ok(CachedRepo {
+                    name: row.get(0)?,
+                    path: PathBuf::from(row.get::<_, String>(1)?),
+                    branch: row.get(2)?,
+                    last_commit: row.get(3)?,
+                    dirty: row.get(4)?,
+                })
+            })?
+            .collect::<rusqlite::Result<_>>()
+            .context("Failed to read cached scan")?;
+        Ok(Some(repos))
     }
 
-    /// Save repository list to cache.
+    /// Save repository list to cache, along with each repository's current
+    /// branch, HEAD commit time, and dirty state (read in parallel).
     ///
     /// # Arguments
     ///
@@ -193,106 +353,126 @@ impl Cache {
     ///
     /// # Errors
     ///
-    /// Returns an error if the cache file cannot be written
+    /// Returns an error if the scan database cannot be written
     pub fn save<P: AsRef<Path>>(&self, search_path: P, repos: &[GitRepo]) -> Result<()> {
-        let cache_path = self.cache_file_path(search_path);
-        let contents: String = repos
-            .iter()
-            .map(|repo| format!("{}\t{}", repo.name, repo.path.display()))
-            .collect::<Vec<_>>()
-            .join("\n");
+        let key = Self::scan_key(search_path);
+        let cached = parallel::filter_map(repos, parallel::default_workers(), |repo| {
+            Some(CachedRepo::from_repo(repo))
+        });
+
+        // One transaction, so a concurrent reader never sees a partial scan
+        let tx = self.state.write()?;
+        tx.execute(
+            "DELETE FROM repos WHERE profile = ?1 AND scan_key = ?2",
+            params![self.profile, key],
+        )?;
+        {
+            let mut insert = tx.prepare(
+                "INSERT OR REPLACE INTO repos
+                 (profile, scan_key, position, path, name, branch, last_commit, dirty)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+            )?;
+            for (position, repo) in cached.iter().enumerate() {
+                insert.execute(params![
+                    self.profile,
+                    key,
+                    position as i64,
+                    state::path_text(&repo.path),
+                    repo.name,
+                    repo.branch,
+                    repo.last_commit,
+                    repo.dirty,
+                ])?;
+            }
+        }
+        tx.execute(
+            "INSERT OR REPLACE INTO scans (profile, scan_key, scanned_at) VALUES (?1, ?2, ?3)",
+            params![self.profile, key, chrono::Utc::now().timestamp()],
+        )?;
+        tx.commit().context("Failed to write cached scan")
+    }
 
-        fs::write(&cache_path, contents)
-            .with_context(|| format!("Failed to write cache file: {}", cache_path.display()))?;
+    /// Remove `paths` from the cached list for one search path, keeping the
+    /// scan's age so the next scan happens when it would have anyway.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the scan database cannot be written
+    pub fn forget<P: AsRef<Path>>(&self, search_path: P, paths: &[PathBuf]) -> Result<()> {
+        let key = Self::scan_key(search_path);
+        let tx = self.state.write()?;
+        for path in paths {
+            tx.execute(
+                "DELETE FROM repos WHERE profile = ?1 AND scan_key = ?2 AND path = ?3",
+                params![self.profile, key, state::path_text(path)],
+            )?;
+        }
+        tx.commit().context("Failed to update cached scan")
+    }
 
-        Ok(())
+    /// Drop the cached scan for one search path, so the next load scans again.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the scan database cannot be written
+    pub fn invalidate<P: AsRef<Path>>(&self, search_path: P) -> Result<()> {
+        let key = Self::scan_key(search_path);
+        let tx = self.state.write()?;
+        for table in ["scans", "repos"] {
+            tx.execute(
+                &format!("DELETE FROM {} WHERE profile = ?1 AND scan_key = ?2", table),
+                params![self.profile, key],
+            )?;
+        }
+        tx.commit().context("Failed to invalidate cached scan")
     }
 
     /// Clear all cached repository data.
     ///
-    /// Removes and recreates the cache directory.
+    /// Drops the cached scans and deletes the cache files (never other files,
+    /// since `cache.directory` may point at a shared location). For a profile
+    /// cache only that profile's scans and files are touched; without a
+    /// profile every profile is cleared.
     ///
     /// # Errors
     ///
-    /// Returns an error if the cache directory cannot be cleared or recreated
+    /// Returns an error if a cache file cannot be deleted or the scan
+    /// database cannot be written
     pub fn clear(&self) -> Result<()> {
-        if self.cache_dir.exists() {
-            fs::remove_dir_all(&self.cache_dir).with_context(|| {
-                format!(
-                    "Failed to clear cache directory: {}",
-                    self.cache_dir.display()
-                )
-            })?;
-            fs::create_dir_all(&self.cache_dir).with_context(|| {
-                format!(
-                    "Failed to recreate cache directory: {}",
-                    self.cache_dir.display()
-                )
-            })?;
+        for file in self.list_cache_files()? {
+            fs::remove_file(&file)
+                .with_context(|| format!("Failed to delete cache file: {}", file.display()))?;
+        }
+
+        let tx = self.state.write()?;
+        for table in ["scans", "repos"] {
+            if self.profile.is_empty() {
+                tx.execute(&format!("DELETE FROM {}", table), [])?;
+            } else {
+                tx.execute(
+                    &format!("DELETE FROM {} WHERE profile = ?1", table),
+                    [&self.profile],
+                )?;
+            }
         }
-        Ok(())
+        tx.commit().context("Failed to clear cached scans")
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::test_util::commit;
 
-    #[test]
-    fn test_cache_file_path_deterministic() {
-        let cache = Cache {
-            cache_dir: PathBuf::from("/tmp/test"),
-            ttl_seconds: 300,
-        };
-
-        let path1 = cache.cache_file_path("/home/user");
-        let path2 = cache.cache_file_path("/home/user");
-        assert_eq!(path1, path2);
-    }
-
-    #[test]
-    fn test_cache_file_path_different_paths() {
-        let cache = Cache {
-            cache_dir: PathBuf::from("/tmp/test"),
-            ttl_seconds: 300,
-        };
-
-        let path1 = cache.cache_file_path("/home/user");
-        let path2 = cache.cache_file_path("/home/other");
-        assert_ne!(path1, path2);
-    }
-
-    #[test]
-    fn test_cache_file_path_contains_hash_prefix() {
-        let cache = Cache {
-            cache_dir: PathBuf::from("/tmp/test"),
-            ttl_seconds: 300,
-        };
-
-        let path = cache.cache_file_path("/home/user");
-        let filename = path.file_name().unwrap().to_str().unwrap();
-        assert!(filename.starts_with("repos_"));
-        assert!(filename.ends_with(".cache"));
-    }
-
-    #[test]
-    fn test_cache_file_path_hash_length() {
-        let cache = Cache {
-            cache_dir: PathBuf::from("/tmp/test"),
-            ttl_seconds: 300,
-        };
-
-        let path = cache.cache_file_path("/home/user");
-        let filename = path.file_name().unwrap().to_str().unwrap();
-        // Should be "repos_" (6 chars) + 16 hex chars + ".cache" (6 chars) = 28 chars total
-        assert_eq!(filename.len(), 28);
+    fn open(dir: &Path, ttl_seconds: u64, profile: Option<&str>) -> Cache {
+        Cache::new(&dir.join("cache"), ttl_seconds, profile).unwrap()
     }
 
     #[test]
     fn test_cache_save_and_load_roundtrip() {
-        // Note: This test requires temp directory handling
-        // For now, we test the logic with mock data
-        let repos = [
+        let tmp = tempfile::tempdir().unwrap();
+        let cache = open(tmp.path(), 300, None);
+        let repos = vec![
             GitRepo {
                 name: "test-repo".to_string(),
                 path: PathBuf::from("/home/user/repos/test-repo"),
@@ -302,72 +482,112 @@ mod tests {
                 path: PathBuf::from("/home/user/repos/another-repo"),
             },
         ];
-        let repos = repos.to_vec();
-
-        let cache_dir = PathBuf::from("/tmp/test");
-        let _cache = Cache {
-            cache_dir,
-            ttl_seconds: 300,
-        };
 
-        let contents: String = repos
-            .iter()
-            .map(|repo| format!("{}\t{}", repo.name, repo.path.display()))
-            .collect::<Vec<_>>()
-            .join("\n");
+        assert!(cache.load("/home/user").unwrap().is_none());
+        assert!(!cache.is_valid("/home/user"));
+        cache.save("/home/user", &repos).unwrap();
+        assert!(cache.is_valid("/home/user"));
+        assert_eq!(cache.load("/home/user").unwrap(), Some(repos));
+        // cache.directory moves the scans along with the other caches
+        assert!(tmp.path().join("cache").join("scans.db").is_file());
+
+        cache.invalidate("/home/user").unwrap();
+        assert!(cache.load("/home/user").unwrap().is_none());
+        cache.invalidate("/home/user").unwrap();
+    }
 
-        // Verify format
-        assert!(contents.contains("test-repo"));
-        assert!(contents.contains("another-repo"));
-        assert!(contents.contains('\t'));
+    #[test]
+    fn test_search_paths_and_profiles_are_cached_apart() {
+        let tmp = tempfile::tempdir().unwrap();
+        let root = open(tmp.path(), 300, None);
+        let work = open(tmp.path(), 300, Some("work"));
+        let api = GitRepo::new(PathBuf::from("/src/api"));
+        root.save("/src", std::slice::from_ref(&api)).unwrap();
+
+        assert_eq!(root.load("/src").unwrap(), Some(vec![api]));
+        assert!(root.load("/src/").unwrap().is_none());
+        assert!(work.load("/src").unwrap().is_none());
     }
 
     #[test]
-    fn test_cache_parse_tsv_format() {
-        let contents = "repo1\t/path/to/repo1\nrepo2\t/path/to/repo2";
-
-        let repos: Vec<GitRepo> = contents
-            .lines()
-            .filter_map(|line| {
-                let parts: Vec<&str> = line.split('\t').collect();
-                if parts.len() == 2 {
-                    Some(GitRepo {
-                        name: parts[0].to_string(),
-                        path: PathBuf::from(parts[1]),
-                    })
-                } else {
-                    None
-                }
-            })
-            .collect();
+    fn test_cache_expires_after_ttl() {
+        let tmp = tempfile::tempdir().unwrap();
+        let cache = open(tmp.path(), 300, None);
+        cache.save("/src", &[]).unwrap();
+        assert!(cache.is_valid("/src"));
+
+        let expired = open(tmp.path(), 0, None);
+        assert!(!expired.is_valid("/src"));
+        assert_eq!(expired.load("/src").unwrap(), Some(vec![]));
+    }
 
-        assert_eq!(repos.len(), 2);
-        assert_eq!(repos[0].name, "repo1");
-        assert_eq!(repos[1].name, "repo2");
+    #[test]
+    fn test_forget_keeps_the_cache_age() {
+        let tmp = tempfile::tempdir().unwrap();
+        let cache = open(tmp.path(), 300, None);
+        let api = GitRepo::new(PathBuf::from("/src/api"));
+        let gone = GitRepo::new(PathBuf::from("/src/gone"));
+        cache.save("/src", &[api.clone(), gone.clone()]).unwrap();
+
+        let scanned_at = |cache: &Cache| -> i64 {
+            cache
+                .state
+                .conn()
+                .query_row("SELECT scanned_at FROM scans", [], |row| row.get(0))
+                .unwrap()
+        };
+        let written = chrono::Utc::now().timestamp() - 200;
+        cache
+            .state
+            .conn()
+            .execute("UPDATE scans SET scanned_at = ?1", [written])
+            .unwrap();
+
+        cache.forget("/src", &[gone.path]).unwrap();
+        assert_eq!(cache.load("/src").unwrap(), Some(vec![api]));
+        assert_eq!(scanned_at(&cache), written);
+        cache.forget("/elsewhere", &[]).unwrap();
     }
 
     #[test]
-    fn test_cache_parse_ignores_malformed_lines() {
-        let contents = "repo1\t/path/to/repo1\nmalformed_line\nrepo2\t/path/to/repo2";
-
-        let repos: Vec<GitRepo> = contents
-            .lines()
-            .filter_map(|line| {
-                let parts: Vec<&str> = line.split('\t').collect();
-                if parts.len() == 2 {
-                    Some(GitRepo {
-                        name: parts[0].to_string(),
-                        path: PathBuf::from(parts[1]),
-                    })
-                } else {
-                    None
-                }
+    fn test_cache_records_branch_and_commit_time() {
+        let tmp = tempfile::tempdir().unwrap();
+        let repo_path = tmp.path().join("api");
+        let repo = git2::Repository::init(&repo_path).unwrap();
+        commit(&repo, "init", Some(1_700_000_000));
+        let branch = repo.head().unwrap().shorthand().unwrap().to_string();
+
+        let cache = open(tmp.path(), 300, None);
+        cache
+            .save("/src", &[GitRepo::new(repo_path.clone())])
+            .unwrap();
+
+        assert_eq!(
+            cache.read("/src").unwrap(),
+            Some(vec![CachedRepo {
+                name: "api".to_string(),
+                path: repo_path.clone(),
+                branch: Some(branch.clone()),
+                last_commit: Some(1_700_000_000),
+                dirty: false,
+            }])
+        );
+
+        fs::write(repo_path.join("file"), "x").unwrap();
+        let mut index = repo.index().unwrap();
+        index.add_path(Path::new("file")).unwrap();
+        index.write().unwrap();
+        cache
+            .save("/src", &[GitRepo::new(repo_path.clone())])
+            .unwrap();
+        assert_eq!(
+            cache.load_meta("/src").unwrap().get(&repo_path),
+            Some(&CachedMeta {
+                branch: Some(branch),
+                dirty: true,
             })
-            .collect();
-
-        assert_eq!(repos.len(), 2);
-        assert_eq!(repos[0].name, "repo1");
-        assert_eq!(repos[1].name, "repo2");
+        );
+        assert!(cache.load_meta("/other").unwrap().is_empty());
     }
 
     #[test]
@@ -382,148 +602,69 @@ mod tests {
     }
 
     #[test]
-    fn test_cache_ttl_parameter() {
-        let cache_short = Cache {
-            cache_dir: PathBuf::from("/tmp/test"),
-            ttl_seconds: 60,
-        };
-
-        let cache_long = Cache {
-            cache_dir: PathBuf::from("/tmp/test"),
-            ttl_seconds: 3600,
-        };
-
-        assert_eq!(cache_short.ttl_seconds, 60);
-        assert_eq!(cache_long.ttl_seconds, 3600);
-        assert_ne!(cache_short.ttl_seconds, cache_long.ttl_seconds);
-    }
-
-    #[test]
-    fn test_cache_file_path_multiple_hash_consistency() {
-        let cache = Cache {
-            cache_dir: PathBuf::from("/tmp/test"),
-            ttl_seconds: 300,
-        };
-
-        // Same path should always generate same hash
-        let paths: Vec<_> = (0..5)
-            .map(|_| cache.cache_file_path("/home/user/projects"))
-            .collect();
+    fn test_cache_handles_empty_repository_list() {
+        let tmp = tempfile::tempdir().unwrap();
+        let cache = open(tmp.path(), 300, None);
 
-        for path in &paths[1..] {
-            assert_eq!(paths[0], *path);
-        }
+        cache.save("/home/user", &[]).unwrap();
+        assert_eq!(cache.load("/home/user").unwrap(), Some(vec![]));
     }
 
     #[test]
-    fn test_cache_file_path_unicode_paths() {
-        let cache = Cache {
-            cache_dir: PathBuf::from("/tmp/test"),
-            ttl_seconds: 300,
-        };
-
-        let path1 = cache.cache_file_path("/home/用户/项目");
-        let path2 = cache.cache_file_path("/home/用户/项目");
-        assert_eq!(path1, path2);
-
-        let path3 = cache.cache_file_path("/home/different/项目");
-        assert_ne!(path1, path3);
+    fn test_clear_includes_profiles_only_when_unscoped() {
+        let tmp = tempfile::tempdir().unwrap();
+        let root = open(tmp.path(), 300, None);
+        let work = open(tmp.path(), 300, Some("work"));
+
+        root.save("/home/user", &[]).unwrap();
+        work.save("/home/user/work", &[]).unwrap();
+
+        // A scan cache from an earlier version, and the other caches
+        let cache_dir = tmp.path().join("cache");
+        fs::write(work.cache_dir().join("repos_0123456789abcdef.cache"), "{}").unwrap();
+        fs::create_dir_all(cache_dir.join("descriptions")).unwrap();
+        fs::write(cache_dir.join("descriptions").join("abc.cache"), "{}").unwrap();
+        fs::create_dir_all(cache_dir.join("github")).unwrap();
+        fs::write(cache_dir.join("github").join("abc.cache"), "{}").unwrap();
+
+        assert_eq!(root.list_cache_files().unwrap().len(), 3);
+        assert_eq!(work.list_cache_files().unwrap().len(), 1);
+        assert_eq!(root.scan_count().unwrap(), 2);
+        assert_eq!(work.scan_count().unwrap(), 1);
+
+        work.clear().unwrap();
+        assert_eq!(work.list_cache_files().unwrap().len(), 0);
+        assert_eq!(work.scan_count().unwrap(), 0);
+        assert_eq!(root.list_cache_files().unwrap().len(), 2);
+        assert_eq!(root.scan_count().unwrap(), 1);
+
+        root.clear().unwrap();
+        assert_eq!(root.scan_count().unwrap(), 0);
     }
 
     #[test]
-    fn test_cache_file_path_similar_paths() {
-        let cache = Cache {
-            cache_dir: PathBuf::from("/tmp/test"),
-            ttl_seconds: 300,
-        };
-
-        // Similar paths should produce different hashes
-        let path1 = cache.cache_file_path("/home/user");
-        let path2 = cache.cache_file_path("/home/user/");
-        let path3 = cache.cache_file_path("/home/user2");
-
-        assert_ne!(path1, path2);
-        assert_ne!(path1, path3);
-        assert_ne!(path2, path3);
+    fn test_resolve_dir_prefers_configured_directory() {
+        assert_eq!(
+            Cache::resolve_dir(Some("/mnt/tmpfs/gitnav")).unwrap(),
+            PathBuf::from("/mnt/tmpfs/gitnav")
+        );
+        let home = dirs::home_dir().unwrap();
+        assert_eq!(
+            Cache::resolve_dir(Some("~/cache")).unwrap(),
+            home.join("cache")
+        );
     }
 
     #[test]
-    fn test_cache_roundtrip_multiple_repos() {
-        let repos = [
-            GitRepo {
-                name: "repo1".to_string(),
-                path: PathBuf::from("/path/1"),
-            },
-            GitRepo {
-                name: "repo2".to_string(),
-                path: PathBuf::from("/path/2"),
-            },
-            GitRepo {
-                name: "repo3".to_string(),
-                path: PathBuf::from("/path/3"),
-            },
-        ];
-        let repos = repos.to_vec();
-
-        let _cache_dir = PathBuf::from("/tmp/test");
-
-        // Format as cache would save
-        let contents: String = repos
-            .iter()
-            .map(|repo| format!("{}\t{}", repo.name, repo.path.display()))
-            .collect::<Vec<_>>()
-            .join("\n");
-
-        // Parse back
-        let parsed: Vec<GitRepo> = contents
-            .lines()
-            .filter_map(|line| {
-                let parts: Vec<&str> = line.split('\t').collect();
-                if parts.len() == 2 {
-                    Some(GitRepo {
-                        name: parts[0].to_string(),
-                        path: PathBuf::from(parts[1]),
-                    })
-                } else {
-                    None
-                }
-            })
-            .collect();
-
-        assert_eq!(repos.len(), parsed.len());
-        for (original, parsed_repo) in repos.iter().zip(parsed.iter()) {
-            assert_eq!(original.name, parsed_repo.name);
-            assert_eq!(original.path, parsed_repo.path);
-        }
-    }
-
-    #[test]
-    fn test_cache_handles_empty_repository_list() {
-        let repos: Vec<GitRepo> = vec![];
-
-        let contents: String = repos
-            .iter()
-            .map(|repo| format!("{}\t{}", repo.name, repo.path.display()))
-            .collect::<Vec<_>>()
-            .join("\n");
-
-        assert_eq!(contents, "");
-
-        let parsed: Vec<GitRepo> = contents
-            .lines()
-            .filter_map(|line| {
-                let parts: Vec<&str> = line.split('\t').collect();
-                if parts.len() == 2 {
-                    Some(GitRepo {
-                        name: parts[0].to_string(),
-                        path: PathBuf::from(parts[1]),
-                    })
-                } else {
-                    None
-                }
-            })
-            .collect();
-
-        assert_eq!(parsed.len(), 0);
+    fn test_clear_only_deletes_cache_files() {
+        let tmp = tempfile::tempdir().unwrap();
+        let cache = open(tmp.path(), 300, None);
+        cache.save("/home/user", &[]).unwrap();
+        fs::write(cache.cache_dir().join("notes.txt"), "keep me").unwrap();
+
+        cache.clear().unwrap();
+        assert!(cache.list_cache_files().unwrap().is_empty());
+        assert!(cache.load("/home/user").unwrap().is_none());
+        assert!(cache.cache_dir().join("notes.txt").exists());
     }
 }
@@ -0,0 +1,126 @@
+use anyhow::{Context, Result};
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+
+use crate::remote::{self, RemoteUrl, Rewrites};
+
+/// What to clone and where its canonical key points.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CloneTarget {
+    /// URL handed to `git clone`, as typed (git applies `insteadOf` itself)
+    pub url: String,
+    pub remote: RemoteUrl,
+}
+
+/// Parse a `gitnav clone` argument.
+///
+/// Accepts anything [`RemoteUrl::parse`] does, with or without `insteadOf`
+/// rewrites applied, plus ghq-style `owner/repo` shorthand for GitHub.
+pub fn parse_target(arg: &str, rewrites: &Rewrites) -> Option<CloneTarget> {
+    let arg = arg.trim();
+    let remote = remote::normalize(arg, rewrites).or_else(|| RemoteUrl::parse(arg));
+    if let Some(remote) = remote {
+        return Some(CloneTarget {
+            url: arg.to_string(),
+            remote,
+        });
+    }
+
+    let (owner, repo) = arg.split_once('/')?;
+    let valid = |s: &str| {
+        !s.is_empty()
+            && s != "."
+            && s != ".."
+            && s.chars()
+                .all(|c| c.is_ascii_alphanumeric() || "-_.".contains(c))
+    };
+    if !valid(owner) || !valid(repo) {
+        return None;
+    }
+    let url = format!("https://github.com/{}/{}", owner, repo);
+    RemoteUrl::parse(&url).map(|remote| CloneTarget { url, remote })
+}
+
+/// Where a remote is cloned: `<root>/<host>/<path>`, e.g. `~/src/github.com/me/foo`.
+pub fn destination(root: &Path, remote: &RemoteUrl) -> PathBuf {
+    let mut dest = root.join(&remote.host);
+    for part in remote.path.split('/').filter(|p| !p.is_empty()) {
+        dest.push(part);
+    }
+    dest
+}
+
+/// Clone `url` into `dest` with `git clone`.
+///
+/// git's own output goes to stderr so stdout stays free for the path the shell
+/// wrapper `cd`s into. Credential and passphrase prompts still reach the
+/// terminal.
+///
+/// # Errors
+///
+/// Returns an error if git cannot be run or the clone fails
+pub fn clone(url: &str, dest: &Path) -> Result<()> {
+    let status = Command::new("git")
+        .arg("clone")
+        .arg("--")
+        .arg(url)
+        .arg(dest)
+        .stdin(Stdio::inherit())
+        .stdout(std::io::stderr())
+        .status()
+        .context("Failed to run git")?;
+    if !status.success() {
+        anyhow::bail!("git clone {} failed with {}", url, status);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn target(arg: &str) -> Option<CloneTarget> {
+        parse_target(arg, &Rewrites::default())
+    }
+
+    #[test]
+    fn test_parse_target_forms() {
+        let ssh = target("git@github.com:me/foo.git").unwrap();
+        assert_eq!(ssh.url, "git@github.com:me/foo.git");
+        assert_eq!(ssh.remote.to_string(), "github.com/me/foo");
+
+        let short = target("me/foo").unwrap();
+        assert_eq!(short.url, "https://github.com/me/foo");
+        assert_eq!(short.remote.to_string(), "github.com/me/foo");
+
+        assert!(target("foo").is_none());
+        assert!(target("../foo").is_none());
+        assert!(target("/srv/git/foo.git").is_none());
+    }
+
+    #[test]
+    fn test_destination_mirrors_host_and_path() {
+        let remote = RemoteUrl::parse("git@gitlab.com:group/sub/project.git").unwrap();
+        assert_eq!(
+            destination(Path::new("/home/u/src"), &remote),
+            Path::new("/home/u/src/gitlab.com/group/sub/project")
+        );
+    }
+
+    #[test]
+    fn test_clone_local_repository() {
+        let tmp = tempfile::tempdir().unwrap();
+        let source = tmp.path().join("source");
+        git2::Repository::init(&source).unwrap();
+        let dest = tmp
+            .path()
+            .join("root")
+            .join("example.com")
+            .join("me")
+            .join("foo");
+
+        clone(&source.display().to_string(), &dest).unwrap();
+        assert!(dest.join(".git").is_dir());
+        assert!(clone("/nonexistent/repo", &tmp.path().join("other")).is_err());
+    }
+}
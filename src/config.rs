@@ -1,21 +1,70 @@
 use anyhow::{Context, Result};
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
-use std::path::PathBuf;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use crate::forge::BrowseField;
+use crate::preview::PreviewField;
+use crate::profile;
+use crate::template::{Field, Template};
+use crate::worktree::PathField;
 
 /// Main configuration structure for gitnav.
 ///
 /// Contains all configuration options organized into nested structures
 /// for search behavior, caching, UI, and preview settings.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
 pub struct Config {
+    #[serde(default)]
     pub search: SearchConfig,
+    #[serde(default)]
     pub cache: CacheConfig,
+    #[serde(default)]
     pub ui: UiConfig,
+    #[serde(default)]
     pub preview: PreviewConfig,
+    #[serde(default)]
+    pub bulk: BulkConfig,
+    #[serde(default)]
+    pub forge: ForgeConfig,
+    #[serde(default)]
+    pub matcher: MatcherConfig,
+    #[serde(default)]
+    pub hooks: HooksConfig,
+    #[serde(default)]
+    pub keybindings: KeybindingsConfig,
+    #[serde(default)]
+    pub display: DisplayConfig,
+    #[serde(default)]
+    pub finder: FinderConfig,
+    #[serde(default)]
+    pub actions: ActionsConfig,
+    #[serde(default)]
+    pub daemon: DaemonConfig,
+    #[serde(default)]
+    pub identity: IdentityConfig,
+    #[serde(default)]
+    pub tmux: TmuxConfig,
+    #[serde(default)]
+    pub zoxide: ZoxideConfig,
+    #[serde(default)]
+    pub worktree: WorktreeConfig,
+    #[serde(default)]
+    pub grep: GrepConfig,
+    #[serde(default)]
+    pub gc: GcConfig,
+    #[serde(default)]
+    pub sync: SyncConfig,
+    /// Named overrides, e.g. `[profiles.work.search]`, laid over the rest of
+    /// the file when that profile is active (`--profile` or `GITNAV_PROFILE`)
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    #[schemars(with = "BTreeMap<String, serde_json::Map<String, serde_json::Value>>")]
+    pub profiles: BTreeMap<String, toml::Table>,
 }
 
 /// Badge display style for project type in the fzf list.
-#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema, Default)]
 #[serde(rename_all = "lowercase")]
 pub enum BadgeStyle {
     None,
@@ -24,10 +73,64 @@ pub enum BadgeStyle {
     Icon,
 }
 
+/// When bulk operations ask for confirmation before running.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum ConfirmMode {
+    /// Always ask before running a bulk operation
+    Always,
+    /// Ask only when the operation can discard work or delete data
+    #[default]
+    Destructive,
+    /// Never ask
+    Never,
+}
+
+/// Order in which repositories are listed.
+///
+/// Ties are always broken by path, so output is byte-identical across runs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum SortOrder {
+    /// Alphabetically by repository name, then by path
+    #[default]
+    Name,
+    /// By full path
+    Path,
+}
+
+/// How linked git worktrees are listed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum WorktreeMode {
+    /// List worktrees named after their main repository, e.g. `api@feature-x`
+    #[default]
+    Labeled,
+    /// List worktrees under their directory name
+    Plain,
+    /// Do not list worktrees
+    Hidden,
+}
+
+/// Which picker interactive mode uses.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum FinderMode {
+    /// fzf when it is installed, otherwise the built-in picker
+    #[default]
+    Auto,
+    /// Always fzf; error if it is not installed
+    Fzf,
+    /// Always the built-in picker
+    Builtin,
+}
+
 /// Configuration for repository search behavior.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(default)]
 pub struct SearchConfig {
-    /// Base path to start searching from (supports ~ expansion). Used when `paths` is empty.
+    /// Base path to start searching from (supports ~ expansion). Used when
+    /// `paths` and `roots` are empty.
     pub base_path: String,
     /// Maximum directory depth to traverse
     pub max_depth: usize,
@@ -37,23 +140,170 @@ pub struct SearchConfig {
     /// Directory names to skip during scanning (e.g. "node_modules", "vendor")
     #[serde(default)]
     pub ignore_patterns: Vec<String>,
+    /// Listing order: "name" (then path) or "path"
+    #[serde(default)]
+    pub order: SortOrder,
+    /// Linked worktrees: "labeled" (`repo@dir`), "plain" (directory name), or "hidden"
+    #[serde(default)]
+    pub worktrees: WorktreeMode,
+    /// Also list repositories inside other repositories (such as vendored
+    /// clones); by default the scan does not descend into a repository
+    #[serde(default)]
+    pub include_nested: bool,
+    /// Skip directories ignored by `.gitignore` and `.ignore` files met while
+    /// scanning; `.gitnavignore` files apply either way
+    #[serde(default = "default_respect_gitignore")]
+    pub respect_gitignore: bool,
+    /// Descend into symlinked directories, skipping links that loop back
+    #[serde(default)]
+    pub follow_symlinks: bool,
+    /// Stay on the filesystem of each search path, so mounted network shares,
+    /// FUSE filesystems, and other disks below it are not scanned
+    #[serde(default = "default_same_filesystem")]
+    pub same_filesystem: bool,
+    /// Give up scanning after this many milliseconds and list what was found
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timeout_ms: Option<u64>,
+    /// Stop scanning once this many repositories were found
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_results: Option<usize>,
+    /// Search paths with their own depth and excludes, searched besides `paths`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub roots: Vec<SearchRoot>,
+    /// Also search the roots of an existing ghq setup (`GHQ_ROOT`, `ghq.root`
+    /// in the git config, or `~/ghq`)
+    #[serde(default)]
+    pub ghq_roots: bool,
+}
+
+impl Default for SearchConfig {
+    fn default() -> Self {
+        Self {
+            base_path: dirs::home_dir()
+                .and_then(|p| p.to_str().map(String::from))
+                .unwrap_or_else(|| String::from("~")),
+            max_depth: 5,
+            paths: Vec::new(),
+            ignore_patterns: Vec::new(),
+            order: SortOrder::Name,
+            worktrees: WorktreeMode::Labeled,
+            include_nested: false,
+            respect_gitignore: true,
+            follow_symlinks: false,
+            same_filesystem: true,
+            timeout_ms: None,
+            max_results: None,
+            roots: Vec::new(),
+            ghq_roots: false,
+        }
+    }
+}
+
+/// A search path with its own scan settings (`[[search.roots]]`).
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct SearchRoot {
+    /// Directory to search (supports ~ expansion)
+    pub path: String,
+    /// Maximum directory depth below this path; `max_depth` when unset
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_depth: Option<usize>,
+    /// Directory names to skip below this path, besides `ignore_patterns`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub exclude: Vec<String>,
+}
+
+/// Name of the per-directory config files that change settings for the
+/// directory they are in and everything below it.
+pub const DIR_CONFIG_FILENAME: &str = ".gitnav.toml";
+
+/// The settings a `.gitnav.toml` changes for its directory's subtree.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct DirConfig {
+    pub search: DirSearchConfig,
+    /// `[preview]` settings for the repositories below the directory
+    pub preview: toml::Table,
+}
+
+/// The `[search]` settings of a `.gitnav.toml`.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct DirSearchConfig {
+    /// Maximum directory depth below this directory
+    pub max_depth: Option<usize>,
+    /// Directory names to skip below this directory, besides `ignore_patterns`
+    pub exclude: Vec<String>,
+}
+
+impl DirConfig {
+    /// The `.gitnav.toml` in `dir`, or `None` if it has none.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error naming the file if it cannot be read or parsed
+    pub fn load(dir: &Path) -> Result<Option<Self>> {
+        let path = dir.join(DIR_CONFIG_FILENAME);
+        if !path.is_file() {
+            return Ok(None);
+        }
+        let contents = std::fs::read_to_string(&path)
+            .with_context(|| format!("Failed to read {}", path.display()))?;
+        toml::from_str(&contents)
+            .map(Some)
+            .with_context(|| format!("Failed to parse {}", path.display()))
+    }
+}
+
+fn default_respect_gitignore() -> bool {
+    true
+}
+
+fn default_same_filesystem() -> bool {
+    true
 }
 
 /// Configuration for caching behavior.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(default)]
 pub struct CacheConfig {
     /// Whether caching is enabled
     pub enabled: bool,
     /// Time-to-live for cached data in seconds
     pub ttl_seconds: u64,
+    /// Directory for cache files (supports ~ expansion); defaults to the platform cache directory
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub directory: Option<String>,
+    /// Open the picker from an expired cache and rescan in the background
+    #[serde(default = "default_background_refresh")]
+    pub background_refresh: bool,
+}
+
+impl Default for CacheConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            ttl_seconds: 300,
+            directory: None,
+            background_refresh: true,
+        }
+    }
+}
+
+fn default_background_refresh() -> bool {
+    true
 }
 
 fn default_show_inline_meta() -> bool {
     true
 }
 
+fn default_stream() -> bool {
+    true
+}
+
 /// Configuration for the fzf UI.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(default)]
 pub struct UiConfig {
     /// The prompt displayed to the user
     pub prompt: String,
@@ -70,13 +320,60 @@ pub struct UiConfig {
     /// Show branch name and dirty indicator inline in the fzf list
     #[serde(default = "default_show_inline_meta")]
     pub show_inline_meta: bool,
+    /// Take the inline branch and dirty indicator from the cache (as of the last
+    /// scan) instead of reading every repository when the picker opens
+    #[serde(default)]
+    pub cached_inline_meta: bool,
     /// Badge style for project type display: "none", "text", or "icon"
     #[serde(default)]
     pub badge_style: BadgeStyle,
+    /// On a cache miss, open fzf immediately and add repositories as the scan finds them
+    #[serde(default = "default_stream")]
+    pub stream: bool,
+    /// Picker to use: "auto", "fzf", or "builtin"
+    #[serde(default)]
+    pub finder: FinderMode,
+    /// Skip the picker when exactly one repository matches the query (`--select-1`)
+    #[serde(default)]
+    pub select_1: bool,
+    /// Exit with code 3 instead of opening the picker when nothing matches (`--exit-0`)
+    #[serde(default)]
+    pub exit_0: bool,
+    /// Editor for `keybindings.open_editor` and `gitnav open`; defaults to
+    /// `$VISUAL`, then `$EDITOR`. `{path}` marks where the path goes (`code {path}`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub editor: Option<String>,
+    /// Inside tmux, open fzf in a centered popup (`fzf --tmux`, fzf 0.53+)
+    /// instead of in the current pane
+    #[serde(default)]
+    pub tmux_popup: bool,
+}
+
+impl Default for UiConfig {
+    fn default() -> Self {
+        Self {
+            prompt: String::from("Select repo > "),
+            header: String::from("Repository (↑/↓, ⏎, Esc)"),
+            preview_width_percent: 60,
+            layout: String::from("reverse"),
+            height_percent: 90,
+            show_border: true,
+            show_inline_meta: true,
+            cached_inline_meta: false,
+            badge_style: BadgeStyle::Text,
+            stream: true,
+            finder: FinderMode::Auto,
+            select_1: false,
+            exit_0: false,
+            editor: None,
+            tmux_popup: false,
+        }
+    }
 }
 
 /// Configuration for repository preview display.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(default)]
 pub struct PreviewConfig {
     /// Whether to show the current branch
     pub show_branch: bool,
@@ -88,70 +385,1077 @@ pub struct PreviewConfig {
     pub recent_commits: usize,
     /// Date format string for timestamps (strftime format)
     pub date_format: String,
+    /// Maximum number of lines in the preview (0 = unlimited).
+    /// Recent commits are dropped first, with a note saying how many were omitted.
+    #[serde(default)]
+    pub max_lines: usize,
+    /// Whether to list remotes and their fetch URLs
+    #[serde(default)]
+    pub show_remotes: bool,
+    /// Whether to summarize uncommitted changes as files changed, insertions, and deletions
+    #[serde(default)]
+    pub show_diffstat: bool,
+    /// Whether to show the most recent tag and how many commits HEAD is past it
+    #[serde(default)]
+    pub show_tags: bool,
+    /// Whether to show the `user.name` and `user.email` commits would be made with
+    #[serde(default)]
+    pub show_identity: bool,
+    /// Whether to show a sparkline of commits per week over the last 13 weeks
+    #[serde(default)]
+    pub show_commit_activity: bool,
+    /// Whether to list local branches, most recently committed first
+    #[serde(default)]
+    pub show_branches: bool,
+    /// Layout replacing the fixed preview, e.g. "{name}\n{branch} {ahead_behind}\n{status}".
+    /// Sections are collected only when their placeholder is used, regardless of
+    /// the show_* toggles
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub template: Option<String>,
+    /// Skip the status and diffstat sections in repositories whose index
+    /// holds more files than this (0 = no limit)
+    #[serde(default = "default_status_max_files")]
+    pub status_max_files: usize,
+    /// Give up on the status section after this many milliseconds (0 = no limit)
+    #[serde(default = "default_status_timeout_ms")]
+    pub status_timeout_ms: u64,
+    /// Seconds a rendered fzf preview is reused while the repository's HEAD and
+    /// index are unchanged (0 = always render)
+    #[serde(default = "default_preview_cache_ttl_seconds")]
+    pub cache_ttl_seconds: u64,
 }
 
-impl Default for Config {
+impl Default for PreviewConfig {
     fn default() -> Self {
         Self {
-            search: SearchConfig {
-                base_path: dirs::home_dir()
-                    .and_then(|p| p.to_str().map(String::from))
-                    .unwrap_or_else(|| String::from("~")),
-                max_depth: 5,
-                paths: Vec::new(),
-                ignore_patterns: Vec::new(),
-            },
-            cache: CacheConfig {
-                enabled: true,
-                ttl_seconds: 300,
-            },
-            ui: UiConfig {
-                prompt: String::from("Select repo > "),
-                header: String::from("Repository (↑/↓, ⏎, Esc)"),
-                preview_width_percent: 60,
-                layout: String::from("reverse"),
-                height_percent: 90,
-                show_border: true,
-                show_inline_meta: true,
-                badge_style: BadgeStyle::Text,
-            },
-            preview: PreviewConfig {
-                show_branch: true,
-                show_last_activity: true,
-                show_status: true,
-                recent_commits: 5,
-                date_format: String::from("%Y-%m-%d %H:%M"),
-            },
+            show_branch: true,
+            show_last_activity: true,
+            show_status: true,
+            recent_commits: 5,
+            date_format: String::from("%Y-%m-%d %H:%M"),
+            max_lines: 0,
+            show_remotes: false,
+            show_diffstat: false,
+            show_tags: false,
+            show_identity: false,
+            show_commit_activity: false,
+            show_branches: false,
+            template: None,
+            status_max_files: 200_000,
+            status_timeout_ms: 1000,
+            cache_ttl_seconds: 5,
+        }
+    }
+}
+
+fn default_status_max_files() -> usize {
+    200_000
+}
+
+fn default_status_timeout_ms() -> u64 {
+    1000
+}
+
+fn default_preview_cache_ttl_seconds() -> u64 {
+    5
+}
+
+/// Configuration for operations that run across many repositories.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct BulkConfig {
+    /// Confirmation policy: "always", "destructive", or "never"
+    #[serde(default)]
+    pub confirm: ConfirmMode,
+    /// Repositories processed at once by network commands such as `fetch-all`
+    /// (0 = one per CPU)
+    #[serde(default = "default_bulk_jobs")]
+    pub jobs: usize,
+}
+
+fn default_bulk_jobs() -> usize {
+    8
+}
+
+impl Default for BulkConfig {
+    fn default() -> Self {
+        Self {
+            confirm: ConfirmMode::default(),
+            jobs: default_bulk_jobs(),
+        }
+    }
+}
+
+/// Shell commands run at points in a gitnav run.
+///
+/// Each hook gets `GITNAV_EVENT` plus event-specific variables such as
+/// `GITNAV_REPO_PATH`, `GITNAV_REPO_COUNT`, and `GITNAV_DURATION_MS`, which
+/// commands can also use as placeholders (`{path}`, `{count}`).
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
+#[serde(default)]
+pub struct HooksConfig {
+    /// Run before a filesystem scan (`GITNAV_SEARCH_PATHS`)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pre_scan: Option<String>,
+    /// Run after a filesystem scan (`GITNAV_REPO_COUNT`, `GITNAV_DURATION_MS`, `GITNAV_SEARCH_PATHS`)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub post_scan: Option<String>,
+    /// Run after a fresh scan is written to the cache (`GITNAV_REPO_COUNT`, `GITNAV_CACHE_DIR`)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub post_cache_refresh: Option<String>,
+    /// Run after a repository is picked (`GITNAV_REPO_PATH`, `GITNAV_REPO_NAME`)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub post_select: Option<String>,
+    /// Run after a repository is cloned (`GITNAV_REPO_PATH`, `GITNAV_REPO_URL`)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub post_clone: Option<String>,
+    /// Run by the shell wrapper in your shell after it changes into a picked
+    /// repository, so it can change the environment ({path}, {name})
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub post_cd: Option<String>,
+}
+
+/// How query terms are matched against repository names and paths.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum MatchAlgorithm {
+    /// Characters must appear in order, with gaps allowed (fzf's default)
+    #[default]
+    Fuzzy,
+    /// Terms must appear as contiguous substrings (like `fzf --exact`)
+    Substring,
+}
+
+/// Configuration for gitnav's built-in matcher (`select` and other non-fzf matching).
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(default)]
+pub struct MatcherConfig {
+    /// Matching algorithm: "fuzzy" or "substring"
+    pub algorithm: MatchAlgorithm,
+    /// Match case-insensitively unless a term contains an uppercase letter; when false, always ignore case
+    pub smart_case: bool,
+    /// Rank matches at word boundaries (`-`, `/`, camelCase) higher
+    pub word_boundaries: bool,
+}
+
+impl Default for MatcherConfig {
+    fn default() -> Self {
+        Self {
+            algorithm: MatchAlgorithm::Fuzzy,
+            smart_case: true,
+            word_boundaries: true,
+        }
+    }
+}
+
+/// Picker keys for the actions available on the highlighted repository.
+///
+/// Keys use fzf's syntax (`ctrl-e`, `alt-o`); the built-in picker understands
+/// `ctrl-<letter>`. An empty string leaves the action unbound.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(default)]
+pub struct KeybindingsConfig {
+    /// Change to the highlighted repository, in addition to Enter
+    pub select: String,
+    /// Open the highlighted repository in the editor instead of changing to it
+    pub open_editor: String,
+    /// Open the web page of the highlighted repository's remote in the browser
+    pub open_browser: String,
+    /// Copy the highlighted repository's path to the clipboard
+    pub copy_path: String,
+    /// Rescan the search paths, bypassing the cache, and reopen the picker
+    pub refresh: String,
+    /// Show or hide the preview pane (fzf only)
+    pub toggle_preview: String,
+}
+
+impl Default for KeybindingsConfig {
+    fn default() -> Self {
+        Self {
+            select: String::from("enter"),
+            open_editor: String::from("ctrl-e"),
+            open_browser: String::from("ctrl-o"),
+            copy_path: String::from("ctrl-y"),
+            refresh: String::from("ctrl-r"),
+            toggle_preview: String::from("ctrl-/"),
+        }
+    }
+}
+
+/// The action menu shown after picking a repository with Enter.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(default)]
+pub struct ActionsConfig {
+    /// Ask what to do with the picked repository instead of changing to it right away
+    pub menu: bool,
+    /// Commands offered in the menu besides cd, editor, remote, and copy path
+    pub items: Vec<ActionItem>,
+}
+
+impl Default for ActionsConfig {
+    fn default() -> Self {
+        Self {
+            menu: false,
+            items: vec![ActionItem {
+                name: String::from("lazygit"),
+                command: String::from("lazygit"),
+            }],
+        }
+    }
+}
+
+/// A command in the action menu.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct ActionItem {
+    /// Label shown in the menu
+    pub name: String,
+    /// Shell command run in the repository; placeholders such as `{path}` and
+    /// `{branch}` are filled in as in `--format`
+    pub command: String,
+}
+
+/// Configuration for `gitnav daemon`.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(default)]
+pub struct DaemonConfig {
+    /// Rescan as soon as a repository appears or disappears under the search
+    /// paths, instead of only every `cache.ttl_seconds`
+    pub watch: bool,
+    /// Find changes by checking directory modification times every
+    /// `poll_seconds` instead of with filesystem events, e.g. on network
+    /// filesystems that do not report events
+    pub poll: bool,
+    /// How often to check the search paths when polling; also used when
+    /// filesystem events are unavailable
+    pub poll_seconds: u64,
+}
+
+impl Default for DaemonConfig {
+    fn default() -> Self {
+        Self {
+            watch: true,
+            poll: false,
+            poll_seconds: 60,
+        }
+    }
+}
+
+/// Expected commit identities, checked by `gitnav identity-check`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
+#[serde(default)]
+pub struct IdentityConfig {
+    /// Email patterns required for repositories under a directory
+    pub rules: Vec<IdentityRule>,
+}
+
+/// Layout of the sessions `gitnav tmux` creates.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
+#[serde(default)]
+pub struct TmuxConfig {
+    /// Windows to open in a new session, in order; empty means a single shell
+    pub windows: Vec<TmuxWindow>,
+}
+
+/// Integration with zoxide's directory database.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
+#[serde(default)]
+pub struct ZoxideConfig {
+    /// Run `zoxide add` for every repository gitnav changes into, so `z` ranks it
+    pub add: bool,
+    /// Add the git repositories in zoxide's database to the list, as `--from-zoxide` does
+    pub merge: bool,
+}
+
+/// Limits for `gitnav grep`.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(default)]
+pub struct GrepConfig {
+    /// Repositories searched at once (0 = one per CPU); `--jobs` overrides it
+    pub jobs: usize,
+    /// Matching lines per repository before its search stops (0 = no limit);
+    /// `--max-per-repo` overrides it
+    pub max_per_repo: usize,
+}
+
+impl Default for GrepConfig {
+    fn default() -> Self {
+        Self {
+            jobs: 0,
+            max_per_repo: 100,
+        }
+    }
+}
+
+/// Limits for `gitnav gc`.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(default)]
+pub struct GcConfig {
+    /// Repositories maintained at once (0 = one per CPU); `--jobs` overrides it.
+    /// git's repacking is itself multi-threaded and memory-hungry, hence the
+    /// low default
+    pub jobs: usize,
+}
+
+impl Default for GcConfig {
+    fn default() -> Self {
+        Self { jobs: 2 }
+    }
+}
+
+/// Forge accounts mirrored by `gitnav sync`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
+#[serde(default)]
+pub struct SyncConfig {
+    /// Users, organizations, or groups whose repositories are cloned
+    pub accounts: Vec<SyncAccount>,
+    /// How new clones talk to the forge
+    pub protocol: CloneProtocol,
+}
+
+/// A forge account mirrored by `gitnav sync` (`[[sync.accounts]]`).
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct SyncAccount {
+    /// Forge host: "github.com", "gitlab.com", or a self-hosted "gitlab.*"
+    #[serde(default = "default_sync_host")]
+    pub host: String,
+    /// GitHub user or organization, or GitLab user or group (e.g. "group/sub")
+    pub owner: String,
+    /// Only repositories with at least one of these topics (empty = all)
+    #[serde(default)]
+    pub topics: Vec<String>,
+    /// Only public or only private repositories
+    #[serde(default)]
+    pub visibility: Visibility,
+    /// API token; without it `GITHUB_TOKEN` / `GH_TOKEN` or `GITLAB_TOKEN` is
+    /// used, then the git credential helper for the host
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub token: Option<String>,
+}
+
+fn default_sync_host() -> String {
+    "github.com".to_string()
+}
+
+/// Which repositories of an account `gitnav sync` clones.
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema, Default, clap::ValueEnum,
+)]
+#[serde(rename_all = "lowercase")]
+pub enum Visibility {
+    #[default]
+    All,
+    Public,
+    /// Private repositories, and GitLab's internal ones
+    Private,
+}
+
+/// URL scheme for repositories cloned by `gitnav sync`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum CloneProtocol {
+    #[default]
+    Https,
+    Ssh,
+}
+
+/// Worktrees created by `gitnav worktree --new`.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(default)]
+pub struct WorktreeConfig {
+    /// Where a new worktree goes: `{repo}` is the repository's directory name
+    /// and `{branch}` the branch with `/` replaced by `-`. Supports ~ expansion;
+    /// relative paths start from the repository's main working tree
+    pub path: String,
+}
+
+impl Default for WorktreeConfig {
+    fn default() -> Self {
+        Self {
+            path: String::from("../{repo}-{branch}"),
+        }
+    }
+}
+
+/// A window in a `gitnav tmux` session (`[[tmux.windows]]`).
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(default)]
+pub struct TmuxWindow {
+    /// Window name (tmux names it after the running program when unset)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    /// One command per pane, run in the repository; the pane drops to a shell
+    /// when it exits, and "" starts at the prompt. No entries means one shell
+    pub panes: Vec<String>,
+    /// tmux layout for the panes, e.g. "main-vertical" or "tiled"
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub layout: Option<String>,
+}
+
+/// A directory whose repositories must commit with a matching `user.email`
+/// (`[[identity.rules]]`).
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct IdentityRule {
+    /// Directory the rule covers (supports ~ expansion); the most specific
+    /// rule containing a repository applies
+    pub path: String,
+    /// Pattern `user.email` must match, `*` standing for any characters
+    /// (e.g. "*@corp.com"); compared case-insensitively
+    pub email: String,
+}
+
+/// The external fuzzy finder that interactive mode runs.
+///
+/// gitnav passes fzf's flags (`--delimiter`, `--with-nth`, `--preview`,
+/// `--expect`, ...), so the binary must accept them; skim (`sk`) does.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(default)]
+pub struct FinderConfig {
+    /// Finder executable, looked up in `PATH` unless it is a path
+    pub binary: String,
+    /// Extra arguments appended after gitnav's own, so they can override them
+    pub extra_args: Vec<String>,
+}
+
+impl Default for FinderConfig {
+    fn default() -> Self {
+        Self {
+            binary: String::from("fzf"),
+            extra_args: Vec::new(),
+        }
+    }
+}
+
+/// Configuration for non-interactive output.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
+#[serde(default)]
+pub struct DisplayConfig {
+    /// Template for `--list` lines, e.g. `"{name}\t{branch}\t{path}"` (same as `--format`)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub list_format: Option<String>,
+}
+
+/// Configuration for metadata looked up on GitHub / GitLab.
+///
+/// Everything here is opt-in; without it gitnav never touches the network.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(default)]
+pub struct ForgeConfig {
+    /// Fetch each remote's description from the GitHub / GitLab API and show it in the preview
+    pub descriptions: bool,
+    /// Seconds a fetched description is reused before it is refreshed in the background
+    pub description_ttl_seconds: u64,
+    /// Show stars, open pull requests, and the CI status of the default branch
+    /// for GitHub-hosted repositories in the preview
+    pub github_stats: bool,
+    /// Seconds fetched GitHub stats are reused before they are refreshed in the background
+    pub github_stats_ttl_seconds: u64,
+    /// Web page links for forges gitnav doesn't know, keyed by host
+    /// (`[forge.browse."git.corp.com"]`)
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub browse: BTreeMap<String, BrowseTemplate>,
+}
+
+impl Default for ForgeConfig {
+    fn default() -> Self {
+        Self {
+            descriptions: false,
+            description_ttl_seconds: 7 * 24 * 60 * 60,
+            github_stats: false,
+            github_stats_ttl_seconds: 6 * 60 * 60,
+            browse: BTreeMap::new(),
+        }
+    }
+}
+
+/// How `gitnav browse` links to a repository on a self-hosted forge.
+///
+/// Both URLs are templates with `{host}`, `{path}` (e.g. "team/api"), and
+/// `{branch}` placeholders.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct BrowseTemplate {
+    /// The repository's page, e.g. "https://{host}/{path}"
+    pub url: String,
+    /// A branch's page, e.g. "https://{host}/{path}/src/branch/{branch}";
+    /// without it `--branch` opens the repository's page
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub branch_url: Option<String>,
+}
+
+/// The config file without its `[profiles]`, for profiles to be laid over.
+fn profile_base(contents: &str) -> Result<toml::Table> {
+    let mut base: toml::Table = toml::from_str(contents)?;
+    base.remove("profiles");
+    Ok(base)
+}
+
+/// `base` with profile `name`'s `overrides` laid over it.
+fn with_profile(base: &toml::Table, name: &str, overrides: &toml::Table) -> Result<Config> {
+    profile::resolve(Some(name)).context("Invalid name in [profiles]")?;
+    if overrides.contains_key("profiles") {
+        anyhow::bail!("[profiles.{}] cannot define other profiles", name);
+    }
+    let mut merged = base.clone();
+    merge_table(&mut merged, overrides.clone());
+    toml::Value::Table(merged)
+        .try_into()
+        .with_context(|| format!("Invalid settings in [profiles.{}]", name))
+}
+
+/// Lay `overrides` over `base`: tables merge key by key, anything else replaces.
+fn merge_table(base: &mut toml::Table, overrides: toml::Table) {
+    for (key, value) in overrides {
+        match (base.get_mut(&key), value) {
+            (Some(toml::Value::Table(base)), toml::Value::Table(overrides)) => {
+                merge_table(base, overrides)
+            }
+            (_, value) => {
+                base.insert(key, value);
+            }
+        }
+    }
+}
+
+/// Environment variable that makes unknown keys in the config file an error;
+/// `--strict-config` sets it.
+pub const STRICT_ENV: &str = "GITNAV_STRICT_CONFIG";
+
+/// A problem in a config file, as found by [`Config::check`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Problem {
+    /// Line the problem is on (from 1), when it can be pinned down
+    pub line: Option<usize>,
+    pub message: String,
+}
+
+impl std::fmt::Display for Problem {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.line {
+            Some(line) => write!(f, "line {}: {}", line, self.message),
+            None => f.write_str(&self.message),
+        }
+    }
+}
+
+/// The line of byte `offset` in `contents`, counting from 1.
+fn line_at(contents: &str, offset: usize) -> usize {
+    let offset = offset.min(contents.len());
+    contents.as_bytes()[..offset]
+        .iter()
+        .filter(|&&b| b == b'\n')
+        .count()
+        + 1
+}
+
+/// The line of the key at `path` in `table`, or of the deepest part of it
+/// that is there.
+fn key_line(table: &dyn toml_edit::TableLike, path: &[&str], contents: &str) -> Option<usize> {
+    let (first, rest) = path.split_first()?;
+    let (key, item) = table.get_key_value(first)?;
+    let here = key
+        .span()
+        .or_else(|| item.span())
+        .map(|span| line_at(contents, span.start));
+    let deeper = item
+        .as_table_like()
+        .and_then(|table| key_line(table, rest, contents));
+    deeper.or(here)
+}
+
+/// Report the keys in `table`, found at `path`, that the table described by
+/// `node` has no field for.
+fn unknown_keys(
+    schema: &serde_json::Value,
+    node: &serde_json::Value,
+    table: &dyn toml_edit::TableLike,
+    path: &mut Vec<String>,
+    contents: &str,
+    problems: &mut Vec<Problem>,
+) {
+    for (key, item) in table.iter() {
+        path.push(key.to_string());
+        // A profile takes the same keys as the file itself
+        let field = if path.len() == 2 && path[0] == "profiles" {
+            Some(schema)
+        } else {
+            env::child(schema, node, key)
+        };
+        match field {
+            None => {
+                let line = table
+                    .get_key_value(key)
+                    .and_then(|(key, _)| key.span())
+                    .or_else(|| item.span())
+                    .map(|span| line_at(contents, span.start));
+                let mut message = format!("unknown key `{}`", path.join("."));
+                if let Some(known) = closest(key, &env::field_names(schema, node)) {
+                    message.push_str(&format!(" (did you mean `{}`?)", known));
+                }
+                problems.push(Problem { line, message });
+            }
+            Some(field) if !env::is_open(schema, field) => {
+                let element = env::resolve(schema, field).get("items").unwrap_or(field);
+                let mut check = |node, table: &dyn toml_edit::TableLike| {
+                    unknown_keys(schema, node, table, path, contents, problems)
+                };
+                match item {
+                    toml_edit::Item::Table(table) => check(field, table),
+                    toml_edit::Item::Value(toml_edit::Value::InlineTable(table)) => {
+                        check(field, table)
+                    }
+                    toml_edit::Item::ArrayOfTables(tables) => {
+                        for table in tables.iter() {
+                            check(element, table);
+                        }
+                    }
+                    toml_edit::Item::Value(toml_edit::Value::Array(values)) => {
+                        for table in values.iter().filter_map(toml_edit::Value::as_inline_table) {
+                            check(element, table);
+                        }
+                    }
+                    _ => {}
+                }
+            }
+            Some(_) => {}
+        }
+        path.pop();
+    }
+}
+
+/// The name in `known` within two typos of `key`, for "did you mean".
+fn closest<'a>(key: &str, known: &[&'a str]) -> Option<&'a str> {
+    known
+        .iter()
+        .map(|name| (edit_distance(key, name), *name))
+        .filter(|(distance, _)| *distance <= 2)
+        .min()
+        .map(|(_, name)| name)
+}
+
+/// Levenshtein distance between `a` and `b`.
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut row: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut diagonal = row[0];
+        row[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let above = row[j + 1];
+            row[j + 1] = (diagonal + usize::from(ca != *cb))
+                .min(above + 1)
+                .min(row[j] + 1);
+            diagonal = above;
+        }
+    }
+    row[b.len()]
+}
+
+/// Short environment variable names from before the `GITNAV_<SECTION>__<FIELD>`
+/// scheme, mapped to the config field they set.
+///
+/// List values (`GITNAV_SEARCH_PATHS`, `GITNAV_IGNORE_PATTERNS`) are separated
+/// like `PATH`: `:` on Unix, `;` on Windows.
+pub const LEGACY_ENV_VARS: &[(&str, &str)] = &[
+    ("GITNAV_BASE_PATH", "search.base_path"),
+    ("GITNAV_MAX_DEPTH", "search.max_depth"),
+    ("GITNAV_SEARCH_PATHS", "search.paths"),
+    ("GITNAV_IGNORE_PATTERNS", "search.ignore_patterns"),
+    ("GITNAV_SEARCH_ORDER", "search.order"),
+    ("GITNAV_CACHE_ENABLED", "cache.enabled"),
+    ("GITNAV_CACHE_TTL", "cache.ttl_seconds"),
+    ("GITNAV_CACHE_DIR", "cache.directory"),
+    ("GITNAV_UI_PROMPT", "ui.prompt"),
+    ("GITNAV_UI_HEADER", "ui.header"),
+    ("GITNAV_UI_PREVIEW_WIDTH", "ui.preview_width_percent"),
+    ("GITNAV_UI_LAYOUT", "ui.layout"),
+    ("GITNAV_UI_HEIGHT", "ui.height_percent"),
+    ("GITNAV_UI_BORDER", "ui.show_border"),
+    ("GITNAV_UI_INLINE_META", "ui.show_inline_meta"),
+    ("GITNAV_UI_BADGE_STYLE", "ui.badge_style"),
+    ("GITNAV_PREVIEW_SHOW_BRANCH", "preview.show_branch"),
+    ("GITNAV_PREVIEW_SHOW_ACTIVITY", "preview.show_last_activity"),
+    ("GITNAV_PREVIEW_SHOW_STATUS", "preview.show_status"),
+    ("GITNAV_PREVIEW_RECENT_COMMITS", "preview.recent_commits"),
+    ("GITNAV_PREVIEW_DATE_FORMAT", "preview.date_format"),
+    ("GITNAV_PREVIEW_MAX_LINES", "preview.max_lines"),
+    ("GITNAV_BULK_CONFIRM", "bulk.confirm"),
+];
+
+/// Schema-driven parsing of environment overrides.
+mod env {
+    use anyhow::{Context, Result};
+    use serde_json::Value as Json;
+
+    /// Follow `$ref`s and `Option<T>`'s `anyOf: [T, null]` to the schema of `T`.
+    pub(super) fn resolve<'a>(root: &'a Json, mut node: &'a Json) -> &'a Json {
+        loop {
+            if let Some(name) = node
+                .get("$ref")
+                .and_then(Json::as_str)
+                .and_then(|r| r.strip_prefix("#/$defs/"))
+            {
+                match root["$defs"].get(name) {
+                    Some(def) => node = def,
+                    None => return node,
+                }
+            } else if let Some(inner) = node
+                .get("anyOf")
+                .and_then(Json::as_array)
+                .and_then(|variants| variants.iter().find(|v| v["type"] != "null"))
+            {
+                node = inner;
+            } else {
+                return node;
+            }
+        }
+    }
+
+    fn is_nullable(node: &Json) -> bool {
+        let type_has_null = match &node["type"] {
+            Json::String(t) => t == "null",
+            Json::Array(types) => types.iter().any(|t| t == "null"),
+            _ => false,
+        };
+        let any_of_null = node["anyOf"]
+            .as_array()
+            .is_some_and(|variants| variants.iter().any(|v| v["type"] == "null"));
+        type_has_null || any_of_null
+    }
+
+    /// The schema of the field at `path`, or `None` if there is no such field.
+    pub(super) fn lookup_field<'a>(root: &'a Json, path: &[String]) -> Option<&'a Json> {
+        path.iter()
+            .try_fold(root, |node, segment| child(root, node, segment))
+    }
+
+    /// The schema of field `key` in the table described by `node`.
+    pub(super) fn child<'a>(root: &'a Json, node: &'a Json, key: &str) -> Option<&'a Json> {
+        let object = resolve(root, node);
+        object
+            .get("properties")
+            .and_then(|p| p.get(key))
+            .or_else(|| object.get("additionalProperties").filter(|v| v.is_object()))
+    }
+
+    /// The names of the fields of the table described by `node`.
+    pub(super) fn field_names<'a>(root: &'a Json, node: &'a Json) -> Vec<&'a str> {
+        resolve(root, node)
+            .get("properties")
+            .and_then(Json::as_object)
+            .map(|fields| fields.keys().map(String::as_str).collect())
+            .unwrap_or_default()
+    }
+
+    /// Whether the table described by `node` takes keys of any name.
+    pub(super) fn is_open(root: &Json, node: &Json) -> bool {
+        resolve(root, node).get("additionalProperties") == Some(&Json::Bool(true))
+    }
+
+    /// Parse `raw` as a value of the field described by `field`.
+    ///
+    /// Returns `Ok(None)` to unset an optional field (empty value).
+    pub(super) fn parse_value(root: &Json, field: &Json, raw: &str) -> Result<Option<toml::Value>> {
+        if raw.is_empty() && is_nullable(field) {
+            return Ok(None);
+        }
+        let schema = resolve(root, field);
+
+        let allowed: Vec<&str> = match (schema["enum"].as_array(), schema["oneOf"].as_array()) {
+            (Some(values), _) => values.iter().filter_map(Json::as_str).collect(),
+            (None, Some(variants)) => variants
+                .iter()
+                .filter_map(|v| v["const"].as_str())
+                .collect(),
+            (None, None) => Vec::new(),
+        };
+        if !allowed.is_empty() {
+            return allowed
+                .iter()
+                .find(|v| v.eq_ignore_ascii_case(raw.trim()))
+                .map(|v| Some(toml::Value::String(v.to_string())))
+                .ok_or_else(|| anyhow::anyhow!("expected one of: {}", allowed.join(", ")));
+        }
+
+        let kind = match &schema["type"] {
+            Json::String(t) => t.as_str(),
+            Json::Array(types) => types
+                .iter()
+                .filter_map(Json::as_str)
+                .find(|t| *t != "null")
+                .unwrap_or("string"),
+            _ => "string",
+        };
+        let value = match kind {
+            "boolean" => toml::Value::Boolean(parse_bool(raw)?),
+            "integer" => {
+                toml::Value::Integer(raw.trim().parse().context("expected a whole number")?)
+            }
+            "number" => toml::Value::Float(raw.trim().parse().context("expected a number")?),
+            "array" => {
+                if raw.trim_start().starts_with('[') {
+                    let table: toml::Table = toml::from_str(&format!("value = {}", raw))
+                        .context("expected a TOML array")?;
+                    table["value"].clone()
+                } else {
+                    let items = &schema["items"];
+                    let values = std::env::split_paths(raw)
+                        .map(|item| parse_value(root, items, &item.to_string_lossy()))
+                        .collect::<Result<Vec<_>>>()?;
+                    toml::Value::Array(values.into_iter().flatten().collect())
+                }
+            }
+            "object" => anyhow::bail!("this is a table; set its fields one at a time"),
+            _ => toml::Value::String(raw.to_string()),
+        };
+        Ok(Some(value))
+    }
+
+    pub(super) fn parse_bool(raw: &str) -> Result<bool> {
+        match raw.trim().to_lowercase().as_str() {
+            "true" | "1" | "yes" | "on" => Ok(true),
+            "false" | "0" | "no" | "off" => Ok(false),
+            _ => anyhow::bail!("expected true/false, 1/0, yes/no, or on/off"),
+        }
+    }
+
+    /// Set (or with `None`, remove) the value at `path`, creating tables as needed.
+    pub(super) fn set_path(tree: &mut toml::Value, path: &[String], value: Option<toml::Value>) {
+        let Some((last, parents)) = path.split_last() else {
+            return;
+        };
+        let mut table = match tree.as_table_mut() {
+            Some(table) => table,
+            None => return,
+        };
+        for segment in parents {
+            let entry = table
+                .entry(segment.clone())
+                .or_insert_with(|| toml::Value::Table(toml::Table::new()));
+            table = match entry.as_table_mut() {
+                Some(table) => table,
+                None => return,
+            };
+        }
+        match value {
+            Some(value) => {
+                table.insert(last.clone(), value);
+            }
+            None => {
+                table.remove(last);
+            }
         }
     }
 }
 
 impl Config {
-    /// Load configuration from a file path, using defaults if file doesn't exist.
+    /// Load configuration from config files, using defaults if none exists.
     ///
     /// # Arguments
     ///
-    /// * `path` - The path to the configuration file
+    /// * `paths` - The configuration files, lowest priority first; each
+    ///   overrides the settings it sets (see [`Config::load`])
     ///
     /// # Returns
     ///
-    /// Loaded configuration or defaults if file doesn't exist
+    /// Loaded configuration or defaults if no file exists, with the active
+    /// profile's overrides applied (see [`Config::parse`])
     ///
     /// # Errors
     ///
-    /// Returns an error if the file exists but cannot be read or parsed
-    pub fn load_from_file(path: &PathBuf) -> Result<Self> {
+    /// Returns an error if a file exists but cannot be read or parsed
+    fn load_from_files(paths: &[PathBuf]) -> Result<Self> {
+        let profile = profile::resolve(None)?;
+        let mut merged = toml::Table::new();
+        let mut layers = 0;
+        for path in paths {
+            if let Some(layer) = Self::read_layer(path, profile.as_deref())? {
+                merge_table(&mut merged, layer);
+                layers += 1;
+            }
+        }
+        match layers {
+            0 => Ok(Self::default()),
+            _ => Self::from_table(merged, profile.as_deref())
+                .context("Failed to combine config files"),
+        }
+    }
+
+    /// Read one config file as a table, or `None` if it does not exist.
+    ///
+    /// The file is parsed on its own first, so a mistake is reported against
+    /// the file that has it rather than the combined config.
+    fn read_layer(path: &Path, profile: Option<&str>) -> Result<Option<toml::Table>> {
         if !path.exists() {
-            return Ok(Self::default());
+            return Ok(None);
         }
 
         let contents = std::fs::read_to_string(path)
             .with_context(|| format!("Failed to read config file: {}", path.display()))?;
 
-        let config: Config = toml::from_str(&contents)
+        if Self::strict()? {
+            let unknown = Self::unknown_keys(&contents)?;
+            if !unknown.is_empty() {
+                let lines: Vec<String> = unknown.iter().map(Problem::to_string).collect();
+                anyhow::bail!(
+                    "Unknown keys in config file {} (not allowed with {} set):\n  {}",
+                    path.display(),
+                    STRICT_ENV,
+                    lines.join("\n  ")
+                );
+            }
+        }
+
+        Self::parse(&contents, profile)
             .with_context(|| format!("Failed to parse config file: {}", path.display()))?;
+        Ok(Some(toml::from_str(&contents)?))
+    }
 
-        Ok(config)
+    /// Whether unknown keys are errors, from `GITNAV_STRICT_CONFIG`.
+    fn strict() -> Result<bool> {
+        match std::env::var(STRICT_ENV) {
+            Ok(value) if !value.is_empty() => env::parse_bool(&value)
+                .with_context(|| format!("Invalid value for {}: {:?}", STRICT_ENV, value)),
+            _ => Ok(false),
+        }
+    }
+
+    /// The keys in the config file text `contents` that are not settings.
+    /// Returns none if `contents` is not valid TOML.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error only if the config schema cannot be built
+    pub fn unknown_keys(contents: &str) -> Result<Vec<Problem>> {
+        let Ok(doc) = toml_edit::ImDocument::parse(contents) else {
+            return Ok(Vec::new());
+        };
+        let schema = serde_json::to_value(schemars::schema_for!(Config))
+            .context("Failed to build config schema")?;
+        let mut problems = Vec::new();
+        unknown_keys(
+            &schema,
+            &schema,
+            doc.as_table(),
+            &mut Vec::new(),
+            contents,
+            &mut problems,
+        );
+        Ok(problems)
+    }
+
+    /// Every problem in the config file text `contents`, in line order: TOML
+    /// syntax, unknown keys, values of the wrong type, and values
+    /// [`Config::validate`] rejects, with every profile applied in turn.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error only if the config schema cannot be built
+    pub fn check(contents: &str) -> Result<Vec<Problem>> {
+        let doc = match toml_edit::ImDocument::parse(contents) {
+            Ok(doc) => doc,
+            Err(e) => {
+                return Ok(vec![Problem {
+                    line: e.span().map(|span| line_at(contents, span.start)),
+                    message: e.message().trim().to_string(),
+                }])
+            }
+        };
+        let mut problems = Self::unknown_keys(contents)?;
+        let config: Config = match toml::from_str(contents) {
+            Ok(config) => config,
+            Err(e) => {
+                problems.push(Problem {
+                    line: e.span().map(|span| line_at(contents, span.start)),
+                    message: e.message().trim().to_string(),
+                });
+                problems.sort_by_key(|p| p.line.unwrap_or(usize::MAX));
+                return Ok(problems);
+            }
+        };
+
+        // Validation messages start with the setting, e.g. "search.max_depth must be..."
+        let invalid = |config: &Config, prefix: &[&str]| {
+            let message = config.validate().err()?.to_string();
+            let setting = message.split_whitespace().next().unwrap_or_default();
+            let path: Vec<&str> = prefix.iter().copied().chain(setting.split('.')).collect();
+            Some(Problem {
+                line: key_line(doc.as_table(), &path, contents),
+                message,
+            })
+        };
+        let base_problem = invalid(&config, &[]);
+        if !config.profiles.is_empty() {
+            let base = profile_base(contents)?;
+            for (name, overrides) in &config.profiles {
+                let problem = match with_profile(&base, name, overrides) {
+                    Ok(applied) => invalid(&applied, &["profiles", name])
+                        // Already reported for the file itself
+                        .filter(|p| Some(&p.message) != base_problem.as_ref().map(|b| &b.message))
+                        .map(|p| Problem {
+                            message: format!("with profile {}: {}", name, p.message),
+                            ..p
+                        }),
+                    Err(e) => Some(Problem {
+                        line: key_line(doc.as_table(), &["profiles", name], contents),
+                        // serde puts the failing setting on its own line
+                        message: format!("{:#}", e)
+                            .split_whitespace()
+                            .collect::<Vec<_>>()
+                            .join(" "),
+                    }),
+                };
+                problems.extend(problem);
+            }
+        }
+        problems.extend(base_problem);
+        problems.sort_by_key(|p| p.line.unwrap_or(usize::MAX));
+        Ok(problems)
+    }
+
+    /// Parse a config file, laying `[profiles.<profile>]` over the rest of it.
+    ///
+    /// Tables in a profile merge into the matching sections key by key; any
+    /// other value, arrays included, replaces the one in the file. Every profile
+    /// is checked, not just the active one, so a typo in `[profiles.home]`
+    /// shows up at work too. A profile without a table changes nothing, since
+    /// profiles also just keep separate caches.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file, or the file with any profile applied, is
+    /// not a valid configuration
+    fn parse(contents: &str, profile: Option<&str>) -> Result<Self> {
+        // Straight from the text, for errors with line numbers
+        let config: Config = toml::from_str(contents)?;
+        if config.profiles.is_empty() {
+            return Ok(config);
+        }
+        config.with_active_profile(&profile_base(contents)?, profile)
+    }
+
+    /// [`Config::parse`] for a config that is already a table.
+    fn from_table(mut table: toml::Table, profile: Option<&str>) -> Result<Self> {
+        let config: Config = toml::Value::Table(table.clone()).try_into()?;
+        table.remove("profiles");
+        config.with_active_profile(&table, profile)
+    }
+
+    /// Check every profile against `base`, the config without its profiles,
+    /// and return it with `profile`'s overrides applied.
+    fn with_active_profile(self, base: &toml::Table, profile: Option<&str>) -> Result<Self> {
+        let mut active = None;
+        for (name, overrides) in &self.profiles {
+            let applied = with_profile(base, name, overrides)?;
+            if profile == Some(name.as_str()) {
+                active = Some(applied);
+            }
+        }
+
+        Ok(match active {
+            Some(applied) => Config {
+                profiles: self.profiles,
+                ..applied
+            },
+            None => self,
+        })
     }
 
     /// Get the default configuration file paths in priority order.
@@ -160,32 +1464,115 @@ impl Config {
     /// 1. Home directory `.config/gitnav/config.toml` (cross-platform compatibility)
     /// 2. Platform-specific config directory (XDG_CONFIG_HOME on Linux, ~/Library/Application Support on macOS, %APPDATA% on Windows)
     ///
-    /// # Returns
+    /// # Returns
+    ///
+    /// A vector of config paths to check, in priority order
+    fn default_paths() -> Vec<PathBuf> {
+        let mut paths = Vec::new();
+
+        // Add cross-platform ~/.config/gitnav/config.toml first (highest priority)
+        if let Some(home_dir) = dirs::home_dir() {
+            paths.push(home_dir.join(".config").join("gitnav").join("config.toml"));
+        }
+
+        // Add platform-specific config directory as fallback
+        if let Some(config_dir) = dirs::config_dir() {
+            paths.push(config_dir.join("gitnav").join("config.toml"));
+        }
+
+        paths
+    }
+
+    /// The config file gitnav reads, and `gitnav config set` and `config edit`
+    /// write: `custom_path` if given, else the first default path that exists,
+    /// else the first default path (which may not exist yet).
+    pub fn file_path(custom_path: Option<PathBuf>) -> Option<PathBuf> {
+        if custom_path.is_some() {
+            return custom_path;
+        }
+        let paths = Self::default_paths();
+        paths
+            .iter()
+            .find(|path| path.exists())
+            .or_else(|| paths.first())
+            .cloned()
+    }
+
+    /// The config files [`Config::load`] reads, lowest priority first: the
+    /// platform config directory's, `~/.config/gitnav/config.toml`, then
+    /// `custom_path`. Some may not exist.
+    pub fn layer_paths(custom_path: Option<PathBuf>) -> Vec<PathBuf> {
+        let mut paths = Self::default_paths();
+        paths.reverse();
+        // Both locations are `~/.config` on Linux
+        paths.dedup();
+        if let Some(custom) = custom_path {
+            paths.retain(|path| *path != custom);
+            paths.push(custom);
+        }
+        paths
+    }
+
+    /// Global scan ignore files, in gitignore syntax: `~/.config/gitnav/ignore`
+    /// and `ignore` in the platform config directory, like [`Self::default_paths`].
+    pub fn ignore_file_paths() -> Vec<PathBuf> {
+        let mut paths: Vec<PathBuf> = Self::default_paths()
+            .into_iter()
+            .filter_map(|config| Some(config.parent()?.join("ignore")))
+            .collect();
+        // Both locations are `~/.config` on Linux
+        paths.dedup();
+        paths
+    }
+
+    /// The preview settings for the repository at `repo`: `[preview]` with the
+    /// `[preview]` tables of the `.gitnav.toml` files in `repo` and the
+    /// directories above it laid over it, the nearest one last.
+    ///
+    /// # Errors
     ///
-    /// A vector of config paths to check, in priority order
-    fn default_paths() -> Vec<PathBuf> {
-        let mut paths = Vec::new();
-
-        // Add cross-platform ~/.config/gitnav/config.toml first (highest priority)
-        if let Some(home_dir) = dirs::home_dir() {
-            paths.push(home_dir.join(".config").join("gitnav").join("config.toml"));
+    /// Returns an error naming the file if a `.gitnav.toml` cannot be read or
+    /// has invalid preview settings
+    pub fn preview_for(&self, repo: &Path) -> Result<PreviewConfig> {
+        let mut found = Vec::new();
+        for dir in repo.ancestors() {
+            if let Some(dir_config) = DirConfig::load(dir)? {
+                found.push((dir, dir_config.preview));
+            }
         }
-
-        // Add platform-specific config directory as fallback
-        if let Some(config_dir) = dirs::config_dir() {
-            paths.push(config_dir.join("gitnav").join("config.toml"));
+        if found.is_empty() {
+            return Ok(self.preview.clone());
         }
 
-        paths
+        let mut preview = toml::Table::try_from(&self.preview)?;
+        let mut merged = self.preview.clone();
+        for (dir, overrides) in found.into_iter().rev() {
+            merge_table(&mut preview, overrides);
+            merged = toml::Value::Table(preview.clone())
+                .try_into()
+                .with_context(|| {
+                    format!(
+                        "Invalid [preview] settings in {}",
+                        dir.join(DIR_CONFIG_FILENAME).display()
+                    )
+                })?;
+        }
+        Ok(merged)
     }
 
     /// Load configuration with priority: env > custom > default > built-in defaults.
     ///
-    /// Configuration is loaded in the following order:
+    /// Configuration is loaded in the following order, each layer overriding
+    /// only the settings it sets:
     /// 1. Built-in defaults
-    /// 2. Default paths in priority order (`~/.config/gitnav/config.toml`, then platform-specific)
-    /// 3. Custom path (if provided)
-    /// 4. Environment variables (override everything)
+    /// 2. The platform-specific config file, then `~/.config/gitnav/config.toml`,
+    ///    then the custom path if provided; see [`Config::layer_paths`]
+    /// 3. Environment variables (override everything)
+    ///
+    /// Files merge like profiles: tables key by key, while any other value,
+    /// arrays included, replaces the one below it. The profile named by
+    /// `GITNAV_PROFILE` is applied to the combined files; `--profile` sets that
+    /// variable at startup.
     ///
     /// # Arguments
     ///
@@ -197,133 +1584,156 @@ impl Config {
     ///
     /// # Errors
     ///
-    /// Returns an error if a specified config file cannot be read or parsed
+    /// Returns an error if a config file cannot be read or parsed
     pub fn load(custom_path: Option<PathBuf>) -> Result<Self> {
-        let mut config = Self::default();
-
-        // Load from first available default path
-        for default_path in Self::default_paths() {
-            if let Ok(loaded) = Self::load_from_file(&default_path) {
-                config = loaded;
-                break;
-            }
-        }
-
-        // Load from custom path if provided
-        if let Some(path) = custom_path {
-            config = Self::load_from_file(&path)?;
-        }
+        let mut config = Self::load_from_files(&Self::layer_paths(custom_path))?;
 
         // Override with environment variables
-        config.apply_env_vars();
+        config.apply_env_vars()?;
 
         Ok(config)
     }
 
     /// Apply environment variable overrides to configuration.
     ///
-    /// Supports the following environment variables:
-    /// - GITNAV_BASE_PATH: Base search path
-    /// - GITNAV_MAX_DEPTH: Maximum search depth
-    /// - GITNAV_CACHE_ENABLED: Cache enabled (true/false)
-    /// - GITNAV_CACHE_TTL: Cache TTL in seconds
-    /// - GITNAV_UI_PROMPT: FZF prompt text
-    /// - GITNAV_UI_HEADER: FZF header text
-    /// - GITNAV_UI_PREVIEW_WIDTH: Preview pane width (0-100)
-    /// - GITNAV_UI_LAYOUT: FZF layout style
-    /// - GITNAV_UI_HEIGHT: FZF window height (1-100)
-    /// - GITNAV_UI_BORDER: Show border (true/false)
-    /// - GITNAV_PREVIEW_SHOW_BRANCH: Show branch info (true/false)
-    /// - GITNAV_PREVIEW_SHOW_ACTIVITY: Show last activity (true/false)
-    /// - GITNAV_PREVIEW_SHOW_STATUS: Show status (true/false)
-    /// - GITNAV_PREVIEW_RECENT_COMMITS: Number of recent commits to show
-    /// - GITNAV_PREVIEW_DATE_FORMAT: Date format string (strftime format)
-    pub(crate) fn apply_env_vars(&mut self) {
-        // Search configuration
-        if let Ok(val) = std::env::var("GITNAV_BASE_PATH") {
-            self.search.base_path = val;
-        }
-        if let Ok(val) = std::env::var("GITNAV_MAX_DEPTH") {
-            if let Ok(depth) = val.parse::<usize>() {
-                self.search.max_depth = depth;
+    /// Every config field can be set with `GITNAV_<SECTION>__<FIELD>`, using a
+    /// double underscore between path segments (e.g. `GITNAV_SEARCH__MAX_DEPTH=8`,
+    /// `GITNAV_CACHE__DIRECTORY=/tmp/gitnav`). Field types come from the config
+    /// schema, so new fields are covered automatically. The older short names in
+    /// [`LEGACY_ENV_VARS`] keep working; the `__` form wins when both are set.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error naming the variable if it does not match a config field
+    /// or its value cannot be parsed as the field's type
+    pub(crate) fn apply_env_vars(&mut self) -> Result<()> {
+        self.apply_env(std::env::vars())
+    }
+
+    /// Apply overrides from the given `(name, value)` pairs; see [`Config::apply_env_vars`].
+    fn apply_env<I>(&mut self, vars: I) -> Result<()>
+    where
+        I: IntoIterator<Item = (String, String)>,
+    {
+        let mut legacy = Vec::new();
+        let mut structured = Vec::new();
+        for (name, value) in vars {
+            if let Some((_, path)) = LEGACY_ENV_VARS.iter().find(|(var, _)| *var == name) {
+                let path: Vec<String> = path.split('.').map(String::from).collect();
+                legacy.push((name, path, value));
+            } else if let Some(rest) = name.strip_prefix("GITNAV_").filter(|r| r.contains("__")) {
+                let path: Vec<String> = rest.split("__").map(str::to_lowercase).collect();
+                structured.push((name, path, value));
             }
         }
-
-        // Cache configuration
-        if let Ok(val) = std::env::var("GITNAV_CACHE_ENABLED") {
-            self.cache.enabled = val.to_lowercase() == "true" || val == "1" || val == "yes";
+        if legacy.is_empty() && structured.is_empty() {
+            return Ok(());
         }
-        if let Ok(val) = std::env::var("GITNAV_CACHE_TTL") {
-            if let Ok(ttl) = val.parse::<u64>() {
-                self.cache.ttl_seconds = ttl;
-            }
+        legacy.sort();
+        structured.sort();
+
+        let schema = serde_json::to_value(schemars::schema_for!(Config))
+            .context("Failed to build config schema")?;
+        let mut tree =
+            toml::Value::try_from(&*self).context("Failed to serialize configuration")?;
+
+        for (var, path, raw) in legacy.into_iter().chain(structured) {
+            let field = env::lookup_field(&schema, &path).ok_or_else(|| {
+                anyhow::anyhow!(
+                    "{} does not match any config field (no `{}` setting)",
+                    var,
+                    path.join(".")
+                )
+            })?;
+            let value = env::parse_value(&schema, field, &raw)
+                .with_context(|| format!("Invalid value for {}: {:?}", var, raw))?;
+            env::set_path(&mut tree, &path, value);
+            *self = tree
+                .clone()
+                .try_into()
+                .with_context(|| format!("Invalid value for {}: {:?}", var, raw))?;
         }
+        Ok(())
+    }
 
-        // UI configuration
-        if let Ok(val) = std::env::var("GITNAV_UI_PROMPT") {
-            self.ui.prompt = val;
+    /// The value of `key` (e.g. `search.max_depth`), or `None` for an optional
+    /// setting that is not set.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `key` does not name a setting
+    pub fn get(&self, key: &str) -> Result<Option<toml::Value>> {
+        let schema = serde_json::to_value(schemars::schema_for!(Config))
+            .context("Failed to build config schema")?;
+        let path: Vec<String> = key.split('.').map(String::from).collect();
+        if env::lookup_field(&schema, &path).is_none() {
+            anyhow::bail!("There is no `{}` setting", key);
         }
-        if let Ok(val) = std::env::var("GITNAV_UI_HEADER") {
-            self.ui.header = val;
+
+        let tree = toml::Value::try_from(self).context("Failed to serialize configuration")?;
+        Ok(path
+            .iter()
+            .try_fold(&tree, |node, segment| node.get(segment))
+            .cloned())
+    }
+
+    /// Set `key` to `raw` in the config file text `contents`, keeping its
+    /// comments and layout, and return the new text.
+    ///
+    /// `raw` is read as the setting's type, as for environment overrides: `7`,
+    /// `yes`, `~/a:~/b` or `["~/a", "~/b"]` for a list, and empty to remove an
+    /// optional setting. Keys under `profiles.<name>.` take the type of the
+    /// setting they override.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `key` does not name a setting, `raw` is not a value
+    /// of its type, or the result is not a valid configuration
+    pub fn set_in(contents: &str, key: &str, raw: &str) -> Result<String> {
+        let schema = serde_json::to_value(schemars::schema_for!(Config))
+            .context("Failed to build config schema")?;
+        let path: Vec<String> = key.split('.').map(String::from).collect();
+        let setting = match path.as_slice() {
+            [profiles, _, rest @ ..] if profiles == "profiles" && !rest.is_empty() => rest,
+            _ => &path[..],
+        };
+        let field = env::lookup_field(&schema, setting)
+            .ok_or_else(|| anyhow::anyhow!("There is no `{}` setting", key))?;
+        let value = env::parse_value(&schema, field, raw)
+            .with_context(|| format!("Invalid value for {}: {:?}", key, raw))?;
+
+        let mut doc: toml_edit::DocumentMut = contents.parse()?;
+        let Some((last, parents)) = path.split_last() else {
+            anyhow::bail!("No setting given");
+        };
+        let mut table: &mut dyn toml_edit::TableLike = doc.as_table_mut();
+        for segment in parents {
+            let item = table.entry(segment).or_insert_with(|| {
+                let mut new = toml_edit::Table::new();
+                new.set_implicit(true);
+                toml_edit::Item::Table(new)
+            });
+            table = item
+                .as_table_like_mut()
+                .with_context(|| format!("`{}` in the config file is not a table", segment))?;
         }
-        if let Ok(val) = std::env::var("GITNAV_UI_PREVIEW_WIDTH") {
-            if let Ok(width) = val.parse::<u8>() {
-                if width <= 100 {
-                    self.ui.preview_width_percent = width;
+        match value {
+            Some(value) => {
+                let mut value: toml_edit::Value = value.to_string().parse()?;
+                // Keep a comment after the old value
+                if let Some(old) = table.get(last).and_then(toml_edit::Item::as_value) {
+                    *value.decor_mut() = old.decor().clone();
                 }
+                table.insert(last, toml_edit::Item::Value(value));
             }
-        }
-        if let Ok(val) = std::env::var("GITNAV_UI_LAYOUT") {
-            self.ui.layout = val;
-        }
-        if let Ok(val) = std::env::var("GITNAV_UI_HEIGHT") {
-            if let Ok(height) = val.parse::<u8>() {
-                if height > 0 && height <= 100 {
-                    self.ui.height_percent = height;
-                }
+            None => {
+                table.remove(last);
             }
         }
-        if let Ok(val) = std::env::var("GITNAV_UI_BORDER") {
-            self.ui.show_border = val.to_lowercase() == "true" || val == "1" || val == "yes";
-        }
-        if let Ok(val) = std::env::var("GITNAV_UI_INLINE_META") {
-            self.ui.show_inline_meta =
-                val.to_lowercase() == "true" || val == "1" || val == "yes";
-        }
-        if let Ok(val) = std::env::var("GITNAV_UI_BADGE_STYLE") {
-            self.ui.badge_style = match val.to_lowercase().as_str() {
-                "none" => BadgeStyle::None,
-                "icon" => BadgeStyle::Icon,
-                _ => BadgeStyle::Text,
-            };
-        }
-        // Search paths override
-        if let Ok(val) = std::env::var("GITNAV_SEARCH_PATHS") {
-            self.search.paths = val.split(':').map(|s| s.to_string()).collect();
-        }
-        if let Ok(val) = std::env::var("GITNAV_IGNORE_PATTERNS") {
-            self.search.ignore_patterns = val.split(':').map(|s| s.to_string()).collect();
-        }
 
-        // Preview configuration
-        if let Ok(val) = std::env::var("GITNAV_PREVIEW_SHOW_BRANCH") {
-            self.preview.show_branch = val.to_lowercase() == "true" || val == "1" || val == "yes";
-        }
-        if let Ok(val) = std::env::var("GITNAV_PREVIEW_SHOW_ACTIVITY") {
-            self.preview.show_last_activity =
-                val.to_lowercase() == "true" || val == "1" || val == "yes";
-        }
-        if let Ok(val) = std::env::var("GITNAV_PREVIEW_SHOW_STATUS") {
-            self.preview.show_status = val.to_lowercase() == "true" || val == "1" || val == "yes";
-        }
-        if let Ok(val) = std::env::var("GITNAV_PREVIEW_RECENT_COMMITS") {
-            if let Ok(commits) = val.parse::<usize>() {
-                self.preview.recent_commits = commits;
-            }
-        }
-        if let Ok(val) = std::env::var("GITNAV_PREVIEW_DATE_FORMAT") {
-            self.preview.date_format = val;
-        }
+        let updated = doc.to_string();
+        Self::parse(&updated, None)?.validate()?;
+        Ok(updated)
     }
 
     /// Validate configuration values for correctness
@@ -335,6 +1745,26 @@ impl Config {
             anyhow::bail!("search.max_depth must be at least 1, got 0");
         }
 
+        if self.search.timeout_ms == Some(0) {
+            anyhow::bail!("search.timeout_ms must be at least 1, got 0");
+        }
+
+        if self.search.max_results == Some(0) {
+            anyhow::bail!("search.max_results must be at least 1, got 0");
+        }
+
+        for root in &self.search.roots {
+            if root.path.trim().is_empty() {
+                anyhow::bail!("search.roots entries need a path");
+            }
+            if root.max_depth == Some(0) {
+                anyhow::bail!(
+                    "search.roots max_depth must be at least 1, got 0 for {}",
+                    root.path
+                );
+            }
+        }
+
         if self.ui.preview_width_percent > 100 {
             anyhow::bail!(
                 "ui.preview_width_percent cannot exceed 100, got {}",
@@ -353,9 +1783,69 @@ impl Config {
             anyhow::bail!("ui.height_percent must be at least 1, got 0");
         }
 
+        if self.daemon.poll_seconds == 0 {
+            anyhow::bail!("daemon.poll_seconds must be at least 1, got 0");
+        }
+
+        for rule in &self.identity.rules {
+            if rule.path.trim().is_empty() || rule.email.trim().is_empty() {
+                anyhow::bail!("identity.rules entries need a path and an email");
+            }
+        }
+
+        if self.finder.binary.trim().is_empty() {
+            anyhow::bail!("finder.binary must not be empty");
+        }
+
+        for account in &self.sync.accounts {
+            if account.owner.trim().is_empty() {
+                anyhow::bail!("sync.accounts entries need an owner");
+            }
+        }
+
+        for item in &self.actions.items {
+            if item.name.trim().is_empty() {
+                anyhow::bail!("actions.items entries need a name");
+            }
+            Template::<Field>::parse(&item.command)
+                .with_context(|| format!("Invalid command for action '{}'", item.name))?;
+        }
+
+        for (host, browse) in &self.forge.browse {
+            Template::<BrowseField>::parse(&browse.url)
+                .with_context(|| format!("Invalid forge.browse.\"{}\".url", host))?;
+            if let Some(ref branch_url) = browse.branch_url {
+                Template::<BrowseField>::parse(branch_url)
+                    .with_context(|| format!("Invalid forge.browse.\"{}\".branch_url", host))?;
+            }
+        }
+
+        if let Some(ref template) = self.preview.template {
+            Template::<PreviewField>::parse(template).context("Invalid preview.template")?;
+        }
+
+        if let Some(ref format) = self.display.list_format {
+            Template::<Field>::parse(format).context("Invalid display.list_format")?;
+        }
+
+        Template::<PathField>::parse(&self.worktree.path).context("Invalid worktree.path")?;
+
         Ok(())
     }
 
+    /// Generate a JSON Schema describing the configuration file.
+    ///
+    /// Used by `gitnav config schema` so editors (taplo / Even Better TOML) can
+    /// validate and autocomplete `config.toml`.
+    ///
+    /// # Returns
+    ///
+    /// A pretty-printed JSON Schema document
+    pub fn json_schema() -> String {
+        let schema = schemars::schema_for!(Config);
+        serde_json::to_string_pretty(&schema).unwrap_or_else(|_| String::from("{}"))
+    }
+
     /// Generate an example configuration file as a TOML string.
     ///
     /// Used by `gitnav config` command to show users an example configuration.
@@ -403,6 +1893,104 @@ mod tests {
             .contains("max_depth must be at least 1"));
     }
 
+    #[test]
+    fn test_search_roots_parse_and_validate() {
+        let search: SearchConfig = toml::from_str(
+            r#"
+            base_path = "~"
+            max_depth = 5
+
+            [[roots]]
+            path = "~/work"
+            max_depth = 3
+            exclude = ["legacy"]
+
+            [[roots]]
+            path = "~/scratch"
+            "#,
+        )
+        .unwrap();
+        assert_eq!(
+            search.roots,
+            vec![
+                SearchRoot {
+                    path: "~/work".to_string(),
+                    max_depth: Some(3),
+                    exclude: vec!["legacy".to_string()],
+                },
+                SearchRoot {
+                    path: "~/scratch".to_string(),
+                    max_depth: None,
+                    exclude: Vec::new(),
+                },
+            ]
+        );
+        let mut config = Config {
+            search,
+            ..Config::default()
+        };
+        assert!(config.validate().is_ok());
+        let reparsed: Config = toml::from_str(&toml::to_string(&config).unwrap()).unwrap();
+        assert_eq!(reparsed.search.roots, config.search.roots);
+
+        config.search.roots[1].max_depth = Some(0);
+        assert!(config.validate().is_err());
+    }
+
+    #[test]
+    fn test_identity_rules_parse_and_validate() {
+        let identity: IdentityConfig = toml::from_str(
+            r#"
+            [[rules]]
+            path = "~/work"
+            email = "*@corp.com"
+            "#,
+        )
+        .unwrap();
+        assert_eq!(
+            identity.rules,
+            vec![IdentityRule {
+                path: "~/work".to_string(),
+                email: "*@corp.com".to_string(),
+            }]
+        );
+        let mut config = Config {
+            identity,
+            ..Config::default()
+        };
+        assert!(config.validate().is_ok());
+
+        config.identity.rules[0].email = String::new();
+        assert!(config.validate().is_err());
+    }
+
+    #[test]
+    fn test_forge_browse_templates_parse_and_validate() {
+        let forge: ForgeConfig = toml::from_str(
+            r#"
+            [browse."git.corp.com"]
+            url = "https://{host}/{path}"
+            "#,
+        )
+        .unwrap();
+        assert_eq!(
+            forge.browse["git.corp.com"],
+            BrowseTemplate {
+                url: "https://{host}/{path}".to_string(),
+                branch_url: None,
+            }
+        );
+        let mut config = Config {
+            forge,
+            ..Config::default()
+        };
+        assert!(config.validate().is_ok());
+
+        let template = config.forge.browse.get_mut("git.corp.com").unwrap();
+        template.branch_url = Some("https://{host}/{path}/tree/{brnch}".to_string());
+        assert!(config.validate().is_err());
+    }
+
     #[test]
     fn test_validation_rejects_preview_width_over_100() {
         let mut config = Config::default();
@@ -439,6 +2027,21 @@ mod tests {
             .contains("height_percent must be at least 1"));
     }
 
+    #[test]
+    fn test_validation_rejects_unknown_list_format_placeholder() {
+        let mut config = Config::default();
+        config.display.list_format = Some("{name}\t{branch}".to_string());
+        assert!(config.validate().is_ok());
+
+        config.display.list_format = Some("{nmae}".to_string());
+        let result = config.validate();
+        assert!(result.is_err());
+        assert!(result
+            .unwrap_err()
+            .to_string()
+            .contains("display.list_format"));
+    }
+
     #[test]
     fn test_validation_accepts_boundary_values() {
         let mut config = Config::default();
@@ -463,6 +2066,7 @@ mod tests {
         let config = Config::default();
         assert!(config.cache.enabled);
         assert_eq!(config.cache.ttl_seconds, 300);
+        assert!(config.cache.background_refresh);
     }
 
     #[test]
@@ -501,7 +2105,7 @@ mod tests {
     #[test]
     fn test_load_from_nonexistent_file_returns_defaults() {
         let nonexistent_path = PathBuf::from("/nonexistent/path/config.toml");
-        let config = Config::load_from_file(&nonexistent_path).expect("Should return defaults");
+        let config = Config::load_from_files(&[nonexistent_path]).expect("Should return defaults");
         assert_eq!(config.search.max_depth, 5);
         assert!(config.cache.enabled);
     }
@@ -625,10 +2229,75 @@ mod tests {
         assert!(example.contains("show_branch"));
         assert!(example.contains("show_last_activity"));
         assert!(example.contains("show_status"));
+        assert!(example.contains("show_remotes"));
+        assert!(example.contains("show_diffstat"));
+        assert!(example.contains("show_tags"));
+        assert!(example.contains("show_identity"));
+        assert!(example.contains("show_commit_activity"));
+        assert!(example.contains("show_branches"));
+        assert!(example.contains("status_max_files"));
+        assert!(example.contains("status_timeout_ms"));
+        assert!(example.contains("cache_ttl_seconds"));
         assert!(example.contains("recent_commits"));
         assert!(example.contains("date_format"));
     }
 
+    #[test]
+    fn test_tmux_windows_parse() {
+        let tmux: TmuxConfig = toml::from_str(
+            r#"
+            [[windows]]
+            name = "code"
+            panes = ["nvim", ""]
+
+            [[windows]]
+            "#,
+        )
+        .unwrap();
+        assert_eq!(
+            tmux.windows,
+            vec![
+                TmuxWindow {
+                    name: Some("code".to_string()),
+                    panes: vec!["nvim".to_string(), String::new()],
+                    layout: None,
+                },
+                TmuxWindow::default(),
+            ]
+        );
+        assert!(Config::default().tmux.windows.is_empty());
+    }
+
+    #[test]
+    fn test_worktree_path_is_validated() {
+        let mut config = Config::default();
+        assert_eq!(config.worktree.path, "../{repo}-{branch}");
+        assert!(config.validate().is_ok());
+
+        config.worktree.path = String::from("~/trees/{name}");
+        let err = config.validate().unwrap_err();
+        assert!(format!("{:#}", err).contains("worktree.path"));
+    }
+
+    #[test]
+    fn test_sync_accounts_parse_and_validate() {
+        let mut config = Config {
+            sync: toml::from_str(
+                "[[accounts]]\nowner = \"acme\"\n\n\
+                 [[accounts]]\nhost = \"gitlab.com\"\nowner = \"group/sub\"\nvisibility = \"private\"\n",
+            )
+            .unwrap(),
+            ..Config::default()
+        };
+        assert_eq!(config.sync.accounts[0].host, "github.com");
+        assert_eq!(config.sync.accounts[1].visibility, Visibility::Private);
+        assert_eq!(config.sync.protocol, CloneProtocol::Https);
+        assert!(config.validate().is_ok());
+
+        config.sync.accounts[0].owner = String::from(" ");
+        assert!(config.validate().is_err());
+    }
+
     #[test]
     fn test_search_config_base_path_not_empty() {
         let config = Config::default();
@@ -756,22 +2425,25 @@ date_format = "%Y-%m-%d"
         assert!(parsed.search.ignore_patterns.is_empty());
         assert!(parsed.ui.show_inline_meta); // default = true
         assert_eq!(parsed.ui.badge_style, BadgeStyle::Text); // default
+        assert!(parsed.cache.background_refresh); // default = true
     }
 
     #[test]
     fn test_env_var_search_paths() {
-        std::env::set_var("GITNAV_SEARCH_PATHS", "/tmp/a:/tmp/b");
+        let paths = std::env::join_paths(["/tmp/a", "/tmp/b"]).unwrap();
+        std::env::set_var("GITNAV_SEARCH_PATHS", paths);
         let mut config = Config::default();
-        config.apply_env_vars();
+        config.apply_env_vars().unwrap();
         assert_eq!(config.search.paths, vec!["/tmp/a", "/tmp/b"]);
         std::env::remove_var("GITNAV_SEARCH_PATHS");
     }
 
     #[test]
     fn test_env_var_ignore_patterns() {
-        std::env::set_var("GITNAV_IGNORE_PATTERNS", "node_modules:vendor");
+        let patterns = std::env::join_paths(["node_modules", "vendor"]).unwrap();
+        std::env::set_var("GITNAV_IGNORE_PATTERNS", patterns);
         let mut config = Config::default();
-        config.apply_env_vars();
+        config.apply_env_vars().unwrap();
         assert_eq!(
             config.search.ignore_patterns,
             vec!["node_modules", "vendor"]
@@ -779,15 +2451,63 @@ date_format = "%Y-%m-%d"
         std::env::remove_var("GITNAV_IGNORE_PATTERNS");
     }
 
+    #[test]
+    fn test_env_var_search_order() {
+        assert_eq!(Config::default().search.order, SortOrder::Name);
+        std::env::set_var("GITNAV_SEARCH_ORDER", "path");
+        let mut config = Config::default();
+        config.apply_env_vars().unwrap();
+        assert_eq!(config.search.order, SortOrder::Path);
+        std::env::remove_var("GITNAV_SEARCH_ORDER");
+    }
+
     #[test]
     fn test_env_var_inline_meta() {
         std::env::set_var("GITNAV_UI_INLINE_META", "false");
         let mut config = Config::default();
-        config.apply_env_vars();
+        config.apply_env_vars().unwrap();
         assert!(!config.ui.show_inline_meta);
         std::env::remove_var("GITNAV_UI_INLINE_META");
     }
 
+    #[test]
+    fn test_cache_directory_from_toml_and_env() {
+        let toml_str = r#"
+[search]
+base_path = "~"
+max_depth = 5
+
+[cache]
+enabled = true
+ttl_seconds = 300
+directory = "/mnt/tmpfs/gitnav"
+
+[ui]
+prompt = "> "
+header = "h"
+preview_width_percent = 60
+layout = "reverse"
+height_percent = 90
+show_border = true
+
+[preview]
+show_branch = true
+show_last_activity = true
+show_status = true
+recent_commits = 5
+date_format = "%Y-%m-%d"
+"#;
+        let config: Config = toml::from_str(toml_str).unwrap();
+        assert_eq!(config.cache.directory.as_deref(), Some("/mnt/tmpfs/gitnav"));
+        assert_eq!(Config::default().cache.directory, None);
+
+        std::env::set_var("GITNAV_CACHE_DIR", "/srv/team-cache");
+        let mut config = Config::default();
+        config.apply_env_vars().unwrap();
+        assert_eq!(config.cache.directory.as_deref(), Some("/srv/team-cache"));
+        std::env::remove_var("GITNAV_CACHE_DIR");
+    }
+
     #[test]
     fn test_env_var_badge_style() {
         // Test all badge style variants sequentially to avoid env var race conditions
@@ -798,7 +2518,7 @@ date_format = "%Y-%m-%d"
         ] {
             std::env::set_var("GITNAV_UI_BADGE_STYLE", val);
             let mut config = Config::default();
-            config.apply_env_vars();
+            config.apply_env_vars().unwrap();
             assert_eq!(
                 config.ui.badge_style, *expected,
                 "badge_style mismatch for value '{}'",
@@ -808,10 +2528,371 @@ date_format = "%Y-%m-%d"
         }
     }
 
+    #[test]
+    fn test_bulk_confirm_default_destructive() {
+        let config = Config::default();
+        assert_eq!(config.bulk.confirm, ConfirmMode::Destructive);
+        assert_eq!(config.bulk.jobs, 8);
+    }
+
+    #[test]
+    fn test_bulk_section_parses() {
+        let mut config = Config::default();
+        config.bulk.confirm = ConfirmMode::Never;
+        let toml_str = toml::to_string(&config).expect("Failed to serialize");
+        assert!(toml_str.contains("[bulk]"));
+        let parsed: Config = toml::from_str(&toml_str).expect("Failed to parse");
+        assert_eq!(parsed.bulk.confirm, ConfirmMode::Never);
+    }
+
+    #[test]
+    fn test_matcher_section_parses() {
+        let mut config = Config::default();
+        config.matcher.algorithm = MatchAlgorithm::Substring;
+        config.matcher.smart_case = false;
+        let toml_str = toml::to_string(&config).expect("Failed to serialize");
+        assert!(toml_str.contains("algorithm = \"substring\""));
+        let parsed: Config = toml::from_str(&toml_str).expect("Failed to parse");
+        assert_eq!(parsed.matcher.algorithm, MatchAlgorithm::Substring);
+        assert!(!parsed.matcher.smart_case);
+        assert!(parsed.matcher.word_boundaries);
+    }
+
+    #[test]
+    fn test_json_schema_describes_all_sections() {
+        let schema: serde_json::Value =
+            serde_json::from_str(&Config::json_schema()).expect("Schema should be valid JSON");
+        let properties = schema["properties"]
+            .as_object()
+            .expect("Schema should have top-level properties");
+        for section in ["search", "cache", "ui", "preview", "bulk", "matcher"] {
+            assert!(
+                properties.contains_key(section),
+                "missing section {}",
+                section
+            );
+        }
+        assert_eq!(schema["title"], "Config");
+    }
+
+    #[test]
+    fn test_json_schema_lists_enum_values() {
+        let schema = Config::json_schema();
+        assert!(schema.contains("\"icon\""));
+        assert!(schema.contains("\"destructive\""));
+    }
+
     #[test]
     fn test_example_toml_contains_new_fields() {
         let example = Config::example_toml();
         assert!(example.contains("show_inline_meta"));
         assert!(example.contains("badge_style"));
     }
+
+    fn vars(pairs: &[(&str, &str)]) -> Vec<(String, String)> {
+        pairs
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn test_structured_env_vars_cover_every_section() {
+        let mut config = Config::default();
+        config
+            .apply_env(vars(&[
+                ("GITNAV_SEARCH__MAX_DEPTH", "8"),
+                ("GITNAV_SEARCH__ORDER", "Path"),
+                ("GITNAV_SEARCH__PATHS", r#"["~/work", "~/oss"]"#),
+                ("GITNAV_CACHE__DIRECTORY", "/tmp/gitnav"),
+                ("GITNAV_UI__SHOW_BORDER", "off"),
+                ("GITNAV_PREVIEW__MAX_LINES", "12"),
+                ("GITNAV_BULK__CONFIRM", "never"),
+                ("GITNAV_MATCHER__ALGORITHM", "substring"),
+            ]))
+            .unwrap();
+        assert_eq!(config.search.max_depth, 8);
+        assert_eq!(config.search.order, SortOrder::Path);
+        assert_eq!(config.search.paths, vec!["~/work", "~/oss"]);
+        assert_eq!(config.cache.directory.as_deref(), Some("/tmp/gitnav"));
+        assert!(!config.ui.show_border);
+        assert_eq!(config.preview.max_lines, 12);
+        assert_eq!(config.bulk.confirm, ConfirmMode::Never);
+        assert_eq!(config.matcher.algorithm, MatchAlgorithm::Substring);
+    }
+
+    #[test]
+    fn test_structured_env_var_wins_over_legacy_name() {
+        let mut config = Config::default();
+        config
+            .apply_env(vars(&[
+                ("GITNAV_SEARCH__MAX_DEPTH", "3"),
+                ("GITNAV_MAX_DEPTH", "9"),
+            ]))
+            .unwrap();
+        assert_eq!(config.search.max_depth, 3);
+    }
+
+    #[test]
+    fn test_empty_env_value_unsets_optional_field() {
+        let mut config = Config::default();
+        config.cache.directory = Some("/somewhere".to_string());
+        config.apply_env(vars(&[("GITNAV_CACHE_DIR", "")])).unwrap();
+        assert_eq!(config.cache.directory, None);
+    }
+
+    #[test]
+    fn test_env_type_errors_are_reported() {
+        let cases = [
+            ("GITNAV_MAX_DEPTH", "deep"),
+            ("GITNAV_UI__HEIGHT_PERCENT", "300"),
+            ("GITNAV_CACHE__ENABLED", "maybe"),
+            ("GITNAV_UI_BADGE_STYLE", "emoji"),
+        ];
+        for (var, value) in cases {
+            let err = Config::default()
+                .apply_env(vars(&[(var, value)]))
+                .unwrap_err();
+            assert!(
+                format!("{:#}", err).contains(var),
+                "error for {} should name the variable: {:#}",
+                var,
+                err
+            );
+        }
+    }
+
+    #[test]
+    fn test_unknown_structured_env_var_is_an_error() {
+        let err = Config::default()
+            .apply_env(vars(&[("GITNAV_SEARCH__MAXDEPTH", "3")]))
+            .unwrap_err();
+        assert!(err.to_string().contains("search.maxdepth"));
+
+        let err = Config::default()
+            .apply_env(vars(&[("GITNAV_SEARCH__MAX_DEPTH__X", "3")]))
+            .unwrap_err();
+        assert!(err.to_string().contains("GITNAV_SEARCH__MAX_DEPTH__X"));
+    }
+
+    #[test]
+    fn test_unrelated_env_vars_are_ignored() {
+        let mut config = Config::default();
+        config
+            .apply_env(vars(&[("GITNAV_PROFILE", "work"), ("HOME", "/home/u")]))
+            .unwrap();
+        assert_eq!(config.search.max_depth, Config::default().search.max_depth);
+    }
+
+    #[test]
+    fn test_config_files_are_layered() {
+        let tmp = tempfile::tempdir().unwrap();
+        let shared = tmp.path().join("shared.toml");
+        let machine = tmp.path().join("machine.toml");
+        std::fs::write(
+            &shared,
+            "[search]\nmax_depth = 3\nignore_patterns = [\"vendor\"]\n\n[ui]\nprompt = \"> \"\n",
+        )
+        .unwrap();
+        std::fs::write(&machine, "[search]\nmax_depth = 8\n").unwrap();
+
+        let config =
+            Config::load_from_files(&[shared.clone(), machine.clone(), tmp.path().join("gone")])
+                .unwrap();
+        assert_eq!(config.search.max_depth, 8);
+        // Settings the later file leaves out come from the earlier one
+        assert_eq!(config.search.ignore_patterns, vec!["vendor".to_string()]);
+        assert_eq!(config.ui.prompt, "> ");
+        assert_eq!(
+            config.ui.height_percent,
+            Config::default().ui.height_percent
+        );
+
+        // A mistake is reported against its own file
+        std::fs::write(&machine, "[search]\nmax_depth = \"deep\"\n").unwrap();
+        let error = Config::load_from_files(&[shared, machine.clone()]).unwrap_err();
+        assert!(format!("{:#}", error).contains(&machine.display().to_string()));
+    }
+
+    #[test]
+    fn test_preview_for_applies_gitnav_toml_files_above_the_repo() {
+        let tmp = tempfile::tempdir().unwrap();
+        let mono = tmp.path().join("mono");
+        let repo = mono.join("team").join("service");
+        std::fs::create_dir_all(&repo).unwrap();
+        std::fs::write(
+            mono.join(DIR_CONFIG_FILENAME),
+            "[preview]\nshow_status = false\nrecent_commits = 1\n",
+        )
+        .unwrap();
+        std::fs::write(
+            repo.join(DIR_CONFIG_FILENAME),
+            "[preview]\nrecent_commits = 3\n",
+        )
+        .unwrap();
+
+        let config = Config::default();
+        let preview = config.preview_for(&repo).unwrap();
+        assert!(!preview.show_status);
+        // The nearest file wins
+        assert_eq!(preview.recent_commits, 3);
+        assert_eq!(preview.date_format, config.preview.date_format);
+
+        let elsewhere = config.preview_for(tmp.path()).unwrap();
+        assert!(elsewhere.show_status);
+
+        std::fs::write(
+            repo.join(DIR_CONFIG_FILENAME),
+            "[preview]\nshow_status = 2\n",
+        )
+        .unwrap();
+        let error = config.preview_for(&repo).unwrap_err();
+        assert!(format!("{:#}", error).contains(&repo.display().to_string()));
+    }
+
+    #[test]
+    fn test_profiles_override_the_file() {
+        let mut toml_str = toml::to_string(&Config::default()).unwrap();
+        toml_str.push_str(
+            r#"
+[profiles.work.search]
+ignore_patterns = ["vendor"]
+roots = [{ path = "~/work", exclude = ["archive"] }]
+
+[profiles.work.ui]
+prompt = "work > "
+
+[profiles.home.search]
+max_depth = 2
+"#,
+        );
+
+        let plain = Config::parse(&toml_str, None).unwrap();
+        assert_eq!(plain.search.max_depth, 5);
+        assert!(plain.search.roots.is_empty());
+        assert_eq!(plain.profiles.len(), 2);
+
+        let work = Config::parse(&toml_str, Some("work")).unwrap();
+        assert_eq!(work.search.ignore_patterns, vec!["vendor".to_string()]);
+        assert_eq!(work.search.roots[0].exclude, vec!["archive".to_string()]);
+        assert_eq!(work.ui.prompt, "work > ");
+        // Keys the profile leaves out keep the file's values
+        assert_eq!(work.search.max_depth, 5);
+        assert_eq!(work.ui.header, plain.ui.header);
+
+        assert_eq!(
+            Config::parse(&toml_str, Some("home"))
+                .unwrap()
+                .search
+                .max_depth,
+            2
+        );
+        // A profile with no table only scopes the cache
+        assert_eq!(
+            Config::parse(&toml_str, Some("laptop"))
+                .unwrap()
+                .search
+                .max_depth,
+            5
+        );
+
+        // Mistakes in any profile are reported, active or not
+        let bad = format!(
+            "{}\n[profiles.typo.search]\nmax_depth = \"deep\"\n",
+            toml_str
+        );
+        let err = Config::parse(&bad, None).unwrap_err();
+        assert!(format!("{:#}", err).contains("[profiles.typo]"));
+    }
+
+    #[test]
+    fn test_set_in_keeps_comments() {
+        let contents = format!(
+            "# my settings\n{}",
+            toml::to_string(&Config::default()).unwrap().replacen(
+                "max_depth = 5",
+                "max_depth = 5 # deep enough",
+                1
+            )
+        );
+
+        let updated = Config::set_in(&contents, "search.max_depth", "7").unwrap();
+        assert!(updated.starts_with("# my settings\n"));
+        assert!(updated.contains("max_depth = 7 # deep enough"));
+        let config = Config::parse(&updated, None).unwrap();
+        assert_eq!(config.get("search.max_depth").unwrap(), Some(7.into()));
+
+        let updated = Config::set_in(&updated, "cache.directory", "/tmp/gitnav").unwrap();
+        let updated = Config::set_in(&updated, "profiles.work.ui.prompt", "work > ").unwrap();
+        let config = Config::parse(&updated, Some("work")).unwrap();
+        assert_eq!(config.cache.directory.as_deref(), Some("/tmp/gitnav"));
+        assert_eq!(config.ui.prompt, "work > ");
+
+        let updated = Config::set_in(&updated, "cache.directory", "").unwrap();
+        let config = Config::parse(&updated, None).unwrap();
+        assert_eq!(config.get("cache.directory").unwrap(), None);
+
+        assert!(Config::set_in(&updated, "search.max_depth", "deep").is_err());
+        assert!(Config::set_in(&updated, "search.max_depth", "0").is_err());
+        assert!(Config::set_in(&updated, "search.depth", "7").is_err());
+        assert!(config.get("search.depth").is_err());
+    }
+
+    #[test]
+    fn test_check_reports_problems_with_lines() {
+        let valid = toml::to_string(&Config::default()).unwrap();
+        assert_eq!(Config::check(&valid).unwrap(), Vec::new());
+
+        let contents = format!(
+            "{}\n[[search.roots]]\npath = \"~/src\"\nexlude = []\n\n[profiles.work.search]\nmax_depth = 0\n\n[profiles.home.uii]\nprompt = \"> \"\n",
+            valid.replacen("max_depth = 5", "max_depth = 0", 1)
+        );
+        let base = valid.lines().count();
+        let problems = Config::check(&contents).unwrap();
+        let found: Vec<(Option<usize>, &str)> = problems
+            .iter()
+            .map(|p| (p.line, p.message.as_str()))
+            .collect();
+        let depth_line = valid.lines().position(|l| l == "max_depth = 5").unwrap() + 1;
+        assert_eq!(
+            found,
+            vec![
+                (
+                    Some(depth_line),
+                    "search.max_depth must be at least 1, got 0"
+                ),
+                (
+                    Some(base + 4),
+                    "unknown key `search.roots.exlude` (did you mean `exclude`?)"
+                ),
+                (
+                    Some(base + 9),
+                    "unknown key `profiles.home.uii` (did you mean `ui`?)"
+                ),
+            ]
+        );
+
+        // A profile's own mistakes are reported at its line
+        let contents = format!("{}\n[profiles.work.search]\nmax_depth = 0\n", valid);
+        let problems = Config::check(&contents).unwrap();
+        assert_eq!(problems.len(), 1);
+        assert_eq!(problems[0].line, Some(base + 3));
+        assert!(problems[0].message.starts_with("with profile work:"));
+
+        let problems = Config::check("[search\n").unwrap();
+        assert_eq!(problems[0].line, Some(1));
+    }
+
+    #[test]
+    fn test_every_legacy_env_var_maps_to_a_field() {
+        let schema = serde_json::to_value(schemars::schema_for!(Config)).unwrap();
+        for (var, path) in LEGACY_ENV_VARS {
+            let path: Vec<String> = path.split('.').map(String::from).collect();
+            assert!(
+                env::lookup_field(&schema, &path).is_some(),
+                "{} points at a missing field",
+                var
+            );
+        }
+    }
 }
@@ -0,0 +1,286 @@
+use anyhow::{Context, Result};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use crate::scanner::GitRepo;
+
+/// How long a client waits for the daemon before scanning on its own.
+#[cfg_attr(not(unix), allow(dead_code))]
+const CLIENT_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// The daemon's socket in a (profile-scoped) cache directory.
+pub fn socket_path(cache_dir: &Path) -> PathBuf {
+    cache_dir.join("daemon.sock")
+}
+
+/// What a daemon serves: the repositories under one set of search paths,
+/// scanned to one depth.
+///
+/// Requests are one line (`list\t<depth>\t<key>`, `ping`, or `stop`); a
+/// `list` reply is `ok` or `miss`, then for each repository its name and
+/// path, every field NUL-terminated so any path survives.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Index {
+    pub key: String,
+    pub max_depth: usize,
+}
+
+impl Index {
+    fn request(&self) -> String {
+        format!("list\t{}\t{}\n", self.max_depth, self.key)
+    }
+}
+
+#[cfg(unix)]
+pub use unix::{is_running, query, serve, stop};
+
+#[cfg(unix)]
+mod unix {
+    use super::*;
+    use std::io::{BufRead, BufReader, Read, Write};
+    use std::os::unix::ffi::OsStrExt;
+    use std::os::unix::net::{UnixListener, UnixStream};
+    use std::sync::{mpsc, RwLock};
+
+    /// Serve `index` on `socket` until a `stop` request. Every `poll`,
+    /// `due` is asked whether to rescan with `scan`.
+    ///
+    /// The first scan runs before the socket is bound, so clients never see
+    /// an empty index. Failed rescans are passed to `on_error` and keep the
+    /// previous list.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a daemon is already running on `socket`, the
+    /// socket cannot be bound, or the first scan fails
+    pub fn serve<S, D, E>(
+        socket: &Path,
+        index: &Index,
+        poll: Duration,
+        scan: S,
+        mut due: D,
+        on_error: E,
+    ) -> Result<()>
+    where
+        S: Fn() -> Result<Vec<GitRepo>> + Sync,
+        D: FnMut() -> bool + Send,
+        E: Fn(anyhow::Error) + Sync,
+    {
+        if is_running(socket) {
+            anyhow::bail!("A daemon is already running on {}", socket.display());
+        }
+        let repos = RwLock::new(scan()?);
+
+        // A socket left behind by a daemon that was killed
+        let _ = std::fs::remove_file(socket);
+        if let Some(parent) = socket.parent() {
+            std::fs::create_dir_all(parent).with_context(|| {
+                format!("Failed to create cache directory: {}", parent.display())
+            })?;
+        }
+        let listener = UnixListener::bind(socket)
+            .with_context(|| format!("Failed to listen on {}", socket.display()))?;
+
+        let (stop_tx, stop_rx) = mpsc::channel::<()>();
+        let result = std::thread::scope(|scope| {
+            let (repos, scan, on_error) = (&repos, &scan, &on_error);
+            scope.spawn(move || {
+                // Wakes up every poll, and exits once the sender is dropped
+                while let Err(mpsc::RecvTimeoutError::Timeout) = stop_rx.recv_timeout(poll) {
+                    if !due() {
+                        continue;
+                    }
+                    match scan() {
+                        Ok(fresh) => *repos.write().unwrap_or_else(|e| e.into_inner()) = fresh,
+                        Err(e) => on_error(e),
+                    }
+                }
+            });
+
+            for stream in listener.incoming() {
+                let Ok(stream) = stream else { continue };
+                let repos = repos.read().unwrap_or_else(|e| e.into_inner());
+                match answer(stream, index, &repos) {
+                    Ok(true) => break,
+                    Ok(false) => {}
+                    Err(e) => on_error(e),
+                }
+            }
+            drop(stop_tx);
+            Ok(())
+        });
+        let _ = std::fs::remove_file(socket);
+        result
+    }
+
+    /// Reply to one client. Returns whether it asked the daemon to stop.
+    fn answer(stream: UnixStream, index: &Index, repos: &[GitRepo]) -> Result<bool> {
+        stream.set_read_timeout(Some(CLIENT_TIMEOUT))?;
+        let mut request = String::new();
+        BufReader::new(&stream)
+            .take(64 * 1024)
+            .read_line(&mut request)
+            .context("Failed to read daemon request")?;
+        let request = request.trim_end_matches('\n');
+
+        let mut out = std::io::BufWriter::new(&stream);
+        if request == "stop" {
+            out.write_all(b"ok\0")?;
+            return Ok(true);
+        }
+        if request == "ping" {
+            out.write_all(b"ok\0")?;
+        } else if request == index.request().trim_end_matches('\n') {
+            out.write_all(b"ok\0")?;
+            for repo in repos {
+                out.write_all(repo.name.as_bytes())?;
+                out.write_all(b"\0")?;
+                out.write_all(repo.path.as_os_str().as_bytes())?;
+                out.write_all(b"\0")?;
+            }
+        } else {
+            out.write_all(b"miss\0")?;
+        }
+        out.flush()?;
+        Ok(false)
+    }
+
+    /// Send one request and read the whole reply, split at NULs. `None` when
+    /// no daemon answers.
+    fn send(socket: &Path, request: &str) -> Option<Vec<Vec<u8>>> {
+        let mut stream = UnixStream::connect(socket).ok()?;
+        stream.set_read_timeout(Some(CLIENT_TIMEOUT)).ok()?;
+        stream.set_write_timeout(Some(CLIENT_TIMEOUT)).ok()?;
+        stream.write_all(request.as_bytes()).ok()?;
+        let mut reply = Vec::new();
+        stream.read_to_end(&mut reply).ok()?;
+        let mut fields: Vec<Vec<u8>> = reply.split(|&b| b == 0).map(<[u8]>::to_vec).collect();
+        // The reply ends with a NUL, leaving an empty last field
+        if fields.pop().is_some_and(|last| !last.is_empty()) {
+            return None;
+        }
+        Some(fields)
+    }
+
+    /// Ask the daemon on `socket` for `index`. `None` when no daemon is
+    /// running, it serves other search paths, or the reply is malformed.
+    pub fn query(socket: &Path, index: &Index) -> Option<Vec<GitRepo>> {
+        let fields = send(socket, &index.request())?;
+        let (status, records) = fields.split_first()?;
+        if status != b"ok" || records.len() % 2 != 0 {
+            return None;
+        }
+        Some(
+            records
+                .chunks(2)
+                .map(|record| GitRepo {
+                    name: String::from_utf8_lossy(&record[0]).into_owned(),
+                    path: PathBuf::from(std::ffi::OsStr::from_bytes(&record[1])),
+                })
+                .collect(),
+        )
+    }
+
+    /// Whether a daemon answers on `socket`.
+    pub fn is_running(socket: &Path) -> bool {
+        send(socket, "ping\n").is_some_and(|fields| fields == [b"ok".to_vec()])
+    }
+
+    /// Ask the daemon on `socket` to exit. Returns whether one was running.
+    pub fn stop(socket: &Path) -> bool {
+        send(socket, "stop\n").is_some()
+    }
+}
+
+/// Without unix sockets no daemon can run; clients always scan themselves.
+#[cfg(not(unix))]
+pub fn serve<S, D, E>(
+    _socket: &Path,
+    _index: &Index,
+    _poll: Duration,
+    _scan: S,
+    _due: D,
+    _on_error: E,
+) -> Result<()>
+where
+    S: Fn() -> Result<Vec<GitRepo>> + Sync,
+    D: FnMut() -> bool + Send,
+    E: Fn(anyhow::Error) + Sync,
+{
+    anyhow::bail!("gitnav daemon is only available on macOS and Linux")
+}
+
+#[cfg(not(unix))]
+pub fn query(_socket: &Path, _index: &Index) -> Option<Vec<GitRepo>> {
+    None
+}
+
+#[cfg(not(unix))]
+pub fn is_running(_socket: &Path) -> bool {
+    false
+}
+
+#[cfg(not(unix))]
+pub fn stop(_socket: &Path) -> bool {
+    false
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+
+    #[test]
+    fn test_serve_query_rescan_and_stop() {
+        let tmp = tempfile::tempdir().unwrap();
+        let socket = socket_path(tmp.path());
+        let index = Index {
+            key: "/src".to_string(),
+            max_depth: 5,
+        };
+        let api = GitRepo::new(PathBuf::from("/src/api"));
+        let odd = GitRepo::new(PathBuf::from("/src/new\nline"));
+        let scans = AtomicUsize::new(0);
+        let rescan = AtomicBool::new(false);
+
+        assert!(!is_running(&socket));
+        assert_eq!(query(&socket, &index), None);
+        std::thread::scope(|scope| {
+            let server = scope.spawn(|| {
+                serve(
+                    &socket,
+                    &index,
+                    Duration::from_millis(10),
+                    || match scans.fetch_add(1, Ordering::Relaxed) {
+                        0 => Ok(vec![api.clone()]),
+                        _ => Ok(vec![api.clone(), odd.clone()]),
+                    },
+                    || rescan.swap(false, Ordering::Relaxed),
+                    |e| panic!("{:#}", e),
+                )
+            });
+            while !is_running(&socket) {
+                std::thread::sleep(Duration::from_millis(10));
+            }
+
+            assert_eq!(query(&socket, &index), Some(vec![api.clone()]));
+            let other = Index {
+                max_depth: 2,
+                ..index.clone()
+            };
+            assert_eq!(query(&socket, &other), None);
+
+            rescan.store(true, Ordering::Relaxed);
+            while query(&socket, &index).is_some_and(|repos| repos.len() == 1) {
+                std::thread::sleep(Duration::from_millis(10));
+            }
+            assert_eq!(query(&socket, &index), Some(vec![api.clone(), odd.clone()]));
+
+            assert!(stop(&socket));
+            server.join().unwrap().unwrap();
+        });
+        assert_eq!(scans.load(Ordering::Relaxed), 2);
+        assert!(!socket.exists());
+        assert!(!is_running(&socket));
+    }
+}
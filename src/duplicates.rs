@@ -0,0 +1,255 @@
+use git2::{Oid, Repository};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use crate::parallel;
+use crate::remote;
+use crate::report::{Cell, Report};
+use crate::scanner::{self, GitRepo};
+use crate::stats;
+
+/// A clone in a [`Group`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct RepoClone {
+    pub name: String,
+    pub path: PathBuf,
+    /// Bytes used by the working tree, `.git` included
+    pub size: u64,
+}
+
+/// Repositories that are clones of the same project.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Group {
+    /// Normalized remote, e.g. "github.com/acme/api", of the first clone that
+    /// has one
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub remote: Option<String>,
+    /// Root commit of the first clone that has one; absent when every clone is empty
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub root_commit: Option<String>,
+    /// Sorted by path
+    pub clones: Vec<RepoClone>,
+}
+
+impl Group {
+    /// Bytes used by all clones together.
+    pub fn size(&self) -> u64 {
+        self.clones.iter().map(|c| c.size).sum()
+    }
+
+    /// The remote, or else the abbreviated root commit, for the text output.
+    pub fn title(&self) -> String {
+        match (&self.remote, &self.root_commit) {
+            (Some(remote), _) => remote.clone(),
+            (None, Some(root)) => format!("root commit {}", &root[..root.len().min(12)]),
+            (None, None) => String::new(),
+        }
+    }
+}
+
+/// How one repository is identified.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Identity {
+    remote: Option<String>,
+    root_commit: Option<String>,
+}
+
+/// Find repositories among `repos` that are clones of the same project: they
+/// share a normalized remote URL or the root commit of their history.
+///
+/// Linked worktrees are not separate clones and are left out. Groups are
+/// ordered by combined size, largest first, since those are the ones worth
+/// cleaning up.
+pub fn find(repos: &[GitRepo]) -> Vec<Group> {
+    let identities = parallel::map_queued(repos, parallel::default_workers(), |repo| {
+        identify(&repo.path)
+    });
+    let clones: Vec<(&GitRepo, Identity)> = repos
+        .iter()
+        .zip(identities)
+        .filter_map(|(repo, identity)| Some((repo, identity?)))
+        .collect();
+
+    let mut groups: Vec<Group> = group_indices(&clones.iter().map(|(_, i)| i).collect::<Vec<_>>())
+        .into_iter()
+        .map(|members| {
+            let mut members: Vec<&(&GitRepo, Identity)> =
+                members.into_iter().map(|m| &clones[m]).collect();
+            members.sort_by(|a, b| a.0.path.cmp(&b.0.path));
+            Group {
+                remote: members.iter().find_map(|(_, i)| i.remote.clone()),
+                root_commit: members.iter().find_map(|(_, i)| i.root_commit.clone()),
+                clones: members
+                    .iter()
+                    .map(|(repo, _)| RepoClone {
+                        name: repo.name.clone(),
+                        path: repo.path.clone(),
+                        size: 0,
+                    })
+                    .collect(),
+            }
+        })
+        .collect();
+
+    // Only the duplicates are measured; walking every repository would be slow
+    for group in &mut groups {
+        for clone in &mut group.clones {
+            clone.size = stats::dir_size(&clone.path);
+        }
+    }
+    groups.sort_by(|a, b| b.size().cmp(&a.size()).then(a.title().cmp(&b.title())));
+    groups
+}
+
+/// The remote and root commit of the repository at `path`, or `None` for a
+/// linked worktree or a path that cannot be opened.
+fn identify(path: &Path) -> Option<Identity> {
+    let repo = Repository::open(path).ok()?;
+    if repo.is_worktree() {
+        return None;
+    }
+    Some(Identity {
+        remote: remote::repo_remote(path).map(|r| r.to_string()),
+        root_commit: root_commit(&repo).map(|oid| oid.to_string()),
+    })
+}
+
+/// The root commit reachable from HEAD. With several (merged unrelated
+/// histories), the smallest id is taken so every clone picks the same one.
+fn root_commit(repo: &Repository) -> Option<Oid> {
+    let mut walk = repo.revwalk().ok()?;
+    walk.push_head().ok()?;
+    walk.flatten()
+        .filter(|oid| {
+            repo.find_commit(*oid)
+                .is_ok_and(|commit| commit.parent_count() == 0)
+        })
+        .min()
+}
+
+/// Indices of identities that share a remote or a root commit, directly or
+/// through another member, in groups of two or more.
+fn group_indices(identities: &[&Identity]) -> Vec<Vec<usize>> {
+    // Union-find over indices, joined through the first index seen per key
+    let mut parent: Vec<usize> = (0..identities.len()).collect();
+    fn find(parent: &mut [usize], i: usize) -> usize {
+        let mut root = i;
+        while parent[root] != root {
+            root = parent[root];
+        }
+        parent[i] = root;
+        root
+    }
+
+    let mut first: HashMap<String, usize> = HashMap::new();
+    for (i, identity) in identities.iter().enumerate() {
+        let keys = [
+            identity.remote.as_ref().map(|r| format!("remote {}", r)),
+            identity.root_commit.as_ref().map(|c| format!("root {}", c)),
+        ];
+        for key in keys.into_iter().flatten() {
+            let other = *first.entry(key).or_insert(i);
+            let (a, b) = (find(&mut parent, i), find(&mut parent, other));
+            parent[a.max(b)] = a.min(b);
+        }
+    }
+
+    let mut groups: HashMap<usize, Vec<usize>> = HashMap::new();
+    for i in 0..identities.len() {
+        let root = find(&mut parent, i);
+        groups.entry(root).or_default().push(i);
+    }
+    let mut groups: Vec<Vec<usize>> = groups.into_values().filter(|g| g.len() > 1).collect();
+    groups.sort();
+    groups
+}
+
+/// A group as a table of its clones, titled with what they share.
+pub fn build_report(group: &Group) -> Report {
+    let home = dirs::home_dir();
+    Report {
+        title: format!("{} ({})", group.title(), stats::format_size(group.size())),
+        notes: Vec::new(),
+        headers: ["Repository", "Size", "Path"]
+            .iter()
+            .map(|h| h.to_string())
+            .collect(),
+        rows: group
+            .clones
+            .iter()
+            .map(|c| {
+                vec![
+                    Cell::new(c.name.clone()),
+                    Cell::new(stats::format_size(c.size)),
+                    Cell::new(scanner::shorten_home(&c.path, home.as_deref())),
+                ]
+            })
+            .collect(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::commit;
+
+    fn identity(remote: Option<&str>, root: Option<&str>) -> Identity {
+        Identity {
+            remote: remote.map(str::to_string),
+            root_commit: root.map(str::to_string),
+        }
+    }
+
+    #[test]
+    fn test_group_indices() {
+        let identities = [
+            identity(Some("github.com/acme/api"), Some("a1")),
+            identity(Some("github.com/acme/web"), Some("b2")),
+            identity(None, Some("a1")),
+            identity(Some("github.com/me/api"), Some("a1")),
+            identity(None, None),
+            identity(Some("github.com/acme/web"), None),
+            identity(Some("github.com/acme/cli"), Some("c3")),
+        ];
+        let refs: Vec<&Identity> = identities.iter().collect();
+        assert_eq!(group_indices(&refs), vec![vec![0, 2, 3], vec![1, 5]]);
+    }
+
+    #[test]
+    fn test_find_groups_clones_but_not_worktrees() {
+        let tmp = tempfile::tempdir().unwrap();
+        let api = tmp.path().join("api");
+        let repo = Repository::init(&api).unwrap();
+        commit(&repo, "first", None);
+        commit(&repo, "second", None);
+        repo.remote("origin", "git@github.com:acme/api.git")
+            .unwrap();
+        repo.worktree("fix", &tmp.path().join("api-fix"), None)
+            .unwrap();
+
+        // A second clone by history, and one that only shares the remote
+        let copy = tmp.path().join("old/api");
+        Repository::clone(api.to_str().unwrap(), &copy).unwrap();
+        let empty = tmp.path().join("empty");
+        Repository::init(&empty)
+            .unwrap()
+            .remote("origin", "https://github.com/acme/api")
+            .unwrap();
+        let other = tmp.path().join("other");
+        commit(&Repository::init(&other).unwrap(), "unrelated", None);
+
+        let repos: Vec<GitRepo> = [&api, &tmp.path().join("api-fix"), &copy, &empty, &other]
+            .iter()
+            .map(|p| GitRepo::new(p.to_path_buf()))
+            .collect();
+        let groups = find(&repos);
+        assert_eq!(groups.len(), 1);
+        let paths: Vec<&Path> = groups[0].clones.iter().map(|c| c.path.as_path()).collect();
+        assert_eq!(paths, vec![api.as_path(), empty.as_path(), copy.as_path()]);
+        assert_eq!(groups[0].remote.as_deref(), Some("github.com/acme/api"));
+        assert_eq!(groups[0].title(), "github.com/acme/api");
+        assert!(groups[0].root_commit.is_some());
+        assert!(groups[0].clones.iter().all(|c| c.size > 0));
+    }
+}
@@ -0,0 +1,346 @@
+use anyhow::{Context, Result};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+
+use crate::scanner::GitRepo;
+use crate::tags::TagStore;
+
+/// Tag given to repositories imported from editors' recent projects. Tagged
+/// repositories join the list even outside the search paths.
+pub const TAG: &str = "editor-recent";
+
+/// VS Code builds, by the name of their directory in the config dir.
+const VSCODE_PRODUCTS: &[&str] = &["Code", "Code - Insiders", "VSCodium"];
+
+/// Key of the recently opened list in VS Code's `state.vscdb`.
+const VSCODE_RECENT_KEY: &str = "history.recentlyOpenedPathsList";
+
+/// One editor's list of recent projects.
+#[derive(Debug)]
+pub struct Source {
+    /// The editor's directory name, e.g. "Code" or "IntelliJIdea2024.1"
+    pub editor: String,
+    pub file: PathBuf,
+    /// Directories the editor opened, or why the file could not be read
+    pub dirs: Result<Vec<PathBuf>>,
+}
+
+/// The recent projects of every VS Code build and JetBrains IDE whose
+/// settings are under `config_dir` (see [`dirs::config_dir`]).
+pub fn sources(config_dir: &Path, home: Option<&Path>) -> Vec<Source> {
+    let mut sources = Vec::new();
+    for product in VSCODE_PRODUCTS {
+        let storage = config_dir.join(product).join("User").join("globalStorage");
+        let state = storage.join("state.vscdb");
+        let legacy = storage.join("storage.json");
+        if state.is_file() {
+            sources.push(Source {
+                editor: product.to_string(),
+                dirs: read_vscode_state(&state),
+                file: state,
+            });
+        } else if legacy.is_file() {
+            sources.push(Source {
+                editor: product.to_string(),
+                dirs: read_vscode_legacy(&legacy),
+                file: legacy,
+            });
+        }
+    }
+
+    // JetBrains/IntelliJIdea2024.1, Google/AndroidStudio2023.3, ...
+    for vendor in ["JetBrains", "Google"] {
+        let Ok(products) = fs::read_dir(config_dir.join(vendor)) else {
+            continue;
+        };
+        let mut products: Vec<PathBuf> = products.flatten().map(|e| e.path()).collect();
+        products.sort();
+        for product in products {
+            for name in ["recentProjects.xml", "recentSolutions.xml"] {
+                let file = product.join("options").join(name);
+                if file.is_file() {
+                    sources.push(Source {
+                        editor: product
+                            .file_name()
+                            .map(|n| n.to_string_lossy().to_string())
+                            .unwrap_or_default(),
+                        dirs: fs::read_to_string(&file)
+                            .with_context(|| format!("Failed to read {}", file.display()))
+                            .map(|xml| parse_jetbrains(&xml, home)),
+                        file,
+                    });
+                }
+            }
+        }
+    }
+    sources
+}
+
+/// Read the recent list from VS Code's SQLite state with the `sqlite3` tool.
+fn read_vscode_state(db: &Path) -> Result<Vec<PathBuf>> {
+    let output = Command::new("sqlite3")
+        .arg("-readonly")
+        .arg(db)
+        .arg(format!(
+            "SELECT value FROM ItemTable WHERE key = '{}'",
+            VSCODE_RECENT_KEY
+        ))
+        .stdin(Stdio::null())
+        .output()
+        .context("Failed to run sqlite3, which reading VS Code's recent projects needs")?;
+    if !output.status.success() {
+        anyhow::bail!(
+            "sqlite3 could not read {}: {}",
+            db.display(),
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    let value = String::from_utf8_lossy(&output.stdout);
+    if value.trim().is_empty() {
+        return Ok(Vec::new());
+    }
+    let json: serde_json::Value =
+        serde_json::from_str(&value).context("VS Code's recent list is not valid JSON")?;
+    Ok(parse_vscode_recent(&json))
+}
+
+/// Read the recent list kept in `storage.json` by VS Code before 1.50.
+fn read_vscode_legacy(file: &Path) -> Result<Vec<PathBuf>> {
+    let contents =
+        fs::read_to_string(file).with_context(|| format!("Failed to read {}", file.display()))?;
+    let json: serde_json::Value = serde_json::from_str(&contents)
+        .with_context(|| format!("Invalid JSON in {}", file.display()))?;
+    Ok(parse_vscode_recent(&json["openedPathsList"]))
+}
+
+/// Folders and workspace files in a VS Code recent list: `entries` in current
+/// versions, `workspaces3` in older ones. Recent files are left out.
+fn parse_vscode_recent(json: &serde_json::Value) -> Vec<PathBuf> {
+    let entries = json["entries"].as_array().into_iter().flatten();
+    let workspaces = json["workspaces3"].as_array().into_iter().flatten();
+    entries
+        .chain(workspaces)
+        .filter_map(|entry| {
+            let uri = entry
+                .as_str()
+                .or_else(|| entry["folderUri"].as_str())
+                .or_else(|| entry["workspace"]["configPath"].as_str())
+                .or_else(|| entry["configURIPath"].as_str())?;
+            let path = file_uri_path(uri)?;
+            // A `.code-workspace` file stands for the folder it is in
+            Some(if path.extension().is_some_and(|e| e == "code-workspace") {
+                path.parent()?.to_path_buf()
+            } else {
+                path
+            })
+        })
+        .collect()
+}
+
+/// The local path of a `file://` URI; `None` for remote ones (SSH, WSL,
+/// containers).
+fn file_uri_path(uri: &str) -> Option<PathBuf> {
+    let path = percent_decode(uri.strip_prefix("file://")?);
+    // file:///c:/Users/me is C:\Users\me on Windows
+    let bytes = path.as_bytes();
+    if bytes.len() > 2 && bytes[0] == b'/' && bytes[1].is_ascii_alphabetic() && bytes[2] == b':' {
+        return Some(PathBuf::from(&path[1..]));
+    }
+    path.starts_with('/').then(|| PathBuf::from(path))
+}
+
+fn percent_decode(text: &str) -> String {
+    let bytes = text.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let hex = bytes
+            .get(i + 1..i + 3)
+            .and_then(|h| std::str::from_utf8(h).ok())
+            .and_then(|h| u8::from_str_radix(h, 16).ok());
+        match (bytes[i], hex) {
+            (b'%', Some(byte)) => {
+                decoded.push(byte);
+                i += 3;
+            }
+            (byte, _) => {
+                decoded.push(byte);
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&decoded).to_string()
+}
+
+/// Project paths in a JetBrains `recentProjects.xml`: the keys of its
+/// `<entry key="...">` elements and, in older versions, the values of its
+/// `<option value="...">` list. `$USER_HOME$` is expanded; anything that is
+/// not an absolute path afterwards is skipped.
+fn parse_jetbrains(xml: &str, home: Option<&Path>) -> Vec<PathBuf> {
+    let mut paths = Vec::new();
+    for (element, attribute) in [("<entry ", "key=\""), ("<option ", "value=\"")] {
+        for (start, _) in xml.match_indices(element) {
+            let tag = &xml[start..];
+            let tag = &tag[..tag.find('>').unwrap_or(tag.len())];
+            let Some(value_start) = tag.find(attribute).map(|i| i + attribute.len()) else {
+                continue;
+            };
+            let value = &tag[value_start..];
+            let value = unescape_xml(&value[..value.find('"').unwrap_or(value.len())]);
+            let value = match (value.strip_prefix("$USER_HOME$"), home) {
+                (Some(rest), Some(home)) => format!("{}{}", home.display(), rest),
+                _ => value,
+            };
+            let path = PathBuf::from(value);
+            if path.is_absolute() {
+                paths.push(path);
+            }
+        }
+    }
+    paths
+}
+
+fn unescape_xml(text: &str) -> String {
+    text.replace("&quot;", "\"")
+        .replace("&apos;", "'")
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&amp;", "&")
+}
+
+/// The repositories `dirs` belong to, each listed once, sorted by path.
+///
+/// A directory inside a repository counts for that repository, but the
+/// search for one stops below `home`, so a dotfiles repository in the home
+/// directory does not claim every project without one.
+pub fn repo_roots(dirs: &[PathBuf], home: Option<&Path>) -> Vec<PathBuf> {
+    let ceiling: Vec<&Path> = home.into_iter().collect();
+    let mut roots: Vec<PathBuf> = dirs
+        .iter()
+        .map(|dir| {
+            if dir.is_file() {
+                dir.parent().unwrap_or(dir).to_path_buf()
+            } else {
+                dir.clone()
+            }
+        })
+        .filter(|dir| dir.is_dir())
+        .filter_map(|dir| {
+            let repo =
+                git2::Repository::open_ext(&dir, git2::RepositoryOpenFlags::empty(), &ceiling)
+                    .ok()?;
+            let root = repo.workdir()?.to_path_buf();
+            Some(root.canonicalize().unwrap_or(root))
+        })
+        .collect();
+    roots.sort();
+    roots.dedup();
+    roots
+}
+
+/// The imported repositories that still exist, to add to the list.
+pub fn imported(store: &TagStore) -> Vec<GitRepo> {
+    store
+        .entries()
+        .filter(|(path, tags)| tags.contains(TAG) && path.join(".git").exists())
+        .map(|(path, _)| GitRepo::new(path.to_path_buf()))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_vscode_recent() {
+        let json = serde_json::json!({
+            "entries": [
+                {"folderUri": "file:///home/me/src/my%20api"},
+                {"workspace": {"id": "1", "configPath": "file:///home/me/work/app.code-workspace"}},
+                {"fileUri": "file:///home/me/notes.md"},
+                {"folderUri": "vscode-remote://ssh-remote%2Bbox/srv/api"},
+                {"folderUri": "file:///c%3A/Users/me/web"}
+            ]
+        });
+        assert_eq!(
+            parse_vscode_recent(&json),
+            vec![
+                PathBuf::from("/home/me/src/my api"),
+                PathBuf::from("/home/me/work"),
+                PathBuf::from("c:/Users/me/web"),
+            ]
+        );
+
+        let legacy = serde_json::json!({
+            "workspaces3": ["file:///home/me/old", {"id": "2", "configURIPath": "file:///home/me/w/x.code-workspace"}]
+        });
+        assert_eq!(
+            parse_vscode_recent(&legacy),
+            vec![PathBuf::from("/home/me/old"), PathBuf::from("/home/me/w")]
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_parse_jetbrains() {
+        let xml = r#"<application>
+  <component name="RecentProjectsManager">
+    <option name="additionalInfo">
+      <map>
+        <entry key="$USER_HOME$/src/api">
+          <value><RecentProjectMetaInfo frameTitle="api" /></value>
+        </entry>
+        <entry key="/opt/work/R&amp;D">
+          <value><RecentProjectMetaInfo /></value>
+        </entry>
+      </map>
+    </option>
+    <option name="recentPaths">
+      <list>
+        <option value="$USER_HOME$/old" />
+      </list>
+    </option>
+  </component>
+</application>"#;
+        assert_eq!(
+            parse_jetbrains(xml, Some(Path::new("/home/me"))),
+            vec![
+                PathBuf::from("/home/me/src/api"),
+                PathBuf::from("/opt/work/R&D"),
+                PathBuf::from("/home/me/old"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_repo_roots_and_sources() {
+        let tmp = tempfile::tempdir().unwrap();
+        let home = tmp.path().canonicalize().unwrap();
+        // A dotfiles repository in the home directory must not match `plain`
+        git2::Repository::init(&home).unwrap();
+        let api = home.join("src/api");
+        git2::Repository::init(&api).unwrap();
+        std::fs::create_dir_all(api.join("web")).unwrap();
+        let plain = home.join("src/plain");
+        std::fs::create_dir_all(&plain).unwrap();
+
+        let config = home.join(".config");
+        let options = config.join("JetBrains/PyCharm2024.1/options");
+        std::fs::create_dir_all(&options).unwrap();
+        std::fs::write(
+            options.join("recentProjects.xml"),
+            r#"<entry key="$USER_HOME$/src/api/web"><entry key="$USER_HOME$/src/plain">"#,
+        )
+        .unwrap();
+
+        let found = sources(&config, Some(&home));
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].editor, "PyCharm2024.1");
+        let dirs = found[0].dirs.as_ref().unwrap();
+        assert_eq!(dirs, &vec![api.join("web"), plain.clone()]);
+        let mut dirs = dirs.clone();
+        dirs.push(api.clone());
+        assert_eq!(repo_roots(&dirs, Some(&home)), vec![api]);
+    }
+}
@@ -0,0 +1,184 @@
+use anyhow::{Context, Result};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::scanner::GitRepo;
+
+/// Persistent list of repositories hidden from gitnav results.
+///
+/// Stored as one absolute path per line in the gitnav data directory, so it
+/// survives `gitnav clear-cache`. Excluding a repository never touches the
+/// repository itself on disk.
+#[derive(Debug)]
+pub struct ExcludeList {
+    file: PathBuf,
+    entries: Vec<PathBuf>,
+}
+
+impl ExcludeList {
+    /// Get the default exclude list file path (`<data_dir>/gitnav/excluded`).
+    pub fn default_path() -> Result<PathBuf> {
+        dirs::data_dir()
+            .map(|p| p.join("gitnav").join("excluded"))
+            .ok_or_else(|| anyhow::anyhow!("Could not determine data directory"))
+    }
+
+    /// Load the exclude list from the default location.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the data directory cannot be determined or the file cannot be read
+    pub fn load_default() -> Result<Self> {
+        Self::load(Self::default_path()?)
+    }
+
+    /// Load the exclude list from a specific file. A missing file yields an empty list.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file exists but cannot be read
+    pub fn load(file: PathBuf) -> Result<Self> {
+        let entries = if file.exists() {
+            fs::read_to_string(&file)
+                .with_context(|| format!("Failed to read exclude list: {}", file.display()))?
+                .lines()
+                .map(str::trim)
+                .filter(|line| !line.is_empty())
+                .map(PathBuf::from)
+                .collect()
+        } else {
+            Vec::new()
+        };
+
+        Ok(Self { file, entries })
+    }
+
+    /// Excluded repository paths, in insertion order.
+    pub fn entries(&self) -> &[PathBuf] {
+        &self.entries
+    }
+
+    /// Add a path to the list. Returns `false` if it was already excluded.
+    pub fn add(&mut self, path: &Path) -> bool {
+        let path = normalize(path);
+        if self.entries.contains(&path) {
+            return false;
+        }
+        self.entries.push(path);
+        true
+    }
+
+    /// Remove a path from the list. Returns `false` if it was not excluded.
+    pub fn remove(&mut self, path: &Path) -> bool {
+        let path = normalize(path);
+        let before = self.entries.len();
+        self.entries.retain(|p| *p != path);
+        self.entries.len() != before
+    }
+
+    /// Check whether a repository path is excluded.
+    pub fn contains(&self, path: &Path) -> bool {
+        if self.entries.is_empty() {
+            return false;
+        }
+        self.entries.iter().any(|p| p == path) || self.entries.contains(&normalize(path))
+    }
+
+    /// Drop excluded repositories from a result list.
+    pub fn filter(&self, repos: Vec<GitRepo>) -> Vec<GitRepo> {
+        if self.entries.is_empty() {
+            return repos;
+        }
+        repos
+            .into_iter()
+            .filter(|repo| !self.contains(&repo.path))
+            .collect()
+    }
+
+    /// Write the list back to disk, creating the parent directory if needed.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file or its directory cannot be written
+    pub fn save(&self) -> Result<()> {
+        if let Some(parent) = self.file.parent() {
+            fs::create_dir_all(parent).with_context(|| {
+                format!("Failed to create data directory: {}", parent.display())
+            })?;
+        }
+
+        let mut contents = self
+            .entries
+            .iter()
+            .map(|p| p.display().to_string())
+            .collect::<Vec<_>>()
+            .join("\n");
+        if !contents.is_empty() {
+            contents.push('\n');
+        }
+
+        fs::write(&self.file, contents)
+            .with_context(|| format!("Failed to write exclude list: {}", self.file.display()))
+    }
+}
+
+/// Canonicalize a path when it exists so `.` and symlinked spellings match scan results.
+fn normalize(path: &Path) -> PathBuf {
+    path.canonicalize().unwrap_or_else(|_| path.to_path_buf())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_load_missing_file_is_empty() {
+        let dir = tempfile::tempdir().unwrap();
+        let list = ExcludeList::load(dir.path().join("excluded")).unwrap();
+        assert!(list.entries().is_empty());
+    }
+
+    #[test]
+    fn test_add_remove_roundtrip() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("state").join("excluded");
+        let repo = PathBuf::from("/nonexistent/vendor/fixture");
+
+        let mut list = ExcludeList::load(file.clone()).unwrap();
+        assert!(list.add(&repo));
+        assert!(!list.add(&repo), "duplicate add should be a no-op");
+        list.save().unwrap();
+
+        let mut reloaded = ExcludeList::load(file).unwrap();
+        assert!(reloaded.contains(&repo));
+        assert!(reloaded.remove(&repo));
+        assert!(!reloaded.remove(&repo));
+        assert!(!reloaded.contains(&repo));
+    }
+
+    #[test]
+    fn test_filter_drops_excluded_repos() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut list = ExcludeList::load(dir.path().join("excluded")).unwrap();
+        list.add(Path::new("/path/to/fixture"));
+
+        let repos = vec![
+            GitRepo::new(PathBuf::from("/path/to/fixture")),
+            GitRepo::new(PathBuf::from("/path/to/real")),
+        ];
+        let filtered = list.filter(repos);
+        assert_eq!(filtered.len(), 1);
+        assert_eq!(filtered[0].name, "real");
+    }
+
+    #[test]
+    fn test_contains_matches_canonical_spelling() {
+        let dir = tempfile::tempdir().unwrap();
+        let repo = dir.path().join("repo");
+        std::fs::create_dir_all(&repo).unwrap();
+
+        let mut list = ExcludeList::load(dir.path().join("excluded")).unwrap();
+        list.add(&repo.join("."));
+        assert!(list.contains(&repo));
+    }
+}
@@ -0,0 +1,229 @@
+use std::io::{BufRead, BufReader, Read};
+use std::process::{Command, Stdio};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread;
+
+use crate::parallel;
+use crate::scanner::GitRepo;
+
+/// Which of the command's streams a line came from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Stream {
+    Stdout,
+    Stderr,
+}
+
+/// How the command ended in one repository.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Outcome {
+    Success,
+    /// Non-zero exit, killed by a signal, or could not be started
+    Failed(String),
+    /// Not started because an earlier repository failed (`--fail-fast`)
+    Skipped,
+}
+
+/// The outcome of the command in one repository.
+#[derive(Debug, Clone)]
+pub struct ExecResult {
+    pub repo: GitRepo,
+    pub outcome: Outcome,
+}
+
+/// Run `command` in one repository, passing each output line to `on_line`.
+///
+/// The command is run directly, not through a shell (use `sh -c` for pipes),
+/// with the repository as working directory, stdin closed, and
+/// `GITNAV_REPO_PATH` / `GITNAV_REPO_NAME` set.
+pub fn run<F>(repo: &GitRepo, command: &[String], on_line: &F) -> Outcome
+where
+    F: Fn(&GitRepo, Stream, &str) + Sync,
+{
+    let Some((program, args)) = command.split_first() else {
+        return Outcome::Failed("no command given".to_string());
+    };
+    let spawned = Command::new(program)
+        .args(args)
+        .current_dir(&repo.path)
+        .env("GITNAV_REPO_PATH", &repo.path)
+        .env("GITNAV_REPO_NAME", &repo.name)
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn();
+    let mut child = match spawned {
+        Ok(child) => child,
+        Err(e) => return Outcome::Failed(format!("failed to start {}: {}", program, e)),
+    };
+
+    let stdout = child.stdout.take();
+    let stderr = child.stderr.take();
+    thread::scope(|scope| {
+        if let Some(stderr) = stderr {
+            scope.spawn(move || forward_lines(stderr, repo, Stream::Stderr, on_line));
+        }
+        if let Some(stdout) = stdout {
+            forward_lines(stdout, repo, Stream::Stdout, on_line);
+        }
+    });
+
+    match child.wait() {
+        Ok(status) if status.success() => Outcome::Success,
+        Ok(status) => Outcome::Failed(match status.code() {
+            Some(code) => format!("exit {}", code),
+            None => status.to_string(),
+        }),
+        Err(e) => Outcome::Failed(e.to_string()),
+    }
+}
+
+fn forward_lines<R, F>(reader: R, repo: &GitRepo, stream: Stream, on_line: &F)
+where
+    R: Read,
+    F: Fn(&GitRepo, Stream, &str),
+{
+    for line in BufReader::new(reader).split(b'\n').map_while(Result::ok) {
+        let line = String::from_utf8_lossy(&line);
+        on_line(repo, stream, line.trim_end_matches('\r'));
+    }
+}
+
+/// Run `command` in every repository, at most `jobs` at a time, keeping input order.
+///
+/// With `fail_fast`, no new repositories are started after the first failure;
+/// commands already running are left to finish.
+pub fn run_all<F>(
+    repos: &[GitRepo],
+    command: &[String],
+    jobs: usize,
+    fail_fast: bool,
+    on_line: F,
+) -> Vec<ExecResult>
+where
+    F: Fn(&GitRepo, Stream, &str) + Sync,
+{
+    let failed = AtomicBool::new(false);
+    parallel::map_queued(repos, jobs, |repo| {
+        let outcome = if fail_fast && failed.load(Ordering::Relaxed) {
+            Outcome::Skipped
+        } else {
+            run(repo, command, &on_line)
+        };
+        if matches!(outcome, Outcome::Failed(_)) {
+            failed.store(true, Ordering::Relaxed);
+        }
+        ExecResult {
+            repo: repo.clone(),
+            outcome,
+        }
+    })
+}
+
+/// Summarize results: one line per failed or skipped repository, then the totals.
+///
+/// Example: `api  exit 2` ... `3 succeeded, 1 failed, 2 skipped`
+pub fn format_summary(results: &[ExecResult]) -> String {
+    let name_width = results.iter().map(|r| r.repo.name.len()).max().unwrap_or(0);
+    let mut lines: Vec<String> = results
+        .iter()
+        .filter_map(|r| {
+            let reason = match r.outcome {
+                Outcome::Success => return None,
+                Outcome::Failed(ref reason) => reason.as_str(),
+                Outcome::Skipped => "skipped",
+            };
+            Some(format!("{:<w$}  {}", r.repo.name, reason, w = name_width))
+        })
+        .collect();
+
+    let count = |f: fn(&Outcome) -> bool| results.iter().filter(|r| f(&r.outcome)).count();
+    let mut totals = vec![format!("{} succeeded", count(|o| *o == Outcome::Success))];
+    let failed = count(|o| matches!(o, Outcome::Failed(_)));
+    if failed > 0 {
+        totals.push(format!("{} failed", failed));
+    }
+    let skipped = count(|o| *o == Outcome::Skipped);
+    if skipped > 0 {
+        totals.push(format!("{} skipped", skipped));
+    }
+    lines.push(totals.join(", "));
+    lines.join("\n")
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+
+    fn sh(script: &str) -> Vec<String> {
+        vec!["sh".to_string(), "-c".to_string(), script.to_string()]
+    }
+
+    fn repos(root: &std::path::Path, names: &[&str]) -> Vec<GitRepo> {
+        names
+            .iter()
+            .map(|name| {
+                let dir = root.join(name);
+                std::fs::create_dir(&dir).unwrap();
+                GitRepo::new(dir)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_run_all_streams_lines_and_collects_outcomes() {
+        let tmp = tempfile::tempdir().unwrap();
+        let repos = repos(tmp.path(), &["api", "web", "cli"]);
+        let lines = Mutex::new(Vec::new());
+
+        let script = r#"echo "$GITNAV_REPO_NAME $(basename "$PWD")"; echo oops >&2; [ "$GITNAV_REPO_NAME" != web ]"#;
+        let results = run_all(&repos, &sh(script), 2, false, |repo, stream, line| {
+            lines
+                .lock()
+                .unwrap()
+                .push((repo.name.clone(), stream, line.to_string()));
+        });
+
+        let outcomes: Vec<&Outcome> = results.iter().map(|r| &r.outcome).collect();
+        assert_eq!(
+            outcomes,
+            vec![
+                &Outcome::Success,
+                &Outcome::Failed("exit 1".to_string()),
+                &Outcome::Success
+            ]
+        );
+        let lines = lines.into_inner().unwrap();
+        assert!(lines.contains(&("api".to_string(), Stream::Stdout, "api api".to_string())));
+        assert!(lines.contains(&("cli".to_string(), Stream::Stderr, "oops".to_string())));
+        assert_eq!(lines.len(), 6);
+    }
+
+    #[test]
+    fn test_run_all_fail_fast_skips_the_rest() {
+        let tmp = tempfile::tempdir().unwrap();
+        let repos = repos(tmp.path(), &["a", "b", "c"]);
+
+        let results = run_all(&repos, &sh("exit 3"), 1, true, |_, _, _| {});
+        let outcomes: Vec<&Outcome> = results.iter().map(|r| &r.outcome).collect();
+        assert_eq!(
+            outcomes,
+            vec![
+                &Outcome::Failed("exit 3".to_string()),
+                &Outcome::Skipped,
+                &Outcome::Skipped
+            ]
+        );
+        assert_eq!(
+            format_summary(&results),
+            "a  exit 3\nb  skipped\nc  skipped\n0 succeeded, 1 failed, 2 skipped"
+        );
+
+        let missing = run(
+            &repos[0],
+            &["gitnav-no-such-command".to_string()],
+            &|_, _, _| {},
+        );
+        assert!(matches!(missing, Outcome::Failed(ref m) if m.starts_with("failed to start")));
+    }
+}
@@ -8,8 +8,18 @@
 /// The application uses:
 /// - 0: Success
 /// - 1: General error
+/// - 3: No match (`--exit-0`)
 /// - 130: User interrupt (SIGINT/Ctrl+C)
 ///
+/// # Shell Wrapper Contract
+///
+/// The `gn` functions generated by `gitnav init` pass the exit code through:
+/// - `EXIT_SUCCESS` with a directory on stdout: the wrapper changes into it
+/// - `EXIT_SUCCESS` with other output (e.g. `--list`): the wrapper prints it
+/// - `EXIT_INTERRUPTED`: the picker was cancelled; the wrapper returns 130
+/// - Any other code: an error was reported on stderr; the wrapper returns it
+///   without changing directory
+///
 /// # Exit Codes
 ///
 /// Exit code for successful execution
@@ -20,11 +30,12 @@ pub const EXIT_SUCCESS: i32 = 0;
 pub const EXIT_GENERAL_ERROR: i32 = 1;
 
 /// Exit code for command-line argument errors
-#[allow(dead_code)]
 pub const EXIT_USAGE_ERROR: i32 = 2;
 
+/// Exit code when `--exit-0` finds no repository matching the query
+pub const EXIT_NO_MATCH: i32 = 3;
+
 /// Exit code for data errors (e.g., invalid input format)
-#[allow(dead_code)]
 pub const EXIT_DATA_ERROR: i32 = 65;
 
 /// Exit code for unavailable resources (e.g., missing dependencies)
@@ -34,6 +45,9 @@ pub const EXIT_UNAVAILABLE: i32 = 69;
 #[allow(dead_code)]
 pub const EXIT_IO_ERROR: i32 = 74;
 
+/// Exit code for an invalid configuration file
+pub const EXIT_CONFIG_ERROR: i32 = 78;
+
 /// Exit code for user interrupt (SIGINT/Ctrl+C)
 ///
 /// This is the standard exit code when a user interrupts the program
@@ -54,6 +68,11 @@ mod tests {
         assert_eq!(EXIT_GENERAL_ERROR, 1);
     }
 
+    #[test]
+    fn test_exit_code_no_match() {
+        assert_eq!(EXIT_NO_MATCH, 3);
+    }
+
     #[test]
     fn test_exit_code_interrupted() {
         assert_eq!(EXIT_INTERRUPTED, 130);
@@ -65,9 +84,11 @@ mod tests {
             EXIT_SUCCESS,
             EXIT_GENERAL_ERROR,
             EXIT_USAGE_ERROR,
+            EXIT_NO_MATCH,
             EXIT_DATA_ERROR,
             EXIT_UNAVAILABLE,
             EXIT_IO_ERROR,
+            EXIT_CONFIG_ERROR,
             EXIT_INTERRUPTED,
         ];
 
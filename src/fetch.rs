@@ -0,0 +1,95 @@
+use std::process::{Command, Stdio};
+
+use crate::parallel;
+use crate::scanner::GitRepo;
+
+/// A repository whose fetch failed, with git's reason.
+#[derive(Debug, Clone)]
+pub struct FetchFailure {
+    pub repo: GitRepo,
+    pub message: String,
+}
+
+/// Run `git fetch --quiet` in one repository.
+///
+/// The fetch runs non-interactively (`GIT_TERMINAL_PROMPT=0`) so a credential
+/// prompt can never hang a batch. On failure, returns the first line git wrote
+/// to stderr, which names the cause (`fatal: repository ... not found`,
+/// `ssh: Could not resolve hostname ...`).
+pub fn fetch(repo: &GitRepo) -> Result<(), String> {
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(&repo.path)
+        .args(["fetch", "--quiet"])
+        .env("GIT_TERMINAL_PROMPT", "0")
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        .output()
+        .map_err(|e| format!("Failed to run git: {}", e))?;
+    if output.status.success() {
+        return Ok(());
+    }
+
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    Err(stderr
+        .lines()
+        .map(str::trim)
+        .find(|line| !line.is_empty())
+        .map(str::to_string)
+        .unwrap_or_else(|| format!("git fetch exited with {}", output.status)))
+}
+
+/// Fetch every repository, at most `jobs` at a time, keeping input order.
+///
+/// `on_done` is called from the worker threads as each fetch finishes, for
+/// progress display. Returns the repositories whose fetch failed.
+pub fn fetch_all<F>(repos: &[GitRepo], jobs: usize, on_done: F) -> Vec<FetchFailure>
+where
+    F: Fn(&GitRepo) + Sync,
+{
+    parallel::map_queued(repos, jobs, |repo| {
+        let result = fetch(repo);
+        on_done(repo);
+        result.err().map(|message| FetchFailure {
+            repo: repo.clone(),
+            message,
+        })
+    })
+    .into_iter()
+    .flatten()
+    .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    #[test]
+    fn test_fetch_all_reports_failures() {
+        let tmp = tempfile::tempdir().unwrap();
+        let upstream = tmp.path().join("upstream");
+        git2::Repository::init(&upstream).unwrap();
+
+        let good = tmp.path().join("good");
+        let repo = git2::Repository::init(&good).unwrap();
+        repo.remote("origin", &upstream.display().to_string())
+            .unwrap();
+        let bad = tmp.path().join("bad");
+        let repo = git2::Repository::init(&bad).unwrap();
+        repo.remote("origin", &tmp.path().join("missing").display().to_string())
+            .unwrap();
+
+        let repos = vec![GitRepo::new(good), GitRepo::new(bad.clone())];
+        let done = AtomicUsize::new(0);
+        let failures = fetch_all(&repos, 2, |_| {
+            done.fetch_add(1, Ordering::Relaxed);
+        });
+
+        assert_eq!(done.load(Ordering::Relaxed), 2);
+        assert_eq!(failures.len(), 1);
+        assert_eq!(failures[0].repo.path, bad);
+        assert!(!failures[0].message.is_empty());
+    }
+}
@@ -0,0 +1,83 @@
+use anyhow::{Context, Result};
+use std::path::Path;
+use std::process::{Command, Stdio};
+
+/// Files in the repository at `repo`, relative to its root, for `gitnav files`.
+///
+/// Lists what `git ls-files` tracks plus untracked files that are not ignored,
+/// so a file created but not yet added can be picked too.
+///
+/// # Errors
+///
+/// Returns an error if git cannot be started or fails (e.g. `repo` is not a
+/// repository)
+pub fn list(repo: &Path) -> Result<Vec<String>> {
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(repo)
+        .args([
+            "ls-files",
+            "-z",
+            "--cached",
+            "--others",
+            "--exclude-standard",
+        ])
+        .stdin(Stdio::null())
+        .stderr(Stdio::piped())
+        .output()
+        .context("Failed to run git")?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        anyhow::bail!(
+            "git ls-files failed in {}: {}",
+            repo.display(),
+            stderr.lines().next().unwrap_or("").trim()
+        );
+    }
+    Ok(parse(&String::from_utf8_lossy(&output.stdout)))
+}
+
+/// Split NUL-separated `ls-files -z` output into a sorted list. git lists
+/// untracked files apart from tracked ones, and files with merge conflicts once per
+/// stage, so the list is sorted and deduplicated.
+fn parse(output: &str) -> Vec<String> {
+    let mut files: Vec<String> = output
+        .split('\0')
+        .filter(|f| !f.is_empty())
+        .map(str::to_string)
+        .collect();
+    files.sort();
+    files.dedup();
+    files
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(
+            parse("src/main.rs\0a b.txt\0a b.txt\0README.md\0"),
+            vec!["README.md", "a b.txt", "src/main.rs"]
+        );
+        assert!(parse("").is_empty());
+    }
+
+    #[test]
+    fn test_list_includes_untracked_but_not_ignored() {
+        let dir = tempfile::tempdir().unwrap();
+        let repo = git2::Repository::init(dir.path()).unwrap();
+        std::fs::write(dir.path().join(".gitignore"), "target/\n").unwrap();
+        std::fs::write(dir.path().join("tracked.rs"), "").unwrap();
+        std::fs::write(dir.path().join("new.rs"), "").unwrap();
+        std::fs::create_dir(dir.path().join("target")).unwrap();
+        std::fs::write(dir.path().join("target/out"), "").unwrap();
+        let mut index = repo.index().unwrap();
+        index.add_path(Path::new("tracked.rs")).unwrap();
+        index.write().unwrap();
+
+        let files = list(dir.path()).unwrap();
+        assert_eq!(files, vec![".gitignore", "new.rs", "tracked.rs"]);
+    }
+}
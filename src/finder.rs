@@ -0,0 +1,626 @@
+//! Minimal built-in picker, used when fzf is not installed.
+//!
+//! It draws on the controlling terminal (`/dev/tty`) the way fzf does, so the
+//! selected path still goes to stdout for the shell wrapper. Matching and
+//! ranking come from [`crate::matcher`], so results follow `[matcher]`.
+//!
+//! Keys: type to filter, Up/Down (or Ctrl-P/Ctrl-N, Ctrl-K/Ctrl-J) to move,
+//! Enter to select, Esc or Ctrl-C to cancel, Ctrl-U/Ctrl-W to clear the query
+//! or its last word. In multi-select mode Tab marks an item. `ctrl-<letter>`
+//! keys from `[keybindings]` work as in fzf. The built-in picker needs `stty`,
+//! so it is Unix only.
+
+use std::time::Duration;
+
+use crate::actions::Action;
+use crate::config::MatcherConfig;
+use crate::matcher;
+use crate::scanner::GitRepo;
+
+/// How long to wait for the rest of an escape sequence before treating Esc
+/// as a key press of its own.
+const ESCAPE_TIMEOUT: Duration = Duration::from_millis(25);
+
+/// Whether the built-in picker works on this platform.
+pub fn is_supported() -> bool {
+    cfg!(unix)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Key {
+    Char(char),
+    Backspace,
+    ClearQuery,
+    DeleteWord,
+    Up,
+    Down,
+    Toggle,
+    Enter,
+    Cancel,
+    /// A Ctrl-letter combination without a built-in meaning
+    Ctrl(char),
+    Other,
+}
+
+/// Decode one key press from a byte source.
+///
+/// `next(None)` blocks for the next byte; `next(Some(timeout))` gives up after
+/// `timeout`. Returns `None` once the source is exhausted.
+fn read_key(next: &mut dyn FnMut(Option<Duration>) -> Option<u8>) -> Option<Key> {
+    let byte = next(None)?;
+    let key = match byte {
+        0x1b => match next(Some(ESCAPE_TIMEOUT)) {
+            None => Key::Cancel,
+            Some(b'[') | Some(b'O') => {
+                // CSI / SS3: parameters, then a final byte in 0x40..=0x7e
+                let mut last = next(Some(ESCAPE_TIMEOUT));
+                while let Some(b) = last {
+                    if (0x40..=0x7e).contains(&b) {
+                        break;
+                    }
+                    last = next(Some(ESCAPE_TIMEOUT));
+                }
+                match last {
+                    Some(b'A') => Key::Up,
+                    Some(b'B') => Key::Down,
+                    _ => Key::Other,
+                }
+            }
+            Some(_) => Key::Other,
+        },
+        b'\r' => Key::Enter,
+        b'\t' => Key::Toggle,
+        0x03 | 0x07 => Key::Cancel,
+        0x7f | 0x08 => Key::Backspace,
+        0x15 => Key::ClearQuery,
+        0x17 => Key::DeleteWord,
+        0x10 | 0x0b => Key::Up,
+        0x0e | b'\n' => Key::Down,
+        0x01..=0x1a => Key::Ctrl((b'a' + byte - 1) as char),
+        0x20..=0x7e => Key::Char(byte as char),
+        0xc0..=0xf7 => {
+            let len = match byte {
+                0xc0..=0xdf => 2,
+                0xe0..=0xef => 3,
+                _ => 4,
+            };
+            let mut buf = vec![byte];
+            for _ in 1..len {
+                buf.push(next(Some(ESCAPE_TIMEOUT))?);
+            }
+            match std::str::from_utf8(&buf)
+                .ok()
+                .and_then(|s| s.chars().next())
+            {
+                Some(c) => Key::Char(c),
+                None => Key::Other,
+            }
+        }
+        _ => Key::Other,
+    };
+    Some(key)
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Outcome {
+    Continue,
+    Select(Vec<usize>),
+    /// An action key was pressed on these items
+    Act(Action, Vec<usize>),
+    Cancel,
+}
+
+/// Built-in picker keys for `[keybindings]`; only `ctrl-<letter>` is supported.
+fn ctrl_key(name: &str) -> Option<char> {
+    let letter = name.strip_prefix("ctrl-")?;
+    let mut chars = letter.chars();
+    match (chars.next(), chars.next()) {
+        (Some(c), None) if c.is_ascii_lowercase() => Some(c),
+        _ => None,
+    }
+}
+
+/// Picker state: the query, the indices of matching items (best first), and
+/// in multi-select mode the marked items in the order they were marked.
+#[derive(Debug)]
+struct Picker {
+    query: String,
+    matches: Vec<usize>,
+    cursor: usize,
+    multi: bool,
+    marked: Vec<usize>,
+    /// Ctrl keys bound to actions
+    actions: Vec<(char, Action)>,
+}
+
+impl Picker {
+    fn new(items: &[GitRepo], options: &MatcherConfig, query: &str, multi: bool) -> Self {
+        let mut picker = Self {
+            query: query.to_string(),
+            matches: Vec::new(),
+            cursor: 0,
+            multi,
+            marked: Vec::new(),
+            actions: Vec::new(),
+        };
+        picker.refilter(items, options);
+        picker
+    }
+
+    /// Recompute matches; an empty query lists everything in input order.
+    fn refilter(&mut self, items: &[GitRepo], options: &MatcherConfig) {
+        self.matches = if self.query.trim().is_empty() {
+            (0..items.len()).collect()
+        } else {
+            matcher::rank(&self.query, items, options)
+                .into_iter()
+                .map(|r| r.index)
+                .collect()
+        };
+        self.cursor = 0;
+    }
+
+    fn handle(&mut self, key: Key, items: &[GitRepo], options: &MatcherConfig) -> Outcome {
+        match key {
+            Key::Char(c) => {
+                self.query.push(c);
+                self.refilter(items, options);
+            }
+            Key::Backspace => {
+                if self.query.pop().is_some() {
+                    self.refilter(items, options);
+                }
+            }
+            Key::ClearQuery => {
+                self.query.clear();
+                self.refilter(items, options);
+            }
+            Key::DeleteWord => {
+                let trimmed = self.query.trim_end();
+                let cut = trimmed.rfind(' ').map_or(0, |i| i + 1);
+                self.query.truncate(cut);
+                self.refilter(items, options);
+            }
+            Key::Up => self.cursor = self.cursor.saturating_sub(1),
+            Key::Down => {
+                if self.cursor + 1 < self.matches.len() {
+                    self.cursor += 1;
+                }
+            }
+            Key::Toggle if self.multi => {
+                if let Some(&index) = self.matches.get(self.cursor) {
+                    match self.marked.iter().position(|&m| m == index) {
+                        Some(pos) => {
+                            self.marked.remove(pos);
+                        }
+                        None => self.marked.push(index),
+                    }
+                    if self.cursor + 1 < self.matches.len() {
+                        self.cursor += 1;
+                    }
+                }
+            }
+            Key::Enter => {
+                if let Some(chosen) = self.chosen() {
+                    return Outcome::Select(chosen);
+                }
+            }
+            Key::Ctrl(c) => {
+                let action = self.actions.iter().find(|(key, _)| *key == c);
+                if let (Some(&(_, action)), Some(chosen)) = (action, self.chosen()) {
+                    return Outcome::Act(action, chosen);
+                }
+            }
+            Key::Cancel => return Outcome::Cancel,
+            Key::Toggle | Key::Other => {}
+        }
+        Outcome::Continue
+    }
+
+    /// The marked items, or the one under the cursor if none are marked.
+    fn chosen(&self) -> Option<Vec<usize>> {
+        if !self.marked.is_empty() {
+            return Some(self.marked.clone());
+        }
+        self.matches.get(self.cursor).map(|&index| vec![index])
+    }
+}
+
+#[cfg(unix)]
+pub use unix::{select_menu, select_repo};
+
+#[cfg(not(unix))]
+pub fn select_repo(
+    _repos: &[crate::scanner::EnrichedRepo],
+    _config: &crate::config::Config,
+    _initial_query: Option<&str>,
+    _multi: bool,
+) -> anyhow::Result<Option<crate::actions::Selection>> {
+    anyhow::bail!("The built-in picker is not available on this platform; install fzf")
+}
+
+#[cfg(not(unix))]
+pub fn select_menu(
+    _labels: &[&str],
+    _config: &crate::config::Config,
+) -> anyhow::Result<Option<usize>> {
+    anyhow::bail!("The built-in picker is not available on this platform; install fzf")
+}
+
+#[cfg(unix)]
+mod unix {
+    use anyhow::{Context, Result};
+    use std::fs::{File, OpenOptions};
+    use std::io::{Read, Write};
+    use std::process::{Command, Stdio};
+    use std::sync::mpsc;
+    use std::thread;
+
+    use super::{ctrl_key, read_key, Outcome, Picker};
+    use crate::actions::{self, Action, Selection};
+    use crate::config::Config;
+    use crate::scanner::{format_display, EnrichedRepo, GitRepo};
+
+    /// Puts the terminal in raw mode on an alternate screen, and restores it on drop.
+    struct RawTerminal {
+        tty: File,
+        saved: String,
+    }
+
+    impl RawTerminal {
+        fn enter(tty: &File) -> Result<Self> {
+            let saved = stty(tty, &["-g"])?;
+            stty(tty, &["raw", "-echo"])?;
+            let mut tty = tty.try_clone()?;
+            write!(tty, "\x1b[?1049h")?;
+            Ok(Self {
+                tty,
+                saved: saved.trim().to_string(),
+            })
+        }
+    }
+
+    impl Drop for RawTerminal {
+        fn drop(&mut self) {
+            let _ = write!(self.tty, "\x1b[?1049l");
+            let _ = self.tty.flush();
+            let _ = stty(&self.tty, &[self.saved.as_str()]);
+        }
+    }
+
+    fn stty(tty: &File, args: &[&str]) -> Result<String> {
+        let output = Command::new("stty")
+            .args(args)
+            .stdin(Stdio::from(tty.try_clone()?))
+            .stderr(Stdio::null())
+            .output()
+            .context("Failed to run stty")?;
+        if !output.status.success() {
+            anyhow::bail!("stty {} failed", args.join(" "));
+        }
+        Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+    }
+
+    /// Terminal height in rows, from `stty size`.
+    fn terminal_rows(tty: &File) -> usize {
+        stty(tty, &["size"])
+            .ok()
+            .and_then(|size| size.split_whitespace().next()?.parse().ok())
+            .filter(|&rows| rows > 0)
+            .unwrap_or(24)
+    }
+
+    /// Let the user pick a repository with the built-in picker.
+    ///
+    /// With `multi`, Tab marks repositories and Enter returns all marked ones
+    /// (or the one under the cursor if none are marked). Action keys choose
+    /// the same way.
+    ///
+    /// # Returns
+    ///
+    /// - `Ok(Some(selection))` with the selected repositories and action
+    /// - `Ok(None)` if the user cancelled
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if there is no controlling terminal or it cannot be
+    /// switched to raw mode
+    pub fn select_repo(
+        repos: &[EnrichedRepo],
+        config: &Config,
+        initial_query: Option<&str>,
+        multi: bool,
+    ) -> Result<Option<Selection>> {
+        if repos.is_empty() {
+            return Ok(None);
+        }
+
+        let tty = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .open("/dev/tty")
+            .context("The built-in picker needs a terminal (/dev/tty)")?;
+        let rows = terminal_rows(&tty);
+        let raw = RawTerminal::enter(&tty)?;
+        let mut out = raw.tty.try_clone()?;
+
+        // Blocking reads happen on their own thread so escape sequences can be
+        // told apart from a lone Esc with a timeout
+        let (tx, rx) = mpsc::channel();
+        let mut input = tty.try_clone()?;
+        thread::spawn(move || {
+            let mut byte = [0u8; 1];
+            while input.read_exact(&mut byte).is_ok() {
+                if tx.send(byte[0]).is_err() {
+                    break;
+                }
+            }
+        });
+        let mut next = |timeout: Option<std::time::Duration>| match timeout {
+            Some(t) => rx.recv_timeout(t).ok(),
+            None => rx.recv().ok(),
+        };
+
+        let items: Vec<GitRepo> = repos
+            .iter()
+            .map(|r| GitRepo {
+                name: r.name.clone(),
+                path: r.path.clone(),
+            })
+            .collect();
+        let use_color = std::env::var("NO_COLOR").is_err();
+        let name_width = repos.iter().map(|r| r.name.len()).max().unwrap_or(0);
+        let lines: Vec<String> = repos
+            .iter()
+            .map(|r| format_display(r, name_width, use_color, &config.ui.badge_style))
+            .collect();
+
+        let mut picker = Picker::new(&items, &config.matcher, initial_query.unwrap_or(""), multi);
+        picker.actions = actions::bindings(config)
+            .into_iter()
+            .filter_map(|(key, action)| Some((ctrl_key(key)?, action)))
+            .collect();
+        let mut offset = 0;
+        loop {
+            let visible = rows.saturating_sub(2).max(1);
+            if picker.cursor < offset {
+                offset = picker.cursor;
+            } else if picker.cursor >= offset + visible {
+                offset = picker.cursor + 1 - visible;
+            }
+            draw(&mut out, config, &picker, &lines, offset, visible)?;
+
+            let Some(key) = read_key(&mut next) else {
+                return Ok(None);
+            };
+            let (action, indices) = match picker.handle(key, &items, &config.matcher) {
+                Outcome::Continue => continue,
+                Outcome::Select(indices) => (Action::Navigate, indices),
+                Outcome::Act(action, indices) => (action, indices),
+                Outcome::Cancel => return Ok(None),
+            };
+            let paths = indices
+                .into_iter()
+                .map(|index| items[index].path.display().to_string())
+                .collect();
+            return Ok(Some(Selection { action, paths }));
+        }
+    }
+
+    /// Let the user pick one of `labels`, for the action menu. Returns the
+    /// index of the chosen label, or `None` if the user cancelled.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if there is no controlling terminal
+    pub fn select_menu(labels: &[&str], config: &Config) -> Result<Option<usize>> {
+        let mut menu_config = config.clone();
+        menu_config.keybindings.select = String::new();
+        menu_config.keybindings.open_editor = String::new();
+        menu_config.keybindings.open_browser = String::new();
+        menu_config.keybindings.copy_path = String::new();
+        menu_config.keybindings.refresh = String::new();
+        // Each label is a pseudo-repository whose "path" is its index
+        let items: Vec<EnrichedRepo> = labels
+            .iter()
+            .enumerate()
+            .map(|(index, label)| {
+                crate::scanner::without_meta(GitRepo {
+                    name: label.to_string(),
+                    path: index.to_string().into(),
+                })
+            })
+            .collect();
+        let selection = select_repo(&items, &menu_config, None, false)?;
+        Ok(selection.and_then(|s| s.paths.first()?.parse().ok()))
+    }
+
+    fn draw(
+        out: &mut File,
+        config: &Config,
+        picker: &Picker,
+        lines: &[String],
+        offset: usize,
+        visible: usize,
+    ) -> Result<()> {
+        let mut screen = String::from("\x1b[H\x1b[2J");
+        screen.push_str(&format!("{}{}\r\n", config.ui.prompt, picker.query));
+        let marked = if picker.marked.is_empty() {
+            String::new()
+        } else {
+            format!(" ({})", picker.marked.len())
+        };
+        screen.push_str(&format!(
+            "\x1b[2m  {}/{}{}  {}\x1b[0m\r\n",
+            picker.matches.len(),
+            lines.len(),
+            marked,
+            config.ui.header
+        ));
+        for (row, &index) in picker.matches.iter().enumerate().skip(offset).take(visible) {
+            let pointer = if row == picker.cursor { '>' } else { ' ' };
+            let mark = if picker.marked.contains(&index) {
+                '+'
+            } else {
+                ' '
+            };
+            screen.push_str(&format!(
+                "\x1b[1m{}{}\x1b[0m{}\r\n",
+                pointer, mark, lines[index]
+            ));
+        }
+        let column = config.ui.prompt.chars().count() + picker.query.chars().count() + 1;
+        screen.push_str(&format!("\x1b[1;{}H", column));
+        out.write_all(screen.as_bytes())?;
+        out.flush()?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::VecDeque;
+    use std::path::PathBuf;
+
+    fn keys(bytes: &[u8]) -> Vec<Key> {
+        let mut input: VecDeque<u8> = bytes.iter().copied().collect();
+        let mut next = |_: Option<Duration>| input.pop_front();
+        std::iter::from_fn(|| read_key(&mut next)).collect()
+    }
+
+    fn repos(names: &[&str]) -> Vec<GitRepo> {
+        names
+            .iter()
+            .map(|n| GitRepo::new(PathBuf::from(format!("/src/{}", n))))
+            .collect()
+    }
+
+    #[test]
+    fn test_read_key_decodes_sequences() {
+        assert_eq!(
+            keys(b"a\x1b[A\x1b[B\x1bOA\r"),
+            vec![Key::Char('a'), Key::Up, Key::Down, Key::Up, Key::Enter]
+        );
+        assert_eq!(
+            keys(b"\x7f\x15\x17\x03\x10\x0e\t"),
+            vec![
+                Key::Backspace,
+                Key::ClearQuery,
+                Key::DeleteWord,
+                Key::Cancel,
+                Key::Up,
+                Key::Down,
+                Key::Toggle
+            ]
+        );
+        assert_eq!(keys(b"\x05\x0f"), vec![Key::Ctrl('e'), Key::Ctrl('o')]);
+        // Delete key (ESC [ 3 ~) is consumed as one unknown key
+        assert_eq!(keys(b"\x1b[3~x"), vec![Key::Other, Key::Char('x')]);
+        assert_eq!(keys("é".as_bytes()), vec![Key::Char('é')]);
+        // A lone Esc (nothing follows within the timeout) cancels
+        assert_eq!(keys(b"\x1b"), vec![Key::Cancel]);
+    }
+
+    #[test]
+    fn test_picker_filters_and_selects() {
+        let items = repos(&["website", "api", "cli-tools"]);
+        let options = MatcherConfig::default();
+        let mut picker = Picker::new(&items, &options, "", false);
+        assert_eq!(picker.matches, vec![0, 1, 2]);
+
+        for c in "cli".chars() {
+            picker.handle(Key::Char(c), &items, &options);
+        }
+        assert_eq!(picker.matches, vec![2]);
+        assert_eq!(
+            picker.handle(Key::Enter, &items, &options),
+            Outcome::Select(vec![2])
+        );
+
+        picker.handle(Key::ClearQuery, &items, &options);
+        picker.handle(Key::Down, &items, &options);
+        picker.handle(Key::Down, &items, &options);
+        picker.handle(Key::Down, &items, &options);
+        assert_eq!(picker.cursor, 2);
+        picker.handle(Key::Up, &items, &options);
+        assert_eq!(
+            picker.handle(Key::Enter, &items, &options),
+            Outcome::Select(vec![1])
+        );
+    }
+
+    #[test]
+    fn test_picker_editing_keys() {
+        let items = repos(&["api"]);
+        let options = MatcherConfig::default();
+        let mut picker = Picker::new(&items, &options, "work api", false);
+
+        picker.handle(Key::DeleteWord, &items, &options);
+        assert_eq!(picker.query, "work ");
+        picker.handle(Key::Backspace, &items, &options);
+        assert_eq!(picker.query, "work");
+        assert!(picker.matches.is_empty());
+        assert_eq!(
+            picker.handle(Key::Enter, &items, &options),
+            Outcome::Continue
+        );
+        assert_eq!(
+            picker.handle(Key::Cancel, &items, &options),
+            Outcome::Cancel
+        );
+    }
+
+    #[test]
+    fn test_picker_action_keys() {
+        let items = repos(&["api", "cli"]);
+        let options = MatcherConfig::default();
+        let mut picker = Picker::new(&items, &options, "", false);
+        picker.actions = vec![('e', Action::OpenEditor)];
+
+        assert_eq!(
+            picker.handle(Key::Ctrl('o'), &items, &options),
+            Outcome::Continue
+        );
+        picker.handle(Key::Down, &items, &options);
+        assert_eq!(
+            picker.handle(Key::Ctrl('e'), &items, &options),
+            Outcome::Act(Action::OpenEditor, vec![1])
+        );
+
+        assert_eq!(ctrl_key("ctrl-e"), Some('e'));
+        assert_eq!(ctrl_key("alt-e"), None);
+        assert_eq!(ctrl_key("ctrl-space"), None);
+    }
+
+    #[test]
+    fn test_picker_multi_select() {
+        let items = repos(&["api", "cli", "web"]);
+        let options = MatcherConfig::default();
+
+        let mut single = Picker::new(&items, &options, "", false);
+        single.handle(Key::Toggle, &items, &options);
+        assert!(single.marked.is_empty());
+
+        let mut picker = Picker::new(&items, &options, "", true);
+        // Tab marks the current item and moves down
+        picker.handle(Key::Toggle, &items, &options);
+        assert_eq!((picker.marked.clone(), picker.cursor), (vec![0], 1));
+        picker.handle(Key::Down, &items, &options);
+        picker.handle(Key::Toggle, &items, &options);
+        // Tab again on a marked item unmarks it
+        picker.handle(Key::Up, &items, &options);
+        picker.handle(Key::Up, &items, &options);
+        picker.handle(Key::Toggle, &items, &options);
+        assert_eq!(
+            picker.handle(Key::Enter, &items, &options),
+            Outcome::Select(vec![2])
+        );
+
+        picker.handle(Key::Toggle, &items, &options);
+        assert_eq!(
+            picker.handle(Key::Enter, &items, &options),
+            Outcome::Select(vec![2, 1])
+        );
+    }
+}
@@ -0,0 +1,720 @@
+use anyhow::{Context, Result};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::Write;
+use std::marker::PhantomData;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use crate::config::BrowseTemplate;
+use crate::remote::RemoteUrl;
+use crate::template::{Part, Placeholder, Template};
+
+/// How long to wait before retrying a fetch that failed.
+const RETRY_AFTER: Duration = Duration::from_secs(3600);
+
+/// A refresh lock older than this is assumed to belong to a crashed process.
+const STALE_LOCK: Duration = Duration::from_secs(60);
+
+/// Most redirects followed for one request.
+const MAX_REDIRECTS: usize = 5;
+
+/// An API request that returns a repository's metadata.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ApiRequest {
+    pub url: String,
+    /// Authentication header built from the forge's usual token variable, if set
+    pub auth_header: Option<String>,
+}
+
+/// The API endpoint describing `remote`, for GitHub and GitLab hosts.
+///
+/// Tokens come from `GITHUB_TOKEN`/`GH_TOKEN` or `GITLAB_TOKEN`; without one the
+/// public, rate-limited API is used. `GITLAB_TOKEN` is only sent to gitlab.com
+/// and to the self-hosted instance named by `GITLAB_HOST` (as for `glab`), never
+/// to any other `gitlab.*` host a repository's remote happens to point at.
+/// Returns `None` for other forges.
+pub fn api_request(remote: &RemoteUrl) -> Option<ApiRequest> {
+    let env = |names: &[&str]| {
+        names
+            .iter()
+            .find_map(|name| std::env::var(name).ok().filter(|v| !v.is_empty()))
+    };
+
+    if remote.host == "github.com" {
+        Some(ApiRequest {
+            url: format!("https://api.github.com/repos/{}", remote.path),
+            auth_header: env(&["GITHUB_TOKEN", "GH_TOKEN"])
+                .map(|token| format!("Authorization: Bearer {}", token)),
+        })
+    } else if remote.host == "gitlab.com" || remote.host.starts_with("gitlab.") {
+        Some(ApiRequest {
+            url: format!(
+                "https://{}/api/v4/projects/{}",
+                remote.host,
+                remote.path.replace('/', "%2F")
+            ),
+            auth_header: env(&["GITLAB_TOKEN"])
+                .filter(|_| gitlab_token_host(&remote.host, env(&["GITLAB_HOST"]).as_deref()))
+                .map(|token| format!("PRIVATE-TOKEN: {}", token)),
+        })
+    } else {
+        None
+    }
+}
+
+/// Whether `GITLAB_TOKEN` may be sent to `host`: gitlab.com, or the instance
+/// `gitlab_host` names (a host or URL, as `GITLAB_HOST` takes).
+fn gitlab_token_host(host: &str, gitlab_host: Option<&str>) -> bool {
+    let configured = gitlab_host.map(|configured| {
+        let configured = configured
+            .split_once("://")
+            .map_or(configured, |(_, rest)| rest);
+        configured.trim_end_matches('/')
+    });
+    host == "gitlab.com"
+        || configured.is_some_and(|configured| configured.eq_ignore_ascii_case(host))
+}
+
+/// Fetch a repository's description from its forge API using `curl`.
+///
+/// # Errors
+///
+/// Returns an error if curl cannot be run, the request fails, or the response
+/// is not JSON
+pub fn fetch_description(request: &ApiRequest) -> Result<Option<String>> {
+    let (status, body) = get(request)?;
+    if !(200..300).contains(&status) {
+        anyhow::bail!("Request to {} failed with HTTP {}", request.url, status);
+    }
+    parse_description(&body)
+}
+
+/// Send `request` with `curl` and return the HTTP status and the response body.
+///
+/// Unlike [`fetch_description`], error statuses are returned rather than
+/// failing, so callers can tell "not found" from other errors. Redirects (a
+/// renamed GitHub repository) are followed, but the authentication header is
+/// only sent again to the same scheme, host, and port.
+///
+/// # Errors
+///
+/// Returns an error if curl cannot be run or the request does not complete
+pub fn get(request: &ApiRequest) -> Result<(u16, Vec<u8>)> {
+    let mut request = request.clone();
+    for _ in 0..=MAX_REDIRECTS {
+        let mut body = curl(&request, &["--write-out", "\n%{http_code} %{redirect_url}"])?;
+        let split = body.iter().rposition(|&b| b == b'\n').unwrap_or(0);
+        let trailer = String::from_utf8_lossy(&body[split..]).trim().to_string();
+        body.truncate(split);
+        let (status, location) = trailer.split_once(' ').unwrap_or((&trailer, ""));
+        let status = status.parse().unwrap_or(0);
+        if !(300..400).contains(&status) || location.is_empty() {
+            return Ok((status, body));
+        }
+        request = redirect(&request, location)?;
+    }
+    anyhow::bail!("Too many redirects, last to {}", request.url)
+}
+
+/// `request` sent on to `location`, without its authentication header if
+/// that is another origin.
+///
+/// # Errors
+///
+/// Returns an error if `location` is not an `https://` URL, so a forge can
+/// never point curl at a local file or another protocol
+fn redirect(request: &ApiRequest, location: &str) -> Result<ApiRequest> {
+    let is_https = location
+        .get(.."https://".len())
+        .is_some_and(|scheme| scheme.eq_ignore_ascii_case("https://"));
+    if !is_https {
+        anyhow::bail!(
+            "Request to {} redirected to non-https {}",
+            request.url,
+            location
+        );
+    }
+    let mut request = request.clone();
+    if origin(location).map(str::to_ascii_lowercase)
+        != origin(&request.url).map(str::to_ascii_lowercase)
+    {
+        request.auth_header = None;
+    }
+    request.url = location.to_string();
+    Ok(request)
+}
+
+/// The `scheme://host[:port]` a URL starts with.
+fn origin(url: &str) -> Option<&str> {
+    let (scheme, rest) = url.split_once("://")?;
+    let end = rest.find('/').unwrap_or(rest.len());
+    Some(&url[..scheme.len() + "://".len() + end])
+}
+
+/// The curl config file for `request`, its values quoted so that no URL or
+/// header can add options of its own.
+fn curl_config(request: &ApiRequest) -> String {
+    let quote = |value: &str| {
+        let mut quoted = String::from("\"");
+        for c in value.chars() {
+            match c {
+                '\\' => quoted.push_str("\\\\"),
+                '"' => quoted.push_str("\\\""),
+                '\n' => quoted.push_str("\\n"),
+                '\r' => quoted.push_str("\\r"),
+                '\t' => quoted.push_str("\\t"),
+                c => quoted.push(c),
+            }
+        }
+        quoted.push('"');
+        quoted
+    };
+    let mut config = format!("url = {}\n", quote(&request.url));
+    if let Some(ref header) = request.auth_header {
+        config.push_str(&format!("header = {}\n", quote(header)));
+    }
+    config
+}
+
+/// Run curl for `request` with `extra_args` and return what it printed.
+///
+/// The request is passed to curl on stdin so tokens never appear in the
+/// process list. Redirects are not followed here; see [`get`]. Only https is
+/// spoken, whatever the URL says.
+fn curl(request: &ApiRequest, extra_args: &[&str]) -> Result<Vec<u8>> {
+    let curl_config = curl_config(request);
+
+    let mut child = Command::new("curl")
+        .args([
+            "--silent",
+            "--show-error",
+            "--max-time",
+            "10",
+            "--proto",
+            "=https",
+            "--proto-redir",
+            "=https",
+            "--user-agent",
+            concat!("gitnav/", env!("CARGO_PKG_VERSION")),
+            "--config",
+            "-",
+        ])
+        .args(extra_args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .context("Failed to run curl")?;
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin
+            .write_all(curl_config.as_bytes())
+            .context("Failed to write curl request")?;
+    }
+
+    let output = child
+        .wait_with_output()
+        .context("Failed to wait for curl")?;
+    if !output.status.success() {
+        anyhow::bail!(
+            "Request to {} failed: {}",
+            request.url,
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    Ok(output.stdout)
+}
+
+/// Extract the `description` field shared by the GitHub and GitLab APIs.
+fn parse_description(body: &[u8]) -> Result<Option<String>> {
+    let json: serde_json::Value =
+        serde_json::from_slice(body).context("Forge API returned invalid JSON")?;
+    Ok(json["description"]
+        .as_str()
+        .map(str::trim)
+        .filter(|d| !d.is_empty())
+        .map(String::from))
+}
+
+/// Result of looking up a cached value.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Lookup<T> {
+    /// Cached and within its TTL (for a description, `None` means the
+    /// repository has none)
+    Fresh(T),
+    /// Cached but expired; show it while a refresh runs
+    Stale(T),
+    /// Never fetched
+    Missing,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct Entry<T> {
+    remote: String,
+    /// Named `description` by versions that cached nothing else
+    #[serde(alias = "description")]
+    value: T,
+    /// Unix timestamp after which the entry should be refreshed
+    expires_at: u64,
+}
+
+/// Values fetched from a forge, one small file per remote under a
+/// subdirectory of the cache dir, e.g. `<cache dir>/descriptions`.
+///
+/// One file per remote lets background refreshes for different repositories
+/// run at the same time without clobbering each other.
+#[derive(Debug, Clone)]
+pub struct Store<T> {
+    dir: PathBuf,
+    value: PhantomData<fn() -> T>,
+}
+
+/// Cached remote descriptions.
+pub type DescriptionStore = Store<Option<String>>;
+
+impl<T: Serialize + DeserializeOwned> Store<T> {
+    /// The store kept in `<cache_dir>/<name>`.
+    pub fn new(cache_dir: &Path, name: &str) -> Self {
+        Self {
+            dir: cache_dir.join(name),
+            value: PhantomData,
+        }
+    }
+
+    fn entry_path(&self, remote: &RemoteUrl, extension: &str) -> PathBuf {
+        let mut hasher = Sha256::new();
+        hasher.update(remote.to_string().as_bytes());
+        let hash = format!("{:x}", hasher.finalize());
+        self.dir.join(format!("{}.{}", &hash[..16], extension))
+    }
+
+    /// Look up the cached value for `remote`.
+    pub fn get(&self, remote: &RemoteUrl) -> Lookup<T> {
+        let entry = fs::read_to_string(self.entry_path(remote, "cache"))
+            .ok()
+            .and_then(|contents| serde_json::from_str::<Entry<T>>(&contents).ok());
+        match entry {
+            Some(entry) if now() < entry.expires_at => Lookup::Fresh(entry.value),
+            Some(entry) => Lookup::Stale(entry.value),
+            None => Lookup::Missing,
+        }
+    }
+
+    /// Store a value that stays fresh for `valid_for`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the entry cannot be written
+    pub fn put(&self, remote: &RemoteUrl, value: T, valid_for: Duration) -> Result<()> {
+        fs::create_dir_all(&self.dir)
+            .with_context(|| format!("Failed to create directory: {}", self.dir.display()))?;
+        let entry = Entry {
+            remote: remote.to_string(),
+            value,
+            expires_at: now() + valid_for.as_secs(),
+        };
+        let path = self.entry_path(remote, "cache");
+        let tmp = self.entry_path(remote, "tmp");
+        fs::write(&tmp, serde_json::to_string(&entry)?)
+            .with_context(|| format!("Failed to write: {}", tmp.display()))?;
+        fs::rename(&tmp, &path).with_context(|| format!("Failed to write: {}", path.display()))?;
+        Ok(())
+    }
+
+    /// Store the result of a fetch: a fetched value stays fresh for `ttl`, while
+    /// a failure keeps the previous value and is retried after an hour.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error only if the result cannot be stored
+    pub fn put_fetched(&self, remote: &RemoteUrl, fetched: Result<T>, ttl: Duration) -> Result<()>
+    where
+        T: Default,
+    {
+        match fetched {
+            Ok(value) => self.put(remote, value, ttl),
+            Err(_) => {
+                let previous = match self.get(remote) {
+                    Lookup::Fresh(v) | Lookup::Stale(v) => v,
+                    Lookup::Missing => T::default(),
+                };
+                self.put(remote, previous, RETRY_AFTER.min(ttl))
+            }
+        }
+    }
+
+    /// Whether another process is currently refreshing `remote`.
+    pub fn is_refreshing(&self, remote: &RemoteUrl) -> bool {
+        lock_is_live(&self.entry_path(remote, "lock"))
+    }
+
+    /// Take the refresh lock for `remote`, or `None` if another process holds it.
+    pub fn lock(&self, remote: &RemoteUrl) -> Option<RefreshLock> {
+        fs::create_dir_all(&self.dir).ok()?;
+        let path = self.entry_path(remote, "lock");
+        if lock_is_live(&path) {
+            return None;
+        }
+        let _ = fs::remove_file(&path);
+        fs::OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(&path)
+            .ok()?;
+        Some(RefreshLock { path })
+    }
+}
+
+fn lock_is_live(path: &Path) -> bool {
+    fs::metadata(path)
+        .and_then(|m| m.modified())
+        .ok()
+        .and_then(|modified| SystemTime::now().duration_since(modified).ok())
+        .is_some_and(|age| age < STALE_LOCK)
+}
+
+/// Held while a value is being fetched; releases the lock when dropped.
+#[derive(Debug)]
+pub struct RefreshLock {
+    path: PathBuf,
+}
+
+impl Drop for RefreshLock {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.path);
+    }
+}
+
+/// Fetch `remote`'s description and store it, unless a refresh is already running.
+///
+/// Failed fetches keep the previous description and are retried after an hour.
+///
+/// # Errors
+///
+/// Returns an error only if the result cannot be stored
+pub fn refresh(store: &DescriptionStore, remote: &RemoteUrl, ttl: Duration) -> Result<()> {
+    let Some(_lock) = store.lock(remote) else {
+        return Ok(());
+    };
+    let Some(request) = api_request(remote) else {
+        return store.put(remote, None, ttl);
+    };
+    store.put_fetched(remote, fetch_description(&request), ttl)
+}
+
+/// A placeholder in a `forge.browse` URL template.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BrowseField {
+    /// `{host}`, e.g. "git.corp.com"
+    Host,
+    /// `{path}`, e.g. "team/api"
+    Path,
+    /// `{branch}`, percent-encoded except for `/`
+    Branch,
+}
+
+impl Placeholder for BrowseField {
+    const ALL: &'static [(&'static str, Self)] = &[
+        ("host", BrowseField::Host),
+        ("path", BrowseField::Path),
+        ("branch", BrowseField::Branch),
+    ];
+}
+
+/// A repository's web page.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WebPage {
+    pub url: String,
+    /// Whether the page is the requested branch's rather than the repository's
+    pub branch_linked: bool,
+}
+
+/// The web page of `remote`, or of `branch` on it when the forge's branch
+/// links are known.
+///
+/// `templates` (`forge.browse`) take precedence. Otherwise GitHub, GitLab, and
+/// Bitbucket are recognized by host (`github.com`, `gitlab.com` and `gitlab.*`,
+/// `bitbucket.org`), and any other host gets `https://<host>/<path>`.
+///
+/// # Errors
+///
+/// Returns an error if the host's template is invalid
+pub fn web_page(
+    remote: &RemoteUrl,
+    branch: Option<&str>,
+    templates: &BTreeMap<String, BrowseTemplate>,
+) -> Result<WebPage> {
+    let configured = templates
+        .iter()
+        .find(|(host, _)| host.eq_ignore_ascii_case(&remote.host))
+        .map(|(_, template)| template);
+
+    let Some(configured) = configured else {
+        let host = remote.host.as_str();
+        // What follows the repository page in a branch's URL
+        let tree = if host == "github.com" {
+            Some("/tree/")
+        } else if host == "gitlab.com" || host.starts_with("gitlab.") {
+            Some("/-/tree/")
+        } else if host == "bitbucket.org" {
+            Some("/src/")
+        } else {
+            None
+        };
+        return Ok(match (branch, tree) {
+            (Some(branch), Some(tree)) => WebPage {
+                url: format!("{}{}{}", remote.https_url(), tree, encode_branch(branch)),
+                branch_linked: true,
+            },
+            _ => WebPage {
+                url: remote.https_url(),
+                branch_linked: false,
+            },
+        });
+    };
+
+    let (template, branch_linked) = match (branch, configured.branch_url.as_deref()) {
+        (Some(_), Some(branch_url)) => (branch_url, true),
+        _ => (configured.url.as_str(), false),
+    };
+    let template = Template::<BrowseField>::parse(template)
+        .with_context(|| format!("Invalid forge.browse template for {}", remote.host))?;
+    let mut url = String::new();
+    for part in template.parts() {
+        match part {
+            Part::Text(text) => url.push_str(text),
+            Part::Field(BrowseField::Host) => url.push_str(&remote.host),
+            Part::Field(BrowseField::Path) => url.push_str(&remote.path),
+            Part::Field(BrowseField::Branch) => url.push_str(&encode_branch(branch.unwrap_or(""))),
+        }
+    }
+    Ok(WebPage { url, branch_linked })
+}
+
+/// Percent-encode a branch name for a URL path, keeping `/` so
+/// `feature/login` stays readable.
+fn encode_branch(branch: &str) -> String {
+    let mut encoded = String::new();
+    for byte in branch.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' | b'/' => {
+                encoded.push(byte as char)
+            }
+            _ => encoded.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+    encoded
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn remote(url: &str) -> RemoteUrl {
+        RemoteUrl::parse(url).unwrap()
+    }
+
+    #[test]
+    fn test_api_request_for_supported_forges() {
+        let github = api_request(&remote("git@github.com:me/foo.git")).unwrap();
+        assert_eq!(github.url, "https://api.github.com/repos/me/foo");
+
+        let gitlab = api_request(&remote("https://gitlab.com/group/sub/project")).unwrap();
+        assert_eq!(
+            gitlab.url,
+            "https://gitlab.com/api/v4/projects/group%2Fsub%2Fproject"
+        );
+
+        assert!(api_request(&remote("git@git.example.com:me/foo.git")).is_none());
+    }
+
+    #[test]
+    fn test_gitlab_token_only_goes_to_configured_hosts() {
+        assert!(gitlab_token_host("gitlab.com", None));
+        assert!(!gitlab_token_host("gitlab.attacker.tld", None));
+        assert!(gitlab_token_host(
+            "gitlab.corp.com",
+            Some("https://gitlab.corp.com/")
+        ));
+        assert!(!gitlab_token_host(
+            "gitlab.other.com",
+            Some("gitlab.corp.com")
+        ));
+    }
+
+    #[test]
+    fn test_curl_config_cannot_be_injected() {
+        let request = ApiRequest {
+            url: "https://api.github.com/repos/me/a\"\noutput = \"/tmp/x".to_string(),
+            auth_header: Some("Authorization: Bearer t\\\nverbose".to_string()),
+        };
+        assert_eq!(
+            curl_config(&request),
+            "url = \"https://api.github.com/repos/me/a\\\"\\noutput = \\\"/tmp/x\"\n\
+             header = \"Authorization: Bearer t\\\\\\nverbose\"\n"
+        );
+        assert_eq!(
+            origin("https://API.github.com:443/x"),
+            Some("https://API.github.com:443")
+        );
+        assert_eq!(origin("https://gitlab.com"), Some("https://gitlab.com"));
+        assert_eq!(origin("/relative"), None);
+    }
+
+    #[test]
+    fn test_redirect_stays_on_https() {
+        let request = ApiRequest {
+            url: "https://api.github.com/repos/me/old".to_string(),
+            auth_header: Some("Authorization: Bearer t".to_string()),
+        };
+        let same = redirect(&request, "https://api.github.com/repositories/1").unwrap();
+        assert_eq!(same.url, "https://api.github.com/repositories/1");
+        assert!(same.auth_header.is_some());
+        let other = redirect(&request, "HTTPS://elsewhere.example/x").unwrap();
+        assert!(other.auth_header.is_none());
+
+        for location in [
+            "file:///etc/passwd",
+            "gopher://evil.example/",
+            "http://api.github.com/x",
+            "/relative",
+        ] {
+            assert!(redirect(&request, location).is_err(), "{}", location);
+        }
+    }
+
+    #[test]
+    fn test_web_page_for_known_forges() {
+        let none = BTreeMap::new();
+        let page = |url: &str, branch: Option<&str>| web_page(&remote(url), branch, &none).unwrap();
+
+        assert_eq!(
+            page("git@github.com:me/foo.git", None).url,
+            "https://github.com/me/foo"
+        );
+        assert_eq!(
+            page("git@github.com:me/foo.git", Some("feature/a#1")).url,
+            "https://github.com/me/foo/tree/feature/a%231"
+        );
+        assert_eq!(
+            page("ssh://git@gitlab.com/group/sub/project.git", Some("main")).url,
+            "https://gitlab.com/group/sub/project/-/tree/main"
+        );
+        assert_eq!(
+            page("git@bitbucket.org:team/repo.git", Some("dev")).url,
+            "https://bitbucket.org/team/repo/src/dev"
+        );
+
+        // Unknown forges get the repository page only
+        let unknown = page("git@git.example.com:me/foo.git", Some("main"));
+        assert_eq!(unknown.url, "https://git.example.com/me/foo");
+        assert!(!unknown.branch_linked);
+    }
+
+    #[test]
+    fn test_web_page_uses_configured_template() {
+        let mut templates = BTreeMap::new();
+        templates.insert(
+            "git.corp.com".to_string(),
+            BrowseTemplate {
+                url: "https://{host}/{path}".to_string(),
+                branch_url: Some("https://{host}/{path}/src/branch/{branch}".to_string()),
+            },
+        );
+        let remote = remote("git@git.corp.com:team/api.git");
+
+        let page = web_page(&remote, Some("main"), &templates).unwrap();
+        assert_eq!(page.url, "https://git.corp.com/team/api/src/branch/main");
+        assert!(page.branch_linked);
+        assert_eq!(
+            web_page(&remote, None, &templates).unwrap().url,
+            "https://git.corp.com/team/api"
+        );
+    }
+
+    #[test]
+    fn test_parse_description() {
+        assert_eq!(
+            parse_description(br#"{"description": " Fast navigator "}"#).unwrap(),
+            Some("Fast navigator".to_string())
+        );
+        assert_eq!(parse_description(br#"{"description": ""}"#).unwrap(), None);
+        assert_eq!(
+            parse_description(br#"{"description": null}"#).unwrap(),
+            None
+        );
+        assert!(parse_description(b"<html>").is_err());
+    }
+
+    #[test]
+    fn test_store_get_put_and_expiry() {
+        let tmp = tempfile::tempdir().unwrap();
+        let store = DescriptionStore::new(tmp.path(), "descriptions");
+        let foo = remote("https://github.com/me/foo");
+        let bar = remote("https://github.com/me/bar");
+
+        assert_eq!(store.get(&foo), Lookup::Missing);
+
+        store
+            .put(&foo, Some("Foo!".to_string()), Duration::from_secs(600))
+            .unwrap();
+        store.put(&bar, None, Duration::ZERO).unwrap();
+
+        assert_eq!(store.get(&foo), Lookup::Fresh(Some("Foo!".to_string())));
+        assert_eq!(store.get(&bar), Lookup::Stale(None));
+        assert!(tmp.path().join("descriptions").is_dir());
+    }
+
+    #[test]
+    fn test_store_reads_entries_from_before_other_values() {
+        let tmp = tempfile::tempdir().unwrap();
+        let store = DescriptionStore::new(tmp.path(), "descriptions");
+        let foo = remote("https://github.com/me/foo");
+        store.put(&foo, None, Duration::from_secs(600)).unwrap();
+
+        // Entries used to name their value `description`
+        let path = store.entry_path(&foo, "cache");
+        let old = format!(
+            r#"{{"remote":"{}","description":"Foo!","expires_at":{}}}"#,
+            foo,
+            now() + 600
+        );
+        std::fs::write(&path, old).unwrap();
+        assert_eq!(store.get(&foo), Lookup::Fresh(Some("Foo!".to_string())));
+    }
+
+    #[test]
+    fn test_refresh_lock_is_exclusive() {
+        let tmp = tempfile::tempdir().unwrap();
+        let store = DescriptionStore::new(tmp.path(), "descriptions");
+        let foo = remote("https://github.com/me/foo");
+
+        let lock = store.lock(&foo).unwrap();
+        assert!(store.is_refreshing(&foo));
+        assert!(store.lock(&foo).is_none());
+        drop(lock);
+        assert!(!store.is_refreshing(&foo));
+        assert!(store.lock(&foo).is_some());
+    }
+
+    #[test]
+    fn test_refresh_stores_none_for_unsupported_forge() {
+        let tmp = tempfile::tempdir().unwrap();
+        let store = DescriptionStore::new(tmp.path(), "descriptions");
+        let selfhosted = remote("git@git.example.com:me/foo.git");
+
+        refresh(&store, &selfhosted, Duration::from_secs(600)).unwrap();
+        assert_eq!(store.get(&selfhosted), Lookup::Fresh(None));
+    }
+}
@@ -1,7 +1,10 @@
 use anyhow::{Context, Result};
 use std::io::Write;
-use std::process::{Command, Stdio};
+use std::process::{Command, Output, Stdio};
+use std::sync::mpsc::Receiver;
+use std::thread;
 
+use crate::actions::{self, Action, Selection};
 use crate::config::{Config, UiConfig};
 use crate::scanner::{format_display, EnrichedRepo};
 
@@ -16,10 +19,12 @@ use crate::scanner::{format_display, EnrichedRepo};
 /// * `config` - Configuration for UI and preview settings
 /// * `preview_binary` - Path to the gitnav binary (for preview commands)
 /// * `initial_query` - Optional query string to pre-fill in fzf
+/// * `multi` - Let the user mark several repositories (`fzf --multi`)
 ///
 /// # Returns
 ///
-/// - `Ok(Some(path))` if the user selected a repository
+/// - `Ok(Some(selection))` with the selected repositories (exactly one unless
+///   `multi`) and the action whose key picked them
 /// - `Ok(None)` if the user cancelled (ESC or Ctrl-C)
 /// - `Err(...)` if fzf cannot be spawned or communication fails
 pub fn select_repo(
@@ -27,17 +32,15 @@ pub fn select_repo(
     config: &Config,
     preview_binary: &str,
     initial_query: Option<&str>,
-) -> Result<Option<String>> {
+    multi: bool,
+) -> Result<Option<Selection>> {
     // fzf always renders ANSI in its list, so force color on
     let use_color = std::env::var("NO_COLOR").is_err();
     let name_width = repos.iter().map(|r| r.name.len()).max().unwrap_or(0);
 
     let input = repos
         .iter()
-        .map(|repo| {
-            let display = format_display(repo, name_width, use_color, &config.ui.badge_style);
-            format!("{}\t{}", display, repo.path.display())
-        })
+        .map(|repo| format_line(repo, name_width, use_color, config))
         .collect::<Vec<_>>()
         .join("\n");
 
@@ -45,47 +48,208 @@ pub fn select_repo(
         return Ok(None);
     }
 
-    // Build fzf command
-    let mut cmd = Command::new("fzf");
+    let mut cmd = fzf_command(config, preview_binary, initial_query, multi);
+    let mut child = cmd
+        .spawn()
+        .with_context(|| format!("Failed to spawn {}", config.finder.binary))?;
+
+    // Write input to fzf
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin
+            .write_all(input.as_bytes())
+            .context("Failed to write to fzf stdin")?;
+    }
+
+    // Wait for fzf to complete and capture output
+    let output = child.wait_with_output().context("Failed to wait for fzf")?;
+    Ok(parse_selection(&output, config))
+}
+
+/// Run fzf on repositories that are still being discovered.
+///
+/// fzf opens immediately and each repository is added to the list as it
+/// arrives on `repos`; the list is complete once the sender is dropped. Names
+/// are padded to a fixed width because the longest name is not known yet.
+///
+/// Without an initial query fzf exits on its own if the input ends up empty,
+/// which is reported like a cancellation (`Ok(None)`).
+///
+/// # Errors
+///
+/// Returns an error if fzf cannot be spawned or communication fails
+pub fn select_repo_streaming(
+    repos: Receiver<EnrichedRepo>,
+    config: &Config,
+    preview_binary: &str,
+    initial_query: Option<&str>,
+    multi: bool,
+) -> Result<Option<Selection>> {
+    let use_color = std::env::var("NO_COLOR").is_err();
+
+    let mut cmd = fzf_command(config, preview_binary, initial_query, multi);
+    if initial_query.is_none() {
+        cmd.arg("--exit-0");
+    }
+    let mut child = cmd
+        .spawn()
+        .with_context(|| format!("Failed to spawn {}", config.finder.binary))?;
+    let mut stdin = child.stdin.take().context("Failed to open fzf stdin")?;
+
+    // The writer is not joined: once fzf exits its next write fails, which
+    // drops `repos` and lets the scanner notice nobody is listening
+    let line_config = config.clone();
+    thread::spawn(move || {
+        for repo in repos {
+            let line = format_line(&repo, STREAM_NAME_WIDTH, use_color, &line_config);
+            if writeln!(stdin, "{}", line).is_err() {
+                break;
+            }
+        }
+    });
+
+    let output = child.wait_with_output().context("Failed to wait for fzf")?;
+    Ok(parse_selection(&output, config))
+}
+
+/// Let the user pick one of `labels` in fzf, for the action menu.
+///
+/// Uses the `[ui]` look and `[finder]` binary, without a preview or action keys.
+///
+/// # Returns
+///
+/// The index of the chosen label, or `None` if the user cancelled
+///
+/// # Errors
+///
+/// Returns an error if fzf cannot be spawned or communication fails
+pub fn select_menu(labels: &[&str], config: &Config) -> Result<Option<usize>> {
+    let mut cmd = Command::new(&config.finder.binary);
+    apply_ui_config(&mut cmd, &config.ui);
+    cmd.args(&config.finder.extra_args);
+    cmd.stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::inherit());
+    let mut child = cmd
+        .spawn()
+        .with_context(|| format!("Failed to spawn {}", config.finder.binary))?;
+
+    let input: String = labels
+        .iter()
+        .enumerate()
+        .map(|(index, label)| format!("{}\t{}\n", label, index))
+        .collect();
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin
+            .write_all(input.as_bytes())
+            .context("Failed to write to fzf stdin")?;
+    }
+
+    let output = child.wait_with_output().context("Failed to wait for fzf")?;
+    if !output.status.success() {
+        return Ok(None);
+    }
+    Ok(selected_paths(&String::from_utf8_lossy(&output.stdout))
+        .first()
+        .and_then(|index| index.parse().ok()))
+}
+
+/// Padding for repository names when streaming, since the longest name is not
+/// known until the scan finishes.
+const STREAM_NAME_WIDTH: usize = 20;
+
+/// One fzf input line: the display column, a tab, and the path.
+fn format_line(repo: &EnrichedRepo, name_width: usize, use_color: bool, config: &Config) -> String {
+    let display = format_display(repo, name_width, use_color, &config.ui.badge_style);
+    format!("{}\t{}", display, repo.path.display())
+}
+
+/// Build the fzf command with UI settings, preview, and piped stdin/stdout.
+///
+/// Runs `finder.binary`, with `finder.extra_args` after gitnav's arguments.
+fn fzf_command(
+    config: &Config,
+    preview_binary: &str,
+    initial_query: Option<&str>,
+    multi: bool,
+) -> Command {
+    let mut cmd = Command::new(&config.finder.binary);
 
     apply_ui_config(&mut cmd, &config.ui);
+    if multi {
+        cmd.arg("--multi");
+    }
 
     // Add preview command that calls gitnav --preview
     let preview_cmd = format!("{} --preview {{2}}", preview_binary);
     cmd.arg("--preview").arg(&preview_cmd);
 
+    // Action keys end fzf like Enter; the key is printed before the selection
+    let keys: Vec<&str> = actions::bindings(config)
+        .into_iter()
+        .map(|(key, _)| key)
+        .collect();
+    if !keys.is_empty() {
+        cmd.arg("--expect").arg(keys.join(","));
+    }
+
     // Pre-fill query if provided
     if let Some(query) = initial_query {
         cmd.arg("--query").arg(query);
     }
 
+    let toggle = config.keybindings.toggle_preview.trim();
+    if !toggle.is_empty() {
+        cmd.arg("--bind").arg(format!("{}:toggle-preview", toggle));
+    }
+
+    // Last, so a repeated flag wins over gitnav's default
+    cmd.args(&config.finder.extra_args);
+
     // Configure input/output
     cmd.stdin(Stdio::piped())
         .stdout(Stdio::piped())
         .stderr(Stdio::inherit());
+    cmd
+}
 
-    let mut child = cmd.spawn().context("Failed to spawn fzf process")?;
-
-    // Write input to fzf
-    if let Some(mut stdin) = child.stdin.take() {
-        stdin
-            .write_all(input.as_bytes())
-            .context("Failed to write to fzf stdin")?;
+/// The selection, or `None` if the user cancelled (ESC or Ctrl-C).
+fn parse_selection(output: &Output, config: &Config) -> Option<Selection> {
+    if !output.status.success() {
+        return None;
     }
 
-    // Wait for fzf to complete and capture output
-    let output = child.wait_with_output().context("Failed to wait for fzf")?;
-
-    if !output.status.success() {
-        // User cancelled (ESC or Ctrl-C)
-        return Ok(None);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let bindings = actions::bindings(config);
+    if bindings.is_empty() {
+        let paths = selected_paths(&stdout);
+        return (!paths.is_empty()).then(|| Selection::navigate(paths));
     }
+    parse_expect(&stdout, &bindings)
+}
 
-    // Parse selected line (format: display\tpath) — path is always the last tab-separated field
-    let selected = String::from_utf8_lossy(&output.stdout);
-    let path = selected.trim().split('\t').last().map(|s| s.to_string());
+/// Split `--expect` output: the first line names the key that ended fzf
+/// (empty for Enter), the rest are the selected lines.
+fn parse_expect(stdout: &str, bindings: &[(&str, Action)]) -> Option<Selection> {
+    let (key, rest) = stdout.split_once('\n').unwrap_or((stdout, ""));
+    let key = key.trim_end_matches('\r');
+    let action = bindings
+        .iter()
+        .find(|(bound, _)| *bound == key)
+        .map_or(Action::Navigate, |(_, action)| *action);
+    let paths = selected_paths(rest);
+    (!paths.is_empty()).then_some(Selection { action, paths })
+}
 
-    Ok(path)
+/// Paths from fzf's output, one selected line each (format: display\tpath).
+/// The path is always the last tab-separated field.
+fn selected_paths(stdout: &str) -> Vec<String> {
+    stdout
+        .lines()
+        .map(|line| line.trim_end_matches('\r'))
+        .filter(|line| !line.trim().is_empty())
+        .filter_map(|line| line.split('\t').next_back())
+        .map(String::from)
+        .collect()
 }
 
 /// Apply UI configuration to an fzf command.
@@ -111,6 +275,10 @@ fn apply_ui_config(cmd: &mut Command, ui: &UiConfig) {
         cmd.arg("--border");
     }
 
+    if let Some(popup) = tmux_popup(ui, std::env::var_os("TMUX").is_some()) {
+        cmd.arg("--tmux").arg(popup);
+    }
+
     // Don't sort (keep alphabetical order from scanner)
     cmd.arg("--no-sort");
 
@@ -118,9 +286,15 @@ fn apply_ui_config(cmd: &mut Command, ui: &UiConfig) {
     cmd.arg("--ansi");
 }
 
-/// Check if fzf is available and executable in the system PATH.
-pub fn is_fzf_available() -> bool {
-    Command::new("fzf")
+/// The `--tmux` popup geometry when `ui.tmux_popup` is set and fzf runs inside
+/// tmux. Outside tmux the flag is left off, so older fzf builds still work.
+fn tmux_popup(ui: &UiConfig, inside_tmux: bool) -> Option<String> {
+    (ui.tmux_popup && inside_tmux).then(|| format!("center,80%,{}%", ui.height_percent))
+}
+
+/// Check if the finder (`finder.binary`) is available and executable.
+pub fn is_fzf_available(binary: &str) -> bool {
+    Command::new(binary)
         .arg("--version")
         .stdout(Stdio::null())
         .stderr(Stdio::null())
@@ -142,10 +316,58 @@ mod tests {
             height_percent: 90,
             show_border: true,
             show_inline_meta: true,
+            cached_inline_meta: false,
             badge_style: BadgeStyle::Text,
+            stream: true,
+            finder: crate::config::FinderMode::Auto,
+            select_1: false,
+            exit_0: false,
+            editor: None,
+            tmux_popup: false,
         }
     }
 
+    #[test]
+    fn test_selected_paths() {
+        assert_eq!(
+            selected_paths("api  main\t/src/api\nweb\t/src/web\r\n\n"),
+            vec!["/src/api".to_string(), "/src/web".to_string()]
+        );
+        assert!(selected_paths("").is_empty());
+    }
+
+    #[test]
+    fn test_parse_expect() {
+        let bindings = [("ctrl-e", Action::OpenEditor)];
+        assert_eq!(
+            parse_expect("\napi\t/src/api\n", &bindings),
+            Some(Selection::navigate(vec!["/src/api".to_string()]))
+        );
+        assert_eq!(
+            parse_expect("ctrl-e\napi\t/src/api\nweb\t/src/web\n", &bindings),
+            Some(Selection {
+                action: Action::OpenEditor,
+                paths: vec!["/src/api".to_string(), "/src/web".to_string()],
+            })
+        );
+        assert_eq!(parse_expect("ctrl-e\n", &bindings), None);
+    }
+
+    #[test]
+    fn test_fzf_command_uses_finder_binary_and_extra_args() {
+        let mut config = Config::default();
+        config.finder.binary = "sk".to_string();
+        config.finder.extra_args = vec!["--cycle".to_string(), "--height=40%".to_string()];
+
+        let cmd = fzf_command(&config, "gitnav", Some("api"), false);
+        assert_eq!(cmd.get_program(), "sk");
+        let args: Vec<_> = cmd.get_args().collect();
+        assert_eq!(args[args.len() - 2..], ["--cycle", "--height=40%"]);
+        assert!(args.contains(&std::ffi::OsStr::new("--with-nth")));
+        assert!(args.contains(&std::ffi::OsStr::new("ctrl-/:toggle-preview")));
+        assert!(args.contains(&std::ffi::OsStr::new("ctrl-e,ctrl-o,ctrl-y,ctrl-r")));
+    }
+
     #[test]
     fn test_apply_ui_config_adds_arguments() {
         let mut cmd = Command::new("fzf");
@@ -161,6 +383,16 @@ mod tests {
         apply_ui_config(&mut cmd, &ui);
     }
 
+    #[test]
+    fn test_tmux_popup_only_inside_tmux() {
+        let mut ui = make_ui_config();
+        assert_eq!(tmux_popup(&ui, true), None);
+
+        ui.tmux_popup = true;
+        assert_eq!(tmux_popup(&ui, false), None);
+        assert_eq!(tmux_popup(&ui, true).as_deref(), Some("center,80%,90%"));
+    }
+
     #[test]
     fn test_ui_config_width_boundary_values() {
         for width in [0u8, 1, 50, 99, 100] {
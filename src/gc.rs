@@ -0,0 +1,137 @@
+use std::process::{Command, Stdio};
+
+use crate::parallel;
+use crate::scanner::GitRepo;
+use crate::stats;
+
+/// What `gitnav gc` runs in each repository.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Mode {
+    /// `git maintenance run --auto`: only the work git's own thresholds call
+    /// for, as after a commit (`git gc --auto`)
+    Auto,
+    /// `git maintenance run`: a full garbage collection
+    Full,
+    /// `git maintenance register`: enroll in git's scheduled background maintenance
+    Register,
+}
+
+impl Mode {
+    fn args(self) -> &'static [&'static str] {
+        match self {
+            Mode::Auto => &["maintenance", "run", "--auto", "--quiet"],
+            Mode::Full => &["maintenance", "run", "--quiet"],
+            Mode::Register => &["maintenance", "register"],
+        }
+    }
+}
+
+/// The result of maintaining one repository.
+#[derive(Debug, Clone)]
+pub struct Outcome {
+    pub repo: GitRepo,
+    /// Bytes the git directory shrank by (0 when registering), or git's reason
+    /// for failing
+    pub result: Result<u64, String>,
+}
+
+/// Run `mode` in one repository.
+///
+/// The size of the git directory is measured before and after a run, so the
+/// result is the space reclaimed. On failure, returns the first line git wrote
+/// to stderr.
+pub fn run(repo: &GitRepo, mode: Mode) -> Result<u64, String> {
+    let git_dir = git2::Repository::open(&repo.path)
+        .map(|r| r.path().to_path_buf())
+        .map_err(|e| e.message().to_string())?;
+    let size = || match mode {
+        Mode::Register => 0,
+        Mode::Auto | Mode::Full => stats::dir_size(&git_dir),
+    };
+    let before = size();
+
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(&repo.path)
+        .args(mode.args())
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        .output()
+        .map_err(|e| format!("Failed to run git: {}", e))?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(stderr
+            .lines()
+            .map(str::trim)
+            .find(|line| !line.is_empty())
+            .map(str::to_string)
+            .unwrap_or_else(|| format!("git maintenance exited with {}", output.status)));
+    }
+
+    Ok(before.saturating_sub(size()))
+}
+
+/// Run `mode` in every repository, at most `jobs` at a time, keeping input order.
+///
+/// Registering writes the global git config, which git locks, so it runs one
+/// repository at a time whatever `jobs` is. `on_done` is called from the worker
+/// threads as each repository finishes, for progress display.
+pub fn run_all<F>(repos: &[GitRepo], mode: Mode, jobs: usize, on_done: F) -> Vec<Outcome>
+where
+    F: Fn(&GitRepo) + Sync,
+{
+    let jobs = if mode == Mode::Register { 1 } else { jobs };
+    parallel::map_queued(repos, jobs, |repo| {
+        let result = run(repo, mode);
+        on_done(repo);
+        Outcome {
+            repo: repo.clone(),
+            result,
+        }
+    })
+}
+
+/// The repositories among `repos` that own their objects: linked worktrees
+/// share them with their main repository and are maintained along with it.
+pub fn main_repos(repos: Vec<GitRepo>) -> Vec<GitRepo> {
+    repos
+        .into_iter()
+        .filter(|repo| git2::Repository::open(&repo.path).is_ok_and(|r| !r.is_worktree()))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::commit;
+
+    #[test]
+    fn test_run_all_reports_failures() {
+        let tmp = tempfile::tempdir().unwrap();
+        let good = tmp.path().join("good");
+        let repo = git2::Repository::init(&good).unwrap();
+        commit(&repo, "first", None);
+        let missing = tmp.path().join("missing");
+
+        let repos = vec![GitRepo::new(good), GitRepo::new(missing.clone())];
+        let outcomes = run_all(&repos, Mode::Full, 2, |_| {});
+        assert!(outcomes[0].result.is_ok());
+        assert_eq!(outcomes[1].repo.path, missing);
+        assert!(outcomes[1].result.is_err());
+    }
+
+    #[test]
+    fn test_main_repos_skips_worktrees() {
+        let tmp = tempfile::tempdir().unwrap();
+        let api = tmp.path().join("api");
+        let repo = git2::Repository::init(&api).unwrap();
+        commit(&repo, "first", None);
+        let fix = tmp.path().join("api-fix");
+        repo.worktree("fix", &fix, None).unwrap();
+
+        let kept = main_repos(vec![GitRepo::new(api.clone()), GitRepo::new(fix)]);
+        let paths: Vec<_> = kept.into_iter().map(|r| r.path).collect();
+        assert_eq!(paths, vec![api]);
+    }
+}
@@ -0,0 +1,88 @@
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+
+/// The directories ghq clones into, as ghq itself finds them: the paths in
+/// `GHQ_ROOT` (separated like `PATH`), else every `ghq.root` in the git
+/// config, else `~/ghq`. Only roots that exist are returned, so a machine
+/// without ghq gets none.
+pub fn roots() -> Vec<PathBuf> {
+    find_roots(
+        std::env::var_os("GHQ_ROOT").as_deref(),
+        &configured_roots(),
+        dirs::home_dir().as_deref(),
+    )
+}
+
+/// Every `ghq.root` in the git config, read with git as ghq does, so includes
+/// and `~` work the same.
+fn configured_roots() -> Vec<String> {
+    Command::new("git")
+        .args(["config", "--path", "--get-all", "ghq.root"])
+        .stdin(Stdio::null())
+        .stderr(Stdio::null())
+        .output()
+        .map(|output| {
+            String::from_utf8_lossy(&output.stdout)
+                .lines()
+                .map(str::trim)
+                .filter(|line| !line.is_empty())
+                .map(str::to_string)
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+fn find_roots(
+    env: Option<&std::ffi::OsStr>,
+    configured: &[String],
+    home: Option<&Path>,
+) -> Vec<PathBuf> {
+    let mut roots: Vec<PathBuf> = match env.filter(|v| !v.is_empty()) {
+        Some(env) => std::env::split_paths(env).collect(),
+        None if !configured.is_empty() => configured
+            .iter()
+            .map(|root| PathBuf::from(shellexpand::tilde(root).to_string()))
+            .collect(),
+        None => home.map(|h| h.join("ghq")).into_iter().collect(),
+    };
+    roots.retain(|root| root.is_dir());
+    let mut seen = Vec::new();
+    roots.retain(|root| {
+        let new = !seen.contains(root);
+        seen.push(root.clone());
+        new
+    });
+    roots
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_find_roots_follows_ghq_precedence() {
+        let tmp = tempfile::tempdir().unwrap();
+        let home = tmp.path();
+        let (ghq, src, work) = (home.join("ghq"), home.join("src"), home.join("work"));
+        for dir in [&ghq, &src, &work] {
+            std::fs::create_dir_all(dir).unwrap();
+        }
+        let configured = vec![
+            src.display().to_string(),
+            home.join("gone").display().to_string(),
+            src.display().to_string(),
+        ];
+
+        // GHQ_ROOT wins over the git config, which wins over ~/ghq
+        let env = std::env::join_paths([&work, &src]).unwrap();
+        assert_eq!(
+            find_roots(Some(&env), &configured, Some(home)),
+            vec![work.clone(), src.clone()]
+        );
+        assert_eq!(find_roots(None, &configured, Some(home)), vec![src.clone()]);
+        assert_eq!(find_roots(None, &[], Some(home)), vec![ghq.clone()]);
+
+        std::fs::remove_dir(&ghq).unwrap();
+        assert!(find_roots(None, &[], Some(home)).is_empty());
+    }
+}
@@ -0,0 +1,237 @@
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+use crate::forge::{self, ApiRequest, Store};
+use crate::remote::RemoteUrl;
+
+/// Cached GitHub stats; `None` for a remote that is not on GitHub.
+pub type StatsStore = Store<Option<Stats>>;
+
+/// What the preview shows about a GitHub repository.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Stats {
+    pub stars: u64,
+    pub open_pulls: u64,
+    /// Combined checks and commit statuses of the default branch's latest
+    /// commit; absent when nothing reports on it
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ci: Option<CiStatus>,
+}
+
+/// Outcome of the CI runs on a commit.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CiStatus {
+    Success,
+    Pending,
+    Failure,
+}
+
+impl Stats {
+    /// One line for the preview, e.g. "★ 1.2k · 3 open PRs · CI ✓".
+    pub fn summary(&self) -> String {
+        let pulls = match self.open_pulls {
+            1 => "1 open PR".to_string(),
+            n => format!("{} open PRs", n),
+        };
+        let mut text = format!("★ {} · {}", abbreviate(self.stars), pulls);
+        if let Some(ci) = self.ci {
+            text.push_str(match ci {
+                CiStatus::Success => " · CI ✓",
+                CiStatus::Pending => " · CI …",
+                CiStatus::Failure => " · CI ✗",
+            });
+        }
+        text
+    }
+}
+
+/// `count` with a k/M suffix past a thousand, e.g. "1.2k".
+fn abbreviate(count: u64) -> String {
+    match count {
+        0..=999 => count.to_string(),
+        1_000..=999_999 => format!("{:.1}k", count as f64 / 1_000.0),
+        _ => format!("{:.1}M", count as f64 / 1_000_000.0),
+    }
+}
+
+/// Whether `remote` is hosted on GitHub, the only forge stats are fetched from.
+pub fn is_github(remote: &RemoteUrl) -> bool {
+    remote.host == "github.com"
+}
+
+/// Fetch the stats of a GitHub repository.
+///
+/// Takes four API requests: the repository, a search for its open pull
+/// requests, and the check runs and commit statuses of its default branch.
+///
+/// # Errors
+///
+/// Returns an error if any request fails or returns something unexpected
+pub fn fetch(request: &ApiRequest) -> Result<Stats> {
+    let repo = get_json(request, "")?;
+    let full_name = repo["full_name"]
+        .as_str()
+        .context("GitHub API response has no full_name")?;
+    let branch = repo["default_branch"]
+        .as_str()
+        .context("GitHub API response has no default_branch")?;
+
+    let search = ApiRequest {
+        url: format!(
+            "https://api.github.com/search/issues?q=repo:{}+is:pr+is:open&per_page=1",
+            full_name
+        ),
+        auth_header: request.auth_header.clone(),
+    };
+    let pulls = get_json(&search, "")?;
+    let commit = format!("/commits/{}", encode(branch));
+    let check_runs = get_json(request, &format!("{}/check-runs?per_page=100", commit))?;
+    let statuses = get_json(request, &format!("{}/status", commit))?;
+
+    Ok(Stats {
+        stars: repo["stargazers_count"].as_u64().unwrap_or(0),
+        open_pulls: pulls["total_count"].as_u64().unwrap_or(0),
+        ci: ci_status(&check_runs, &statuses),
+    })
+}
+
+/// GET `request`'s URL with `suffix` appended, as JSON.
+fn get_json(request: &ApiRequest, suffix: &str) -> Result<serde_json::Value> {
+    let request = ApiRequest {
+        url: format!("{}{}", request.url, suffix),
+        auth_header: request.auth_header.clone(),
+    };
+    let (status, body) = forge::get(&request)?;
+    let json: serde_json::Value =
+        serde_json::from_slice(&body).context("GitHub API returned invalid JSON")?;
+    if !(200..300).contains(&status) {
+        anyhow::bail!(
+            "GitHub API returned {} for {}: {}",
+            status,
+            request.url,
+            json["message"].as_str().unwrap_or("")
+        );
+    }
+    Ok(json)
+}
+
+/// Combine check runs (GitHub Actions and apps) and commit statuses (older
+/// integrations): any failure fails, then anything unfinished is pending.
+fn ci_status(check_runs: &serde_json::Value, statuses: &serde_json::Value) -> Option<CiStatus> {
+    let runs = check_runs["check_runs"].as_array().into_iter().flatten();
+    let from_runs = runs.map(|run| match run["status"].as_str() {
+        Some("completed") => match run["conclusion"].as_str() {
+            Some("failure" | "timed_out" | "cancelled" | "action_required" | "startup_failure") => {
+                CiStatus::Failure
+            }
+            _ => CiStatus::Success,
+        },
+        _ => CiStatus::Pending,
+    });
+    let contexts = statuses["statuses"].as_array().into_iter().flatten();
+    let from_statuses = contexts.map(|status| match status["state"].as_str() {
+        Some("success") => CiStatus::Success,
+        Some("pending") => CiStatus::Pending,
+        _ => CiStatus::Failure,
+    });
+    // Success < Pending < Failure, so the worst one wins
+    from_runs.chain(from_statuses).max()
+}
+
+/// Percent-encode a branch name as a single URL path segment.
+fn encode(branch: &str) -> String {
+    let mut encoded = String::new();
+    for byte in branch.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => {
+                encoded.push(byte as char)
+            }
+            _ => encoded.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+    encoded
+}
+
+/// Fetch `remote`'s stats and store them, unless a refresh is already running.
+///
+/// Failed fetches keep the previous stats and are retried after an hour.
+///
+/// # Errors
+///
+/// Returns an error only if the result cannot be stored
+pub fn refresh(store: &StatsStore, remote: &RemoteUrl, ttl: Duration) -> Result<()> {
+    let Some(_lock) = store.lock(remote) else {
+        return Ok(());
+    };
+    let request = forge::api_request(remote).filter(|_| is_github(remote));
+    let Some(request) = request else {
+        return store.put(remote, None, ttl);
+    };
+    store.put_fetched(remote, fetch(&request).map(Some), ttl)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_summary() {
+        let stats = Stats {
+            stars: 1_234,
+            open_pulls: 1,
+            ci: Some(CiStatus::Failure),
+        };
+        assert_eq!(stats.summary(), "★ 1.2k · 1 open PR · CI ✗");
+        let quiet = Stats {
+            stars: 7,
+            open_pulls: 0,
+            ci: None,
+        };
+        assert_eq!(quiet.summary(), "★ 7 · 0 open PRs");
+        assert_eq!(abbreviate(2_500_000), "2.5M");
+    }
+
+    #[test]
+    fn test_ci_status() {
+        let runs = |runs: serde_json::Value| json!({ "check_runs": runs });
+        let statuses = |statuses: serde_json::Value| json!({ "statuses": statuses });
+        let passed = json!({"status": "completed", "conclusion": "success"});
+        let skipped = json!({"status": "completed", "conclusion": "skipped"});
+
+        assert_eq!(ci_status(&runs(json!([])), &statuses(json!([]))), None);
+        assert_eq!(
+            ci_status(
+                &runs(json!([passed.clone(), skipped])),
+                &statuses(json!([]))
+            ),
+            Some(CiStatus::Success)
+        );
+        assert_eq!(
+            ci_status(
+                &runs(json!([passed, {"status": "in_progress"}])),
+                &statuses(json!([{"state": "success"}]))
+            ),
+            Some(CiStatus::Pending)
+        );
+        assert_eq!(
+            ci_status(
+                &runs(json!([{"status": "queued"}])),
+                &statuses(json!([{"state": "error"}]))
+            ),
+            Some(CiStatus::Failure)
+        );
+    }
+
+    #[test]
+    fn test_refresh_stores_none_off_github() {
+        let tmp = tempfile::tempdir().unwrap();
+        let store = StatsStore::new(tmp.path(), "github");
+        let gitlab = RemoteUrl::parse("https://gitlab.com/group/project").unwrap();
+
+        refresh(&store, &gitlab, Duration::from_secs(600)).unwrap();
+        assert_eq!(store.get(&gitlab), forge::Lookup::Fresh(None));
+    }
+}
@@ -0,0 +1,200 @@
+use anyhow::{Context, Result};
+use std::io::{BufRead, BufReader, Read};
+use std::path::Path;
+use std::process::{Command, Stdio};
+
+use crate::parallel;
+use crate::scanner::GitRepo;
+
+/// How `gitnav grep` searches each repository.
+#[derive(Debug, Clone, Default)]
+pub struct Options {
+    /// Match regardless of case (`git grep -i`)
+    pub ignore_case: bool,
+    /// Take the pattern literally instead of as an extended regex
+    pub fixed_strings: bool,
+    /// Stop after this many matching lines per repository (0 = no limit)
+    pub max_per_repo: usize,
+}
+
+/// A matching line.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Match {
+    /// File path relative to the repository root
+    pub file: String,
+    pub line: usize,
+    pub text: String,
+}
+
+/// The matches in one repository.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RepoMatches {
+    pub matches: Vec<Match>,
+    /// The search stopped at `max_per_repo` matches
+    pub truncated: bool,
+}
+
+/// Search the tracked files of every repository, `jobs` at a time.
+///
+/// Results keep the order of `repos`; a repository git cannot search carries
+/// its error.
+pub fn search_all(
+    repos: &[GitRepo],
+    pattern: &str,
+    options: &Options,
+    jobs: usize,
+) -> Vec<Result<RepoMatches>> {
+    parallel::map_queued(repos, jobs, |repo| search(&repo.path, pattern, options))
+}
+
+/// Search the tracked files of the repository at `path` with `git grep`.
+///
+/// Binary files are skipped. With a `max_per_repo` limit, git is stopped once
+/// that many lines matched.
+///
+/// # Errors
+///
+/// Returns an error if git cannot be started or fails (e.g. an invalid regex)
+pub fn search(path: &Path, pattern: &str, options: &Options) -> Result<RepoMatches> {
+    let mut cmd = Command::new("git");
+    cmd.arg("-C")
+        .arg(path)
+        .args(["grep", "-n", "-z", "-I", "--no-color"]);
+    if options.ignore_case {
+        cmd.arg("-i");
+    }
+    cmd.arg(if options.fixed_strings { "-F" } else { "-E" });
+    let mut child = cmd
+        .arg("-e")
+        .arg(pattern)
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .context("Failed to run git")?;
+
+    let mut result = RepoMatches::default();
+    if let Some(stdout) = child.stdout.take() {
+        for line in BufReader::new(stdout).split(b'\n') {
+            let line = line.context("Failed to read git grep output")?;
+            if options.max_per_repo > 0 && result.matches.len() == options.max_per_repo {
+                result.truncated = true;
+                break;
+            }
+            if let Some(found) = parse_line(&String::from_utf8_lossy(&line)) {
+                result.matches.push(found);
+            }
+        }
+    }
+    if result.truncated {
+        let _ = child.kill();
+        let _ = child.wait();
+        return Ok(result);
+    }
+
+    let mut stderr = String::new();
+    if let Some(mut pipe) = child.stderr.take() {
+        let _ = pipe.read_to_string(&mut stderr);
+    }
+    let status = child.wait().context("Failed to wait for git")?;
+    // git grep exits with 1 when nothing matched
+    if !status.success() && status.code() != Some(1) {
+        anyhow::bail!(
+            "git grep failed: {}",
+            stderr.lines().next().unwrap_or("").trim()
+        );
+    }
+    Ok(result)
+}
+
+/// Parse one line of `git grep -n -z` output: `file\0line\0text`.
+fn parse_line(line: &str) -> Option<Match> {
+    let mut fields = line.splitn(3, '\0');
+    let file = fields.next()?;
+    let number = fields.next()?.parse().ok()?;
+    let text = fields.next()?;
+    Some(Match {
+        file: file.to_string(),
+        line: number,
+        text: text.trim_end_matches('\r').to_string(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn repo_with(files: &[(&str, &str)]) -> tempfile::TempDir {
+        let dir = tempfile::tempdir().unwrap();
+        let repo = git2::Repository::init(dir.path()).unwrap();
+        let mut index = repo.index().unwrap();
+        for (name, contents) in files {
+            std::fs::write(dir.path().join(name), contents).unwrap();
+            index.add_path(Path::new(name)).unwrap();
+        }
+        index.write().unwrap();
+        dir
+    }
+
+    #[test]
+    fn test_parse_line() {
+        assert_eq!(
+            parse_line("src/a b.rs\x0012\x00    let x = 1; // TODO"),
+            Some(Match {
+                file: "src/a b.rs".to_string(),
+                line: 12,
+                text: "    let x = 1; // TODO".to_string(),
+            })
+        );
+        assert_eq!(parse_line("Binary file x matches"), None);
+    }
+
+    #[test]
+    fn test_search_finds_tracked_matches() {
+        let dir = repo_with(&[("a.txt", "one\nTODO two\n"), ("b.txt", "todo three\n")]);
+        std::fs::write(dir.path().join("untracked.txt"), "TODO\n").unwrap();
+
+        let found = search(dir.path(), "TODO", &Options::default()).unwrap();
+        assert_eq!(
+            found.matches,
+            vec![Match {
+                file: "a.txt".to_string(),
+                line: 2,
+                text: "TODO two".to_string(),
+            }]
+        );
+
+        let options = Options {
+            ignore_case: true,
+            ..Options::default()
+        };
+        assert_eq!(
+            search(dir.path(), "todo", &options).unwrap().matches.len(),
+            2
+        );
+
+        let options = Options {
+            fixed_strings: true,
+            ..Options::default()
+        };
+        assert!(search(dir.path(), "T.DO", &options)
+            .unwrap()
+            .matches
+            .is_empty());
+    }
+
+    #[test]
+    fn test_search_stops_at_limit() {
+        let dir = repo_with(&[("a.txt", "x\nx\nx\n")]);
+        let options = Options {
+            max_per_repo: 2,
+            ..Options::default()
+        };
+        let found = search(dir.path(), "x", &options).unwrap();
+        assert_eq!(found.matches.len(), 2);
+        assert!(found.truncated);
+
+        let found = search(dir.path(), "nothing", &options).unwrap();
+        assert!(found.matches.is_empty() && !found.truncated);
+    }
+}
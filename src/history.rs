@@ -0,0 +1,311 @@
+use anyhow::Result;
+use rusqlite::params;
+use std::path::{Path, PathBuf};
+
+use crate::state::{self, State};
+
+/// Visits kept per profile; older visits are dropped.
+const MAX_ENTRIES: usize = 500;
+
+/// One visit to a repository.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Visit {
+    pub path: PathBuf,
+    /// Unix timestamp of the visit
+    pub at: i64,
+}
+
+/// Repositories picked in gitnav, oldest first.
+///
+/// Stored in the `visits` table of the state database, one row per visit and
+/// separately for each profile. Saving only inserts the new visits and
+/// deletes the dropped ones, and `gitnav clear-cache` leaves history alone.
+#[derive(Debug)]
+pub struct History {
+    state: State,
+    profile: String,
+    visits: Vec<Visit>,
+    /// Visits recorded since loading, not yet in the database
+    pending: Vec<Visit>,
+    /// Repositories whose visits were dropped since loading
+    removed: Vec<PathBuf>,
+}
+
+impl History {
+    /// Load the history of `profile` from `state`, first importing the
+    /// `history` file (`<timestamp>\t<path>` lines) that earlier versions kept
+    /// in the profile's cache directory. Malformed lines are skipped.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the database or the old file cannot be read
+    pub fn load(state: State, profile: Option<&str>, cache_dir: &Path) -> Result<Self> {
+        let profile = profile.unwrap_or_default().to_string();
+        state.import_once(&cache_dir.join("history"), |tx, contents| {
+            for line in contents.lines() {
+                let Some((at, path)) = line.split_once('\t') else {
+                    continue;
+                };
+                if let Ok(at) = at.parse::<i64>() {
+                    tx.execute(
+                        "INSERT INTO visits (profile, path, visited_at) VALUES (?1, ?2, ?3)",
+                        params![profile, path, at],
+                    )?;
+                }
+            }
+            Ok(())
+        })?;
+
+        let visits = state
+            .conn()
+            .prepare("SELECT path, visited_at FROM visits WHERE profile = ?1 ORDER BY id")?
+            .query_map([&profile], |row| {
+                Ok(Visit {
+                    path: PathBuf::from(row.get::<_, String>(0)?),
+                    at: row.get(1)?,
+                })
+            })?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+
+        let mut history = Self {
+            state,
+            profile,
+            visits,
+            pending: Vec::new(),
+            removed: Vec::new(),
+        };
+        history.trim();
+        Ok(history)
+    }
+
+    /// Record a visit, dropping the oldest entries beyond the cap.
+    pub fn record(&mut self, path: &Path, at: i64) {
+        let visit = Visit {
+            path: path.to_path_buf(),
+            at,
+        };
+        self.visits.push(visit.clone());
+        self.pending.push(visit);
+        self.trim();
+    }
+
+    /// Drop the oldest visits beyond the cap. The database is trimmed the
+    /// same way on save.
+    fn trim(&mut self) {
+        if self.visits.len() > MAX_ENTRIES {
+            let excess = self.visits.len() - MAX_ENTRIES;
+            self.visits.drain(..excess);
+        }
+        if self.pending.len() > MAX_ENTRIES {
+            let excess = self.pending.len() - MAX_ENTRIES;
+            self.pending.drain(..excess);
+        }
+    }
+
+    /// The most recent visit to each repository that still exists, newest first.
+    pub fn recent(&self) -> Vec<&Visit> {
+        let mut seen = std::collections::HashSet::new();
+        self.visits
+            .iter()
+            .rev()
+            .filter(|v| seen.insert(&v.path))
+            .filter(|v| v.path.is_dir())
+            .collect()
+    }
+
+    /// The most recently visited repository other than the one containing `cwd`.
+    ///
+    /// This is what `gn -` goes back to.
+    pub fn previous(&self, cwd: &Path) -> Option<&Visit> {
+        let cwd = cwd.canonicalize().unwrap_or_else(|_| cwd.to_path_buf());
+        self.recent().into_iter().find(|v| {
+            let path = v.path.canonicalize().unwrap_or_else(|_| v.path.clone());
+            !cwd.starts_with(&path)
+        })
+    }
+
+    /// Drop the visits to repositories that no longer exist. Returns their
+    /// paths, each once, newest first.
+    pub fn remove_missing(&mut self) -> Vec<PathBuf> {
+        let mut removed: Vec<PathBuf> = Vec::new();
+        for visit in self.visits.iter().rev() {
+            if !visit.path.is_dir() && !removed.contains(&visit.path) {
+                removed.push(visit.path.clone());
+            }
+        }
+        self.visits.retain(|v| !removed.contains(&v.path));
+        self.pending.retain(|v| !removed.contains(&v.path));
+        self.removed.extend(removed.iter().cloned());
+        removed
+    }
+
+    /// Write the new and dropped visits to the database.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the database cannot be written
+    pub fn save(&mut self) -> Result<()> {
+        let tx = self.state.write()?;
+        for path in &self.removed {
+            tx.execute(
+                "DELETE FROM visits WHERE profile = ?1 AND path = ?2",
+                params![self.profile, state::path_text(path)],
+            )?;
+        }
+        for visit in &self.pending {
+            tx.execute(
+                "INSERT INTO visits (profile, path, visited_at) VALUES (?1, ?2, ?3)",
+                params![self.profile, state::path_text(&visit.path), visit.at],
+            )?;
+        }
+        tx.execute(
+            "DELETE FROM visits WHERE profile = ?1 AND id NOT IN
+                 (SELECT id FROM visits WHERE profile = ?1 ORDER BY id DESC LIMIT ?2)",
+            params![self.profile, MAX_ENTRIES as i64],
+        )?;
+        tx.commit()?;
+
+        self.pending.clear();
+        self.removed.clear();
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    fn load(dir: &Path, profile: Option<&str>) -> History {
+        History::load(State::open(&dir.join("state.db")).unwrap(), profile, dir).unwrap()
+    }
+
+    #[test]
+    fn test_record_save_and_reload() {
+        let tmp = tempfile::tempdir().unwrap();
+        let repo = tmp.path().join("api");
+        fs::create_dir(&repo).unwrap();
+
+        let mut history = load(tmp.path(), None);
+        history.record(&repo, 100);
+        history.save().unwrap();
+
+        let reloaded = load(tmp.path(), None);
+        assert_eq!(
+            reloaded.recent(),
+            vec![&Visit {
+                path: repo,
+                at: 100
+            }]
+        );
+    }
+
+    #[test]
+    fn test_recent_dedups_and_skips_missing() {
+        let tmp = tempfile::tempdir().unwrap();
+        let (api, web) = (tmp.path().join("api"), tmp.path().join("web"));
+        fs::create_dir(&api).unwrap();
+        fs::create_dir(&web).unwrap();
+
+        let mut history = load(tmp.path(), None);
+        history.record(&api, 1);
+        history.record(&web, 2);
+        history.record(&tmp.path().join("deleted"), 3);
+        history.record(&api, 4);
+
+        let recent: Vec<(&Path, i64)> = history
+            .recent()
+            .iter()
+            .map(|v| (v.path.as_path(), v.at))
+            .collect();
+        assert_eq!(recent, vec![(api.as_path(), 4), (web.as_path(), 2)]);
+    }
+
+    #[test]
+    fn test_remove_missing() {
+        let tmp = tempfile::tempdir().unwrap();
+        let api = tmp.path().join("api");
+        fs::create_dir(&api).unwrap();
+        let gone = tmp.path().join("gone");
+
+        let mut history = load(tmp.path(), None);
+        history.record(&gone, 1);
+        history.record(&api, 2);
+        history.record(&gone, 3);
+
+        assert_eq!(history.remove_missing(), vec![gone]);
+        assert_eq!(history.visits, vec![Visit { path: api, at: 2 }]);
+        assert!(history.remove_missing().is_empty());
+    }
+
+    #[test]
+    fn test_previous_skips_current_repo() {
+        let tmp = tempfile::tempdir().unwrap();
+        let (api, web) = (tmp.path().join("api"), tmp.path().join("web"));
+        fs::create_dir_all(api.join("src")).unwrap();
+        fs::create_dir(&web).unwrap();
+
+        let mut history = load(tmp.path(), None);
+        history.record(&web, 1);
+        history.record(&api, 2);
+
+        assert_eq!(history.previous(&api.join("src")).unwrap().path, web);
+        assert_eq!(history.previous(tmp.path()).unwrap().path, api);
+    }
+
+    #[test]
+    fn test_history_is_capped() {
+        let tmp = tempfile::tempdir().unwrap();
+        let mut history = load(tmp.path(), None);
+        for i in 0..(MAX_ENTRIES as i64 + 10) {
+            history.record(Path::new("/src/api"), i);
+        }
+        assert_eq!(history.visits.len(), MAX_ENTRIES);
+        assert_eq!(history.visits[0].at, 10);
+    }
+
+    #[test]
+    fn test_imports_history_file_per_profile() {
+        let tmp = tempfile::tempdir().unwrap();
+        let (api, web) = (tmp.path().join("api"), tmp.path().join("web"));
+        fs::create_dir(&api).unwrap();
+        fs::create_dir(&web).unwrap();
+        let work_dir = tmp.path().join("profiles").join("work");
+        fs::create_dir_all(&work_dir).unwrap();
+        fs::write(
+            work_dir.join("history"),
+            format!("1\t{}\ngarbage line\n2\t{}\n", api.display(), web.display()),
+        )
+        .unwrap();
+
+        let state = || State::open(&tmp.path().join("state.db")).unwrap();
+        let mut work = History::load(state(), Some("work"), &work_dir).unwrap();
+        assert_eq!(work.previous(tmp.path()).unwrap().path, web);
+        work.record(&api, 3);
+        work.save().unwrap();
+
+        let work = History::load(state(), Some("work"), &work_dir).unwrap();
+        assert_eq!(work.visits.len(), 3, "the old file is imported only once");
+        assert_eq!(work.previous(tmp.path()).unwrap().path, api);
+        assert!(load(tmp.path(), None).recent().is_empty());
+    }
+
+    #[test]
+    fn test_save_trims_and_drops_removed_visits() {
+        let tmp = tempfile::tempdir().unwrap();
+        let gone = tmp.path().join("gone");
+        let mut history = load(tmp.path(), None);
+        for i in 0..(MAX_ENTRIES as i64 + 10) {
+            history.record(tmp.path(), i);
+        }
+        history.record(&gone, 1000);
+        history.save().unwrap();
+        assert_eq!(load(tmp.path(), None).visits.len(), MAX_ENTRIES);
+
+        history.remove_missing();
+        history.save().unwrap();
+        let reloaded = load(tmp.path(), None);
+        assert_eq!(reloaded.visits.len(), MAX_ENTRIES - 1);
+        assert!(reloaded.visits.iter().all(|v| v.path != gone));
+    }
+}
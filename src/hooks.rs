@@ -0,0 +1,295 @@
+use anyhow::{Context, Result};
+use std::fs;
+use std::process::{Command, Stdio};
+
+use crate::config::HooksConfig;
+
+/// A point in a gitnav run where a user-defined hook can run.
+// Variants mirror the `[hooks]` keys
+#[allow(clippy::enum_variant_names)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Event {
+    /// A filesystem scan is about to start
+    PreScan,
+    /// A filesystem scan finished
+    PostScan,
+    /// A fresh scan was written to the cache
+    PostCacheRefresh,
+    /// A repository was picked in the interactive picker
+    PostSelect,
+    /// A repository was cloned
+    PostClone,
+}
+
+impl Event {
+    /// The event's config key, also passed to hooks as `GITNAV_EVENT`.
+    pub fn name(self) -> &'static str {
+        match self {
+            Event::PreScan => "pre_scan",
+            Event::PostScan => "post_scan",
+            Event::PostCacheRefresh => "post_cache_refresh",
+            Event::PostSelect => "post_select",
+            Event::PostClone => "post_clone",
+        }
+    }
+
+    fn command(self, hooks: &HooksConfig) -> Option<&str> {
+        match self {
+            Event::PreScan => hooks.pre_scan.as_deref(),
+            Event::PostScan => hooks.post_scan.as_deref(),
+            Event::PostCacheRefresh => hooks.post_cache_refresh.as_deref(),
+            Event::PostSelect => hooks.post_select.as_deref(),
+            Event::PostClone => hooks.post_clone.as_deref(),
+        }
+        .filter(|c| !c.trim().is_empty())
+    }
+}
+
+/// Placeholders hook commands can use, and the variable each stands for.
+const PLACEHOLDERS: &[(&str, &str)] = &[
+    ("event", "GITNAV_EVENT"),
+    ("path", "GITNAV_REPO_PATH"),
+    ("name", "GITNAV_REPO_NAME"),
+    ("url", "GITNAV_REPO_URL"),
+    ("count", "GITNAV_REPO_COUNT"),
+    ("duration_ms", "GITNAV_DURATION_MS"),
+    ("search_paths", "GITNAV_SEARCH_PATHS"),
+    ("cache_dir", "GITNAV_CACHE_DIR"),
+];
+
+/// Environment variable the shell wrappers from `gitnav init` set to their
+/// shell, asking for the `post_cd` hook along with the path to change into.
+pub const SHELL_ENV: &str = "GITNAV_SHELL";
+
+/// Environment variable naming the empty file the shell wrappers create for
+/// gitnav to write the `post_cd` hook to. The hook never goes to stdout, so
+/// nothing in the path the wrapper `cd`s into can be run as a command.
+pub const POST_CD_FILE_ENV: &str = "GITNAV_POST_CD_FILE";
+
+/// Replace each placeholder in `command` (`{name}`) with `value(variable)`.
+/// Other braces, such as `${HOME}` or `awk '{print}'`, are left alone.
+fn expand(command: &str, value: impl Fn(&str) -> String) -> String {
+    let mut expanded = String::new();
+    let mut rest = command;
+    while let Some(start) = rest.find('{') {
+        expanded.push_str(&rest[..start]);
+        rest = &rest[start..];
+        let placeholder = rest.find('}').and_then(|end| {
+            let &(_, var) = PLACEHOLDERS
+                .iter()
+                .find(|(name, _)| *name == &rest[1..end])?;
+            Some((end, var))
+        });
+        match placeholder {
+            // `${name}` is the shell's
+            Some((end, var)) if !expanded.ends_with('$') => {
+                expanded.push_str(&value(var));
+                rest = &rest[end + 1..];
+            }
+            _ => {
+                expanded.push('{');
+                rest = &rest[1..];
+            }
+        }
+    }
+    expanded.push_str(rest);
+    expanded
+}
+
+/// Run the hook configured for `event`, if there is one.
+///
+/// The command runs through `sh -c` (`cmd /C` on Windows) with `GITNAV_EVENT`
+/// and `env` set, and gitnav waits for it to finish. Placeholders such as
+/// `{path}` become quoted references to those variables, so any value is safe
+/// in the command. Its stdout is sent to stderr so a hook can never corrupt
+/// gitnav's own output, such as the path the shell wrapper `cd`s into.
+///
+/// # Errors
+///
+/// Returns an error if the command cannot be started or exits unsuccessfully
+pub fn run(hooks: &HooksConfig, event: Event, env: &[(&str, String)]) -> Result<()> {
+    let Some(command) = event.command(hooks) else {
+        return Ok(());
+    };
+
+    let command = &expand(command, |var| {
+        if cfg!(windows) {
+            format!("\"%{}%\"", var)
+        } else {
+            format!("\"${}\"", var)
+        }
+    });
+    let mut shell = shell_command(command);
+    shell
+        .env("GITNAV_EVENT", event.name())
+        .envs(env.iter().map(|(k, v)| (*k, v.as_str())))
+        .stdin(Stdio::null())
+        .stdout(std::io::stderr());
+
+    let status = shell
+        .status()
+        .with_context(|| format!("Failed to run {} hook: {}", event.name(), command))?;
+    if !status.success() {
+        anyhow::bail!("{} hook exited with {}: {}", event.name(), status, command);
+    }
+    Ok(())
+}
+
+/// The `post_cd` hook for a shell wrapper to run after changing into a
+/// repository, in `shell`'s syntax (`bash`, `zsh`, `fish`, or `powershell`).
+///
+/// The hook runs in the user's own shell rather than a child process, so it
+/// can change its environment (`source .venv/bin/activate`). Placeholders
+/// become values from `env` quoted for `shell`. Returns `None` when no hook
+/// is set or the shell cannot run one.
+pub fn post_cd(hooks: &HooksConfig, shell: &str, env: &[(&str, String)]) -> Option<String> {
+    let command = hooks.post_cd.as_deref().filter(|c| !c.trim().is_empty())?;
+    let quote: fn(&str) -> String = match shell {
+        "bash" | "zsh" => |value| format!("'{}'", value.replace('\'', "'\\''")),
+        "fish" => |value| format!("'{}'", value.replace('\\', "\\\\").replace('\'', "\\'")),
+        "powershell" => |value| format!("'{}'", value.replace('\'', "''")),
+        _ => return None,
+    };
+    Some(expand(command, |var| {
+        let value = match var {
+            "GITNAV_EVENT" => "post_cd",
+            _ => env
+                .iter()
+                .find(|(name, _)| *name == var)
+                .map_or("", |(_, value)| value.as_str()),
+        };
+        quote(value)
+    }))
+}
+
+/// Write the `post_cd` hook to the file the calling shell wrapper named in
+/// `GITNAV_POST_CD_FILE`, in the syntax of the shell in `GITNAV_SHELL`.
+///
+/// Does nothing when gitnav was not run by a wrapper, no hook is set, or the
+/// wrapper's shell cannot run one.
+///
+/// # Errors
+///
+/// Returns an error if the file cannot be written
+pub fn write_post_cd(hooks: &HooksConfig, env: &[(&str, String)]) -> Result<()> {
+    let Some(file) = std::env::var_os(POST_CD_FILE_ENV).filter(|f| !f.is_empty()) else {
+        return Ok(());
+    };
+    let shell = std::env::var(SHELL_ENV).unwrap_or_default();
+    let Some(command) = post_cd(hooks, &shell, env) else {
+        return Ok(());
+    };
+    fs::write(&file, command + "\n").with_context(|| {
+        format!(
+            "Failed to write post_cd hook to {}",
+            std::path::Path::new(&file).display()
+        )
+    })
+}
+
+/// A `sh -c` (`cmd /C` on Windows) command for a user-supplied command line.
+#[cfg(windows)]
+pub fn shell_command(command: &str) -> Command {
+    let mut shell = Command::new("cmd");
+    shell.arg("/C").arg(command);
+    shell
+}
+
+/// A `sh -c` (`cmd /C` on Windows) command for a user-supplied command line.
+#[cfg(not(windows))]
+pub fn shell_command(command: &str) -> Command {
+    let mut shell = Command::new("sh");
+    shell.arg("-c").arg(command);
+    shell
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_unset_hook_is_a_no_op() {
+        let hooks = HooksConfig::default();
+        assert!(run(&hooks, Event::PostScan, &[]).is_ok());
+
+        let blank = HooksConfig {
+            post_scan: Some("  ".to_string()),
+            ..HooksConfig::default()
+        };
+        assert!(run(&blank, Event::PostScan, &[]).is_ok());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_hook_receives_event_and_env() {
+        let tmp = tempfile::tempdir().unwrap();
+        let out = tmp.path().join("out");
+        let hooks = HooksConfig {
+            post_select: Some(format!(
+                "printf '%s %s' \"$GITNAV_EVENT\" \"$GITNAV_REPO_PATH\" > '{}'",
+                out.display()
+            )),
+            ..HooksConfig::default()
+        };
+
+        run(
+            &hooks,
+            Event::PostSelect,
+            &[("GITNAV_REPO_PATH", "/src/api".to_string())],
+        )
+        .unwrap();
+        assert_eq!(
+            std::fs::read_to_string(&out).unwrap(),
+            "post_select /src/api"
+        );
+    }
+
+    #[test]
+    fn test_expand_replaces_only_known_placeholders() {
+        let expanded = expand("tmux rename-window {name} # {nope} ${path} {{", |var| {
+            format!("<{}>", var)
+        });
+        assert_eq!(
+            expanded,
+            "tmux rename-window <GITNAV_REPO_NAME> # {nope} ${path} {{"
+        );
+    }
+
+    #[test]
+    fn test_post_cd_quotes_values_for_the_shell() {
+        let hooks = HooksConfig {
+            post_cd: Some("cd {path} && echo {name}".to_string()),
+            ..HooksConfig::default()
+        };
+        let env = [
+            ("GITNAV_REPO_PATH", "/src/it's".to_string()),
+            ("GITNAV_REPO_NAME", "it's".to_string()),
+        ];
+        assert_eq!(
+            post_cd(&hooks, "bash", &env).unwrap(),
+            r"cd '/src/it'\''s' && echo 'it'\''s'"
+        );
+        assert_eq!(
+            post_cd(&hooks, "fish", &env).unwrap(),
+            r"cd '/src/it\'s' && echo 'it\'s'"
+        );
+        assert_eq!(
+            post_cd(&hooks, "powershell", &env).unwrap(),
+            "cd '/src/it''s' && echo 'it''s'"
+        );
+        // Without a wrapper that runs it, or without a hook
+        assert_eq!(post_cd(&hooks, "", &env), None);
+        assert_eq!(post_cd(&HooksConfig::default(), "bash", &env), None);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_failing_hook_is_an_error() {
+        let hooks = HooksConfig {
+            post_scan: Some("exit 3".to_string()),
+            ..HooksConfig::default()
+        };
+        let err = run(&hooks, Event::PostScan, &[]).unwrap_err();
+        assert!(err.to_string().contains("post_scan hook exited"));
+    }
+}
@@ -0,0 +1,171 @@
+use git2::Repository;
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+
+use crate::config::IdentityRule;
+use crate::parallel;
+use crate::report::{Cell, Report};
+use crate::scanner::GitRepo;
+
+/// A repository whose `user.email` does not match the rule for its directory.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Violation {
+    pub name: String,
+    pub path: PathBuf,
+    /// Effective `user.email`, or `None` when it is not set at all
+    pub email: Option<String>,
+    /// The `email` pattern of the rule that applies
+    pub expected: String,
+    /// The `path` of the rule that applies, as configured
+    pub rule: String,
+}
+
+/// Check every repository's effective `user.email` against `rules`.
+///
+/// The rule with the longest path containing the repository applies, so a rule
+/// for `~/work/oss` overrides one for `~/work`. Repositories outside every rule
+/// are not checked. Repositories are inspected in parallel; the result keeps
+/// the input order.
+pub fn check(repos: &[GitRepo], rules: &[IdentityRule]) -> Vec<Violation> {
+    let rules: Vec<(PathBuf, &IdentityRule)> = rules
+        .iter()
+        .map(|rule| {
+            (
+                PathBuf::from(shellexpand::tilde(&rule.path).to_string()),
+                rule,
+            )
+        })
+        .collect();
+
+    parallel::filter_map(repos, parallel::default_workers(), |repo| {
+        let (_, rule) = rules
+            .iter()
+            .filter(|(dir, _)| repo.path.starts_with(dir))
+            .max_by_key(|(dir, _)| dir.components().count())?;
+        let email = effective_email(&repo.path);
+        if email
+            .as_deref()
+            .is_some_and(|email| email_matches(&rule.email, email))
+        {
+            return None;
+        }
+        Some(Violation {
+            name: repo.name.clone(),
+            path: repo.path.clone(),
+            email,
+            expected: rule.email.clone(),
+            rule: rule.path.clone(),
+        })
+    })
+}
+
+/// The `user.email` git would commit with, repository config overriding global.
+fn effective_email(repo_path: &Path) -> Option<String> {
+    let repo = Repository::open(repo_path).ok()?;
+    repo.config().ok()?.get_string("user.email").ok()
+}
+
+/// Match an email against a pattern where `*` stands for any run of characters
+/// (`*@corp.com`). Comparison ignores case, as email domains do.
+pub fn email_matches(pattern: &str, email: &str) -> bool {
+    let pattern = pattern.to_lowercase();
+    let email = email.to_lowercase();
+    let mut parts = pattern.split('*');
+    let first = parts.next().unwrap_or("");
+    let Some(mut rest) = email.strip_prefix(first) else {
+        return false;
+    };
+    let parts: Vec<&str> = parts.collect();
+    let Some((last, middle)) = parts.split_last() else {
+        // No `*`: the whole email must equal the pattern
+        return rest.is_empty();
+    };
+    for part in middle {
+        match rest.find(part) {
+            Some(i) => rest = &rest[i + part.len()..],
+            None => return false,
+        }
+    }
+    rest.ends_with(last)
+}
+
+/// Build the identity-check table.
+pub fn build_report(violations: &[Violation]) -> Report {
+    let rows = violations
+        .iter()
+        .map(|v| {
+            vec![
+                Cell::new(v.name.clone()),
+                Cell::new(v.email.clone().unwrap_or_else(|| "(not set)".to_string())),
+                Cell::new(v.expected.clone()),
+                Cell::new(v.path.display().to_string()),
+            ]
+        })
+        .collect();
+
+    Report {
+        title: "gitnav identity-check".to_string(),
+        notes: Vec::new(),
+        headers: ["Repository", "Email", "Expected", "Path"]
+            .iter()
+            .map(|h| h.to_string())
+            .collect(),
+        rows,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn init_repo(dir: &Path, email: Option<&str>) -> GitRepo {
+        let repo = Repository::init(dir).unwrap();
+        if let Some(email) = email {
+            repo.config().unwrap().set_str("user.email", email).unwrap();
+        }
+        GitRepo::new(dir.to_path_buf())
+    }
+
+    fn rule(path: &Path, email: &str) -> IdentityRule {
+        IdentityRule {
+            path: path.display().to_string(),
+            email: email.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_email_matches() {
+        assert!(email_matches("*@corp.com", "jane@corp.com"));
+        assert!(email_matches("*@corp.com", "Jane@Corp.com"));
+        assert!(!email_matches("*@corp.com", "jane@gmail.com"));
+        assert!(!email_matches("*@corp.com", "jane@corp.com.evil"));
+        assert!(email_matches("jane@corp.com", "jane@corp.com"));
+        assert!(!email_matches("jane@corp.com", "jane@corp.community"));
+        assert!(email_matches("jane+*@*.corp.com", "jane+ci@eu.corp.com"));
+        assert!(email_matches("*", "anything"));
+    }
+
+    #[test]
+    fn test_check_uses_most_specific_rule() {
+        let tmp = tempfile::tempdir().unwrap();
+        let work = tmp.path().join("work");
+        let oss = work.join("oss");
+        let repos = vec![
+            init_repo(&work.join("api"), Some("jane@corp.com")),
+            init_repo(&work.join("web"), Some("jane@gmail.com")),
+            init_repo(&oss.join("lib"), Some("jane@gmail.com")),
+            init_repo(&tmp.path().join("home/dotfiles"), Some("jane@gmail.com")),
+        ];
+        let rules = vec![rule(&work, "*@corp.com"), rule(&oss, "*@gmail.com")];
+
+        let violations = check(&repos, &rules);
+        let names: Vec<&str> = violations.iter().map(|v| v.name.as_str()).collect();
+        assert_eq!(names, vec!["web"]);
+        assert_eq!(violations[0].email.as_deref(), Some("jane@gmail.com"));
+        assert_eq!(violations[0].expected, "*@corp.com");
+
+        let report = build_report(&violations);
+        assert_eq!(report.rows.len(), 1);
+        assert_eq!(report.rows[0][1].text, "jane@gmail.com");
+    }
+}
@@ -1,18 +1,64 @@
+mod actions;
+mod branch;
+mod bulk;
 mod cache;
+mod clone;
 mod config;
+mod daemon;
+mod duplicates;
+mod editors;
+mod exclude;
+mod exec;
 mod exit_codes;
+mod fetch;
+mod files;
+mod finder;
+mod forge;
 mod fzf;
+mod gc;
+mod ghq;
+mod github;
+mod grep;
+mod history;
+mod hooks;
+mod identity;
+mod manifest;
+mod matcher;
+mod notify;
 mod output;
+mod parallel;
+mod pin;
 mod preview;
+mod preview_cache;
+mod profile;
+mod prune;
+mod remote;
+mod report;
 mod scanner;
 mod shell;
+mod shell_check;
+mod state;
+mod stats;
+mod status;
+mod sync;
+mod tags;
+mod template;
+#[cfg(test)]
+mod test_util;
+mod tmux;
+mod unpushed;
+mod watch;
+mod worktree;
+mod zoxide;
 
 use anyhow::{Context, Result};
 use clap::{Parser, Subcommand};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 #[derive(Parser)]
 #[command(name = "gitnav")]
+#[command(group(clap::ArgGroup::new("paths_out").args(["list", "multi"])))]
+#[command(group(clap::ArgGroup::new("json_out").args(["json", "ndjson"])))]
 #[command(author, version)]
 #[command(about = "Fast git repository navigator with fuzzy finding")]
 #[command(long_about = "gitnav - Fast git repository navigator\n\n\
@@ -25,12 +71,43 @@ gn --path ~/work --max-depth 8  # Search deeper\n\n  \
 Non-Interactive (Scripting):\n    \
 gn --list                       # List all repositories\n    \
 gn --list --json                # Output as JSON\n    \
-gn --list > repos.txt           # Save to file\n\n  \
+gn --list > repos.txt           # Save to file\n    \
+gitnav select api --first       # Best match for a query\n    \
+gitnav open api                 # Open a repository in your editor\n    \
+gitnav files api                # Pick a file in a repository to edit\n    \
+gitnav grep 'TODO|FIXME'        # Search every repository's tracked files\n    \
+gitnav stats                    # Repositories by host and language, stalest, largest\n    \
+gitnav duplicates               # Repositories cloned more than once\n    \
+gitnav unpushed                 # Branches and repositories not pushed anywhere\n    \
+gitnav gc                       # Run git's maintenance in every repository\n    \
+gn branch api                   # Switch a repository's branch and cd into it\n    \
+gn worktree --new fix/login     # Add a worktree for a branch and cd into it\n    \
+gitnav browse --branch          # Open this repository's branch on its forge\n    \
+gitnav tmux api                 # Open a tmux session rooted at a repository\n    \
+gitnav sync my-org --dry-run    # Repositories of an account not cloned yet\n    \
+ghq list -p | gitnav --stdin    # Pick from your own candidate list\n\n  \
 Cache Management:\n    \
 gn clear-cache                  # Clear all cached data\n    \
 gn clear-cache --dry-run        # Preview what will be deleted\n\n  \
+Exclusions:\n    \
+gitnav exclude ~/src/fixture    # Hide a repository from results\n    \
+gitnav exclude --restore fixture # Show it again\n\n  \
+Pins:\n    \
+gitnav pin ~/src/api            # Keep a repository at the top of the picker\n    \
+gitnav unpin api                # Remove the pin\n\n  \
+Tags:\n    \
+gitnav tag add ~/src/api work   # Tag a repository\n    \
+gn --tag work                   # Only pick among repositories tagged \"work\"\n    \
+gitnav import editors           # Add VS Code and JetBrains recent projects\n\n  \
 Configuration:\n    \
 gitnav config                   # Show example configuration\n    \
+gn --profile work               # Use [profiles.work] from the config file\n    \
+gitnav preview --json           # Summarize the current repository\n    \
+gitnav config schema            # JSON Schema for editor validation\n    \
+gitnav config set ui.prompt '>' # Change a setting in the config file\n    \
+gitnav config edit              # Open the config file in $EDITOR\n    \
+gitnav config path              # Show which config file is in use\n    \
+gitnav config validate          # Check the config file for mistakes\n    \
 gitnav init zsh                 # Generate shell integration\n    \
 gitnav version --verbose        # Show detailed version info\n\n\
 ENVIRONMENT:\n  \
@@ -41,21 +118,33 @@ HELP:\n  \
 Use 'gitnav <COMMAND> --help' for detailed command information")]
 struct Cli {
     /// Force refresh (bypass cache)
-    #[arg(short, long)]
+    #[arg(short, long, global = true)]
     force: bool,
 
     /// Override base search path
-    #[arg(short, long)]
+    #[arg(short, long, global = true)]
     path: Option<PathBuf>,
 
     /// Override max search depth
-    #[arg(short = 'd', long)]
+    #[arg(short = 'd', long, global = true)]
     max_depth: Option<usize>,
 
     /// Path to custom config file
-    #[arg(short, long)]
+    #[arg(short, long, global = true)]
     config: Option<PathBuf>,
 
+    /// Use a named profile: its [profiles.NAME] settings and its own cache (also set by GITNAV_PROFILE)
+    #[arg(long, global = true, value_name = "NAME")]
+    profile: Option<String>,
+
+    /// Fail on unknown keys in the config file (also set by GITNAV_STRICT_CONFIG=1)
+    #[arg(long, global = true)]
+    strict_config: bool,
+
+    /// Only repositories with this tag (repeat to require several)
+    #[arg(long = "tag", global = true, value_name = "TAG")]
+    tags: Vec<String>,
+
     /// List repositories without launching fzf (enables piping)
     #[arg(short, long)]
     list: bool,
@@ -64,8 +153,25 @@ struct Cli {
     #[arg(long)]
     json: bool,
 
+    /// With --list, print one JSON object per line (for streaming consumers)
+    #[arg(long, requires = "list", conflicts_with = "json")]
+    ndjson: bool,
+
+    /// Add branch, dirty flag, last commit time, and remote URL to JSON list output
+    #[arg(long, requires = "json_out")]
+    details: bool,
+
+    /// With --list, print each repository with a template, e.g. "{name}\t{branch}\t{path}"
+    #[arg(
+        long,
+        value_name = "TEMPLATE",
+        requires = "list",
+        conflicts_with = "json_out"
+    )]
+    format: Option<String>,
+
     /// Suppress non-error output
-    #[arg(short, long)]
+    #[arg(short, long, global = true)]
     quiet: bool,
 
     /// Show verbose output
@@ -73,21 +179,61 @@ struct Cli {
     verbose: bool,
 
     /// Disable colored output
-    #[arg(long)]
+    #[arg(long, global = true)]
     no_color: bool,
 
     /// Enable debug output
-    #[arg(long)]
+    #[arg(long, global = true)]
     debug: bool,
 
-    /// Start fzf with this query pre-typed (also set by passing a positional arg via shell wrapper)
+    /// Read candidate paths from stdin (one per line or NUL-delimited) instead of scanning
     #[arg(long)]
+    stdin: bool,
+
+    /// Also list the git repositories in zoxide's database, outside the search paths too
+    #[arg(long, global = true)]
+    from_zoxide: bool,
+
+    /// Select several repositories (Tab to mark) and print all their paths
+    #[arg(short, long, conflicts_with = "list")]
+    multi: bool,
+
+    /// Separate --list or --multi paths with NUL instead of newlines (for `xargs -0`)
+    #[arg(long, requires = "paths_out", conflicts_with_all = ["json", "ndjson"])]
+    print0: bool,
+
+    /// Start fzf with this query pre-typed (`gn <query>` jumps directly instead; see `jump`)
+    #[arg(short = 'Q', long)]
     query: Option<String>,
 
+    /// Skip the picker when exactly one repository matches the query
+    #[arg(short = '1', long = "select-1")]
+    select_1: bool,
+
+    /// Exit with code 3 instead of opening the picker when nothing matches the query
+    #[arg(short = '0', long = "exit-0")]
+    exit_0: bool,
+
     /// Generate shell preview for a repository path (internal use by fzf)
     #[arg(long, hide = true)]
     preview: Option<PathBuf>,
 
+    /// Print the data the preview shows for a repository as JSON (same as `gitnav preview --json PATH`)
+    #[arg(long, value_name = "PATH")]
+    preview_json: Option<PathBuf>,
+
+    /// Fetch and cache a repository's remote description (internal, run in the background)
+    #[arg(long, hide = true, value_name = "PATH")]
+    refresh_description: Option<PathBuf>,
+
+    /// Fetch and cache a repository's GitHub stats (internal, run in the background)
+    #[arg(long, hide = true, value_name = "PATH")]
+    refresh_github: Option<PathBuf>,
+
+    /// Scan and write the repository cache (internal, run in the background)
+    #[arg(long, hide = true)]
+    refresh_cache: bool,
+
     #[command(subcommand)]
     command: Option<Commands>,
 }
@@ -99,12 +245,44 @@ enum Commands {
     /// Creates a shell function that allows you to use gitnav from your shell.
     /// After running this, you can use 'gn' as a shortcut to navigate.
     ///
+    /// Without a shell argument, the invoking shell is detected from the parent
+    /// process or $SHELL.
+    ///
     /// EXAMPLE:
+    ///   eval "$(gitnav init)"      # Detect the shell (shared dotfiles)
     ///   eval "$(gitnav init zsh)" # For Zsh
     ///   eval "$(gitnav init bash)"  # For Bash
+    ///   gitnav init zsh --check    # Verify the integration is loaded
+    ///   eval "$(gitnav init zsh --widget)"  # Also bind Ctrl-G to the picker
+    ///   eval "$(gitnav init zsh --cmd j --no-ls --post-cd 'git status -sb')"
     Init {
-        /// Shell type: zsh, bash, fish, nu, or nushell
-        shell: String,
+        /// Shell type: zsh, bash, fish, nu, nushell, or powershell (detected when omitted)
+        shell: Option<String>,
+
+        /// Check that a new shell session has a working `gn` (or --cmd) function instead of printing the script
+        #[arg(long)]
+        check: bool,
+
+        /// Name of the shell function to define instead of `gn`
+        #[arg(long, value_name = "NAME", default_value = "gn", value_parser = shell::parse_cmd_name)]
+        cmd: String,
+
+        /// Don't list the repository (with eza or ls) after changing into it
+        #[arg(long)]
+        no_ls: bool,
+
+        /// Command to run after changing into a repository, in the shell's own syntax
+        #[arg(long, value_name = "COMMAND")]
+        post_cd: Option<String>,
+
+        /// Also bind a key (Ctrl-G) that opens the picker from the command line
+        /// and changes to the picked repository (zsh, bash, fish)
+        #[arg(long)]
+        widget: bool,
+
+        /// Key for the widget, as ctrl-<letter> or alt-<letter>; implies --widget
+        #[arg(long, value_name = "KEY", value_parser = shell::WidgetKey::parse)]
+        widget_key: Option<shell::WidgetKey>,
     },
 
     /// Print example configuration file to stdout
@@ -114,302 +292,4229 @@ enum Commands {
     ///
     /// EXAMPLE:
     ///   gitnav config > ~/.config/gitnav/config.toml
-    Config,
+    ///   gitnav config schema > ~/.config/gitnav/config.schema.json
+    ///   gitnav config set search.max_depth 7
+    Config {
+        #[command(subcommand)]
+        action: Option<ConfigCommand>,
+    },
 
     /// Clear all cached repository data
     ///
     /// Removes cached repository lists. Use --dry-run to preview what will be deleted.
     /// Cache is automatically recreated the next time you run gitnav.
+    /// With --profile only that profile's cache is removed.
     ///
     /// EXAMPLE:
     ///   gitnav clear-cache          # Delete all cache
     ///   gitnav clear-cache --dry-run # Preview deletion
+    ///   gitnav clear-cache --profile work
     ClearCache {
         /// Show what would be deleted without deleting
         #[arg(long)]
         dry_run: bool,
     },
 
-    /// Show version information
+    /// Hide a repository from all future results
     ///
-    /// Display the installed version. Use --verbose for detailed build information.
+    /// Adds the repository to a persistent exclude list (kept outside the cache,
+    /// so clearing the cache does not undo it). Nothing on disk is touched.
+    /// Accepts a path or the name of a discovered repository.
     ///
     /// EXAMPLE:
-    ///   gitnav version              # Show version
-    ///   gitnav version --verbose    # Show build details
-    Version {
-        /// Show detailed version information (OS, arch, build profile)
-        #[arg(short, long)]
-        verbose: bool,
+    ///   gitnav exclude ~/src/vendor/fixture    # Hide by path
+    ///   gitnav exclude fixture                 # Hide by name
+    ///   gitnav exclude --restore fixture       # Show it again
+    ///   gitnav exclude --list                  # Show excluded repositories
+    #[command(alias = "rm")]
+    Exclude {
+        /// Repository path or name
+        #[arg(required_unless_present = "list")]
+        repo: Option<String>,
+
+        /// Remove the repository from the exclude list instead of adding it
+        #[arg(long, conflicts_with = "list")]
+        restore: bool,
+
+        /// List excluded repositories
+        #[arg(long)]
+        list: bool,
     },
-}
 
-fn main() -> Result<()> {
-    let cli = Cli::parse();
+    /// List recently visited repositories, newest first
+    ///
+    /// Every repository picked with gn (or reached with `gitnav jump`/`back`) is
+    /// recorded in a history file in the cache directory.
+    ///
+    /// EXAMPLE:
+    ///   gitnav recent                  # Last 10 repositories
+    ///   gitnav recent --limit 3
+    Recent {
+        /// Number of repositories to show
+        #[arg(short = 'n', long, default_value_t = 10)]
+        limit: usize,
+    },
 
-    // Handle subcommands
-    if let Some(command) = cli.command {
-        return handle_subcommand(command);
-    }
+    /// Go back to the previously visited repository
+    ///
+    /// Prints the most recently visited repository other than the one you are
+    /// in. The shell wrapper runs this for `gn -`.
+    ///
+    /// EXAMPLE:
+    ///   gn -                           # cd back to the previous repository
+    Back,
 
-    // Handle preview mode (called by fzf)
-    if let Some(repo_path) = cli.preview {
-        return handle_preview(&repo_path);
-    }
+    /// Clone a repository into a host/owner/repo layout under the search root
+    ///
+    /// Clones into `<base>/<host>/<path>` (e.g. ~/src/github.com/me/foo, like
+    /// ghq), where <base> is --path, else the first of search.paths, else
+    /// search.base_path. Prints the path so the shell wrapper cds into it, and
+    /// adds it to a fresh cache. A repository that is already there is not
+    /// cloned again. `owner/repo` is shorthand for a GitHub repository.
+    ///
+    /// EXAMPLE:
+    ///   gitnav clone git@github.com:me/foo.git
+    ///   gitnav clone https://gitlab.com/group/project
+    ///   gitnav clone me/foo                    # https://github.com/me/foo
+    ///   gn clone me/foo                        # Clone, then cd into it
+    Clone {
+        /// Remote URL, or owner/repo for GitHub
+        url: String,
+    },
 
-    // Main navigation mode
-    run_navigation(&cli)
-}
+    /// Clone the repositories of a GitHub or GitLab account
+    ///
+    /// Lists the repositories of each `[[sync.accounts]]` entry (or of the
+    /// ACCOUNT given) through the forge's API and clones those missing under
+    /// the first search path, laid out like `gitnav clone`:
+    /// <root>/<host>/<owner>/<repo>. ACCOUNT is a GitHub user or organization,
+    /// or HOST/OWNER for GitLab. The API token comes from the account's
+    /// `token`, then GITHUB_TOKEN / GH_TOKEN or GITLAB_TOKEN, then the git
+    /// credential helper; without one, only public repositories are listed.
+    ///
+    /// EXAMPLE:
+    ///   gitnav sync                           # Every configured account
+    ///   gitnav sync my-org --dry-run          # What would be cloned
+    ///   gitnav sync my-org --topic rust --visibility public
+    ///   gitnav sync gitlab.com/group/sub
+    Sync {
+        /// Account to sync instead of the configured ones: OWNER or HOST/OWNER
+        account: Option<String>,
 
-fn handle_subcommand(command: Commands) -> Result<()> {
-    match command {
-        Commands::Init { shell } => {
-            if let Some(script) = shell::generate_init_script(&shell) {
-                print!("{}", script);
-                Ok(())
-            } else {
-                let formatter = output::OutputFormatter::new(false, false, false);
-                let error = output::ErrorInfo::new(
-                    "ENOSUPPORT",
-                    "Unsupported shell",
-                    format!("The shell '{}' is not supported by gitnav.", shell),
-                    "Use one of the supported shells: zsh, bash, fish, nu, nushell, or powershell.\n  Examples:\n    gitnav init zsh\n    gitnav init bash\n    gitnav init fish\n    gitnav init nu\n    gitnav init powershell",
-                    "https://github.com/msetsma/gitnav#shell-integration"
-                );
-                formatter.error(&error);
-                std::process::exit(exit_codes::EXIT_GENERAL_ERROR);
-            }
-        }
-        Commands::Config => {
-            println!("{}", config::Config::example_toml());
-            Ok(())
-        }
-        Commands::ClearCache { dry_run } => {
-            let formatter = output::OutputFormatter::new(false, false, false);
-            let config = config::Config::load(None)?;
-            let cache = cache::Cache::new(config.cache.ttl_seconds)?;
+        /// List what would be cloned without cloning
+        #[arg(long)]
+        dry_run: bool,
 
-            let cache_files = cache.list_cache_files()?;
-            let cache_size = cache.get_cache_size()?;
+        /// Only repositories with this topic (repeat to accept several)
+        #[arg(long = "topic", value_name = "TOPIC")]
+        topics: Vec<String>,
 
-            if dry_run {
-                println!("Cache directory: {}", cache.cache_dir().display());
-                println!("Cache files: {}", cache_files.len());
-                println!("Total size: {} bytes\n", cache_size);
+        /// Only public or only private repositories
+        #[arg(long, value_enum)]
+        visibility: Option<config::Visibility>,
+    },
 
-                if !cache_files.is_empty() {
-                    println!("Files to be deleted:");
-                    for file in &cache_files {
-                        if let Ok(metadata) = std::fs::metadata(file) {
-                            println!("  {} ({} bytes)", file.display(), metadata.len());
-                        } else {
-                            println!("  {}", file.display());
-                        }
-                    }
-                } else {
-                    println!("No cache files to delete");
-                }
-            } else {
-                cache.clear()?;
-                formatter.success("Cache cleared successfully");
-                if !cache_files.is_empty() {
-                    println!(
-                        "Deleted {} cache files ({} bytes)",
-                        cache_files.len(),
-                        cache_size
-                    );
-                }
-            }
-            Ok(())
-        }
-        Commands::Version { verbose } => {
-            println!("gitnav {}", env!("CARGO_PKG_VERSION"));
+    /// Pin a repository to the top of the picker
+    ///
+    /// Pinned repositories are listed first, with a ★ marker, in the order they
+    /// were pinned. Pins are kept outside the cache, so clearing the cache does
+    /// not lose them. Accepts a path or the name of a discovered repository.
+    ///
+    /// EXAMPLE:
+    ///   gitnav pin ~/src/work/api              # Pin by path
+    ///   gitnav pin api                         # Pin by name
+    ///   gitnav pin --list                      # Show pinned repositories
+    Pin {
+        /// Repository path or name
+        #[arg(required_unless_present = "list")]
+        repo: Option<String>,
 
-            if verbose {
-                println!("\nBuild Information:");
-                println!("  Version: {}", env!("CARGO_PKG_VERSION"));
-                println!("  Authors: {}", env!("CARGO_PKG_AUTHORS"));
-                println!("  License: {}", env!("CARGO_PKG_LICENSE"));
-                println!("  Repository: {}", env!("CARGO_PKG_REPOSITORY"));
-                println!("\nSystem Information:");
-                println!("  OS: {}", std::env::consts::OS);
-                println!("  Architecture: {}", std::env::consts::ARCH);
-                #[cfg(debug_assertions)]
-                println!("  Build Profile: debug");
-                #[cfg(not(debug_assertions))]
-                println!("  Build Profile: release");
+        /// List pinned repositories
+        #[arg(long)]
+        list: bool,
+    },
 
-                println!("\nFeatures:");
-                println!(
-                    "  Colors: {}",
-                    if output::should_use_color() {
-                        "enabled"
-                    } else {
-                        "disabled"
-                    }
-                );
-                println!("  Interactive Mode: enabled");
+    /// Remove a repository's pin
+    ///
+    /// EXAMPLE:
+    ///   gitnav unpin api                       # Unpin by name
+    ///   gitnav unpin ~/src/work/api            # Unpin by path
+    Unpin {
+        /// Repository path or name
+        repo: String,
+    },
 
-                println!("\nDependencies:");
-                println!("  clap: 4.5");
-                println!("  git2: 0.19");
-                println!("  serde: 1.0");
-                println!("  chrono: 0.4");
-            }
-            Ok(())
-        }
-    }
-}
+    /// Tag repositories, to filter with --tag
+    ///
+    /// Tags are shown as #tag in the picker and included in `--list --json`.
+    /// Every command that works on discovered repositories accepts --tag, so
+    /// `gitnav --tag work exec -- git pull` pulls only work repositories. Tags
+    /// are kept outside the cache, so clearing the cache does not lose them.
+    ///
+    /// EXAMPLE:
+    ///   gitnav tag add ~/src/api work oss      # Tag by path
+    ///   gitnav tag remove api oss              # Remove one tag, by name
+    ///   gitnav tag list                        # Show tagged repositories
+    ///   gn --tag work                          # Pick among work repositories
+    Tag {
+        #[command(subcommand)]
+        action: TagCommand,
+    },
 
-fn handle_preview(repo_path: &PathBuf) -> Result<()> {
-    let config = config::Config::load(None)?;
-    let preview_text = preview::generate_preview_colored(repo_path, &config.preview)?;
-    println!("{}", preview_text);
-    Ok(())
-}
+    /// Add repositories other tools know about to the list
+    ///
+    /// Imported repositories are tagged, and tagged ones are listed wherever
+    /// they are, even outside the search paths. Remove the tag to drop one.
+    ///
+    /// EXAMPLE:
+    ///   gitnav import editors                  # VS Code and JetBrains recent projects
+    ///   gitnav import editors --dry-run        # Show what would be imported
+    ///   gn --tag editor-recent                 # Pick among them
+    Import {
+        #[command(subcommand)]
+        source: ImportCommand,
+    },
 
-fn run_navigation(cli: &Cli) -> Result<()> {
-    let _formatter = output::OutputFormatter::new(cli.quiet, cli.verbose, cli.no_color);
+    /// Print a repository summary (the same one shown in the fzf preview pane)
+    ///
+    /// Shows branch, last activity, working tree status, and recent commits for a
+    /// repository so other tools (tmux status lines, editor sidebars) can reuse it.
+    /// Colors are used only when stdout is a terminal.
+    ///
+    /// EXAMPLE:
+    ///   gitnav preview                  # Summarize the repository you are in
+    ///   gitnav preview ~/src/gitnav     # Summarize a specific repository
+    ///   gitnav preview --plain          # Never emit ANSI colors
+    ///   gitnav preview --json | jq .branch
+    Preview {
+        /// Repository path (defaults to the current directory)
+        path: Option<PathBuf>,
 
-    // Load configuration
-    let config = config::Config::load(cli.config.clone())?;
+        /// Output plain text without colors
+        #[arg(long, conflicts_with = "json")]
+        plain: bool,
 
-    // Validate configuration
-    config.validate()?;
+        /// Output the summary as JSON
+        #[arg(long)]
+        json: bool,
+    },
 
-    // Determine search paths (CLI --path overrides everything; config.paths overrides base_path)
-    let search_paths: Vec<String> = if let Some(ref p) = cli.path {
-        vec![shellexpand::tilde(&p.to_string_lossy().to_string()).to_string()]
-    } else if !config.search.paths.is_empty() {
-        config
-            .search
-            .paths
-            .iter()
-            .map(|p| shellexpand::tilde(p).to_string())
-            .collect()
-    } else {
-        vec![shellexpand::tilde(&config.search.base_path).to_string()]
-    };
+    /// Show a status dashboard for all repositories
+    ///
+    /// Lists every discovered repository with its current branch, ahead/behind
+    /// counts against its upstream (as of the last fetch), working tree state,
+    /// and last commit time. Repositories are inspected in parallel. Use --html
+    /// to write a self-contained report (sortable table, no external assets)
+    /// that can be shared or archived.
+    ///
+    /// EXAMPLE:
+    ///   gitnav status                       # Print the dashboard
+    ///   gitnav status --html report.html    # Write an HTML report
+    ///   gitnav status --html - > out.html   # HTML to stdout
+    ///   gitnav status --markdown | pbcopy   # Paste into an issue or wiki page
+    ///   gitnav status --json | jq '.[] | select(.status.untracked > 0)'
+    #[command(visible_alias = "status-all")]
+    Status {
+        /// Write the dashboard as a self-contained HTML page to FILE ("-" for stdout)
+        #[arg(long, value_name = "FILE")]
+        html: Option<PathBuf>,
 
-    let max_depth = cli.max_depth.unwrap_or(config.search.max_depth);
-    let ignore_patterns = &config.search.ignore_patterns;
+        /// Print the dashboard as a GitHub-flavored Markdown table
+        #[arg(long, conflicts_with = "html")]
+        markdown: bool,
 
-    // Build a stable cache key from sorted paths
-    let cache_key = {
-        let mut sorted = search_paths.clone();
-        sorted.sort();
-        sorted.join("|")
-    };
+        /// Output the dashboard as JSON (for scripting)
+        #[arg(long, conflicts_with_all = ["html", "markdown"])]
+        json: bool,
+    },
 
-    if cli.debug {
-        eprintln!("DEBUG: Search paths: {:?}", search_paths);
-        eprintln!("DEBUG: Max depth: {}", max_depth);
-        eprintln!("DEBUG: Cache key: {}", cache_key);
-        eprintln!("DEBUG: Cache enabled: {}", config.cache.enabled);
-        eprintln!("DEBUG: Force refresh: {}", cli.force);
-    }
+    /// Report repositories committing with the wrong email
+    ///
+    /// Checks each repository's effective `user.email` (repository config
+    /// overriding global) against the `[[identity.rules]]` for its directory,
+    /// e.g. "repositories under ~/work must use *@corp.com". The most specific
+    /// rule applies; repositories outside every rule are not checked. Exits
+    /// with status 1 when any repository violates its rule.
+    ///
+    /// EXAMPLE:
+    ///   gitnav identity-check
+    ///   gitnav identity-check --json | jq -r '.[].path'
+    IdentityCheck {
+        /// Output the violations as JSON (for scripting)
+        #[arg(long)]
+        json: bool,
+    },
 
-    // Get repos (from cache or fresh scan)
-    let repos = if config.cache.enabled && !cli.force {
-        let cache = cache::Cache::new(config.cache.ttl_seconds)?;
+    /// Summarize every repository: hosts, languages, and what needs attention
+    ///
+    /// Counts the repositories by forge host and by primary language, and
+    /// lists how many have uncommitted changes, which have no remote, and the
+    /// stalest (oldest last commit) and largest on disk. Sizes include `.git`
+    /// and untracked files such as build output, so walking large trees can
+    /// take a moment. Use --json to feed a dashboard.
+    ///
+    /// EXAMPLE:
+    ///   gitnav stats
+    ///   gitnav stats --top 10 --tag work
+    ///   gitnav stats --markdown > fleet.md
+    ///   gitnav stats --json | jq .by_host
+    Stats {
+        /// How many of the stalest and largest repositories to list
+        #[arg(long, value_name = "N", default_value_t = 5)]
+        top: usize,
 
-        if cache.is_valid(&cache_key) {
-            if cli.verbose {
-                eprintln!("DEBUG: Loading from cache");
-            }
-            cache.load(&cache_key)?
-        } else {
-            if cli.verbose {
-                eprintln!("DEBUG: Cache miss, scanning repositories");
-            }
-            let repos =
-                scanner::scan_repos_multi(&search_paths, max_depth, ignore_patterns)?;
-            cache.save(&cache_key, &repos)?;
-            repos
-        }
-    } else {
-        if cli.verbose {
-            eprintln!("DEBUG: Scanning repositories (cache disabled or force refresh)");
-        }
-        scanner::scan_repos_multi(&search_paths, max_depth, ignore_patterns)?
-    };
+        /// Print the summary as GitHub-flavored Markdown tables
+        #[arg(long)]
+        markdown: bool,
 
-    if repos.is_empty() {
-        let formatter = output::OutputFormatter::new(cli.quiet, cli.verbose, cli.no_color);
-        let error = output::ErrorInfo::new(
-            "ENOREPOS",
-            "No repositories found",
-            format!("No git repositories found in: {}", search_paths.join(", ")),
-            "Verify the path exists and contains git repositories.\nYou can also try:\n  gitnav --path <different_path>\n  gitnav --max-depth <higher_number>".to_string(),
-            "https://github.com/msetsma/gitnav#usage"
-        );
-        formatter.error(&error);
-        std::process::exit(exit_codes::EXIT_GENERAL_ERROR);
-    }
+        /// Output the summary as JSON (for scripting)
+        #[arg(long, conflicts_with = "markdown")]
+        json: bool,
+    },
 
-    if cli.verbose {
-        eprintln!("DEBUG: Found {} repositories", repos.len());
-    }
+    /// Find repositories cloned more than once
+    ///
+    /// Groups repositories that share a remote (compared after normalizing the
+    /// URL, so SSH and HTTPS clones match) or the root commit of their history,
+    /// and lists each clone with its size on disk, the largest groups first.
+    /// Linked worktrees of one clone are not counted as duplicates. Forks
+    /// share their upstream's history, so they are grouped with it too.
+    ///
+    /// EXAMPLE:
+    ///   gitnav duplicates
+    ///   gitnav duplicates --json | jq -r '.[].clones[1:][].path'
+    Duplicates {
+        /// Output the groups as JSON (for scripting)
+        #[arg(long)]
+        json: bool,
+    },
 
-    // Handle --list mode (non-interactive, pipe-friendly)
-    if cli.list {
-        if cli.json {
-            let json_output = serde_json::to_string_pretty(&repos)
-                .context("Failed to serialize repositories as JSON")?;
-            println!("{}", json_output);
-        } else {
-            for repo in &repos {
-                println!("{}", repo.path.display());
-            }
-        }
-        return Ok(());
-    }
+    /// Report work that exists only on this machine
+    ///
+    /// Lists local branches with commits that no remote-tracking branch
+    /// contains (as of the last fetch), and repositories with no remote at
+    /// all. A branch pushed under another name or merged into a pushed one
+    /// counts as pushed. Exits with status 1 when anything is unpushed, so it
+    /// can gate a backup or wipe.
+    ///
+    /// EXAMPLE:
+    ///   gitnav unpushed
+    ///   gitnav unpushed --json | jq -r '.[] | select(.no_remote) | .path'
+    ///   gitnav fetch-all && gitnav unpushed || echo "push first"
+    Unpushed {
+        /// Output the unpushed work as JSON (for scripting)
+        #[arg(long)]
+        json: bool,
+    },
 
-    // Interactive mode requires fzf
-    if !fzf::is_fzf_available() {
-        let formatter = output::OutputFormatter::new(cli.quiet, cli.verbose, cli.no_color);
-        let error = output::ErrorInfo::new(
+    /// Run a command in every repository
+    ///
+    /// Runs the command in each repository (or those matching --filter) and
+    /// prefixes every output line with the repository name, then summarizes
+    /// exit statuses on stderr. The command runs directly, not through a shell;
+    /// use `sh -c '...'` for pipes. GITNAV_REPO_PATH and GITNAV_REPO_NAME are
+    /// set for it. Commands that look destructive (`git reset --hard`, `rm`, ...)
+    /// are confirmed first, as set by `bulk.confirm`. Exits with status 1 if
+    /// the command failed anywhere.
+    ///
+    /// EXAMPLE:
+    ///   gitnav exec -- git status --short
+    ///   gitnav exec --filter work -j 4 -- git pull --ff-only
+    ///   gitnav exec --fail-fast -- cargo test
+    ///   gitnav exec -- sh -c 'git log -1 --format=%cr'
+    Exec {
+        /// Only run in repositories matching this query (same syntax as `select`)
+        #[arg(long, value_name = "QUERY")]
+        filter: Option<String>,
+
+        /// Maximum number of repositories at once (0 = one per CPU)
+        #[arg(short, long, value_name = "N", default_value_t = 1)]
+        jobs: usize,
+
+        /// Start no further repositories once the command has failed in one
+        #[arg(long)]
+        fail_fast: bool,
+
+        /// Do not ask for confirmation
+        #[arg(short, long)]
+        yes: bool,
+
+        /// Command to run, with its arguments
+        #[arg(required = true, trailing_var_arg = true, allow_hyphen_values = true)]
+        command: Vec<String>,
+    },
+
+    /// Search the tracked files of every repository
+    ///
+    /// Runs `git grep` in the repositories in parallel, `grep.jobs` at a time,
+    /// and prints each matching line as repo:file:line:text. The pattern is an
+    /// extended regular expression unless --fixed-strings is given; binary
+    /// files are skipped. A repository stops being searched after
+    /// `grep.max_per_repo` matches. With --pick, the matches open in the
+    /// picker instead, and the chosen file opens in your editor. Exits with
+    /// status 1 when nothing matched or a repository could not be searched.
+    ///
+    /// EXAMPLE:
+    ///   gitnav grep 'TODO|FIXME'
+    ///   gitnav grep -i -F 'api_key' --tag work
+    ///   gitnav grep 'fn main' --pick               # Pick a match, edit its file
+    ///   gitnav grep deprecated --max-per-repo 5 -j 16
+    Grep {
+        /// Pattern to search for (extended regex)
+        pattern: String,
+
+        /// Match regardless of case
+        #[arg(short, long)]
+        ignore_case: bool,
+
+        /// Take the pattern as a literal string
+        #[arg(short = 'F', long)]
+        fixed_strings: bool,
+
+        /// Maximum number of repositories searched at once (0 = one per CPU)
+        #[arg(short, long, value_name = "N")]
+        jobs: Option<usize>,
+
+        /// Matches per repository before moving on (0 = no limit)
+        #[arg(long, value_name = "N")]
+        max_per_repo: Option<usize>,
+
+        /// Pick a match interactively and open its file in your editor
+        #[arg(long)]
+        pick: bool,
+    },
+
+    /// Run git's maintenance in every repository
+    ///
+    /// Runs `git maintenance run --auto` in each repository, which does only
+    /// the work git's own thresholds call for (as `git gc --auto` does), at
+    /// most `gc.jobs` at a time (--jobs overrides it). --full runs a complete
+    /// garbage collection instead. Prints the space reclaimed per repository
+    /// and in total. --register instead enrolls every repository in git's
+    /// scheduled background maintenance (`git maintenance register`). Linked
+    /// worktrees are maintained with their main repository. Failures are
+    /// listed at the end and make the command exit with status 1.
+    ///
+    /// EXAMPLE:
+    ///   gitnav gc                     # Light maintenance where needed
+    ///   gitnav gc --full --jobs 4     # Full gc, four repositories at once
+    ///   gitnav gc --register          # Let git maintain them in the background
+    Gc {
+        /// Run a full garbage collection, not only what git's thresholds call for
+        #[arg(long)]
+        full: bool,
+
+        /// Enroll the repositories in git's background maintenance instead
+        #[arg(long, conflicts_with = "full")]
+        register: bool,
+
+        /// Maximum number of repositories maintained at once (0 = one per CPU)
+        #[arg(short, long, value_name = "N")]
+        jobs: Option<usize>,
+    },
+
+    /// Run `git fetch` in every repository
+    ///
+    /// Fetches run in parallel, at most `bulk.jobs` at a time (--jobs overrides
+    /// it), with a progress line on stderr. Fetches never prompt for
+    /// credentials. Failures are listed at the end and make the command exit
+    /// with status 1.
+    ///
+    /// EXAMPLE:
+    ///   gitnav fetch-all              # Fetch everything
+    ///   gitnav fetch-all --jobs 16    # More fetches at once
+    ///   gitnav fetch-all && gitnav status
+    FetchAll {
+        /// Maximum number of fetches at once (0 = one per CPU)
+        #[arg(short, long, value_name = "N")]
+        jobs: Option<usize>,
+    },
+
+    /// Report repositories that are behind upstream or have unpushed work
+    ///
+    /// Designed for cron or systemd timers: prints nothing when every repository
+    /// is in sync, and one compact line per repository otherwise. Uses the
+    /// remote-tracking refs from the last fetch unless --fetch is given.
+    ///
+    /// EXAMPLE:
+    ///   gitnav notify                  # Check against last fetched state
+    ///   gitnav notify --fetch          # Fetch every repository first
+    ///   gitnav notify --fetch --desktop
+    ///   # crontab: 0 9 * * 1-5  gitnav notify --fetch --desktop
+    Notify {
+        /// Run `git fetch` in every repository before checking
+        #[arg(long)]
+        fetch: bool,
+
+        /// Also show a desktop notification (requires the desktop-notifications feature)
+        #[arg(long)]
+        desktop: bool,
+    },
+
+    /// Forget repositories that no longer exist
+    ///
+    /// Removes deleted repositories from the navigation history, the pin
+    /// list, and the tags. Listings already leave out repositories deleted
+    /// since the last scan; this cleans up the records that still name them.
+    ///
+    /// EXAMPLE:
+    ///   gitnav prune --dry-run         # Show what would be removed
+    ///   gitnav prune
+    Prune {
+        /// List the dead entries without removing them
+        #[arg(long)]
+        dry_run: bool,
+    },
+
+    /// Delete local branches whose upstream is gone, in every repository
+    ///
+    /// Runs `git fetch --all --prune` in each repository, then deletes local
+    /// branches that tracked a remote branch which no longer exists (typically
+    /// merged pull requests). The checked-out branch is never deleted. Each
+    /// deleted branch is listed with its old tip so it can be restored.
+    ///
+    /// --dry-run changes nothing: it asks each remote which branches are gone
+    /// (`git remote prune --dry-run`) and lists what would be pruned and deleted.
+    ///
+    /// EXAMPLE:
+    ///   gitnav prune-gone --dry-run    # Show what would be deleted
+    ///   gitnav prune-gone              # Confirm, then delete
+    ///   gitnav prune-gone --yes        # Skip the confirmation prompt
+    PruneGone {
+        /// List gone branches without deleting them
+        #[arg(long)]
+        dry_run: bool,
+
+        /// Do not ask for confirmation
+        #[arg(short, long)]
+        yes: bool,
+    },
+
+    /// Rank repositories against a query without opening fzf
+    ///
+    /// Runs the same fuzzy matching as the interactive picker (smart-case,
+    /// space-separated terms, and fzf's 'exact ^prefix suffix$ !negate operators)
+    /// and prints every match with its score, best first. Exits with status 1
+    /// when nothing matches.
+    ///
+    /// EXAMPLE:
+    ///   gitnav select api               # Ranked matches with scores
+    ///   gitnav select api --json        # Same, as JSON
+    ///   cd "$(gitnav select api --first)"
+    Select {
+        /// Query to match against repository names and paths
+        query: String,
+
+        /// Output matches as JSON
+        #[arg(long)]
+        json: bool,
+
+        /// Print only the best match's path
+        #[arg(long)]
+        first: bool,
+    },
+
+    /// Go straight to the repository a query names
+    ///
+    /// Prints the path of the matching repository without opening the picker
+    /// when the query names exactly one repository: a unique exact name, or
+    /// the only match. Otherwise the picker opens with the query pre-typed.
+    /// The shell wrapper runs this for `gn <query>`.
+    ///
+    /// EXAMPLE:
+    ///   gitnav jump cli-tools           # Print the path of cli-tools
+    ///   gn cli-tools                    # Same, and cd into it
+    Jump {
+        /// Query to match against repository names and paths
+        query: String,
+    },
+
+    /// Open a repository in your editor instead of changing to it
+    ///
+    /// Opens the repository the query names, as `jump` resolves it, or the
+    /// picker when the query matches several (or is left out); Enter then opens
+    /// the highlighted repository. The editor is editor under [ui], then
+    /// $VISUAL, then $EDITOR; a {path} placeholder in it marks where the path
+    /// goes, otherwise the path is appended.
+    ///
+    /// EXAMPLE:
+    ///   gitnav open                     # Pick a repository to open
+    ///   gitnav open api                 # Open the repository named api
+    ///   GITNAV_UI__EDITOR='code -n {path}' gitnav open api
+    Open {
+        /// Query to match against repository names and paths
+        query: Option<String>,
+    },
+
+    /// Pick a repository, then a file in it, and open the file in your editor
+    ///
+    /// The repository is chosen as `gitnav open` chooses it. A second picker
+    /// then lists its files (tracked, plus untracked ones that are not
+    /// ignored), and the chosen one opens in the editor from `gitnav open`,
+    /// started in the repository root.
+    ///
+    /// EXAMPLE:
+    ///   gitnav files                    # Pick a repository, then a file
+    ///   gitnav files api                # Pick a file in the repository named api
+    Files {
+        /// Query to match against repository names and paths
+        query: Option<String>,
+    },
+
+    /// Pick a repository, then one of its branches, and change to it
+    ///
+    /// The repository is chosen as `gitnav open` chooses it. A second picker
+    /// lists its local branches, newest first, then remote branches without a
+    /// local one. A branch checked out in a worktree prints that worktree's
+    /// path; any other branch is checked out in the repository (a remote one
+    /// as a new tracking branch), whose path is printed. With uncommitted
+    /// changes to tracked files, gitnav asks before checking out, and refuses
+    /// when it cannot ask.
+    ///
+    /// EXAMPLE:
+    ///   gn branch                       # Pick a repository, then a branch
+    ///   gn branch api                   # Pick a branch of the repository named api
+    Branch {
+        /// Query to match against repository names and paths
+        query: Option<String>,
+    },
+
+    /// Jump to, list, or create worktrees of a repository
+    ///
+    /// Without flags, the repository is chosen as `gitnav open` chooses it and
+    /// a second picker lists its worktrees; the picked one's path is printed.
+    /// --list and --new work on the repository the query names, or the one
+    /// containing the current directory. --new checks out an existing local or
+    /// remote branch, or creates the branch from HEAD, in a new worktree placed
+    /// by path under [worktree], and prints its path.
+    ///
+    /// EXAMPLE:
+    ///   gn worktree                     # Pick a repository, then a worktree
+    ///   gn worktree api                 # Pick a worktree of the repository named api
+    ///   gn worktree --new fix/login     # New worktree of this repository
+    ///   gitnav worktree api --list      # Branch and path of every worktree
+    Worktree {
+        /// Query to match against repository names and paths
+        query: Option<String>,
+
+        /// Create a worktree for this branch and print its path
+        #[arg(long, value_name = "BRANCH", conflicts_with = "list")]
+        new: Option<String>,
+
+        /// Print the worktrees instead of picking one
+        #[arg(long)]
+        list: bool,
+    },
+
+    /// Open a repository's page on GitHub, GitLab, or Bitbucket
+    ///
+    /// Uses origin (or the first remote) with insteadOf rewrites applied, so
+    /// SSH remotes open as their https:// page. Other forges get
+    /// https://<host>/<path> unless [forge.browse."<host>"] gives URL
+    /// templates for them.
+    ///
+    /// EXAMPLE:
+    ///   gitnav browse                   # The current repository's page
+    ///   gitnav browse --branch          # The checked-out branch's page
+    ///   gitnav browse ~/src/api --print # Print the URL instead
+    Browse {
+        /// Repository path (defaults to the current directory)
+        path: Option<PathBuf>,
+
+        /// Link to the checked-out branch
+        #[arg(short, long)]
+        branch: bool,
+
+        /// Print the URL instead of opening it
+        #[arg(long)]
+        print: bool,
+    },
+
+    /// Open a tmux session for a repository
+    ///
+    /// Attaches to the session named after the repository, creating it rooted
+    /// at the repository first if it does not exist; inside tmux the current
+    /// client switches to it. The query is resolved as `jump` resolves it, with
+    /// the picker for several matches (or none given). New sessions are laid
+    /// out by [[tmux.windows]] in the config, or get a single shell.
+    ///
+    /// EXAMPLE:
+    ///   gitnav tmux                     # Pick a repository
+    ///   gitnav tmux api                 # Session for the repository named api
+    Tmux {
+        /// Query to match against repository names and paths
+        query: Option<String>,
+    },
+
+    /// Keep the repository list in memory and serve it to other gitnav runs
+    ///
+    /// Runs in the foreground until stopped. While it runs, gitnav asks it for
+    /// the repository list over a unix socket in the cache directory instead
+    /// of reading the cache or scanning, and falls back to both when it is not
+    /// running. The daemon rescans when a repository is cloned or deleted under
+    /// the search paths (daemon.watch) and every cache.ttl_seconds, and keeps
+    /// the cached scan up to date. --force still rescans directly.
+    ///
+    /// EXAMPLE:
+    ///   gitnav daemon &                # Start it for this session
+    ///   gitnav daemon --status         # Exit 0 if it is running
+    ///   gitnav daemon --stop
+    Daemon {
+        /// Stop the running daemon
+        #[arg(long, conflicts_with = "status")]
+        stop: bool,
+
+        /// Report whether a daemon is running (exit 1 if not)
+        #[arg(long)]
+        status: bool,
+    },
+
+    /// Show version information
+    ///
+    /// Display the installed version. Use --verbose for detailed build information.
+    ///
+    /// EXAMPLE:
+    ///   gitnav version              # Show version
+    ///   gitnav version --verbose    # Show build details
+    Version {
+        /// Show detailed version information (OS, arch, build profile)
+        #[arg(short, long)]
+        verbose: bool,
+    },
+}
+
+#[derive(Subcommand)]
+enum TagCommand {
+    /// Add tags to a repository
+    Add {
+        /// Repository path or name
+        repo: String,
+
+        /// Tags to add (no spaces or commas)
+        #[arg(required = true)]
+        tags: Vec<String>,
+    },
+
+    /// Remove tags from a repository (all of them when none are given)
+    #[command(alias = "rm")]
+    Remove {
+        /// Repository path or name
+        repo: String,
+
+        /// Tags to remove
+        tags: Vec<String>,
+    },
+
+    /// List tagged repositories, or the tags of one repository
+    List {
+        /// Repository path or name
+        repo: Option<String>,
+    },
+}
+
+#[derive(Subcommand)]
+enum ImportCommand {
+    /// Import the git repositories among VS Code's and JetBrains IDEs' recent
+    /// projects, tagged editor-recent
+    ///
+    /// Reads the recent lists of VS Code (also Insiders and VSCodium; needs
+    /// the sqlite3 tool) and of every JetBrains IDE and Android Studio. A
+    /// project inside a repository imports the repository.
+    Editors {
+        /// List the repositories that would be imported without tagging them
+        #[arg(long)]
+        dry_run: bool,
+    },
+}
+
+#[derive(Subcommand)]
+enum ConfigCommand {
+    /// Print a JSON Schema for the configuration file
+    ///
+    /// Point your editor's TOML language server (taplo / Even Better TOML) at the
+    /// output to get validation and autocompletion for config.toml.
+    ///
+    /// EXAMPLE:
+    ///   gitnav config schema > ~/.config/gitnav/config.schema.json
+    ///   # then add to the top of config.toml:
+    ///   #:schema ./config.schema.json
+    Schema,
+
+    /// Print the value of a setting
+    ///
+    /// Shows the value in effect, after the active profile and environment
+    /// variables. Prints nothing for an optional setting that is not set.
+    ///
+    /// EXAMPLE:
+    ///   gitnav config get search.max_depth
+    ///   gitnav config get ui
+    Get {
+        /// Setting, as <section>.<field>
+        key: String,
+    },
+
+    /// Change a setting in the config file
+    ///
+    /// Edits the file given with --config, else ~/.config/gitnav/config.toml
+    /// (created from the example if missing), keeping its comments. The value
+    /// is read as the setting's type, like an environment variable: lists as
+    /// `a:b` or `["a", "b"]`, and an empty value removes an optional setting.
+    ///
+    /// EXAMPLE:
+    ///   gitnav config set search.max_depth 7
+    ///   gitnav config set search.ignore_patterns '["vendor", "dist"]'
+    ///   gitnav config set profiles.work.ui.prompt 'work > '
+    ///   gitnav config set cache.directory ''
+    Set {
+        /// Setting, as <section>.<field>
+        key: String,
+
+        /// New value
+        #[arg(allow_hyphen_values = true)]
+        value: String,
+    },
+
+    /// Open the config file in $VISUAL or $EDITOR
+    ///
+    /// Opens the file given with --config, else the config file gitnav reads,
+    /// creating it from the example first if there is none. The file is
+    /// checked when the editor exits.
+    ///
+    /// EXAMPLE:
+    ///   gitnav config edit
+    ///   EDITOR=nano gitnav config edit
+    Edit,
+
+    /// Print the path of the config file in use
+    ///
+    /// Checks --config, then ~/.config/gitnav/config.toml, then the platform
+    /// config directory, and prints the first that exists. When none does,
+    /// prints where `config set` and `config edit` would create one, with a
+    /// warning on stderr.
+    ///
+    /// Settings are read from every config file that exists, each overriding
+    /// the ones before it; --all lists them, lowest priority first.
+    ///
+    /// EXAMPLE:
+    ///   gitnav config path
+    ///   gitnav config path --all
+    ///   code "$(gitnav config path)"
+    Path {
+        /// Print every config file that is read, lowest priority first
+        #[arg(long)]
+        all: bool,
+    },
+
+    /// Check the config file for mistakes
+    ///
+    /// Reports TOML syntax errors, unknown or misspelled keys, values of the
+    /// wrong type, and values gitnav rejects (such as max_depth = 0), each with
+    /// its line number. Every profile is checked too. Exits with 78 if there
+    /// are problems.
+    ///
+    /// Unknown keys are otherwise ignored when gitnav loads its config; use
+    /// --strict-config or GITNAV_STRICT_CONFIG=1 to make them errors.
+    ///
+    /// EXAMPLE:
+    ///   gitnav config validate
+    ///   gitnav config validate --file ./dotfiles/gitnav.toml
+    Validate {
+        /// Config file to check, instead of the one gitnav reads
+        #[arg(long, value_name = "PATH")]
+        file: Option<PathBuf>,
+    },
+}
+
+fn main() -> Result<()> {
+    let mut cli = Cli::parse();
+
+    // Config loading reads the profile from the environment, as do the
+    // processes gitnav starts (fzf's preview, background refreshes)
+    if let Some(ref profile) = cli.profile {
+        std::env::set_var(profile::PROFILE_ENV, profile);
+    }
+    if cli.strict_config {
+        std::env::set_var(config::STRICT_ENV, "1");
+    }
+
+    // Handle subcommands
+    if let Some(command) = cli.command.take() {
+        return handle_subcommand(command, &cli);
+    }
+
+    // Handle preview mode (called by fzf)
+    if let Some(repo_path) = cli.preview {
+        return handle_preview(&repo_path);
+    }
+    if let Some(ref repo_path) = cli.preview_json {
+        return handle_preview_command(&cli, Some(repo_path), false, true);
+    }
+
+    if let Some(ref repo_path) = cli.refresh_description {
+        return handle_refresh_description(&cli, repo_path);
+    }
+    if let Some(ref repo_path) = cli.refresh_github {
+        return handle_refresh_github(&cli, repo_path);
+    }
+
+    if cli.refresh_cache {
+        return handle_refresh_cache(&cli);
+    }
+
+    // Main navigation mode
+    run_navigation(&cli, cli.query.as_deref(), actions::Action::Navigate)
+}
+
+fn handle_subcommand(command: Commands, cli: &Cli) -> Result<()> {
+    match command {
+        Commands::Init {
+            shell,
+            check,
+            cmd,
+            no_ls,
+            post_cd,
+            widget,
+            widget_key,
+        } => {
+            let Some(shell) = shell.or_else(|| shell::detect_shell().map(String::from)) else {
+                let formatter = output::OutputFormatter::new(false, false, false);
+                let error = output::ErrorInfo::new(
+                    "ENOSHELL",
+                    "Could not detect your shell",
+                    "Neither the parent process nor $SHELL is a shell gitnav supports.",
+                    "Pass the shell explicitly: zsh, bash, fish, nu, nushell, or powershell.\n  Examples:\n    eval \"$(gitnav init zsh)\"\n    eval \"$(gitnav init bash)\"\n    gitnav init fish | source",
+                    "https://github.com/msetsma/gitnav#shell-integration",
+                );
+                formatter.error(&error);
+                std::process::exit(exit_codes::EXIT_GENERAL_ERROR);
+            };
+            if cli.debug {
+                eprintln!("DEBUG: Shell: {}", shell);
+            }
+            let options = shell::InitOptions {
+                cmd,
+                ls: !no_ls,
+                post_cd,
+            };
+            if check && shell::generate_init_script(&shell, &options).is_some() {
+                return handle_init_check(cli, &shell.to_lowercase(), &options.cmd);
+            }
+            if let Some(script) = shell::generate_init_script(&shell, &options) {
+                print!("{}", script);
+                if widget || widget_key.is_some() {
+                    match shell::generate_widget(&shell, widget_key.unwrap_or_default()) {
+                        Some(widget) => print!("{}", widget),
+                        None => {
+                            let formatter = output::OutputFormatter::new(false, false, false);
+                            formatter.warn(&format!(
+                                "No picker widget for {}; printing the gn function only",
+                                shell
+                            ));
+                        }
+                    }
+                }
+                Ok(())
+            } else {
+                let formatter = output::OutputFormatter::new(false, false, false);
+                let error = output::ErrorInfo::new(
+                    "ENOSUPPORT",
+                    "Unsupported shell",
+                    format!("The shell '{}' is not supported by gitnav.", shell),
+                    "Use one of the supported shells: zsh, bash, fish, nu, nushell, or powershell.\n  Examples:\n    gitnav init zsh\n    gitnav init bash\n    gitnav init fish\n    gitnav init nu\n    gitnav init powershell",
+                    "https://github.com/msetsma/gitnav#shell-integration"
+                );
+                formatter.error(&error);
+                std::process::exit(exit_codes::EXIT_GENERAL_ERROR);
+            }
+        }
+        Commands::Config { action } => match action {
+            None => {
+                println!("{}", config::Config::example_toml());
+                Ok(())
+            }
+            Some(ConfigCommand::Schema) => {
+                println!("{}", config::Config::json_schema());
+                Ok(())
+            }
+            Some(ConfigCommand::Get { key }) => {
+                let config = config::Config::load(cli.config.clone())?;
+                match config.get(&key)? {
+                    Some(toml::Value::String(text)) => println!("{}", text),
+                    Some(toml::Value::Table(table)) => print!("{}", toml::to_string(&table)?),
+                    Some(value) => println!("{}", value),
+                    None => {}
+                }
+                Ok(())
+            }
+            Some(ConfigCommand::Set { key, value }) => handle_config_set(cli, &key, &value),
+            Some(ConfigCommand::Edit) => handle_config_edit(cli),
+            Some(ConfigCommand::Validate { file }) => handle_config_validate(cli, file),
+            Some(ConfigCommand::Path { all: true }) => {
+                let layers = config::Config::layer_paths(cli.config.clone());
+                for path in layers.iter().filter(|path| path.exists()) {
+                    println!("{}", path.display());
+                }
+                Ok(())
+            }
+            Some(ConfigCommand::Path { all: false }) => {
+                let formatter = output::OutputFormatter::new(cli.quiet, cli.verbose, cli.no_color);
+                let path = config_file_path(cli)?;
+                println!("{}", path.display());
+                if !path.exists() {
+                    formatter.warn("This file does not exist yet; gitnav uses the defaults");
+                }
+                Ok(())
+            }
+        },
+        Commands::ClearCache { dry_run } => {
+            let formatter = output::OutputFormatter::new(false, false, false);
+            let config = config::Config::load(cli.config.clone())?;
+            let profile = profile::resolve(cli.profile.as_deref())?;
+            let cache_dir = cache::Cache::resolve_dir(config.cache.directory.as_deref())?;
+            let cache =
+                cache::Cache::new(&cache_dir, config.cache.ttl_seconds, profile.as_deref())?;
+
+            let cache_files = cache.list_cache_files()?;
+            let cache_size = cache.get_cache_size()?;
+            let scans = cache.scan_count()?;
+
+            if dry_run {
+                println!("Cache directory: {}", cache.cache_dir().display());
+                println!("Cached scans: {}", scans);
+                println!("Cache files: {}", cache_files.len());
+                println!("Total size: {} bytes\n", cache_size);
+
+                if !cache_files.is_empty() {
+                    println!("Files to be deleted:");
+                    for file in &cache_files {
+                        if let Ok(metadata) = std::fs::metadata(file) {
+                            println!("  {} ({} bytes)", file.display(), metadata.len());
+                        } else {
+                            println!("  {}", file.display());
+                        }
+                    }
+                } else {
+                    println!("No cache files to delete");
+                }
+            } else {
+                cache.clear()?;
+                match profile {
+                    Some(ref name) => formatter.success(&format!(
+                        "Cache for profile '{}' cleared successfully",
+                        name
+                    )),
+                    None => formatter.success("Cache cleared successfully"),
+                }
+                if scans > 0 {
+                    println!("Dropped {} cached scans", scans);
+                }
+                if !cache_files.is_empty() {
+                    println!(
+                        "Deleted {} cache files ({} bytes)",
+                        cache_files.len(),
+                        cache_size
+                    );
+                }
+            }
+            Ok(())
+        }
+        Commands::Exclude {
+            repo,
+            restore,
+            list,
+        } => handle_exclude(cli, repo.as_deref(), restore, list),
+        Commands::Recent { limit } => handle_recent(cli, limit),
+        Commands::Back => handle_back(cli),
+        Commands::Clone { url } => handle_clone(cli, &url),
+        Commands::Sync {
+            account,
+            dry_run,
+            topics,
+            visibility,
+        } => handle_sync(cli, account.as_deref(), dry_run, &topics, visibility),
+        Commands::Pin { repo, list } => handle_pin(cli, repo.as_deref(), list),
+        Commands::Unpin { repo } => handle_unpin(cli, &repo),
+        Commands::Tag { action } => handle_tag(cli, action),
+        Commands::Import {
+            source: ImportCommand::Editors { dry_run },
+        } => handle_import_editors(cli, dry_run),
+        Commands::Preview { path, plain, json } => {
+            handle_preview_command(cli, path.as_deref(), plain, json)
+        }
+        Commands::Status {
+            html,
+            markdown,
+            json,
+        } => handle_status(cli, html.as_deref(), markdown, json),
+        Commands::IdentityCheck { json } => handle_identity_check(cli, json),
+        Commands::Stats {
+            top,
+            markdown,
+            json,
+        } => handle_stats(cli, top, markdown, json),
+        Commands::Duplicates { json } => handle_duplicates(cli, json),
+        Commands::Unpushed { json } => handle_unpushed(cli, json),
+        Commands::Gc {
+            full,
+            register,
+            jobs,
+        } => {
+            let mode = if register {
+                gc::Mode::Register
+            } else if full {
+                gc::Mode::Full
+            } else {
+                gc::Mode::Auto
+            };
+            handle_gc(cli, mode, jobs)
+        }
+        Commands::Exec {
+            filter,
+            jobs,
+            fail_fast,
+            yes,
+            command,
+        } => handle_exec(cli, filter.as_deref(), jobs, fail_fast, yes, &command),
+        Commands::Grep {
+            pattern,
+            ignore_case,
+            fixed_strings,
+            jobs,
+            max_per_repo,
+            pick,
+        } => {
+            let options = grep::Options {
+                ignore_case,
+                fixed_strings,
+                max_per_repo: 0,
+            };
+            handle_grep(cli, &pattern, options, jobs, max_per_repo, pick)
+        }
+        Commands::FetchAll { jobs } => handle_fetch_all(cli, jobs),
+        Commands::Notify { fetch, desktop } => handle_notify(cli, fetch, desktop),
+        Commands::Prune { dry_run } => handle_prune(cli, dry_run),
+        Commands::PruneGone { dry_run, yes } => handle_prune_gone(cli, dry_run, yes),
+        Commands::Select { query, json, first } => handle_select(cli, &query, json, first),
+        Commands::Jump { query } => handle_jump(cli, &query),
+        Commands::Open { query } => handle_open(cli, query.as_deref()),
+        Commands::Files { query } => handle_files(cli, query.as_deref()),
+        Commands::Branch { query } => handle_branch(cli, query.as_deref()),
+        Commands::Worktree { query, new, list } => {
+            handle_worktree(cli, query.as_deref(), new.as_deref(), list)
+        }
+        Commands::Browse {
+            path,
+            branch,
+            print,
+        } => handle_browse(cli, path.as_deref(), branch, print),
+        Commands::Tmux { query } => handle_tmux(cli, query.as_deref()),
+        Commands::Daemon { stop, status } => handle_daemon(cli, stop, status),
+        Commands::Version { verbose } => {
+            println!("gitnav {}", env!("CARGO_PKG_VERSION"));
+
+            if verbose {
+                println!("\nBuild Information:");
+                println!("  Version: {}", env!("CARGO_PKG_VERSION"));
+                println!("  Authors: {}", env!("CARGO_PKG_AUTHORS"));
+                println!("  License: {}", env!("CARGO_PKG_LICENSE"));
+                println!("  Repository: {}", env!("CARGO_PKG_REPOSITORY"));
+                println!("\nSystem Information:");
+                println!("  OS: {}", std::env::consts::OS);
+                println!("  Architecture: {}", std::env::consts::ARCH);
+                #[cfg(debug_assertions)]
+                println!("  Build Profile: debug");
+                #[cfg(not(debug_assertions))]
+                println!("  Build Profile: release");
+
+                println!("\nFeatures:");
+                println!(
+                    "  Colors: {}",
+                    if output::should_use_color() {
+                        "enabled"
+                    } else {
+                        "disabled"
+                    }
+                );
+                println!("  Interactive Mode: enabled");
+
+                println!("\nDependencies:");
+                println!("  clap: 4.5");
+                println!("  git2: 0.19");
+                println!("  serde: 1.0");
+                println!("  chrono: 0.4");
+            }
+            Ok(())
+        }
+    }
+}
+
+fn handle_init_check(cli: &Cli, shell: &str, cmd: &str) -> Result<()> {
+    let formatter = output::OutputFormatter::new(cli.quiet, cli.verbose, cli.no_color);
+    let this_exe = std::env::current_exe().context("Failed to get current executable path")?;
+
+    let checks = match shell_check::run_probe(shell, cmd) {
+        Ok(probe) => shell_check::evaluate(shell, cmd, &probe, &this_exe),
+        Err(reason) => {
+            let error = output::ErrorInfo::new(
+                "ESHELLCHECK",
+                format!("Could not inspect {}", shell),
+                reason,
+                format!(
+                    "Run the check on a machine where {} is installed and on PATH.",
+                    shell
+                ),
+                "https://github.com/msetsma/gitnav#shell-integration",
+            );
+            formatter.error(&error);
+            std::process::exit(exit_codes::EXIT_UNAVAILABLE);
+        }
+    };
+
+    for check in &checks {
+        if check.passed {
+            println!("{} {}", formatter.green("✓"), check.label);
+        } else {
+            println!("{} {}", formatter.red("✗"), check.label);
+            if let Some(ref remedy) = check.remedy {
+                println!("  {}", remedy);
+            }
+        }
+    }
+
+    if checks.iter().any(|c| !c.passed) {
+        std::process::exit(exit_codes::EXIT_GENERAL_ERROR);
+    }
+    Ok(())
+}
+
+/// The config file gitnav reads, or would create.
+fn config_file_path(cli: &Cli) -> Result<PathBuf> {
+    config::Config::file_path(cli.config.clone())
+        .context("Could not determine the config file location")
+}
+
+/// The text of the config file at `path`, or the example configuration if
+/// there is none yet. A file laid over other config files starts empty
+/// instead, so that it does not replace every setting they make.
+fn config_file_contents(cli: &Cli, path: &Path) -> Result<String> {
+    if !path.exists() {
+        if overridden_files(cli, path).is_empty() {
+            return Ok(config::Config::example_toml());
+        }
+        return Ok(String::new());
+    }
+    std::fs::read_to_string(path)
+        .with_context(|| format!("Failed to read config file: {}", path.display()))
+}
+
+/// The existing config files that the one at `path` is laid over.
+fn overridden_files(cli: &Cli, path: &Path) -> Vec<PathBuf> {
+    config::Config::layer_paths(cli.config.clone())
+        .into_iter()
+        .take_while(|layer| layer != path)
+        .filter(|layer| layer.exists())
+        .collect()
+}
+
+fn write_config_file(path: &Path, contents: &str) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)
+            .with_context(|| format!("Failed to create {}", parent.display()))?;
+    }
+    std::fs::write(path, contents)
+        .with_context(|| format!("Failed to write config file: {}", path.display()))
+}
+
+fn handle_config_set(cli: &Cli, key: &str, value: &str) -> Result<()> {
+    let formatter = output::OutputFormatter::new(cli.quiet, cli.verbose, cli.no_color);
+    let path = config_file_path(cli)?;
+    let contents = config_file_contents(cli, &path)?;
+
+    let updated = config::Config::set_in(&contents, key, value)
+        .with_context(|| format!("Failed to set {} in {}", key, path.display()))?;
+    write_config_file(&path, &updated)?;
+    formatter.success(&format!("Set {} in {}", key, path.display()));
+    Ok(())
+}
+
+fn handle_config_edit(cli: &Cli) -> Result<()> {
+    let formatter = output::OutputFormatter::new(cli.quiet, cli.verbose, cli.no_color);
+    let path = config_file_path(cli)?;
+    // Prefer the environment: a broken config file is when this is needed most
+    let editor = ["VISUAL", "EDITOR"]
+        .iter()
+        .filter_map(|var| std::env::var(var).ok())
+        .find(|editor| !editor.trim().is_empty())
+        .or_else(|| {
+            let config = config::Config::load(cli.config.clone()).ok()?;
+            actions::editor_command(&config)
+        });
+    let Some(editor) = editor else {
+        let error = output::ErrorInfo::new(
+            "ENOEDITOR",
+            "No editor configured",
+            "Editing the config file needs an editor, but neither $VISUAL nor $EDITOR is set.",
+            "Set one of them, for example:\n  export EDITOR=vim",
+            "https://github.com/msetsma/gitnav#configuration",
+        );
+        formatter.error(&error);
+        std::process::exit(exit_codes::EXIT_UNAVAILABLE);
+    };
+
+    if !path.exists() {
+        let below = overridden_files(cli, &path);
+        let contents = if below.is_empty() {
+            config::Config::example_toml()
+        } else {
+            let below: Vec<String> = below.iter().map(|p| p.display().to_string()).collect();
+            format!("# Overrides the settings in {}\n", below.join(", "))
+        };
+        write_config_file(&path, &contents)?;
+        formatter.info(&format!("Created {}", path.display()));
+    }
+    actions::open_in_editor(&editor, &[path.display().to_string()], None)?;
+
+    let problems = config::Config::check(&config_file_contents(cli, &path)?)?;
+    if problems.is_empty() {
+        return Ok(());
+    }
+    formatter.warn(&format!("{} has problems:", path.display()));
+    for problem in &problems {
+        eprintln!("  {}", problem);
+    }
+    std::process::exit(exit_codes::EXIT_CONFIG_ERROR);
+}
+
+fn handle_config_validate(cli: &Cli, file: Option<PathBuf>) -> Result<()> {
+    let formatter = output::OutputFormatter::new(cli.quiet, cli.verbose, cli.no_color);
+    let path = match file {
+        Some(path) => path,
+        None => config_file_path(cli)?,
+    };
+    if !path.exists() {
+        anyhow::bail!("Config file not found: {}", path.display());
+    }
+    let contents = std::fs::read_to_string(&path)
+        .with_context(|| format!("Failed to read config file: {}", path.display()))?;
+
+    let problems = config::Config::check(&contents)?;
+    if problems.is_empty() {
+        formatter.success(&format!("{}: no problems found", path.display()));
+        return Ok(());
+    }
+    for problem in &problems {
+        match problem.line {
+            Some(line) => println!("{}:{}: {}", path.display(), line, problem.message),
+            None => println!("{}: {}", path.display(), problem.message),
+        }
+    }
+    std::process::exit(exit_codes::EXIT_CONFIG_ERROR);
+}
+
+fn handle_preview(repo_path: &PathBuf) -> Result<()> {
+    let config = config::Config::load(None)?;
+    let forge_info = cached_forge_info(&config, None, repo_path);
+    let preview_config = config.preview_for(repo_path)?;
+
+    // fzf re-runs this on every cursor move; reuse recent output for an unchanged repo
+    let use_color = std::env::var("NO_COLOR").is_err();
+    let store = (preview_config.cache_ttl_seconds > 0)
+        .then(|| cache::Cache::resolve_dir(config.cache.directory.as_deref()).ok())
+        .flatten()
+        .map(|dir| {
+            preview_cache::PreviewCache::new(
+                &dir,
+                std::time::Duration::from_secs(preview_config.cache_ttl_seconds),
+            )
+        });
+    let key = store.as_ref().and_then(|_| {
+        preview_cache::PreviewCache::key(repo_path, &preview_config, &forge_info, use_color)
+    });
+    if let (Some(store), Some(key)) = (&store, &key) {
+        if let Some(preview_text) = store.get(key) {
+            println!("{}", preview_text);
+            return Ok(());
+        }
+    }
+
+    let preview_text = preview::generate_preview_colored(repo_path, &preview_config, forge_info)?;
+    if let (Some(store), Some(key)) = (&store, &key) {
+        let _ = store.put(key, &preview_text);
+    }
+    println!("{}", preview_text);
+    Ok(())
+}
+
+/// What the preview shows from a repository's forge, as far as it is cached:
+/// the remote description when `forge.descriptions` is on, and GitHub stats
+/// when `forge.github_stats` is.
+///
+/// Never waits on the network: a missing or expired entry starts a background
+/// `--refresh-description` or `--refresh-github` process and the next preview
+/// picks up its result.
+fn cached_forge_info(
+    config: &config::Config,
+    config_path: Option<&Path>,
+    repo_path: &Path,
+) -> preview::ForgeInfo {
+    let mut info = preview::ForgeInfo::default();
+    if !config.forge.descriptions && !config.forge.github_stats {
+        return info;
+    }
+    let Some(remote) = remote::repo_remote(repo_path) else {
+        return info;
+    };
+    if forge::api_request(&remote).is_none() {
+        return info;
+    }
+    let Ok(cache_dir) = cache::Cache::resolve_dir(config.cache.directory.as_deref()) else {
+        return info;
+    };
+
+    if config.forge.descriptions {
+        let store = forge::DescriptionStore::new(&cache_dir, "descriptions");
+        info.description = cached_value(&store, &remote, || {
+            spawn_refresh(config_path, "--refresh-description", repo_path)
+        })
+        .flatten();
+    }
+    if config.forge.github_stats && github::is_github(&remote) {
+        let store = github::StatsStore::new(&cache_dir, "github");
+        info.github = cached_value(&store, &remote, || {
+            spawn_refresh(config_path, "--refresh-github", repo_path)
+        })
+        .flatten();
+    }
+    info
+}
+
+/// The value cached for `remote`, calling `refresh` when it is missing or
+/// expired and no other process is already refreshing it.
+fn cached_value<T: serde::Serialize + serde::de::DeserializeOwned>(
+    store: &forge::Store<T>,
+    remote: &remote::RemoteUrl,
+    refresh: impl FnOnce(),
+) -> Option<T> {
+    let (value, stale) = match store.get(remote) {
+        forge::Lookup::Fresh(v) => (Some(v), false),
+        forge::Lookup::Stale(v) => (Some(v), true),
+        forge::Lookup::Missing => (None, true),
+    };
+    if stale && !store.is_refreshing(remote) {
+        refresh();
+    }
+    value
+}
+
+/// Run `gitnav <flag> <repo_path>` in the background, detached from the preview.
+fn spawn_refresh(config_path: Option<&Path>, flag: &str, repo_path: &Path) {
+    if let Ok(exe) = std::env::current_exe() {
+        let mut cmd = std::process::Command::new(exe);
+        if let Some(path) = config_path {
+            cmd.arg("--config").arg(path);
+        }
+        let _ = cmd
+            .arg(flag)
+            .arg(repo_path)
+            .stdin(std::process::Stdio::null())
+            .stdout(std::process::Stdio::null())
+            .stderr(std::process::Stdio::null())
+            .spawn();
+    }
+}
+
+fn handle_refresh_description(cli: &Cli, repo_path: &Path) -> Result<()> {
+    let config = config::Config::load(cli.config.clone())?;
+    let Some(remote) = remote::repo_remote(repo_path) else {
+        return Ok(());
+    };
+    let cache_dir = cache::Cache::resolve_dir(config.cache.directory.as_deref())?;
+    let store = forge::DescriptionStore::new(&cache_dir, "descriptions");
+    let ttl = std::time::Duration::from_secs(config.forge.description_ttl_seconds);
+    forge::refresh(&store, &remote, ttl)
+}
+
+fn handle_refresh_github(cli: &Cli, repo_path: &Path) -> Result<()> {
+    let config = config::Config::load(cli.config.clone())?;
+    let Some(remote) = remote::repo_remote(repo_path) else {
+        return Ok(());
+    };
+    let cache_dir = cache::Cache::resolve_dir(config.cache.directory.as_deref())?;
+    let store = github::StatsStore::new(&cache_dir, "github");
+    let ttl = std::time::Duration::from_secs(config.forge.github_stats_ttl_seconds);
+    github::refresh(&store, &remote, ttl)
+}
+
+fn handle_exclude(cli: &Cli, repo: Option<&str>, restore: bool, list: bool) -> Result<()> {
+    let mut excludes = exclude::ExcludeList::load_default()?;
+
+    if list {
+        if excludes.entries().is_empty() {
+            println!("No excluded repositories");
+        } else {
+            for path in excludes.entries() {
+                println!("{}", path.display());
+            }
+        }
+        return Ok(());
+    }
+
+    let formatter = output::OutputFormatter::new(cli.quiet, cli.verbose, cli.no_color);
+    let repo = repo.context("A repository path or name is required")?;
+
+    if restore {
+        let target = resolve_listed(excludes.entries(), |p| excludes.contains(p), repo)?;
+        if excludes.remove(&target) {
+            excludes.save()?;
+            formatter.success(&format!("Restored {}", target.display()));
+        } else {
+            formatter.warn(&format!("{} is not excluded", target.display()));
+        }
+        return Ok(());
+    }
+
+    let config = config::Config::load(cli.config.clone())?;
+    let target = resolve_repo_arg(cli, &config, repo)?;
+    if excludes.add(&target) {
+        excludes.save()?;
+        formatter.success(&format!(
+            "Excluded {} (undo with: gitnav exclude --restore {})",
+            target.display(),
+            repo
+        ));
+    } else {
+        formatter.warn(&format!("{} is already excluded", target.display()));
+    }
+    Ok(())
+}
+
+/// The navigation history for the active profile.
+fn open_history(cli: &Cli, config: &config::Config) -> Result<history::History> {
+    let profile = profile::resolve(cli.profile.as_deref())?;
+    let cache_dir = cache::Cache::resolve_dir(config.cache.directory.as_deref())?;
+    history::History::load(
+        state::State::open_default()?,
+        profile.as_deref(),
+        &profile::scoped_dir(&cache_dir, profile.as_deref()),
+    )
+}
+
+fn handle_recent(cli: &Cli, limit: usize) -> Result<()> {
+    let config = config::Config::load(cli.config.clone())?;
+    let history = open_history(cli, &config)?;
+    let recent: Vec<_> = history.recent().into_iter().take(limit).collect();
+
+    if recent.is_empty() {
+        println!("No recently visited repositories");
+        return Ok(());
+    }
+
+    let now = chrono::Utc::now().timestamp();
+    let rows: Vec<(String, String, String)> = recent
+        .iter()
+        .map(|visit| {
+            (
+                preview::format_duration(chrono::Duration::seconds(now - visit.at)),
+                scanner::GitRepo::new(visit.path.clone()).name,
+                visit.path.display().to_string(),
+            )
+        })
+        .collect();
+    let when_width = rows.iter().map(|r| r.0.len()).max().unwrap_or(0);
+    let name_width = rows.iter().map(|r| r.1.len()).max().unwrap_or(0);
+    for (when, name, path) in rows {
+        println!(
+            "{:<ww$}  {:<nw$}  {}",
+            when,
+            name,
+            path,
+            ww = when_width,
+            nw = name_width
+        );
+    }
+    Ok(())
+}
+
+fn handle_back(cli: &Cli) -> Result<()> {
+    let config = config::Config::load(cli.config.clone())?;
+    let history = open_history(cli, &config)?;
+    let cwd = std::env::current_dir().context("Failed to get current directory")?;
+
+    match history.previous(&cwd) {
+        Some(visit) => {
+            let path = visit.path.display().to_string();
+            finish_selection(cli, &config, Some(path))
+        }
+        None => {
+            let formatter = output::OutputFormatter::new(cli.quiet, cli.verbose, cli.no_color);
+            let error = output::ErrorInfo::new(
+                "ENOHISTORY",
+                "No previous repository",
+                "There is no other recently visited repository to go back to.",
+                "Pick a repository with gn first; see what has been recorded with:\n  gitnav recent",
+                "https://github.com/msetsma/gitnav#usage",
+            );
+            formatter.error(&error);
+            std::process::exit(exit_codes::EXIT_GENERAL_ERROR);
+        }
+    }
+}
+
+fn handle_clone(cli: &Cli, url: &str) -> Result<()> {
+    let formatter = output::OutputFormatter::new(cli.quiet, cli.verbose, cli.no_color);
+    let config = config::Config::load(cli.config.clone())?;
+    config.validate()?;
+
+    let rewrites = git2::Config::open_default()
+        .map(|c| remote::Rewrites::from_config(&c))
+        .unwrap_or_default();
+    let Some(target) = clone::parse_target(url, &rewrites) else {
+        let error = output::ErrorInfo::new(
+            "EBADURL",
+            "Not a remote URL",
+            format!("Cannot tell which host and repository '{}' refers to.", url),
+            "Pass a remote URL or GitHub shorthand, for example:\n  gitnav clone git@github.com:owner/repo.git\n  gitnav clone owner/repo",
+            "https://github.com/msetsma/gitnav#usage",
+        );
+        formatter.error(&error);
+        std::process::exit(exit_codes::EXIT_USAGE_ERROR);
+    };
+
+    let search_paths = resolve_search_paths(cli, &config);
+    let root = PathBuf::from(&search_paths[0]);
+    let dest = clone::destination(&root, &target.remote);
+
+    if dest.join(".git").exists() {
+        formatter.info(&format!("Already cloned: {}", dest.display()));
+    } else {
+        if dest.exists() && dest.read_dir().is_ok_and(|mut d| d.next().is_some()) {
+            anyhow::bail!(
+                "{} already exists and is not a git repository",
+                dest.display()
+            );
+        }
+        clone::clone(&target.url, &dest)?;
+
+        run_hook(
+            &formatter,
+            &config,
+            hooks::Event::PostClone,
+            &[
+                ("GITNAV_REPO_PATH", dest.display().to_string()),
+                ("GITNAV_REPO_URL", target.url.clone()),
+            ],
+        );
+    }
+
+    add_to_cache(cli, &config, &search_paths, std::slice::from_ref(&dest))?;
+    println!("{}", dest.display());
+    Ok(())
+}
+
+/// Add new clones to a fresh cache so they show up without a rescan; a stale
+/// or missing cache will pick them up on the next scan anyway.
+fn add_to_cache(
+    cli: &Cli,
+    config: &config::Config,
+    search_paths: &[String],
+    paths: &[PathBuf],
+) -> Result<()> {
+    if !config.cache.enabled {
+        return Ok(());
+    }
+    let cache = open_cache(cli, config)?;
+    let key = cache_key(search_paths);
+    let cached = if cache.is_valid(&key) {
+        cache.load(&key)?
+    } else {
+        None
+    };
+    if let Some(mut repos) = cached {
+        let before = repos.len();
+        for path in paths {
+            if !repos.iter().any(|r| &r.path == path) {
+                repos.push(scanner::GitRepo::new(path.clone()));
+            }
+        }
+        if repos.len() > before {
+            scanner::sort_repos(&mut repos, config.search.order);
+            cache.save(&key, &repos)?;
+        }
+    }
+    Ok(())
+}
+
+fn handle_sync(
+    cli: &Cli,
+    account: Option<&str>,
+    dry_run: bool,
+    topics: &[String],
+    visibility: Option<config::Visibility>,
+) -> Result<()> {
+    let formatter = output::OutputFormatter::new(cli.quiet, cli.verbose, cli.no_color);
+    let config = config::Config::load(cli.config.clone())?;
+    config.validate()?;
+
+    let mut accounts = match account {
+        Some(arg) => {
+            let parsed = sync::parse_account(arg);
+            // A configured account keeps its token and filters
+            let configured = config.sync.accounts.iter().find(|a| {
+                a.host.eq_ignore_ascii_case(&parsed.host)
+                    && a.owner.eq_ignore_ascii_case(&parsed.owner)
+            });
+            vec![configured.cloned().unwrap_or(parsed)]
+        }
+        None => config.sync.accounts.clone(),
+    };
+    if accounts.is_empty() {
+        let error = output::ErrorInfo::new(
+            "ENOACCOUNTS",
+            "No accounts to sync",
+            "sync clones the repositories of a GitHub or GitLab account, and none was given or configured.",
+            "Name one, as in `gitnav sync my-org`, or add it to ~/.config/gitnav/config.toml:\n  [[sync.accounts]]\n  owner = \"my-org\"",
+            "https://github.com/msetsma/gitnav#usage",
+        );
+        formatter.error(&error);
+        std::process::exit(exit_codes::EXIT_GENERAL_ERROR);
+    }
+    for account in &mut accounts {
+        if !topics.is_empty() {
+            account.topics = topics.to_vec();
+        }
+        if let Some(visibility) = visibility {
+            account.visibility = visibility;
+        }
+    }
+
+    let search_paths = resolve_search_paths(cli, &config);
+    let root = PathBuf::from(&search_paths[0]);
+    let mut missing: Vec<(sync::ForgeRepo, PathBuf)> = Vec::new();
+    let mut present = 0;
+    let mut failed = false;
+    for account in &accounts {
+        let token = sync::token(account);
+        match sync::list(account, config.sync.protocol, token.as_deref()) {
+            Ok(repos) => {
+                for repo in repos
+                    .into_iter()
+                    .filter(|r| r.is_wanted(&account.topics, account.visibility))
+                {
+                    let dest = clone::destination(&root, &repo.remote);
+                    if dest.join(".git").exists() {
+                        present += 1;
+                    } else {
+                        missing.push((repo, dest));
+                    }
+                }
+            }
+            Err(e) => {
+                formatter.warn(&format!("{}/{}: {:#}", account.host, account.owner, e));
+                failed = true;
+            }
+        }
+    }
+
+    let home = dirs::home_dir();
+    if dry_run {
+        for (repo, dest) in &missing {
+            println!(
+                "{}  {}",
+                repo.remote,
+                scanner::shorten_home(dest, home.as_deref())
+            );
+        }
+        formatter.info(&format!(
+            "Would clone {} repositories; {} already cloned",
+            missing.len(),
+            present
+        ));
+    } else {
+        let mut cloned = Vec::new();
+        for (i, (repo, dest)) in missing.iter().enumerate() {
+            if !cli.quiet {
+                eprintln!("[{}/{}] {}", i + 1, missing.len(), repo.remote);
+            }
+            if dest.exists() && dest.read_dir().is_ok_and(|mut d| d.next().is_some()) {
+                formatter.warn(&format!(
+                    "{}: {} already exists and is not a git repository",
+                    repo.remote,
+                    dest.display()
+                ));
+                failed = true;
+                continue;
+            }
+            if let Err(e) = clone::clone(&repo.clone_url, dest) {
+                formatter.warn(&format!("{}: {:#}", repo.remote, e));
+                failed = true;
+                continue;
+            }
+            run_hook(
+                &formatter,
+                &config,
+                hooks::Event::PostClone,
+                &[
+                    ("GITNAV_REPO_PATH", dest.display().to_string()),
+                    ("GITNAV_REPO_URL", repo.clone_url.clone()),
+                ],
+            );
+            cloned.push(dest.clone());
+        }
+        add_to_cache(cli, &config, &search_paths, &cloned)?;
+        let summary = format!(
+            "Cloned {} repositories; {} already cloned",
+            cloned.len(),
+            present
+        );
+        if failed {
+            formatter.info(&summary);
+        } else {
+            formatter.success(&summary);
+        }
+    }
+
+    if failed {
+        std::process::exit(exit_codes::EXIT_GENERAL_ERROR);
+    }
+    Ok(())
+}
+
+fn handle_pin(cli: &Cli, repo: Option<&str>, list: bool) -> Result<()> {
+    let mut pins = pin::PinList::load_default()?;
+
+    if list {
+        if pins.entries().is_empty() {
+            println!("No pinned repositories");
+        } else {
+            for path in pins.entries() {
+                println!("{}", path.display());
+            }
+        }
+        return Ok(());
+    }
+
+    let formatter = output::OutputFormatter::new(cli.quiet, cli.verbose, cli.no_color);
+    let repo = repo.context("A repository path or name is required")?;
+    let config = config::Config::load(cli.config.clone())?;
+    let target = resolve_repo_arg(cli, &config, repo)?;
+    if pins.add(&target) {
+        pins.save()?;
+        formatter.success(&format!("Pinned {}", target.display()));
+    } else {
+        formatter.warn(&format!("{} is already pinned", target.display()));
+    }
+    Ok(())
+}
+
+fn handle_unpin(cli: &Cli, repo: &str) -> Result<()> {
+    let mut pins = pin::PinList::load_default()?;
+    let formatter = output::OutputFormatter::new(cli.quiet, cli.verbose, cli.no_color);
+
+    let target = resolve_listed(pins.entries(), |p| pins.contains(p), repo)?;
+    if pins.remove(&target) {
+        pins.save()?;
+        formatter.success(&format!("Unpinned {}", target.display()));
+    } else {
+        formatter.warn(&format!("{} is not pinned", target.display()));
+    }
+    Ok(())
+}
+
+fn handle_tag(cli: &Cli, action: TagCommand) -> Result<()> {
+    let mut store = tags::TagStore::load_default()?;
+    let formatter = output::OutputFormatter::new(cli.quiet, cli.verbose, cli.no_color);
+    let tagged: Vec<PathBuf> = store.entries().map(|(p, _)| p.to_path_buf()).collect();
+
+    match action {
+        TagCommand::Add { repo, tags } => {
+            if let Some(bad) = tags.iter().find(|t| !tags::is_valid(t)) {
+                anyhow::bail!(
+                    "Invalid tag '{}': tags cannot contain spaces or commas",
+                    bad
+                );
+            }
+            let config = config::Config::load(cli.config.clone())?;
+            let target = resolve_repo_arg(cli, &config, &repo)?;
+            let added = store.add(&target, &tags);
+            if added.is_empty() {
+                formatter.warn(&format!("{} already has those tags", target.display()));
+            } else {
+                store.save()?;
+                formatter.success(&format!(
+                    "Tagged {}: {}",
+                    target.display(),
+                    added.join(", ")
+                ));
+            }
+        }
+        TagCommand::Remove { repo, tags } => {
+            let target = resolve_listed(&tagged, |p| store.contains(p), &repo)?;
+            let removed = store.remove(&target, &tags);
+            if removed.is_empty() {
+                formatter.warn(&format!("{} has no such tags", target.display()));
+            } else {
+                store.save()?;
+                formatter.success(&format!(
+                    "Removed from {}: {}",
+                    target.display(),
+                    removed.join(", ")
+                ));
+            }
+        }
+        TagCommand::List { repo: Some(repo) } => {
+            let target = resolve_listed(&tagged, |p| store.contains(p), &repo)?;
+            for tag in store.tags_for(&target) {
+                println!("{}", tag);
+            }
+        }
+        TagCommand::List { repo: None } => {
+            if tagged.is_empty() {
+                println!("No tagged repositories");
+            }
+            for (path, tags) in store.entries() {
+                let tags: Vec<&str> = tags.iter().map(String::as_str).collect();
+                println!("{}  {}", path.display(), tags.join(" "));
+            }
+        }
+    }
+    Ok(())
+}
+
+fn handle_import_editors(cli: &Cli, dry_run: bool) -> Result<()> {
+    let formatter = output::OutputFormatter::new(cli.quiet, cli.verbose, cli.no_color);
+    let config_dir = dirs::config_dir()
+        .ok_or_else(|| anyhow::anyhow!("Could not determine config directory"))?;
+    let home = dirs::home_dir();
+
+    let sources = editors::sources(&config_dir, home.as_deref());
+    if sources.is_empty() {
+        formatter.info("No VS Code or JetBrains recent projects found");
+        return Ok(());
+    }
+    let mut dirs = Vec::new();
+    for source in &sources {
+        match source.dirs {
+            Ok(ref found) => {
+                if cli.verbose {
+                    eprintln!(
+                        "DEBUG: {} recent projects in {}",
+                        found.len(),
+                        source.file.display()
+                    );
+                }
+                dirs.extend(found.iter().cloned());
+            }
+            Err(ref e) => formatter.warn(&format!(
+                "Could not read {}'s recent projects: {:#}",
+                source.editor, e
+            )),
+        }
+    }
+
+    let mut store = tags::TagStore::load_default()?;
+    let repos = editors::repo_roots(&dirs, home.as_deref());
+    let new: Vec<&PathBuf> = repos
+        .iter()
+        .filter(|path| !store.tags_for(path).iter().any(|t| t == editors::TAG))
+        .collect();
+    for path in &new {
+        println!("{}", path.display());
+    }
+
+    if dry_run {
+        formatter.info(&format!(
+            "Would import {} of {} repositories in recent editor projects",
+            new.len(),
+            repos.len()
+        ));
+        return Ok(());
+    }
+    for path in &new {
+        store.add(path, &[editors::TAG.to_string()]);
+    }
+    if !new.is_empty() {
+        store.save()?;
+    }
+    formatter.success(&format!(
+        "Imported {} of {} repositories in recent editor projects (tagged {})",
+        new.len(),
+        repos.len(),
+        editors::TAG
+    ));
+    Ok(())
+}
+
+/// Resolve a repository argument given as a path or as the name of a discovered repository.
+fn resolve_repo_arg(cli: &Cli, config: &config::Config, repo: &str) -> Result<PathBuf> {
+    let expanded = PathBuf::from(shellexpand::tilde(repo).to_string());
+    if expanded.is_dir() {
+        return Ok(expanded.canonicalize().unwrap_or(expanded));
+    }
+
+    let search_paths = resolve_search_paths(cli, config);
+    let matches: Vec<_> = load_repos(cli, config, &search_paths)?
+        .into_iter()
+        .filter(|r| r.name == repo)
+        .collect();
+
+    match matches.len() {
+        0 => anyhow::bail!("No repository path or name matches '{}'", repo),
+        1 => Ok(matches[0].path.clone()),
+        _ => anyhow::bail!(
+            "'{}' matches {} repositories, pass a path instead:\n  {}",
+            repo,
+            matches.len(),
+            matches
+                .iter()
+                .map(|r| r.path.display().to_string())
+                .collect::<Vec<_>>()
+                .join("\n  ")
+        ),
+    }
+}
+
+/// Resolve a path or name against the entries of a stored list (excluded or pinned).
+///
+/// Used where the repository may no longer be discovered, such as
+/// `exclude --restore` and `unpin`.
+fn resolve_listed(
+    entries: &[PathBuf],
+    contains: impl Fn(&Path) -> bool,
+    repo: &str,
+) -> Result<PathBuf> {
+    let expanded = PathBuf::from(shellexpand::tilde(repo).to_string());
+    if contains(&expanded) {
+        return Ok(expanded);
+    }
+
+    let by_name: Vec<&PathBuf> = entries
+        .iter()
+        .filter(|p| p.file_name().and_then(|n| n.to_str()) == Some(repo))
+        .collect();
+
+    match by_name.as_slice() {
+        [single] => Ok((*single).clone()),
+        [] => Ok(expanded),
+        _ => anyhow::bail!(
+            "'{}' matches {} listed repositories, pass a path instead",
+            repo,
+            by_name.len()
+        ),
+    }
+}
+
+/// Determine search paths (CLI --path overrides everything; config.paths overrides base_path).
+fn resolve_search_paths(cli: &Cli, config: &config::Config) -> Vec<String> {
+    if let Some(ref p) = cli.path {
+        return vec![shellexpand::tilde(&p.to_string_lossy().to_string()).to_string()];
+    }
+    let mut paths: Vec<String> =
+        if !config.search.paths.is_empty() || !config.search.roots.is_empty() {
+            let roots = config.search.roots.iter().map(|root| &root.path);
+            config
+                .search
+                .paths
+                .iter()
+                .chain(roots)
+                .map(|p| shellexpand::tilde(p).to_string())
+                .collect()
+        } else {
+            vec![shellexpand::tilde(&config.search.base_path).to_string()]
+        };
+
+    // ghq roots already inside a search path are scanned anyway
+    if config.search.ghq_roots {
+        for root in ghq::roots() {
+            if !paths.iter().any(|p| root.starts_with(p)) {
+                paths.push(root.to_string_lossy().to_string());
+            }
+        }
+    }
+    paths
+}
+
+/// Get repos from cache or a fresh scan, without applying the exclude list.
+fn load_repos(
+    cli: &Cli,
+    config: &config::Config,
+    search_paths: &[String],
+) -> Result<Vec<scanner::GitRepo>> {
+    let max_depth = cli.max_depth.unwrap_or(config.search.max_depth);
+    let profile = profile::resolve(cli.profile.as_deref())?;
+    let cache_key = cache_key(search_paths);
+    let formatter = output::OutputFormatter::new(cli.quiet, cli.verbose, cli.no_color);
+
+    if cli.debug {
+        eprintln!("DEBUG: Search paths: {:?}", search_paths);
+        eprintln!("DEBUG: Max depth: {}", max_depth);
+        eprintln!("DEBUG: Cache key: {}", cache_key);
+        eprintln!("DEBUG: Profile: {}", profile.as_deref().unwrap_or("(none)"));
+        eprintln!("DEBUG: Cache enabled: {}", config.cache.enabled);
+        if let Some(ref dir) = config.cache.directory {
+            eprintln!("DEBUG: Cache directory: {}", dir);
+        }
+        eprintln!("DEBUG: Force refresh: {}", cli.force);
+    }
+
+    let daemon_index = daemon::Index {
+        key: cache_key.clone(),
+        max_depth,
+    };
+    let served = if cli.force {
+        None
+    } else {
+        daemon::query(&daemon_socket(cli, config)?, &daemon_index)
+    };
+
+    let mut repos = if let Some(mut repos) = served {
+        if cli.verbose {
+            eprintln!("DEBUG: Loading from the daemon");
+        }
+        scanner::drop_missing(&mut repos);
+        repos
+    } else if config.cache.enabled && !cli.force {
+        let cache = open_cache(cli, config)?;
+
+        let cached = if cache.is_valid(&cache_key) {
+            cache.load(&cache_key)?
+        } else {
+            None
+        };
+        match cached {
+            Some(mut repos) => {
+                if cli.verbose {
+                    eprintln!("DEBUG: Loading from cache");
+                }
+                let missing = scanner::drop_missing(&mut repos);
+                if !missing.is_empty() {
+                    if cli.verbose {
+                        eprintln!(
+                            "DEBUG: Dropping {} deleted repositories from the cache",
+                            missing.len()
+                        );
+                    }
+                    if let Err(e) = cache.forget(&cache_key, &missing) {
+                        formatter.warn(&format!("{:#}", e));
+                    }
+                }
+                repos
+            }
+            None => {
+                if cli.verbose {
+                    eprintln!("DEBUG: Cache miss, scanning repositories");
+                }
+                let cache = Some((&cache, cache_key.as_str()));
+                scan_and_cache(
+                    &formatter,
+                    config,
+                    search_paths,
+                    cli.max_depth,
+                    cache,
+                    |_| true,
+                )?
+                .unwrap_or_default()
+            }
+        }
+    } else {
+        if cli.verbose {
+            eprintln!("DEBUG: Scanning repositories (cache disabled or force refresh)");
+        }
+        scan_and_cache(
+            &formatter,
+            config,
+            search_paths,
+            cli.max_depth,
+            None,
+            |_| true,
+        )?
+        .unwrap_or_default()
+    };
+
+    // Cached lists may predate the configured order, so always re-sort
+    scanner::sort_repos(&mut repos, config.search.order);
+    Ok(repos)
+}
+
+/// Build a stable cache key from sorted canonical paths, so equivalent
+/// spellings of the same directories share one cache entry.
+fn cache_key(search_paths: &[String]) -> String {
+    let mut sorted = scanner::normalize_search_paths(search_paths);
+    sorted.sort();
+    sorted.join("|")
+}
+
+/// Branch and dirty state recorded in the cache, when `ui.cached_inline_meta`
+/// asks for it and the list came from the cache rather than stdin.
+fn cached_meta(
+    cli: &Cli,
+    config: &config::Config,
+    search_paths: &[String],
+) -> Result<Option<std::collections::HashMap<PathBuf, cache::CachedMeta>>> {
+    if !config.ui.cached_inline_meta || !config.cache.enabled || cli.stdin {
+        return Ok(None);
+    }
+    let cached = open_cache(cli, config)?.load_meta(cache_key(search_paths))?;
+    Ok((!cached.is_empty()).then_some(cached))
+}
+
+/// The repository cache for the active profile.
+fn open_cache(cli: &Cli, config: &config::Config) -> Result<cache::Cache> {
+    let profile = profile::resolve(cli.profile.as_deref())?;
+    let cache_dir = cache::Cache::resolve_dir(config.cache.directory.as_deref())?;
+    cache::Cache::new(&cache_dir, config.cache.ttl_seconds, profile.as_deref())
+}
+
+/// The socket `gitnav daemon` listens on for the active profile.
+fn daemon_socket(cli: &Cli, config: &config::Config) -> Result<PathBuf> {
+    let profile = profile::resolve(cli.profile.as_deref())?;
+    let cache_dir = cache::Cache::resolve_dir(config.cache.directory.as_deref())?;
+    Ok(daemon::socket_path(&profile::scoped_dir(
+        &cache_dir,
+        profile.as_deref(),
+    )))
+}
+
+/// Whether the repository list can be served from the cache without scanning.
+fn cache_is_fresh(cli: &Cli, config: &config::Config, search_paths: &[String]) -> Result<bool> {
+    if !config.cache.enabled || cli.force {
+        return Ok(false);
+    }
+    Ok(open_cache(cli, config)?.is_valid(cache_key(search_paths)))
+}
+
+/// Scan `search_paths` and run the `post_scan` hook, then write the result to
+/// `cache` (if given) and run `post_cache_refresh`.
+///
+/// `on_repo` sees each repository as soon as it is found and can stop the scan
+/// by returning `false`, in which case nothing is cached and `None` is returned.
+/// A scan cut short by `search.timeout_ms` or `search.max_results` is returned
+/// but not cached, so the next run scans again.
+fn scan_and_cache(
+    formatter: &output::OutputFormatter,
+    config: &config::Config,
+    search_paths: &[String],
+    max_depth_flag: Option<usize>,
+    cache: Option<(&cache::Cache, &str)>,
+    on_repo: impl FnMut(&scanner::GitRepo) -> bool,
+) -> Result<Option<Vec<scanner::GitRepo>>> {
+    let joined_paths = std::env::join_paths(search_paths)
+        .map(|p| p.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    run_hook(
+        formatter,
+        config,
+        hooks::Event::PreScan,
+        &[("GITNAV_SEARCH_PATHS", joined_paths.clone())],
+    );
+
+    let started = std::time::Instant::now();
+    let Some(scanned) = scanner::scan_repos_streaming(
+        search_paths,
+        &scanner::ScanOptions::from_config(&config.search, max_depth_flag),
+        on_repo,
+    )?
+    else {
+        return Ok(None);
+    };
+    let repos = scanned.repos;
+
+    run_hook(
+        formatter,
+        config,
+        hooks::Event::PostScan,
+        &[
+            ("GITNAV_REPO_COUNT", repos.len().to_string()),
+            (
+                "GITNAV_DURATION_MS",
+                started.elapsed().as_millis().to_string(),
+            ),
+            ("GITNAV_SEARCH_PATHS", joined_paths),
+        ],
+    );
+
+    if let Some((cache, key)) = cache.filter(|_| !scanned.partial) {
+        cache.save(key, &repos)?;
+        run_hook(
+            formatter,
+            config,
+            hooks::Event::PostCacheRefresh,
+            &[
+                ("GITNAV_REPO_COUNT", repos.len().to_string()),
+                ("GITNAV_CACHE_DIR", cache.cache_dir().display().to_string()),
+            ],
+        );
+    }
+    Ok(Some(repos))
+}
+
+/// Run a user hook, reporting failures as warnings so a broken hook never
+/// blocks navigation.
+fn run_hook(
+    formatter: &output::OutputFormatter,
+    config: &config::Config,
+    event: hooks::Event,
+    env: &[(&str, String)],
+) {
+    if let Err(e) = hooks::run(&config.hooks, event, env) {
+        formatter.warn(&format!("{:#}", e));
+    }
+}
+
+/// Get repos from cache or a fresh scan, with excluded repositories removed.
+fn discover_repos(
+    cli: &Cli,
+    config: &config::Config,
+    search_paths: &[String],
+) -> Result<Vec<scanner::GitRepo>> {
+    let repos = merge_zoxide(cli, config, load_repos(cli, config, search_paths)?);
+    let repos = merge_imported(config, repos)?;
+    let excludes = exclude::ExcludeList::load_default()?;
+    filter_tags(cli, excludes.filter(repos))
+}
+
+/// Add the repositories `gitnav import` tagged, which are listed wherever
+/// they are, like zoxide's.
+fn merge_imported(
+    config: &config::Config,
+    repos: Vec<scanner::GitRepo>,
+) -> Result<Vec<scanner::GitRepo>> {
+    let extra = editors::imported(&tags::TagStore::load_default()?);
+    if extra.is_empty() {
+        return Ok(repos);
+    }
+    let mut merged = repos;
+    merged.extend(extra);
+    let mut merged = scanner::dedup_repos(merged);
+    scanner::sort_repos(&mut merged, config.search.order);
+    Ok(merged)
+}
+
+/// Whether zoxide's repositories join the list (`--from-zoxide` or `zoxide.merge`).
+fn uses_zoxide(cli: &Cli, config: &config::Config) -> bool {
+    cli.from_zoxide || config.zoxide.merge
+}
+
+/// Add the git repositories in zoxide's database to `repos` when asked to.
+///
+/// A missing or failing zoxide is reported and leaves `repos` as they are.
+fn merge_zoxide(
+    cli: &Cli,
+    config: &config::Config,
+    repos: Vec<scanner::GitRepo>,
+) -> Vec<scanner::GitRepo> {
+    if !uses_zoxide(cli, config) {
+        return repos;
+    }
+    let dirs = match zoxide::list() {
+        Ok(dirs) => dirs,
+        Err(e) => {
+            let formatter = output::OutputFormatter::new(cli.quiet, cli.verbose, cli.no_color);
+            formatter.warn(&format!("Could not read zoxide's database: {:#}", e));
+            return repos;
+        }
+    };
+    let extra = zoxide::repos(&dirs);
+    if cli.verbose {
+        eprintln!(
+            "DEBUG: {} of zoxide's {} directories are repositories",
+            extra.len(),
+            dirs.len()
+        );
+    }
+    let mut merged = zoxide::merge(repos, extra);
+    scanner::sort_repos(&mut merged, config.search.order);
+    merged
+}
+
+/// Keep repositories carrying every `--tag`.
+fn filter_tags(cli: &Cli, repos: Vec<scanner::GitRepo>) -> Result<Vec<scanner::GitRepo>> {
+    if cli.tags.is_empty() {
+        return Ok(repos);
+    }
+    Ok(tags::TagStore::load_default()?.filter(repos, &cli.tags))
+}
+
+/// Where repositories were looked for, for error messages.
+fn describe_source(cli: &Cli, source: String) -> String {
+    if cli.tags.is_empty() {
+        source
+    } else {
+        format!("{} (tagged {})", source, cli.tags.join(", "))
+    }
+}
+
+fn handle_status(cli: &Cli, html: Option<&Path>, markdown: bool, json: bool) -> Result<()> {
+    let formatter = output::OutputFormatter::new(cli.quiet, cli.verbose, cli.no_color);
+    let config = config::Config::load(cli.config.clone())?;
+    config.validate()?;
+
+    let search_paths = resolve_search_paths(cli, &config);
+    let repos = discover_repos(cli, &config, &search_paths)?;
+    let summaries = status::collect(&repos, &config.preview.date_format);
+
+    if json {
+        let json_output = serde_json::to_string_pretty(&summaries)
+            .context("Failed to serialize status to JSON")?;
+        println!("{}", json_output);
+        return Ok(());
+    }
+
+    let notes = vec![
+        format!(
+            "Generated {} by gitnav {}",
+            chrono::Local::now().format("%Y-%m-%d %H:%M:%S %z"),
+            env!("CARGO_PKG_VERSION")
+        ),
+        format!(
+            "{} repositories in {}",
+            summaries.len(),
+            search_paths.join(", ")
+        ),
+    ];
+    let report = status::build_report(&summaries, notes);
+
+    match html {
+        Some(file) if file == Path::new("-") => print!("{}", report.render_html()),
+        Some(file) => {
+            std::fs::write(file, report.render_html())
+                .with_context(|| format!("Failed to write HTML report: {}", file.display()))?;
+            formatter.success(&format!(
+                "Wrote status report for {} repositories to {}",
+                summaries.len(),
+                file.display()
+            ));
+        }
+        None if markdown => println!("{}", report.render_markdown()),
+        None => println!("{}", report.render_text()),
+    }
+    Ok(())
+}
+
+fn handle_identity_check(cli: &Cli, json: bool) -> Result<()> {
+    let formatter = output::OutputFormatter::new(cli.quiet, cli.verbose, cli.no_color);
+    let config = config::Config::load(cli.config.clone())?;
+    config.validate()?;
+
+    if config.identity.rules.is_empty() {
+        let error = output::ErrorInfo::new(
+            "ENORULES",
+            "No identity rules configured",
+            "identity-check compares each repository's user.email with the rules for its directory, and none are set.",
+            "Add a rule to ~/.config/gitnav/config.toml:\n  [[identity.rules]]\n  path = \"~/work\"\n  email = \"*@corp.com\"",
+            "https://github.com/msetsma/gitnav#usage",
+        );
+        formatter.error(&error);
+        std::process::exit(exit_codes::EXIT_GENERAL_ERROR);
+    }
+
+    let search_paths = resolve_search_paths(cli, &config);
+    let repos = discover_repos(cli, &config, &search_paths)?;
+    let violations = identity::check(&repos, &config.identity.rules);
+
+    if json {
+        let json_output = serde_json::to_string_pretty(&violations)
+            .context("Failed to serialize identity check to JSON")?;
+        println!("{}", json_output);
+    } else if violations.is_empty() {
+        formatter.success("Every repository matches its identity rule");
+    } else {
+        println!("{}", identity::build_report(&violations).render_text());
+    }
+
+    if !violations.is_empty() {
+        std::process::exit(exit_codes::EXIT_GENERAL_ERROR);
+    }
+    Ok(())
+}
+
+fn handle_stats(cli: &Cli, top: usize, markdown: bool, json: bool) -> Result<()> {
+    let config = config::Config::load(cli.config.clone())?;
+    config.validate()?;
+
+    let search_paths = resolve_search_paths(cli, &config);
+    let repos = discover_repos(cli, &config, &search_paths)?;
+    let summary = stats::summarize(&stats::collect(&repos), top);
+
+    if json {
+        let json_output =
+            serde_json::to_string_pretty(&summary).context("Failed to serialize stats to JSON")?;
+        println!("{}", json_output);
+        return Ok(());
+    }
+
+    println!(
+        "{} repositories, {} with uncommitted changes",
+        summary.total, summary.dirty
+    );
+    let now = chrono::Utc::now().timestamp();
+    for report in stats::build_reports(&summary, now) {
+        if report.rows.is_empty() {
+            continue;
+        }
+        if markdown {
+            println!("\n{}", report.render_markdown());
+        } else {
+            println!("\n{}\n{}", report.title, report.render_text());
+        }
+    }
+    Ok(())
+}
+
+fn handle_duplicates(cli: &Cli, json: bool) -> Result<()> {
+    let formatter = output::OutputFormatter::new(cli.quiet, cli.verbose, cli.no_color);
+    let config = config::Config::load(cli.config.clone())?;
+    config.validate()?;
+
+    let search_paths = resolve_search_paths(cli, &config);
+    let repos = discover_repos(cli, &config, &search_paths)?;
+    let groups = duplicates::find(&repos);
+
+    if json {
+        let json_output = serde_json::to_string_pretty(&groups)
+            .context("Failed to serialize duplicates to JSON")?;
+        println!("{}", json_output);
+    } else if groups.is_empty() {
+        formatter.success("No repository is cloned more than once");
+    } else {
+        let reports: Vec<String> = groups
+            .iter()
+            .map(|group| {
+                let report = duplicates::build_report(group);
+                format!("{}\n{}", report.title, report.render_text())
+            })
+            .collect();
+        println!("{}", reports.join("\n\n"));
+    }
+    Ok(())
+}
+
+fn handle_unpushed(cli: &Cli, json: bool) -> Result<()> {
+    let formatter = output::OutputFormatter::new(cli.quiet, cli.verbose, cli.no_color);
+    let config = config::Config::load(cli.config.clone())?;
+    config.validate()?;
+
+    let search_paths = resolve_search_paths(cli, &config);
+    let repos = discover_repos(cli, &config, &search_paths)?;
+    let found = unpushed::collect(&repos);
+
+    if json {
+        let json_output = serde_json::to_string_pretty(&found)
+            .context("Failed to serialize unpushed work to JSON")?;
+        println!("{}", json_output);
+    } else if found.is_empty() {
+        formatter.success("Everything is pushed");
+    } else {
+        let reports: Vec<String> = unpushed::build_reports(&found)
+            .into_iter()
+            .filter(|report| !report.rows.is_empty())
+            .map(|report| format!("{}\n{}", report.title, report.render_text()))
+            .collect();
+        println!("{}", reports.join("\n\n"));
+    }
+
+    if !found.is_empty() {
+        std::process::exit(exit_codes::EXIT_GENERAL_ERROR);
+    }
+    Ok(())
+}
+
+fn handle_exec(
+    cli: &Cli,
+    filter: Option<&str>,
+    jobs: usize,
+    fail_fast: bool,
+    yes: bool,
+    command: &[String],
+) -> Result<()> {
+    let formatter = output::OutputFormatter::new(cli.quiet, cli.verbose, cli.no_color);
+    let config = config::Config::load(cli.config.clone())?;
+    config.validate()?;
+
+    let search_paths = resolve_search_paths(cli, &config);
+    let mut repos = discover_repos(cli, &config, &search_paths)?;
+    if let Some(query) = filter {
+        // Keep the listing order rather than the match ranking
+        let mut matched: Vec<usize> = matcher::rank(query, &repos, &config.matcher)
+            .iter()
+            .map(|r| r.index)
+            .collect();
+        matched.sort_unstable();
+        repos = matched.into_iter().map(|i| repos[i].clone()).collect();
+    }
+    if repos.is_empty() {
+        let error = output::ErrorInfo::new(
+            "ENOMATCH",
+            "No matching repositories",
+            format!("No repository matches '{}'.", filter.unwrap_or_default()),
+            "Try a shorter or looser query, or list everything with:\n  gitnav --list",
+            "https://github.com/msetsma/gitnav#usage",
+        );
+        formatter.error(&error);
+        std::process::exit(exit_codes::EXIT_GENERAL_ERROR);
+    }
+
+    let action = format!("Run `{}`", command.join(" "));
+    let destructive = bulk::looks_destructive(command);
+    if !bulk::confirm(&action, &repos, &config.bulk.confirm, destructive, yes)? {
+        formatter.info("Aborted; nothing was run");
+        return Ok(());
+    }
+
+    let jobs = match jobs {
+        0 => parallel::default_workers(),
+        n => n,
+    };
+    let name_width = repos.iter().map(|r| r.name.len()).max().unwrap_or(0);
+    let results = exec::run_all(&repos, command, jobs, fail_fast, |repo, stream, line| {
+        let prefix = formatter.cyan(&format!("{:<w$} |", repo.name, w = name_width));
+        match stream {
+            exec::Stream::Stdout => println!("{} {}", prefix, line),
+            exec::Stream::Stderr => eprintln!("{} {}", prefix, line),
+        }
+    });
+
+    let failed = results
+        .iter()
+        .any(|r| !matches!(r.outcome, exec::Outcome::Success));
+    if failed || !cli.quiet {
+        eprintln!("{}", exec::format_summary(&results));
+    }
+    if failed {
+        std::process::exit(exit_codes::EXIT_GENERAL_ERROR);
+    }
+    Ok(())
+}
+
+fn handle_daemon(cli: &Cli, stop: bool, status: bool) -> Result<()> {
+    let formatter = output::OutputFormatter::new(cli.quiet, cli.verbose, cli.no_color);
+    let config = config::Config::load(cli.config.clone())?;
+    config.validate()?;
+    let socket = daemon_socket(cli, &config)?;
+
+    if stop {
+        if daemon::stop(&socket) {
+            formatter.success("Stopped the daemon");
+        } else {
+            formatter.info("No daemon is running");
+        }
+        return Ok(());
+    }
+    if status {
+        if !daemon::is_running(&socket) {
+            formatter.info("No daemon is running");
+            std::process::exit(exit_codes::EXIT_GENERAL_ERROR);
+        }
+        formatter.success(&format!("Daemon running on {}", socket.display()));
+        return Ok(());
+    }
+    if daemon::is_running(&socket) {
+        anyhow::bail!("A daemon is already running on {}", socket.display());
+    }
+
+    let search_paths = resolve_search_paths(cli, &config);
+    let max_depth = cli.max_depth.unwrap_or(config.search.max_depth);
+    let index = daemon::Index {
+        key: cache_key(&search_paths),
+        max_depth,
+    };
+    // Scans run on the server's threads, which share one database connection
+    let cache = if config.cache.enabled {
+        Some(std::sync::Mutex::new(open_cache(cli, &config)?))
+    } else {
+        None
+    };
+    let ttl = std::time::Duration::from_secs(config.cache.ttl_seconds.max(1));
+    let mut watcher = config.daemon.watch.then(|| {
+        watch::Watcher::new(
+            &search_paths,
+            scanner::ScanOptions::from_config(&config.search, cli.max_depth),
+            config.daemon.poll,
+        )
+    });
+    let poll = match &watcher {
+        Some(watcher) if watcher.is_polling() => {
+            if !config.daemon.poll {
+                formatter.warn(&format!(
+                    "Filesystem events are unavailable; checking the search paths every {}s",
+                    config.daemon.poll_seconds
+                ));
+            }
+            std::time::Duration::from_secs(config.daemon.poll_seconds).min(ttl)
+        }
+        Some(_) => watch::EVENT_CHECK.min(ttl),
+        None => ttl,
+    };
+    let mut scanned_at = std::time::Instant::now();
+
+    formatter.info(&format!(
+        "Serving repositories in {} on {} (Ctrl-C to stop)",
+        search_paths.join(", "),
+        socket.display()
+    ));
+    daemon::serve(
+        &socket,
+        &index,
+        poll,
+        || {
+            let cache = cache
+                .as_ref()
+                .map(|cache| cache.lock().unwrap_or_else(|e| e.into_inner()));
+            let cache = cache.as_deref().map(|cache| (cache, index.key.as_str()));
+            let repos = scan_and_cache(
+                &formatter,
+                &config,
+                &search_paths,
+                cli.max_depth,
+                cache,
+                |_| true,
+            )?
+            .unwrap_or_default();
+            if cli.verbose {
+                eprintln!("DEBUG: Scanned {} repositories", repos.len());
+            }
+            Ok(repos)
+        },
+        || {
+            let changed = watcher.as_mut().is_some_and(|w| w.changed());
+            if cli.verbose && changed {
+                eprintln!("DEBUG: Search paths changed, rescanning");
+            }
+            if changed || scanned_at.elapsed() >= ttl {
+                scanned_at = std::time::Instant::now();
+                return true;
+            }
+            false
+        },
+        |e| formatter.warn(&format!("{:#}", e)),
+    )
+}
+
+fn handle_fetch_all(cli: &Cli, jobs: Option<usize>) -> Result<()> {
+    let formatter = output::OutputFormatter::new(cli.quiet, cli.verbose, cli.no_color);
+    let config = config::Config::load(cli.config.clone())?;
+    config.validate()?;
+
+    let search_paths = resolve_search_paths(cli, &config);
+    let repos = discover_repos(cli, &config, &search_paths)?;
+    let jobs = match jobs.unwrap_or(config.bulk.jobs) {
+        0 => parallel::default_workers(),
+        n => n,
+    };
+
+    // `[12/40] api` rewritten in place; only on a terminal so logs stay clean
+    let progress = !cli.quiet && atty::is(atty::Stream::Stderr);
+    let done = std::sync::atomic::AtomicUsize::new(0);
+    let failures = fetch::fetch_all(&repos, jobs, |repo| {
+        let n = done.fetch_add(1, std::sync::atomic::Ordering::Relaxed) + 1;
+        if progress {
+            eprint!("\r\x1b[2K[{}/{}] {}", n, repos.len(), repo.name);
+        }
+    });
+    if progress {
+        eprint!("\r\x1b[2K");
+    }
+
+    if failures.is_empty() {
+        formatter.success(&format!("Fetched {} repositories", repos.len()));
+        return Ok(());
+    }
+
+    let name_width = failures
+        .iter()
+        .map(|f| f.repo.name.len())
+        .max()
+        .unwrap_or(0);
+    for failure in &failures {
+        formatter.warn(&format!(
+            "{:<width$}  {}",
+            failure.repo.name,
+            failure.message,
+            width = name_width
+        ));
+    }
+    formatter.info(&format!(
+        "Fetched {} of {} repositories; {} failed",
+        repos.len() - failures.len(),
+        repos.len(),
+        failures.len()
+    ));
+    std::process::exit(exit_codes::EXIT_GENERAL_ERROR);
+}
+
+fn handle_gc(cli: &Cli, mode: gc::Mode, jobs: Option<usize>) -> Result<()> {
+    let formatter = output::OutputFormatter::new(cli.quiet, cli.verbose, cli.no_color);
+    let config = config::Config::load(cli.config.clone())?;
+    config.validate()?;
+
+    let search_paths = resolve_search_paths(cli, &config);
+    let repos = gc::main_repos(discover_repos(cli, &config, &search_paths)?);
+    let jobs = match jobs.unwrap_or(config.gc.jobs) {
+        0 => parallel::default_workers(),
+        n => n,
+    };
+
+    // `[12/40] api` rewritten in place; only on a terminal so logs stay clean
+    let progress = !cli.quiet && atty::is(atty::Stream::Stderr);
+    let done = std::sync::atomic::AtomicUsize::new(0);
+    let outcomes = gc::run_all(&repos, mode, jobs, |repo| {
+        let n = done.fetch_add(1, std::sync::atomic::Ordering::Relaxed) + 1;
+        if progress {
+            eprint!("\r\x1b[2K[{}/{}] {}", n, repos.len(), repo.name);
+        }
+    });
+    if progress {
+        eprint!("\r\x1b[2K");
+    }
+
+    let mut reclaimed: Vec<(&str, u64)> = outcomes
+        .iter()
+        .filter_map(|o| Some((o.repo.name.as_str(), *o.result.as_ref().ok()?)))
+        .filter(|(_, bytes)| *bytes > 0)
+        .collect();
+    reclaimed.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(b.0)));
+    let name_width = reclaimed
+        .iter()
+        .map(|(name, _)| name.len())
+        .max()
+        .unwrap_or(0);
+    for (name, bytes) in &reclaimed {
+        println!(
+            "{:<width$}  {}",
+            name,
+            stats::format_size(*bytes),
+            width = name_width
+        );
+    }
+    let total: u64 = reclaimed.iter().map(|(_, bytes)| bytes).sum();
+    let failures: Vec<&gc::Outcome> = outcomes.iter().filter(|o| o.result.is_err()).collect();
+    let succeeded = outcomes.len() - failures.len();
+    let summary = match mode {
+        gc::Mode::Register => format!(
+            "Registered {} repositories for background maintenance",
+            succeeded
+        ),
+        _ => format!(
+            "Maintained {} repositories; reclaimed {}",
+            succeeded,
+            stats::format_size(total)
+        ),
+    };
+
+    if failures.is_empty() {
+        formatter.success(&summary);
+        return Ok(());
+    }
+
+    let name_width = failures
+        .iter()
+        .map(|f| f.repo.name.len())
+        .max()
+        .unwrap_or(0);
+    for failure in &failures {
+        if let Err(message) = &failure.result {
+            formatter.warn(&format!(
+                "{:<width$}  {}",
+                failure.repo.name,
+                message,
+                width = name_width
+            ));
+        }
+    }
+    formatter.info(&format!("{}; {} failed", summary, failures.len()));
+    std::process::exit(exit_codes::EXIT_GENERAL_ERROR);
+}
+
+fn handle_grep(
+    cli: &Cli,
+    pattern: &str,
+    mut options: grep::Options,
+    jobs: Option<usize>,
+    max_per_repo: Option<usize>,
+    pick: bool,
+) -> Result<()> {
+    let formatter = output::OutputFormatter::new(cli.quiet, cli.verbose, cli.no_color);
+    let config = config::Config::load(cli.config.clone())?;
+    config.validate()?;
+    // Resolve the editor first, so a missing one is reported before searching
+    let editor = pick.then(|| editor_or_exit(cli, &config));
+
+    let search_paths = resolve_search_paths(cli, &config);
+    let repos = discover_repos(cli, &config, &search_paths)?;
+    let jobs = match jobs.unwrap_or(config.grep.jobs) {
+        0 => parallel::default_workers(),
+        n => n,
+    };
+    options.max_per_repo = max_per_repo.unwrap_or(config.grep.max_per_repo);
+
+    let results = grep::search_all(&repos, pattern, &options, jobs);
+    let mut hits: Vec<(&scanner::GitRepo, grep::Match)> = Vec::new();
+    let mut failed = false;
+    for (repo, result) in repos.iter().zip(results) {
+        match result {
+            Ok(found) => {
+                // Notes go to stderr, leaving stdout to the matches
+                if found.truncated && !cli.quiet {
+                    eprintln!(
+                        "{}: stopped after {} matches (max_per_repo)",
+                        repo.name, options.max_per_repo
+                    );
+                }
+                hits.extend(found.matches.into_iter().map(|m| (repo, m)));
+            }
+            Err(e) => {
+                formatter.warn(&format!("{}: {:#}", repo.name, e));
+                failed = true;
+            }
+        }
+    }
+
+    let lines: Vec<String> = hits
+        .iter()
+        .map(|(repo, m)| format!("{}:{}:{}:{}", repo.name, m.file, m.line, m.text))
+        .collect();
+    if lines.is_empty() {
+        // Like grep, a search that found nothing fails
+        if !cli.quiet {
+            eprintln!("No matches for '{}'", pattern);
+        }
+        std::process::exit(exit_codes::EXIT_GENERAL_ERROR);
+    }
+    if let Some(editor) = editor {
+        // The picker shows the text before the first tab
+        let labels: Vec<String> = lines.iter().map(|l| l.replace('\t', "    ")).collect();
+        let labels: Vec<&str> = labels.iter().map(String::as_str).collect();
+        let mut picker_config = config.clone();
+        picker_config.ui.prompt = String::from("grep > ");
+        picker_config.ui.header = String::from("Match (↑/↓, ⏎, Esc)");
+        let chosen = if use_fzf(&config) {
+            fzf::select_menu(&labels, &picker_config)?
+        } else {
+            finder::select_menu(&labels, &picker_config)?
+        };
+        let Some((repo, found)) = chosen.and_then(|index| hits.get(index)) else {
+            std::process::exit(exit_codes::EXIT_INTERRUPTED);
+        };
+        return actions::open_in_editor(
+            &editor,
+            std::slice::from_ref(&found.file),
+            Some(&repo.path),
+        );
+    }
+
+    use std::io::Write;
+    let mut stdout = std::io::stdout().lock();
+    for line in &lines {
+        writeln!(stdout, "{}", line)?;
+    }
+    stdout.flush()?;
+    if failed {
+        std::process::exit(exit_codes::EXIT_GENERAL_ERROR);
+    }
+    Ok(())
+}
+
+fn handle_notify(cli: &Cli, fetch: bool, desktop: bool) -> Result<()> {
+    let formatter = output::OutputFormatter::new(cli.quiet, cli.verbose, cli.no_color);
+    let config = config::Config::load(cli.config.clone())?;
+    config.validate()?;
+
+    let search_paths = resolve_search_paths(cli, &config);
+    let repos = discover_repos(cli, &config, &search_paths)?;
+
+    if fetch {
+        for failure in fetch::fetch_all(&repos, parallel::default_workers(), |_| {}) {
+            formatter.warn(&format!("Fetch failed for {}", failure.repo.path.display()));
+        }
+    }
+
+    let findings = notify::find_pending(&repos);
+    if findings.is_empty() {
+        return Ok(());
+    }
+
+    println!("{}", notify::format_summary(&findings));
+    if desktop {
+        notify::send_desktop(&findings)?;
+    }
+    Ok(())
+}
+
+fn handle_prune(cli: &Cli, dry_run: bool) -> Result<()> {
+    let formatter = output::OutputFormatter::new(cli.quiet, cli.verbose, cli.no_color);
+    let config = config::Config::load(cli.config.clone())?;
+    let mut history = open_history(cli, &config)?;
+    let mut pins = pin::PinList::load_default()?;
+    let mut store = tags::TagStore::load_default()?;
+
+    let found = [
+        ("History", history.remove_missing()),
+        ("Pins", pins.remove_missing()),
+        ("Tags", store.remove_missing()),
+    ];
+    let count: usize = found.iter().map(|(_, paths)| paths.len()).sum();
+    if count == 0 {
+        formatter.info("Nothing refers to a deleted repository");
+        return Ok(());
+    }
+    for (what, paths) in found.iter().filter(|(_, paths)| !paths.is_empty()) {
+        println!("{}:", what);
+        for path in paths {
+            println!("  {}", path.display());
+        }
+    }
+    if dry_run {
+        return Ok(());
+    }
+
+    if !found[0].1.is_empty() {
+        history.save()?;
+    }
+    if !found[1].1.is_empty() {
+        pins.save()?;
+    }
+    if !found[2].1.is_empty() {
+        store.save()?;
+    }
+    formatter.success(&format!(
+        "Removed {} {}",
+        count,
+        if count == 1 { "entry" } else { "entries" }
+    ));
+    Ok(())
+}
+
+fn handle_prune_gone(cli: &Cli, dry_run: bool, yes: bool) -> Result<()> {
+    let formatter = output::OutputFormatter::new(cli.quiet, cli.verbose, cli.no_color);
+    let config = config::Config::load(cli.config.clone())?;
+    config.validate()?;
+
+    let search_paths = resolve_search_paths(cli, &config);
+    let repos = discover_repos(cli, &config, &search_paths)?;
+    let results = prune::scan(&repos, dry_run);
+
+    let deletable: Vec<scanner::GitRepo> = results
+        .iter()
+        .filter(|r| r.deletable().next().is_some())
+        .map(|r| scanner::GitRepo::new(r.path.clone()))
+        .collect();
+    if results.is_empty() {
+        formatter.info("No gone branches");
+        return Ok(());
+    }
+    if dry_run || deletable.is_empty() {
+        println!("{}", prune::format_summary(&results, true));
+        return Ok(());
+    }
+
+    let count: usize = results.iter().map(|r| r.deletable().count()).sum();
+    let action = format!(
+        "Delete {} gone {}",
+        count,
+        if count == 1 { "branch" } else { "branches" }
+    );
+    if !bulk::confirm(&action, &deletable, &config.bulk.confirm, true, yes)? {
+        formatter.info("Aborted; no branches were deleted");
+        return Ok(());
+    }
+
+    let mut summary = Vec::with_capacity(results.len());
+    for mut result in results {
+        if let Err(e) = prune::delete(&result.path, &result.gone) {
+            formatter.warn(&format!("{}: {:#}", result.path.display(), e));
+            // Only report the branches that were actually deleted
+            let remaining: Vec<String> = prune::gone_branches(&result.path)
+                .unwrap_or_default()
+                .into_iter()
+                .map(|b| b.name)
+                .collect();
+            result
+                .gone
+                .retain(|b| b.checked_out || !remaining.contains(&b.name));
+        }
+        summary.push(result);
+    }
+    println!("{}", prune::format_summary(&summary, false));
+    Ok(())
+}
+
+fn handle_select(cli: &Cli, query: &str, json: bool, first: bool) -> Result<()> {
+    let config = config::Config::load(cli.config.clone())?;
+    config.validate()?;
+
+    let search_paths = resolve_search_paths(cli, &config);
+    let repos = discover_repos(cli, &config, &search_paths)?;
+    let mut ranked = matcher::rank(query, &repos, &config.matcher);
+
+    if ranked.is_empty() {
+        let formatter = output::OutputFormatter::new(cli.quiet, cli.verbose, cli.no_color);
+        let error = output::ErrorInfo::new(
+            "ENOMATCH",
+            "No matching repositories",
+            format!("No repository matches '{}'.", query),
+            "Try a shorter or looser query, or list everything with:\n  gitnav --list",
+            "https://github.com/msetsma/gitnav#usage",
+        );
+        formatter.error(&error);
+        std::process::exit(exit_codes::EXIT_GENERAL_ERROR);
+    }
+
+    if first {
+        ranked.truncate(1);
+    }
+
+    if json {
+        let json_output = if first {
+            serde_json::to_string_pretty(&ranked[0])
+        } else {
+            serde_json::to_string_pretty(&ranked)
+        }
+        .context("Failed to serialize matches as JSON")?;
+        println!("{}", json_output);
+    } else if first {
+        println!("{}", ranked[0].path);
+    } else {
+        let score_width = ranked
+            .iter()
+            .map(|r| r.score.to_string().len())
+            .max()
+            .unwrap_or(0);
+        let name_width = ranked.iter().map(|r| r.name.len()).max().unwrap_or(0);
+        for r in &ranked {
+            println!(
+                "{:>sw$}  {:<nw$}  {}",
+                r.score,
+                r.name,
+                r.path,
+                sw = score_width,
+                nw = name_width
+            );
+        }
+    }
+    Ok(())
+}
+
+fn handle_jump(cli: &Cli, query: &str) -> Result<()> {
+    let config = config::Config::load(cli.config.clone())?;
+    config.validate()?;
+
+    let search_paths = resolve_search_paths(cli, &config);
+    let repos = discover_repos(cli, &config, &search_paths)?;
+
+    match matcher::resolve(query, &repos, &config.matcher) {
+        matcher::Resolution::Unique(repo) => finish_selection(cli, &config, Some(repo.path)),
+        matcher::Resolution::Ambiguous => {
+            if cli.verbose {
+                eprintln!("DEBUG: '{}' is ambiguous, opening the picker", query);
+            }
+            run_navigation(cli, Some(query), actions::Action::Navigate)
+        }
+        matcher::Resolution::NoMatch => exit_no_match(cli, query, exit_codes::EXIT_GENERAL_ERROR),
+    }
+}
+
+fn handle_open(cli: &Cli, query: Option<&str>) -> Result<()> {
+    let Some(query) = query else {
+        return run_navigation(cli, None, actions::Action::OpenEditor);
+    };
+    let config = config::Config::load(cli.config.clone())?;
+    config.validate()?;
+
+    let search_paths = resolve_search_paths(cli, &config);
+    let repos = discover_repos(cli, &config, &search_paths)?;
+
+    match matcher::resolve(query, &repos, &config.matcher) {
+        matcher::Resolution::Unique(repo) => open_in_editor(cli, &config, &[repo.path]),
+        matcher::Resolution::Ambiguous => {
+            if cli.verbose {
+                eprintln!("DEBUG: '{}' is ambiguous, opening the picker", query);
+            }
+            run_navigation(cli, Some(query), actions::Action::OpenEditor)
+        }
+        matcher::Resolution::NoMatch => exit_no_match(cli, query, exit_codes::EXIT_GENERAL_ERROR),
+    }
+}
+
+fn handle_files(cli: &Cli, query: Option<&str>) -> Result<()> {
+    let Some(query) = query else {
+        return run_navigation(cli, None, actions::Action::Files);
+    };
+    let config = config::Config::load(cli.config.clone())?;
+    config.validate()?;
+
+    let search_paths = resolve_search_paths(cli, &config);
+    let repos = discover_repos(cli, &config, &search_paths)?;
+
+    match matcher::resolve(query, &repos, &config.matcher) {
+        matcher::Resolution::Unique(repo) => pick_file(cli, &config, &[repo.path]),
+        matcher::Resolution::Ambiguous => {
+            if cli.verbose {
+                eprintln!("DEBUG: '{}' is ambiguous, opening the picker", query);
+            }
+            run_navigation(cli, Some(query), actions::Action::Files)
+        }
+        matcher::Resolution::NoMatch => exit_no_match(cli, query, exit_codes::EXIT_GENERAL_ERROR),
+    }
+}
+
+/// Pick a file in the first of `paths` and open it in the editor, started in
+/// the repository root.
+fn pick_file(cli: &Cli, config: &config::Config, paths: &[String]) -> Result<()> {
+    let Some(root) = paths.first().map(PathBuf::from) else {
+        return Ok(());
+    };
+    let editor = editor_or_exit(cli, config);
+    let files = files::list(&root)?;
+    if files.is_empty() {
+        anyhow::bail!("No files to pick in {}", root.display());
+    }
+
+    let repo = scanner::GitRepo::new(root.clone());
+    let labels: Vec<&str> = files.iter().map(String::as_str).collect();
+    let mut picker_config = config.clone();
+    picker_config.ui.prompt = format!("{} > ", repo.name);
+    picker_config.ui.header = String::from("File (↑/↓, ⏎, Esc)");
+    let chosen = if use_fzf(config) {
+        fzf::select_menu(&labels, &picker_config)?
+    } else {
+        finder::select_menu(&labels, &picker_config)?
+    };
+    let Some(file) = chosen.and_then(|index| files.get(index)) else {
+        std::process::exit(exit_codes::EXIT_INTERRUPTED);
+    };
+    if cli.verbose {
+        eprintln!(
+            "DEBUG: Opening {} in {} with {}",
+            file,
+            root.display(),
+            editor
+        );
+    }
+    actions::open_in_editor(&editor, std::slice::from_ref(file), Some(&root))
+}
+
+fn handle_branch(cli: &Cli, query: Option<&str>) -> Result<()> {
+    let Some(query) = query else {
+        return run_navigation(cli, None, actions::Action::Branch);
+    };
+    let config = config::Config::load(cli.config.clone())?;
+    config.validate()?;
+
+    let search_paths = resolve_search_paths(cli, &config);
+    let repos = discover_repos(cli, &config, &search_paths)?;
+
+    match matcher::resolve(query, &repos, &config.matcher) {
+        matcher::Resolution::Unique(repo) => pick_branch(cli, &config, &[repo.path]),
+        matcher::Resolution::Ambiguous => {
+            if cli.verbose {
+                eprintln!("DEBUG: '{}' is ambiguous, opening the picker", query);
+            }
+            run_navigation(cli, Some(query), actions::Action::Branch)
+        }
+        matcher::Resolution::NoMatch => exit_no_match(cli, query, exit_codes::EXIT_GENERAL_ERROR),
+    }
+}
+
+/// Pick a branch of the first of `paths` and change to it: to the worktree
+/// that has it checked out, or to the repository after checking it out there.
+fn pick_branch(cli: &Cli, config: &config::Config, paths: &[String]) -> Result<()> {
+    let Some(root) = paths.first().map(PathBuf::from) else {
+        return Ok(());
+    };
+    let branches = branch::list(&root)?;
+    if branches.is_empty() {
+        anyhow::bail!("No branches in {}", root.display());
+    }
+
+    let repo = scanner::GitRepo::new(root.clone());
+    let width = branches.iter().map(|b| b.name.len()).max().unwrap_or(0);
+    let labels: Vec<String> = branches.iter().map(|b| b.label(width)).collect();
+    let labels: Vec<&str> = labels.iter().map(String::as_str).collect();
+    let mut picker_config = config.clone();
+    picker_config.ui.prompt = format!("{} > ", repo.name);
+    picker_config.ui.header = String::from("Branch (↑/↓, ⏎, Esc)");
+    let chosen = if use_fzf(config) {
+        fzf::select_menu(&labels, &picker_config)?
+    } else {
+        finder::select_menu(&labels, &picker_config)?
+    };
+    let Some(branch) = chosen.and_then(|index| branches.get(index)) else {
+        std::process::exit(exit_codes::EXIT_INTERRUPTED);
+    };
+
+    if let Some(worktree) = &branch.worktree {
+        if cli.verbose {
+            eprintln!(
+                "DEBUG: {} is checked out in {}",
+                branch.name,
+                worktree.display()
+            );
+        }
+        return finish_selection(cli, config, Some(worktree.display().to_string()));
+    }
+
+    let dirty = git2::Repository::open(&root)
+        .map(|r| scanner::has_tracked_changes(&r))
+        .unwrap_or(false);
+    if dirty && !confirm_dirty_checkout(cli, &repo, &branch.name)? {
+        let formatter = output::OutputFormatter::new(cli.quiet, cli.verbose, cli.no_color);
+        formatter.info("Aborted; nothing was checked out");
+        return Ok(());
+    }
+    branch::checkout(&root, branch)?;
+    finish_selection(cli, config, Some(root.display().to_string()))
+}
+
+/// Ask whether to check out `branch` in `repo` although tracked files have
+/// uncommitted changes. Without a terminal to ask on, report the changes and
+/// exit.
+fn confirm_dirty_checkout(cli: &Cli, repo: &scanner::GitRepo, branch: &str) -> Result<bool> {
+    let question = format!(
+        "{} has uncommitted changes. Check out {} anyway?",
+        repo.name, branch
+    );
+    if let Some(answer) = bulk::ask(&question)? {
+        return Ok(answer);
+    }
+
+    let formatter = output::OutputFormatter::new(cli.quiet, cli.verbose, cli.no_color);
+    let error = output::ErrorInfo::new(
+        "EDIRTY",
+        "Uncommitted changes",
+        format!(
+            "{} has uncommitted changes, so {} was not checked out.",
+            repo.name, branch
+        ),
+        format!(
+            "Commit or stash them first, or run gitnav branch in a terminal to confirm:\n  git -C {} stash",
+            repo.path.display()
+        ),
+        "https://github.com/msetsma/gitnav#usage",
+    );
+    formatter.error(&error);
+    std::process::exit(exit_codes::EXIT_GENERAL_ERROR);
+}
+
+fn handle_worktree(cli: &Cli, query: Option<&str>, new: Option<&str>, list: bool) -> Result<()> {
+    if new.is_none() && !list {
+        let Some(query) = query else {
+            return run_navigation(cli, None, actions::Action::Worktree);
+        };
+        let config = config::Config::load(cli.config.clone())?;
+        config.validate()?;
+
+        let search_paths = resolve_search_paths(cli, &config);
+        let repos = discover_repos(cli, &config, &search_paths)?;
+
+        return match matcher::resolve(query, &repos, &config.matcher) {
+            matcher::Resolution::Unique(repo) => pick_worktree(cli, &config, &[repo.path]),
+            matcher::Resolution::Ambiguous => {
+                if cli.verbose {
+                    eprintln!("DEBUG: '{}' is ambiguous, opening the picker", query);
+                }
+                run_navigation(cli, Some(query), actions::Action::Worktree)
+            }
+            matcher::Resolution::NoMatch => {
+                exit_no_match(cli, query, exit_codes::EXIT_GENERAL_ERROR)
+            }
+        };
+    }
+
+    let config = config::Config::load(cli.config.clone())?;
+    config.validate()?;
+    let root = match query {
+        // The repository containing the current directory, like `gitnav browse`
+        None => {
+            let repo = git2::Repository::discover(".")
+                .context("Not in a git repository; name one with a query")?;
+            repo.workdir().unwrap_or_else(|| repo.path()).to_path_buf()
+        }
+        Some(query) => {
+            let search_paths = resolve_search_paths(cli, &config);
+            let repos = discover_repos(cli, &config, &search_paths)?;
+            match matcher::resolve(query, &repos, &config.matcher) {
+                matcher::Resolution::Unique(repo) => PathBuf::from(repo.path),
+                matcher::Resolution::Ambiguous => {
+                    let formatter =
+                        output::OutputFormatter::new(cli.quiet, cli.verbose, cli.no_color);
+                    let error = output::ErrorInfo::new(
+                        "EAMBIGUOUS",
+                        "Several repositories match",
+                        format!("'{}' matches more than one repository.", query),
+                        format!(
+                            "Use a more specific query; see what matches with:\n  gitnav select {}",
+                            query
+                        ),
+                        "https://github.com/msetsma/gitnav#usage",
+                    );
+                    formatter.error(&error);
+                    std::process::exit(exit_codes::EXIT_GENERAL_ERROR);
+                }
+                matcher::Resolution::NoMatch => {
+                    exit_no_match(cli, query, exit_codes::EXIT_GENERAL_ERROR)
+                }
+            }
+        }
+    };
+
+    let worktrees = worktree::list(&root)?;
+    if let Some(branch) = new {
+        return add_worktree(cli, &config, &root, &worktrees, branch);
+    }
+    let width = worktrees
+        .iter()
+        .map(|w| w.branch.as_deref().unwrap_or("(detached)").len())
+        .max()
+        .unwrap_or(0);
+    for w in &worktrees {
+        println!(
+            "{:<bw$}  {}",
+            w.branch.as_deref().unwrap_or("(detached)"),
+            w.path.display(),
+            bw = width
+        );
+    }
+    Ok(())
+}
+
+/// Pick a worktree of the first of `paths` and print its path for the shell
+/// wrapper.
+fn pick_worktree(cli: &Cli, config: &config::Config, paths: &[String]) -> Result<()> {
+    let Some(root) = paths.first().map(PathBuf::from) else {
+        return Ok(());
+    };
+    let worktrees: Vec<worktree::Worktree> = worktree::list(&root)?
+        .into_iter()
+        .filter(|w| w.path.is_dir())
+        .collect();
+
+    let repo = scanner::GitRepo::new(root.clone());
+    let home = dirs::home_dir();
+    let width = worktrees
+        .iter()
+        .map(|w| w.branch.as_deref().unwrap_or("(detached)").len())
+        .max()
+        .unwrap_or(0);
+    let labels: Vec<String> = worktrees
+        .iter()
+        .map(|w| {
+            format!(
+                "{:<bw$}  {}",
+                w.branch.as_deref().unwrap_or("(detached)"),
+                scanner::shorten_home(&w.path, home.as_deref()),
+                bw = width
+            )
+        })
+        .collect();
+    let labels: Vec<&str> = labels.iter().map(String::as_str).collect();
+    let mut picker_config = config.clone();
+    picker_config.ui.prompt = format!("{} > ", repo.name);
+    picker_config.ui.header = String::from("Worktree (↑/↓, ⏎, Esc)");
+    let chosen = if use_fzf(config) {
+        fzf::select_menu(&labels, &picker_config)?
+    } else {
+        finder::select_menu(&labels, &picker_config)?
+    };
+    let Some(picked) = chosen.and_then(|index| worktrees.get(index)) else {
+        std::process::exit(exit_codes::EXIT_INTERRUPTED);
+    };
+    finish_selection(cli, config, Some(picked.path.display().to_string()))
+}
+
+/// Create a worktree of `root` for `branch` where `worktree.path` puts it and
+/// print its path; a branch already checked out in a worktree prints that one.
+fn add_worktree(
+    cli: &Cli,
+    config: &config::Config,
+    root: &Path,
+    worktrees: &[worktree::Worktree],
+    branch: &str,
+) -> Result<()> {
+    if let Some(existing) = worktrees
+        .iter()
+        .find(|w| w.branch.as_deref() == Some(branch) && w.path.is_dir())
+    {
+        // On stderr: the shell wrapper reads the path from stdout
+        if !cli.quiet {
+            eprintln!(
+                "{} is already checked out in {}",
+                branch,
+                existing.path.display()
+            );
+        }
+        return finish_selection(cli, config, Some(existing.path.display().to_string()));
+    }
+
+    let main = worktrees
+        .iter()
+        .find(|w| w.main)
+        .map_or(root, |w| w.path.as_path());
+    let path = worktree::new_path(&config.worktree.path, main, branch)?;
+    if cli.verbose {
+        eprintln!(
+            "DEBUG: Adding a worktree for {} at {}",
+            branch,
+            path.display()
+        );
+    }
+    worktree::add(root, &path, branch)?;
+    finish_selection(cli, config, Some(path.display().to_string()))
+}
+
+/// Report that no repository matches `query` and exit with `code`.
+fn exit_no_match(cli: &Cli, query: &str, code: i32) -> ! {
+    let formatter = output::OutputFormatter::new(cli.quiet, cli.verbose, cli.no_color);
+    let error = output::ErrorInfo::new(
+        "ENOMATCH",
+        "No matching repositories",
+        format!("No repository matches '{}'.", query),
+        "Try a shorter or looser query, or pick interactively with:\n  gn",
+        "https://github.com/msetsma/gitnav#usage",
+    );
+    formatter.error(&error);
+    std::process::exit(code);
+}
+
+fn handle_preview_command(cli: &Cli, path: Option<&Path>, plain: bool, json: bool) -> Result<()> {
+    let config = config::Config::load(cli.config.clone())?;
+    let start = path
+        .map(PathBuf::from)
+        .unwrap_or_else(|| PathBuf::from("."));
+    let start = PathBuf::from(shellexpand::tilde(&start.to_string_lossy()).to_string());
+
+    // Accept any path inside a repository, like git itself does
+    let repo = git2::Repository::discover(&start)
+        .with_context(|| format!("Not a git repository: {}", start.display()))?;
+    let root = repo.workdir().unwrap_or_else(|| repo.path()).to_path_buf();
+    let root = root.canonicalize().unwrap_or(root);
+
+    let forge_info = cached_forge_info(&config, cli.config.as_deref(), &root);
+    let preview_config = config.preview_for(&root)?;
+    if json {
+        let mut summary = preview::collect_preview_summary(&root, &preview_config)?;
+        forge_info.apply(&mut summary);
+        let json_output = serde_json::to_string_pretty(&summary)
+            .context("Failed to serialize repository summary as JSON")?;
+        println!("{}", json_output);
+    } else if plain || cli.no_color {
+        println!(
+            "{}",
+            preview::generate_preview_plain(&root, &preview_config, forge_info)?
+        );
+    } else {
+        println!(
+            "{}",
+            preview::generate_preview(&root, &preview_config, forge_info)?
+        );
+    }
+    Ok(())
+}
+
+/// Read `--stdin` candidates, skipping the scan and cache entirely.
+fn read_stdin_candidates(cli: &Cli) -> Result<Vec<scanner::GitRepo>> {
+    use std::io::Read;
+
+    let mut input = Vec::new();
+    std::io::stdin()
+        .read_to_end(&mut input)
+        .context("Failed to read candidates from stdin")?;
+    let (repos, skipped) = scanner::parse_candidates(&String::from_utf8_lossy(&input));
+
+    if skipped > 0 {
+        let formatter = output::OutputFormatter::new(cli.quiet, cli.verbose, cli.no_color);
+        formatter.warn(&format!(
+            "Skipped {} stdin entries that are not directories",
+            skipped
+        ));
+    }
+    Ok(repos)
+}
+
+/// Pick a repository and act on it: print its path for the shell wrapper, or
+/// do `enter` when Enter is pressed (`gitnav open` opens the editor).
+fn run_navigation(cli: &Cli, query: Option<&str>, enter: actions::Action) -> Result<()> {
+    let _formatter = output::OutputFormatter::new(cli.quiet, cli.verbose, cli.no_color);
+
+    // Load configuration
+    let config = config::Config::load(cli.config.clone())?;
+
+    // Validate configuration
+    config.validate()?;
+
+    let search_paths = resolve_search_paths(cli, &config);
+
+    let use_fzf = use_fzf(&config);
+
+    // An expired cache opens the picker right away while a background process
+    // rescans for the next run
+    let stale = if !cli.stdin && !cli.list {
+        stale_repos(cli, &config, &search_paths)?
+    } else {
+        None
+    };
+
+    let select_1 = cli.select_1 || config.ui.select_1;
+    let exit_0 = cli.exit_0 || config.ui.exit_0;
+
+    // On a cache miss, open fzf right away and fill it in while scanning
+    // (not with --select-1 / --exit-0, which need the whole list first)
+    if stale.is_none()
+        && !cli.stdin
+        && !cli.list
+        && !select_1
+        && !exit_0
+        && use_fzf
+        && config.ui.stream
+        && !uses_zoxide(cli, &config)
+        && editors::imported(&tags::TagStore::load_default()?).is_empty()
+        && !cache_is_fresh(cli, &config, &search_paths)?
+        && !daemon::is_running(&daemon_socket(cli, &config)?)
+        && fzf::is_fzf_available(&config.finder.binary)
+    {
+        return stream_navigation(cli, &config, &search_paths, query, enter);
+    }
+
+    // Get repos (from stdin, cache, or fresh scan)
+    let repos = if cli.stdin {
+        filter_tags(cli, read_stdin_candidates(cli)?)?
+    } else if let Some(repos) = stale {
+        filter_tags(cli, repos)?
+    } else {
+        discover_repos(cli, &config, &search_paths)?
+    };
+
+    if repos.is_empty() {
+        let source = if cli.stdin {
+            "stdin".to_string()
+        } else {
+            search_paths.join(", ")
+        };
+        exit_no_repos(cli, &describe_source(cli, source));
+    }
+
+    if cli.verbose {
+        eprintln!("DEBUG: Found {} repositories", repos.len());
+    }
+
+    // Handle --list mode (non-interactive, pipe-friendly)
+    if cli.list {
+        return print_list(cli, &config, &search_paths, &repos);
+    }
+
+    // Settle a single match or no match without opening the picker
+    if select_1 || exit_0 {
+        let query = query.unwrap_or("");
+        let matches = matcher::rank(query, &repos, &config.matcher);
+        match matches.as_slice() {
+            [only] if select_1 => {
+                if cli.verbose {
+                    eprintln!("DEBUG: Only {} matches, skipping the picker", only.name);
+                }
+                let selection = actions::Selection::navigate(vec![only.path.clone()]);
+                return finish_picker(cli, &config, Some(selection), enter);
+            }
+            [] if exit_0 => exit_no_match(cli, query, exit_codes::EXIT_NO_MATCH),
+            _ => {}
+        }
+    }
+
+    // Interactive mode needs fzf unless the built-in picker was chosen
+    if use_fzf && !fzf::is_fzf_available(&config.finder.binary) {
+        let formatter = output::OutputFormatter::new(cli.quiet, cli.verbose, cli.no_color);
+        let error = output::ErrorInfo::new(
             "ENOFZF",
-            "fzf not found",
-            "fzf is required for interactive mode but was not found in your PATH.",
-            "Install fzf for your system:\n  macOS:   brew install fzf\n  Linux:   apt install fzf  or  pacman -S fzf\n  Windows: scoop install fzf\n\nAlternatively, use non-interactive mode:\n  gitnav --list",
+            format!("{} not found", config.finder.binary),
+            format!(
+                "{} is required for interactive mode but was not found in your PATH.",
+                config.finder.binary
+            ),
+            "Install fzf for your system:\n  macOS:   brew install fzf\n  Linux:   apt install fzf  or  pacman -S fzf\n  Windows: scoop install fzf\n\nOr point binary under [finder] at another fzf-compatible finder.\n\nOn macOS and Linux, set finder = \"auto\" under [ui] to fall back\nto the built-in picker.\n\nAlternatively, use non-interactive mode:\n  gitnav --list",
             "https://github.com/msetsma/gitnav#requirements"
         );
         formatter.error(&error);
         std::process::exit(exit_codes::EXIT_UNAVAILABLE);
     }
 
+    // Pinned repos go first
+    let pins = pin::PinList::load_default()?;
+    let repos = pins.float(repos);
+
     // Enrich repos with branch, dirty status, and project type (unless disabled)
-    let enriched = if config.ui.show_inline_meta {
+    let mut enriched = if !config.ui.show_inline_meta {
+        repos.into_iter().map(scanner::without_meta).collect()
+    } else if let Some(cached) = cached_meta(cli, &config, &search_paths)? {
+        if cli.verbose {
+            eprintln!("DEBUG: Using cached git metadata for the list");
+        }
+        scanner::enrich_repos_cached(repos, &cached)
+    } else {
         if cli.verbose {
             eprintln!("DEBUG: Enriching repos with git metadata");
         }
         scanner::enrich_repos(repos)
-    } else {
-        repos
-            .into_iter()
-            .map(|r| scanner::EnrichedRepo {
-                meta: scanner::RepoMeta {
-                    branch: None,
-                    is_dirty: false,
-                    is_detached: false,
-                    project_type: scanner::ProjectType::Unknown,
-                },
-                name: r.name,
-                path: r.path,
-            })
-            .collect()
     };
+    for repo in enriched.iter_mut().take(pins.entries().len()) {
+        repo.pinned = pins.contains(&repo.path);
+    }
+    let tags = tags::TagStore::load_default()?;
+    for repo in &mut enriched {
+        repo.tags = tags.tags_for(&repo.path);
+    }
+    scanner::disambiguate(&mut enriched);
+
+    if !use_fzf {
+        if cli.verbose {
+            eprintln!("DEBUG: Using the built-in picker");
+        }
+        let selection = finder::select_repo(&enriched, &config, query, cli.multi)?;
+        return finish_picker(cli, &config, selection, enter);
+    }
 
     // Get path to current binary for preview
     let current_exe = std::env::current_exe().context("Failed to get current executable path")?;
     let binary_path = current_exe.to_string_lossy();
 
     // Run fzf and get selection
-    match fzf::select_repo(&enriched, &config, &binary_path, cli.query.as_deref())? {
+    let selection = fzf::select_repo(&enriched, &config, &binary_path, query, cli.multi)?;
+    finish_picker(cli, &config, selection, enter)
+}
+
+/// `--list` output: paths one per line (NUL-separated with `--print0`), a JSON
+/// array with `--json`, one JSON object per line with `--ndjson`, or one
+/// rendered line per repository with `--format` / `display.list_format`.
+fn print_list(
+    cli: &Cli,
+    config: &config::Config,
+    search_paths: &[String],
+    repos: &[scanner::GitRepo],
+) -> Result<()> {
+    use std::io::Write;
+    let mut stdout = std::io::stdout().lock();
+    let terminator = if cli.print0 { b'\0' } else { b'\n' };
+    let format = if cli.json || cli.ndjson {
+        None
+    } else {
+        cli.format.as_ref().or(config.display.list_format.as_ref())
+    };
+
+    if cli.json || cli.ndjson {
+        let tags = tags::TagStore::load_default()?;
+        // Opening every repository is slow; only done when asked for
+        let details: Vec<Option<scanner::RepoDetails>> = if cli.details {
+            scanner::collect_details(repos)
+                .into_iter()
+                .map(Some)
+                .collect()
+        } else {
+            vec![None; repos.len()]
+        };
+        let listed: Vec<ListedRepo> = repos
+            .iter()
+            .zip(details)
+            .map(|(repo, details)| ListedRepo {
+                tags: tags.tags_for(&repo.path),
+                details,
+                repo,
+            })
+            .collect();
+        if cli.ndjson {
+            for entry in &listed {
+                let line = serde_json::to_string(entry)
+                    .context("Failed to serialize repository as JSON")?;
+                writeln!(stdout, "{}", line)?;
+            }
+        } else {
+            let json_output = serde_json::to_string_pretty(&listed)
+                .context("Failed to serialize repositories as JSON")?;
+            writeln!(stdout, "{}", json_output)?;
+        }
+    } else if let Some(format) = format {
+        let template = template::Template::parse(format).context("Invalid --format template")?;
+        let tags = tags::TagStore::load_default()?;
+        let details = if template.needs_git() {
+            scanner::collect_details(repos)
+        } else {
+            vec![scanner::RepoDetails::default(); repos.len()]
+        };
+        for (repo, details) in repos.iter().zip(&details) {
+            let repo_tags = tags.tags_for(&repo.path);
+            let line = template.render(&template::Values {
+                name: &repo.name,
+                path: &repo.path,
+                rel_path: relative_to_search_path(&repo.path, search_paths),
+                branch: details.branch.as_deref(),
+                last_commit: details.last_commit,
+                tags: &repo_tags,
+            });
+            stdout.write_all(line.as_bytes())?;
+            stdout.write_all(&[terminator])?;
+        }
+    } else {
+        for repo in repos {
+            write_path(&mut stdout, &repo.path, terminator)?;
+        }
+    }
+    stdout.flush()?;
+    Ok(())
+}
+
+/// `path` relative to the longest search path containing it, if any.
+fn relative_to_search_path<'a>(path: &'a Path, search_paths: &[String]) -> Option<&'a Path> {
+    search_paths
+        .iter()
+        .flat_map(|base| {
+            let base = Path::new(base);
+            [Some(base.to_path_buf()), base.canonicalize().ok()]
+        })
+        .flatten()
+        .filter_map(|base| path.strip_prefix(base).ok())
+        .filter(|rel| !rel.as_os_str().is_empty())
+        .min_by_key(|rel| rel.components().count())
+}
+
+/// Write a path followed by `terminator`, byte for byte where the platform allows,
+/// so non-UTF-8 names survive `--print0`.
+fn write_path(out: &mut impl std::io::Write, path: &Path, terminator: u8) -> Result<()> {
+    #[cfg(unix)]
+    out.write_all(std::os::unix::ffi::OsStrExt::as_bytes(path.as_os_str()))?;
+    #[cfg(not(unix))]
+    out.write_all(path.to_string_lossy().as_bytes())?;
+    out.write_all(&[terminator])?;
+    Ok(())
+}
+
+/// A `--list --json` / `--ndjson` entry.
+#[derive(serde::Serialize)]
+struct ListedRepo<'a> {
+    #[serde(flatten)]
+    repo: &'a scanner::GitRepo,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    tags: Vec<String>,
+    /// Present with `--details`
+    #[serde(flatten, skip_serializing_if = "Option::is_none")]
+    details: Option<scanner::RepoDetails>,
+}
+
+/// Repositories from an expired cache, after starting a background refresh.
+///
+/// Returns `None` when the cache is fresh, missing, or unusable, or when
+/// `background_refresh` is off, so the caller falls back to the usual path.
+/// Repositories deleted since the cache was written are dropped.
+fn stale_repos(
+    cli: &Cli,
+    config: &config::Config,
+    search_paths: &[String],
+) -> Result<Option<Vec<scanner::GitRepo>>> {
+    if !config.cache.enabled || !config.cache.background_refresh || cli.force {
+        return Ok(None);
+    }
+    let cache = open_cache(cli, config)?;
+    let key = cache_key(search_paths);
+    if cache.is_valid(&key) {
+        return Ok(None);
+    }
+    let Some(mut repos) = cache.load(&key)? else {
+        return Ok(None);
+    };
+    scanner::drop_missing(&mut repos);
+    if repos.is_empty() {
+        return Ok(None);
+    }
+
+    if cli.verbose {
+        eprintln!("DEBUG: Cache expired, using it while refreshing in the background");
+    }
+    let current_exe = std::env::current_exe().context("Failed to get current executable path")?;
+    spawn_cache_refresh(cli, &current_exe);
+
+    scanner::sort_repos(&mut repos, config.search.order);
+    let repos = merge_imported(config, merge_zoxide(cli, config, repos))?;
+    Ok(Some(exclude::ExcludeList::load_default()?.filter(repos)))
+}
+
+/// Report that no repositories were found and exit.
+fn exit_no_repos(cli: &Cli, source: &str) -> ! {
+    let formatter = output::OutputFormatter::new(cli.quiet, cli.verbose, cli.no_color);
+    let error = output::ErrorInfo::new(
+        "ENOREPOS",
+        "No repositories found",
+        format!("No git repositories found in: {}", source),
+        "Verify the path exists and contains git repositories.\nYou can also try:\n  gitnav --path <different_path>\n  gitnav --max-depth <higher_number>".to_string(),
+        "https://github.com/msetsma/gitnav#usage"
+    );
+    formatter.error(&error);
+    std::process::exit(exit_codes::EXIT_GENERAL_ERROR);
+}
+
+fn record_visit(cli: &Cli, config: &config::Config, path: &Path) -> Result<()> {
+    let mut history = open_history(cli, config)?;
+    history.record(path, chrono::Utc::now().timestamp());
+    history.save()
+}
+
+/// Whether the picker is fzf (or `finder.binary`) rather than the built-in one.
+fn use_fzf(config: &config::Config) -> bool {
+    match config.ui.finder {
+        config::FinderMode::Auto => {
+            fzf::is_fzf_available(&config.finder.binary) || !finder::is_supported()
+        }
+        config::FinderMode::Fzf => true,
+        config::FinderMode::Builtin => false,
+    }
+}
+
+/// Act on what the picker returned.
+///
+/// Enter prints every path with `--multi`, otherwise the single selection
+/// (see [`finish_selection`]). Multi-select is for scripting, so it neither
+/// records history nor runs the `post_select` hook. Action keys run their
+/// action instead and print nothing, so the shell wrapper stays put.
+fn finish_picker(
+    cli: &Cli,
+    config: &config::Config,
+    selection: Option<actions::Selection>,
+    enter: actions::Action,
+) -> Result<()> {
+    let Some(selection) = selection else {
+        std::process::exit(exit_codes::EXIT_INTERRUPTED);
+    };
+    let action = match selection.action {
+        actions::Action::Navigate => enter,
+        action => action,
+    };
+    match action {
+        actions::Action::Navigate => {}
+        actions::Action::OpenEditor => return open_in_editor(cli, config, &selection.paths),
+        actions::Action::OpenBrowser => return open_in_browser(cli, config, &selection.paths),
+        actions::Action::Tmux => return open_in_tmux(cli, config, &selection.paths),
+        actions::Action::Files => return pick_file(cli, config, &selection.paths),
+        actions::Action::Branch => return pick_branch(cli, config, &selection.paths),
+        actions::Action::Worktree => return pick_worktree(cli, config, &selection.paths),
+        actions::Action::CopyPath => return copy_paths(cli, &selection.paths),
+        actions::Action::Refresh => return refresh_picker(cli, config, enter),
+    }
+
+    if !cli.multi {
+        let path = selection.paths.into_iter().next();
+        return match path {
+            Some(path) if config.actions.menu => run_action_menu(cli, config, path),
+            path => finish_selection(cli, config, path),
+        };
+    }
+    use std::io::Write;
+    let mut stdout = std::io::stdout().lock();
+    let terminator = if cli.print0 { b'\0' } else { b'\n' };
+    for path in selection.paths {
+        write_path(&mut stdout, Path::new(&path), terminator)?;
+    }
+    stdout.flush()?;
+    Ok(())
+}
+
+/// Ask what to do with the picked repository (`actions.menu`) and do it.
+///
+/// "cd" finishes as Enter does without the menu; commands from
+/// `actions.items` are rendered as templates and run in the repository.
+fn run_action_menu(cli: &Cli, config: &config::Config, path: String) -> Result<()> {
+    let repo = scanner::GitRepo::new(PathBuf::from(&path));
+    let entries = actions::menu(config);
+    let labels: Vec<&str> = entries.iter().map(|e| e.label()).collect();
+    let mut menu_config = config.clone();
+    menu_config.ui.prompt = format!("{} > ", repo.name);
+    menu_config.ui.header = String::from("Action (↑/↓, ⏎, Esc)");
+    let chosen = if use_fzf(config) {
+        fzf::select_menu(&labels, &menu_config)?
+    } else {
+        finder::select_menu(&labels, &menu_config)?
+    };
+    let Some(entry) = chosen.and_then(|index| entries.get(index)) else {
+        std::process::exit(exit_codes::EXIT_INTERRUPTED);
+    };
+    if cli.verbose {
+        eprintln!("DEBUG: Chose '{}' for {}", entry.label(), path);
+    }
+
+    let paths = [path];
+    match entry {
+        actions::MenuEntry::Cd => finish_selection(cli, config, paths.into_iter().next()),
+        actions::MenuEntry::OpenEditor => open_in_editor(cli, config, &paths),
+        actions::MenuEntry::OpenBrowser => open_in_browser(cli, config, &paths),
+        actions::MenuEntry::CopyPath => copy_paths(cli, &paths),
+        actions::MenuEntry::Command(item) => {
+            let template = template::Template::parse(&item.command)
+                .with_context(|| format!("Invalid command for action '{}'", item.name))?;
+            let details = if template.needs_git() {
+                scanner::collect_details(std::slice::from_ref(&repo))
+                    .pop()
+                    .unwrap_or_default()
+            } else {
+                scanner::RepoDetails::default()
+            };
+            let repo_tags = tags::TagStore::load_default()?.tags_for(&repo.path);
+            let command = template.render(&template::Values {
+                name: &repo.name,
+                path: &repo.path,
+                rel_path: relative_to_search_path(&repo.path, &resolve_search_paths(cli, config)),
+                branch: details.branch.as_deref(),
+                last_commit: details.last_commit,
+                tags: &repo_tags,
+            });
+            if cli.verbose {
+                eprintln!("DEBUG: Running {}", command);
+            }
+            actions::run_command(&command, &repo.path, &repo.name)
+        }
+    }
+}
+
+/// Copy the picked paths to the clipboard, one per line (`keybindings.copy_path`).
+fn copy_paths(cli: &Cli, paths: &[String]) -> Result<()> {
+    actions::copy_to_clipboard(&paths.join("\n"))?;
+    let formatter = output::OutputFormatter::new(cli.quiet, cli.verbose, cli.no_color);
+    formatter.success(&format!("Copied {} to the clipboard", paths.join(", ")));
+    Ok(())
+}
+
+/// Drop the cached list and reopen the picker on a fresh scan (`keybindings.refresh`).
+fn refresh_picker(cli: &Cli, config: &config::Config, enter: actions::Action) -> Result<()> {
+    if cli.stdin {
+        anyhow::bail!("Cannot refresh a list read from --stdin");
+    }
+    if config.cache.enabled {
+        let search_paths = resolve_search_paths(cli, config);
+        open_cache(cli, config)?.invalidate(cache_key(&search_paths))?;
+    }
+    if cli.verbose {
+        eprintln!("DEBUG: Refreshing the repository list");
+    }
+    run_navigation(cli, cli.query.as_deref(), enter)
+}
+
+/// Open the web pages of the picked repositories' remotes (`keybindings.open_browser`).
+///
+/// Uses `origin` (or the first remote) with `insteadOf` rewrites applied, so
+/// SSH remotes open as their `https://` page. Repositories without a remote are
+/// reported and skipped.
+fn open_in_browser(cli: &Cli, config: &config::Config, paths: &[String]) -> Result<()> {
+    let formatter = output::OutputFormatter::new(cli.quiet, cli.verbose, cli.no_color);
+    let mut opened = 0;
+    for path in paths {
+        let Some(remote) = remote::repo_remote(Path::new(path)) else {
+            formatter.warn(&format!("{} has no remote to open", path));
+            continue;
+        };
+        let page = forge::web_page(&remote, None, &config.forge.browse)?;
+        if cli.verbose {
+            eprintln!("DEBUG: Opening {}", page.url);
+        }
+        actions::open_url(&page.url)?;
+        opened += 1;
+    }
+
+    if opened == 0 {
+        exit_no_remote(
+            cli,
+            "None of the selected repositories has a remote with a recognizable URL.",
+        );
+    }
+    Ok(())
+}
+
+/// Report that there is no remote to open and exit.
+fn exit_no_remote(cli: &Cli, message: &str) -> ! {
+    let formatter = output::OutputFormatter::new(cli.quiet, cli.verbose, cli.no_color);
+    let error = output::ErrorInfo::new(
+        "ENOREMOTE",
+        "No remote to open",
+        message,
+        "Add one with:\n  git remote add origin <url>",
+        "https://github.com/msetsma/gitnav#usage",
+    );
+    formatter.error(&error);
+    std::process::exit(exit_codes::EXIT_GENERAL_ERROR);
+}
+
+fn handle_browse(cli: &Cli, path: Option<&Path>, branch: bool, print: bool) -> Result<()> {
+    let config = config::Config::load(cli.config.clone())?;
+    config.validate()?;
+    let formatter = output::OutputFormatter::new(cli.quiet, cli.verbose, cli.no_color);
+
+    let start = path.unwrap_or(Path::new("."));
+    let start = PathBuf::from(shellexpand::tilde(&start.to_string_lossy()).to_string());
+    let repo = git2::Repository::discover(&start)
+        .with_context(|| format!("Not a git repository: {}", start.display()))?;
+    let root = repo.workdir().unwrap_or_else(|| repo.path()).to_path_buf();
+
+    let Some(remote) = remote::repo_remote(&root) else {
+        exit_no_remote(
+            cli,
+            &format!("{} has no remote with a recognizable URL.", root.display()),
+        );
+    };
+
+    let branch_name = if branch {
+        let head = repo.head().ok();
+        let name = head
+            .as_ref()
+            .filter(|h| h.is_branch())
+            .and_then(|h| h.shorthand())
+            .map(str::to_string);
+        if name.is_none() {
+            formatter.warn("HEAD is not on a branch; opening the repository page");
+        }
+        name
+    } else {
+        None
+    };
+
+    let page = forge::web_page(&remote, branch_name.as_deref(), &config.forge.browse)?;
+    if branch_name.is_some() && !page.branch_linked {
+        formatter.warn(&format!(
+            "No branch links known for {}; set branch_url under [forge.browse.\"{}\"]",
+            remote.host, remote.host
+        ));
+    }
+
+    if print {
+        println!("{}", page.url);
+        return Ok(());
+    }
+    if cli.verbose {
+        eprintln!("DEBUG: Opening {}", page.url);
+    }
+    actions::open_url(&page.url)
+}
+
+/// Open picked repositories in the editor (`keybindings.open_editor`).
+fn open_in_editor(cli: &Cli, config: &config::Config, paths: &[String]) -> Result<()> {
+    let editor = editor_or_exit(cli, config);
+    if cli.verbose {
+        eprintln!("DEBUG: Opening {} with {}", paths.join(", "), editor);
+    }
+    actions::open_in_editor(&editor, paths, None)
+}
+
+/// The editor command, or exit with an error saying how to configure one.
+fn editor_or_exit(cli: &Cli, config: &config::Config) -> String {
+    let Some(editor) = actions::editor_command(config) else {
+        let formatter = output::OutputFormatter::new(cli.quiet, cli.verbose, cli.no_color);
+        let error = output::ErrorInfo::new(
+            "ENOEDITOR",
+            "No editor configured",
+            "Opening a repository needs an editor, but neither editor in [ui] nor $VISUAL or $EDITOR is set.",
+            "Set one of them, for example:\n  export EDITOR=vim\n\nor in config.toml:\n  [ui]\n  editor = \"code\"",
+            "https://github.com/msetsma/gitnav#configuration",
+        );
+        formatter.error(&error);
+        std::process::exit(exit_codes::EXIT_UNAVAILABLE);
+    };
+    editor
+}
+
+fn handle_tmux(cli: &Cli, query: Option<&str>) -> Result<()> {
+    let Some(query) = query else {
+        return run_navigation(cli, None, actions::Action::Tmux);
+    };
+    let config = config::Config::load(cli.config.clone())?;
+    config.validate()?;
+
+    let search_paths = resolve_search_paths(cli, &config);
+    let repos = discover_repos(cli, &config, &search_paths)?;
+
+    match matcher::resolve(query, &repos, &config.matcher) {
+        matcher::Resolution::Unique(repo) => open_in_tmux(cli, &config, &[repo.path]),
+        matcher::Resolution::Ambiguous => {
+            if cli.verbose {
+                eprintln!("DEBUG: '{}' is ambiguous, opening the picker", query);
+            }
+            run_navigation(cli, Some(query), actions::Action::Tmux)
+        }
+        matcher::Resolution::NoMatch => exit_no_match(cli, query, exit_codes::EXIT_GENERAL_ERROR),
+    }
+}
+
+/// Create a tmux session for each picked repository that lacks one, then
+/// attach to (or switch to) the first.
+fn open_in_tmux(cli: &Cli, config: &config::Config, paths: &[String]) -> Result<()> {
+    if !tmux::is_available() {
+        let formatter = output::OutputFormatter::new(cli.quiet, cli.verbose, cli.no_color);
+        let error = output::ErrorInfo::new(
+            "ENOTMUX",
+            "tmux not found",
+            "gitnav tmux needs tmux, but it was not found in your PATH.",
+            "Install tmux for your system:\n  macOS:   brew install tmux\n  Linux:   apt install tmux  or  pacman -S tmux",
+            "https://github.com/msetsma/gitnav#usage",
+        );
+        formatter.error(&error);
+        std::process::exit(exit_codes::EXIT_UNAVAILABLE);
+    }
+
+    let mut first = None;
+    for path in paths {
+        let path = Path::new(path);
+        let name = tmux::session_name(&scanner::GitRepo::new(path.to_path_buf()).name);
+        if tmux::has_session(&name) {
+            if cli.verbose {
+                eprintln!("DEBUG: tmux session '{}' already exists", name);
+            }
+        } else {
+            if cli.verbose {
+                eprintln!(
+                    "DEBUG: Creating tmux session '{}' in {}",
+                    name,
+                    path.display()
+                );
+            }
+            tmux::create_session(&name, path, &config.tmux.windows)?;
+        }
+        first.get_or_insert(name);
+    }
+    match first {
+        Some(name) => tmux::attach(&name),
+        None => Ok(()),
+    }
+}
+
+/// Print the picked path for the shell wrapper, or exit as cancelled.
+fn finish_selection(cli: &Cli, config: &config::Config, selection: Option<String>) -> Result<()> {
+    match selection {
         Some(selected_path) => {
+            let formatter = output::OutputFormatter::new(cli.quiet, cli.verbose, cli.no_color);
+            // The wrapper reads one line of stdout as the path
+            if selected_path.contains(['\n', '\r']) {
+                let error = output::ErrorInfo::new(
+                    "ENEWLINE",
+                    "Path contains a line break",
+                    format!(
+                        "The path {:?} has a line break in it, which the shell wrapper cannot cd into safely.",
+                        selected_path
+                    ),
+                    "Rename the directory so its name has no line breaks.",
+                    "https://github.com/msetsma/gitnav#shell-integration",
+                );
+                formatter.error(&error);
+                std::process::exit(exit_codes::EXIT_DATA_ERROR);
+            }
+            let name = scanner::GitRepo::new(PathBuf::from(&selected_path)).name;
+            if let Err(e) = record_visit(cli, config, Path::new(&selected_path)) {
+                formatter.warn(&format!("Could not update history: {:#}", e));
+            }
+            if config.zoxide.add {
+                if let Err(e) = zoxide::add(Path::new(&selected_path)) {
+                    formatter.warn(&format!("Could not add to zoxide: {:#}", e));
+                }
+            }
+            let env = [
+                ("GITNAV_REPO_PATH", selected_path.clone()),
+                ("GITNAV_REPO_NAME", name),
+            ];
+            run_hook(&formatter, config, hooks::Event::PostSelect, &env);
+
             // Output selected path to stdout (shell wrapper will cd to it)
             println!("{}", selected_path);
+            // and the post_cd hook to the file the wrapper runs it from
+            if let Err(e) = hooks::write_post_cd(&config.hooks, &env) {
+                formatter.warn(&format!("Could not pass on post_cd hook: {:#}", e));
+            }
             Ok(())
         }
         None => {
@@ -418,3 +4523,176 @@ fn run_navigation(cli: &Cli) -> Result<()> {
         }
     }
 }
+
+/// Open fzf immediately and feed it repositories while the scan runs.
+///
+/// Used on a cache miss. A finished scan is cached and runs the usual hooks. If
+/// a repository is picked before the scan finishes, a detached
+/// `gitnav --refresh-cache` completes the scan so the next run hits the cache.
+fn stream_navigation(
+    cli: &Cli,
+    config: &config::Config,
+    search_paths: &[String],
+    query: Option<&str>,
+    enter: actions::Action,
+) -> Result<()> {
+    let max_depth_flag = cli.max_depth;
+    let cache = if config.cache.enabled && !cli.force {
+        Some((open_cache(cli, config)?, cache_key(search_paths)))
+    } else {
+        None
+    };
+    let excludes = exclude::ExcludeList::load_default()?;
+    let tags = tags::TagStore::load_default()?;
+    let required_tags = cli.tags.clone();
+    let wanted = move |tags: &tags::TagStore, path: &Path| {
+        let have = tags.tags_for(path);
+        required_tags.iter().all(|tag| have.contains(tag))
+    };
+
+    // Pinned repos under the search paths are sent before the scan starts so
+    // they stay at the top; the scan skips them when it reaches them
+    let roots: Vec<PathBuf> = search_paths
+        .iter()
+        .filter_map(|p| Path::new(p).canonicalize().ok())
+        .collect();
+    let pinned: Vec<PathBuf> = pin::PinList::load_default()?
+        .entries()
+        .iter()
+        .filter(|p| roots.iter().any(|root| p.starts_with(root)))
+        .filter(|p| p.join(".git").exists() && !excludes.contains(p))
+        .filter(|p| wanted(&tags, p))
+        .cloned()
+        .collect();
+
+    if cli.verbose {
+        eprintln!("DEBUG: Cache miss, streaming scan results into fzf");
+    }
+
+    let (tx, rx) = std::sync::mpsc::channel();
+    let streamed = std::sync::Arc::new(std::sync::atomic::AtomicUsize::new(0));
+    let scan = {
+        let streamed = streamed.clone();
+        let formatter = output::OutputFormatter::new(cli.quiet, cli.verbose, cli.no_color);
+        let config = config.clone();
+        let search_paths = search_paths.to_vec();
+        std::thread::spawn(move || -> Result<Option<usize>> {
+            let enrich = |repo: scanner::GitRepo| {
+                let mut repo = if config.ui.show_inline_meta {
+                    scanner::enrich_repos(vec![repo]).remove(0)
+                } else {
+                    scanner::without_meta(repo)
+                };
+                repo.tags = tags.tags_for(&repo.path);
+                repo
+            };
+            for path in &pinned {
+                let mut repo = enrich(scanner::GitRepo::new(path.clone()));
+                repo.pinned = true;
+                streamed.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+                if tx.send(repo).is_err() {
+                    return Ok(None);
+                }
+            }
+
+            let cache = cache.as_ref().map(|(cache, key)| (cache, key.as_str()));
+            let repos = scan_and_cache(
+                &formatter,
+                &config,
+                &search_paths,
+                max_depth_flag,
+                cache,
+                |repo| {
+                    if excludes.contains(&repo.path) || !wanted(&tags, &repo.path) {
+                        return true;
+                    }
+                    let already_sent = !pinned.is_empty()
+                        && (pinned.contains(&repo.path)
+                            || repo
+                                .path
+                                .canonicalize()
+                                .is_ok_and(|path| pinned.contains(&path)));
+                    if already_sent {
+                        return true;
+                    }
+                    streamed.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+                    tx.send(enrich(repo.clone())).is_ok()
+                },
+            )?;
+            Ok(repos.map(|repos| {
+                excludes
+                    .filter(repos)
+                    .iter()
+                    .filter(|repo| wanted(&tags, &repo.path))
+                    .count()
+            }))
+        })
+    };
+
+    let current_exe = std::env::current_exe().context("Failed to get current executable path")?;
+    let binary_path = current_exe.to_string_lossy();
+    let selection = fzf::select_repo_streaming(rx, config, &binary_path, query, cli.multi)?;
+
+    // fzf exits on its own when the scan found nothing; wait for the scan to
+    // wrap up (hooks, cache) so that case is reported as an error
+    let nothing_streamed = streamed.load(std::sync::atomic::Ordering::Relaxed) == 0;
+    let found = if scan.is_finished() || (selection.is_none() && nothing_streamed) {
+        scan.join()
+            .map_err(|_| anyhow::anyhow!("Repository scan panicked"))??
+    } else {
+        None
+    };
+    if selection.is_none() && found == Some(0) {
+        exit_no_repos(cli, &describe_source(cli, search_paths.join(", ")));
+    }
+    // `None` means the scan was cut short, so the cache was not written
+    if found.is_none() && selection.is_some() && config.cache.enabled && !cli.force {
+        spawn_cache_refresh(cli, &current_exe);
+    }
+
+    finish_picker(cli, config, selection, enter)
+}
+
+/// Rescan and write the cache in a detached process that outlives this one.
+fn spawn_cache_refresh(cli: &Cli, current_exe: &Path) {
+    let mut cmd = std::process::Command::new(current_exe);
+    if let Some(ref path) = cli.config {
+        cmd.arg("--config").arg(path);
+    }
+    if let Some(ref path) = cli.path {
+        cmd.arg("--path").arg(path);
+    }
+    if let Some(depth) = cli.max_depth {
+        cmd.arg("--max-depth").arg(depth.to_string());
+    }
+    if let Some(ref profile) = cli.profile {
+        cmd.arg("--profile").arg(profile);
+    }
+    let _ = cmd
+        .arg("--refresh-cache")
+        .stdin(std::process::Stdio::null())
+        .stdout(std::process::Stdio::null())
+        .stderr(std::process::Stdio::null())
+        .spawn();
+}
+
+/// Scan and write the cache (`--refresh-cache`, run in the background).
+fn handle_refresh_cache(cli: &Cli) -> Result<()> {
+    let config = config::Config::load(cli.config.clone())?;
+    if !config.cache.enabled {
+        return Ok(());
+    }
+    let search_paths = resolve_search_paths(cli, &config);
+    let cache = open_cache(cli, &config)?;
+    let key = cache_key(&search_paths);
+    let formatter = output::OutputFormatter::new(true, false, true);
+    scan_and_cache(
+        &formatter,
+        &config,
+        &search_paths,
+        cli.max_depth,
+        Some((&cache, key.as_str())),
+        |_| true,
+    )?;
+    Ok(())
+}
@@ -0,0 +1,192 @@
+use serde::Serialize;
+use std::path::Path;
+
+/// Package metadata read from a repository's project manifest.
+///
+/// Shown in the preview because the directory a repository was cloned into
+/// often differs from the package it builds.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Manifest {
+    /// Manifest file the metadata came from (e.g. "Cargo.toml")
+    pub file: String,
+    pub name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub version: Option<String>,
+    /// Primary language, e.g. "Rust" or "TypeScript"
+    pub language: String,
+}
+
+/// Read the project manifest at the root of `path`.
+///
+/// Manifests are checked in the same order as
+/// [`detect_project_type`](crate::scanner::detect_project_type): `Cargo.toml`,
+/// `package.json`, `go.mod`, then `pyproject.toml`. Returns `None` when none of
+/// them exists or names a package (a Cargo workspace root, say).
+pub fn detect(path: &Path) -> Option<Manifest> {
+    cargo(path)
+        .or_else(|| package_json(path))
+        .or_else(|| go_mod(path))
+        .or_else(|| pyproject(path))
+}
+
+fn cargo(path: &Path) -> Option<Manifest> {
+    let value = read_toml(&path.join("Cargo.toml"))?;
+    let package = value.get("package")?;
+    Some(Manifest {
+        file: "Cargo.toml".to_string(),
+        name: string_field(package, "name")?,
+        // `version.workspace = true` is a table and yields no version
+        version: string_field(package, "version"),
+        language: "Rust".to_string(),
+    })
+}
+
+fn package_json(path: &Path) -> Option<Manifest> {
+    let text = std::fs::read_to_string(path.join("package.json")).ok()?;
+    let value: serde_json::Value = serde_json::from_str(&text).ok()?;
+    let field = |key: &str| value.get(key)?.as_str().map(str::to_string);
+    let typescript = path.join("tsconfig.json").exists()
+        || ["dependencies", "devDependencies"]
+            .iter()
+            .any(|deps| value.get(deps).and_then(|d| d.get("typescript")).is_some());
+    Some(Manifest {
+        file: "package.json".to_string(),
+        name: field("name")?,
+        version: field("version"),
+        language: if typescript {
+            "TypeScript"
+        } else {
+            "JavaScript"
+        }
+        .to_string(),
+    })
+}
+
+fn go_mod(path: &Path) -> Option<Manifest> {
+    let text = std::fs::read_to_string(path.join("go.mod")).ok()?;
+    let module = text.lines().find_map(|line| {
+        let rest = line.trim().strip_prefix("module")?;
+        rest.starts_with(char::is_whitespace)
+            .then(|| rest.trim().trim_matches('"').to_string())
+    })?;
+    Some(Manifest {
+        file: "go.mod".to_string(),
+        name: module,
+        version: None,
+        language: "Go".to_string(),
+    })
+}
+
+fn pyproject(path: &Path) -> Option<Manifest> {
+    let value = read_toml(&path.join("pyproject.toml"))?;
+    // PEP 621 `[project]`, falling back to Poetry's `[tool.poetry]`
+    let table = value
+        .get("project")
+        .filter(|p| p.get("name").is_some())
+        .or_else(|| value.get("tool")?.get("poetry"))?;
+    Some(Manifest {
+        file: "pyproject.toml".to_string(),
+        name: string_field(table, "name")?,
+        version: string_field(table, "version"),
+        language: "Python".to_string(),
+    })
+}
+
+fn read_toml(path: &Path) -> Option<toml::Value> {
+    std::fs::read_to_string(path).ok()?.parse().ok()
+}
+
+fn string_field(table: &toml::Value, key: &str) -> Option<String> {
+    table.get(key)?.as_str().map(str::to_string)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn write(dir: &Path, file: &str, contents: &str) {
+        std::fs::write(dir.join(file), contents).unwrap();
+    }
+
+    #[test]
+    fn test_detect_cargo() {
+        let dir = tempfile::tempdir().unwrap();
+        write(
+            dir.path(),
+            "Cargo.toml",
+            "[package]\nname = \"gitnav\"\nversion = \"0.2.0\"\n",
+        );
+        assert_eq!(
+            detect(dir.path()),
+            Some(Manifest {
+                file: "Cargo.toml".to_string(),
+                name: "gitnav".to_string(),
+                version: Some("0.2.0".to_string()),
+                language: "Rust".to_string(),
+            })
+        );
+    }
+
+    #[test]
+    fn test_detect_cargo_workspace_root_has_no_package() {
+        let dir = tempfile::tempdir().unwrap();
+        write(dir.path(), "Cargo.toml", "[workspace]\nmembers = [\"a\"]\n");
+        assert_eq!(detect(dir.path()), None);
+    }
+
+    #[test]
+    fn test_detect_package_json_language() {
+        let dir = tempfile::tempdir().unwrap();
+        write(
+            dir.path(),
+            "package.json",
+            r#"{"name": "@acme/web", "version": "1.4.2"}"#,
+        );
+        let manifest = detect(dir.path()).unwrap();
+        assert_eq!(manifest.name, "@acme/web");
+        assert_eq!(manifest.version.as_deref(), Some("1.4.2"));
+        assert_eq!(manifest.language, "JavaScript");
+
+        write(
+            dir.path(),
+            "package.json",
+            r#"{"name": "web", "devDependencies": {"typescript": "^5.0.0"}}"#,
+        );
+        let manifest = detect(dir.path()).unwrap();
+        assert_eq!(manifest.version, None);
+        assert_eq!(manifest.language, "TypeScript");
+    }
+
+    #[test]
+    fn test_detect_go_mod() {
+        let dir = tempfile::tempdir().unwrap();
+        write(
+            dir.path(),
+            "go.mod",
+            "// comment\nmodule github.com/acme/api\n\ngo 1.22\n",
+        );
+        let manifest = detect(dir.path()).unwrap();
+        assert_eq!(manifest.name, "github.com/acme/api");
+        assert_eq!(manifest.language, "Go");
+    }
+
+    #[test]
+    fn test_detect_pyproject_and_poetry() {
+        let dir = tempfile::tempdir().unwrap();
+        write(
+            dir.path(),
+            "pyproject.toml",
+            "[project]\nname = \"tool\"\nversion = \"3.1\"\n",
+        );
+        let manifest = detect(dir.path()).unwrap();
+        assert_eq!(manifest.name, "tool");
+        assert_eq!(manifest.version.as_deref(), Some("3.1"));
+
+        write(
+            dir.path(),
+            "pyproject.toml",
+            "[tool.poetry]\nname = \"legacy\"\nversion = \"0.9\"\n",
+        );
+        assert_eq!(detect(dir.path()).unwrap().name, "legacy");
+    }
+}
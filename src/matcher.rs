@@ -0,0 +1,470 @@
+//! Non-interactive fuzzy matching that mirrors fzf's default behavior.
+//!
+//! The query is split on whitespace into terms that must all match (fzf's
+//! extended search). Each term is matched fuzzily unless it uses one of fzf's
+//! operators: `'exact`, `^prefix`, `suffix$`, or `!negated`. Terms are
+//! smart-case: case-insensitive unless they contain an uppercase letter.
+//!
+//! Scores use fzf's constants and bonuses (word boundaries, camelCase,
+//! consecutive runs), so rankings line up with what the picker shows.
+//!
+//! `[matcher]` in the config can switch plain terms to substring matching
+//! (like `fzf --exact`, where `'` then marks a fuzzy term), turn off smart-case,
+//! or drop the word-boundary bonuses.
+
+use serde::Serialize;
+
+use crate::config::{MatchAlgorithm, MatcherConfig};
+use crate::scanner::GitRepo;
+
+const SCORE_MATCH: i64 = 16;
+const SCORE_GAP_START: i64 = -3;
+const SCORE_GAP_EXTENSION: i64 = -1;
+const BONUS_BOUNDARY: i64 = SCORE_MATCH / 2;
+const BONUS_BOUNDARY_WHITE: i64 = BONUS_BOUNDARY + 2;
+const BONUS_BOUNDARY_DELIMITER: i64 = BONUS_BOUNDARY + 1;
+const BONUS_CAMEL: i64 = BONUS_BOUNDARY - 1;
+const BONUS_CONSECUTIVE: i64 = -(SCORE_GAP_START + SCORE_GAP_EXTENSION);
+const BONUS_FIRST_CHAR_MULTIPLIER: i64 = 2;
+
+/// A repository that matched a query, with its score.
+#[derive(Debug, Clone, Serialize)]
+pub struct Ranked {
+    pub name: String,
+    pub path: String,
+    pub score: i64,
+    /// Position of the repository in the input slice
+    #[serde(skip)]
+    pub index: usize,
+}
+
+/// Rank repositories against `query`, best match first.
+///
+/// Each repository is matched on its name followed by its path, like the
+/// picker's list lines. Ties are broken by shorter text, then input order.
+pub fn rank(query: &str, repos: &[GitRepo], options: &MatcherConfig) -> Vec<Ranked> {
+    let terms = parse_query(query, options);
+    let mut ranked: Vec<(usize, usize, Ranked)> = repos
+        .iter()
+        .enumerate()
+        .filter_map(|(index, repo)| {
+            let path = repo.path.display().to_string();
+            let text = format!("{} {}", repo.name, path);
+            score_terms(&terms, &text, options.word_boundaries).map(|score| {
+                (
+                    index,
+                    text.chars().count(),
+                    Ranked {
+                        name: repo.name.clone(),
+                        path,
+                        score,
+                        index,
+                    },
+                )
+            })
+        })
+        .collect();
+
+    ranked.sort_by(|a, b| {
+        b.2.score
+            .cmp(&a.2.score)
+            .then(a.1.cmp(&b.1))
+            .then(a.0.cmp(&b.0))
+    });
+    ranked.into_iter().map(|(_, _, r)| r).collect()
+}
+
+/// Outcome of resolving a query to a single repository for `gitnav jump`.
+#[derive(Debug, Clone)]
+pub enum Resolution {
+    /// Exactly one repository is the obvious target
+    Unique(Ranked),
+    /// Several repositories match and none stands out
+    Ambiguous,
+    /// Nothing matches
+    NoMatch,
+}
+
+/// Resolve `query` to one repository, if it clearly names one.
+///
+/// A repository whose name equals the query (ignoring case) wins when it is
+/// the only one with that name; otherwise the query must match exactly one
+/// repository.
+pub fn resolve(query: &str, repos: &[GitRepo], options: &MatcherConfig) -> Resolution {
+    let mut ranked = rank(query, repos, options);
+    let wanted = query.trim();
+    let mut exact = ranked
+        .iter()
+        .filter(|r| r.name.eq_ignore_ascii_case(wanted))
+        .cloned();
+
+    match (exact.next(), exact.next()) {
+        (Some(only), None) => Resolution::Unique(only),
+        _ if ranked.len() == 1 => Resolution::Unique(ranked.remove(0)),
+        _ if ranked.is_empty() => Resolution::NoMatch,
+        _ => Resolution::Ambiguous,
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum TermKind {
+    Fuzzy,
+    Exact,
+    Prefix,
+    Suffix,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct Term {
+    kind: TermKind,
+    text: Vec<char>,
+    negated: bool,
+    case_sensitive: bool,
+}
+
+fn parse_query(query: &str, options: &MatcherConfig) -> Vec<Term> {
+    let plain = match options.algorithm {
+        MatchAlgorithm::Fuzzy => TermKind::Fuzzy,
+        MatchAlgorithm::Substring => TermKind::Exact,
+    };
+    query
+        .split_whitespace()
+        .filter_map(|raw| {
+            let mut s = raw;
+            let negated = s.starts_with('!');
+            if negated {
+                s = &s[1..];
+            }
+
+            let mut kind = if negated {
+                TermKind::Exact
+            } else {
+                plain.clone()
+            };
+            if let Some(rest) = s.strip_prefix('\'') {
+                // `'` selects the other algorithm; negated terms are always exact
+                kind = if negated || plain == TermKind::Fuzzy {
+                    TermKind::Exact
+                } else {
+                    TermKind::Fuzzy
+                };
+                s = rest;
+            } else if let Some(rest) = s.strip_prefix('^') {
+                kind = TermKind::Prefix;
+                s = rest;
+            } else if s.len() > 1 && s.ends_with('$') {
+                kind = TermKind::Suffix;
+                s = &s[..s.len() - 1];
+            }
+
+            if s.is_empty() {
+                return None;
+            }
+            let case_sensitive = options.smart_case && s.chars().any(char::is_uppercase);
+            let text = if case_sensitive {
+                s.chars().collect()
+            } else {
+                s.chars()
+                    .map(|c| c.to_lowercase().next().unwrap_or(c))
+                    .collect()
+            };
+            Some(Term {
+                kind,
+                text,
+                negated,
+                case_sensitive,
+            })
+        })
+        .collect()
+}
+
+/// All terms must match; negated terms must not match and add no score.
+///
+/// Without `word_boundaries` only consecutive runs earn a bonus.
+fn score_terms(terms: &[Term], text: &str, word_boundaries: bool) -> Option<i64> {
+    let original: Vec<char> = text.chars().collect();
+    let folded: Vec<char> = original
+        .iter()
+        .map(|c| c.to_lowercase().next().unwrap_or(*c))
+        .collect();
+    let bonuses: Vec<i64> = (0..original.len())
+        .map(|i| {
+            if word_boundaries {
+                bonus(&original, i)
+            } else {
+                0
+            }
+        })
+        .collect();
+
+    let mut total = 0;
+    for term in terms {
+        let haystack = if term.case_sensitive {
+            &original
+        } else {
+            &folded
+        };
+        let result = match term.kind {
+            TermKind::Fuzzy => fuzzy_score(&bonuses, haystack, &term.text),
+            TermKind::Exact => best_substring(&bonuses, haystack, &term.text),
+            TermKind::Prefix => haystack
+                .starts_with(&term.text)
+                .then(|| run_score(&bonuses, 0, term.text.len())),
+            TermKind::Suffix => haystack
+                .ends_with(&term.text)
+                .then(|| run_score(&bonuses, haystack.len() - term.text.len(), term.text.len())),
+        };
+        match (result, term.negated) {
+            (Some(_), true) | (None, false) => return None,
+            (Some(score), false) => total += score,
+            (None, true) => {}
+        }
+    }
+    Some(total)
+}
+
+/// Bonus for matching the character at `index`, based on the one before it.
+fn bonus(text: &[char], index: usize) -> i64 {
+    let current = text[index];
+    if !current.is_alphanumeric() {
+        return 0;
+    }
+    let Some(&prev) = index.checked_sub(1).and_then(|i| text.get(i)) else {
+        return BONUS_BOUNDARY_WHITE;
+    };
+    if prev.is_whitespace() {
+        BONUS_BOUNDARY_WHITE
+    } else if matches!(prev, '/' | '\\' | ':' | ',' | ';' | '|') {
+        BONUS_BOUNDARY_DELIMITER
+    } else if !prev.is_alphanumeric() {
+        BONUS_BOUNDARY
+    } else if (prev.is_lowercase() && current.is_uppercase())
+        || (!prev.is_numeric() && current.is_numeric())
+    {
+        BONUS_CAMEL
+    } else {
+        0
+    }
+}
+
+/// Score a contiguous run of `len` matched characters starting at `start`.
+fn run_score(bonuses: &[i64], start: usize, len: usize) -> i64 {
+    let first = bonuses[start];
+    let mut score = SCORE_MATCH + first * BONUS_FIRST_CHAR_MULTIPLIER;
+    let mut run_bonus = first;
+    for &b in &bonuses[start + 1..start + len] {
+        run_bonus = run_bonus.max(b).max(BONUS_CONSECUTIVE);
+        score += SCORE_MATCH + run_bonus;
+    }
+    score
+}
+
+fn best_substring(bonuses: &[i64], haystack: &[char], needle: &[char]) -> Option<i64> {
+    if needle.len() > haystack.len() {
+        return None;
+    }
+    haystack
+        .windows(needle.len())
+        .enumerate()
+        .filter(|(_, window)| *window == needle)
+        .map(|(start, _)| run_score(bonuses, start, needle.len()))
+        .max()
+}
+
+/// Best-scoring fuzzy alignment of `needle` in `haystack`.
+///
+/// A dynamic program over (needle char, haystack position) with affine gap
+/// penalties: `matched[j]` is the best score with the current needle character
+/// placed at `j`, and `run[j]` tracks the consecutive-match bonus carried there.
+fn fuzzy_score(bonuses: &[i64], haystack: &[char], needle: &[char]) -> Option<i64> {
+    let n = haystack.len();
+    if needle.is_empty() || needle.len() > n {
+        return None;
+    }
+
+    let mut prev_matched: Vec<Option<i64>> = vec![None; n];
+    let mut prev_run: Vec<i64> = vec![0; n];
+
+    for (i, &qc) in needle.iter().enumerate() {
+        let mut matched: Vec<Option<i64>> = vec![None; n];
+        let mut run: Vec<i64> = vec![0; n];
+        // Best score from the previous row ending at least two positions back,
+        // already charged for the gap up to the current position
+        let mut gapped: Option<i64> = None;
+
+        for j in i..n {
+            if i > 0 && j >= 2 {
+                let opened = prev_matched[j - 2].map(|s| s + SCORE_GAP_START);
+                let extended = gapped.map(|s| s + SCORE_GAP_EXTENSION);
+                gapped = opened.max(extended);
+            }
+            if haystack[j] != qc {
+                continue;
+            }
+
+            let b = bonuses[j];
+            if i == 0 {
+                matched[j] = Some(SCORE_MATCH + b * BONUS_FIRST_CHAR_MULTIPLIER);
+                run[j] = b;
+                continue;
+            }
+
+            let consecutive = prev_matched[j - 1].map(|s| {
+                let run_bonus = prev_run[j - 1].max(b).max(BONUS_CONSECUTIVE);
+                (s + SCORE_MATCH + run_bonus, run_bonus)
+            });
+            let after_gap = gapped.map(|s| (s + SCORE_MATCH + b, b));
+            let best = match (consecutive, after_gap) {
+                (Some(c), Some(g)) => Some(if g.0 > c.0 { g } else { c }),
+                (c, g) => c.or(g),
+            };
+            if let Some((score, run_bonus)) = best {
+                matched[j] = Some(score);
+                run[j] = run_bonus;
+            }
+        }
+
+        prev_matched = matched;
+        prev_run = run;
+    }
+
+    prev_matched.into_iter().flatten().max()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    fn score(query: &str, text: &str) -> Option<i64> {
+        score_with(&MatcherConfig::default(), query, text)
+    }
+
+    fn score_with(options: &MatcherConfig, query: &str, text: &str) -> Option<i64> {
+        score_terms(&parse_query(query, options), text, options.word_boundaries)
+    }
+
+    fn repo(path: &str) -> GitRepo {
+        GitRepo::new(PathBuf::from(path))
+    }
+
+    #[test]
+    fn test_fuzzy_requires_subsequence() {
+        assert!(score("gnv", "gitnav").is_some());
+        assert!(score("vng", "gitnav").is_none());
+        assert!(score("", "gitnav") == Some(0));
+    }
+
+    #[test]
+    fn test_smart_case() {
+        assert!(score("api", "API-server").is_some());
+        assert!(score("API", "api-server").is_none());
+        assert!(score("API", "API-server").is_some());
+    }
+
+    #[test]
+    fn test_consecutive_and_boundary_matches_score_higher() {
+        assert!(score("nav", "gitnav").unwrap() > score("nav", "gnxaxv").unwrap());
+        assert!(score("nav", "n-a-v").unwrap() > score("nav", "xnxaxv").unwrap());
+        assert!(score("cli", "my-cli").unwrap() > score("cli", "myclix").unwrap());
+    }
+
+    #[test]
+    fn test_extended_operators() {
+        assert!(score("'nav", "gitnav").is_some());
+        assert!(score("'gnv", "gitnav").is_none());
+        assert!(score("^git", "gitnav").is_some());
+        assert!(score("^nav", "gitnav").is_none());
+        assert!(score("nav$", "gitnav").is_some());
+        assert!(score("git$", "gitnav").is_none());
+        assert!(score("git !fork", "gitnav").is_some());
+        assert!(score("git !nav", "gitnav").is_none());
+    }
+
+    #[test]
+    fn test_all_terms_must_match() {
+        assert!(score("work api", "api /home/u/work/api").is_some());
+        assert!(score("work web", "api /home/u/work/api").is_none());
+    }
+
+    #[test]
+    fn test_rank_orders_by_score_then_length() {
+        let repos = vec![
+            repo("/src/tools/cli-tools-legacy"),
+            repo("/src/c-l-i"),
+            repo("/src/cli-tools"),
+        ];
+        let ranked = rank("cli-tools", &repos, &MatcherConfig::default());
+        assert_eq!(ranked.len(), 2);
+        assert_eq!(ranked[0].name, "cli-tools");
+        assert_eq!(ranked[1].name, "cli-tools-legacy");
+        assert!(ranked[0].score >= ranked[1].score);
+
+        assert!(rank("zzz", &repos, &MatcherConfig::default()).is_empty());
+    }
+
+    #[test]
+    fn test_substring_algorithm() {
+        let options = MatcherConfig {
+            algorithm: MatchAlgorithm::Substring,
+            ..MatcherConfig::default()
+        };
+        assert!(score_with(&options, "nav", "gitnav").is_some());
+        assert!(score_with(&options, "gnv", "gitnav").is_none());
+        // `'` switches a term back to fuzzy matching
+        assert!(score_with(&options, "'gnv", "gitnav").is_some());
+        assert!(score_with(&options, "git !nav", "gitnav").is_none());
+    }
+
+    #[test]
+    fn test_smart_case_can_be_disabled() {
+        let options = MatcherConfig {
+            smart_case: false,
+            ..MatcherConfig::default()
+        };
+        assert!(score_with(&options, "API", "api-server").is_some());
+    }
+
+    #[test]
+    fn test_word_boundary_bonus_can_be_disabled() {
+        let options = MatcherConfig {
+            word_boundaries: false,
+            ..MatcherConfig::default()
+        };
+        assert!(score("cli", "my-cli").unwrap() > score("cli", "myclix").unwrap());
+        assert_eq!(
+            score_with(&options, "cli", "my-cli"),
+            score_with(&options, "cli", "myclix")
+        );
+    }
+
+    #[test]
+    fn test_resolve() {
+        let repos = vec![
+            repo("/src/cli"),
+            repo("/src/cli-tools"),
+            repo("/src/work/api"),
+            repo("/src/home/api"),
+        ];
+        let options = MatcherConfig::default();
+        let path = |query| match resolve(query, &repos, &options) {
+            Resolution::Unique(r) => Some(r.path),
+            _ => None,
+        };
+
+        // An exact name wins even though "cli-tools" also matches
+        assert_eq!(path("cli"), Some("/src/cli".to_string()));
+        assert_eq!(path("tools"), Some("/src/cli-tools".to_string()));
+        assert_eq!(path("work api"), Some("/src/work/api".to_string()));
+
+        // Two repositories named "api"
+        assert!(matches!(
+            resolve("api", &repos, &options),
+            Resolution::Ambiguous
+        ));
+        assert!(matches!(
+            resolve("zzz", &repos, &options),
+            Resolution::NoMatch
+        ));
+    }
+}
@@ -0,0 +1,165 @@
+use anyhow::Result;
+
+use crate::parallel;
+use crate::scanner::GitRepo;
+use crate::status::{self, Divergence};
+
+/// A repository whose current branch is behind its upstream or has unpushed commits.
+#[derive(Debug, Clone)]
+pub struct Finding {
+    pub name: String,
+    pub divergence: Divergence,
+}
+
+/// Find repositories that are behind their upstream or have unpushed commits.
+///
+/// Uses the remote-tracking refs left by the last fetch; call [`crate::fetch::fetch_all`] first
+/// for fresh data.
+pub fn find_pending(repos: &[GitRepo]) -> Vec<Finding> {
+    parallel::filter_map(repos, parallel::default_workers(), |repo| {
+        status::divergence(&repo.path)
+            .filter(|d| d.ahead > 0 || d.behind > 0)
+            .map(|divergence| Finding {
+                name: repo.name.clone(),
+                divergence,
+            })
+    })
+}
+
+/// Format findings as one compact line per repository.
+///
+/// Example: `api  main  2 unpushed, 1 behind origin/main`
+pub fn format_summary(findings: &[Finding]) -> String {
+    let name_width = findings.iter().map(|f| f.name.len()).max().unwrap_or(0);
+    let branch_width = findings
+        .iter()
+        .map(|f| f.divergence.branch.len())
+        .max()
+        .unwrap_or(0);
+
+    findings
+        .iter()
+        .map(|f| {
+            format!(
+                "{:<nw$}  {:<bw$}  {}",
+                f.name,
+                f.divergence.branch,
+                describe(&f.divergence),
+                nw = name_width,
+                bw = branch_width
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Describe a divergence, e.g. "2 unpushed, 1 behind origin/main".
+fn describe(d: &Divergence) -> String {
+    let mut parts = Vec::new();
+    if d.ahead > 0 {
+        parts.push(format!("{} unpushed", d.ahead));
+    }
+    if d.behind > 0 {
+        parts.push(format!("{} behind {}", d.behind, d.upstream));
+    }
+    parts.join(", ")
+}
+
+/// Show a single desktop notification summarizing the findings.
+///
+/// # Errors
+///
+/// Returns an error if the notification cannot be delivered, or if gitnav was
+/// built without the `desktop-notifications` feature.
+#[cfg(feature = "desktop-notifications")]
+pub fn send_desktop(findings: &[Finding]) -> Result<()> {
+    use anyhow::Context;
+
+    let summary = match findings.len() {
+        1 => "gitnav: 1 repository needs attention".to_string(),
+        n => format!("gitnav: {} repositories need attention", n),
+    };
+    let body = findings
+        .iter()
+        .map(|f| format!("{}: {}", f.name, describe(&f.divergence)))
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    notify_rust::Notification::new()
+        .summary(&summary)
+        .body(&body)
+        .appname("gitnav")
+        .show()
+        .context("Failed to show desktop notification")?;
+    Ok(())
+}
+
+#[cfg(not(feature = "desktop-notifications"))]
+pub fn send_desktop(_findings: &[Finding]) -> Result<()> {
+    anyhow::bail!(
+        "gitnav was built without desktop notification support; \
+         rebuild with `--features desktop-notifications` or drop --desktop"
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::commit;
+    use git2::Repository;
+    use std::path::Path;
+
+    /// Create a repo whose `main` tracks `origin/main`, `ahead` commits ahead.
+    fn repo_with_upstream(dir: &Path, ahead: usize) {
+        let mut opts = git2::RepositoryInitOptions::new();
+        opts.initial_head("main");
+        let repo = Repository::init_opts(dir, &opts).unwrap();
+        let base = commit(&repo, "base", None);
+        repo.reference("refs/remotes/origin/main", base, true, "test")
+            .unwrap();
+        repo.remote("origin", "https://example.com/repo.git")
+            .unwrap();
+        repo.find_branch("main", git2::BranchType::Local)
+            .unwrap()
+            .set_upstream(Some("origin/main"))
+            .unwrap();
+        for i in 0..ahead {
+            commit(&repo, &format!("local {}", i), None);
+        }
+    }
+
+    #[test]
+    fn test_find_pending_reports_only_diverged_repos() {
+        let tmp = tempfile::tempdir().unwrap();
+        repo_with_upstream(&tmp.path().join("synced"), 0);
+        repo_with_upstream(&tmp.path().join("unpushed"), 2);
+        let repos = vec![
+            GitRepo::new(tmp.path().join("synced")),
+            GitRepo::new(tmp.path().join("unpushed")),
+        ];
+
+        let findings = find_pending(&repos);
+        assert_eq!(findings.len(), 1);
+        assert_eq!(findings[0].name, "unpushed");
+        assert_eq!(findings[0].divergence.ahead, 2);
+        assert_eq!(findings[0].divergence.behind, 0);
+        assert_eq!(findings[0].divergence.upstream, "origin/main");
+    }
+
+    #[test]
+    fn test_format_summary() {
+        let finding = |name: &str, ahead, behind| Finding {
+            name: name.to_string(),
+            divergence: Divergence {
+                branch: "main".to_string(),
+                upstream: "origin/main".to_string(),
+                ahead,
+                behind,
+            },
+        };
+        let text = format_summary(&[finding("api", 2, 0), finding("website", 1, 3)]);
+        let lines: Vec<&str> = text.lines().collect();
+        assert_eq!(lines[0], "api      main  2 unpushed");
+        assert_eq!(lines[1], "website  main  1 unpushed, 3 behind origin/main");
+    }
+}
@@ -166,7 +166,6 @@ impl OutputFormatter {
     }
 
     /// Print warning message to stderr.
-    #[allow(dead_code)]
     pub fn warn(&self, msg: &str) {
         let _ = writeln!(stderr(), "Warning: {}", msg);
     }
@@ -199,13 +198,11 @@ impl OutputFormatter {
     }
 
     /// Format green text.
-    #[allow(dead_code)]
     pub fn green(&self, text: &str) -> String {
         self.colorize(text, "\x1b[32m")
     }
 
     /// Format red text.
-    #[allow(dead_code)]
     pub fn red(&self, text: &str) -> String {
         self.colorize(text, "\x1b[31m")
     }
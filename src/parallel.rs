@@ -0,0 +1,111 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::thread;
+
+/// Default number of worker threads: one per available CPU.
+pub fn default_workers() -> usize {
+    thread::available_parallelism()
+        .map(|n| n.get())
+        .unwrap_or(4)
+}
+
+/// Apply `f` to every item on up to `workers` threads, keeping input order.
+///
+/// Items are split into contiguous chunks, one per worker, so results come back
+/// in the same order as `items`. Items for which `f` returns `None` are dropped.
+pub fn filter_map<T, R, F>(items: &[T], workers: usize, f: F) -> Vec<R>
+where
+    T: Sync,
+    R: Send,
+    F: Fn(&T) -> Option<R> + Sync,
+{
+    if items.is_empty() {
+        return Vec::new();
+    }
+    let chunk_size = items.len().div_ceil(workers.max(1));
+    let f = &f;
+
+    thread::scope(|scope| {
+        let handles: Vec<_> = items
+            .chunks(chunk_size)
+            .map(|chunk| scope.spawn(move || chunk.iter().filter_map(f).collect::<Vec<_>>()))
+            .collect();
+
+        handles
+            .into_iter()
+            .flat_map(|h| h.join().unwrap_or_default())
+            .collect()
+    })
+}
+
+/// Apply `f` to every item on up to `workers` threads, keeping input order.
+///
+/// Each worker takes the next unclaimed item, so one slow item does not hold
+/// up others the way a chunk in [`filter_map`] does. Suits network-bound work.
+pub fn map_queued<T, R, F>(items: &[T], workers: usize, f: F) -> Vec<R>
+where
+    T: Sync,
+    R: Send,
+    F: Fn(&T) -> R + Sync,
+{
+    let next = AtomicUsize::new(0);
+    let (next, f) = (&next, &f);
+
+    let mut results: Vec<(usize, R)> = thread::scope(|scope| {
+        let handles: Vec<_> = (0..workers.clamp(1, items.len().max(1)))
+            .map(|_| {
+                scope.spawn(move || {
+                    let mut done = Vec::new();
+                    loop {
+                        let i = next.fetch_add(1, Ordering::Relaxed);
+                        let Some(item) = items.get(i) else { break };
+                        done.push((i, f(item)));
+                    }
+                    done
+                })
+            })
+            .collect();
+
+        handles
+            .into_iter()
+            .flat_map(|h| h.join().unwrap_or_default())
+            .collect()
+    });
+    results.sort_by_key(|(i, _)| *i);
+    results.into_iter().map(|(_, r)| r).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_filter_map_keeps_order() {
+        let items: Vec<u32> = (0..100).collect();
+        let evens = filter_map(&items, 7, |n| (n % 2 == 0).then_some(n * 10));
+        assert_eq!(
+            evens,
+            (0..100).step_by(2).map(|n| n * 10).collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn test_filter_map_handles_edge_sizes() {
+        let empty: Vec<u32> = Vec::new();
+        assert!(filter_map(&empty, 4, |n| Some(*n)).is_empty());
+        assert_eq!(filter_map(&[1, 2], 0, |n| Some(*n)), vec![1, 2]);
+        assert_eq!(filter_map(&[1, 2], 16, |n| Some(*n)), vec![1, 2]);
+    }
+
+    #[test]
+    fn test_map_queued_keeps_order() {
+        let items: Vec<u64> = (0..50).collect();
+        let doubled = map_queued(&items, 4, |n| {
+            // Uneven work so items finish out of order
+            thread::sleep(std::time::Duration::from_millis(n % 3));
+            n * 2
+        });
+        assert_eq!(doubled, (0..50).map(|n| n * 2).collect::<Vec<_>>());
+        assert!(map_queued(&[] as &[u64], 4, |n| *n).is_empty());
+        assert_eq!(map_queued(&[1, 2], 0, |n| *n), vec![1, 2]);
+    }
+}
@@ -0,0 +1,263 @@
+use anyhow::Result;
+use rusqlite::params;
+use std::path::{Path, PathBuf};
+
+use crate::scanner::GitRepo;
+use crate::state::{self, State};
+
+/// Persistent list of repositories pinned to the top of the picker.
+///
+/// Stored in the `pins` table of the state database in the gitnav data
+/// directory, so pins survive `gitnav clear-cache`. Pinned repositories are
+/// listed in the order they were pinned. Saving only inserts the new pins and
+/// deletes the dropped ones, so two gitnav commands never undo each other.
+#[derive(Debug)]
+pub struct PinList {
+    state: State,
+    entries: Vec<PathBuf>,
+    /// Pinned since loading, not yet in the database
+    added: Vec<PathBuf>,
+    /// Unpinned since loading
+    removed: Vec<PathBuf>,
+}
+
+impl PinList {
+    /// Load the pin list from the default state database.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the data directory cannot be determined or the
+    /// database cannot be read
+    pub fn load_default() -> Result<Self> {
+        Self::load(State::open_default()?)
+    }
+
+    /// Load the pin list from `state`, first importing the `pinned` file
+    /// (one path per line) that earlier versions kept next to it.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the database or the old file cannot be read
+    pub fn load(state: State) -> Result<Self> {
+        state.import_once(&state.legacy_file("pinned"), |tx, contents| {
+            let paths = contents.lines().map(str::trim).filter(|l| !l.is_empty());
+            for (position, path) in paths.enumerate() {
+                tx.execute(
+                    "INSERT OR IGNORE INTO pins (path, position) VALUES (?1, ?2)",
+                    params![path, position as i64],
+                )?;
+            }
+            Ok(())
+        })?;
+
+        let entries = state
+            .conn()
+            .prepare("SELECT path FROM pins ORDER BY position")?
+            .query_map([], |row| row.get::<_, String>(0).map(PathBuf::from))?
+            .collect::<rusqlite::Result<_>>()?;
+        Ok(Self {
+            state,
+            entries,
+            added: Vec::new(),
+            removed: Vec::new(),
+        })
+    }
+
+    /// Pinned repository paths, in the order they were pinned.
+    pub fn entries(&self) -> &[PathBuf] {
+        &self.entries
+    }
+
+    /// Add a path to the list. Returns `false` if it was already pinned.
+    pub fn add(&mut self, path: &Path) -> bool {
+        let path = normalize(path);
+        if self.entries.contains(&path) {
+            return false;
+        }
+        self.removed.retain(|p| *p != path);
+        self.added.push(path.clone());
+        self.entries.push(path);
+        true
+    }
+
+    /// Remove a path from the list. Returns `false` if it was not pinned.
+    pub fn remove(&mut self, path: &Path) -> bool {
+        let path = normalize(path);
+        let before = self.entries.len();
+        self.entries.retain(|p| *p != path);
+        if self.entries.len() == before {
+            return false;
+        }
+        self.added.retain(|p| *p != path);
+        self.removed.push(path);
+        true
+    }
+
+    /// Unpin repositories that no longer exist. Returns their paths.
+    pub fn remove_missing(&mut self) -> Vec<PathBuf> {
+        let (kept, removed): (Vec<PathBuf>, Vec<PathBuf>) = std::mem::take(&mut self.entries)
+            .into_iter()
+            .partition(|p| p.is_dir());
+        self.entries = kept;
+        self.added.retain(|p| !removed.contains(p));
+        self.removed.extend(removed.iter().cloned());
+        removed
+    }
+
+    /// Position of a repository path in the pin list, if it is pinned.
+    fn position(&self, path: &Path) -> Option<usize> {
+        if self.entries.is_empty() {
+            return None;
+        }
+        self.entries.iter().position(|p| p == path).or_else(|| {
+            let path = normalize(path);
+            self.entries.iter().position(|p| *p == path)
+        })
+    }
+
+    /// Check whether a repository path is pinned.
+    pub fn contains(&self, path: &Path) -> bool {
+        self.position(path).is_some()
+    }
+
+    /// Move pinned repositories to the front, in pin order; the rest keep their order.
+    pub fn float(&self, repos: Vec<GitRepo>) -> Vec<GitRepo> {
+        if self.entries.is_empty() {
+            return repos;
+        }
+        let mut keyed: Vec<(Option<usize>, GitRepo)> = repos
+            .into_iter()
+            .map(|repo| (self.position(&repo.path), repo))
+            .collect();
+        // Stable sort: `None` (unpinned) sorts after every `Some`
+        keyed.sort_by_key(|(pos, _)| pos.unwrap_or(usize::MAX));
+        keyed.into_iter().map(|(_, repo)| repo).collect()
+    }
+
+    /// Write the new and dropped pins to the database. New pins go after
+    /// every pin already there.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the database cannot be written
+    pub fn save(&mut self) -> Result<()> {
+        let tx = self.state.write()?;
+        for path in &self.removed {
+            tx.execute("DELETE FROM pins WHERE path = ?1", [state::path_text(path)])?;
+        }
+        for path in &self.added {
+            tx.execute(
+                "INSERT OR IGNORE INTO pins (path, position)
+                 SELECT ?1, COALESCE(MAX(position) + 1, 0) FROM pins",
+                [state::path_text(path)],
+            )?;
+        }
+        tx.commit()?;
+
+        self.added.clear();
+        self.removed.clear();
+        Ok(())
+    }
+}
+
+/// Canonicalize a path when it exists so `.` and symlinked spellings match scan results.
+fn normalize(path: &Path) -> PathBuf {
+    path.canonicalize().unwrap_or_else(|_| path.to_path_buf())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn open(dir: &Path) -> State {
+        State::open(&dir.join("state.db")).unwrap()
+    }
+
+    #[test]
+    fn test_add_remove_roundtrip() {
+        let dir = tempfile::tempdir().unwrap();
+        let repo = PathBuf::from("/nonexistent/work/api");
+
+        let mut list = PinList::load(open(dir.path())).unwrap();
+        assert!(list.add(&repo));
+        assert!(!list.add(&repo), "duplicate add should be a no-op");
+        list.save().unwrap();
+
+        let mut reloaded = PinList::load(open(dir.path())).unwrap();
+        assert!(reloaded.contains(&repo));
+        assert!(reloaded.remove(&repo));
+        assert!(!reloaded.remove(&repo));
+        assert!(!reloaded.contains(&repo));
+    }
+
+    #[test]
+    fn test_imports_pinned_file_once() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("pinned"), "/src/web\n\n/src/api\n").unwrap();
+
+        let mut list = PinList::load(open(dir.path())).unwrap();
+        assert_eq!(
+            list.entries(),
+            [PathBuf::from("/src/web"), PathBuf::from("/src/api")]
+        );
+        list.remove(Path::new("/src/web"));
+        list.save().unwrap();
+
+        let reloaded = PinList::load(open(dir.path())).unwrap();
+        assert_eq!(reloaded.entries(), [PathBuf::from("/src/api")]);
+        assert!(dir.path().join("pinned").exists(), "old file is kept");
+    }
+
+    #[test]
+    fn test_save_keeps_changes_saved_since_loading() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut list = PinList::load(open(dir.path())).unwrap();
+        list.add(Path::new("/src/api"));
+        list.add(Path::new("/src/web"));
+        list.save().unwrap();
+
+        // Two commands load the same list, then save in turn
+        let mut first = PinList::load(open(dir.path())).unwrap();
+        let mut second = PinList::load(open(dir.path())).unwrap();
+        first.add(Path::new("/src/cli"));
+        first.save().unwrap();
+        second.remove(Path::new("/src/api"));
+        second.add(Path::new("/src/docs"));
+        second.save().unwrap();
+
+        let reloaded = PinList::load(open(dir.path())).unwrap();
+        assert_eq!(
+            reloaded.entries(),
+            ["/src/web", "/src/cli", "/src/docs"].map(PathBuf::from)
+        );
+    }
+
+    #[test]
+    fn test_remove_missing() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut list = PinList::load(open(dir.path())).unwrap();
+        list.add(Path::new("/nonexistent/api"));
+        list.add(dir.path());
+
+        assert_eq!(
+            list.remove_missing(),
+            vec![PathBuf::from("/nonexistent/api")]
+        );
+        assert_eq!(list.entries(), [normalize(dir.path())]);
+    }
+
+    #[test]
+    fn test_float_moves_pins_first_in_pin_order() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut list = PinList::load(open(dir.path())).unwrap();
+        list.add(Path::new("/src/web"));
+        list.add(Path::new("/src/api"));
+
+        let repos = ["/src/api", "/src/cli", "/src/docs", "/src/web"]
+            .iter()
+            .map(|p| GitRepo::new(PathBuf::from(p)))
+            .collect();
+        let names: Vec<String> = list.float(repos).into_iter().map(|r| r.name).collect();
+        assert_eq!(names, vec!["web", "api", "cli", "docs"]);
+    }
+}
@@ -1,11 +1,15 @@
 use anyhow::{Context, Result};
 use chrono::{DateTime, Local};
 use git2::Repository;
-use std::path::Path;
+use serde::Serialize;
+use std::path::{Path, PathBuf};
 
 use crate::config::PreviewConfig;
+use crate::github;
+use crate::manifest::{self, Manifest};
 use crate::output;
 use crate::scanner::{detect_project_type, ProjectType};
+use crate::template::{Part, Placeholder, Template};
 
 /// Generate a colored preview of a git repository.
 ///
@@ -16,6 +20,7 @@ use crate::scanner::{detect_project_type, ProjectType};
 ///
 /// * `repo_path` - Path to the git repository
 /// * `config` - Preview configuration controlling what information to display
+/// * `forge` - Cached data from the repository's forge to show, if any
 ///
 /// # Returns
 ///
@@ -25,181 +30,1198 @@ use crate::scanner::{detect_project_type, ProjectType};
 /// # Errors
 ///
 /// Returns an error if the repository cannot be opened or accessed
-#[allow(dead_code)]
-pub fn generate_preview<P: AsRef<Path>>(repo_path: P, config: &PreviewConfig) -> Result<String> {
+pub fn generate_preview<P: AsRef<Path>>(
+    repo_path: P,
+    config: &PreviewConfig,
+    forge: ForgeInfo,
+) -> Result<String> {
     let use_color = output::should_use_color();
-    generate_preview_internal(repo_path, config, use_color)
+    generate_preview_internal(repo_path, config, forge, use_color)
 }
 
 pub fn generate_preview_colored<P: AsRef<Path>>(
     repo_path: P,
     config: &PreviewConfig,
+    forge: ForgeInfo,
 ) -> Result<String> {
     let use_color = std::env::var("NO_COLOR").is_err();
-    generate_preview_internal(repo_path, config, use_color)
+    generate_preview_internal(repo_path, config, forge, use_color)
+}
+
+/// Structured repository summary shown in the preview pane.
+///
+/// Sections disabled in `PreviewConfig` are left empty and omitted from JSON output.
+#[derive(Debug, Clone, Serialize)]
+pub struct RepoSummary {
+    pub name: String,
+    pub path: PathBuf,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub project_type: Option<ProjectType>,
+    /// Package name, version, and language from the project manifest
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub manifest: Option<Manifest>,
+    /// The remote's description from its forge, when `forge.descriptions` is on
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    /// Stars, open pull requests, and CI status, when `forge.github_stats` is on
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub github: Option<github::Stats>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub branch: Option<String>,
+    pub detached: bool,
+    /// Merge, rebase, or other operation left unfinished in the working tree
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub operation: Option<String>,
+    /// Upstream of the current branch, as of the last fetch
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tracking: Option<Tracking>,
+    /// Most recent tag reachable from HEAD
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tag: Option<LatestTag>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_activity: Option<Activity>,
+    /// Commits per week over the last [`ACTIVITY_WEEKS`] weeks, oldest first
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub commit_activity: Option<Vec<usize>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub identity: Option<Identity>,
+    /// Every local branch, most recently committed first
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub branches: Option<Vec<BranchSummary>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub remotes: Option<Vec<RemoteSummary>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub status: Option<StatusCounts>,
+    /// Why the status was not collected, e.g. "repo too large"
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub status_skipped: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub diffstat: Option<DiffStat>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub recent_commits: Option<Vec<CommitSummary>>,
+}
+
+/// What the preview shows from the repository's forge, read from the cache by
+/// the caller so rendering never waits on the network.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ForgeInfo {
+    pub description: Option<String>,
+    pub github: Option<github::Stats>,
+}
+
+impl ForgeInfo {
+    /// Fill in the forge fields of `summary`.
+    pub fn apply(self, summary: &mut RepoSummary) {
+        summary.description = self.description;
+        summary.github = self.github;
+    }
+}
+
+/// Timestamp of the most recent commit on HEAD.
+#[derive(Debug, Clone, Serialize)]
+pub struct Activity {
+    /// Unix timestamp in seconds
+    pub timestamp: i64,
+    /// Human-readable relative time (e.g. "3 days ago")
+    pub relative: String,
+    /// Timestamp formatted with `preview.date_format`
+    pub formatted: String,
+}
+
+/// How the current branch compares with its upstream.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Tracking {
+    /// Upstream branch name (e.g. "origin/main")
+    pub upstream: String,
+    /// Local commits not on the upstream
+    pub ahead: usize,
+    /// Upstream commits not merged locally
+    pub behind: usize,
+}
+
+/// Weeks of history the commit activity sparkline covers.
+pub const ACTIVITY_WEEKS: usize = 13;
+
+/// Branches listed in the preview; the rest are summed up as `… N more`.
+const BRANCHES_SHOWN: usize = 5;
+
+/// Most commits the activity sparkline walks, so huge histories stay fast.
+const ACTIVITY_MAX_COMMITS: usize = 10_000;
+
+/// The most recent tag reachable from HEAD, as `git describe --tags` finds it.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct LatestTag {
+    pub name: String,
+    /// Commits on HEAD since the tag (0 when HEAD is tagged)
+    pub distance: usize,
+}
+
+/// The committer identity git would use in this repository.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Identity {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub email: Option<String>,
+    /// Whether `user.email` is set in the repository's own `.git/config`
+    /// rather than inherited from the global config
+    pub local: bool,
+}
+
+/// A local branch and when its tip was committed.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct BranchSummary {
+    pub name: String,
+    /// Whether the branch is checked out
+    pub current: bool,
+    /// Unix timestamp of the tip commit
+    pub timestamp: i64,
+    /// Human-readable relative time (e.g. "3 weeks ago")
+    pub relative: String,
+}
+
+/// A configured remote and its fetch URL.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct RemoteSummary {
+    pub name: String,
+    pub url: String,
+}
+
+/// Working tree status counts.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct StatusCounts {
+    pub staged: usize,
+    pub unstaged: usize,
+    pub untracked: usize,
+}
+
+/// Size of the uncommitted changes (staged and unstaged) against HEAD.
+///
+/// Untracked files are not counted, as with `git diff HEAD --stat`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct DiffStat {
+    pub files_changed: usize,
+    pub insertions: usize,
+    pub deletions: usize,
+}
+
+/// A single entry of the recent commits section.
+#[derive(Debug, Clone, Serialize)]
+pub struct CommitSummary {
+    pub id: String,
+    pub subject: String,
+}
+
+/// A section `preview.template` can place, e.g. `{branch}`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PreviewField {
+    Name,
+    Path,
+    /// Package name, version, and language, or the project type
+    Project,
+    Description,
+    /// `★ 1.2k · 3 open PRs · CI ✓`, empty unless `forge.github_stats` is on
+    Github,
+    /// `⚠ rebase in progress`, empty when nothing is in progress
+    Operation,
+    Branch,
+    /// `↑2 ↓5 origin/main`, empty without an upstream
+    AheadBehind,
+    Tag,
+    LastActivity,
+    /// Sparkline of commits per week, e.g. `▁▁▃█▅▂▁▁▄▆▇▃▂ 42 commits in 13 weeks`
+    CommitActivity,
+    Identity,
+    /// One `name relative-time` line per local branch, newest first
+    Branches,
+    /// One `name url` line per remote
+    Remotes,
+    /// `+1 staged ~2 unstaged ?3 untracked`, or `clean`
+    Status,
+    Diffstat,
+    /// One `id subject` line per commit, up to `recent_commits`
+    RecentCommits,
+}
+
+impl Placeholder for PreviewField {
+    const ALL: &'static [(&'static str, Self)] = &[
+        ("name", PreviewField::Name),
+        ("path", PreviewField::Path),
+        ("project", PreviewField::Project),
+        ("description", PreviewField::Description),
+        ("github", PreviewField::Github),
+        ("operation", PreviewField::Operation),
+        ("branch", PreviewField::Branch),
+        ("ahead_behind", PreviewField::AheadBehind),
+        ("tag", PreviewField::Tag),
+        ("last_activity", PreviewField::LastActivity),
+        ("commit_activity", PreviewField::CommitActivity),
+        ("identity", PreviewField::Identity),
+        ("branches", PreviewField::Branches),
+        ("remotes", PreviewField::Remotes),
+        ("status", PreviewField::Status),
+        ("diffstat", PreviewField::Diffstat),
+        ("recent_commits", PreviewField::RecentCommits),
+    ];
+}
+
+/// Generate the preview as plain text (no ANSI escapes), regardless of TTY.
+pub fn generate_preview_plain<P: AsRef<Path>>(
+    repo_path: P,
+    config: &PreviewConfig,
+    forge: ForgeInfo,
+) -> Result<String> {
+    generate_preview_internal(repo_path, config, forge, false)
 }
 
 /// Internal implementation of generate_preview with color control.
 fn generate_preview_internal<P: AsRef<Path>>(
     repo_path: P,
     config: &PreviewConfig,
+    forge: ForgeInfo,
     use_color: bool,
 ) -> Result<String> {
+    if let Some(ref template) = config.template {
+        let template = Template::parse(template).context("Invalid preview.template")?;
+        let mut summary = collect_summary(repo_path, &template_config(config, &template))?;
+        forge.apply(&mut summary);
+        return Ok(render_template(&template, &summary, config, use_color));
+    }
+
+    let mut summary = collect_summary(repo_path, config)?;
+    forge.apply(&mut summary);
+    Ok(render_summary(&summary, config, use_color, pane_width()))
+}
+
+/// Collect the sections the preview shows: those `preview.template` uses
+/// when one is set, otherwise those turned on by the show_* toggles.
+///
+/// # Errors
+///
+/// Returns an error if the template is invalid or the repository cannot be opened
+pub fn collect_preview_summary<P: AsRef<Path>>(
+    repo_path: P,
+    config: &PreviewConfig,
+) -> Result<RepoSummary> {
+    match config.template {
+        Some(ref template) => {
+            let template = Template::parse(template).context("Invalid preview.template")?;
+            collect_summary(repo_path, &template_config(config, &template))
+        }
+        None => collect_summary(repo_path, config),
+    }
+}
+
+/// Turn on exactly the sections `template` uses, whatever the show_* toggles say.
+fn template_config(config: &PreviewConfig, template: &Template<PreviewField>) -> PreviewConfig {
+    let uses = |field| template.uses(field);
+    PreviewConfig {
+        show_branch: uses(PreviewField::Branch) || uses(PreviewField::AheadBehind),
+        show_last_activity: uses(PreviewField::LastActivity),
+        show_status: uses(PreviewField::Status),
+        recent_commits: if uses(PreviewField::RecentCommits) {
+            config.recent_commits
+        } else {
+            0
+        },
+        show_remotes: uses(PreviewField::Remotes),
+        show_diffstat: uses(PreviewField::Diffstat),
+        show_tags: uses(PreviewField::Tag),
+        show_identity: uses(PreviewField::Identity),
+        show_commit_activity: uses(PreviewField::CommitActivity),
+        show_branches: uses(PreviewField::Branches),
+        ..config.clone()
+    }
+}
+
+/// Width of the fzf preview pane, if running inside one.
+///
+/// fzf exports `FZF_PREVIEW_COLUMNS` to preview commands; outside fzf nothing
+/// is truncated by width.
+fn pane_width() -> Option<usize> {
+    std::env::var("FZF_PREVIEW_COLUMNS")
+        .ok()
+        .and_then(|v| v.trim().parse().ok())
+        .filter(|&w: &usize| w > 0)
+}
+
+/// Inspect a repository and collect the data displayed in the preview.
+///
+/// # Errors
+///
+/// Returns an error if the repository cannot be opened
+pub fn collect_summary<P: AsRef<Path>>(
+    repo_path: P,
+    config: &PreviewConfig,
+) -> Result<RepoSummary> {
     let repo_path = repo_path.as_ref();
     let repo = Repository::open(repo_path)
         .with_context(|| format!("Failed to open repository: {}", repo_path.display()))?;
 
-    let mut output = Vec::new();
+    let name = repo_path
+        .file_name()
+        .and_then(|n| n.to_str())
+        .unwrap_or("unknown")
+        .to_string();
+
+    let project_type = Some(detect_project_type(repo_path)).filter(|t| *t != ProjectType::Unknown);
+
+    let mut summary = RepoSummary {
+        name,
+        path: repo_path.to_path_buf(),
+        project_type,
+        manifest: manifest::detect(repo_path),
+        description: None,
+        github: None,
+        branch: None,
+        detached: false,
+        operation: in_progress_operation(&repo).map(str::to_string),
+        tracking: None,
+        tag: None,
+        last_activity: None,
+        commit_activity: None,
+        identity: None,
+        branches: None,
+        remotes: None,
+        status: None,
+        status_skipped: None,
+        diffstat: None,
+        recent_commits: None,
+    };
 
-    // Helper function to apply color codes conditionally
-    let colorize = |text: &str, color: &str| -> String {
-        if use_color {
-            format!("{}{}\x1b[0m", color, text)
-        } else {
-            text.to_string()
+    // Branch information
+    if config.show_branch {
+        if let Ok(head) = repo.head() {
+            if head.is_branch() {
+                summary.branch = Some(head.shorthand().unwrap_or("unknown").to_string());
+                summary.tracking = crate::status::divergence(repo_path).map(|d| Tracking {
+                    upstream: d.upstream,
+                    ahead: d.ahead,
+                    behind: d.behind,
+                });
+            } else {
+                summary.detached = true;
+            }
+        }
+    }
+
+    // Latest tag and how far HEAD has moved past it
+    if config.show_tags {
+        summary.tag = latest_tag(&repo);
+    }
+
+    // Last activity (most recent commit)
+    if config.show_last_activity {
+        if let Ok(head) = repo.head() {
+            if let Ok(commit) = head.peel_to_commit() {
+                let time = commit.time();
+                let dt = DateTime::<Local>::from(
+                    std::time::UNIX_EPOCH + std::time::Duration::from_secs(time.seconds() as u64),
+                );
+
+                let duration = Local::now().signed_duration_since(dt);
+                summary.last_activity = Some(Activity {
+                    timestamp: time.seconds(),
+                    relative: format_duration(duration),
+                    formatted: dt.format(&config.date_format).to_string(),
+                });
+            }
+        }
+    }
+
+    // Commits per week, to tell active repositories from dormant ones
+    if config.show_commit_activity {
+        summary.commit_activity = Some(weekly_commits(&repo, Local::now().timestamp()));
+    }
+
+    // Committer identity, with repository config overriding global config
+    if config.show_identity {
+        if let Ok(git_config) = repo.config() {
+            let local = git_config
+                .open_level(git2::ConfigLevel::Local)
+                .is_ok_and(|c| c.get_string("user.email").is_ok());
+            summary.identity = Some(Identity {
+                name: git_config.get_string("user.name").ok(),
+                email: git_config.get_string("user.email").ok(),
+                local,
+            });
+        }
+    }
+
+    // Local branches, to spot forgotten feature branches
+    if config.show_branches {
+        summary.branches = Some(local_branches(&repo));
+    }
+
+    // Remotes, in git's (alphabetical) order
+    if config.show_remotes {
+        let remotes = repo
+            .remotes()
+            .map(|names| {
+                names
+                    .iter()
+                    .flatten()
+                    .filter_map(|name| {
+                        let remote = repo.find_remote(name).ok()?;
+                        Some(RemoteSummary {
+                            name: name.to_string(),
+                            url: remote.url().unwrap_or("").to_string(),
+                        })
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+        summary.remotes = Some(remotes);
+    }
+
+    // Huge repositories: skip the working tree scans rather than hang the preview
+    if (config.show_status || config.show_diffstat)
+        && config.status_max_files > 0
+        && repo
+            .index()
+            .is_ok_and(|index| index.len() > config.status_max_files)
+    {
+        summary.status_skipped = Some("repo too large".to_string());
+    }
+
+    // Status information
+    if config.show_status && summary.status_skipped.is_none() {
+        match count_status(repo_path, config.status_timeout_ms) {
+            Counted::Done(counts) => summary.status = Some(counts),
+            Counted::Failed => {}
+            Counted::TimedOut => {
+                summary.status_skipped = Some(format!("took over {} ms", config.status_timeout_ms));
+            }
+        }
+    }
+
+    // Diffstat of the working tree and index against HEAD
+    if config.show_diffstat && summary.status_skipped.is_none() {
+        let head = repo.head().ok().and_then(|h| h.peel_to_tree().ok());
+        if let Ok(stats) = repo
+            .diff_tree_to_workdir_with_index(head.as_ref(), None)
+            .and_then(|diff| diff.stats())
+        {
+            summary.diffstat = Some(DiffStat {
+                files_changed: stats.files_changed(),
+                insertions: stats.insertions(),
+                deletions: stats.deletions(),
+            });
         }
+    }
+
+    // Recent commits
+    if config.recent_commits > 0 {
+        let mut commits = Vec::new();
+        if let Ok(mut revwalk) = repo.revwalk() {
+            revwalk.push_head().ok();
+            commits = revwalk
+                .take(config.recent_commits)
+                .filter_map(|oid| oid.ok())
+                .filter_map(|oid| repo.find_commit(oid).ok())
+                .map(|commit| CommitSummary {
+                    id: commit.id().to_string()[..7].to_string(),
+                    subject: commit
+                        .message()
+                        .unwrap_or("")
+                        .lines()
+                        .next()
+                        .unwrap_or("")
+                        .to_string(),
+                })
+                .collect();
+        }
+        summary.recent_commits = Some(commits);
+    }
+
+    Ok(summary)
+}
+
+/// Outcome of [`count_status`].
+enum Counted {
+    Done(StatusCounts),
+    Failed,
+    TimedOut,
+}
+
+/// Count staged, unstaged, and untracked files, giving up after `timeout_ms`
+/// (0 = no limit).
+///
+/// The count runs on its own thread with its own handle on the repository. On
+/// timeout it is abandoned and finishes in the background, which for the
+/// short-lived preview process means until it exits.
+fn count_status(repo_path: &Path, timeout_ms: u64) -> Counted {
+    let count = |path: &Path| {
+        let repo = Repository::open(path).ok()?;
+        let statuses = repo.statuses(None).ok()?;
+        let mut counts = StatusCounts::default();
+        for entry in statuses.iter() {
+            let status = entry.status();
+            if status.is_index_new() || status.is_index_modified() || status.is_index_deleted() {
+                counts.staged += 1;
+            }
+            if status.is_wt_modified() || status.is_wt_deleted() {
+                counts.unstaged += 1;
+            }
+            if status.is_wt_new() {
+                counts.untracked += 1;
+            }
+        }
+        Some(counts)
+    };
+
+    if timeout_ms == 0 {
+        return count(repo_path).map_or(Counted::Failed, Counted::Done);
+    }
+    let (tx, rx) = std::sync::mpsc::channel();
+    let path = repo_path.to_path_buf();
+    std::thread::spawn(move || {
+        let _ = tx.send(count(&path));
+    });
+    match rx.recv_timeout(std::time::Duration::from_millis(timeout_ms)) {
+        Ok(Some(counts)) => Counted::Done(counts),
+        Ok(None) => Counted::Failed,
+        Err(_) => Counted::TimedOut,
+    }
+}
+
+/// List local branches, most recently committed first.
+fn local_branches(repo: &Repository) -> Vec<BranchSummary> {
+    let Ok(branches) = repo.branches(Some(git2::BranchType::Local)) else {
+        return Vec::new();
+    };
+    let now = Local::now().timestamp();
+    let mut branches: Vec<BranchSummary> = branches
+        .flatten()
+        .filter_map(|(branch, _)| {
+            let name = branch.name().ok()??.to_string();
+            let timestamp = branch.get().peel_to_commit().ok()?.time().seconds();
+            Some(BranchSummary {
+                name,
+                current: branch.is_head(),
+                timestamp,
+                relative: format_duration(chrono::Duration::seconds(now - timestamp)),
+            })
+        })
+        .collect();
+    branches.sort_by(|a, b| b.timestamp.cmp(&a.timestamp).then(a.name.cmp(&b.name)));
+    branches
+}
+
+/// Count the commits on HEAD in each of the [`ACTIVITY_WEEKS`] weeks before
+/// `now` (a Unix timestamp), oldest week first.
+///
+/// The walk goes newest first and stops at the first commit older than the
+/// window, or after [`ACTIVITY_MAX_COMMITS`] commits.
+fn weekly_commits(repo: &Repository, now: i64) -> Vec<usize> {
+    const WEEK: i64 = 7 * 24 * 60 * 60;
+    let mut weeks = vec![0; ACTIVITY_WEEKS];
+    let Ok(mut revwalk) = repo.revwalk() else {
+        return weeks;
     };
+    if revwalk.push_head().is_err() || revwalk.set_sorting(git2::Sort::TIME).is_err() {
+        return weeks;
+    }
+    for oid in revwalk
+        .take(ACTIVITY_MAX_COMMITS)
+        .filter_map(|oid| oid.ok())
+    {
+        let Ok(commit) = repo.find_commit(oid) else {
+            continue;
+        };
+        // Weeks back from now; commits dated in the future count as this week
+        let age = (now - commit.time().seconds()).max(0) / WEEK;
+        if age >= ACTIVITY_WEEKS as i64 {
+            break;
+        }
+        weeks[ACTIVITY_WEEKS - 1 - age as usize] += 1;
+    }
+    weeks
+}
+
+/// Name the operation in progress, from the state files git leaves in `.git`
+/// (`MERGE_HEAD`, `rebase-merge/`, `rebase-apply/`, `BISECT_LOG`, ...).
+fn in_progress_operation(repo: &Repository) -> Option<&'static str> {
+    use git2::RepositoryState;
+
+    match repo.state() {
+        RepositoryState::Clean => None,
+        RepositoryState::Merge => Some("merge"),
+        RepositoryState::Revert | RepositoryState::RevertSequence => Some("revert"),
+        RepositoryState::CherryPick | RepositoryState::CherryPickSequence => Some("cherry-pick"),
+        RepositoryState::Bisect => Some("bisect"),
+        RepositoryState::Rebase
+        | RepositoryState::RebaseInteractive
+        | RepositoryState::RebaseMerge
+        | RepositoryState::ApplyMailboxOrRebase => Some("rebase"),
+        RepositoryState::ApplyMailbox => Some("am"),
+    }
+}
+
+/// Find the most recent tag reachable from HEAD, lightweight tags included.
+fn latest_tag(repo: &Repository) -> Option<LatestTag> {
+    let describe = repo
+        .describe(git2::DescribeOptions::new().describe_tags())
+        .ok()?;
+    // An abbreviation size of 0 prints the bare tag name
+    let name = describe
+        .format(Some(git2::DescribeFormatOptions::new().abbreviated_size(0)))
+        .ok()?;
+    let head = repo.head().ok()?.peel_to_commit().ok()?.id();
+    let tagged = repo
+        .revparse_single(&name)
+        .ok()?
+        .peel_to_commit()
+        .ok()?
+        .id();
+    let (distance, _) = repo.graph_ahead_behind(head, tagged).ok()?;
+    Some(LatestTag { name, distance })
+}
+
+/// Render a collected summary as preview text.
+///
+/// With a `width`, long values are shortened to fit: paths keep their last
+/// components (`…/work/api`) and branch names and commit subjects are cut at the
+/// end. `config.max_lines` then caps the height, dropping recent commits first.
+pub fn render_summary(
+    summary: &RepoSummary,
+    config: &PreviewConfig,
+    use_color: bool,
+    width: Option<usize>,
+) -> String {
+    let mut output = Vec::new();
+    // Room left on a line after a label of `used` columns
+    let room = |used: usize| width.map(|w| w.saturating_sub(used));
+
+    let colorize = |text: &str, color: &str| paint(text, color, use_color);
 
     // Repository name and location
-    let name = repo_path
-        .file_name()
-        .and_then(|n| n.to_str())
-        .unwrap_or("unknown");
     output.push(format!(
         "{} {}",
         colorize("Repository:", "\x1b[1;36m"),
-        name
+        summary.name
     ));
     output.push(format!(
         "{} {}",
         colorize("Location:", "\x1b[1;36m"),
-        repo_path.display()
+        truncate_start(
+            &summary.path.display().to_string(),
+            room("Location: ".len())
+        )
     ));
 
-    // Project type
-    let project_type = detect_project_type(repo_path);
-    if project_type != ProjectType::Unknown {
+    // Project type, or the package it builds when the manifest names one
+    if let Some(ref manifest) = summary.manifest {
+        let language = format!(" ({})", manifest.language);
+        let package = package_text(manifest);
+        output.push(format!(
+            "{} {}{}",
+            colorize("Project:", "\x1b[1;36m"),
+            truncate_end(&package, room("Project: ".len() + language.chars().count())),
+            language
+        ));
+    } else if let Some(ref project_type) = summary.project_type {
         output.push(format!(
             "{} {}",
             colorize("Project:", "\x1b[1;36m"),
             project_type.badge_text()
         ));
     }
+    if let Some(ref description) = summary.description {
+        output.push(format!(
+            "{} {}",
+            colorize("About:", "\x1b[1;36m"),
+            truncate_end(description, room("About: ".len()))
+        ));
+    }
+    if let Some(ref github) = summary.github {
+        output.push(format!(
+            "{} {}",
+            colorize("GitHub:", "\x1b[1;36m"),
+            truncate_end(&github.summary(), room("GitHub: ".len()))
+        ));
+    }
 
     output.push(String::new());
 
+    // Unfinished merge/rebase/bisect, above everything else that can be cut
+    if let Some(ref operation) = summary.operation {
+        output.push(colorize(
+            &format!("⚠ {} in progress", operation),
+            "\x1b[1;31m",
+        ));
+        output.push(String::new());
+    }
+
     // Branch information
-    if config.show_branch {
-        if let Ok(head) = repo.head() {
-            let branch_name = if head.is_branch() {
-                head.shorthand().unwrap_or("unknown")
-            } else {
-                "(detached HEAD)"
-            };
-            output.push(format!(
-                "{} {}",
-                colorize("Branch:", "\x1b[1;33m"),
-                branch_name
-            ));
-        }
+    if summary.branch.is_some() || summary.detached {
+        let branch_name = summary.branch.as_deref().unwrap_or("(detached HEAD)");
+        // `↑2 ↓5 origin/main`, or `= origin/main` when in sync
+        let (tracking, tracking_width) = match summary.tracking {
+            Some(ref t) => {
+                let (text, width) = tracking_text(t, use_color);
+                (format!(" {}", text), width + 1)
+            }
+            None => (String::new(), 0),
+        };
+        output.push(format!(
+            "{} {}{}",
+            colorize("Branch:", "\x1b[1;33m"),
+            truncate_end(branch_name, room("Branch: ".len() + tracking_width)),
+            tracking
+        ));
+    }
+
+    // Latest tag, e.g. `v1.4.2 + 17 commits`
+    if let Some(ref tag) = summary.tag {
+        let distance = tag_distance_text(tag);
+        output.push(format!(
+            "{} {}{}",
+            colorize("Tag:", "\x1b[1;33m"),
+            truncate_end(&tag.name, room("Tag: ".len() + distance.chars().count())),
+            distance
+        ));
     }
 
     // Last activity (most recent commit)
-    if config.show_last_activity {
-        if let Ok(head) = repo.head() {
-            if let Ok(commit) = head.peel_to_commit() {
-                let time = commit.time();
-                let dt = DateTime::<Local>::from(
-                    std::time::UNIX_EPOCH + std::time::Duration::from_secs(time.seconds() as u64),
-                );
+    if let Some(ref activity) = summary.last_activity {
+        output.push(format!(
+            "{} {} ({})",
+            colorize("Last Activity:", "\x1b[1;35m"),
+            activity.relative,
+            activity.formatted
+        ));
+    }
 
-                // Relative time
-                let now = Local::now();
-                let duration = now.signed_duration_since(dt);
-                let relative = format_duration(duration);
+    // Commits per week, e.g. `Activity: ▁▁▃█▅▂▁▁▄▆▇▃▂ 42 commits in 13 weeks`
+    if let Some(ref weeks) = summary.commit_activity {
+        output.push(format!(
+            "{} {}",
+            colorize("Activity:", "\x1b[1;35m"),
+            activity_text(weeks, use_color)
+        ));
+    }
+    if config.show_last_activity || config.show_commit_activity {
+        output.push(String::new());
+    }
 
-                // Absolute time
-                let absolute = dt.format(&config.date_format).to_string();
+    // Committer identity, e.g. `Jane Doe <jane@corp.com> (repo)`
+    if let Some(ref identity) = summary.identity {
+        let who = identity_text(identity);
+        let scope = if identity.local { " (repo)" } else { "" };
+        output.push(format!(
+            "{} {}{}",
+            colorize("Identity:", "\x1b[1;34m"),
+            truncate_end(&who, room("Identity: ".len() + scope.len())),
+            scope
+        ));
+        output.push(String::new());
+    }
 
-                output.push(format!(
-                    "{} {} ({})",
-                    colorize("Last Activity:", "\x1b[1;35m"),
-                    relative,
-                    absolute
-                ));
-            }
+    // Local branches, newest first; the current one is marked `*`
+    if let Some(ref branches) = summary.branches {
+        output.push(colorize("Branches:", "\x1b[1;33m"));
+        if branches.is_empty() {
+            output.push("  (none)".to_string());
+        }
+        let shown = &branches[..branches.len().min(BRANCHES_SHOWN)];
+        let relative_width = shown.iter().map(|b| b.relative.len()).max().unwrap_or(0);
+        // Names are cut to leave room for the `* ` marker and the time column
+        let name_room = room(relative_width + 3);
+        let name_width = shown
+            .iter()
+            .map(|b| b.name.chars().count())
+            .max()
+            .unwrap_or(0)
+            .min(name_room.unwrap_or(usize::MAX));
+        for branch in shown {
+            output.push(format!(
+                "{} {:<width$} {}",
+                if branch.current { "*" } else { " " },
+                truncate_end(&branch.name, name_room),
+                branch.relative,
+                width = name_width
+            ));
+        }
+        if branches.len() > shown.len() {
+            output.push(format!("  … {} more", branches.len() - shown.len()));
         }
         output.push(String::new());
     }
 
-    // Status information
-    if config.show_status {
-        if let Ok(statuses) = repo.statuses(None) {
-            let mut staged = 0;
-            let mut unstaged = 0;
-            let mut untracked = 0;
-
-            for entry in statuses.iter() {
-                let status = entry.status();
-                if status.is_index_new() || status.is_index_modified() || status.is_index_deleted()
-                {
-                    staged += 1;
-                }
-                if status.is_wt_modified() || status.is_wt_deleted() {
-                    unstaged += 1;
-                }
-                if status.is_wt_new() {
-                    untracked += 1;
-                }
-            }
+    // Remotes
+    if let Some(ref remotes) = summary.remotes {
+        output.push(colorize("Remotes:", "\x1b[1;34m"));
+        if remotes.is_empty() {
+            output.push("  (none)".to_string());
+        }
+        let name_width = remotes
+            .iter()
+            .map(|r| r.name.chars().count())
+            .max()
+            .unwrap_or(0);
+        for remote in remotes {
+            output.push(format!(
+                "  {:<width$} {}",
+                remote.name,
+                truncate_start(&remote.url, room(name_width + 3)),
+                width = name_width
+            ));
+        }
+        output.push(String::new());
+    }
 
-            output.push(colorize("Status:", "\x1b[1;35m"));
-            if staged > 0 || unstaged > 0 || untracked > 0 {
-                if staged > 0 {
-                    output.push(format!(
-                        "  {}",
-                        colorize(&format!("+{} staged", staged), "\x1b[32m")
-                    ));
-                }
-                if unstaged > 0 {
-                    output.push(format!(
-                        "  {}",
-                        colorize(&format!("~{} unstaged", unstaged), "\x1b[33m")
-                    ));
-                }
-                if untracked > 0 {
-                    output.push(format!(
-                        "  {}",
-                        colorize(&format!("?{} untracked", untracked), "\x1b[31m")
-                    ));
-                }
-            } else {
-                output.push("  Clean working tree".to_string());
-            }
-            output.push(String::new());
+    // Status information
+    if let Some(ref reason) = summary.status_skipped {
+        output.push(colorize("Status:", "\x1b[1;35m"));
+        output.push(format!("  status skipped ({})", reason));
+        output.push(String::new());
+    }
+    if let Some(ref counts) = summary.status {
+        output.push(colorize("Status:", "\x1b[1;35m"));
+        let parts = status_parts(counts, use_color);
+        if parts.is_empty() {
+            output.push("  Clean working tree".to_string());
         }
+        for part in parts {
+            output.push(format!("  {}", part));
+        }
+        output.push(String::new());
+    }
+
+    // Diffstat, only when there is something uncommitted
+    if let Some(stat) = summary.diffstat.as_ref().filter(|s| s.files_changed > 0) {
+        output.push(format!(
+            "{} {}",
+            colorize("Changes:", "\x1b[1;35m"),
+            diffstat_text(stat, use_color)
+        ));
+        output.push(String::new());
     }
 
     // Recent commits
-    if config.recent_commits > 0 {
+    let mut commit_lines = Vec::new();
+    if let Some(ref commits) = summary.recent_commits {
         output.push(colorize("Recent commits:", "\x1b[1;32m"));
-        if let Ok(mut revwalk) = repo.revwalk() {
-            revwalk.push_head().ok();
-            let commits: Vec<_> = revwalk
-                .take(config.recent_commits)
-                .filter_map(|oid| oid.ok())
-                .filter_map(|oid| repo.find_commit(oid).ok())
-                .collect();
+        for commit in commits {
+            commit_lines.push(format!(
+                "  {} {}",
+                colorize(&commit.id, "\x1b[33m"),
+                truncate_end(&commit.subject, room(commit.id.chars().count() + 3))
+            ));
+        }
+    }
+
+    // Fit the height: drop commits first, then cut whatever is left
+    let max_lines = config.max_lines;
+    if max_lines > output.len() && output.len() + commit_lines.len() > max_lines {
+        let keep = max_lines - output.len() - 1;
+        let omitted = commit_lines.len().saturating_sub(keep);
+        if omitted > 0 {
+            commit_lines.truncate(keep);
+            commit_lines.push(format!(
+                "  … {} more {}",
+                omitted,
+                if omitted == 1 { "commit" } else { "commits" }
+            ));
+        }
+    }
+    output.extend(commit_lines);
+    if max_lines > 0 && output.len() > max_lines {
+        let omitted = output.len() - (max_lines - 1);
+        output.truncate(max_lines - 1);
+        output.push(format!("… {} more lines", omitted));
+    }
+
+    output.join("\n")
+}
+
+/// Render a collected summary through `preview.template`.
+///
+/// Placeholders for multi-line sections (`{remotes}`, `{recent_commits}`)
+/// expand to several lines. A line made only of placeholders that came out
+/// empty is dropped, so `{operation}` on its own line costs nothing when no
+/// operation is in progress. `config.max_lines` caps the height.
+pub fn render_template(
+    template: &Template<PreviewField>,
+    summary: &RepoSummary,
+    config: &PreviewConfig,
+    use_color: bool,
+) -> String {
+    // Each line, and whether it holds a placeholder
+    let mut lines = vec![(String::new(), false)];
+    let mut push = |text: &str, field: bool| {
+        for (i, piece) in text.split('\n').enumerate() {
+            if i > 0 {
+                lines.push((String::new(), false));
+            }
+            let line = lines.last_mut().expect("lines is never empty");
+            line.0.push_str(piece);
+            line.1 |= field;
+        }
+    };
+    for part in template.parts() {
+        match part {
+            Part::Text(text) => push(text, false),
+            Part::Field(field) => push(&template_field(*field, summary, use_color), true),
+        }
+    }
+
+    let mut output: Vec<String> = lines
+        .into_iter()
+        .filter(|(line, field)| !(*field && line.trim().is_empty()))
+        .map(|(line, _)| line)
+        .collect();
+    let max_lines = config.max_lines;
+    if max_lines > 0 && output.len() > max_lines {
+        let omitted = output.len() - (max_lines - 1);
+        output.truncate(max_lines - 1);
+        output.push(format!("… {} more lines", omitted));
+    }
+    output.join("\n")
+}
 
-            for commit in commits {
-                let short_id = &commit.id().to_string()[..7];
-                let message = commit.message().unwrap_or("").lines().next().unwrap_or("");
-                output.push(format!("  {} {}", colorize(short_id, "\x1b[33m"), message));
+/// The text a template placeholder stands for; empty when there is nothing to show.
+fn template_field(field: PreviewField, summary: &RepoSummary, use_color: bool) -> String {
+    match field {
+        PreviewField::Name => summary.name.clone(),
+        PreviewField::Path => summary.path.display().to_string(),
+        PreviewField::Project => match (&summary.manifest, &summary.project_type) {
+            (Some(manifest), _) => format!("{} ({})", package_text(manifest), manifest.language),
+            (None, Some(project_type)) => project_type.badge_text().to_string(),
+            (None, None) => String::new(),
+        },
+        PreviewField::Description => summary.description.clone().unwrap_or_default(),
+        PreviewField::Github => summary
+            .github
+            .as_ref()
+            .map(github::Stats::summary)
+            .unwrap_or_default(),
+        PreviewField::Operation => summary
+            .operation
+            .as_ref()
+            .map(|op| paint(&format!("⚠ {} in progress", op), "\x1b[1;31m", use_color))
+            .unwrap_or_default(),
+        PreviewField::Branch => match summary.branch {
+            Some(ref branch) => branch.clone(),
+            None if summary.detached => "(detached HEAD)".to_string(),
+            None => String::new(),
+        },
+        PreviewField::AheadBehind => summary
+            .tracking
+            .as_ref()
+            .map(|t| tracking_text(t, use_color).0)
+            .unwrap_or_default(),
+        PreviewField::Tag => summary
+            .tag
+            .as_ref()
+            .map(|tag| format!("{}{}", tag.name, tag_distance_text(tag)))
+            .unwrap_or_default(),
+        PreviewField::LastActivity => summary
+            .last_activity
+            .as_ref()
+            .map(|a| format!("{} ({})", a.relative, a.formatted))
+            .unwrap_or_default(),
+        PreviewField::CommitActivity => summary
+            .commit_activity
+            .as_ref()
+            .map(|weeks| activity_text(weeks, use_color))
+            .unwrap_or_default(),
+        PreviewField::Identity => summary
+            .identity
+            .as_ref()
+            .map(|identity| {
+                let scope = if identity.local { " (repo)" } else { "" };
+                format!("{}{}", identity_text(identity), scope)
+            })
+            .unwrap_or_default(),
+        PreviewField::Branches => summary
+            .branches
+            .iter()
+            .flatten()
+            .take(BRANCHES_SHOWN)
+            .map(|b| format!("{} {}", b.name, b.relative))
+            .collect::<Vec<_>>()
+            .join("\n"),
+        PreviewField::Remotes => summary
+            .remotes
+            .iter()
+            .flatten()
+            .map(|r| format!("{} {}", r.name, r.url))
+            .collect::<Vec<_>>()
+            .join("\n"),
+        PreviewField::Status => match summary.status {
+            None if summary.status_skipped.is_some() => format!(
+                "status skipped ({})",
+                summary.status_skipped.as_deref().unwrap_or_default()
+            ),
+            Some(ref counts) => {
+                let parts = status_parts(counts, use_color);
+                if parts.is_empty() {
+                    "clean".to_string()
+                } else {
+                    parts.join(" ")
+                }
             }
+            None => String::new(),
+        },
+        PreviewField::Diffstat => summary
+            .diffstat
+            .as_ref()
+            .filter(|s| s.files_changed > 0)
+            .map(|s| diffstat_text(s, use_color))
+            .unwrap_or_default(),
+        PreviewField::RecentCommits => summary
+            .recent_commits
+            .iter()
+            .flatten()
+            .map(|c| format!("{} {}", paint(&c.id, "\x1b[33m", use_color), c.subject))
+            .collect::<Vec<_>>()
+            .join("\n"),
+    }
+}
+
+/// Wrap `text` in an ANSI color code when `use_color` is set.
+fn paint(text: &str, color: &str, use_color: bool) -> String {
+    if use_color {
+        format!("{}{}\x1b[0m", color, text)
+    } else {
+        text.to_string()
+    }
+}
+
+/// Package name and version from a manifest, e.g. `gitnav 0.2.0`.
+fn package_text(manifest: &Manifest) -> String {
+    match manifest.version {
+        Some(ref version) => format!("{} {}", manifest.name, version),
+        None => manifest.name.clone(),
+    }
+}
+
+/// `↑2 ↓5 origin/main`, or `= origin/main` when in sync, with its width in columns.
+fn tracking_text(tracking: &Tracking, use_color: bool) -> (String, usize) {
+    let mut parts = Vec::new();
+    let mut plain = Vec::new();
+    if tracking.ahead > 0 {
+        let text = format!("↑{}", tracking.ahead);
+        parts.push(paint(&text, "\x1b[32m", use_color));
+        plain.push(text);
+    }
+    if tracking.behind > 0 {
+        let text = format!("↓{}", tracking.behind);
+        parts.push(paint(&text, "\x1b[31m", use_color));
+        plain.push(text);
+    }
+    if parts.is_empty() {
+        parts.push("=".to_string());
+        plain.push("=".to_string());
+    }
+    parts.push(tracking.upstream.clone());
+    plain.push(tracking.upstream.clone());
+    (parts.join(" "), plain.join(" ").chars().count())
+}
+
+/// ` + 17 commits` after a tag name; empty when HEAD is the tagged commit.
+fn tag_distance_text(tag: &LatestTag) -> String {
+    match tag.distance {
+        0 => String::new(),
+        1 => " + 1 commit".to_string(),
+        n => format!(" + {} commits", n),
+    }
+}
+
+/// A sparkline of weekly commit counts followed by the total, e.g.
+/// `▁▁▃█▅▂▁▁▄▆▇▃▂ 42 commits in 13 weeks`.
+///
+/// Bars are scaled to the busiest week; `▁` marks a week without commits.
+fn activity_text(weeks: &[usize], use_color: bool) -> String {
+    const BARS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+    let max = weeks.iter().copied().max().unwrap_or(0);
+    let sparkline: String = weeks
+        .iter()
+        .map(|&n| match n {
+            0 => BARS[0],
+            // 1..=max onto the seven bars above the empty one
+            n => BARS[1 + (n - 1) * 6 / max.saturating_sub(1).max(1)],
+        })
+        .collect();
+    let total: usize = weeks.iter().sum();
+    format!(
+        "{} {} {} in {} weeks",
+        paint(&sparkline, "\x1b[32m", use_color),
+        total,
+        if total == 1 { "commit" } else { "commits" },
+        weeks.len()
+    )
+}
+
+/// `Jane Doe <jane@corp.com>`, noting whichever half is unset.
+fn identity_text(identity: &Identity) -> String {
+    match (&identity.name, &identity.email) {
+        (Some(name), Some(email)) => format!("{} <{}>", name, email),
+        (Some(name), None) => format!("{} (no email)", name),
+        (None, Some(email)) => format!("<{}>", email),
+        (None, None) => "(not set)".to_string(),
+    }
+}
+
+/// Non-zero status counts (`+1 staged`, `~2 unstaged`, `?3 untracked`); empty when clean.
+fn status_parts(counts: &StatusCounts, use_color: bool) -> Vec<String> {
+    [
+        (counts.staged, "+", "staged", "\x1b[32m"),
+        (counts.unstaged, "~", "unstaged", "\x1b[33m"),
+        (counts.untracked, "?", "untracked", "\x1b[31m"),
+    ]
+    .iter()
+    .filter(|(n, ..)| *n > 0)
+    .map(|(n, sign, label, color)| paint(&format!("{}{} {}", sign, n, label), color, use_color))
+    .collect()
+}
+
+/// `4 files changed, +120 -14`.
+fn diffstat_text(stat: &DiffStat, use_color: bool) -> String {
+    format!(
+        "{} {} changed, {} {}",
+        stat.files_changed,
+        if stat.files_changed == 1 {
+            "file"
+        } else {
+            "files"
+        },
+        paint(&format!("+{}", stat.insertions), "\x1b[32m", use_color),
+        paint(&format!("-{}", stat.deletions), "\x1b[31m", use_color)
+    )
+}
+
+/// Shorten `text` to `max` characters by cutting the end, marking the cut with `…`.
+fn truncate_end(text: &str, max: Option<usize>) -> String {
+    match max {
+        Some(max) if text.chars().count() > max => {
+            let kept: String = text.chars().take(max.saturating_sub(1)).collect();
+            format!("{}…", kept)
         }
+        _ => text.to_string(),
+    }
+}
+
+/// Shorten a path to `max` characters by cutting the start, keeping whole
+/// trailing components where possible (`…/work/api`).
+fn truncate_start(path: &str, max: Option<usize>) -> String {
+    let Some(max) = max else {
+        return path.to_string();
+    };
+    let len = path.chars().count();
+    if len <= max {
+        return path.to_string();
     }
 
-    Ok(output.join("\n"))
+    let tail: String = path.chars().skip(len - max.saturating_sub(1)).collect();
+    // Start at a separator so the first component shown is complete
+    let tail = match tail.find(['/', '\\']) {
+        Some(i) if i > 0 => &tail[i..],
+        _ => &tail[..],
+    };
+    format!("…{}", tail)
 }
 
 /// Format a duration into human-readable relative time.
@@ -214,7 +1236,7 @@ fn generate_preview_internal<P: AsRef<Path>>(
 /// # Returns
 ///
 /// A formatted string describing the duration in human-readable terms
-fn format_duration(duration: chrono::Duration) -> String {
+pub fn format_duration(duration: chrono::Duration) -> String {
     let seconds = duration.num_seconds().abs();
 
     if seconds < 60 {
@@ -237,6 +1259,7 @@ fn format_duration(duration: chrono::Duration) -> String {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::test_util::commit;
 
     #[test]
     fn test_format_duration_seconds() {
@@ -301,6 +1324,17 @@ mod tests {
             show_status: true,
             recent_commits: 5,
             date_format: "%Y-%m-%d %H:%M".to_string(),
+            max_lines: 0,
+            show_remotes: false,
+            show_diffstat: false,
+            show_tags: false,
+            show_identity: false,
+            show_commit_activity: false,
+            show_branches: false,
+            template: None,
+            status_max_files: 0,
+            status_timeout_ms: 0,
+            cache_ttl_seconds: 0,
         };
 
         assert!(config.show_branch);
@@ -317,6 +1351,17 @@ mod tests {
             show_status: false,
             recent_commits: 0,
             date_format: "%Y-%m-%d".to_string(),
+            max_lines: 0,
+            show_remotes: false,
+            show_diffstat: false,
+            show_tags: false,
+            show_identity: false,
+            show_commit_activity: false,
+            show_branches: false,
+            template: None,
+            status_max_files: 0,
+            status_timeout_ms: 0,
+            cache_ttl_seconds: 0,
         };
 
         assert!(!config.show_branch);
@@ -411,6 +1456,17 @@ mod tests {
             show_status: true,
             recent_commits: 10,
             date_format: "%Y-%m-%d".to_string(),
+            max_lines: 0,
+            show_remotes: false,
+            show_diffstat: false,
+            show_tags: false,
+            show_identity: false,
+            show_commit_activity: false,
+            show_branches: false,
+            template: None,
+            status_max_files: 0,
+            status_timeout_ms: 0,
+            cache_ttl_seconds: 0,
         };
 
         // Verify all fields are accessible
@@ -429,6 +1485,17 @@ mod tests {
             show_status: false,
             recent_commits: 5,
             date_format: "%Y-%m-%d %H:%M".to_string(),
+            max_lines: 0,
+            show_remotes: false,
+            show_diffstat: false,
+            show_tags: false,
+            show_identity: false,
+            show_commit_activity: false,
+            show_branches: false,
+            template: None,
+            status_max_files: 0,
+            status_timeout_ms: 0,
+            cache_ttl_seconds: 0,
         };
 
         let config2 = config1.clone();
@@ -515,6 +1582,17 @@ mod tests {
             show_status: true,
             recent_commits: 0,
             date_format: "%Y-%m-%d".to_string(),
+            max_lines: 0,
+            show_remotes: false,
+            show_diffstat: false,
+            show_tags: false,
+            show_identity: false,
+            show_commit_activity: false,
+            show_branches: false,
+            template: None,
+            status_max_files: 0,
+            status_timeout_ms: 0,
+            cache_ttl_seconds: 0,
         };
 
         assert_eq!(config.recent_commits, 0);
@@ -528,6 +1606,17 @@ mod tests {
             show_status: true,
             recent_commits: 1000,
             date_format: "%Y-%m-%d".to_string(),
+            max_lines: 0,
+            show_remotes: false,
+            show_diffstat: false,
+            show_tags: false,
+            show_identity: false,
+            show_commit_activity: false,
+            show_branches: false,
+            template: None,
+            status_max_files: 0,
+            status_timeout_ms: 0,
+            cache_ttl_seconds: 0,
         };
 
         assert_eq!(config.recent_commits, 1000);
@@ -544,6 +1633,17 @@ mod tests {
                 show_status: true,
                 recent_commits: 5,
                 date_format: format.to_string(),
+                max_lines: 0,
+                show_remotes: false,
+                show_diffstat: false,
+                show_tags: false,
+                show_identity: false,
+                show_commit_activity: false,
+                show_branches: false,
+                template: None,
+                status_max_files: 0,
+                status_timeout_ms: 0,
+                cache_ttl_seconds: 0,
             };
 
             assert_eq!(config.date_format, format);
@@ -564,11 +1664,47 @@ mod tests {
             show_status: false,
             recent_commits: 0,
             date_format: "%Y-%m-%d".to_string(),
+            max_lines: 0,
+            show_remotes: false,
+            show_diffstat: false,
+            show_tags: false,
+            show_identity: false,
+            show_commit_activity: false,
+            show_branches: false,
+            template: None,
+            status_max_files: 0,
+            status_timeout_ms: 0,
+            cache_ttl_seconds: 0,
         };
 
-        let output = generate_preview_colored(dir.path(), &config).unwrap();
-        assert!(output.contains("rust"), "Expected 'rust' in preview: {}", output);
-        assert!(output.contains("Project:"), "Expected 'Project:' label: {}", output);
+        let output = generate_preview_colored(dir.path(), &config, ForgeInfo::default()).unwrap();
+        assert!(
+            output.contains("rust"),
+            "Expected 'rust' in preview: {}",
+            output
+        );
+        assert!(
+            output.contains("Project:"),
+            "Expected 'Project:' label: {}",
+            output
+        );
+    }
+
+    #[test]
+    fn test_preview_shows_package_from_manifest() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(
+            dir.path().join("Cargo.toml"),
+            "[package]\nname = \"gitnav\"\nversion = \"0.2.0\"\n",
+        )
+        .unwrap();
+        git2::Repository::init(dir.path()).unwrap();
+
+        let config = full_config();
+        let summary = collect_summary(dir.path(), &config).unwrap();
+        assert_eq!(summary.manifest.as_ref().unwrap().name, "gitnav");
+        let text = render_summary(&summary, &config, false, None);
+        assert!(text.contains("Project: gitnav 0.2.0 (Rust)"), "{}", text);
     }
 
     #[test]
@@ -582,9 +1718,563 @@ mod tests {
             show_status: false,
             recent_commits: 0,
             date_format: "%Y-%m-%d".to_string(),
+            max_lines: 0,
+            show_remotes: false,
+            show_diffstat: false,
+            show_tags: false,
+            show_identity: false,
+            show_commit_activity: false,
+            show_branches: false,
+            template: None,
+            status_max_files: 0,
+            status_timeout_ms: 0,
+            cache_ttl_seconds: 0,
+        };
+
+        let output = generate_preview_colored(dir.path(), &config, ForgeInfo::default()).unwrap();
+        assert!(
+            !output.contains("Project:"),
+            "Unknown type should not show Project label"
+        );
+    }
+
+    fn init_repo_with_commit(dir: &Path) -> Repository {
+        let repo = Repository::init(dir).unwrap();
+        std::fs::write(dir.join("file.txt"), "hello").unwrap();
+        {
+            let mut index = repo.index().unwrap();
+            index.add_path(Path::new("file.txt")).unwrap();
+            index.write().unwrap();
+        }
+        commit(&repo, "Initial commit", None);
+        repo
+    }
+
+    fn full_config() -> PreviewConfig {
+        PreviewConfig {
+            show_branch: true,
+            show_last_activity: true,
+            show_status: true,
+            recent_commits: 5,
+            date_format: "%Y-%m-%d".to_string(),
+            max_lines: 0,
+            show_remotes: false,
+            show_diffstat: false,
+            show_tags: false,
+            show_identity: false,
+            show_commit_activity: false,
+            show_branches: false,
+            template: None,
+            status_max_files: 0,
+            status_timeout_ms: 0,
+            cache_ttl_seconds: 0,
+        }
+    }
+
+    #[test]
+    fn test_collect_summary_reports_commits_and_status() {
+        let dir = tempfile::tempdir().unwrap();
+        init_repo_with_commit(dir.path());
+        std::fs::write(dir.path().join("new.txt"), "untracked").unwrap();
+
+        let summary = collect_summary(dir.path(), &full_config()).unwrap();
+        assert!(summary.branch.is_some());
+        assert!(!summary.detached);
+        assert!(summary.last_activity.is_some());
+        assert_eq!(summary.status.as_ref().unwrap().untracked, 1);
+        let commits = summary.recent_commits.unwrap();
+        assert_eq!(commits.len(), 1);
+        assert_eq!(commits[0].subject, "Initial commit");
+        assert_eq!(commits[0].id.len(), 7);
+    }
+
+    #[test]
+    fn test_summary_json_omits_disabled_sections() {
+        let dir = tempfile::tempdir().unwrap();
+        init_repo_with_commit(dir.path());
+
+        let config = PreviewConfig {
+            show_branch: false,
+            show_last_activity: false,
+            show_status: false,
+            recent_commits: 0,
+            date_format: "%Y-%m-%d".to_string(),
+            max_lines: 0,
+            show_remotes: false,
+            show_diffstat: false,
+            show_tags: false,
+            show_identity: false,
+            show_commit_activity: false,
+            show_branches: false,
+            template: None,
+            status_max_files: 0,
+            status_timeout_ms: 0,
+            cache_ttl_seconds: 0,
+        };
+        let summary = collect_summary(dir.path(), &config).unwrap();
+        let json = serde_json::to_value(&summary).unwrap();
+        assert!(json.get("name").is_some());
+        assert!(json.get("branch").is_none());
+        assert!(json.get("status").is_none());
+        assert!(json.get("recent_commits").is_none());
+    }
+
+    #[test]
+    fn test_plain_preview_has_no_ansi_codes() {
+        let dir = tempfile::tempdir().unwrap();
+        init_repo_with_commit(dir.path());
+
+        let output =
+            generate_preview_plain(dir.path(), &full_config(), ForgeInfo::default()).unwrap();
+        assert!(!output.contains('\x1b'));
+        assert!(output.contains("Initial commit"));
+        assert!(output.contains("Clean working tree"));
+    }
+
+    fn summary_with_commits(count: usize) -> RepoSummary {
+        RepoSummary {
+            name: "api".to_string(),
+            path: PathBuf::from("/home/user/work/clients/acme/api"),
+            project_type: None,
+            manifest: None,
+            description: Some("A service that answers questions about widgets".to_string()),
+            github: None,
+            branch: Some("feature/very-long-branch-name".to_string()),
+            detached: false,
+            operation: None,
+            tracking: None,
+            tag: None,
+            last_activity: None,
+            commit_activity: None,
+            identity: None,
+            branches: None,
+            remotes: None,
+            status: None,
+            status_skipped: None,
+            diffstat: None,
+            recent_commits: Some(
+                (0..count)
+                    .map(|i| CommitSummary {
+                        id: format!("abc{:04}", i),
+                        subject: format!("Commit number {} with a long subject line", i),
+                    })
+                    .collect(),
+            ),
+        }
+    }
+
+    #[test]
+    fn test_collect_summary_reports_tracking() {
+        let dir = tempfile::tempdir().unwrap();
+        let repo = init_repo_with_commit(dir.path());
+        let head = repo.head().unwrap();
+        let branch = head.shorthand().unwrap().to_string();
+        let base = head.target().unwrap();
+        repo.reference(
+            &format!("refs/remotes/origin/{}", branch),
+            base,
+            true,
+            "test",
+        )
+        .unwrap();
+        repo.remote("origin", "https://example.com/repo.git")
+            .unwrap();
+        repo.find_branch(&branch, git2::BranchType::Local)
+            .unwrap()
+            .set_upstream(Some(&format!("origin/{}", branch)))
+            .unwrap();
+        commit(&repo, "Local work", None);
+
+        let summary = collect_summary(dir.path(), &full_config()).unwrap();
+        assert_eq!(
+            summary.tracking,
+            Some(Tracking {
+                upstream: format!("origin/{}", branch),
+                ahead: 1,
+                behind: 0,
+            })
+        );
+    }
+
+    #[test]
+    fn test_render_tracking() {
+        let render = |ahead, behind| {
+            let mut summary = summary_with_commits(0);
+            summary.branch = Some("main".to_string());
+            summary.tracking = Some(Tracking {
+                upstream: "origin/main".to_string(),
+                ahead,
+                behind,
+            });
+            render_summary(&summary, &full_config(), false, None)
+        };
+        assert!(render(2, 5).contains("Branch: main ↑2 ↓5 origin/main"));
+        assert!(render(0, 3).contains("Branch: main ↓3 origin/main"));
+        assert!(render(0, 0).contains("Branch: main = origin/main"));
+    }
+
+    #[test]
+    fn test_collect_summary_lists_remotes() {
+        let dir = tempfile::tempdir().unwrap();
+        let repo = init_repo_with_commit(dir.path());
+        repo.remote("upstream", "https://github.com/acme/api.git")
+            .unwrap();
+        repo.remote("origin", "git@github.com:me/api.git").unwrap();
+
+        let mut config = full_config();
+        let summary = collect_summary(dir.path(), &config).unwrap();
+        assert!(summary.remotes.is_none(), "remotes are off by default");
+
+        config.show_remotes = true;
+        let summary = collect_summary(dir.path(), &config).unwrap();
+        let names: Vec<&str> = summary
+            .remotes
+            .as_ref()
+            .unwrap()
+            .iter()
+            .map(|r| r.name.as_str())
+            .collect();
+        assert_eq!(names, vec!["origin", "upstream"]);
+
+        let text = render_summary(&summary, &config, false, None);
+        assert!(text.contains("Remotes:\n  origin   git@github.com:me/api.git\n  upstream https://github.com/acme/api.git"));
+    }
+
+    #[test]
+    fn test_collect_summary_diffstat() {
+        let dir = tempfile::tempdir().unwrap();
+        init_repo_with_commit(dir.path());
+
+        let mut config = full_config();
+        config.show_diffstat = true;
+        let summary = collect_summary(dir.path(), &config).unwrap();
+        assert_eq!(summary.diffstat, Some(DiffStat::default()));
+        let text = render_summary(&summary, &config, false, None);
+        assert!(
+            !text.contains("Changes:"),
+            "clean trees have no diffstat line"
+        );
+
+        std::fs::write(dir.path().join("file.txt"), "hello\nworld\n").unwrap();
+        std::fs::write(dir.path().join("untracked.txt"), "new").unwrap();
+        let summary = collect_summary(dir.path(), &config).unwrap();
+        assert_eq!(
+            summary.diffstat,
+            Some(DiffStat {
+                files_changed: 1,
+                insertions: 2,
+                deletions: 1,
+            })
+        );
+        let text = render_summary(&summary, &config, false, None);
+        assert!(text.contains("Changes: 1 file changed, +2 -1"));
+    }
+
+    #[test]
+    fn test_collect_summary_latest_tag() {
+        let dir = tempfile::tempdir().unwrap();
+        let repo = init_repo_with_commit(dir.path());
+
+        let mut config = full_config();
+        config.show_tags = true;
+        let summary = collect_summary(dir.path(), &config).unwrap();
+        assert!(summary.tag.is_none(), "no tags yet");
+
+        let head = repo.head().unwrap().peel_to_commit().unwrap();
+        repo.tag_lightweight("v1.4.2", head.as_object(), false)
+            .unwrap();
+        let summary = collect_summary(dir.path(), &config).unwrap();
+        let text = render_summary(&summary, &config, false, None);
+        assert!(text.contains("Tag: v1.4.2\n"));
+
+        for _ in 0..2 {
+            commit(&repo, "More", None);
+        }
+        let summary = collect_summary(dir.path(), &config).unwrap();
+        assert_eq!(
+            summary.tag,
+            Some(LatestTag {
+                name: "v1.4.2".to_string(),
+                distance: 2,
+            })
+        );
+        let text = render_summary(&summary, &config, false, None);
+        assert!(text.contains("Tag: v1.4.2 + 2 commits"));
+    }
+
+    #[test]
+    fn test_collect_summary_lists_branches_newest_first() {
+        let dir = tempfile::tempdir().unwrap();
+        let repo = init_repo_with_commit(dir.path());
+        let head = repo.head().unwrap().peel_to_commit().unwrap();
+        let now = Local::now().timestamp();
+        let day = 24 * 60 * 60;
+        // Seven branches whose tips are 1..=7 weeks old
+        for weeks in 1..=7 {
+            let time = git2::Time::new(now - weeks * 7 * day, 0);
+            let sig = git2::Signature::new("Test", "test@example.com", &time).unwrap();
+            let tree = head.tree().unwrap();
+            let id = repo
+                .commit(None, &sig, &sig, "wip", &tree, &[&head])
+                .unwrap();
+            repo.branch(
+                &format!("feature/{}", weeks),
+                &repo.find_commit(id).unwrap(),
+                false,
+            )
+            .unwrap();
+        }
+
+        let config = PreviewConfig {
+            show_branches: true,
+            ..full_config()
+        };
+        let summary = collect_summary(dir.path(), &config).unwrap();
+        let branches = summary.branches.as_ref().unwrap();
+        assert_eq!(branches.len(), 8);
+        assert!(branches[0].current);
+        assert_eq!(branches[1].name, "feature/1");
+        assert_eq!(branches[1].relative, "1 weeks ago");
+        assert_eq!(branches[7].name, "feature/7");
+
+        let text = render_summary(&summary, &config, false, None);
+        let lines: Vec<&str> = text.lines().collect();
+        let start = lines.iter().position(|l| *l == "Branches:").unwrap();
+        assert!(lines[start + 1].starts_with("* "));
+        assert_eq!(lines[start + 2], "  feature/1 1 weeks ago");
+        assert_eq!(lines[start + 6], "  … 3 more");
+    }
+
+    #[test]
+    fn test_weekly_commits_and_sparkline() {
+        let dir = tempfile::tempdir().unwrap();
+        let repo = init_repo_with_commit(dir.path());
+        let now = Local::now().timestamp();
+        let day = 24 * 60 * 60;
+        // Three commits this week, one five weeks back, one a year back
+        for days_ago in [400, 35, 2, 1, 0] {
+            commit(&repo, "work", Some(now - days_ago * day));
+        }
+
+        let weeks = weekly_commits(&repo, now);
+        assert_eq!(weeks.len(), ACTIVITY_WEEKS);
+        // The initial commit was made just now too
+        assert_eq!(weeks[ACTIVITY_WEEKS - 1], 4);
+        assert_eq!(weeks[ACTIVITY_WEEKS - 6], 1);
+        assert_eq!(weeks.iter().sum::<usize>(), 5);
+
+        assert_eq!(
+            activity_text(&[0, 1, 4, 0], false),
+            "▁▂█▁ 5 commits in 4 weeks"
+        );
+        assert_eq!(activity_text(&[0, 1], false), "▁▂ 1 commit in 2 weeks");
+
+        let config = PreviewConfig {
+            show_commit_activity: true,
+            ..full_config()
+        };
+        let text = generate_preview_plain(dir.path(), &config, ForgeInfo::default()).unwrap();
+        assert!(text.contains("Activity: ▁▁▁▁▁▁▁▂▁▁▁▁█ 5 commits in 13 weeks"));
+    }
+
+    #[test]
+    fn test_preview_flags_operation_in_progress() {
+        let dir = tempfile::tempdir().unwrap();
+        init_repo_with_commit(dir.path());
+        let config = full_config();
+
+        let summary = collect_summary(dir.path(), &config).unwrap();
+        assert_eq!(summary.operation, None);
+
+        let git_dir = dir.path().join(".git");
+        std::fs::create_dir(git_dir.join("rebase-merge")).unwrap();
+        let summary = collect_summary(dir.path(), &config).unwrap();
+        assert_eq!(summary.operation.as_deref(), Some("rebase"));
+        let text = render_summary(&summary, &config, false, None);
+        assert!(text.contains("⚠ rebase in progress"), "{}", text);
+
+        std::fs::remove_dir(git_dir.join("rebase-merge")).unwrap();
+        std::fs::write(git_dir.join("BISECT_LOG"), "").unwrap();
+        let summary = collect_summary(dir.path(), &config).unwrap();
+        assert_eq!(summary.operation.as_deref(), Some("bisect"));
+    }
+
+    #[test]
+    fn test_collect_summary_identity_prefers_repo_config() {
+        let dir = tempfile::tempdir().unwrap();
+        let repo = init_repo_with_commit(dir.path());
+        let mut git_config = repo.config().unwrap();
+        git_config.set_str("user.name", "Jane Doe").unwrap();
+        git_config.set_str("user.email", "jane@corp.com").unwrap();
+
+        let mut config = full_config();
+        let summary = collect_summary(dir.path(), &config).unwrap();
+        assert!(summary.identity.is_none(), "identity is off by default");
+
+        config.show_identity = true;
+        let summary = collect_summary(dir.path(), &config).unwrap();
+        assert_eq!(
+            summary.identity,
+            Some(Identity {
+                name: Some("Jane Doe".to_string()),
+                email: Some("jane@corp.com".to_string()),
+                local: true,
+            })
+        );
+        let text = render_summary(&summary, &config, false, None);
+        assert!(text.contains("Identity: Jane Doe <jane@corp.com> (repo)"));
+    }
+
+    #[test]
+    fn test_render_template() {
+        let dir = tempfile::tempdir().unwrap();
+        init_repo_with_commit(dir.path());
+        std::fs::write(dir.path().join("new.txt"), "x").unwrap();
+
+        // Sections follow the template, not the show_* toggles
+        let config = PreviewConfig {
+            show_branch: false,
+            show_status: false,
+            recent_commits: 3,
+            template: Some(
+                "{name} | {branch}{ahead_behind}\n{operation}\nStatus: {status}\n--\n{recent_commits}"
+                    .to_string(),
+            ),
+            ..full_config()
         };
+        let text = generate_preview_plain(dir.path(), &config, ForgeInfo::default()).unwrap();
+        let name = dir.path().file_name().unwrap().to_str().unwrap();
+        let branch = Repository::open(dir.path())
+            .unwrap()
+            .head()
+            .unwrap()
+            .shorthand()
+            .unwrap()
+            .to_string();
+        let lines: Vec<&str> = text.lines().collect();
+        assert_eq!(lines[0], format!("{} | {}", name, branch));
+        // The empty {operation} line is dropped
+        assert_eq!(lines[1], "Status: ?1 untracked");
+        assert_eq!(lines[2], "--");
+        assert!(lines[3].ends_with(" Initial commit"));
+        assert_eq!(lines.len(), 4);
+
+        // JSON output carries what the template shows
+        let summary = collect_preview_summary(dir.path(), &config).unwrap();
+        assert!(summary.status.is_some());
+        assert!(summary.last_activity.is_none());
+
+        let config = PreviewConfig {
+            template: Some("{nmae}".to_string()),
+            ..full_config()
+        };
+        assert!(generate_preview_plain(dir.path(), &config, ForgeInfo::default()).is_err());
+    }
+
+    #[test]
+    fn test_status_skipped_in_large_repos() {
+        let dir = tempfile::tempdir().unwrap();
+        let repo = init_repo_with_commit(dir.path());
+        std::fs::write(dir.path().join("other.txt"), "x").unwrap();
+        let mut index = repo.index().unwrap();
+        index.add_path(Path::new("other.txt")).unwrap();
+        index.write().unwrap();
+
+        let mut config = full_config();
+        config.show_diffstat = true;
+        config.status_timeout_ms = 5000;
+        let summary = collect_summary(dir.path(), &config).unwrap();
+        assert_eq!(summary.status.as_ref().unwrap().staged, 1);
+        assert!(summary.status_skipped.is_none());
+
+        // Two files in the index, one allowed
+        config.status_max_files = 1;
+        let summary = collect_summary(dir.path(), &config).unwrap();
+        assert!(summary.status.is_none());
+        assert!(summary.diffstat.is_none());
+        assert_eq!(summary.status_skipped.as_deref(), Some("repo too large"));
+        let text = render_summary(&summary, &config, false, None);
+        assert!(text.contains("Status:\n  status skipped (repo too large)"));
+    }
 
-        let output = generate_preview_colored(dir.path(), &config).unwrap();
-        assert!(!output.contains("Project:"), "Unknown type should not show Project label");
+    #[test]
+    fn test_truncate_end() {
+        assert_eq!(truncate_end("short", Some(10)), "short");
+        assert_eq!(truncate_end("exactly10!", Some(10)), "exactly10!");
+        assert_eq!(truncate_end("a long subject", Some(8)), "a long …");
+        assert_eq!(truncate_end("anything", None), "anything");
+    }
+
+    #[test]
+    fn test_truncate_start_keeps_whole_components() {
+        assert_eq!(truncate_start("/a/b", Some(10)), "/a/b");
+        assert_eq!(
+            truncate_start("/home/user/work/clients/acme/api", Some(12)),
+            "…/acme/api"
+        );
+        assert_eq!(
+            truncate_start("/x/averyveryverylongname", Some(8)),
+            "…ongname"
+        );
+    }
+
+    #[test]
+    fn test_render_fits_width() {
+        let mut config = full_config();
+        config.show_last_activity = false;
+        let text = render_summary(&summary_with_commits(2), &config, false, Some(30));
+        for line in text.lines() {
+            assert!(line.chars().count() <= 30, "too wide: {:?}", line);
+        }
+        assert!(text.contains("Location: …/clients/acme/api"));
+        assert!(text.contains("Branch: feature/very-long-bra…"));
+        assert!(text.contains("About: A service that answers…"));
+    }
+
+    #[test]
+    fn test_render_github_stats() {
+        let mut summary = summary_with_commits(0);
+        let config = full_config();
+        assert!(!render_summary(&summary, &config, false, None).contains("GitHub:"));
+
+        summary.github = Some(github::Stats {
+            stars: 42,
+            open_pulls: 3,
+            ci: Some(github::CiStatus::Success),
+        });
+        let text = render_summary(&summary, &config, false, None);
+        assert!(text.contains("About: A service that answers questions about widgets\nGitHub: ★ 42 · 3 open PRs · CI ✓"));
+
+        let template = Template::parse("{name}: {github}").unwrap();
+        assert_eq!(
+            render_template(&template, &summary, &config, false),
+            "api: ★ 42 · 3 open PRs · CI ✓"
+        );
+    }
+
+    #[test]
+    fn test_render_max_lines_drops_commits_first() {
+        let mut config = full_config();
+        config.show_last_activity = false;
+        config.max_lines = 9;
+        let text = render_summary(&summary_with_commits(10), &config, false, None);
+        let lines: Vec<&str> = text.lines().collect();
+        assert_eq!(lines.len(), 9);
+        assert_eq!(
+            lines[6],
+            "  abc0000 Commit number 0 with a long subject line"
+        );
+        assert_eq!(lines[8], "  … 8 more commits");
+    }
+
+    #[test]
+    fn test_render_max_lines_cuts_remaining_lines() {
+        let mut config = full_config();
+        config.show_last_activity = false;
+        config.max_lines = 3;
+        let text = render_summary(&summary_with_commits(4), &config, false, None);
+        let lines: Vec<&str> = text.lines().collect();
+        assert_eq!(lines.len(), 3);
+        assert_eq!(lines[2], "… 8 more lines");
     }
 }
@@ -0,0 +1,200 @@
+use anyhow::{Context, Result};
+use sha2::{Digest, Sha256};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+use crate::config::PreviewConfig;
+use crate::preview::ForgeInfo;
+
+/// Rendered previews, one file per repository state under `<cache dir>/previews`.
+///
+/// fzf re-runs the preview command every time the cursor lands on a
+/// repository, so scrolling back and forth regenerates the same preview many
+/// times. Entries are keyed by the repository's HEAD and index modification
+/// time, so a commit, checkout, or `git add` misses the cache at once; changes
+/// git cannot see without walking the tree (edited files, a fetch) show up
+/// once the short TTL runs out.
+#[derive(Debug, Clone)]
+pub struct PreviewCache {
+    dir: PathBuf,
+    ttl: Duration,
+}
+
+impl PreviewCache {
+    pub fn new(cache_dir: &Path, ttl: Duration) -> Self {
+        Self {
+            dir: cache_dir.join("previews"),
+            ttl,
+        }
+    }
+
+    /// Cache key for the preview of `repo_path` rendered with these inputs.
+    ///
+    /// Returns `None` when the repository cannot be opened, so the preview is
+    /// rendered (and reports the error) without touching the cache.
+    pub fn key(
+        repo_path: &Path,
+        config: &PreviewConfig,
+        forge: &ForgeInfo,
+        use_color: bool,
+    ) -> Option<String> {
+        let repo = git2::Repository::open(repo_path).ok()?;
+        let head = repo.head().ok();
+        let index_mtime = fs::metadata(repo.path().join("index"))
+            .and_then(|m| m.modified())
+            .ok()
+            .and_then(|t| t.duration_since(SystemTime::UNIX_EPOCH).ok())
+            .map(|d| d.as_nanos());
+
+        let mut hasher = Sha256::new();
+        hasher.update(repo_path.to_string_lossy().as_bytes());
+        hasher.update([0]);
+        hasher.update(
+            head.as_ref()
+                .and_then(|h| h.name())
+                .unwrap_or("")
+                .as_bytes(),
+        );
+        hasher.update([0]);
+        hasher.update(
+            head.and_then(|h| h.target())
+                .map(|oid| oid.to_string())
+                .unwrap_or_default()
+                .as_bytes(),
+        );
+        hasher.update([0]);
+        hasher.update(index_mtime.unwrap_or_default().to_le_bytes());
+        // Anything else that changes the rendered text
+        hasher.update(serde_json::to_string(config).ok()?.as_bytes());
+        hasher.update([0]);
+        hasher.update(serde_json::to_string(forge).ok()?.as_bytes());
+        hasher.update([0, u8::from(use_color)]);
+        hasher.update(
+            std::env::var("FZF_PREVIEW_COLUMNS")
+                .unwrap_or_default()
+                .as_bytes(),
+        );
+        let hash = format!("{:x}", hasher.finalize());
+        Some(hash[..32].to_string())
+    }
+
+    fn entry_path(&self, key: &str, extension: &str) -> PathBuf {
+        self.dir.join(format!("preview_{}.{}", key, extension))
+    }
+
+    /// The cached preview for `key`, if it was rendered within the TTL.
+    pub fn get(&self, key: &str) -> Option<String> {
+        let path = self.entry_path(key, "cache");
+        if !is_fresh(&path, self.ttl) {
+            return None;
+        }
+        fs::read_to_string(path).ok()
+    }
+
+    /// Store the rendered preview for `key` and remove expired entries.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the entry cannot be written
+    pub fn put(&self, key: &str, preview: &str) -> Result<()> {
+        fs::create_dir_all(&self.dir)
+            .with_context(|| format!("Failed to create directory: {}", self.dir.display()))?;
+        self.remove_expired();
+
+        let path = self.entry_path(key, "cache");
+        let tmp = self.entry_path(key, "tmp");
+        fs::write(&tmp, preview).with_context(|| format!("Failed to write: {}", tmp.display()))?;
+        fs::rename(&tmp, &path).with_context(|| format!("Failed to write: {}", path.display()))?;
+        Ok(())
+    }
+
+    /// Delete entries past the TTL, so the directory holds only recent previews.
+    fn remove_expired(&self) {
+        let Ok(entries) = fs::read_dir(&self.dir) else {
+            return;
+        };
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if path.extension().is_some_and(|ext| ext == "cache") && !is_fresh(&path, self.ttl) {
+                let _ = fs::remove_file(path);
+            }
+        }
+    }
+}
+
+fn is_fresh(path: &Path, ttl: Duration) -> bool {
+    fs::metadata(path)
+        .and_then(|m| m.modified())
+        .ok()
+        .and_then(|modified| SystemTime::now().duration_since(modified).ok())
+        .is_some_and(|age| age < ttl)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Config;
+    use crate::github;
+    use crate::test_util::commit;
+
+    #[test]
+    fn test_key_changes_with_head_and_inputs() {
+        let dir = tempfile::tempdir().unwrap();
+        let repo = git2::Repository::init(dir.path()).unwrap();
+        commit(&repo, "first", None);
+        let config = Config::default().preview;
+
+        let none = ForgeInfo::default();
+        let key = PreviewCache::key(dir.path(), &config, &none, true).unwrap();
+        assert_eq!(
+            PreviewCache::key(dir.path(), &config, &none, true).as_deref(),
+            Some(key.as_str())
+        );
+        assert_ne!(
+            PreviewCache::key(dir.path(), &config, &none, false).unwrap(),
+            key
+        );
+        let described = ForgeInfo {
+            description: Some("A CLI".to_string()),
+            github: None,
+        };
+        assert_ne!(
+            PreviewCache::key(dir.path(), &config, &described, true).unwrap(),
+            key
+        );
+        let starred = ForgeInfo {
+            description: None,
+            github: Some(github::Stats {
+                stars: 3,
+                open_pulls: 0,
+                ci: None,
+            }),
+        };
+        assert_ne!(
+            PreviewCache::key(dir.path(), &config, &starred, true).unwrap(),
+            key
+        );
+
+        commit(&repo, "second", None);
+        assert_ne!(
+            PreviewCache::key(dir.path(), &config, &none, true).unwrap(),
+            key
+        );
+    }
+
+    #[test]
+    fn test_get_respects_ttl() {
+        let dir = tempfile::tempdir().unwrap();
+        let cache = PreviewCache::new(dir.path(), Duration::from_secs(60));
+        assert_eq!(cache.get("abc"), None);
+
+        cache.put("abc", "preview text").unwrap();
+        assert_eq!(cache.get("abc").as_deref(), Some("preview text"));
+
+        let expired = PreviewCache::new(dir.path(), Duration::ZERO);
+        assert_eq!(expired.get("abc"), None);
+        expired.put("def", "other").unwrap();
+        assert!(!cache.entry_path("abc", "cache").exists());
+    }
+}
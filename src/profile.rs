@@ -0,0 +1,72 @@
+use anyhow::Result;
+use std::path::{Path, PathBuf};
+
+/// Environment variable selecting the active profile when `--profile` is not given.
+pub const PROFILE_ENV: &str = "GITNAV_PROFILE";
+
+/// Resolve the active profile from `--profile` or `GITNAV_PROFILE`.
+///
+/// An empty value means "no profile". Names are limited to ASCII letters,
+/// digits, `-` and `_` because they become directory names.
+///
+/// # Errors
+///
+/// Returns an error if the profile name contains other characters
+pub fn resolve(flag: Option<&str>) -> Result<Option<String>> {
+    let name = match flag {
+        Some(name) => name.to_string(),
+        None => std::env::var(PROFILE_ENV).unwrap_or_default(),
+    };
+
+    if name.is_empty() {
+        return Ok(None);
+    }
+    if !name
+        .chars()
+        .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+    {
+        anyhow::bail!(
+            "Invalid profile name '{}': use only letters, digits, '-' and '_'",
+            name
+        );
+    }
+    Ok(Some(name))
+}
+
+/// Directory holding a profile's data under `base`.
+///
+/// Without a profile this is `base` itself, so existing caches and data files
+/// keep working; profile `work` lives in `base/profiles/work`.
+pub fn scoped_dir(base: &Path, profile: Option<&str>) -> PathBuf {
+    match profile {
+        Some(name) => base.join("profiles").join(name),
+        None => base.to_path_buf(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_resolve_validates_names() {
+        assert_eq!(resolve(Some("work")).unwrap(), Some("work".to_string()));
+        assert_eq!(
+            resolve(Some("side_project-2")).unwrap(),
+            Some("side_project-2".to_string())
+        );
+        assert_eq!(resolve(Some("")).unwrap(), None);
+        assert!(resolve(Some("../etc")).is_err());
+        assert!(resolve(Some("a b")).is_err());
+    }
+
+    #[test]
+    fn test_scoped_dir() {
+        let base = Path::new("/cache/gitnav");
+        assert_eq!(scoped_dir(base, None), PathBuf::from("/cache/gitnav"));
+        assert_eq!(
+            scoped_dir(base, Some("work")),
+            PathBuf::from("/cache/gitnav/profiles/work")
+        );
+    }
+}
@@ -0,0 +1,402 @@
+use anyhow::{Context, Result};
+use git2::{BranchType, Repository};
+use std::collections::BTreeSet;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+
+use crate::parallel;
+use crate::scanner::GitRepo;
+
+/// A local branch whose configured upstream no longer exists.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GoneBranch {
+    pub name: String,
+    /// Full name of the missing upstream ref, e.g. `refs/remotes/origin/feature`
+    pub upstream: String,
+    /// Commit the branch pointed at, so a deleted branch can be restored
+    pub tip: git2::Oid,
+    /// The branch is checked out and cannot be deleted
+    pub checked_out: bool,
+}
+
+impl GoneBranch {
+    fn short_tip(&self) -> String {
+        self.tip.to_string()[..7].to_string()
+    }
+}
+
+/// What `prune-gone` found (and did) in one repository.
+#[derive(Debug, Clone)]
+pub struct RepoPrune {
+    pub name: String,
+    pub path: PathBuf,
+    /// Remote-tracking refs removed by `git fetch --prune` (or that would be,
+    /// in a dry run)
+    pub pruned: Vec<String>,
+    pub gone: Vec<GoneBranch>,
+    pub fetch_failed: bool,
+}
+
+impl RepoPrune {
+    /// Gone branches that can be deleted (everything except the checked-out one).
+    pub fn deletable(&self) -> impl Iterator<Item = &GoneBranch> {
+        self.gone.iter().filter(|b| !b.checked_out)
+    }
+}
+
+/// Prune every repository's remote-tracking refs and find its gone branches, in parallel.
+///
+/// Fetches run non-interactively (`GIT_TERMINAL_PROMPT=0`). With `dry_run`,
+/// nothing is fetched or pruned: `git remote prune --dry-run` asks each remote
+/// which refs are gone, and branches tracking those count as gone too. Only
+/// repositories with something to report are returned.
+pub fn scan(repos: &[GitRepo], dry_run: bool) -> Vec<RepoPrune> {
+    parallel::filter_map(repos, parallel::default_workers(), |repo| {
+        let (pruned, fetch_failed) = if dry_run {
+            match would_prune(&repo.path) {
+                Some(pruned) => (pruned, false),
+                None => (BTreeSet::new(), true),
+            }
+        } else {
+            let before = remote_refs(&repo.path);
+            let fetch_failed = !fetch_prune(&repo.path);
+            let after = remote_refs(&repo.path);
+            (before.difference(&after).cloned().collect(), fetch_failed)
+        };
+        let gone = find_gone(&repo.path, &pruned).unwrap_or_default();
+
+        let result = RepoPrune {
+            name: repo.name.clone(),
+            path: repo.path.clone(),
+            pruned: pruned.into_iter().collect(),
+            gone,
+            fetch_failed,
+        };
+        (fetch_failed || !result.pruned.is_empty() || !result.gone.is_empty()).then_some(result)
+    })
+}
+
+fn fetch_prune(path: &Path) -> bool {
+    Command::new("git")
+        .arg("-C")
+        .arg(path)
+        .args(["fetch", "--all", "--prune", "--quiet"])
+        .env("GIT_TERMINAL_PROMPT", "0")
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status()
+        .is_ok_and(|s| s.success())
+}
+
+/// The remote-tracking branches `git remote prune` would remove, by short
+/// name, asking every remote without changing anything. `None` if a remote
+/// cannot be reached.
+fn would_prune(path: &Path) -> Option<BTreeSet<String>> {
+    let repo = Repository::open(path).ok()?;
+    let remotes = repo.remotes().ok()?;
+    let mut pruned = BTreeSet::new();
+    for remote in remotes.iter().flatten() {
+        let output = Command::new("git")
+            .arg("-C")
+            .arg(path)
+            .args(["remote", "prune", "--dry-run", remote])
+            .env("GIT_TERMINAL_PROMPT", "0")
+            // The output is parsed, so it must not be translated
+            .env("LC_ALL", "C")
+            .stdin(Stdio::null())
+            .stderr(Stdio::null())
+            .output()
+            .ok()
+            .filter(|output| output.status.success())?;
+        pruned.extend(
+            String::from_utf8_lossy(&output.stdout)
+                .lines()
+                .filter_map(|line| line.split_once("[would prune] "))
+                .map(|(_, name)| name.trim().to_string()),
+        );
+    }
+    Some(pruned)
+}
+
+/// Short names of all remote-tracking branches, e.g. `origin/feature`.
+fn remote_refs(path: &Path) -> BTreeSet<String> {
+    let Ok(repo) = Repository::open(path) else {
+        return BTreeSet::new();
+    };
+    let Ok(branches) = repo.branches(Some(BranchType::Remote)) else {
+        return BTreeSet::new();
+    };
+    branches
+        .flatten()
+        .filter_map(|(branch, _)| branch.name().ok().flatten().map(String::from))
+        .filter(|name| !name.ends_with("/HEAD"))
+        .collect()
+}
+
+/// Local branches that track an upstream that no longer exists.
+///
+/// Branches that never had an upstream are left alone.
+///
+/// # Errors
+///
+/// Returns an error if the repository or its branches cannot be read
+pub fn gone_branches(path: &Path) -> Result<Vec<GoneBranch>> {
+    find_gone(path, &BTreeSet::new())
+}
+
+/// [`gone_branches`], also counting upstreams in `pruned` (short names such
+/// as `origin/feature`) as gone.
+fn find_gone(path: &Path, pruned: &BTreeSet<String>) -> Result<Vec<GoneBranch>> {
+    let repo = Repository::open(path)
+        .with_context(|| format!("Failed to open repository: {}", path.display()))?;
+    let head = repo
+        .head()
+        .ok()
+        .and_then(|h| h.shorthand().map(String::from));
+
+    let mut gone = Vec::new();
+    for (branch, _) in repo.branches(Some(BranchType::Local))?.flatten() {
+        let reference = branch.get();
+        let (Some(refname), Some(tip)) = (reference.name(), reference.target()) else {
+            continue;
+        };
+        let Ok(upstream) = repo.branch_upstream_name(refname) else {
+            continue;
+        };
+        let Some(upstream) = upstream.as_str().map(String::from) else {
+            continue;
+        };
+        let pruning = upstream
+            .strip_prefix("refs/remotes/")
+            .is_some_and(|short| pruned.contains(short));
+        if !pruning && repo.find_reference(&upstream).is_ok() {
+            continue;
+        }
+
+        let name = branch.name()?.unwrap_or_default().to_string();
+        gone.push(GoneBranch {
+            checked_out: head.as_deref() == Some(name.as_str()),
+            name,
+            upstream,
+            tip,
+        });
+    }
+    gone.sort_by(|a, b| a.name.cmp(&b.name));
+    Ok(gone)
+}
+
+/// Delete the given branches, skipping the checked-out one.
+///
+/// Returns the names of the branches that were deleted.
+///
+/// # Errors
+///
+/// Returns an error if the repository cannot be opened or a branch cannot be deleted
+pub fn delete(path: &Path, branches: &[GoneBranch]) -> Result<Vec<String>> {
+    let repo = Repository::open(path)
+        .with_context(|| format!("Failed to open repository: {}", path.display()))?;
+    let mut deleted = Vec::new();
+    for gone in branches.iter().filter(|b| !b.checked_out) {
+        let mut branch = repo.find_branch(&gone.name, BranchType::Local)?;
+        branch
+            .delete()
+            .with_context(|| format!("Failed to delete branch {}", gone.name))?;
+        deleted.push(gone.name.clone());
+    }
+    Ok(deleted)
+}
+
+/// Format one block per repository listing pruned refs and (to be) deleted branches.
+///
+/// Deleted branches show their old tip so they can be restored with
+/// `git branch <name> <sha>`. Example:
+///
+/// ```text
+/// api
+///   pruned   origin/feature/login
+///   deleted  feature/login (was 1a2b3c4)
+///   kept     main (checked out; upstream is gone)
+/// ```
+pub fn format_summary(results: &[RepoPrune], dry_run: bool) -> String {
+    let (action, pruned_label) = if dry_run {
+        ("would delete", "would prune")
+    } else {
+        ("deleted", "pruned")
+    };
+    let width = action.len().max(pruned_label.len());
+    let mut out = String::new();
+
+    for result in results {
+        out.push_str(&result.name);
+        out.push('\n');
+        if result.fetch_failed {
+            out.push_str(&format!(
+                "  {:<w$}  fetch failed; using refs from the last fetch\n",
+                "warning",
+                w = width
+            ));
+        }
+        for pruned in &result.pruned {
+            out.push_str(&format!("  {:<w$}  {}\n", pruned_label, pruned, w = width));
+        }
+        for branch in &result.gone {
+            if branch.checked_out {
+                out.push_str(&format!(
+                    "  {:<w$}  {} (checked out; upstream is gone)\n",
+                    "kept",
+                    branch.name,
+                    w = width
+                ));
+            } else {
+                out.push_str(&format!(
+                    "  {:<w$}  {} (was {})\n",
+                    action,
+                    branch.name,
+                    branch.short_tip(),
+                    w = width
+                ));
+            }
+        }
+    }
+    out.trim_end().to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::commit;
+
+    /// Create `branch` tracking `origin/<branch>`, then remove the remote ref
+    /// when `gone` is set, as `git fetch --prune` would.
+    fn track(repo: &Repository, branch: &str, gone: bool) {
+        let tip = repo.head().unwrap().target().unwrap();
+        let remote_ref = format!("refs/remotes/origin/{}", branch);
+        repo.reference(&remote_ref, tip, true, "test").unwrap();
+        if repo.find_branch(branch, BranchType::Local).is_err() {
+            repo.branch(branch, &repo.find_commit(tip).unwrap(), false)
+                .unwrap();
+        }
+        repo.find_branch(branch, BranchType::Local)
+            .unwrap()
+            .set_upstream(Some(&format!("origin/{}", branch)))
+            .unwrap();
+        if gone {
+            repo.find_reference(&remote_ref).unwrap().delete().unwrap();
+        }
+    }
+
+    fn setup(dir: &Path) -> Repository {
+        let mut opts = git2::RepositoryInitOptions::new();
+        opts.initial_head("main");
+        let repo = Repository::init_opts(dir, &opts).unwrap();
+        commit(&repo, "base", None);
+        repo.remote("origin", "https://example.com/repo.git")
+            .unwrap();
+        repo
+    }
+
+    #[test]
+    fn test_gone_branches_only_reports_missing_upstreams() {
+        let tmp = tempfile::tempdir().unwrap();
+        let repo = setup(tmp.path());
+        track(&repo, "main", false);
+        track(&repo, "merged", true);
+        track(&repo, "active", false);
+        let head = repo.head().unwrap().peel_to_commit().unwrap();
+        repo.branch("local-only", &head, false).unwrap();
+
+        let gone = gone_branches(tmp.path()).unwrap();
+        assert_eq!(gone.len(), 1);
+        assert_eq!(gone[0].name, "merged");
+        assert_eq!(gone[0].upstream, "refs/remotes/origin/merged");
+        assert_eq!(gone[0].tip, head.id());
+        assert!(!gone[0].checked_out);
+    }
+
+    #[test]
+    fn test_delete_keeps_checked_out_branch() {
+        let tmp = tempfile::tempdir().unwrap();
+        let repo = setup(tmp.path());
+        track(&repo, "main", true);
+        track(&repo, "merged", true);
+
+        let gone = gone_branches(tmp.path()).unwrap();
+        assert_eq!(gone.len(), 2);
+        assert!(gone.iter().any(|b| b.name == "main" && b.checked_out));
+
+        let deleted = delete(tmp.path(), &gone).unwrap();
+        assert_eq!(deleted, vec!["merged".to_string()]);
+        assert!(repo.find_branch("merged", BranchType::Local).is_err());
+        assert!(repo.find_branch("main", BranchType::Local).is_ok());
+    }
+
+    #[test]
+    fn test_dry_run_leaves_refs_alone() {
+        let tmp = tempfile::tempdir().unwrap();
+        let upstream_dir = tmp.path().join("upstream.git");
+        Repository::init_bare(&upstream_dir).unwrap();
+        let work = tmp.path().join("work");
+        let repo = setup(&work);
+        repo.remote_set_url("origin", upstream_dir.to_str().unwrap())
+            .unwrap();
+        repo.find_remote("origin")
+            .unwrap()
+            .push(&["refs/heads/main:refs/heads/main"], None)
+            .unwrap();
+        track(&repo, "main", false);
+        // Tracked, but deleted on the remote since the last fetch
+        track(&repo, "merged", false);
+
+        let before = remote_refs(&work);
+        let results = scan(&[GitRepo::new(work.clone())], true);
+        assert_eq!(remote_refs(&work), before);
+        assert!(repo.find_branch("merged", BranchType::Local).is_ok());
+
+        assert_eq!(results.len(), 1);
+        assert!(!results[0].fetch_failed);
+        assert_eq!(results[0].pruned, vec!["origin/merged".to_string()]);
+        let gone: Vec<&str> = results[0].gone.iter().map(|b| b.name.as_str()).collect();
+        assert_eq!(gone, vec!["merged"]);
+    }
+
+    #[test]
+    fn test_remote_refs_lists_tracking_branches() {
+        let tmp = tempfile::tempdir().unwrap();
+        let repo = setup(tmp.path());
+        track(&repo, "main", false);
+        track(&repo, "merged", true);
+
+        let refs: Vec<String> = remote_refs(tmp.path()).into_iter().collect();
+        assert_eq!(refs, vec!["origin/main".to_string()]);
+    }
+
+    #[test]
+    fn test_format_summary() {
+        let tip = git2::Oid::from_str("1a2b3c4d5e6f7a8b9c0d1e2f3a4b5c6d7e8f9a0b").unwrap();
+        let branch = |name: &str, checked_out| GoneBranch {
+            name: name.to_string(),
+            upstream: format!("refs/remotes/origin/{}", name),
+            tip,
+            checked_out,
+        };
+        let results = vec![RepoPrune {
+            name: "api".to_string(),
+            path: PathBuf::from("/src/api"),
+            pruned: vec!["origin/login".to_string()],
+            gone: vec![branch("login", false), branch("main", true)],
+            fetch_failed: false,
+        }];
+
+        let text = format_summary(&results, false);
+        let lines: Vec<&str> = text.lines().collect();
+        assert_eq!(lines[0], "api");
+        assert_eq!(lines[1], "  pruned   origin/login");
+        assert_eq!(lines[2], "  deleted  login (was 1a2b3c4)");
+        assert_eq!(lines[3], "  kept     main (checked out; upstream is gone)");
+
+        let dry = format_summary(&results, true);
+        assert!(dry.contains("  would delete  login (was 1a2b3c4)"));
+        assert!(dry.contains("  would prune   origin/login"));
+    }
+}
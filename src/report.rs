@@ -0,0 +1,276 @@
+/// A single table cell: the displayed text and an optional machine sort key.
+///
+/// The sort key lets rendered reports order columns such as "last activity" by
+/// timestamp instead of by their human-readable text.
+#[derive(Debug, Clone)]
+pub struct Cell {
+    pub text: String,
+    pub sort_key: Option<String>,
+}
+
+impl Cell {
+    pub fn new(text: impl Into<String>) -> Self {
+        Self {
+            text: text.into(),
+            sort_key: None,
+        }
+    }
+
+    pub fn sorted_by(text: impl Into<String>, sort_key: impl Into<String>) -> Self {
+        Self {
+            text: text.into(),
+            sort_key: Some(sort_key.into()),
+        }
+    }
+}
+
+/// A titled table of repository data, rendered as plain text or HTML.
+#[derive(Debug, Clone)]
+pub struct Report {
+    pub title: String,
+    /// Lines shown under the title (generation time, search paths, ...)
+    pub notes: Vec<String>,
+    pub headers: Vec<String>,
+    pub rows: Vec<Vec<Cell>>,
+}
+
+impl Report {
+    /// Render as an aligned plain-text table.
+    pub fn render_text(&self) -> String {
+        let mut widths: Vec<usize> = self.headers.iter().map(|h| h.chars().count()).collect();
+        for row in &self.rows {
+            for (i, cell) in row.iter().enumerate() {
+                widths[i] = widths[i].max(cell.text.chars().count());
+            }
+        }
+
+        let format_row = |cells: Vec<&str>| {
+            cells
+                .iter()
+                .enumerate()
+                .map(|(i, text)| format!("{:<width$}", text, width = widths[i]))
+                .collect::<Vec<_>>()
+                .join("  ")
+                .trim_end()
+                .to_string()
+        };
+
+        let mut lines = vec![format_row(
+            self.headers.iter().map(String::as_str).collect(),
+        )];
+        for row in &self.rows {
+            lines.push(format_row(row.iter().map(|c| c.text.as_str()).collect()));
+        }
+        lines.join("\n")
+    }
+
+    /// Render as GitHub-flavored Markdown: a heading, the notes, and a pipe table.
+    ///
+    /// Suitable for pasting into an issue, pull request, or wiki page.
+    pub fn render_markdown(&self) -> String {
+        let row = |cells: Vec<String>| format!("| {} |", cells.join(" | "));
+
+        let mut lines = vec![
+            format!("### {}", escape_markdown(&self.title)),
+            String::new(),
+        ];
+        for note in &self.notes {
+            lines.push(format!("_{}_  ", escape_markdown(note)));
+        }
+        if !self.notes.is_empty() {
+            lines.push(String::new());
+        }
+
+        lines.push(row(self
+            .headers
+            .iter()
+            .map(|h| escape_markdown(h))
+            .collect()));
+        lines.push(row(self
+            .headers
+            .iter()
+            .map(|_| "---".to_string())
+            .collect()));
+        for cells in &self.rows {
+            lines.push(row(cells
+                .iter()
+                .map(|c| escape_markdown(&c.text))
+                .collect()));
+        }
+        lines.join("\n")
+    }
+
+    /// Render as a self-contained HTML page with a click-to-sort table.
+    ///
+    /// The page has no external assets, so it can be mailed around or archived as-is.
+    pub fn render_html(&self) -> String {
+        let mut html = String::new();
+        html.push_str("<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n");
+        html.push_str(&format!("<title>{}</title>\n", escape_html(&self.title)));
+        html.push_str(HTML_STYLE);
+        html.push_str("</head>\n<body>\n");
+        html.push_str(&format!("<h1>{}</h1>\n", escape_html(&self.title)));
+        for note in &self.notes {
+            html.push_str(&format!("<p class=\"note\">{}</p>\n", escape_html(note)));
+        }
+
+        html.push_str("<table id=\"report\">\n<thead><tr>");
+        for header in &self.headers {
+            html.push_str(&format!("<th>{}</th>", escape_html(header)));
+        }
+        html.push_str("</tr></thead>\n<tbody>\n");
+        for row in &self.rows {
+            html.push_str("<tr>");
+            for cell in row {
+                match cell.sort_key {
+                    Some(ref key) => html.push_str(&format!(
+                        "<td data-sort=\"{}\">{}</td>",
+                        escape_html(key),
+                        escape_html(&cell.text)
+                    )),
+                    None => html.push_str(&format!("<td>{}</td>", escape_html(&cell.text))),
+                }
+            }
+            html.push_str("</tr>\n");
+        }
+        html.push_str("</tbody>\n</table>\n");
+        html.push_str(HTML_SCRIPT);
+        html.push_str("</body>\n</html>\n");
+        html
+    }
+}
+
+/// Escape text for use in HTML element content and attribute values.
+fn escape_html(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Escape text for use in a Markdown table cell.
+///
+/// Pipes would end the cell and newlines the row; emphasis characters are
+/// escaped so branch names like `feat_x_y` render literally.
+fn escape_markdown(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '|' | '*' | '_' | '`' | '\\' | '<' | '[' | ']' => {
+                escaped.push('\\');
+                escaped.push(c);
+            }
+            '\n' | '\r' => escaped.push(' '),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+const HTML_STYLE: &str = r#"<style>
+body { font-family: -apple-system, "Segoe UI", Helvetica, Arial, sans-serif; margin: 2rem; color: #1f2328; }
+h1 { font-size: 1.4rem; margin-bottom: 0.25rem; }
+.note { color: #59636e; margin: 0.1rem 0; font-size: 0.9rem; }
+table { border-collapse: collapse; margin-top: 1rem; width: 100%; }
+th, td { text-align: left; padding: 0.35rem 0.75rem; border-bottom: 1px solid #d1d9e0; }
+th { cursor: pointer; user-select: none; background: #f6f8fa; }
+th.asc::after { content: " \25B2"; }
+th.desc::after { content: " \25BC"; }
+tbody tr:hover { background: #f6f8fa; }
+</style>
+"#;
+
+const HTML_SCRIPT: &str = r##"<script>
+document.querySelectorAll("#report th").forEach(function (th, col) {
+  th.addEventListener("click", function () {
+    var tbody = document.querySelector("#report tbody");
+    var asc = !th.classList.contains("asc");
+    document.querySelectorAll("#report th").forEach(function (h) { h.classList.remove("asc", "desc"); });
+    th.classList.add(asc ? "asc" : "desc");
+    var key = function (row) {
+      var td = row.children[col];
+      return td.hasAttribute("data-sort") ? td.getAttribute("data-sort") : td.textContent;
+    };
+    Array.from(tbody.rows)
+      .sort(function (a, b) {
+        var x = key(a), y = key(b);
+        var c = (!isNaN(x) && !isNaN(y) && x !== "" && y !== "") ? x - y : x.localeCompare(y);
+        return asc ? c : -c;
+      })
+      .forEach(function (row) { tbody.appendChild(row); });
+  });
+});
+</script>
+"##;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample() -> Report {
+        Report {
+            title: "Repositories".to_string(),
+            notes: vec!["Generated now".to_string()],
+            headers: vec!["Name".to_string(), "Branch".to_string()],
+            rows: vec![
+                vec![Cell::new("api"), Cell::new("main")],
+                vec![Cell::new("<web>"), Cell::sorted_by("feature/x", "2")],
+            ],
+        }
+    }
+
+    #[test]
+    fn test_render_text_aligns_columns() {
+        let text = sample().render_text();
+        let lines: Vec<&str> = text.lines().collect();
+        assert_eq!(lines[0], "Name   Branch");
+        assert_eq!(lines[1], "api    main");
+        assert_eq!(lines[2], "<web>  feature/x");
+    }
+
+    #[test]
+    fn test_render_html_is_self_contained_and_escaped() {
+        let html = sample().render_html();
+        assert!(html.starts_with("<!DOCTYPE html>"));
+        assert!(html.contains("<td>&lt;web&gt;</td>"));
+        assert!(html.contains("<td data-sort=\"2\">feature/x</td>"));
+        assert!(html.contains("<script>"));
+        assert!(!html.contains("<link"));
+        assert!(!html.contains("src=\"http"));
+    }
+
+    #[test]
+    fn test_render_markdown_table() {
+        let md = sample().render_markdown();
+        let lines: Vec<&str> = md.lines().collect();
+        assert_eq!(lines[0], "### Repositories");
+        assert_eq!(lines[2], "_Generated now_  ");
+        assert_eq!(lines[4], "| Name | Branch |");
+        assert_eq!(lines[5], "| --- | --- |");
+        assert_eq!(lines[6], "| api | main |");
+        assert_eq!(lines[7], "| \\<web> | feature/x |");
+    }
+
+    #[test]
+    fn test_escape_markdown() {
+        assert_eq!(escape_markdown("a|b"), "a\\|b");
+        assert_eq!(escape_markdown("feat_x"), "feat\\_x");
+        assert_eq!(escape_markdown("two\nlines"), "two lines");
+    }
+
+    #[test]
+    fn test_escape_html() {
+        assert_eq!(
+            escape_html("a & \"b\" <c>"),
+            "a &amp; &quot;b&quot; &lt;c&gt;"
+        );
+    }
+}
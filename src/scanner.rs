@@ -2,9 +2,16 @@ use anyhow::Result;
 use git2::{Repository, StatusOptions};
 use ignore::WalkBuilder;
 use serde::{Deserialize, Serialize};
+use std::collections::{BTreeSet, HashMap};
 use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering as AtomicOrdering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant, SystemTime};
 
-use crate::config::BadgeStyle;
+use crate::cache::CachedMeta;
+use crate::config::{
+    BadgeStyle, DirConfig, SearchConfig, SearchRoot, SortOrder, WorktreeMode, DIR_CONFIG_FILENAME,
+};
 
 /// Represents a git repository found during scanning.
 ///
@@ -85,6 +92,12 @@ pub struct EnrichedRepo {
     pub name: String,
     pub path: PathBuf,
     pub meta: RepoMeta,
+    /// Pinned with `gitnav pin`; shown first, with a marker
+    pub pinned: bool,
+    /// Tags from `gitnav tag add`, sorted
+    pub tags: Vec<String>,
+    /// Shortened parent directory, set when another listed repo has the same name
+    pub location: Option<String>,
 }
 
 /// Detect the primary project type by checking for marker files.
@@ -154,33 +167,80 @@ fn enrich_single(path: &Path) -> RepoMeta {
         Err(_) => (None, false),
     };
 
-    let is_dirty = {
-        let mut opts = StatusOptions::new();
-        opts.include_untracked(false)
-            .include_ignored(false)
-            .recurse_untracked_dirs(false);
-
-        match git_repo.statuses(Some(&mut opts)) {
-            Ok(statuses) => statuses.iter().any(|s| {
-                let flags = s.status();
-                flags.is_index_modified()
-                    || flags.is_index_new()
-                    || flags.is_index_deleted()
-                    || flags.is_wt_modified()
-                    || flags.is_wt_deleted()
-            }),
-            Err(_) => false,
-        }
-    };
-
     RepoMeta {
         branch,
-        is_dirty,
+        is_dirty: has_tracked_changes(&git_repo),
         is_detached,
         project_type,
     }
 }
 
+/// Whether tracked files have staged or unstaged changes (untracked files are ignored).
+pub fn has_tracked_changes(repo: &Repository) -> bool {
+    let mut opts = StatusOptions::new();
+    opts.include_untracked(false)
+        .include_ignored(false)
+        .recurse_untracked_dirs(false);
+
+    match repo.statuses(Some(&mut opts)) {
+        Ok(statuses) => statuses.iter().any(|s| {
+            let flags = s.status();
+            flags.is_index_modified()
+                || flags.is_index_new()
+                || flags.is_index_deleted()
+                || flags.is_wt_modified()
+                || flags.is_wt_deleted()
+        }),
+        Err(_) => false,
+    }
+}
+
+/// Repository metadata for `--list --details`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct RepoDetails {
+    /// Checked-out branch; absent for detached or unborn HEADs
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub branch: Option<String>,
+    pub detached: bool,
+    /// Tracked files have staged or unstaged changes
+    pub dirty: bool,
+    /// Unix timestamp of the commit HEAD points to
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_commit: Option<i64>,
+    /// URL of `origin`, or of the first remote
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub remote: Option<String>,
+}
+
+/// Collect [`RepoDetails`] for every repository, in parallel, keeping input order.
+///
+/// Repositories that cannot be opened get empty details.
+pub fn collect_details(repos: &[GitRepo]) -> Vec<RepoDetails> {
+    crate::parallel::filter_map(repos, crate::parallel::default_workers(), |repo| {
+        Some(details_single(&repo.path))
+    })
+}
+
+fn details_single(path: &Path) -> RepoDetails {
+    let Ok(repo) = Repository::open(path) else {
+        return RepoDetails::default();
+    };
+    let head = repo.head().ok();
+    RepoDetails {
+        branch: head
+            .as_ref()
+            .filter(|h| h.is_branch())
+            .and_then(|h| h.shorthand())
+            .map(str::to_string),
+        detached: repo.head_detached().unwrap_or(false),
+        dirty: has_tracked_changes(&repo),
+        last_commit: head
+            .and_then(|h| h.peel_to_commit().ok())
+            .map(|c| c.time().seconds()),
+        remote: crate::remote::primary_url(&repo),
+    }
+}
+
 /// Enrich a list of repos with git metadata and project type.
 ///
 /// Opens each repo with git2 to read branch, dirty status, and detached HEAD state.
@@ -194,15 +254,97 @@ pub fn enrich_repos(repos: Vec<GitRepo>) -> Vec<EnrichedRepo> {
                 name: repo.name,
                 path: repo.path,
                 meta,
+                pinned: false,
+                tags: Vec::new(),
+                location: None,
+            }
+        })
+        .collect()
+}
+
+/// Like [`enrich_repos`], but take branch and dirty state from `cached` (as
+/// recorded by the last scan) instead of opening every repository, so the
+/// picker opens instantly. Repositories missing from `cached` are read live.
+pub fn enrich_repos_cached(
+    repos: Vec<GitRepo>,
+    cached: &HashMap<PathBuf, CachedMeta>,
+) -> Vec<EnrichedRepo> {
+    repos
+        .into_iter()
+        .map(|repo| {
+            let meta = match cached.get(&repo.path) {
+                Some(cached) => RepoMeta {
+                    branch: cached.branch.clone(),
+                    is_dirty: cached.dirty,
+                    is_detached: false,
+                    project_type: detect_project_type(&repo.path),
+                },
+                None => enrich_single(&repo.path),
+            };
+            EnrichedRepo {
+                name: repo.name,
+                path: repo.path,
+                meta,
+                pinned: false,
+                tags: Vec::new(),
+                location: None,
             }
         })
         .collect()
 }
 
+/// Wrap a repo with empty metadata, for when `show_inline_meta` is off.
+pub fn without_meta(repo: GitRepo) -> EnrichedRepo {
+    EnrichedRepo {
+        meta: RepoMeta {
+            branch: None,
+            is_dirty: false,
+            is_detached: false,
+            project_type: ProjectType::Unknown,
+        },
+        name: repo.name,
+        path: repo.path,
+        pinned: false,
+        tags: Vec::new(),
+        location: None,
+    }
+}
+
+/// Set [`EnrichedRepo::location`] on every repo whose name is shared with
+/// another, so identical names can be told apart (`api  ~/work/client-a`).
+pub fn disambiguate(repos: &mut [EnrichedRepo]) {
+    let mut counts: HashMap<&str, usize> = HashMap::new();
+    for repo in repos.iter() {
+        *counts.entry(repo.name.as_str()).or_default() += 1;
+    }
+    let duplicates: Vec<bool> = repos.iter().map(|r| counts[r.name.as_str()] > 1).collect();
+
+    let home = dirs::home_dir();
+    for (repo, duplicate) in repos.iter_mut().zip(duplicates) {
+        if duplicate {
+            repo.location = repo
+                .path
+                .parent()
+                .map(|parent| shorten_home(parent, home.as_deref()));
+        }
+    }
+}
+
+/// `path` with the home directory replaced by `~`.
+pub fn shorten_home(path: &Path, home: Option<&Path>) -> String {
+    match home.and_then(|home| path.strip_prefix(home).ok()) {
+        Some(rest) if rest.as_os_str().is_empty() => "~".to_string(),
+        Some(rest) => format!("~/{}", rest.display()),
+        None => path.display().to_string(),
+    }
+}
+
 /// Format a single enriched repo's display string for the fzf list.
 ///
-/// The name is padded to `name_width` for alignment. Branch and dirty indicator
-/// are appended when present. Project badge is appended based on `badge_style`.
+/// The name is padded to `name_width` for alignment. Pinned repos get a `★`
+/// marker in front. The location of a duplicate name comes next (dimmed), then
+/// branch and dirty indicator when present.
+/// Project badge is appended based on `badge_style`, then tags as `#tag`.
 pub fn format_display(
     repo: &EnrichedRepo,
     name_width: usize,
@@ -210,8 +352,21 @@ pub fn format_display(
     badge_style: &BadgeStyle,
 ) -> String {
     let padded_name = format!("{:<width$}", repo.name, width = name_width);
+    let padded_name = match (repo.pinned, use_color) {
+        (true, true) => format!("\x1b[0;33m★\x1b[0m {}", padded_name),
+        (true, false) => format!("★ {}", padded_name),
+        (false, _) => padded_name,
+    };
     let mut parts: Vec<String> = vec![padded_name];
 
+    if let Some(ref location) = repo.location {
+        parts.push(if use_color {
+            format!("\x1b[2m{}\x1b[0m", location)
+        } else {
+            location.clone()
+        });
+    }
+
     if let Some(ref branch) = repo.meta.branch {
         let branch_str = if use_color {
             format!("\x1b[0;36m{}\x1b[0m", branch)
@@ -247,6 +402,20 @@ pub fn format_display(
         parts.push(badge);
     }
 
+    if !repo.tags.is_empty() {
+        let tags = repo
+            .tags
+            .iter()
+            .map(|tag| format!("#{}", tag))
+            .collect::<Vec<_>>()
+            .join(" ");
+        parts.push(if use_color {
+            format!("\x1b[0;35m{}\x1b[0m", tags)
+        } else {
+            tags
+        });
+    }
+
     parts.join("  ")
 }
 
@@ -265,25 +434,409 @@ fn should_ignore_path(path: &Path, ignore_patterns: &[String]) -> bool {
     })
 }
 
-/// Internal scanner implementation used by both `scan_repos` and `scan_repos_multi`.
+/// Name of the per-directory ignore files that prune the scan.
+pub const IGNORE_FILENAME: &str = ".gitnavignore";
+
+/// How to walk the search paths.
+#[derive(Debug, Clone)]
+pub struct ScanOptions {
+    /// Maximum directory depth to traverse
+    pub max_depth: usize,
+    /// Directory names to skip (e.g. `["node_modules", "vendor"]`)
+    pub ignore_patterns: Vec<String>,
+    pub worktrees: WorktreeMode,
+    /// Also list repositories inside other repositories
+    pub include_nested: bool,
+    /// Files in gitignore syntax applied under every search path, like a
+    /// `.gitnavignore` at its top
+    pub ignore_files: Vec<PathBuf>,
+    /// Skip what `.gitignore`, `.ignore`, and git's exclude files ignore
+    pub respect_gitignore: bool,
+    /// Descend into symlinked directories; links back to an ancestor are
+    /// skipped, and a repository reached twice is listed once
+    pub follow_symlinks: bool,
+    /// Stay on the filesystem of each search path, skipping mount points
+    pub same_filesystem: bool,
+    /// Search paths with their own depth and excludes (paths expanded), see
+    /// [`ScanOptions::for_root`]
+    pub roots: Vec<SearchRoot>,
+    /// Stop scanning after this long, keeping what was found
+    pub timeout: Option<Duration>,
+    /// Stop scanning once this many repositories were found
+    pub max_results: Option<usize>,
+    /// Let `.gitnav.toml` files change `max_depth` below them (off with
+    /// `--max-depth`); their excludes apply either way
+    pub dir_depths: bool,
+}
+
+impl Default for ScanOptions {
+    fn default() -> Self {
+        Self {
+            max_depth: 0,
+            ignore_patterns: Vec::new(),
+            worktrees: WorktreeMode::default(),
+            include_nested: false,
+            ignore_files: Vec::new(),
+            respect_gitignore: true,
+            follow_symlinks: false,
+            same_filesystem: true,
+            roots: Vec::new(),
+            timeout: None,
+            max_results: None,
+            dir_depths: true,
+        }
+    }
+}
+
+impl ScanOptions {
+    /// Options from the `[search]` config; the global ignore files are the
+    /// ones from [`crate::config::Config::ignore_file_paths`] that exist.
+    ///
+    /// `max_depth_flag` is `--max-depth`, which overrides every configured
+    /// depth, including those of `[[search.roots]]`.
+    pub fn from_config(search: &SearchConfig, max_depth_flag: Option<usize>) -> Self {
+        Self {
+            max_depth: max_depth_flag.unwrap_or(search.max_depth),
+            ignore_patterns: search.ignore_patterns.clone(),
+            worktrees: search.worktrees,
+            include_nested: search.include_nested,
+            ignore_files: crate::config::Config::ignore_file_paths()
+                .into_iter()
+                .filter(|path| path.is_file())
+                .collect(),
+            respect_gitignore: search.respect_gitignore,
+            follow_symlinks: search.follow_symlinks,
+            same_filesystem: search.same_filesystem,
+            roots: search
+                .roots
+                .iter()
+                .map(|root| SearchRoot {
+                    path: shellexpand::tilde(&root.path).to_string(),
+                    max_depth: root.max_depth.filter(|_| max_depth_flag.is_none()),
+                    exclude: root.exclude.clone(),
+                })
+                .collect(),
+            timeout: search.timeout_ms.map(Duration::from_millis),
+            max_results: search.max_results,
+            dir_depths: max_depth_flag.is_none(),
+        }
+    }
+
+    /// The options for scanning `root`: the depth and excludes of its
+    /// `[[search.roots]]` entry when it has one, these options otherwise.
+    pub fn for_root(&self, root: &Path) -> ScanOptions {
+        let mut options = self.clone();
+        let canonical = root.canonicalize().ok();
+        let entry = self.roots.iter().find(|entry| {
+            let path = Path::new(&entry.path);
+            path == root || (canonical.is_some() && path.canonicalize().ok() == canonical)
+        });
+        if let Some(entry) = entry {
+            if let Some(depth) = entry.max_depth {
+                options.max_depth = depth;
+            }
+            options
+                .ignore_patterns
+                .extend(entry.exclude.iter().cloned());
+        }
+        options
+    }
+
+    /// A walk over `root` with these options' depth and ignore files, for
+    /// the scanner and the daemon's watcher. `.gitnavignore` and the global
+    /// ignore files apply even when `respect_gitignore` is off.
+    ///
+    /// The walk yields the entries `filter` accepts, within the depth and
+    /// excludes of the `.gitnav.toml` files met along the way (see [`Subtrees`]).
+    pub fn walker<F>(&self, root: &Path, filter: F) -> Walker
+    where
+        F: Fn(&ignore::DirEntry) -> bool + Send + Sync + 'static,
+    {
+        let subtrees = Arc::new(Subtrees::new(root, self));
+        let filter: Arc<EntryFilter> = Arc::new(filter);
+        let span = Span {
+            root: root.to_path_buf(),
+            depth: 0,
+            max_depth: subtrees.top.max_depth,
+        };
+        let current = self.walk_builder(span, &subtrees, &filter).build();
+        Walker {
+            options: self.clone(),
+            subtrees,
+            filter,
+            current,
+            nested: false,
+        }
+    }
+
+    fn walk_builder(
+        &self,
+        span: Span,
+        subtrees: &Arc<Subtrees>,
+        filter: &Arc<EntryFilter>,
+    ) -> WalkBuilder {
+        let mut builder = WalkBuilder::new(&span.root);
+        let nested = span.depth > 0;
+        let max_depth = span.max_depth - span.depth;
+        let (subtrees, filter) = (Arc::clone(subtrees), Arc::clone(filter));
+        builder
+            .max_depth(Some(max_depth))
+            .filter_entry(move |entry| filter(entry) && subtrees.visit(entry, &span))
+            // A directory's .gitnav.toml comes before the rest of it
+            .sort_by_file_name(|a, b| (b == DIR_CONFIG_FILENAME).cmp(&(a == DIR_CONFIG_FILENAME)))
+            .hidden(false)
+            .follow_links(self.follow_symlinks)
+            .same_file_system(self.same_filesystem)
+            // A subtree walked on its own still needs the .gitnavignore files above it
+            .parents(self.respect_gitignore || nested)
+            .ignore(self.respect_gitignore)
+            .git_ignore(self.respect_gitignore)
+            .git_global(self.respect_gitignore)
+            .git_exclude(self.respect_gitignore)
+            .add_custom_ignore_filename(IGNORE_FILENAME);
+        for file in &self.ignore_files {
+            if let Some(e) = builder.add_ignore(file) {
+                if !nested {
+                    eprintln!("Warning: {}: {}", file.display(), e);
+                }
+            }
+        }
+        builder
+    }
+}
+
+type EntryFilter = dyn Fn(&ignore::DirEntry) -> bool + Send + Sync;
+
+/// The entries of one search path, from [`ScanOptions::walker`].
+///
+/// Walks the search path down to its depth first, then on their own the
+/// subtrees whose `.gitnav.toml` lets them go deeper.
+pub struct Walker {
+    options: ScanOptions,
+    subtrees: Arc<Subtrees>,
+    filter: Arc<EntryFilter>,
+    current: ignore::Walk,
+    /// Whether `current` walks a subtree, whose top was already yielded
+    nested: bool,
+}
+
+impl Iterator for Walker {
+    type Item = Result<ignore::DirEntry, ignore::Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            match self.current.next() {
+                Some(Ok(entry)) if self.nested && entry.depth() == 0 => {}
+                Some(entry) => return Some(entry),
+                None => {
+                    let span = self.subtrees.next_deeper()?;
+                    self.current = self
+                        .options
+                        .walk_builder(span, &self.subtrees, &self.filter)
+                        .build();
+                    self.nested = true;
+                }
+            }
+        }
+    }
+}
+
+/// One walk of a [`Walker`]: the search path, or a subtree below it.
+struct Span {
+    root: PathBuf,
+    /// Depth of `root` below the search path
+    depth: usize,
+    /// Deepest depth below the search path this walk reaches
+    max_depth: usize,
+}
+
+/// The depth and excludes in effect below each directory of one walk, as set
+/// by the `.gitnav.toml` files met along the way.
+///
+/// A file's `max_depth` counts from its own directory and replaces the one
+/// above it; its excludes add to those above it. A file is read where the
+/// walk lists a directory, so within the scan depth, from the directory's
+/// own listing; nothing is looked up until the first one turns up. A file
+/// that reaches deeper than its walk has its directory walked on its own.
+struct Subtrees {
+    root: PathBuf,
+    dir_depths: bool,
+    /// The limits below the search path
+    top: Arc<Limits>,
+    /// Whether `limits` has any
+    found: AtomicBool,
+    /// The limits below directories with a `.gitnav.toml`, under the search path
+    limits: Mutex<HashMap<PathBuf, Arc<Limits>>>,
+    /// Directories still to walk on their own
+    deeper: Mutex<Vec<Span>>,
+}
+
+/// What a walk may visit below a directory.
+#[derive(Debug, Clone)]
+struct Limits {
+    /// Deepest walker depth, counted from the search path
+    max_depth: usize,
+    /// Directory names to skip
+    exclude: Vec<String>,
+    /// Visited only by the directory's own walk
+    separate: bool,
+}
+
+impl Subtrees {
+    fn new(root: &Path, options: &ScanOptions) -> Self {
+        let limits = Limits {
+            max_depth: options.max_depth,
+            exclude: Vec::new(),
+            separate: false,
+        };
+        let top = with_dir_config(root, 0, &limits, options.dir_depths).unwrap_or(limits);
+        Self {
+            root: root.to_path_buf(),
+            dir_depths: options.dir_depths,
+            top: Arc::new(top),
+            found: AtomicBool::new(false),
+            limits: Mutex::new(HashMap::new()),
+            deeper: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// Whether the walk over `span` should visit `entry`, reading it first if
+    /// it is a `.gitnav.toml`.
+    fn visit(&self, entry: &ignore::DirEntry, span: &Span) -> bool {
+        let depth = span.depth + entry.depth();
+        let (dir, limits) = self.limits_for(entry.path());
+        if limits.separate && dir != span.root {
+            return false;
+        }
+        if depth > limits.max_depth {
+            return false;
+        }
+        let Some(file_type) = entry.file_type() else {
+            return true;
+        };
+        if file_type.is_file() && entry.file_name() == DIR_CONFIG_FILENAME {
+            return match entry.path().parent() {
+                Some(parent) if parent != dir => self.read(parent, depth - 1, &limits, span),
+                // Read before its walk started
+                _ => true,
+            };
+        }
+        if !file_type.is_dir() {
+            return true;
+        }
+        !entry
+            .file_name()
+            .to_str()
+            .is_some_and(|name| limits.exclude.iter().any(|p| p == name))
+    }
+
+    /// Record the limits below `dir`, at `depth`, from its `.gitnav.toml`.
+    /// Returns whether the walk over `span` should go on visiting the file,
+    /// which it does unless `dir` is to be walked on its own.
+    fn read(&self, dir: &Path, depth: usize, limits: &Limits, span: &Span) -> bool {
+        let Some(mut below) = with_dir_config(dir, depth, limits, self.dir_depths) else {
+            return true;
+        };
+        let deeper = below.max_depth > span.max_depth;
+        if deeper {
+            below.separate = true;
+            let mut all = self.deeper.lock().unwrap_or_else(|e| e.into_inner());
+            all.push(Span {
+                root: dir.to_path_buf(),
+                depth,
+                max_depth: below.max_depth,
+            });
+        }
+        let mut all = self.limits.lock().unwrap_or_else(|e| e.into_inner());
+        all.insert(dir.to_path_buf(), Arc::new(below));
+        self.found.store(true, AtomicOrdering::Relaxed);
+        !deeper
+    }
+
+    /// The nearest directory above `path` with a `.gitnav.toml` (or the
+    /// search path), and the limits below it.
+    fn limits_for<'a>(&'a self, path: &'a Path) -> (&'a Path, Arc<Limits>) {
+        if self.found.load(AtomicOrdering::Relaxed) {
+            let limits = self.limits.lock().unwrap_or_else(|e| e.into_inner());
+            let nearest = path
+                .ancestors()
+                .skip(1)
+                .take_while(|dir| *dir != self.root && dir.starts_with(&self.root))
+                .find_map(|dir| Some((dir, Arc::clone(limits.get(dir)?))));
+            if let Some(nearest) = nearest {
+                return nearest;
+            }
+        }
+        (&self.root, Arc::clone(&self.top))
+    }
+
+    /// The next directory to walk on its own.
+    fn next_deeper(&self) -> Option<Span> {
+        self.deeper.lock().unwrap_or_else(|e| e.into_inner()).pop()
+    }
+}
+
+/// `limits` changed by the `.gitnav.toml` in `dir`, at walker `depth`, or
+/// `None` if it has none. A file that cannot be read is skipped with a
+/// warning, given once for each version of the file since the daemon walks
+/// every few seconds.
+fn with_dir_config(dir: &Path, depth: usize, limits: &Limits, dir_depths: bool) -> Option<Limits> {
+    static WARNED: Mutex<BTreeSet<(PathBuf, Option<SystemTime>)>> = Mutex::new(BTreeSet::new());
+
+    let dir_config = match DirConfig::load(dir) {
+        Ok(dir_config) => dir_config?,
+        Err(e) => {
+            let file = dir.join(DIR_CONFIG_FILENAME);
+            let modified = file.metadata().and_then(|m| m.modified()).ok();
+            let mut warned = WARNED.lock().unwrap_or_else(|e| e.into_inner());
+            if warned.insert((file, modified)) {
+                eprintln!("Warning: {:#}", e);
+            }
+            return None;
+        }
+    };
+    let mut below = Limits {
+        separate: false,
+        ..limits.clone()
+    };
+    if let Some(max_depth) = dir_config.search.max_depth.filter(|_| dir_depths) {
+        below.max_depth = depth + max_depth;
+    }
+    below.exclude.extend(dir_config.search.exclude);
+    Some(below)
+}
+
+/// How a walk over one search path ended.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Walk {
+    Finished,
+    /// `on_repo` returned `false`
+    Stopped,
+    /// The deadline passed
+    TimedOut,
+}
+
+/// Internal scanner implementation used by `scan_repos` and `scan_repos_streaming`.
+///
+/// Calls `on_repo` for each repository as it is found, until it returns
+/// `false` or `deadline` passes.
 fn scan_repos_inner(
     base_path: &Path,
-    max_depth: usize,
-    ignore_patterns: &[String],
-) -> Result<Vec<GitRepo>> {
+    options: &ScanOptions,
+    deadline: Option<Instant>,
+    on_repo: &mut dyn FnMut(GitRepo) -> bool,
+) -> Result<Walk> {
     if !base_path.exists() {
         anyhow::bail!("Base path does not exist: {}", base_path.display());
     }
 
-    let mut repos = Vec::new();
-
-    let walker = WalkBuilder::new(base_path)
-        .max_depth(Some(max_depth))
-        .hidden(false)
-        .follow_links(false)
-        .build();
+    let include_nested = options.include_nested;
+    let walker = options.walker(base_path, move |entry| stays_in_scan(entry, include_nested));
 
     for entry in walker {
+        if deadline.is_some_and(|deadline| Instant::now() >= deadline) {
+            return Ok(Walk::TimedOut);
+        }
         let entry = match entry {
             Ok(e) => e,
             Err(_) => continue,
@@ -291,16 +844,76 @@ fn scan_repos_inner(
 
         let path = entry.path();
 
-        if path.file_name().and_then(|n| n.to_str()) == Some(".git") && path.is_dir() {
-            if let Some(repo_path) = path.parent() {
-                if !should_ignore_path(repo_path, ignore_patterns) {
-                    repos.push(GitRepo::new(repo_path.to_path_buf()));
-                }
+        if path.file_name().and_then(|n| n.to_str()) != Some(".git") {
+            continue;
+        }
+        let Some(repo_path) = path.parent() else {
+            continue;
+        };
+        let repo = if path.is_dir() {
+            GitRepo::new(repo_path.to_path_buf())
+        } else if options.worktrees == WorktreeMode::Hidden {
+            continue;
+        } else if let Some(main) = worktree_main_name(path) {
+            let mut repo = GitRepo::new(repo_path.to_path_buf());
+            if options.worktrees == WorktreeMode::Labeled {
+                repo.name = format!("{}@{}", main, repo.name);
             }
+            repo
+        } else {
+            // A `.git` file that is not a worktree link, e.g. a submodule
+            continue;
+        };
+
+        if !should_ignore_path(repo_path, &options.ignore_patterns) && !on_repo(repo) {
+            return Ok(Walk::Stopped);
         }
     }
 
-    Ok(repos)
+    Ok(Walk::Finished)
+}
+
+/// Whether the scan should visit `entry`: never the inside of a `.git`
+/// directory, and, unless `include_nested`, no directory of a repository but
+/// its `.git` (so clones vendored inside a repository are not listed).
+fn stays_in_scan(entry: &ignore::DirEntry, include_nested: bool) -> bool {
+    let Some(parent) = entry.path().parent().filter(|_| entry.depth() > 0) else {
+        return true;
+    };
+    if parent.file_name().is_some_and(|name| name == ".git") {
+        return false;
+    }
+    if include_nested
+        || entry.file_name() == ".git"
+        || !entry.file_type().is_some_and(|t| t.is_dir())
+    {
+        return true;
+    }
+    !parent.join(".git").exists()
+}
+
+/// Name of the main repository of a linked worktree, given the worktree's `.git` file.
+///
+/// A linked worktree's `.git` file reads `gitdir: <main>/.git/worktrees/<id>`
+/// (or `<main>.git/worktrees/<id>` for a bare main repository). Submodules also
+/// use a `.git` file, pointing into `.git/modules/`; those return `None`.
+fn worktree_main_name(dot_git: &Path) -> Option<String> {
+    let contents = std::fs::read_to_string(dot_git).ok()?;
+    let gitdir = contents.lines().next()?.strip_prefix("gitdir:")?.trim();
+    let gitdir = dot_git.parent()?.join(gitdir);
+
+    let worktrees_dir = gitdir.parent()?;
+    if worktrees_dir.file_name()? != "worktrees" {
+        return None;
+    }
+    let common_dir = worktrees_dir.parent()?;
+    let main_dir = if common_dir.file_name()? == ".git" {
+        common_dir.parent()?
+    } else {
+        common_dir
+    };
+    let name = main_dir.file_name()?.to_str()?;
+    Some(name.strip_suffix(".git").unwrap_or(name).to_string())
 }
 
 /// Scan for git repositories starting from a base path up to a maximum depth.
@@ -322,51 +935,209 @@ fn scan_repos_inner(
 /// Returns an error if the base path does not exist or cannot be accessed
 #[allow(dead_code)]
 pub fn scan_repos<P: AsRef<Path>>(base_path: P, max_depth: usize) -> Result<Vec<GitRepo>> {
-    let mut repos = scan_repos_inner(base_path.as_ref(), max_depth, &[])?;
-    repos.sort_by(|a, b| a.name.cmp(&b.name));
+    let mut repos = Vec::new();
+    let options = ScanOptions {
+        max_depth,
+        worktrees: WorktreeMode::Hidden,
+        ..ScanOptions::default()
+    };
+    scan_repos_inner(base_path.as_ref(), &options, None, &mut |repo| {
+        repos.push(repo);
+        true
+    })?;
+    sort_repos(&mut repos, SortOrder::Name);
     Ok(repos)
 }
 
-/// Scan multiple base paths and merge results, deduplicating by path.
-///
-/// Paths are expanded (tilde expansion must be done before calling).
-/// Results are merged, deduplicated by path, and sorted alphabetically by name.
+/// What [`scan_repos_streaming`] found.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Scanned {
+    /// Repositories sorted by name
+    pub repos: Vec<GitRepo>,
+    /// The scan hit `timeout` or `max_results`, so some repositories may be missing
+    pub partial: bool,
+}
+
+/// Scan several base paths, handing each repository to `on_repo` as soon as
+/// it is found.
 ///
-/// # Arguments
+/// Repositories reach `on_repo` in discovery order and already de-duplicated
+/// (the first path to a directory wins, as in [`dedup_repos`]). Returning
+/// `false` from `on_repo` stops the scan.
 ///
-/// * `paths` - The directories to scan
-/// * `max_depth` - Maximum directory depth to traverse in each path
-/// * `ignore_patterns` - Directory names to skip (e.g. `["node_modules", "vendor"]`)
+/// The scan also ends early, with a warning, once `timeout` has passed or
+/// `max_results` repositories were found; what was found so far is returned.
 ///
-/// # Errors
+/// # Returns
 ///
-/// Returns an error if any path cannot be accessed
-pub fn scan_repos_multi(
+/// The repositories found, or `None` if `on_repo` stopped the scan.
+/// Paths that cannot be scanned are skipped with a warning.
+pub fn scan_repos_streaming(
     paths: &[String],
-    max_depth: usize,
-    ignore_patterns: &[String],
-) -> Result<Vec<GitRepo>> {
+    options: &ScanOptions,
+    mut on_repo: impl FnMut(&GitRepo) -> bool,
+) -> Result<Option<Scanned>> {
+    let mut seen = std::collections::HashSet::new();
     let mut all_repos: Vec<GitRepo> = Vec::new();
+    let deadline = options.timeout.map(|timeout| Instant::now() + timeout);
+    let mut at_cap = false;
+    let mut partial = false;
 
     for path_str in paths {
         let path = Path::new(path_str);
-        match scan_repos_inner(path, max_depth, ignore_patterns) {
-            Ok(mut repos) => all_repos.append(&mut repos),
-            Err(e) => {
-                // Log warning but continue with other paths
-                eprintln!("Warning: skipping path '{}': {}", path_str, e);
+        let result = scan_repos_inner(path, &options.for_root(path), deadline, &mut |repo| {
+            let key = repo
+                .path
+                .canonicalize()
+                .unwrap_or_else(|_| repo.path.clone());
+            if !seen.insert(key) {
+                return true;
             }
+            let keep_going = on_repo(&repo);
+            all_repos.push(repo);
+            if !keep_going {
+                return false;
+            }
+            at_cap = options
+                .max_results
+                .is_some_and(|max| all_repos.len() >= max);
+            !at_cap
+        });
+        match result {
+            Ok(Walk::Finished) => {}
+            Ok(Walk::Stopped) if at_cap => {
+                eprintln!(
+                    "Warning: scan stopped at {} repositories (search.max_results)",
+                    all_repos.len()
+                );
+                partial = true;
+                break;
+            }
+            Ok(Walk::Stopped) => return Ok(None),
+            Ok(Walk::TimedOut) => {
+                eprintln!(
+                    "Warning: scan stopped after {} ms (search.timeout_ms); results are partial",
+                    options.timeout.unwrap_or_default().as_millis()
+                );
+                partial = true;
+                break;
+            }
+            Err(e) => eprintln!("Warning: skipping path '{}': {}", path_str, e),
         }
     }
 
-    // Deduplicate by canonical path
-    all_repos.sort_by(|a, b| a.path.cmp(&b.path));
-    all_repos.dedup_by(|a, b| a.path == b.path);
+    sort_repos(&mut all_repos, SortOrder::Name);
+    Ok(Some(Scanned {
+        repos: all_repos,
+        partial,
+    }))
+}
+
+/// Canonicalize search paths and drop duplicates, preserving the configured order.
+///
+/// `~/work`, `~/work/` and a symlink to it all resolve to the same entry, so the
+/// cache key (and therefore the cache file) is shared between them. Paths that
+/// cannot be canonicalized (e.g. missing directories) are kept as given so the
+/// scanner can still report them.
+pub fn normalize_search_paths(paths: &[String]) -> Vec<String> {
+    let mut seen = std::collections::HashSet::new();
+    paths
+        .iter()
+        .map(|p| {
+            Path::new(p)
+                .canonicalize()
+                .map(|c| c.to_string_lossy().to_string())
+                .unwrap_or_else(|_| p.clone())
+        })
+        .filter(|p| seen.insert(p.clone()))
+        .collect()
+}
+
+/// Remove repositories that resolve to the same directory, then sort by name and path.
+///
+/// Overlapping base paths (e.g. `~` and `~/work`) or symlinked checkouts find the
+/// same repository more than once; the first occurrence is kept.
+pub fn dedup_repos(repos: Vec<GitRepo>) -> Vec<GitRepo> {
+    let mut seen = std::collections::HashSet::new();
+    let mut unique: Vec<GitRepo> = repos
+        .into_iter()
+        .filter(|repo| {
+            let key = repo
+                .path
+                .canonicalize()
+                .unwrap_or_else(|_| repo.path.clone());
+            seen.insert(key)
+        })
+        .collect();
 
     // Sort by name for display
-    all_repos.sort_by(|a, b| a.name.cmp(&b.name));
+    sort_repos(&mut unique, SortOrder::Name);
+    unique
+}
 
-    Ok(all_repos)
+/// Drop repositories whose directory no longer exists, checking them in
+/// parallel. Returns the paths that were dropped, in list order.
+///
+/// Cached lists outlive the repositories in them; without this a deleted
+/// repository stays listed until the next scan.
+pub fn drop_missing(repos: &mut Vec<GitRepo>) -> Vec<PathBuf> {
+    let missing = crate::parallel::filter_map(repos, crate::parallel::default_workers(), |repo| {
+        (!repo.path.is_dir()).then(|| repo.path.clone())
+    });
+    if !missing.is_empty() {
+        let gone: std::collections::HashSet<&PathBuf> = missing.iter().collect();
+        repos.retain(|repo| !gone.contains(&repo.path));
+    }
+    missing
+}
+
+/// Sort repositories in the configured order.
+///
+/// Ties (e.g. two checkouts named `api`) are broken by path, so the order is
+/// total and repeated runs list repositories identically.
+pub fn sort_repos(repos: &mut [GitRepo], order: SortOrder) {
+    match order {
+        SortOrder::Name => {
+            repos.sort_by(|a, b| a.name.cmp(&b.name).then_with(|| a.path.cmp(&b.path)))
+        }
+        SortOrder::Path => repos.sort_by(|a, b| a.path.cmp(&b.path)),
+    }
+}
+
+/// Parse candidate repository paths supplied on stdin (`--stdin`).
+///
+/// Input is NUL-delimited when it contains a NUL byte, otherwise one path per
+/// line. Paths support `~`, are kept in input order, and duplicates are dropped.
+/// Entries that are not existing directories are skipped.
+///
+/// # Returns
+///
+/// The candidates and the number of skipped entries
+pub fn parse_candidates(input: &str) -> (Vec<GitRepo>, usize) {
+    let entries: Vec<&str> = if input.contains('\0') {
+        input.split('\0').collect()
+    } else {
+        input.lines().collect()
+    };
+
+    let mut seen = std::collections::HashSet::new();
+    let mut skipped = 0;
+    let mut repos = Vec::new();
+    for entry in entries {
+        let entry = entry.trim_end_matches(['\r', '\n']);
+        if entry.trim().is_empty() {
+            continue;
+        }
+        let path = PathBuf::from(shellexpand::tilde(entry).to_string());
+        if !path.is_dir() {
+            skipped += 1;
+            continue;
+        }
+        if seen.insert(path.clone()) {
+            repos.push(GitRepo::new(path));
+        }
+    }
+    (repos, skipped)
 }
 
 /// Format repositories as tab-separated values for fzf input.
@@ -392,6 +1163,24 @@ pub fn format_for_fzf(repos: &[GitRepo]) -> String {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::test_util::commit;
+
+    /// Depth 5, worktrees hidden, and no ignore files.
+    fn scan_options() -> ScanOptions {
+        ScanOptions {
+            max_depth: 5,
+            worktrees: WorktreeMode::Hidden,
+            ..ScanOptions::default()
+        }
+    }
+
+    /// Scan `paths` with [`scan_options`], keeping every repository.
+    fn scan_paths(paths: &[String]) -> Vec<GitRepo> {
+        scan_repos_streaming(paths, &scan_options(), |_| true)
+            .unwrap()
+            .unwrap()
+            .repos
+    }
 
     #[test]
     fn test_format_for_fzf() {
@@ -762,6 +1551,9 @@ mod tests {
                 is_detached: false,
                 project_type: ProjectType::Unknown,
             },
+            pinned: false,
+            tags: Vec::new(),
+            location: None,
         };
         let display = format_display(&repo, 6, false, &BadgeStyle::None);
         assert_eq!(display, "myrepo");
@@ -778,6 +1570,9 @@ mod tests {
                 is_detached: false,
                 project_type: ProjectType::Unknown,
             },
+            pinned: false,
+            tags: Vec::new(),
+            location: None,
         };
         let display = format_display(&repo, 6, false, &BadgeStyle::None);
         assert!(display.contains("main"));
@@ -795,6 +1590,9 @@ mod tests {
                 is_detached: false,
                 project_type: ProjectType::Unknown,
             },
+            pinned: false,
+            tags: Vec::new(),
+            location: None,
         };
         let display = format_display(&repo, 6, false, &BadgeStyle::None);
         assert!(display.contains('●'));
@@ -811,11 +1609,54 @@ mod tests {
                 is_detached: false,
                 project_type: ProjectType::Rust,
             },
+            pinned: false,
+            tags: Vec::new(),
+            location: None,
         };
         let display = format_display(&repo, 4, false, &BadgeStyle::Text);
         assert!(display.contains("[rust]"));
     }
 
+    #[test]
+    fn test_format_display_pinned() {
+        let mut repo = without_meta(GitRepo::new(PathBuf::from("/path/api")));
+        repo.pinned = true;
+        assert_eq!(format_display(&repo, 3, false, &BadgeStyle::None), "★ api");
+    }
+
+    #[test]
+    fn test_format_display_tags() {
+        let mut repo = without_meta(GitRepo::new(PathBuf::from("/path/api")));
+        repo.tags = vec!["oss".to_string(), "work".to_string()];
+        assert_eq!(
+            format_display(&repo, 4, false, &BadgeStyle::None),
+            "api   #oss #work"
+        );
+    }
+
+    #[test]
+    fn test_disambiguate_duplicate_names() {
+        let mut repos: Vec<EnrichedRepo> = ["/src/work/client-a/api", "/src/cli", "/src/oss/api"]
+            .iter()
+            .map(|p| without_meta(GitRepo::new(PathBuf::from(p))))
+            .collect();
+        disambiguate(&mut repos);
+
+        let locations: Vec<Option<&str>> = repos.iter().map(|r| r.location.as_deref()).collect();
+        assert_eq!(
+            locations,
+            vec![Some("/src/work/client-a"), None, Some("/src/oss")]
+        );
+        assert_eq!(
+            format_display(&repos[0], 3, false, &BadgeStyle::None),
+            "api  /src/work/client-a"
+        );
+        assert_eq!(
+            shorten_home(Path::new("/home/me/work"), Some(Path::new("/home/me"))),
+            "~/work"
+        );
+    }
+
     #[test]
     fn test_should_ignore_path() {
         let path = Path::new("/home/user/node_modules/some-pkg");
@@ -833,17 +1674,562 @@ mod tests {
     }
 
     #[test]
-    fn test_scan_repos_multi_deduplicates() {
+    fn test_scan_paths_deduplicates() {
         // Two identical paths should yield same repos once
         let tmp = tempfile::tempdir().unwrap();
         let repo_path = tmp.path().join("myrepo");
         std::fs::create_dir_all(repo_path.join(".git")).unwrap();
 
         let path_str = tmp.path().to_string_lossy().to_string();
-        let repos =
-            scan_repos_multi(&[path_str.clone(), path_str], 5, &[]).unwrap();
+        let repos = scan_paths(&[path_str.clone(), path_str]);
 
         let names: Vec<&str> = repos.iter().map(|r| r.name.as_str()).collect();
         assert_eq!(names.iter().filter(|&&n| n == "myrepo").count(), 1);
     }
+
+    #[test]
+    fn test_scan_paths_deduplicates_overlapping_paths() {
+        // A parent and a child base path both find the nested repo
+        let tmp = tempfile::tempdir().unwrap();
+        let work = tmp.path().join("work");
+        std::fs::create_dir_all(work.join("api/.git")).unwrap();
+        std::fs::create_dir_all(tmp.path().join("notes/.git")).unwrap();
+
+        let parent = tmp.path().to_string_lossy().to_string();
+        let child = format!("{}/", work.to_string_lossy());
+        let repos = scan_paths(&[parent, child]);
+
+        let names: Vec<&str> = repos.iter().map(|r| r.name.as_str()).collect();
+        assert_eq!(names, vec!["api", "notes"]);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_dedup_repos_resolves_symlinks() {
+        let tmp = tempfile::tempdir().unwrap();
+        let real = tmp.path().join("real");
+        std::fs::create_dir_all(&real).unwrap();
+        let link = tmp.path().join("link");
+        std::os::unix::fs::symlink(&real, &link).unwrap();
+
+        let repos = dedup_repos(vec![GitRepo::new(real.clone()), GitRepo::new(link)]);
+        assert_eq!(repos.len(), 1);
+        assert_eq!(repos[0].path, real);
+    }
+
+    #[test]
+    fn test_normalize_search_paths_merges_equivalent_paths() {
+        let tmp = tempfile::tempdir().unwrap();
+        let base = tmp.path().to_string_lossy().to_string();
+        let paths = vec![
+            base.clone(),
+            format!("{}/", base),
+            format!("{}/./", base),
+            "/nonexistent/gitnav-path".to_string(),
+        ];
+
+        let normalized = normalize_search_paths(&paths);
+        assert_eq!(normalized.len(), 2);
+        assert_eq!(normalized[1], "/nonexistent/gitnav-path");
+    }
+
+    #[test]
+    fn test_drop_missing_keeps_existing_repos() {
+        let tmp = tempfile::tempdir().unwrap();
+        let api = tmp.path().join("api");
+        std::fs::create_dir(&api).unwrap();
+        let gone = tmp.path().join("gone");
+
+        let mut repos = vec![
+            GitRepo::new(gone.clone()),
+            GitRepo::new(api.clone()),
+            GitRepo::new(tmp.path().join("also-gone")),
+        ];
+        let dropped = drop_missing(&mut repos);
+        assert_eq!(dropped, vec![gone, tmp.path().join("also-gone")]);
+        assert_eq!(repos, vec![GitRepo::new(api)]);
+        assert!(drop_missing(&mut repos).is_empty());
+    }
+
+    #[test]
+    fn test_sort_repos_breaks_name_ties_by_path() {
+        let mut repos = vec![
+            GitRepo::new(PathBuf::from("/work/b/api")),
+            GitRepo::new(PathBuf::from("/home/zeta")),
+            GitRepo::new(PathBuf::from("/work/a/api")),
+        ];
+
+        sort_repos(&mut repos, SortOrder::Name);
+        let paths: Vec<&str> = repos.iter().map(|r| r.path.to_str().unwrap()).collect();
+        assert_eq!(paths, vec!["/work/a/api", "/work/b/api", "/home/zeta"]);
+
+        sort_repos(&mut repos, SortOrder::Path);
+        let paths: Vec<&str> = repos.iter().map(|r| r.path.to_str().unwrap()).collect();
+        assert_eq!(paths, vec!["/home/zeta", "/work/a/api", "/work/b/api"]);
+    }
+
+    #[test]
+    fn test_scan_repos_streaming_reports_each_repo_once() {
+        let tmp = tempfile::tempdir().unwrap();
+        let work = tmp.path().join("work");
+        std::fs::create_dir_all(work.join("api/.git")).unwrap();
+        std::fs::create_dir_all(tmp.path().join("notes/.git")).unwrap();
+
+        let parent = tmp.path().to_string_lossy().to_string();
+        let child = work.to_string_lossy().to_string();
+        let mut streamed = Vec::new();
+        let repos = scan_repos_streaming(&[parent, child], &scan_options(), |repo| {
+            streamed.push(repo.name.clone());
+            true
+        })
+        .unwrap()
+        .unwrap()
+        .repos;
+
+        streamed.sort();
+        assert_eq!(streamed, vec!["api", "notes"]);
+        let names: Vec<&str> = repos.iter().map(|r| r.name.as_str()).collect();
+        assert_eq!(names, vec!["api", "notes"]);
+    }
+
+    #[test]
+    fn test_collect_details() {
+        let tmp = tempfile::tempdir().unwrap();
+        let mut opts = git2::RepositoryInitOptions::new();
+        opts.initial_head("main");
+        let repo = Repository::init_opts(tmp.path().join("api"), &opts).unwrap();
+        repo.remote("origin", "git@github.com:me/api.git").unwrap();
+        std::fs::write(tmp.path().join("api/README"), "hi").unwrap();
+        let mut index = repo.index().unwrap();
+        index.add_path(Path::new("README")).unwrap();
+        index.write().unwrap();
+        commit(&repo, "init", Some(1_700_000_000));
+        std::fs::write(tmp.path().join("api/README"), "changed").unwrap();
+
+        let repos = vec![
+            GitRepo::new(tmp.path().join("api")),
+            GitRepo::new(tmp.path().join("missing")),
+        ];
+        let details = collect_details(&repos);
+        assert_eq!(
+            details[0],
+            RepoDetails {
+                branch: Some("main".to_string()),
+                detached: false,
+                dirty: true,
+                last_commit: Some(1_700_000_000),
+                remote: Some("git@github.com:me/api.git".to_string()),
+            }
+        );
+        assert_eq!(details[1], RepoDetails::default());
+    }
+
+    #[test]
+    fn test_scan_discovers_linked_worktrees() {
+        let tmp = tempfile::tempdir().unwrap();
+        let mut opts = git2::RepositoryInitOptions::new();
+        opts.initial_head("main");
+        let repo = Repository::init_opts(tmp.path().join("api"), &opts).unwrap();
+        commit(&repo, "init", None);
+        repo.worktree("feature-x", &tmp.path().join("api-feature-x"), None)
+            .unwrap();
+        // Submodules also have a `.git` file, but are not worktrees
+        std::fs::create_dir_all(tmp.path().join("api/.git/modules/vendored")).unwrap();
+        std::fs::create_dir_all(tmp.path().join("lib/vendored")).unwrap();
+        std::fs::write(
+            tmp.path().join("lib/vendored/.git"),
+            "gitdir: ../../api/.git/modules/vendored\n",
+        )
+        .unwrap();
+
+        let base = tmp.path().to_string_lossy().to_string();
+        let scan = |mode| {
+            let mut names: Vec<String> = scan_repos_streaming(
+                std::slice::from_ref(&base),
+                &ScanOptions {
+                    worktrees: mode,
+                    ..scan_options()
+                },
+                |_| true,
+            )
+            .unwrap()
+            .unwrap()
+            .repos
+            .into_iter()
+            .map(|r| r.name)
+            .collect();
+            names.sort();
+            names
+        };
+
+        assert_eq!(
+            scan(WorktreeMode::Labeled),
+            vec!["api", "api@api-feature-x"]
+        );
+        assert_eq!(scan(WorktreeMode::Plain), vec!["api", "api-feature-x"]);
+        assert_eq!(scan(WorktreeMode::Hidden), vec!["api"]);
+    }
+
+    #[test]
+    fn test_scan_stops_at_repositories_unless_nested_is_on() {
+        let tmp = tempfile::tempdir().unwrap();
+        std::fs::create_dir_all(tmp.path().join("app/.git/objects")).unwrap();
+        std::fs::create_dir_all(tmp.path().join("app/vendor/lib/.git")).unwrap();
+        std::fs::create_dir_all(tmp.path().join("tools/cli/.git")).unwrap();
+
+        let base = tmp.path().to_string_lossy().to_string();
+        let scan = |include_nested| {
+            let options = ScanOptions {
+                include_nested,
+                ..scan_options()
+            };
+            let repos = scan_repos_streaming(std::slice::from_ref(&base), &options, |_| true)
+                .unwrap()
+                .unwrap()
+                .repos;
+            repos.into_iter().map(|r| r.name).collect::<Vec<_>>()
+        };
+
+        assert_eq!(scan(false), vec!["app", "cli"]);
+        assert_eq!(scan(true), vec!["app", "cli", "lib"]);
+    }
+
+    #[test]
+    fn test_scan_honors_gitnavignore_and_global_ignore_files() {
+        let tmp = tempfile::tempdir().unwrap();
+        let base = tmp.path().join("src");
+        for repo in ["work/api", "work/archive/old", "play/tmp-x", "play/game"] {
+            std::fs::create_dir_all(base.join(repo).join(".git")).unwrap();
+        }
+        std::fs::write(base.join("work").join(IGNORE_FILENAME), "archive/\n").unwrap();
+        let global = tmp.path().join("ignore");
+        std::fs::write(&global, "# scratch checkouts\ntmp-*\n").unwrap();
+
+        let options = ScanOptions {
+            ignore_files: vec![global],
+            ..scan_options()
+        };
+        let repos = scan_repos_streaming(&[base.display().to_string()], &options, |_| true)
+            .unwrap()
+            .unwrap()
+            .repos;
+        let names: Vec<String> = repos.into_iter().map(|r| r.name).collect();
+        assert_eq!(names, vec!["api", "game"]);
+    }
+
+    #[test]
+    fn test_scan_applies_gitnav_toml_below_its_directory() {
+        let tmp = tempfile::tempdir().unwrap();
+        let base = tmp.path();
+        for repo in [
+            "mono/a/b/deep",
+            "mono/node_modules/dep",
+            "mono/lib/node_modules/dep",
+            "scratch/a/b/far",
+            "api",
+        ] {
+            std::fs::create_dir_all(base.join(repo).join(".git")).unwrap();
+        }
+        let dir_config = "[search]\nmax_depth = 4\nexclude = [\"node_modules\"]\n";
+        std::fs::write(base.join("mono").join(".gitnav.toml"), dir_config).unwrap();
+
+        let scan = |dir_depths| {
+            let options = ScanOptions {
+                max_depth: 3,
+                dir_depths,
+                ..scan_options()
+            };
+            let repos = scan_repos_streaming(&[base.display().to_string()], &options, |_| true)
+                .unwrap()
+                .unwrap()
+                .repos;
+            repos.into_iter().map(|r| r.name).collect::<Vec<_>>()
+        };
+
+        // Deeper below mono/ only, and its excludes reach every level below it
+        assert_eq!(scan(true), vec!["api", "deep"]);
+        // --max-depth wins over the file's depth, not its excludes
+        assert_eq!(scan(false), vec!["api"]);
+    }
+
+    #[test]
+    fn test_walker_goes_deeper_below_nested_gitnav_toml_once() {
+        let tmp = tempfile::tempdir().unwrap();
+        let base = tmp.path();
+        for dir in ["mono/a/b/c/d/e/far", "mono/a/b/c/node_modules/dep", "x/y/z"] {
+            std::fs::create_dir_all(base.join(dir)).unwrap();
+        }
+        std::fs::write(
+            base.join("mono").join(DIR_CONFIG_FILENAME),
+            "[search]\nmax_depth = 2\nexclude = [\"node_modules\"]\n",
+        )
+        .unwrap();
+        // Counted from mono/a, past where mono's own walk stops
+        std::fs::write(
+            base.join("mono/a").join(DIR_CONFIG_FILENAME),
+            "[search]\nmax_depth = 5\n",
+        )
+        .unwrap();
+
+        let options = ScanOptions {
+            max_depth: 2,
+            ..scan_options()
+        };
+        let mut paths: Vec<PathBuf> = options
+            .walker(base, |_| true)
+            .map(|entry| {
+                entry
+                    .unwrap()
+                    .path()
+                    .strip_prefix(base)
+                    .unwrap()
+                    .to_path_buf()
+            })
+            .collect();
+        paths.sort();
+        let mut deduped = paths.clone();
+        deduped.dedup();
+        assert_eq!(paths, deduped, "an entry was yielded twice");
+
+        assert!(paths.contains(&PathBuf::from("mono/a/b/c/d/e/far")));
+        // mono's excludes still reach below mono/a
+        assert!(!paths.iter().any(|p| p.ends_with("node_modules")));
+        // Elsewhere the scan depth still holds
+        assert!(paths.contains(&PathBuf::from("x/y")));
+        assert!(!paths.contains(&PathBuf::from("x/y/z")));
+    }
+
+    #[test]
+    fn test_scan_can_disregard_ignore_files_of_the_tree() {
+        let tmp = tempfile::tempdir().unwrap();
+        std::fs::create_dir_all(tmp.path().join("api/.git")).unwrap();
+        std::fs::create_dir_all(tmp.path().join("build/gen/.git")).unwrap();
+        std::fs::write(tmp.path().join(".ignore"), "build/\n").unwrap();
+
+        let base = tmp.path().display().to_string();
+        let scan = |respect_gitignore| {
+            let options = ScanOptions {
+                respect_gitignore,
+                ..scan_options()
+            };
+            let repos = scan_repos_streaming(std::slice::from_ref(&base), &options, |_| true)
+                .unwrap()
+                .unwrap()
+                .repos;
+            repos.into_iter().map(|r| r.name).collect::<Vec<_>>()
+        };
+
+        assert_eq!(scan(true), vec!["api"]);
+        assert_eq!(scan(false), vec!["api", "gen"]);
+    }
+
+    #[test]
+    fn test_scan_applies_per_root_depth_and_excludes() {
+        let tmp = tempfile::tempdir().unwrap();
+        let work = tmp.path().join("work");
+        let scratch = tmp.path().join("scratch");
+        std::fs::create_dir_all(work.join("api/.git")).unwrap();
+        std::fs::create_dir_all(work.join("legacy/.git")).unwrap();
+        std::fs::create_dir_all(work.join("team/tools/cli/.git")).unwrap();
+        std::fs::create_dir_all(scratch.join("a/b/c/d/e/try/.git")).unwrap();
+
+        let mut search = crate::config::Config::default().search;
+        search.max_depth = 4;
+        search.roots = vec![
+            SearchRoot {
+                path: work.display().to_string(),
+                max_depth: Some(2),
+                exclude: vec!["legacy".to_string()],
+            },
+            SearchRoot {
+                path: scratch.display().to_string(),
+                max_depth: Some(8),
+                exclude: Vec::new(),
+            },
+        ];
+        let paths = vec![work.display().to_string(), scratch.display().to_string()];
+        let scan = |flag| {
+            let options = ScanOptions::from_config(&search, flag);
+            let repos = scan_repos_streaming(&paths, &options, |_| true)
+                .unwrap()
+                .unwrap()
+                .repos;
+            repos.into_iter().map(|r| r.name).collect::<Vec<_>>()
+        };
+
+        assert_eq!(scan(None), vec!["api", "try"]);
+        // --max-depth overrides the roots' depths, not their excludes
+        assert_eq!(scan(Some(4)), vec!["api", "cli"]);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_scan_follows_symlinks_without_looping() {
+        let tmp = tempfile::tempdir().unwrap();
+        let data = tmp.path().join("data");
+        std::fs::create_dir_all(data.join("code/api/.git")).unwrap();
+        let home = tmp.path().join("home");
+        std::fs::create_dir_all(home.join("work/web/.git")).unwrap();
+        std::os::unix::fs::symlink(data.join("code"), home.join("code")).unwrap();
+        std::os::unix::fs::symlink(data.join("code"), home.join("code-again")).unwrap();
+        // A link back to an ancestor
+        std::os::unix::fs::symlink(&home, home.join("work/loop")).unwrap();
+
+        let base = home.display().to_string();
+        let scan = |follow_symlinks| {
+            let options = ScanOptions {
+                follow_symlinks,
+                max_depth: 12,
+                ..scan_options()
+            };
+            let repos = scan_repos_streaming(std::slice::from_ref(&base), &options, |_| true)
+                .unwrap()
+                .unwrap()
+                .repos;
+            repos.into_iter().map(|r| r.name).collect::<Vec<_>>()
+        };
+
+        assert_eq!(scan(false), vec!["web"]);
+        assert_eq!(scan(true), vec!["api", "web"]);
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_scan_stays_on_one_filesystem() {
+        use std::os::unix::fs::MetadataExt;
+
+        // A directory on another filesystem to reach through a symlink
+        let Ok(other) = tempfile::tempdir_in("/dev/shm") else {
+            return;
+        };
+        let tmp = tempfile::tempdir().unwrap();
+        let dev = |path: &Path| std::fs::metadata(path).unwrap().dev();
+        if dev(other.path()) == dev(tmp.path()) {
+            return;
+        }
+        std::fs::create_dir_all(other.path().join("scratch/.git")).unwrap();
+        std::fs::create_dir_all(tmp.path().join("api/.git")).unwrap();
+        std::os::unix::fs::symlink(other.path(), tmp.path().join("mnt")).unwrap();
+
+        let base = tmp.path().display().to_string();
+        let scan = |same_filesystem| {
+            let options = ScanOptions {
+                follow_symlinks: true,
+                same_filesystem,
+                ..scan_options()
+            };
+            let repos = scan_repos_streaming(std::slice::from_ref(&base), &options, |_| true)
+                .unwrap()
+                .unwrap()
+                .repos;
+            repos.into_iter().map(|r| r.name).collect::<Vec<_>>()
+        };
+
+        assert_eq!(scan(true), vec!["api"]);
+        assert_eq!(scan(false), vec!["api", "scratch"]);
+    }
+
+    #[test]
+    fn test_scan_repos_streaming_can_be_stopped() {
+        let tmp = tempfile::tempdir().unwrap();
+        for dir in ["a", "b", "c"] {
+            std::fs::create_dir_all(tmp.path().join(dir).join(".git")).unwrap();
+        }
+        let base = tmp.path().to_string_lossy().to_string();
+
+        let mut seen = 0;
+        let result = scan_repos_streaming(&[base], &scan_options(), |_| {
+            seen += 1;
+            false
+        })
+        .unwrap();
+        assert!(result.is_none());
+        assert_eq!(seen, 1);
+    }
+
+    #[test]
+    fn test_scan_repos_streaming_stops_at_limits_with_partial_results() {
+        let tmp = tempfile::tempdir().unwrap();
+        for dir in ["a", "b", "c"] {
+            std::fs::create_dir_all(tmp.path().join(dir).join(".git")).unwrap();
+        }
+        let paths = vec![tmp.path().to_string_lossy().to_string()];
+        let scan = |options: ScanOptions| {
+            scan_repos_streaming(&paths, &options, |_| true)
+                .unwrap()
+                .unwrap()
+        };
+
+        let full = scan(scan_options());
+        assert_eq!(full.repos.len(), 3);
+        assert!(!full.partial);
+
+        let capped = scan(ScanOptions {
+            max_results: Some(2),
+            ..scan_options()
+        });
+        assert_eq!(capped.repos.len(), 2);
+        assert!(capped.partial);
+
+        let timed_out = scan(ScanOptions {
+            timeout: Some(Duration::ZERO),
+            ..scan_options()
+        });
+        assert!(timed_out.repos.is_empty());
+        assert!(timed_out.partial);
+    }
+
+    #[test]
+    fn test_scan_paths_is_deterministic_with_name_collisions() {
+        let tmp = tempfile::tempdir().unwrap();
+        for dir in ["z/api", "a/api", "m/api", "web"] {
+            std::fs::create_dir_all(tmp.path().join(dir).join(".git")).unwrap();
+        }
+        let base = tmp.path().to_string_lossy().to_string();
+
+        let first = scan_paths(std::slice::from_ref(&base));
+        for _ in 0..5 {
+            assert_eq!(scan_paths(std::slice::from_ref(&base)), first);
+        }
+        let names: Vec<String> = first
+            .iter()
+            .map(|r| {
+                r.path
+                    .strip_prefix(tmp.path())
+                    .unwrap()
+                    .display()
+                    .to_string()
+            })
+            .collect();
+        assert_eq!(names, vec!["a/api", "m/api", "z/api", "web"]);
+    }
+
+    #[test]
+    fn test_parse_candidates_lines_and_nul() {
+        let tmp = tempfile::tempdir().unwrap();
+        let a = tmp.path().join("a");
+        let b = tmp.path().join("b c");
+        std::fs::create_dir_all(&a).unwrap();
+        std::fs::create_dir_all(&b).unwrap();
+        let missing = tmp.path().join("missing");
+
+        let lines = format!(
+            "{}\r\n{}\n\n{}\n{}\n",
+            b.display(),
+            a.display(),
+            missing.display(),
+            b.display()
+        );
+        let (repos, skipped) = parse_candidates(&lines);
+        assert_eq!(
+            repos.iter().map(|r| r.name.as_str()).collect::<Vec<_>>(),
+            vec!["b c", "a"]
+        );
+        assert_eq!(skipped, 1);
+
+        let nul = format!("{}\0{}\0", a.display(), b.display());
+        let (repos, skipped) = parse_candidates(&nul);
+        assert_eq!(repos.len(), 2);
+        assert_eq!(skipped, 0);
+    }
 }
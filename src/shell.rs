@@ -1,200 +1,643 @@
 /// Generate a shell initialization script for the given shell type.
 ///
-/// Produces shell-specific code that creates a `gn` function to use gitnav.
-/// Supports bash, zsh, fish, and nushell.
+/// Produces shell-specific code that creates a `gn` function (or the name
+/// given in `options`) to use gitnav. Supports bash, zsh, fish, nushell, and
+/// PowerShell.
+///
+/// Every wrapper follows the exit code contract documented in
+/// [`crate::exit_codes`]: it only changes directory when gitnav exits with
+/// `EXIT_SUCCESS` and prints a directory, and otherwise returns gitnav's exit
+/// code unchanged so `gn && make` behaves predictably.
 ///
 /// # Arguments
 ///
 /// * `shell` - The shell type: "bash", "zsh", "fish", or "nu"/"nushell"
+/// * `options` - Function name and what runs after `cd` (`gitnav init` flags)
 ///
 /// # Returns
 ///
 /// A string containing the shell function definition, or `None` if shell is unsupported
-pub fn generate_init_script(shell: &str) -> Option<String> {
+pub fn generate_init_script(shell: &str, options: &InitOptions) -> Option<String> {
+    match shell.to_lowercase().as_str() {
+        "zsh" => Some(generate_zsh_script(options)),
+        "bash" => Some(generate_bash_script(options)),
+        "fish" => Some(generate_fish_script(options)),
+        "nu" | "nushell" => Some(generate_nushell_script(options)),
+        "powershell" | "pwsh" => Some(generate_powershell_script(options)),
+        _ => None,
+    }
+}
+
+/// How `gitnav init` customizes the wrapper function.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InitOptions {
+    /// Name of the wrapper function (`--cmd`)
+    pub cmd: String,
+    /// List the repository after changing into it (turned off by `--no-ls`)
+    pub ls: bool,
+    /// Shell code run after changing into a repository (`--post-cd`)
+    pub post_cd: Option<String>,
+}
+
+impl Default for InitOptions {
+    fn default() -> Self {
+        Self {
+            cmd: "gn".to_string(),
+            ls: true,
+            post_cd: None,
+        }
+    }
+}
+
+/// Check a wrapper function name for `gitnav init --cmd`.
+///
+/// The name is pasted into shell code, so only letters, digits, `_`, and `-`
+/// (not leading) are accepted. `gitnav` itself is refused because the wrapper
+/// calls gitnav by name and would call itself.
+///
+/// # Errors
+///
+/// Returns a message explaining what is wrong with the name
+pub fn parse_cmd_name(name: &str) -> Result<String, String> {
+    let valid = name
+        .chars()
+        .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-');
+    if name.is_empty() || name.starts_with('-') || !valid {
+        return Err(format!(
+            "'{}' is not a valid function name; use letters, digits, '_' and '-'",
+            name
+        ));
+    }
+    if name == "gitnav" {
+        return Err("the wrapper calls gitnav, so it cannot be named gitnav".to_string());
+    }
+    Ok(name.to_string())
+}
+
+/// A key for the picker widget, in fzf's key syntax: `ctrl-<letter>` or
+/// `alt-<letter or digit>`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WidgetKey {
+    alt: bool,
+    key: char,
+}
+
+impl WidgetKey {
+    /// Parse `ctrl-g` or `alt-g`, for `gitnav init --widget-key`.
+    ///
+    /// # Errors
+    ///
+    /// Returns a message naming the accepted forms for anything else
+    pub fn parse(spec: &str) -> Result<Self, String> {
+        let spec = spec.trim().to_lowercase();
+        let parsed = match spec.split_once('-') {
+            Some(("ctrl", key)) => single_char(key)
+                .filter(char::is_ascii_lowercase)
+                .map(|key| Self { alt: false, key }),
+            Some(("alt", key)) => single_char(key)
+                .filter(char::is_ascii_alphanumeric)
+                .map(|key| Self { alt: true, key }),
+            _ => None,
+        };
+        parsed.ok_or_else(|| {
+            format!(
+                "'{}' is not a supported key; use ctrl-<letter> or alt-<letter or digit>, e.g. ctrl-g",
+                spec
+            )
+        })
+    }
+}
+
+impl Default for WidgetKey {
+    fn default() -> Self {
+        Self {
+            alt: false,
+            key: 'g',
+        }
+    }
+}
+
+fn single_char(s: &str) -> Option<char> {
+    let mut chars = s.chars();
+    let c = chars.next()?;
+    chars.next().is_none().then_some(c)
+}
+
+/// Generate the picker widget for `gitnav init --widget`: a key binding that
+/// opens the picker from the command line and changes to the picked repository.
+///
+/// In zsh and fish the `cd` is run right away, with the line being edited kept
+/// for the next prompt. bash cannot run a command from a key binding without
+/// losing the line, so there the `cd` is inserted for Enter to run.
+///
+/// # Returns
+///
+/// The widget code to append to the init script, or `None` if the shell has no
+/// widget (nushell, PowerShell)
+pub fn generate_widget(shell: &str, key: WidgetKey) -> Option<String> {
+    let WidgetKey { alt, key } = key;
     match shell.to_lowercase().as_str() {
-        "zsh" => Some(generate_zsh_script()),
-        "bash" => Some(generate_bash_script()),
-        "fish" => Some(generate_fish_script()),
-        "nu" | "nushell" => Some(generate_nushell_script()),
-        "powershell" | "pwsh" => Some(generate_powershell_script()),
+        "zsh" => Some(generate_zsh_widget(&if alt {
+            format!("^[{}", key)
+        } else {
+            format!("^{}", key.to_ascii_uppercase())
+        })),
+        "bash" => Some(generate_bash_widget(&if alt {
+            format!("\\e{}", key)
+        } else {
+            format!("\\C-{}", key)
+        })),
+        "fish" => Some(generate_fish_widget(&if alt {
+            format!("\\e{}", key)
+        } else {
+            format!("\\c{}", key)
+        })),
+        _ => None,
+    }
+}
+
+/// Detect the shell that invoked gitnav, for `gitnav init` without a shell argument.
+///
+/// Looks at the parent process first (the shell evaluating `$(gitnav init)`),
+/// then at `$SHELL`, so shared dotfiles work even where the login shell differs
+/// from the one actually running.
+///
+/// # Returns
+///
+/// The shell name accepted by [`generate_init_script`], or `None` if neither
+/// source names a supported shell
+pub fn detect_shell() -> Option<&'static str> {
+    parent_process_name()
+        .as_deref()
+        .and_then(shell_from_program)
+        .or_else(|| {
+            std::env::var("SHELL")
+                .ok()
+                .as_deref()
+                .and_then(shell_from_program)
+        })
+}
+
+/// Map a program name or path (`/bin/zsh`, `-bash`, `pwsh.exe`) to a supported shell.
+fn shell_from_program(program: &str) -> Option<&'static str> {
+    let name = program.trim().rsplit(['/', '\\']).next()?;
+    // Login shells are reported as "-zsh"
+    let name = name.trim_start_matches('-').to_lowercase();
+    let name = name.strip_suffix(".exe").unwrap_or(&name);
+    match name {
+        "zsh" => Some("zsh"),
+        "bash" => Some("bash"),
+        "fish" => Some("fish"),
+        "nu" | "nushell" => Some("nu"),
+        "pwsh" | "powershell" => Some("powershell"),
         _ => None,
     }
 }
 
-fn generate_zsh_script() -> String {
+#[cfg(unix)]
+fn parent_process_name() -> Option<String> {
+    let ppid = std::os::unix::process::parent_id();
+    if let Ok(comm) = std::fs::read_to_string(format!("/proc/{}/comm", ppid)) {
+        return Some(comm.trim().to_string());
+    }
+
+    // No procfs (macOS, BSD): ask ps
+    let output = std::process::Command::new("ps")
+        .args(["-o", "comm=", "-p", &ppid.to_string()])
+        .output()
+        .ok()?;
+    output
+        .status
+        .success()
+        .then(|| String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+#[cfg(not(unix))]
+fn parent_process_name() -> Option<String> {
+    None
+}
+
+/// Directory listing the wrappers run after `cd` unless `--no-ls` is given.
+const POSIX_LISTING: &str = r#"
+    # Optional: show a quick listing after cd
+    if command -v eza &> /dev/null; then
+      eza -l
+    elif command -v ls &> /dev/null; then
+      ls -la
+    fi
+"#;
+
+const FISH_LISTING: &str = r#"
+    # Optional: show a quick listing after cd
+    if command -q eza
+      eza -l
+    else
+      ls -la
+    end
+"#;
+
+const NU_LISTING: &str = r#"
+    # Optional: show a quick listing after cd
+    if (which eza | length) > 0 {
+      eza -l
+    } else {
+      ls
+    }
+"#;
+
+const POWERSHELL_LISTING: &str = r#"
+    # Optional: show a quick listing after cd
+    if (Get-Command eza -ErrorAction SilentlyContinue) {
+      eza -l
+    } else {
+      Get-ChildItem
+    }
+"#;
+
+/// The code a wrapper runs after changing into a repository: the listing
+/// (unless `--no-ls`), the `--post-cd` command, then `run_hook`, which runs
+/// the `post_cd` hook gitnav wrote to the wrapper's hook file (empty for
+/// shells that cannot).
+fn post_cd_block(options: &InitOptions, listing: &str, run_hook: &str) -> String {
+    let mut block = String::new();
+    if options.ls {
+        block.push_str(listing);
+    }
+    if let Some(ref command) = options.post_cd {
+        block.push_str("\n    # Run after changing directory (--post-cd)\n");
+        for line in command.lines() {
+            block.push_str("    ");
+            block.push_str(line);
+            block.push('\n');
+        }
+    }
+    block.push_str(run_hook);
+    block
+}
+
+/// Reads the `post_cd` hook gitnav wrote to the hook file in bash and zsh,
+/// then deletes the file.
+const POSIX_READ_HOOK: &str = r#"
+  # The post_cd hook from [hooks], if gitnav wrote one
+  hook=
+  if [[ -n "$GITNAV_POST_CD_FILE" ]]; then
+    [[ -s "$GITNAV_POST_CD_FILE" ]] && hook=$(<"$GITNAV_POST_CD_FILE")
+    rm -f -- "$GITNAV_POST_CD_FILE"
+  fi
+"#;
+
+const POSIX_RUN_HOOK: &str = r#"
+    # Run the post_cd hook from [hooks]
+    if [[ -n "$hook" ]]; then
+      eval "$hook"
+    fi
+"#;
+
+const FISH_RUN_HOOK: &str = r#"
+    # Run the post_cd hook from [hooks]
+    if set -q hook[1]
+      string join \n -- $hook | source
+    end
+"#;
+
+const POWERSHELL_RUN_HOOK: &str = r#"
+    # Run the post_cd hook from [hooks]
+    if ($hook) { Invoke-Expression $hook }
+"#;
+
+fn generate_zsh_script(options: &InitOptions) -> String {
     r#"# gitnav shell integration for zsh
 # Add this to your ~/.zshrc:
 #   eval "$(gitnav init zsh)"
+#
+# Exit codes are passed through: 0 = selected (cd), 130 = cancelled, other = error
 
-gn() {
-  local result
-  # First non-flag argument is treated as an initial fzf query
-  if [[ $# -gt 0 ]] && [[ "$1" != -* ]]; then
-    result=$(gitnav --query "$1" "${@:2}")
+__CMD__() {
+  local result exit_code hook hook_file
+  # Asks gitnav to write the post_cd hook to a file of its own, never stdout
+  local -x GITNAV_SHELL=__SHELL__
+  hook_file=$(mktemp 2>/dev/null) || hook_file=
+  local -x GITNAV_POST_CD_FILE=$hook_file
+  # A first non-flag argument jumps to the matching repo (picker if ambiguous);
+  # `__CMD__ clone <url>` clones and cds into the new repo, `__CMD__ branch` and
+  # `__CMD__ worktree` into the picked branch or worktree; `__CMD__ -` goes back
+  if [[ "$1" == - ]]; then
+    result=$(gitnav back "${@:2}")
+  elif [[ $# -gt 0 ]] && [[ "$1" != -* ]] && [[ "$1" != clone ]] && [[ "$1" != branch ]] && [[ "$1" != worktree ]]; then
+    result=$(gitnav jump "$1" "${@:2}")
   else
     result=$(gitnav "$@")
   fi
+  exit_code=$?
+__READ_HOOK__
+  if [[ $exit_code -ne 0 ]]; then
+    [[ -n "$result" ]] && printf '%s\n' "$result"
+    return $exit_code
+  fi
 
   if [[ -n "$result" ]] && [[ -d "$result" ]]; then
     cd "$result" || return 1
+__POST_CD__  elif [[ -n "$result" ]]; then
+    printf '%s\n' "$result"
+  fi
+}
+"#
+    .replace("__CMD__", &options.cmd)
+    .replace("__SHELL__", "zsh")
+    .replace("__READ_HOOK__", POSIX_READ_HOOK)
+    .replace(
+        "__POST_CD__",
+        &post_cd_block(options, POSIX_LISTING, POSIX_RUN_HOOK),
+    )
+}
 
-    # Optional: show a quick listing after cd
-    if command -v eza &> /dev/null; then
-      eza -l
-    elif command -v ls &> /dev/null; then
-      ls -la
-    fi
+fn generate_zsh_widget(key: &str) -> String {
+    r#"
+# Picker widget: the bound key opens the picker and changes to the picked
+# repository
+_gitnav_widget() {
+  local result
+  result=$(gitnav)
+  if [[ $? -eq 0 ]] && [[ -d "$result" ]]; then
+    zle push-line
+    BUFFER="cd -- ${(q)result}"
+    zle accept-line
   fi
+  zle reset-prompt
 }
+zle -N _gitnav_widget
+bindkey 'KEY' _gitnav_widget
 "#
-    .to_string()
+    .replace("KEY", key)
 }
 
-fn generate_bash_script() -> String {
+fn generate_bash_script(options: &InitOptions) -> String {
     r#"# gitnav shell integration for bash
 # Add this to your ~/.bashrc:
 #   eval "$(gitnav init bash)"
+#
+# Exit codes are passed through: 0 = selected (cd), 130 = cancelled, other = error
 
-gn() {
-  local result
-  # First non-flag argument is treated as an initial fzf query
-  if [[ $# -gt 0 ]] && [[ "$1" != -* ]]; then
-    result=$(gitnav --query "$1" "${@:2}")
+__CMD__() {
+  local result exit_code hook hook_file
+  # Asks gitnav to write the post_cd hook to a file of its own, never stdout
+  local -x GITNAV_SHELL=__SHELL__
+  hook_file=$(mktemp 2>/dev/null) || hook_file=
+  local -x GITNAV_POST_CD_FILE=$hook_file
+  # A first non-flag argument jumps to the matching repo (picker if ambiguous);
+  # `__CMD__ clone <url>` clones and cds into the new repo, `__CMD__ branch` and
+  # `__CMD__ worktree` into the picked branch or worktree; `__CMD__ -` goes back
+  if [[ "$1" == - ]]; then
+    result=$(gitnav back "${@:2}")
+  elif [[ $# -gt 0 ]] && [[ "$1" != -* ]] && [[ "$1" != clone ]] && [[ "$1" != branch ]] && [[ "$1" != worktree ]]; then
+    result=$(gitnav jump "$1" "${@:2}")
   else
     result=$(gitnav "$@")
   fi
+  exit_code=$?
+__READ_HOOK__
+  if [[ $exit_code -ne 0 ]]; then
+    [[ -n "$result" ]] && printf '%s\n' "$result"
+    return $exit_code
+  fi
 
   if [[ -n "$result" ]] && [[ -d "$result" ]]; then
     cd "$result" || return 1
+__POST_CD__  elif [[ -n "$result" ]]; then
+    printf '%s\n' "$result"
+  fi
+}
+"#
+    .replace("__CMD__", &options.cmd)
+    .replace("__SHELL__", "bash")
+    .replace("__READ_HOOK__", POSIX_READ_HOOK)
+    .replace(
+        "__POST_CD__",
+        &post_cd_block(options, POSIX_LISTING, POSIX_RUN_HOOK),
+    )
+}
 
-    # Optional: show a quick listing after cd
-    if command -v eza &> /dev/null; then
-      eza -l
-    elif command -v ls &> /dev/null; then
-      ls -la
-    fi
+fn generate_bash_widget(key: &str) -> String {
+    r#"
+# Picker widget: the bound key opens the picker and puts a cd to the picked
+# repository on the command line; press Enter to run it
+_gitnav_widget() {
+  local result
+  result=$(gitnav)
+  if [[ $? -eq 0 ]] && [[ -d "$result" ]]; then
+    printf -v READLINE_LINE 'cd -- %q' "$result"
+    READLINE_POINT=${#READLINE_LINE}
   fi
 }
+bind -x '"KEY": _gitnav_widget'
 "#
-    .to_string()
+    .replace("KEY", key)
 }
 
-fn generate_fish_script() -> String {
+fn generate_fish_script(options: &InitOptions) -> String {
     r#"# gitnav shell integration for fish
 # Add this to your ~/.config/fish/config.fish:
 #   gitnav init fish | source
+#
+# Exit codes are passed through: 0 = selected (cd), 130 = cancelled, other = error
 
-function gn
-  # First non-flag argument is treated as an initial fzf query
+function __CMD__
+  # Asks gitnav to write the post_cd hook to a file of its own, never stdout
+  set -lx GITNAV_SHELL fish
+  set -lx GITNAV_POST_CD_FILE (mktemp 2>/dev/null)
+  # A first non-flag argument jumps to the matching repo (picker if ambiguous);
+  # `__CMD__ clone <url>` clones and cds into the new repo, `__CMD__ branch` and
+  # `__CMD__ worktree` into the picked branch or worktree; `__CMD__ -` goes back
   set result
-  if test (count $argv) -gt 0; and not string match -q -- '-*' $argv[1]
-    set result (gitnav --query $argv[1] $argv[2..])
+  if test (count $argv) -gt 0; and test "$argv[1]" = -
+    set result (gitnav back $argv[2..])
+  else if test (count $argv) -gt 0; and not string match -q -- '-*' $argv[1]; and test "$argv[1]" != clone; and test "$argv[1]" != branch; and test "$argv[1]" != worktree
+    set result (gitnav jump $argv[1] $argv[2..])
   else
     set result (gitnav $argv)
   end
+  set -l exit_code $status
+
+  # The post_cd hook from [hooks], if gitnav wrote one
+  set -l hook
+  if test -n "$GITNAV_POST_CD_FILE"
+    set hook (cat $GITNAV_POST_CD_FILE)
+    rm -f -- $GITNAV_POST_CD_FILE
+  end
+
+  if test $exit_code -ne 0
+    test -n "$result"; and printf '%s\n' $result
+    return $exit_code
+  end
 
   if test -n "$result" -a -d "$result"
     cd "$result"; or return 1
+__POST_CD__  else if test -n "$result"
+    printf '%s\n' $result
+  end
+end
+"#
+    .replace("__CMD__", &options.cmd)
+    .replace(
+        "__POST_CD__",
+        &post_cd_block(options, FISH_LISTING, FISH_RUN_HOOK),
+    )
+}
 
-    # Optional: show a quick listing after cd
-    if command -q eza
-      eza -l
-    else
-      ls -la
-    end
+fn generate_fish_widget(key: &str) -> String {
+    r#"
+# Picker widget: the bound key opens the picker and changes to the picked
+# repository
+function _gitnav_widget
+  set -l result (gitnav)
+  if test $status -eq 0 -a -n "$result" -a -d "$result"
+    commandline -r -- "cd -- "(string escape -- $result)
+    commandline -f execute
   end
+  commandline -f repaint
 end
+bind KEY _gitnav_widget
+bind -M insert KEY _gitnav_widget
 "#
-    .to_string()
+    .replace("KEY", key)
 }
 
-fn generate_nushell_script() -> String {
+fn generate_nushell_script(options: &InitOptions) -> String {
     r#"# gitnav shell integration for nushell
 # Add this to your nushell config (typically ~/.config/nushell/config.nu):
 #   gitnav init nu | save --force ~/.cache/gitnav/init.nu
 #   source ~/.cache/gitnav/init.nu
+#
+# Exit codes are passed through: 0 = selected (cd), 130 = cancelled, other = error
 
-def --env gn [...args] {
-  # First non-flag argument is treated as an initial fzf query
-  let result = if ($args | length) > 0 and not ($args | first | str starts-with '-') {
-    (gitnav --query ($args | first) ...($args | skip 1) | str trim)
+def --env __CMD__ [...args] {
+  # A first non-flag argument jumps to the matching repo (picker if ambiguous);
+  # `__CMD__ clone <url>` clones and cds into the new repo, `__CMD__ branch` and
+  # `__CMD__ worktree` into the picked branch or worktree; `__CMD__ -` goes back
+  let out = if ($args | length) > 0 and ($args | first) == '-' {
+    (^gitnav back ...($args | skip 1) | complete)
+  } else if ($args | length) > 0 and not ($args | first | str starts-with '-') and ($args | first) != 'clone' and ($args | first) != 'branch' and ($args | first) != 'worktree' {
+    (^gitnav jump ($args | first) ...($args | skip 1) | complete)
   } else {
-    (gitnav ...$args | str trim)
+    (^gitnav ...$args | complete)
   }
+  let result = ($out.stdout | str trim)
 
-  if ($result != "") and ($result | path exists) {
-    cd $result
+  if $out.exit_code != 0 {
+    if ($out.stderr | is-not-empty) { print -e -n $out.stderr }
+    if $out.exit_code == 130 { return }
+    error make --unspanned { msg: $"gitnav exited with code ($out.exit_code)" }
+  }
 
-    # Optional: show a quick listing after cd
-    if (which eza | length) > 0 {
-      eza -l
-    } else {
-      ls
-    }
+  if ($out.stderr | is-not-empty) { print -e -n $out.stderr }
+
+  if ($result != "") and ($result | path exists) and (($result | path type) == "dir") {
+    cd $result
+__POST_CD__  } else if $result != "" {
+    print $result
   }
 }
 "#
-    .to_string()
+    .replace("__CMD__", &options.cmd)
+    .replace("__POST_CD__", &post_cd_block(options, NU_LISTING, ""))
 }
 
-fn generate_powershell_script() -> String {
+fn generate_powershell_script(options: &InitOptions) -> String {
     r#"# gitnav shell integration for PowerShell
 # Add this to your PowerShell profile ($PROFILE):
 #   Invoke-Expression (& gitnav init powershell)
+#
+# Exit codes are passed through: 0 = selected (cd), 130 = cancelled, other = error
 
-function gn {
-  # First non-flag argument is treated as an initial fzf query
-  $result = if ($args.Count -gt 0 -and -not $args[0].StartsWith('-')) {
-    & gitnav --query $args[0] @($args | Select-Object -Skip 1)
-  } else {
-    & gitnav @args
+function __CMD__ {
+  # A first non-flag argument jumps to the matching repo (picker if ambiguous);
+  # `__CMD__ clone <url>` clones and cds into the new repo, `__CMD__ branch` and
+  # `__CMD__ worktree` into the picked branch or worktree; `__CMD__ -` goes back
+  # Asks gitnav to write the post_cd hook to a file of its own, never stdout
+  $hookFile = (New-TemporaryFile).FullName
+  $env:GITNAV_SHELL = 'powershell'
+  $env:GITNAV_POST_CD_FILE = $hookFile
+  try {
+    $result = if ($args.Count -gt 0 -and $args[0] -eq '-') {
+      & gitnav back @($args | Select-Object -Skip 1)
+    } elseif ($args.Count -gt 0 -and -not $args[0].StartsWith('-') -and $args[0] -ne 'clone' -and $args[0] -ne 'branch' -and $args[0] -ne 'worktree') {
+      & gitnav jump $args[0] @($args | Select-Object -Skip 1)
+    } else {
+      & gitnav @args
+    }
+  } finally {
+    Remove-Item Env:GITNAV_SHELL, Env:GITNAV_POST_CD_FILE -ErrorAction SilentlyContinue
+  }
+  $exitCode = $LASTEXITCODE
+
+  # The post_cd hook from [hooks], if gitnav wrote one
+  $hook = Get-Content -Raw -LiteralPath $hookFile
+  Remove-Item -LiteralPath $hookFile -ErrorAction SilentlyContinue
+
+  if ($exitCode -ne 0) {
+    if ($result) { $result }
+    $global:LASTEXITCODE = $exitCode
+    return
   }
 
   if ($result -and (Test-Path $result -PathType Container)) {
     Set-Location $result
-
-    # Optional: show a quick listing after cd
-    if (Get-Command eza -ErrorAction SilentlyContinue) {
-      eza -l
-    } else {
-      Get-ChildItem
-    }
+__POST_CD__  } elseif ($result) {
+    $result
   }
 }
 "#
-    .to_string()
+    .replace("__CMD__", &options.cmd)
+    .replace(
+        "__POST_CD__",
+        &post_cd_block(options, POWERSHELL_LISTING, POWERSHELL_RUN_HOOK),
+    )
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::exit_codes::EXIT_INTERRUPTED;
 
     #[test]
     fn test_generate_init_script() {
-        assert!(generate_init_script("zsh").is_some());
-        assert!(generate_init_script("bash").is_some());
-        assert!(generate_init_script("fish").is_some());
-        assert!(generate_init_script("nu").is_some());
-        assert!(generate_init_script("nushell").is_some());
-        assert!(generate_init_script("powershell").is_some());
-        assert!(generate_init_script("pwsh").is_some());
-        assert!(generate_init_script("unknown").is_none());
+        let options = InitOptions::default();
+        for shell in ["zsh", "bash", "fish", "nu", "nushell", "powershell", "pwsh"] {
+            assert!(generate_init_script(shell, &options).is_some());
+        }
+        assert!(generate_init_script("unknown", &options).is_none());
+    }
+
+    #[test]
+    fn test_shell_from_program() {
+        assert_eq!(shell_from_program("/bin/zsh"), Some("zsh"));
+        assert_eq!(shell_from_program("-bash"), Some("bash"));
+        assert_eq!(shell_from_program("/usr/local/bin/fish\n"), Some("fish"));
+        assert_eq!(shell_from_program("nu"), Some("nu"));
+        assert_eq!(
+            shell_from_program("C:\\Program Files\\PowerShell\\7\\pwsh.exe"),
+            Some("powershell")
+        );
+        assert_eq!(shell_from_program("powershell.EXE"), Some("powershell"));
+        assert_eq!(shell_from_program("/bin/sh"), None);
+        assert_eq!(shell_from_program("cmd.exe"), None);
+        assert_eq!(shell_from_program(""), None);
     }
 
     #[test]
     fn test_zsh_script_contains_function() {
-        let script = generate_zsh_script();
+        let script = generate_zsh_script(&InitOptions::default());
         assert!(script.contains("gn()"));
         assert!(script.contains("gitnav"));
     }
 
     #[test]
-    fn test_all_shells_contain_query_flag() {
+    fn test_all_shells_jump_on_positional_query() {
         for shell in &["zsh", "bash", "fish", "nu", "powershell"] {
-            let script = generate_init_script(shell).unwrap();
+            let script = generate_init_script(shell, &InitOptions::default()).unwrap();
             assert!(
-                script.contains("--query"),
-                "Shell '{}' script missing --query support",
+                script.contains("gitnav jump"),
+                "Shell '{}' script missing jump support",
                 shell
             );
         }
@@ -203,19 +646,250 @@ mod tests {
     #[test]
     fn test_all_shells_handle_non_flag_positional() {
         // zsh/bash: detect "$1" not starting with -
-        let zsh = generate_zsh_script();
+        let zsh = generate_zsh_script(&InitOptions::default());
         assert!(zsh.contains("\"$1\" != -*") || zsh.contains("[[ \"$1\" != -*"));
         // fish: string match pattern
-        let fish = generate_fish_script();
+        let fish = generate_fish_script(&InitOptions::default());
         assert!(fish.contains("not string match") || fish.contains("'-*'"));
         // powershell: StartsWith('-')
-        let ps = generate_powershell_script();
+        let ps = generate_powershell_script(&InitOptions::default());
         assert!(ps.contains("StartsWith"));
     }
 
+    #[test]
+    fn test_all_shells_pass_clone_through() {
+        for shell in &["zsh", "bash", "fish", "nu", "powershell"] {
+            let script = generate_init_script(shell, &InitOptions::default()).unwrap();
+            assert!(
+                ["!= clone", "!= 'clone'", "-ne 'clone'"]
+                    .iter()
+                    .any(|check| script.contains(check)),
+                "Shell '{}' script sends `gn clone` to jump",
+                shell
+            );
+        }
+    }
+
+    #[test]
+    fn test_all_shells_pass_branch_and_worktree_through() {
+        for shell in &["zsh", "bash", "fish", "nu", "powershell"] {
+            let script = generate_init_script(shell, &InitOptions::default()).unwrap();
+            for command in ["branch", "worktree"] {
+                assert!(
+                    [
+                        format!("!= {}", command),
+                        format!("!= '{}'", command),
+                        format!("-ne '{}'", command),
+                    ]
+                    .iter()
+                    .any(|check| script.contains(check.as_str())),
+                    "Shell '{}' script sends `gn {}` to jump",
+                    shell,
+                    command
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_all_shells_map_dash_to_back() {
+        for shell in &["zsh", "bash", "fish", "nu", "powershell"] {
+            let script = generate_init_script(shell, &InitOptions::default()).unwrap();
+            assert!(
+                script.contains("gitnav back"),
+                "Shell '{}' script missing `gn -` support",
+                shell
+            );
+        }
+    }
+
+    #[test]
+    fn test_all_shells_propagate_exit_code() {
+        for shell in &["zsh", "bash", "fish", "nu", "powershell"] {
+            let script = generate_init_script(shell, &InitOptions::default()).unwrap();
+            assert!(
+                script.contains(&EXIT_INTERRUPTED.to_string()),
+                "Shell '{}' script does not document the cancel exit code",
+                shell
+            );
+        }
+        assert!(generate_zsh_script(&InitOptions::default()).contains("return $exit_code"));
+        assert!(generate_bash_script(&InitOptions::default()).contains("return $exit_code"));
+        assert!(generate_fish_script(&InitOptions::default()).contains("return $exit_code"));
+        assert!(generate_nushell_script(&InitOptions::default()).contains("$out.exit_code != 0"));
+        assert!(generate_powershell_script(&InitOptions::default())
+            .contains("$global:LASTEXITCODE = $exitCode"));
+    }
+
+    #[test]
+    fn test_posix_shells_check_exit_before_cd() {
+        for script in [
+            generate_zsh_script(&InitOptions::default()),
+            generate_bash_script(&InitOptions::default()),
+        ] {
+            let check = script.find("exit_code -ne 0").unwrap();
+            let cd = script.find("cd \"$result\"").unwrap();
+            assert!(check < cd, "exit code must be checked before cd");
+        }
+    }
+
+    #[test]
+    fn test_wrappers_run_post_cd_hook() {
+        for shell in ["zsh", "bash", "fish", "powershell"] {
+            let script = generate_init_script(shell, &InitOptions::default()).unwrap();
+            assert!(!script.contains("__SHELL__") && !script.contains("__READ_HOOK__"));
+            assert!(script.contains(crate::hooks::SHELL_ENV), "{}", shell);
+            assert!(script.contains(crate::hooks::POST_CD_FILE_ENV), "{}", shell);
+        }
+        // nushell cannot evaluate code, so it does not ask for the hook
+        let nu = generate_nushell_script(&InitOptions::default());
+        assert!(!nu.contains(crate::hooks::SHELL_ENV));
+    }
+
+    /// Run the bash wrapper as `gn` in `dir`, with `gitnav` replaced by the
+    /// function `gitnav_stub`. Returns `None` when bash is not installed.
+    #[cfg(unix)]
+    fn run_bash_wrapper(dir: &std::path::Path, gitnav_stub: &str) -> Option<String> {
+        let options = InitOptions {
+            ls: false,
+            ..InitOptions::default()
+        };
+        let script = format!(
+            "{}\ngitnav() {{\n{}\n}}\ngn && pwd",
+            generate_bash_script(&options),
+            gitnav_stub
+        );
+        let output = std::process::Command::new("bash")
+            .args(["--norc", "-c", &script])
+            .current_dir(dir)
+            .output()
+            .ok()?;
+        assert!(output.status.success(), "{:?}", output);
+        Some(String::from_utf8(output.stdout).unwrap())
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_bash_wrapper_runs_hook_from_file() {
+        let tmp = tempfile::tempdir().unwrap();
+        let repo = tmp.path().join("api");
+        std::fs::create_dir(&repo).unwrap();
+
+        let stub = format!(
+            "printf 'echo hooked in \"$PWD\"\\n' > \"$GITNAV_POST_CD_FILE\"\n\
+             echo \"$GITNAV_POST_CD_FILE\" > {0}/hook-file\n\
+             printf '%s\\n' {0}/api",
+            tmp.path().display()
+        );
+        let Some(stdout) = run_bash_wrapper(tmp.path(), &stub) else {
+            return;
+        };
+        let repo = repo.display();
+        assert_eq!(stdout, format!("hooked in {0}\n{0}\n", repo));
+
+        // The wrapper deletes the hook file once it has read it
+        let hook_file = std::fs::read_to_string(tmp.path().join("hook-file")).unwrap();
+        assert!(!std::path::Path::new(hook_file.trim()).exists());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_bash_wrapper_never_runs_path_as_code() {
+        let tmp = tempfile::tempdir().unwrap();
+        let pwned = tmp.path().join("pwned");
+        // A directory name that looks like the old hook marker and a command
+        let evil = tmp
+            .path()
+            .join(format!("repo\n# gitnav post-cd\ntouch {}", pwned.display()));
+        std::fs::create_dir_all(tmp.path().join("repo")).unwrap();
+        std::fs::create_dir_all(&evil).unwrap();
+        std::fs::write(
+            tmp.path().join("selection"),
+            format!("{}\n", evil.display()),
+        )
+        .unwrap();
+
+        let stub = format!("cat {}/selection", tmp.path().display());
+        let Some(stdout) = run_bash_wrapper(tmp.path(), &stub) else {
+            return;
+        };
+        assert!(!pwned.exists(), "the path was run as a hook");
+        assert_eq!(stdout, format!("{}\n", evil.display()));
+    }
+
+    #[test]
+    fn test_init_options_customize_wrapper() {
+        let options = InitOptions {
+            cmd: "j".to_string(),
+            ls: false,
+            post_cd: Some("git status -sb".to_string()),
+        };
+        for shell in ["zsh", "bash", "fish", "nu", "powershell"] {
+            let script = generate_init_script(shell, &options).unwrap();
+            assert!(!script.contains("__CMD__") && !script.contains("__POST_CD__"));
+            assert!(
+                !script.contains("eza"),
+                "{} still lists the directory",
+                shell
+            );
+            assert!(script.contains("    git status -sb\n"), "{}", shell);
+            assert!(!script.contains("gn"), "{} still mentions gn", shell);
+        }
+        assert!(generate_zsh_script(&options).contains("\nj() {"));
+        assert!(generate_fish_script(&options).contains("\nfunction j\n"));
+
+        let default = generate_bash_script(&InitOptions::default());
+        assert!(default.contains("\ngn() {"));
+        assert!(default.contains("eza -l"));
+        assert!(!default.contains("--post-cd"));
+    }
+
+    #[test]
+    fn test_parse_cmd_name() {
+        assert_eq!(parse_cmd_name("j"), Ok("j".to_string()));
+        assert_eq!(parse_cmd_name("go-repo_2"), Ok("go-repo_2".to_string()));
+        assert!(parse_cmd_name("").is_err());
+        assert!(parse_cmd_name("-j").is_err());
+        assert!(parse_cmd_name("j; rm").is_err());
+        assert!(parse_cmd_name("gitnav").is_err());
+    }
+
+    #[test]
+    fn test_widget_key_parse() {
+        assert_eq!(WidgetKey::parse("ctrl-g"), Ok(WidgetKey::default()));
+        assert_eq!(
+            WidgetKey::parse("Alt-1"),
+            Ok(WidgetKey {
+                alt: true,
+                key: '1'
+            })
+        );
+        assert!(WidgetKey::parse("ctrl-1").is_err());
+        assert!(WidgetKey::parse("ctrl-gg").is_err());
+        assert!(WidgetKey::parse("f5").is_err());
+    }
+
+    #[test]
+    fn test_widget_binds_key_per_shell() {
+        let ctrl_g = WidgetKey::default();
+        let alt_j = WidgetKey::parse("alt-j").unwrap();
+        assert!(generate_widget("zsh", ctrl_g)
+            .unwrap()
+            .contains("bindkey '^G' _gitnav_widget"));
+        assert!(generate_widget("zsh", alt_j).unwrap().contains("'^[j'"));
+        assert!(generate_widget("bash", ctrl_g)
+            .unwrap()
+            .contains(r#"bind -x '"\C-g": _gitnav_widget'"#));
+        assert!(generate_widget("fish", alt_j)
+            .unwrap()
+            .contains(r"bind \ej _gitnav_widget"));
+        assert_eq!(generate_widget("nu", ctrl_g), None);
+        assert_eq!(generate_widget("powershell", ctrl_g), None);
+    }
+
     #[test]
     fn test_powershell_script_contains_function() {
-        let script = generate_powershell_script();
+        let script = generate_powershell_script(&InitOptions::default());
         assert!(script.contains("function gn"));
         assert!(script.contains("gitnav"));
     }
@@ -0,0 +1,404 @@
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::time::{Duration, Instant};
+
+/// How long a probe shell may take to load its rc files before it is killed.
+const PROBE_TIMEOUT: Duration = Duration::from_secs(10);
+
+const KIND_MARKER: &str = "__GITNAV_KIND__=";
+const BIN_MARKER: &str = "__GITNAV_BIN__=";
+const BODY_START: &str = "__GITNAV_BODY_START__";
+const BODY_END: &str = "__GITNAV_BODY_END__";
+
+/// Result of a single shell integration check.
+#[derive(Debug, Clone)]
+pub struct Check {
+    pub label: String,
+    pub passed: bool,
+    /// Precise steps to fix a failed check
+    pub remedy: Option<String>,
+}
+
+impl Check {
+    fn pass(label: impl Into<String>) -> Self {
+        Self {
+            label: label.into(),
+            passed: true,
+            remedy: None,
+        }
+    }
+
+    fn fail(label: impl Into<String>, remedy: impl Into<String>) -> Self {
+        Self {
+            label: label.into(),
+            passed: false,
+            remedy: Some(remedy.into()),
+        }
+    }
+}
+
+/// What a freshly started shell knows about the wrapper (`gn`) and `gitnav`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Probe {
+    /// Command kind of the wrapper as reported by the shell ("function", "alias", ...), empty if undefined
+    pub kind: String,
+    /// Definition of the wrapper when it is a function
+    pub body: String,
+    /// `gitnav` binary found on the shell's PATH
+    pub gitnav: Option<PathBuf>,
+}
+
+/// Shell program, arguments, and probe script for a supported shell.
+///
+/// Each probe starts the shell the way a new terminal would (loading its rc
+/// files) and prints marker lines describing the wrapper `cmd` and the `gitnav`
+/// on PATH.
+fn probe_command(shell: &str, cmd: &str) -> Option<(&'static str, Vec<String>)> {
+    let (program, args) = probe_template(shell)?;
+    let args = args
+        .into_iter()
+        .map(|arg| arg.replace("__CMD__", cmd))
+        .collect();
+    Some((program, args))
+}
+
+fn probe_template(shell: &str) -> Option<(&'static str, Vec<&'static str>)> {
+    match shell {
+        "zsh" => Some((
+            "zsh",
+            vec![
+                "-i",
+                "-c",
+                r#"k=$(whence -w __CMD__); echo "__GITNAV_KIND__=${k#*: }"; echo __GITNAV_BODY_START__; whence -f __CMD__; echo __GITNAV_BODY_END__; echo "__GITNAV_BIN__=$(whence -p gitnav)""#,
+            ],
+        )),
+        "bash" => Some((
+            "bash",
+            vec![
+                "-i",
+                "-c",
+                r#"echo "__GITNAV_KIND__=$(type -t __CMD__)"; echo __GITNAV_BODY_START__; declare -f __CMD__; echo __GITNAV_BODY_END__; echo "__GITNAV_BIN__=$(type -P gitnav)""#,
+            ],
+        )),
+        "fish" => Some((
+            "fish",
+            vec![
+                "-i",
+                "-c",
+                r#"if functions -q __CMD__; echo __GITNAV_KIND__=function; else; echo __GITNAV_KIND__=(type -t __CMD__ 2>/dev/null); end; echo __GITNAV_BODY_START__; functions __CMD__ 2>/dev/null; echo __GITNAV_BODY_END__; echo __GITNAV_BIN__=(command -s gitnav)"#,
+            ],
+        )),
+        "nu" | "nushell" => Some((
+            "nu",
+            vec![
+                "-l",
+                "-c",
+                r#"let c = (which __CMD__); print $"__GITNAV_KIND__=(if ($c | is-empty) { '' } else { $c.0.type })"; print __GITNAV_BODY_START__; try { print (view source __CMD__) }; print __GITNAV_BODY_END__; let g = (which -a gitnav | where type == external); print $"__GITNAV_BIN__=(if ($g | is-empty) { '' } else { $g.0.path })""#,
+            ],
+        )),
+        "powershell" | "pwsh" => Some((
+            "pwsh",
+            vec![
+                "-NoLogo",
+                "-Command",
+                r#"$c = Get-Command __CMD__ -ErrorAction SilentlyContinue; "__GITNAV_KIND__=" + $(if ($c) { $c.CommandType.ToString().ToLower() } else { '' }); '__GITNAV_BODY_START__'; if ($c -and $c.CommandType -eq 'Function') { $c.Definition }; '__GITNAV_BODY_END__'; $g = Get-Command gitnav -CommandType Application -ErrorAction SilentlyContinue | Select-Object -First 1; "__GITNAV_BIN__=" + $(if ($g) { $g.Source } else { '' })"#,
+            ],
+        )),
+        _ => None,
+    }
+}
+
+/// Start the shell with its rc files loaded and report what it sees.
+///
+/// # Errors
+///
+/// Returns a message if the shell is unsupported, cannot be started, or does
+/// not finish loading within a few seconds.
+pub fn run_probe(shell: &str, cmd: &str) -> std::result::Result<Probe, String> {
+    let (program, args) =
+        probe_command(shell, cmd).ok_or_else(|| format!("unsupported shell '{}'", shell))?;
+
+    let mut child = Command::new(program)
+        .args(&args)
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()
+        .map_err(|e| format!("could not start {}: {}", program, e))?;
+
+    // Drain stdout on a separate thread so chatty rc files cannot fill the pipe
+    let mut stdout = child.stdout.take();
+    let reader = std::thread::spawn(move || {
+        let mut output = String::new();
+        if let Some(ref mut stdout) = stdout {
+            let _ = stdout.read_to_string(&mut output);
+        }
+        output
+    });
+
+    let started = Instant::now();
+    loop {
+        match child.try_wait() {
+            Ok(Some(_)) => break,
+            Ok(None) if started.elapsed() > PROBE_TIMEOUT => {
+                let _ = child.kill();
+                return Err(format!(
+                    "{} did not finish loading its startup files within {}s",
+                    program,
+                    PROBE_TIMEOUT.as_secs()
+                ));
+            }
+            Ok(None) => std::thread::sleep(Duration::from_millis(50)),
+            Err(e) => return Err(format!("failed waiting for {}: {}", program, e)),
+        }
+    }
+
+    let output = reader.join().unwrap_or_default();
+    Ok(parse_probe(&output))
+}
+
+/// Parse the marker lines printed by a probe script.
+pub fn parse_probe(output: &str) -> Probe {
+    let mut probe = Probe::default();
+    let mut in_body = false;
+    let mut body = Vec::new();
+
+    for line in output.lines() {
+        let line = line.trim_end_matches('\r');
+        if line == BODY_START {
+            in_body = true;
+        } else if line == BODY_END {
+            in_body = false;
+        } else if in_body {
+            body.push(line);
+        } else if let Some(kind) = line.strip_prefix(KIND_MARKER) {
+            probe.kind = kind.trim().to_lowercase();
+        } else if let Some(bin) = line.strip_prefix(BIN_MARKER) {
+            let bin = bin.trim();
+            if !bin.is_empty() {
+                probe.gitnav = Some(PathBuf::from(bin));
+            }
+        }
+    }
+
+    probe.body = body.join("\n");
+    probe
+}
+
+/// Startup file and line that load the integration for a shell.
+pub fn setup_hint(shell: &str) -> (&'static str, &'static str) {
+    match shell {
+        "zsh" => ("~/.zshrc", r#"eval "$(gitnav init zsh)""#),
+        "bash" => ("~/.bashrc", r#"eval "$(gitnav init bash)""#),
+        "fish" => ("~/.config/fish/config.fish", "gitnav init fish | source"),
+        "nu" | "nushell" => (
+            "your nushell config.nu",
+            "source ~/.cache/gitnav/init.nu   # after: gitnav init nu | save --force ~/.cache/gitnav/init.nu",
+        ),
+        _ => ("$PROFILE", "Invoke-Expression (& gitnav init powershell)"),
+    }
+}
+
+/// Evaluate a probe against the expected integration.
+///
+/// # Arguments
+///
+/// * `shell` - Shell that was probed
+/// * `cmd` - Name of the wrapper function (`gn` unless `--cmd` renamed it)
+/// * `probe` - What the shell reported
+/// * `this_exe` - Path of the running gitnav binary
+pub fn evaluate(shell: &str, cmd: &str, probe: &Probe, this_exe: &Path) -> Vec<Check> {
+    let (rc_file, line) = setup_hint(shell);
+    let load = format!(
+        "Add this line to {}:\n    {}\n  then open a new terminal (or re-source {}).",
+        rc_file, line, rc_file
+    );
+    let mut checks = Vec::new();
+
+    match probe.kind.as_str() {
+        "function" | "custom" => {
+            checks.push(Check::pass(format!("`{}` is defined as a shell function", cmd)));
+            let changes_dir = probe.body.contains("cd ")
+                || probe.body.contains("cd\t")
+                || probe.body.contains("Set-Location");
+            if !probe.body.contains("gitnav") {
+                checks.push(Check::fail(
+                    format!("`{}` does not call gitnav", cmd),
+                    format!(
+                        "Another tool defines a `{}` function. Rename or remove it, then:\n  {}",
+                        cmd, load
+                    ),
+                ));
+            } else if !changes_dir {
+                checks.push(Check::fail(
+                    format!("`{}` calls gitnav but never changes directory", cmd),
+                    format!(
+                        "This is not gitnav's wrapper (it prints the path instead of cd-ing into it). Replace it:\n  {}",
+                        load
+                    ),
+                ));
+            } else {
+                checks.push(Check::pass(format!(
+                    "`{}` runs gitnav and changes into the selection",
+                    cmd
+                )));
+            }
+        }
+        "alias" => checks.push(Check::fail(
+            format!("`{}` is an alias, not gitnav's function", cmd),
+            format!(
+                "An alias can print the selected path but cannot cd into it. Remove the alias (`unalias {}` and its definition in {}), then:\n  {}",
+                cmd, rc_file, load
+            ),
+        )),
+        "" | "none" => checks.push(Check::fail(
+            format!("`{}` is not defined in new shell sessions", cmd),
+            format!(
+                "Note: running `gitnav init {}` only prints the script; it has to be evaluated by your shell.\n  {}",
+                shell, load
+            ),
+        )),
+        other => checks.push(Check::fail(
+            format!("`{}` resolves to a {}, not gitnav's function", cmd, other),
+            format!(
+                "Something else named `{}` is on your PATH. Rename it or load gitnav's function after it:\n  {}",
+                cmd, load
+            ),
+        )),
+    }
+
+    let this_exe = this_exe
+        .canonicalize()
+        .unwrap_or_else(|_| this_exe.to_path_buf());
+    match &probe.gitnav {
+        None => checks.push(Check::fail(
+            "`gitnav` is not on PATH in new shell sessions",
+            format!(
+                "`{}` calls `gitnav` by name. Add {} to PATH in {}.",
+                cmd,
+                this_exe
+                    .parent()
+                    .map(|p| p.display().to_string())
+                    .unwrap_or_else(|| "the directory containing gitnav".to_string()),
+                rc_file
+            ),
+        )),
+        Some(found) => {
+            let found_canonical = found.canonicalize().unwrap_or_else(|_| found.clone());
+            if found_canonical == this_exe {
+                checks.push(Check::pass(format!(
+                    "`gitnav` on PATH is this binary ({})",
+                    found.display()
+                )));
+            } else {
+                checks.push(Check::fail(
+                    format!(
+                        "`gitnav` on PATH is {}, not this binary ({})",
+                        found.display(),
+                        this_exe.display()
+                    ),
+                    format!(
+                        "`{}` will run the other binary. Remove the old installation or put this binary's directory earlier in PATH.",
+                        cmd
+                    ),
+                ));
+            }
+        }
+    }
+
+    checks
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::shell::{generate_init_script, InitOptions};
+
+    fn probe(kind: &str, body: &str, gitnav: Option<&Path>) -> Probe {
+        Probe {
+            kind: kind.to_string(),
+            body: body.to_string(),
+            gitnav: gitnav.map(Path::to_path_buf),
+        }
+    }
+
+    #[test]
+    fn test_parse_probe() {
+        let output = "noise from .zshrc\n__GITNAV_KIND__=function\n__GITNAV_BODY_START__\ngn () {\n  cd \"$result\"\n}\n__GITNAV_BODY_END__\n__GITNAV_BIN__=/usr/bin/gitnav\n";
+        let parsed = parse_probe(output);
+        assert_eq!(parsed.kind, "function");
+        assert!(parsed.body.contains("cd \"$result\""));
+        assert_eq!(parsed.gitnav, Some(PathBuf::from("/usr/bin/gitnav")));
+
+        let missing = parse_probe(
+            "__GITNAV_KIND__=\n__GITNAV_BODY_START__\n__GITNAV_BODY_END__\n__GITNAV_BIN__=\n",
+        );
+        assert_eq!(missing, Probe::default());
+    }
+
+    #[test]
+    fn test_evaluate_accepts_generated_wrapper() {
+        let exe = std::env::current_exe().unwrap();
+        for shell in ["zsh", "bash", "fish", "nu", "powershell"] {
+            let script = generate_init_script(shell, &InitOptions::default()).unwrap();
+            let checks = evaluate(shell, "gn", &probe("function", &script, Some(&exe)), &exe);
+            assert!(checks.iter().all(|c| c.passed), "{}: {:?}", shell, checks);
+        }
+    }
+
+    #[test]
+    fn test_probe_and_evaluate_use_custom_name() {
+        let (_, args) = probe_command("bash", "j").unwrap();
+        assert!(args[2].contains("type -t j)"));
+        assert!(!args[2].contains("__CMD__"));
+
+        let exe = std::env::current_exe().unwrap();
+        let options = InitOptions {
+            cmd: "j".to_string(),
+            ..InitOptions::default()
+        };
+        let script = generate_init_script("zsh", &options).unwrap();
+        let checks = evaluate("zsh", "j", &probe("function", &script, Some(&exe)), &exe);
+        assert!(checks.iter().all(|c| c.passed), "{:?}", checks);
+        assert_eq!(checks[0].label, "`j` is defined as a shell function");
+    }
+
+    #[test]
+    fn test_evaluate_flags_alias_and_missing_function() {
+        let exe = std::env::current_exe().unwrap();
+
+        let checks = evaluate("zsh", "gn", &probe("alias", "", Some(&exe)), &exe);
+        assert!(!checks[0].passed);
+        assert!(checks[0].remedy.as_ref().unwrap().contains("unalias gn"));
+
+        let checks = evaluate("bash", "gn", &probe("", "", Some(&exe)), &exe);
+        assert!(!checks[0].passed);
+        assert!(checks[0]
+            .remedy
+            .as_ref()
+            .unwrap()
+            .contains("eval \"$(gitnav init bash)\""));
+    }
+
+    #[test]
+    fn test_evaluate_flags_wrapper_without_cd() {
+        let exe = std::env::current_exe().unwrap();
+        let body = "gn () {\n  gitnav \"$@\"\n}";
+        let checks = evaluate("bash", "gn", &probe("function", body, Some(&exe)), &exe);
+        assert!(!checks[1].passed);
+        assert!(checks[1].label.contains("never changes directory"));
+    }
+
+    #[test]
+    fn test_evaluate_flags_other_gitnav_binary() {
+        let exe = std::env::current_exe().unwrap();
+        let other = Path::new("/nonexistent/bin/gitnav");
+        let script = generate_init_script("zsh", &InitOptions::default()).unwrap();
+        let checks = evaluate("zsh", "gn", &probe("function", &script, Some(other)), &exe);
+        assert!(!checks.last().unwrap().passed);
+
+        let checks = evaluate("zsh", "gn", &probe("function", &script, None), &exe);
+        assert!(checks.last().unwrap().label.contains("not on PATH"));
+    }
+}
@@ -0,0 +1,346 @@
+use anyhow::{bail, Context, Result};
+use rusqlite::{params, Connection, OptionalExtension, Transaction, TransactionBehavior};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+/// Schema changes of the data directory's database, applied in order.
+/// `schema_version` in the `meta` table is the number applied so far, so a new
+/// version only ever appends here.
+const DATA_MIGRATIONS: &[&str] = &[
+    // 1: navigation history, pins, and tags
+    "CREATE TABLE visits (
+         id INTEGER PRIMARY KEY,
+         profile TEXT NOT NULL,
+         path TEXT NOT NULL,
+         visited_at INTEGER NOT NULL
+     );
+     CREATE INDEX visits_by_profile ON visits (profile);
+     CREATE TABLE pins (
+         path TEXT PRIMARY KEY,
+         position INTEGER NOT NULL
+     );
+     CREATE TABLE tags (
+         path TEXT NOT NULL,
+         tag TEXT NOT NULL,
+         PRIMARY KEY (path, tag)
+     );",
+];
+
+/// Schema changes of the cache directory's database, as for [`DATA_MIGRATIONS`].
+const CACHE_MIGRATIONS: &[&str] = &[
+    // 1: scan results
+    "CREATE TABLE scans (
+         profile TEXT NOT NULL,
+         scan_key TEXT NOT NULL,
+         scanned_at INTEGER NOT NULL,
+         PRIMARY KEY (profile, scan_key)
+     );
+     CREATE TABLE repos (
+         profile TEXT NOT NULL,
+         scan_key TEXT NOT NULL,
+         position INTEGER NOT NULL,
+         path TEXT NOT NULL,
+         name TEXT NOT NULL,
+         branch TEXT,
+         last_commit INTEGER,
+         dirty INTEGER NOT NULL,
+         PRIMARY KEY (profile, scan_key, path)
+     );",
+];
+
+/// How long to wait for another gitnav process (say, the daemon) holding the
+/// write lock before giving up.
+const BUSY_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// A SQLite database of gitnav's state.
+///
+/// Navigation history, pins, and tags live in the gitnav data directory
+/// (`<data_dir>/gitnav/state.db`) next to the flat files earlier versions
+/// used. Each of those is imported the first time the module that owns it
+/// opens the database, and is left in place so an older gitnav keeps working.
+///
+/// Scan results live in their own database in the cache directory
+/// (`<cache dir>/scans.db`, see [`State::open_cache`]), so `cache.directory`
+/// moves them and the data directory is not rewritten on every scan.
+#[derive(Debug)]
+pub struct State {
+    conn: Connection,
+    file: PathBuf,
+}
+
+impl State {
+    /// Get the default database path (`<data_dir>/gitnav/state.db`).
+    pub fn default_path() -> Result<PathBuf> {
+        dirs::data_dir()
+            .map(|p| p.join("gitnav").join("state.db"))
+            .ok_or_else(|| anyhow::anyhow!("Could not determine data directory"))
+    }
+
+    /// Open the database at the default location.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the data directory cannot be determined or the
+    /// database cannot be opened
+    pub fn open_default() -> Result<Self> {
+        Self::open(&Self::default_path()?)
+    }
+
+    /// Open (creating if needed) the data database at `file` and bring its
+    /// schema up to date.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the database cannot be opened or migrated, or was
+    /// written by a newer gitnav
+    pub fn open(file: &Path) -> Result<Self> {
+        Self::open_with(file, DATA_MIGRATIONS)
+    }
+
+    /// Open (creating if needed) the scan database in `cache_dir`, shared by
+    /// every profile, and bring its schema up to date.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the database cannot be opened or migrated, or was
+    /// written by a newer gitnav
+    pub fn open_cache(cache_dir: &Path) -> Result<Self> {
+        Self::open_with(&cache_dir.join("scans.db"), CACHE_MIGRATIONS)
+    }
+
+    fn open_with(file: &Path, migrations: &[&str]) -> Result<Self> {
+        if let Some(parent) = file.parent() {
+            fs::create_dir_all(parent).with_context(|| {
+                format!("Failed to create data directory: {}", parent.display())
+            })?;
+        }
+        let mut conn = Connection::open(file)
+            .with_context(|| format!("Failed to open state database: {}", file.display()))?;
+        conn.busy_timeout(BUSY_TIMEOUT)?;
+        // Readers (the picker) never wait for a writer (the daemon)
+        conn.pragma_update_and_check(None, "journal_mode", "WAL", |_| Ok(()))?;
+        migrate(&mut conn, migrations)
+            .with_context(|| format!("Failed to migrate state database: {}", file.display()))?;
+
+        Ok(Self {
+            conn,
+            file: file.to_path_buf(),
+        })
+    }
+
+    /// A flat file from an earlier gitnav version, which sits next to the database.
+    pub fn legacy_file(&self, name: &str) -> PathBuf {
+        self.file.with_file_name(name)
+    }
+
+    pub(crate) fn conn(&self) -> &Connection {
+        &self.conn
+    }
+
+    /// Start a transaction that takes the write lock at once, so two gitnav
+    /// processes never interleave their changes.
+    pub(crate) fn write(&self) -> Result<Transaction<'_>> {
+        Ok(Transaction::new_unchecked(
+            &self.conn,
+            TransactionBehavior::Immediate,
+        )?)
+    }
+
+    /// Import the flat file `file` with `import`, unless it was imported before.
+    ///
+    /// A missing file counts as imported, since from then on the data lives in
+    /// the database.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file exists but cannot be read, or the import fails
+    pub(crate) fn import_once(
+        &self,
+        file: &Path,
+        import: impl FnOnce(&Transaction, &str) -> rusqlite::Result<()>,
+    ) -> Result<()> {
+        let key = format!("imported:{}", file.display());
+        if meta(&self.conn, &key)?.is_some() {
+            return Ok(());
+        }
+
+        let tx = self.write()?;
+        // Another process may have imported it while we waited for the lock
+        if meta(&tx, &key)?.is_some() {
+            return Ok(());
+        }
+        match fs::read_to_string(file) {
+            Ok(contents) => import(&tx, &contents)
+                .with_context(|| format!("Failed to import {}", file.display()))?,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+            Err(e) => {
+                return Err(e).with_context(|| format!("Failed to read {}", file.display()));
+            }
+        }
+        set_meta(&tx, &key, "1")?;
+        tx.commit()?;
+        Ok(())
+    }
+}
+
+/// Bring the schema up to `migrations.len()`.
+///
+/// The version is read first without a lock, so opening an up-to-date
+/// database never waits for a writer; the write lock is only taken to migrate.
+fn migrate(conn: &mut Connection, migrations: &[&str]) -> Result<()> {
+    if check_version(schema_version(conn)?, migrations)? == migrations.len() {
+        return Ok(());
+    }
+
+    let tx = conn.transaction_with_behavior(TransactionBehavior::Immediate)?;
+    tx.execute_batch(
+        "CREATE TABLE IF NOT EXISTS meta (key TEXT PRIMARY KEY, value TEXT NOT NULL)",
+    )?;
+    // Another process may have migrated while we waited for the lock
+    let version = check_version(schema_version(&tx)?, migrations)?;
+    if version == migrations.len() {
+        return Ok(());
+    }
+
+    for sql in &migrations[version..] {
+        tx.execute_batch(sql)?;
+    }
+    set_meta(&tx, "schema_version", &migrations.len().to_string())?;
+    tx.commit()?;
+    Ok(())
+}
+
+/// The schema version recorded in `meta`, or `None` for a new database.
+fn schema_version(conn: &Connection) -> Result<Option<usize>> {
+    let has_meta: bool = conn.query_row(
+        "SELECT EXISTS (SELECT 1 FROM sqlite_master WHERE type = 'table' AND name = 'meta')",
+        [],
+        |row| row.get(0),
+    )?;
+    if !has_meta {
+        return Ok(None);
+    }
+    meta(conn, "schema_version")?
+        .map(|v| v.parse())
+        .transpose()
+        .context("Invalid schema_version")
+}
+
+/// `version` (0 for a new database), unless it is newer than this gitnav.
+fn check_version(version: Option<usize>, migrations: &[&str]) -> Result<usize> {
+    let version = version.unwrap_or(0);
+    if version > migrations.len() {
+        bail!(
+            "Schema version {} is newer than this gitnav supports ({}); upgrade gitnav",
+            version,
+            migrations.len()
+        );
+    }
+    Ok(version)
+}
+
+fn meta(conn: &Connection, key: &str) -> Result<Option<String>> {
+    Ok(conn
+        .query_row("SELECT value FROM meta WHERE key = ?1", [key], |row| {
+            row.get(0)
+        })
+        .optional()?)
+}
+
+fn set_meta(conn: &Connection, key: &str, value: &str) -> Result<()> {
+    conn.execute(
+        "INSERT OR REPLACE INTO meta (key, value) VALUES (?1, ?2)",
+        params![key, value],
+    )?;
+    Ok(())
+}
+
+/// How a path is stored in a `TEXT` column.
+pub(crate) fn path_text(path: &Path) -> String {
+    path.to_string_lossy().into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_open_creates_current_schema_once() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("data").join("state.db");
+
+        let state = State::open(&file).unwrap();
+        let version = DATA_MIGRATIONS.len().to_string();
+        assert_eq!(
+            meta(state.conn(), "schema_version").unwrap(),
+            Some(version.clone())
+        );
+        drop(state);
+
+        let state = State::open(&file).unwrap();
+        assert_eq!(meta(state.conn(), "schema_version").unwrap(), Some(version));
+    }
+
+    #[test]
+    fn test_open_refuses_newer_schema() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("state.db");
+        let state = State::open(&file).unwrap();
+        set_meta(state.conn(), "schema_version", "999").unwrap();
+        drop(state);
+
+        let err = State::open(&file).unwrap_err();
+        assert!(format!("{:#}", err).contains("upgrade gitnav"), "{:#}", err);
+    }
+
+    #[test]
+    fn test_open_does_not_wait_for_a_writer() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("state.db");
+        drop(State::open(&file).unwrap());
+
+        let writer = State::open(&file).unwrap();
+        let tx = writer.write().unwrap();
+        tx.execute(
+            "INSERT INTO pins (path, position) VALUES ('/src/api', 0)",
+            [],
+        )
+        .unwrap();
+
+        let started = std::time::Instant::now();
+        let reader = State::open(&file).unwrap();
+        assert!(started.elapsed() < BUSY_TIMEOUT / 2);
+        let pins: i64 = reader
+            .conn()
+            .query_row("SELECT COUNT(*) FROM pins", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(pins, 0);
+        tx.commit().unwrap();
+    }
+
+    #[test]
+    fn test_import_once() {
+        let dir = tempfile::tempdir().unwrap();
+        let state = State::open(&dir.path().join("state.db")).unwrap();
+        let legacy = state.legacy_file("pinned");
+        assert_eq!(legacy, dir.path().join("pinned"));
+        fs::write(&legacy, "/src/api\n").unwrap();
+
+        let mut imported = Vec::new();
+        for _ in 0..2 {
+            state
+                .import_once(&legacy, |_, contents| {
+                    imported.push(contents.to_string());
+                    Ok(())
+                })
+                .unwrap();
+        }
+        assert_eq!(imported, vec!["/src/api\n"]);
+
+        let missing = state.legacy_file("tags");
+        state
+            .import_once(&missing, |_, _| panic!("nothing to import"))
+            .unwrap();
+    }
+}
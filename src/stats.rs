@@ -0,0 +1,312 @@
+use git2::Repository;
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use crate::manifest;
+use crate::parallel;
+use crate::preview;
+use crate::remote;
+use crate::report::{Cell, Report};
+use crate::scanner::{self, GitRepo, ProjectType};
+
+/// Label for repositories without a remote or a recognized language.
+const NONE: &str = "(none)";
+
+/// What `gitnav stats` learns about one repository.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RepoStats {
+    pub name: String,
+    pub path: PathBuf,
+    /// Host of `origin` (or the first remote), e.g. "github.com"
+    pub host: Option<String>,
+    /// Primary language, from the project manifest or marker files
+    pub language: Option<String>,
+    /// Tracked files have staged or unstaged changes
+    pub dirty: bool,
+    /// Unix timestamp of the commit HEAD points to
+    pub last_commit: Option<i64>,
+    /// Bytes used by the working tree, `.git` included
+    pub size: u64,
+}
+
+/// A repository named in one of the report's lists.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Entry {
+    pub name: String,
+    pub path: PathBuf,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_commit: Option<i64>,
+    pub size: u64,
+}
+
+/// The fleet summary printed by `gitnav stats`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Summary {
+    pub total: usize,
+    pub dirty: usize,
+    /// Repositories per forge host; "(none)" counts those without a remote
+    pub by_host: BTreeMap<String, usize>,
+    /// Repositories per primary language; "(none)" counts unrecognized ones
+    pub by_language: BTreeMap<String, usize>,
+    pub without_remote: Vec<Entry>,
+    /// Oldest last commit first
+    pub stalest: Vec<Entry>,
+    /// Largest on disk first
+    pub largest: Vec<Entry>,
+}
+
+/// Inspect every repository in parallel, keeping input order.
+pub fn collect(repos: &[GitRepo]) -> Vec<RepoStats> {
+    parallel::filter_map(repos, parallel::default_workers(), |repo| {
+        Some(inspect(repo))
+    })
+}
+
+fn inspect(repo: &GitRepo) -> RepoStats {
+    let opened = Repository::open(&repo.path).ok();
+    RepoStats {
+        name: repo.name.clone(),
+        path: repo.path.clone(),
+        host: remote::repo_remote(&repo.path).map(|r| r.host),
+        language: language(&repo.path),
+        dirty: opened.as_ref().is_some_and(scanner::has_tracked_changes),
+        last_commit: opened
+            .as_ref()
+            .and_then(|r| r.head().ok())
+            .and_then(|h| h.peel_to_commit().ok())
+            .map(|c| c.time().seconds()),
+        size: dir_size(&repo.path),
+    }
+}
+
+/// The language named by the project manifest, falling back to marker files.
+fn language(path: &Path) -> Option<String> {
+    if let Some(manifest) = manifest::detect(path) {
+        return Some(manifest.language);
+    }
+    let name = match scanner::detect_project_type(path) {
+        ProjectType::Rust => "Rust",
+        ProjectType::Node => "JavaScript",
+        ProjectType::Go => "Go",
+        ProjectType::Python => "Python",
+        ProjectType::Ruby => "Ruby",
+        ProjectType::Java => "Java",
+        ProjectType::CSharp => "C#",
+        ProjectType::Unknown => return None,
+    };
+    Some(name.to_string())
+}
+
+/// Total size of the files under `path`. Symlinks are counted, not followed;
+/// entries that cannot be read are skipped.
+pub fn dir_size(path: &Path) -> u64 {
+    let Ok(entries) = std::fs::read_dir(path) else {
+        return 0;
+    };
+    entries
+        .flatten()
+        .map(|entry| match entry.metadata() {
+            Ok(meta) if meta.is_dir() => dir_size(&entry.path()),
+            Ok(meta) => meta.len(),
+            Err(_) => 0,
+        })
+        .sum()
+}
+
+/// Summarize `stats`, keeping the `top` stalest and largest repositories.
+pub fn summarize(stats: &[RepoStats], top: usize) -> Summary {
+    let entry = |s: &RepoStats| Entry {
+        name: s.name.clone(),
+        path: s.path.clone(),
+        last_commit: s.last_commit,
+        size: s.size,
+    };
+    let mut by_host = BTreeMap::new();
+    let mut by_language = BTreeMap::new();
+    for s in stats {
+        let host = s.host.clone().unwrap_or_else(|| NONE.to_string());
+        *by_host.entry(host).or_insert(0) += 1;
+        let language = s.language.clone().unwrap_or_else(|| NONE.to_string());
+        *by_language.entry(language).or_insert(0) += 1;
+    }
+
+    let mut stalest: Vec<&RepoStats> = stats.iter().collect();
+    // Repositories without commits have nothing to be stale about
+    stalest.retain(|s| s.last_commit.is_some());
+    stalest.sort_by(|a, b| a.last_commit.cmp(&b.last_commit).then(a.name.cmp(&b.name)));
+    let mut largest: Vec<&RepoStats> = stats.iter().collect();
+    largest.sort_by(|a, b| b.size.cmp(&a.size).then(a.name.cmp(&b.name)));
+
+    Summary {
+        total: stats.len(),
+        dirty: stats.iter().filter(|s| s.dirty).count(),
+        by_host,
+        by_language,
+        without_remote: stats
+            .iter()
+            .filter(|s| s.host.is_none())
+            .map(entry)
+            .collect(),
+        stalest: stalest.into_iter().take(top).map(entry).collect(),
+        largest: largest.into_iter().take(top).map(entry).collect(),
+    }
+}
+
+/// The summary as titled tables, for the text output. `now` is a Unix timestamp.
+pub fn build_reports(summary: &Summary, now: i64) -> Vec<Report> {
+    let counts = |title: &str, header: &str, counts: &BTreeMap<String, usize>| {
+        let mut counts: Vec<(&String, &usize)> = counts.iter().collect();
+        counts.sort_by(|a, b| b.1.cmp(a.1).then(a.0.cmp(b.0)));
+        report(
+            title,
+            &[header, "Repositories"],
+            counts
+                .into_iter()
+                .map(|(name, count)| vec![Cell::new(name.clone()), Cell::new(count.to_string())])
+                .collect(),
+        )
+    };
+    let location =
+        |e: &Entry| Cell::new(scanner::shorten_home(&e.path, dirs::home_dir().as_deref()));
+
+    vec![
+        counts("By host", "Host", &summary.by_host),
+        counts("By language", "Language", &summary.by_language),
+        report(
+            "Without a remote",
+            &["Repository", "Path"],
+            summary
+                .without_remote
+                .iter()
+                .map(|e| vec![Cell::new(e.name.clone()), location(e)])
+                .collect(),
+        ),
+        report(
+            "Stalest",
+            &["Repository", "Last commit", "Path"],
+            summary
+                .stalest
+                .iter()
+                .map(|e| {
+                    let age = now - e.last_commit.unwrap_or(now);
+                    vec![
+                        Cell::new(e.name.clone()),
+                        Cell::new(preview::format_duration(chrono::Duration::seconds(age))),
+                        location(e),
+                    ]
+                })
+                .collect(),
+        ),
+        report(
+            "Largest",
+            &["Repository", "Size", "Path"],
+            summary
+                .largest
+                .iter()
+                .map(|e| {
+                    vec![
+                        Cell::new(e.name.clone()),
+                        Cell::new(format_size(e.size)),
+                        location(e),
+                    ]
+                })
+                .collect(),
+        ),
+    ]
+}
+
+fn report(title: &str, headers: &[&str], rows: Vec<Vec<Cell>>) -> Report {
+    Report {
+        title: title.to_string(),
+        notes: Vec::new(),
+        headers: headers.iter().map(|h| h.to_string()).collect(),
+        rows,
+    }
+}
+
+/// `bytes` in binary units with one decimal, e.g. "3.2 GiB".
+pub fn format_size(bytes: u64) -> String {
+    const UNITS: [&str; 5] = ["B", "KiB", "MiB", "GiB", "TiB"];
+    let mut size = bytes as f64;
+    let mut unit = 0;
+    while size >= 1024.0 && unit < UNITS.len() - 1 {
+        size /= 1024.0;
+        unit += 1;
+    }
+    if unit == 0 {
+        format!("{} B", bytes)
+    } else {
+        format!("{:.1} {}", size, UNITS[unit])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn stats(name: &str, host: Option<&str>, last_commit: Option<i64>, size: u64) -> RepoStats {
+        RepoStats {
+            name: name.to_string(),
+            path: PathBuf::from("/src").join(name),
+            host: host.map(str::to_string),
+            language: Some("Rust".to_string()),
+            dirty: false,
+            last_commit,
+            size,
+        }
+    }
+
+    #[test]
+    fn test_format_size() {
+        assert_eq!(format_size(0), "0 B");
+        assert_eq!(format_size(1023), "1023 B");
+        assert_eq!(format_size(1536), "1.5 KiB");
+        assert_eq!(format_size(3 * 1024 * 1024 * 1024), "3.0 GiB");
+    }
+
+    #[test]
+    fn test_summarize() {
+        let mut api = stats("api", Some("github.com"), Some(3_000), 10);
+        api.dirty = true;
+        let mut notes = stats("notes", None, Some(1_000), 500);
+        notes.language = None;
+        let repos = vec![
+            api,
+            stats("web", Some("github.com"), Some(2_000), 200),
+            notes,
+            stats("empty", Some("gitlab.com"), None, 1),
+        ];
+
+        let summary = summarize(&repos, 2);
+        assert_eq!(summary.total, 4);
+        assert_eq!(summary.dirty, 1);
+        assert_eq!(summary.by_host.get("github.com"), Some(&2));
+        assert_eq!(summary.by_host.get(NONE), Some(&1));
+        assert_eq!(summary.by_language.get("Rust"), Some(&3));
+        assert_eq!(summary.by_language.get(NONE), Some(&1));
+        let names = |entries: &[Entry]| entries.iter().map(|e| e.name.clone()).collect::<Vec<_>>();
+        assert_eq!(names(&summary.without_remote), vec!["notes"]);
+        assert_eq!(names(&summary.stalest), vec!["notes", "web"]);
+        assert_eq!(names(&summary.largest), vec!["notes", "web"]);
+
+        let reports = build_reports(&summary, 3_600 + 1_000);
+        assert_eq!(reports[0].rows[0][0].text, "github.com");
+        assert_eq!(reports[3].rows[0][1].text, "1 hours ago");
+    }
+
+    #[test]
+    fn test_collect_reads_repository() {
+        let dir = tempfile::tempdir().unwrap();
+        let repo = Repository::init(dir.path()).unwrap();
+        repo.remote("origin", "git@github.com:acme/api.git")
+            .unwrap();
+        std::fs::write(dir.path().join("Cargo.toml"), "[workspace]\n").unwrap();
+
+        let found = collect(&[GitRepo::new(dir.path().to_path_buf())]);
+        assert_eq!(found[0].host.as_deref(), Some("github.com"));
+        assert_eq!(found[0].language.as_deref(), Some("Rust"));
+        assert_eq!(found[0].last_commit, None);
+        assert!(found[0].size >= 12);
+    }
+}
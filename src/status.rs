@@ -0,0 +1,256 @@
+use git2::{BranchType, Repository};
+use serde::Serialize;
+use std::path::Path;
+
+use crate::config::PreviewConfig;
+use crate::parallel;
+use crate::preview::{self, RepoSummary, StatusCounts, Tracking};
+use crate::report::{Cell, Report};
+use crate::scanner::GitRepo;
+
+/// Collect branch, working tree state, and last activity for every repository.
+///
+/// Repositories are inspected in parallel; the result keeps the input order.
+/// Repositories that cannot be opened are skipped.
+///
+/// # Arguments
+///
+/// * `repos` - Repositories to inspect
+/// * `date_format` - strftime format for the last activity column (`preview.date_format`)
+pub fn collect(repos: &[GitRepo], date_format: &str) -> Vec<RepoSummary> {
+    let config = PreviewConfig {
+        show_branch: true,
+        show_last_activity: true,
+        show_status: true,
+        recent_commits: 0,
+        date_format: date_format.to_string(),
+        max_lines: 0,
+        show_remotes: false,
+        show_diffstat: false,
+        show_tags: false,
+        show_identity: false,
+        show_commit_activity: false,
+        show_branches: false,
+        template: None,
+        // The dashboard lists every repository's state, however long it takes
+        status_max_files: 0,
+        status_timeout_ms: 0,
+        cache_ttl_seconds: 0,
+    };
+
+    parallel::filter_map(repos, parallel::default_workers(), |repo| {
+        preview::collect_summary(&repo.path, &config).ok()
+    })
+}
+
+/// How far the current branch has diverged from its upstream.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Divergence {
+    pub branch: String,
+    pub upstream: String,
+    /// Local commits not on the upstream (unpushed work)
+    pub ahead: usize,
+    /// Upstream commits not yet merged locally
+    pub behind: usize,
+}
+
+/// Compare the checked-out branch with its upstream using the last fetched remote refs.
+///
+/// Returns `None` for detached HEADs, unborn branches, branches without an
+/// upstream, or repositories that cannot be opened. No network access is made.
+pub fn divergence(repo_path: &Path) -> Option<Divergence> {
+    let repo = Repository::open(repo_path).ok()?;
+    let head = repo.head().ok()?;
+    if !head.is_branch() {
+        return None;
+    }
+    let branch_name = head.shorthand()?.to_string();
+    let local_oid = head.target()?;
+
+    let upstream = repo
+        .find_branch(&branch_name, BranchType::Local)
+        .ok()?
+        .upstream()
+        .ok()?;
+    let upstream_name = upstream.name().ok().flatten()?.to_string();
+    let upstream_oid = upstream.get().target()?;
+
+    let (ahead, behind) = repo.graph_ahead_behind(local_oid, upstream_oid).ok()?;
+    Some(Divergence {
+        branch: branch_name,
+        upstream: upstream_name,
+        ahead,
+        behind,
+    })
+}
+
+/// Describe a working tree as "clean" or a list of non-zero change counts.
+pub fn describe_state(status: Option<&StatusCounts>) -> String {
+    let Some(counts) = status else {
+        return "unknown".to_string();
+    };
+
+    let parts: Vec<String> = [
+        (counts.staged, "staged"),
+        (counts.unstaged, "modified"),
+        (counts.untracked, "untracked"),
+    ]
+    .iter()
+    .filter(|(n, _)| *n > 0)
+    .map(|(n, label)| format!("{} {}", n, label))
+    .collect();
+
+    if parts.is_empty() {
+        "clean".to_string()
+    } else {
+        parts.join(", ")
+    }
+}
+
+/// Describe the current branch against its upstream: `↑2 ↓1 origin/main`,
+/// `= origin/main` when in sync, or `-` without an upstream.
+pub fn describe_tracking(tracking: Option<&Tracking>) -> String {
+    let Some(t) = tracking else {
+        return "-".to_string();
+    };
+
+    let mut parts = Vec::new();
+    if t.ahead > 0 {
+        parts.push(format!("↑{}", t.ahead));
+    }
+    if t.behind > 0 {
+        parts.push(format!("↓{}", t.behind));
+    }
+    if parts.is_empty() {
+        parts.push("=".to_string());
+    }
+    parts.push(t.upstream.clone());
+    parts.join(" ")
+}
+
+/// Build the status dashboard table.
+///
+/// # Arguments
+///
+/// * `summaries` - Output of [`collect`]
+/// * `notes` - Lines shown under the report title
+pub fn build_report(summaries: &[RepoSummary], notes: Vec<String>) -> Report {
+    let rows = summaries
+        .iter()
+        .map(|s| {
+            let branch = match (&s.branch, s.detached) {
+                (Some(b), _) => b.clone(),
+                (None, true) => "(detached)".to_string(),
+                (None, false) => "-".to_string(),
+            };
+            let changes = s
+                .status
+                .as_ref()
+                .map(|c| c.staged + c.unstaged + c.untracked)
+                .unwrap_or(0);
+            let diverged = s.tracking.as_ref().map(|t| t.ahead + t.behind).unwrap_or(0);
+            let activity = match s.last_activity {
+                Some(ref a) => Cell::sorted_by(
+                    format!("{} ({})", a.relative, a.formatted),
+                    a.timestamp.to_string(),
+                ),
+                None => Cell::sorted_by("-", "0"),
+            };
+
+            vec![
+                Cell::new(s.name.clone()),
+                Cell::new(branch),
+                Cell::sorted_by(describe_tracking(s.tracking.as_ref()), diverged.to_string()),
+                Cell::sorted_by(describe_state(s.status.as_ref()), changes.to_string()),
+                activity,
+                Cell::new(s.path.display().to_string()),
+            ]
+        })
+        .collect();
+
+    Report {
+        title: "gitnav status".to_string(),
+        notes,
+        headers: [
+            "Repository",
+            "Branch",
+            "Upstream",
+            "State",
+            "Last activity",
+            "Path",
+        ]
+        .iter()
+        .map(|h| h.to_string())
+        .collect(),
+        rows,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::commit;
+    use git2::{Repository, RepositoryInitOptions};
+    use std::path::Path;
+
+    fn init_repo_on_main(dir: &Path) {
+        let mut opts = RepositoryInitOptions::new();
+        opts.initial_head("main");
+        let repo = Repository::init_opts(dir, &opts).unwrap();
+        commit(&repo, "Initial commit", None);
+    }
+
+    #[test]
+    fn test_describe_state() {
+        assert_eq!(describe_state(None), "unknown");
+        assert_eq!(describe_state(Some(&StatusCounts::default())), "clean");
+        let counts = StatusCounts {
+            staged: 2,
+            unstaged: 0,
+            untracked: 3,
+        };
+        assert_eq!(describe_state(Some(&counts)), "2 staged, 3 untracked");
+    }
+
+    #[test]
+    fn test_describe_tracking() {
+        let tracking = |ahead, behind| Tracking {
+            upstream: "origin/main".to_string(),
+            ahead,
+            behind,
+        };
+        assert_eq!(describe_tracking(None), "-");
+        assert_eq!(describe_tracking(Some(&tracking(0, 0))), "= origin/main");
+        assert_eq!(
+            describe_tracking(Some(&tracking(2, 1))),
+            "↑2 ↓1 origin/main"
+        );
+        assert_eq!(describe_tracking(Some(&tracking(0, 4))), "↓4 origin/main");
+    }
+
+    #[test]
+    fn test_collect_and_build_report() {
+        let tmp = tempfile::tempdir().unwrap();
+        let mut repos = Vec::new();
+        for name in ["alpha", "beta", "gamma"] {
+            let dir = tmp.path().join(name);
+            init_repo_on_main(&dir);
+            repos.push(GitRepo::new(dir));
+        }
+        std::fs::write(tmp.path().join("beta/new.txt"), "x").unwrap();
+        // Missing repositories are skipped rather than failing the report
+        repos.push(GitRepo::new(tmp.path().join("missing")));
+
+        let summaries = collect(&repos, "%Y-%m-%d");
+        let names: Vec<&str> = summaries.iter().map(|s| s.name.as_str()).collect();
+        assert_eq!(names, vec!["alpha", "beta", "gamma"]);
+
+        let report = build_report(&summaries, Vec::new());
+        assert_eq!(report.rows.len(), 3);
+        assert_eq!(report.rows[0][1].text, "main");
+        assert_eq!(report.rows[0][2].text, "-");
+        assert_eq!(report.rows[0][3].text, "clean");
+        assert_eq!(report.rows[1][3].text, "1 untracked");
+        assert!(report.rows[0][4].sort_key.as_deref().unwrap() != "0");
+    }
+}
@@ -0,0 +1,414 @@
+use anyhow::{Context, Result};
+use std::io::Write;
+use std::process::{Command, Stdio};
+
+use crate::config::{CloneProtocol, SyncAccount, Visibility};
+use crate::forge::{self, ApiRequest};
+use crate::remote::RemoteUrl;
+
+/// Repositories requested per API page (the maximum both forges allow).
+const PER_PAGE: usize = 100;
+
+/// Pages read before giving up, so a misbehaving server cannot loop forever.
+const MAX_PAGES: usize = 100;
+
+/// A repository an account has on its forge.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ForgeRepo {
+    pub remote: RemoteUrl,
+    /// URL to clone, in the configured protocol
+    pub clone_url: String,
+    /// Private, or internal on GitLab
+    pub private: bool,
+    pub topics: Vec<String>,
+}
+
+impl ForgeRepo {
+    /// Whether the repository passes the `topics` and `visibility` filters.
+    pub fn is_wanted(&self, topics: &[String], visibility: Visibility) -> bool {
+        let visible = match visibility {
+            Visibility::All => true,
+            Visibility::Public => !self.private,
+            Visibility::Private => self.private,
+        };
+        visible
+            && (topics.is_empty()
+                || topics
+                    .iter()
+                    .any(|t| self.topics.iter().any(|own| own.eq_ignore_ascii_case(t))))
+    }
+}
+
+/// Parse a `gitnav sync` argument: `owner` for GitHub, or `host/owner`.
+pub fn parse_account(arg: &str) -> SyncAccount {
+    let arg = arg.trim().trim_matches('/');
+    let (host, owner) = match arg.split_once('/') {
+        Some((host, owner)) if host.contains('.') => (host.to_string(), owner.to_string()),
+        _ => ("github.com".to_string(), arg.to_string()),
+    };
+    SyncAccount {
+        host,
+        owner,
+        topics: Vec::new(),
+        visibility: Visibility::All,
+        token: None,
+    }
+}
+
+/// The API token for `account`: the configured one, then the forge's usual
+/// environment variable, then whatever the git credential helper has stored
+/// for the host.
+pub fn token(account: &SyncAccount) -> Option<String> {
+    if let Some(token) = account.token.clone().filter(|t| !t.is_empty()) {
+        return Some(token);
+    }
+    let names: &[&str] = if is_github(&account.host) {
+        &["GITHUB_TOKEN", "GH_TOKEN"]
+    } else {
+        &["GITLAB_TOKEN"]
+    };
+    names
+        .iter()
+        .find_map(|name| std::env::var(name).ok().filter(|v| !v.is_empty()))
+        .or_else(|| credential_helper(&account.host))
+}
+
+/// Ask `git credential fill` for the password stored for `https://<host>`,
+/// without ever prompting.
+fn credential_helper(host: &str) -> Option<String> {
+    let mut child = Command::new("git")
+        .args(["credential", "fill"])
+        .env("GIT_TERMINAL_PROMPT", "0")
+        .env("GIT_ASKPASS", "")
+        .env("SSH_ASKPASS", "")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()
+        .ok()?;
+    child
+        .stdin
+        .take()?
+        .write_all(format!("protocol=https\nhost={}\n\n", host).as_bytes())
+        .ok()?;
+    let output = child.wait_with_output().ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    parse_credential(&String::from_utf8_lossy(&output.stdout))
+}
+
+/// The `password` field of `git credential fill` output.
+fn parse_credential(output: &str) -> Option<String> {
+    output
+        .lines()
+        .find_map(|line| line.strip_prefix("password="))
+        .filter(|p| !p.is_empty())
+        .map(str::to_string)
+}
+
+fn is_github(host: &str) -> bool {
+    host.eq_ignore_ascii_case("github.com")
+}
+
+fn is_gitlab(host: &str) -> bool {
+    let host = host.to_ascii_lowercase();
+    host == "gitlab.com" || host.starts_with("gitlab.")
+}
+
+/// Every repository `account` has on its forge, with clone URLs for `protocol`.
+///
+/// For GitHub, an organization lists all repositories the token can see, and
+/// the token's own user lists its private ones too; other users list their
+/// public repositories. For GitLab, a group includes its subgroups.
+///
+/// # Errors
+///
+/// Returns an error if the host is not GitHub or GitLab, the account does not
+/// exist, or a request fails
+pub fn list(
+    account: &SyncAccount,
+    protocol: CloneProtocol,
+    token: Option<&str>,
+) -> Result<Vec<ForgeRepo>> {
+    if is_github(&account.host) {
+        list_github(&account.owner, protocol, token)
+    } else if is_gitlab(&account.host) {
+        list_gitlab(&account.host, &account.owner, protocol, token)
+    } else {
+        anyhow::bail!(
+            "{} is not a supported forge; gitnav sync knows github.com and GitLab hosts",
+            account.host
+        )
+    }
+}
+
+fn list_github(
+    owner: &str,
+    protocol: CloneProtocol,
+    token: Option<&str>,
+) -> Result<Vec<ForgeRepo>> {
+    let request = |path: String| ApiRequest {
+        url: format!("https://api.github.com{}", path),
+        auth_header: token.map(|t| format!("Authorization: Bearer {}", t)),
+    };
+
+    let (status, body) = forge::get(&request(format!("/users/{}", owner)))?;
+    let user = match status {
+        200 => parse_json(&body)?,
+        404 => anyhow::bail!("No GitHub user or organization named {}", owner),
+        _ => anyhow::bail!(
+            "GitHub API error {} for {}: {}",
+            status,
+            owner,
+            api_message(&body)
+        ),
+    };
+    let base = if user["type"].as_str() == Some("Organization") {
+        format!("/orgs/{}/repos?type=all", owner)
+    } else if token.is_some() && is_authenticated_as(&request("/user".to_string()), owner) {
+        "/user/repos?affiliation=owner".to_string()
+    } else {
+        format!("/users/{}/repos?type=owner", owner)
+    };
+
+    paginate(
+        "GitHub",
+        owner,
+        |page| request(page_path(&base, page)),
+        |json| parse_github(json, protocol),
+    )
+}
+
+/// Whether the token behind `request` (`GET /user`) belongs to `owner`.
+fn is_authenticated_as(request: &ApiRequest, owner: &str) -> bool {
+    match forge::get(request) {
+        Ok((200, body)) => parse_json(&body)
+            .ok()
+            .and_then(|user| {
+                user["login"]
+                    .as_str()
+                    .map(|l| l.eq_ignore_ascii_case(owner))
+            })
+            .unwrap_or(false),
+        _ => false,
+    }
+}
+
+fn list_gitlab(
+    host: &str,
+    owner: &str,
+    protocol: CloneProtocol,
+    token: Option<&str>,
+) -> Result<Vec<ForgeRepo>> {
+    let request = |path: String| ApiRequest {
+        url: format!("https://{}/api/v4{}", host, path),
+        auth_header: token.map(|t| format!("PRIVATE-TOKEN: {}", t)),
+    };
+    let encoded = owner.replace('/', "%2F");
+    let (status, body) = forge::get(&request(format!("/groups/{}?with_projects=false", encoded)))?;
+    let base = match status {
+        200 => format!("/groups/{}/projects?include_subgroups=true", encoded),
+        // Not a group; the user of that name, if any
+        404 => format!("/users/{}/projects?owned=true", encoded),
+        _ => anyhow::bail!(
+            "GitLab API error {} for {}: {}",
+            status,
+            owner,
+            api_message(&body)
+        ),
+    };
+    paginate(
+        "GitLab",
+        owner,
+        |page| request(page_path(&base, page)),
+        |json| parse_gitlab(json, protocol),
+    )
+}
+
+/// `base` (which already has a query) with paging parameters.
+fn page_path(base: &str, page: usize) -> String {
+    format!("{}&per_page={}&page={}", base, PER_PAGE, page)
+}
+
+/// Read pages from `request(1)` on until one comes back short.
+fn paginate(
+    forge_name: &str,
+    owner: &str,
+    request: impl Fn(usize) -> ApiRequest,
+    parse: impl Fn(&serde_json::Value) -> Vec<Option<ForgeRepo>>,
+) -> Result<Vec<ForgeRepo>> {
+    let mut repos = Vec::new();
+    for page in 1..=MAX_PAGES {
+        let (status, body) = forge::get(&request(page))?;
+        if status != 200 {
+            anyhow::bail!(
+                "{} API error {} for {}: {}",
+                forge_name,
+                status,
+                owner,
+                api_message(&body)
+            );
+        }
+        let items = parse(&parse_json(&body)?);
+        let last = items.len() < PER_PAGE;
+        repos.extend(items.into_iter().flatten());
+        if last {
+            break;
+        }
+    }
+    Ok(repos)
+}
+
+fn parse_json(body: &[u8]) -> Result<serde_json::Value> {
+    serde_json::from_slice(body).context("Forge API returned invalid JSON")
+}
+
+/// The `message` of an API error response, or the start of the body.
+fn api_message(body: &[u8]) -> String {
+    parse_json(body)
+        .ok()
+        .and_then(|json| json["message"].as_str().map(str::to_string))
+        .unwrap_or_else(|| String::from_utf8_lossy(body).chars().take(200).collect())
+}
+
+/// One page of GitHub's repository list; `None` for entries that cannot be read.
+fn parse_github(page: &serde_json::Value, protocol: CloneProtocol) -> Vec<Option<ForgeRepo>> {
+    let items = page.as_array().map(Vec::as_slice).unwrap_or_default();
+    items
+        .iter()
+        .map(|item| {
+            let url_key = match protocol {
+                CloneProtocol::Https => "clone_url",
+                CloneProtocol::Ssh => "ssh_url",
+            };
+            let clone_url = item[url_key].as_str()?.to_string();
+            Some(ForgeRepo {
+                remote: RemoteUrl::parse(&clone_url)?,
+                clone_url,
+                private: item["private"].as_bool().unwrap_or(false),
+                topics: strings(&item["topics"]),
+            })
+        })
+        .collect()
+}
+
+/// One page of GitLab's project list; `None` for entries that cannot be read.
+fn parse_gitlab(page: &serde_json::Value, protocol: CloneProtocol) -> Vec<Option<ForgeRepo>> {
+    let items = page.as_array().map(Vec::as_slice).unwrap_or_default();
+    items
+        .iter()
+        .map(|item| {
+            let url_key = match protocol {
+                CloneProtocol::Https => "http_url_to_repo",
+                CloneProtocol::Ssh => "ssh_url_to_repo",
+            };
+            let clone_url = item[url_key].as_str()?.to_string();
+            let mut topics = strings(&item["topics"]);
+            if topics.is_empty() {
+                // GitLab before 14.5 called them tags
+                topics = strings(&item["tag_list"]);
+            }
+            Some(ForgeRepo {
+                remote: RemoteUrl::parse(&clone_url)?,
+                clone_url,
+                private: item["visibility"].as_str() != Some("public"),
+                topics,
+            })
+        })
+        .collect()
+}
+
+fn strings(value: &serde_json::Value) -> Vec<String> {
+    value
+        .as_array()
+        .map(|items| {
+            items
+                .iter()
+                .filter_map(|t| t.as_str().map(str::to_string))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_account() {
+        let github = parse_account("acme");
+        assert_eq!(
+            (github.host.as_str(), github.owner.as_str()),
+            ("github.com", "acme")
+        );
+        let gitlab = parse_account("gitlab.com/group/sub/");
+        assert_eq!(
+            (gitlab.host.as_str(), gitlab.owner.as_str()),
+            ("gitlab.com", "group/sub")
+        );
+    }
+
+    #[test]
+    fn test_parse_github_page() {
+        let page = serde_json::json!([
+            {
+                "clone_url": "https://github.com/acme/api.git",
+                "ssh_url": "git@github.com:acme/api.git",
+                "private": true,
+                "topics": ["rust", "cli"]
+            },
+            { "name": "broken" }
+        ]);
+        let repos = parse_github(&page, CloneProtocol::Ssh);
+        assert_eq!(repos.len(), 2);
+        let api = repos[0].as_ref().unwrap();
+        assert_eq!(api.clone_url, "git@github.com:acme/api.git");
+        assert_eq!(api.remote.to_string(), "github.com/acme/api");
+        assert!(api.private);
+        assert_eq!(api.topics, vec!["rust", "cli"]);
+        assert!(repos[1].is_none());
+    }
+
+    #[test]
+    fn test_parse_gitlab_page() {
+        let page = serde_json::json!([
+            {
+                "http_url_to_repo": "https://gitlab.com/group/sub/web.git",
+                "ssh_url_to_repo": "git@gitlab.com:group/sub/web.git",
+                "visibility": "internal",
+                "tag_list": ["frontend"]
+            }
+        ]);
+        let repos = parse_gitlab(&page, CloneProtocol::Https);
+        let web = repos[0].as_ref().unwrap();
+        assert_eq!(web.clone_url, "https://gitlab.com/group/sub/web.git");
+        assert_eq!(web.remote.to_string(), "gitlab.com/group/sub/web");
+        assert!(web.private);
+        assert_eq!(web.topics, vec!["frontend"]);
+    }
+
+    #[test]
+    fn test_is_wanted() {
+        let repo = ForgeRepo {
+            remote: RemoteUrl::parse("https://github.com/acme/api").unwrap(),
+            clone_url: "https://github.com/acme/api.git".to_string(),
+            private: false,
+            topics: vec!["Rust".to_string()],
+        };
+        assert!(repo.is_wanted(&[], Visibility::All));
+        assert!(repo.is_wanted(&[], Visibility::Public));
+        assert!(!repo.is_wanted(&[], Visibility::Private));
+        assert!(repo.is_wanted(&["go".to_string(), "rust".to_string()], Visibility::All));
+        assert!(!repo.is_wanted(&["go".to_string()], Visibility::All));
+    }
+
+    #[test]
+    fn test_parse_credential() {
+        assert_eq!(
+            parse_credential("protocol=https\nhost=github.com\nusername=me\npassword=ghp_x\n"),
+            Some("ghp_x".to_string())
+        );
+        assert_eq!(parse_credential("protocol=https\nhost=github.com\n"), None);
+    }
+}
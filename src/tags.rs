@@ -0,0 +1,341 @@
+use anyhow::Result;
+use rusqlite::params;
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::{Path, PathBuf};
+
+use crate::scanner::GitRepo;
+use crate::state::{self, State};
+
+/// Tags assigned to repositories with `gitnav tag add`.
+///
+/// Stored in the `tags` table of the state database in the gitnav data
+/// directory, next to the pins, so tags survive `gitnav clear-cache`. Saving
+/// only inserts the new tags and deletes the dropped ones, so two gitnav
+/// commands never undo each other.
+#[derive(Debug)]
+pub struct TagStore {
+    state: State,
+    tags: BTreeMap<PathBuf, BTreeSet<String>>,
+    /// Tags added since loading, not yet in the database
+    added: BTreeSet<(PathBuf, String)>,
+    /// Tags removed since loading
+    removed: BTreeSet<(PathBuf, String)>,
+}
+
+impl TagStore {
+    /// Load tags from the default state database.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the data directory cannot be determined or the
+    /// database cannot be read
+    pub fn load_default() -> Result<Self> {
+        Self::load(State::open_default()?)
+    }
+
+    /// Load tags from `state`, first importing the `tags` file (`<tag>\t<path>`
+    /// lines) that earlier versions kept next to it. Malformed lines are skipped.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the database or the old file cannot be read
+    pub fn load(state: State) -> Result<Self> {
+        state.import_once(&state.legacy_file("tags"), |tx, contents| {
+            for line in contents.lines() {
+                if let Some((tag, path)) = line.split_once('\t') {
+                    if is_valid(tag) && !path.is_empty() {
+                        tx.execute(
+                            "INSERT OR IGNORE INTO tags (path, tag) VALUES (?1, ?2)",
+                            params![path, tag],
+                        )?;
+                    }
+                }
+            }
+            Ok(())
+        })?;
+
+        let mut tags: BTreeMap<PathBuf, BTreeSet<String>> = BTreeMap::new();
+        let mut stmt = state.conn().prepare("SELECT path, tag FROM tags")?;
+        let rows = stmt.query_map([], |row| {
+            Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?))
+        })?;
+        for row in rows {
+            let (path, tag) = row?;
+            tags.entry(PathBuf::from(path)).or_default().insert(tag);
+        }
+        drop(stmt);
+
+        Ok(Self {
+            state,
+            tags,
+            added: BTreeSet::new(),
+            removed: BTreeSet::new(),
+        })
+    }
+
+    /// Tagged repository paths with their tags, sorted by path.
+    pub fn entries(&self) -> impl Iterator<Item = (&Path, &BTreeSet<String>)> {
+        self.tags.iter().map(|(path, tags)| (path.as_path(), tags))
+    }
+
+    /// Tags of a repository, sorted; empty when it has none.
+    pub fn tags_for(&self, path: &Path) -> Vec<String> {
+        if self.tags.is_empty() {
+            return Vec::new();
+        }
+        self.tags
+            .get(path)
+            .or_else(|| self.tags.get(&normalize(path)))
+            .map(|tags| tags.iter().cloned().collect())
+            .unwrap_or_default()
+    }
+
+    /// Add tags to a repository. Returns the tags that were not already set.
+    pub fn add(&mut self, path: &Path, tags: &[String]) -> Vec<String> {
+        let path = normalize(path);
+        let set = self.tags.entry(path.clone()).or_default();
+        let added: Vec<String> = tags
+            .iter()
+            .filter(|tag| set.insert(tag.to_string()))
+            .cloned()
+            .collect();
+        for tag in &added {
+            let change = (path.clone(), tag.clone());
+            self.removed.remove(&change);
+            self.added.insert(change);
+        }
+        added
+    }
+
+    /// Remove tags from a repository, or all of its tags when `tags` is empty.
+    /// Returns the tags that were removed.
+    pub fn remove(&mut self, path: &Path, tags: &[String]) -> Vec<String> {
+        let key = if self.tags.contains_key(path) {
+            path.to_path_buf()
+        } else {
+            normalize(path)
+        };
+        let Some(set) = self.tags.get_mut(&key) else {
+            return Vec::new();
+        };
+
+        let removed: Vec<String> = if tags.is_empty() {
+            std::mem::take(set).into_iter().collect()
+        } else {
+            tags.iter()
+                .filter(|tag| set.remove(*tag))
+                .cloned()
+                .collect()
+        };
+        if set.is_empty() {
+            self.tags.remove(&key);
+        }
+        for tag in &removed {
+            self.forget(&key, tag);
+        }
+        removed
+    }
+
+    /// Note that `path` lost `tag`, for [`TagStore::save`].
+    fn forget(&mut self, path: &Path, tag: &str) {
+        let change = (path.to_path_buf(), tag.to_string());
+        self.added.remove(&change);
+        self.removed.insert(change);
+    }
+
+    /// Check whether a repository path has any tags.
+    pub fn contains(&self, path: &Path) -> bool {
+        !self.tags_for(path).is_empty()
+    }
+
+    /// Drop the tags of repositories that no longer exist. Returns their paths.
+    pub fn remove_missing(&mut self) -> Vec<PathBuf> {
+        let removed: Vec<PathBuf> = self.tags.keys().filter(|p| !p.is_dir()).cloned().collect();
+        for path in &removed {
+            for tag in self.tags.remove(path).unwrap_or_default() {
+                self.forget(path, &tag);
+            }
+        }
+        removed
+    }
+
+    /// Keep the repositories that carry every one of `required`; all of them
+    /// when `required` is empty.
+    pub fn filter(&self, repos: Vec<GitRepo>, required: &[String]) -> Vec<GitRepo> {
+        if required.is_empty() {
+            return repos;
+        }
+        repos
+            .into_iter()
+            .filter(|repo| {
+                let tags = self.tags_for(&repo.path);
+                required.iter().all(|tag| tags.contains(tag))
+            })
+            .collect()
+    }
+
+    /// Write the new and dropped tags to the database.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the database cannot be written
+    pub fn save(&mut self) -> Result<()> {
+        let tx = self.state.write()?;
+        for (path, tag) in &self.removed {
+            tx.execute(
+                "DELETE FROM tags WHERE path = ?1 AND tag = ?2",
+                params![state::path_text(path), tag],
+            )?;
+        }
+        for (path, tag) in &self.added {
+            tx.execute(
+                "INSERT OR IGNORE INTO tags (path, tag) VALUES (?1, ?2)",
+                params![state::path_text(path), tag],
+            )?;
+        }
+        tx.commit()?;
+
+        self.added.clear();
+        self.removed.clear();
+        Ok(())
+    }
+}
+
+/// Whether `tag` can be stored: non-empty, without whitespace or commas.
+pub fn is_valid(tag: &str) -> bool {
+    !tag.is_empty() && !tag.chars().any(|c| c.is_whitespace() || c == ',')
+}
+
+/// Canonicalize a path when it exists so `.` and symlinked spellings match scan results.
+fn normalize(path: &Path) -> PathBuf {
+    path.canonicalize().unwrap_or_else(|_| path.to_path_buf())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tags(names: &[&str]) -> Vec<String> {
+        names.iter().map(|s| s.to_string()).collect()
+    }
+
+    fn open(dir: &Path) -> State {
+        State::open(&dir.join("state.db")).unwrap()
+    }
+
+    #[test]
+    fn test_add_remove_roundtrip() {
+        let dir = tempfile::tempdir().unwrap();
+        let repo = PathBuf::from("/nonexistent/work/api");
+
+        let mut store = TagStore::load(open(dir.path())).unwrap();
+        assert_eq!(
+            store.add(&repo, &tags(&["work", "oss"])),
+            tags(&["work", "oss"])
+        );
+        assert_eq!(store.add(&repo, &tags(&["work"])), Vec::<String>::new());
+        store.save().unwrap();
+
+        let mut reloaded = TagStore::load(open(dir.path())).unwrap();
+        assert_eq!(reloaded.tags_for(&repo), tags(&["oss", "work"]));
+        assert_eq!(
+            reloaded.remove(&repo, &tags(&["oss", "nope"])),
+            tags(&["oss"])
+        );
+        assert_eq!(reloaded.remove(&repo, &[]), tags(&["work"]));
+        assert!(!reloaded.contains(&repo));
+        assert_eq!(reloaded.entries().count(), 0);
+    }
+
+    #[test]
+    fn test_imports_tags_file_once() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(
+            dir.path().join("tags"),
+            "work\t/src/api\nrust\t/src/api\ngarbage\nbad tag\t/src/web\n",
+        )
+        .unwrap();
+
+        let mut store = TagStore::load(open(dir.path())).unwrap();
+        assert_eq!(
+            store.tags_for(Path::new("/src/api")),
+            tags(&["rust", "work"])
+        );
+        assert_eq!(store.entries().count(), 1);
+        store.remove(Path::new("/src/api"), &tags(&["rust"]));
+        store.save().unwrap();
+
+        let reloaded = TagStore::load(open(dir.path())).unwrap();
+        assert_eq!(reloaded.tags_for(Path::new("/src/api")), tags(&["work"]));
+    }
+
+    #[test]
+    fn test_save_keeps_changes_saved_since_loading() {
+        let dir = tempfile::tempdir().unwrap();
+        let api = Path::new("/src/api");
+        let mut store = TagStore::load(open(dir.path())).unwrap();
+        store.add(api, &tags(&["work", "rust"]));
+        store.save().unwrap();
+
+        // Two commands load the same tags, then save in turn
+        let mut first = TagStore::load(open(dir.path())).unwrap();
+        let mut second = TagStore::load(open(dir.path())).unwrap();
+        first.add(api, &tags(&["oss"]));
+        first.save().unwrap();
+        second.remove(api, &tags(&["work"]));
+        second.add(Path::new("/src/web"), &tags(&["work"]));
+        second.save().unwrap();
+
+        let reloaded = TagStore::load(open(dir.path())).unwrap();
+        assert_eq!(reloaded.tags_for(api), tags(&["oss", "rust"]));
+        assert_eq!(reloaded.tags_for(Path::new("/src/web")), tags(&["work"]));
+    }
+
+    #[test]
+    fn test_filter_requires_every_tag() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut store = TagStore::load(open(dir.path())).unwrap();
+        store.add(Path::new("/src/api"), &tags(&["work", "rust"]));
+        store.add(Path::new("/src/web"), &tags(&["work"]));
+
+        let repos: Vec<GitRepo> = ["/src/api", "/src/cli", "/src/web"]
+            .iter()
+            .map(|p| GitRepo::new(PathBuf::from(p)))
+            .collect();
+        let names = |repos: Vec<GitRepo>| repos.into_iter().map(|r| r.name).collect::<Vec<_>>();
+
+        assert_eq!(
+            names(store.filter(repos.clone(), &tags(&["work"]))),
+            vec!["api", "web"]
+        );
+        assert_eq!(
+            names(store.filter(repos.clone(), &tags(&["work", "rust"]))),
+            vec!["api"]
+        );
+        assert_eq!(names(store.filter(repos, &[])), vec!["api", "cli", "web"]);
+    }
+
+    #[test]
+    fn test_remove_missing() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut store = TagStore::load(open(dir.path())).unwrap();
+        store.add(Path::new("/nonexistent/api"), &tags(&["work"]));
+        store.add(dir.path(), &tags(&["oss"]));
+
+        assert_eq!(
+            store.remove_missing(),
+            vec![PathBuf::from("/nonexistent/api")]
+        );
+        assert_eq!(store.entries().count(), 1);
+        assert!(store.contains(dir.path()));
+    }
+
+    #[test]
+    fn test_is_valid() {
+        assert!(is_valid("work"));
+        assert!(is_valid("client-a"));
+        assert!(!is_valid(""));
+        assert!(!is_valid("two words"));
+        assert!(!is_valid("a,b"));
+    }
+}
@@ -0,0 +1,256 @@
+use anyhow::Result;
+use std::path::Path;
+
+use crate::preview;
+
+/// A value that can appear in a `--format` template.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Field {
+    /// `{name}`: the repository name
+    Name,
+    /// `{path}`: the absolute path
+    Path,
+    /// `{relpath}`: the path relative to the search path it was found under
+    RelPath,
+    /// `{branch}`: the checked-out branch, empty when detached
+    Branch,
+    /// `{activity}`: time since the last commit, e.g. "3 days ago"
+    Activity,
+    /// `{tags}`: tags from `gitnav tag`, comma-separated
+    Tags,
+}
+
+/// The set of placeholders a kind of template accepts.
+pub trait Placeholder: Copy + 'static {
+    /// Placeholder names and the values they stand for
+    const ALL: &'static [(&'static str, Self)];
+}
+
+impl Placeholder for Field {
+    const ALL: &'static [(&'static str, Self)] = &[
+        ("name", Field::Name),
+        ("path", Field::Path),
+        ("relpath", Field::RelPath),
+        ("branch", Field::Branch),
+        ("activity", Field::Activity),
+        ("tags", Field::Tags),
+    ];
+}
+
+/// A piece of a parsed template: literal text or a placeholder.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Part<F> {
+    Text(String),
+    Field(F),
+}
+
+/// A parsed template such as `{name}\t{branch}\t{path}`.
+///
+/// `{{` and `}}` produce literal braces, and `\t`, `\n`, `\0`, and `\\` are
+/// unescaped so templates can be passed in plain shell quotes. `F` is the set
+/// of placeholders: [`Field`] for list output, others for the preview.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Template<F = Field> {
+    parts: Vec<Part<F>>,
+}
+
+/// Per-repository values for [`Template::render`].
+#[derive(Debug, Clone)]
+pub struct Values<'a> {
+    pub name: &'a str,
+    pub path: &'a Path,
+    pub rel_path: Option<&'a Path>,
+    pub branch: Option<&'a str>,
+    /// Unix timestamp of the last commit
+    pub last_commit: Option<i64>,
+    pub tags: &'a [String],
+}
+
+impl Default for Values<'_> {
+    fn default() -> Self {
+        Self {
+            name: "",
+            path: Path::new(""),
+            rel_path: None,
+            branch: None,
+            last_commit: None,
+            tags: &[],
+        }
+    }
+}
+
+impl<F: Placeholder + PartialEq> Template<F> {
+    /// Parse a template.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error for unknown placeholders and unbalanced braces
+    pub fn parse(template: &str) -> Result<Self> {
+        let mut parts = Vec::new();
+        let mut text = String::new();
+        let mut chars = template.chars().peekable();
+
+        while let Some(c) = chars.next() {
+            match c {
+                '{' if chars.peek() == Some(&'{') => {
+                    chars.next();
+                    text.push('{');
+                }
+                '}' if chars.peek() == Some(&'}') => {
+                    chars.next();
+                    text.push('}');
+                }
+                '{' => {
+                    let mut name = String::new();
+                    let mut closed = false;
+                    for c in chars.by_ref() {
+                        if c == '}' {
+                            closed = true;
+                            break;
+                        }
+                        name.push(c);
+                    }
+                    if !closed {
+                        anyhow::bail!("Unclosed '{{{}' in template", name);
+                    }
+                    let Some(&(_, field)) = F::ALL.iter().find(|(n, _)| *n == name.trim()) else {
+                        let known: Vec<String> =
+                            F::ALL.iter().map(|(n, _)| format!("{{{}}}", n)).collect();
+                        anyhow::bail!(
+                            "Unknown placeholder {{{}}} in template; available: {}",
+                            name,
+                            known.join(", ")
+                        );
+                    };
+                    if !text.is_empty() {
+                        parts.push(Part::Text(std::mem::take(&mut text)));
+                    }
+                    parts.push(Part::Field(field));
+                }
+                '}' => {
+                    anyhow::bail!("Unmatched '}}' in template; write '}}}}' for a literal brace")
+                }
+                '\\' => {
+                    let unescaped = match chars.peek() {
+                        Some('t') => Some('\t'),
+                        Some('n') => Some('\n'),
+                        Some('0') => Some('\0'),
+                        Some('\\') => Some('\\'),
+                        _ => None,
+                    };
+                    match unescaped {
+                        Some(c) => {
+                            chars.next();
+                            text.push(c);
+                        }
+                        None => text.push('\\'),
+                    }
+                }
+                c => text.push(c),
+            }
+        }
+        if !text.is_empty() {
+            parts.push(Part::Text(text));
+        }
+        Ok(Self { parts })
+    }
+
+    /// The text and placeholders, in order.
+    pub fn parts(&self) -> &[Part<F>] {
+        &self.parts
+    }
+
+    /// Whether the template uses `field`.
+    pub fn uses(&self, field: F) -> bool {
+        self.parts.contains(&Part::Field(field))
+    }
+}
+
+impl Template {
+    /// Whether rendering needs data read from the repository (branch, last commit).
+    pub fn needs_git(&self) -> bool {
+        self.parts
+            .iter()
+            .any(|p| matches!(p, Part::Field(Field::Branch | Field::Activity)))
+    }
+
+    /// Render the template for one repository.
+    pub fn render(&self, values: &Values) -> String {
+        let mut out = String::new();
+        for part in &self.parts {
+            match part {
+                Part::Text(text) => out.push_str(text),
+                Part::Field(Field::Name) => out.push_str(values.name),
+                Part::Field(Field::Path) => out.push_str(&values.path.display().to_string()),
+                Part::Field(Field::RelPath) => {
+                    let path = values.rel_path.unwrap_or(values.path);
+                    out.push_str(&path.display().to_string());
+                }
+                Part::Field(Field::Branch) => out.push_str(values.branch.unwrap_or_default()),
+                Part::Field(Field::Activity) => {
+                    if let Some(at) = values.last_commit {
+                        let elapsed = chrono::Utc::now().timestamp() - at;
+                        out.push_str(&preview::format_duration(chrono::Duration::seconds(
+                            elapsed,
+                        )));
+                    }
+                }
+                Part::Field(Field::Tags) => out.push_str(&values.tags.join(",")),
+            }
+        }
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    #[test]
+    fn test_render_fields_and_escapes() {
+        let template = Template::parse(r"{name}\t{relpath}\t{branch}|{tags}|{{x}}\n").unwrap();
+        assert!(template.needs_git());
+        let path = PathBuf::from("/src/work/api");
+        let tags = vec!["oss".to_string(), "work".to_string()];
+        let values = Values {
+            name: "api",
+            path: &path,
+            rel_path: Some(Path::new("work/api")),
+            branch: Some("main"),
+            tags: &tags,
+            ..Values::default()
+        };
+        assert_eq!(
+            template.render(&values),
+            "api\twork/api\tmain|oss,work|{x}\n"
+        );
+
+        let plain = Template::parse(r"{path} {relpath}{branch}{activity} C:\dir").unwrap();
+        let values = Values {
+            name: "api",
+            path: &path,
+            ..Values::default()
+        };
+        assert_eq!(plain.render(&values), r"/src/work/api /src/work/api C:\dir");
+    }
+
+    #[test]
+    fn test_render_activity() {
+        let template = Template::parse("{activity}").unwrap();
+        let values = Values {
+            last_commit: Some(chrono::Utc::now().timestamp() - 3 * 86400),
+            ..Values::default()
+        };
+        assert_eq!(template.render(&values), "3 days ago");
+        assert!(!Template::parse("{name} {tags}").unwrap().needs_git());
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        let err = Template::<Field>::parse("{nmae}").unwrap_err().to_string();
+        assert!(err.contains("{nmae}") && err.contains("{relpath}"));
+        assert!(Template::<Field>::parse("{name").is_err());
+        assert!(Template::<Field>::parse("name}").is_err());
+    }
+}
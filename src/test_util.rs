@@ -0,0 +1,18 @@
+use git2::{Oid, Repository, Signature, Time};
+
+/// Commit the index on top of HEAD (or as the first commit), authored at
+/// `time` (Unix seconds) or now. Returns the new commit.
+pub fn commit(repo: &Repository, message: &str, time: Option<i64>) -> Oid {
+    let sig = match time {
+        Some(time) => Signature::new("Test", "test@example.com", &Time::new(time, 0)),
+        None => Signature::now("Test", "test@example.com"),
+    }
+    .unwrap();
+    let tree = repo
+        .find_tree(repo.index().unwrap().write_tree().unwrap())
+        .unwrap();
+    let parent = repo.head().ok().and_then(|h| h.peel_to_commit().ok());
+    let parents: Vec<&git2::Commit> = parent.iter().collect();
+    repo.commit(Some("HEAD"), &sig, &sig, message, &tree, &parents)
+        .unwrap()
+}
@@ -0,0 +1,195 @@
+use anyhow::{Context, Result};
+use std::path::Path;
+use std::process::{Command, Stdio};
+
+use crate::config::TmuxWindow;
+
+/// A tmux session name for a repository.
+///
+/// tmux reserves `.` and `:` for window and pane targets, so they become `_`
+/// (`gitnav.io` → `gitnav_io`).
+pub fn session_name(repo_name: &str) -> String {
+    repo_name.replace(['.', ':'], "_")
+}
+
+/// Whether the tmux binary can be run.
+pub fn is_available() -> bool {
+    Command::new("tmux")
+        .arg("-V")
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status()
+        .is_ok_and(|status| status.success())
+}
+
+/// Whether a session named exactly `name` exists.
+pub fn has_session(name: &str) -> bool {
+    Command::new("tmux")
+        .args(["has-session", "-t", &exact(name)])
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status()
+        .is_ok_and(|status| status.success())
+}
+
+/// Create a detached session named `name` rooted at `path`, laid out as `windows`.
+///
+/// # Errors
+///
+/// Returns an error if tmux cannot be started or fails
+pub fn create_session(name: &str, path: &Path, windows: &[TmuxWindow]) -> Result<()> {
+    let status = Command::new("tmux")
+        .args(session_args(name, path, windows))
+        .stdout(Stdio::null())
+        .status()
+        .context("Failed to run tmux")?;
+    if !status.success() {
+        anyhow::bail!("tmux failed to create session '{}' ({})", name, status);
+    }
+    Ok(())
+}
+
+/// Attach to session `name`, or switch the current client to it when already
+/// inside tmux.
+///
+/// # Errors
+///
+/// Returns an error if tmux cannot be started or fails
+pub fn attach(name: &str) -> Result<()> {
+    let verb = if std::env::var_os("TMUX").is_some() {
+        "switch-client"
+    } else {
+        "attach-session"
+    };
+    let status = Command::new("tmux")
+        .args([verb, "-t", &exact(name)])
+        .status()
+        .context("Failed to run tmux")?;
+    if !status.success() {
+        anyhow::bail!("tmux {} failed for session '{}' ({})", verb, name, status);
+    }
+    Ok(())
+}
+
+/// A session target that matches `name` exactly rather than as a prefix.
+fn exact(name: &str) -> String {
+    format!("={}", name)
+}
+
+/// The arguments of one tmux invocation that creates the session.
+///
+/// Commands are chained with `;`, so each `split-window` and `new-window`
+/// applies to the window created just before it.
+fn session_args(name: &str, path: &Path, windows: &[TmuxWindow]) -> Vec<String> {
+    let path = path.display().to_string();
+    let default = [TmuxWindow::default()];
+    let windows = if windows.is_empty() {
+        &default[..]
+    } else {
+        windows
+    };
+
+    let mut args: Vec<String> = Vec::new();
+    let mut push = |command: Vec<&str>| {
+        if !args.is_empty() {
+            args.push(";".to_string());
+        }
+        args.extend(command.into_iter().map(str::to_string));
+    };
+    for (i, window) in windows.iter().enumerate() {
+        let scripts: Vec<Option<String>> = if window.panes.is_empty() {
+            vec![None]
+        } else {
+            window.panes.iter().map(|c| pane_script(c)).collect()
+        };
+        for (pane, script) in scripts.iter().enumerate() {
+            let mut command = match (i, pane) {
+                (0, 0) => vec!["new-session", "-d", "-s", name, "-c", &path],
+                (_, 0) => vec!["new-window", "-c", &path],
+                _ => vec!["split-window", "-c", &path],
+            };
+            if let (0, Some(window_name)) = (pane, &window.name) {
+                command.extend(["-n", window_name]);
+            }
+            if let Some(script) = script {
+                command.extend(["sh", "-c", script]);
+            }
+            push(command);
+        }
+        if let Some(ref layout) = window.layout {
+            push(vec!["select-layout", layout]);
+        }
+    }
+    if windows.len() > 1 {
+        // Start on the first window, as session files usually do
+        let first = format!("{}:^", exact(name));
+        push(vec!["select-window", "-t", &first]);
+    }
+    args
+}
+
+/// The `sh -c` script for a pane running `command`, or `None` for a plain shell.
+///
+/// tmux runs a multi-argument command directly, so this works whatever the
+/// user's shell is. The pane falls back to `$SHELL` when the command exits,
+/// and survives Ctrl-C, so stopping a dev server leaves a prompt behind.
+fn pane_script(command: &str) -> Option<String> {
+    if command.trim().is_empty() {
+        return None;
+    }
+    Some(format!("trap : INT; {}\nexec \"${{SHELL:-sh}}\"", command))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_session_name_avoids_target_separators() {
+        assert_eq!(session_name("gitnav"), "gitnav");
+        assert_eq!(session_name("socket.io:v2"), "socket_io_v2");
+    }
+
+    #[test]
+    fn test_session_args_default_is_one_shell() {
+        assert_eq!(
+            session_args("api", Path::new("/src/api"), &[]),
+            vec!["new-session", "-d", "-s", "api", "-c", "/src/api"]
+        );
+    }
+
+    #[test]
+    fn test_session_args_lay_out_windows_and_panes() {
+        let windows = vec![
+            TmuxWindow {
+                name: Some("code".to_string()),
+                panes: vec!["nvim".to_string(), String::new()],
+                layout: Some("main-vertical".to_string()),
+            },
+            TmuxWindow {
+                name: None,
+                panes: vec!["npm run dev".to_string()],
+                layout: None,
+            },
+        ];
+        let args = session_args("api", Path::new("/src/api"), &windows);
+        let commands: Vec<String> = args
+            .split(|arg| arg == ";")
+            .map(|command| command.join(" "))
+            .collect();
+        let script = |command: &str| pane_script(command).unwrap();
+        assert_eq!(
+            commands,
+            vec![
+                format!(
+                    "new-session -d -s api -c /src/api -n code sh -c {}",
+                    script("nvim")
+                ),
+                "split-window -c /src/api".to_string(),
+                "select-layout main-vertical".to_string(),
+                format!("new-window -c /src/api sh -c {}", script("npm run dev")),
+                "select-window -t =api:^".to_string(),
+            ]
+        );
+    }
+}
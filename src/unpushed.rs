@@ -0,0 +1,182 @@
+use git2::{BranchType, Repository};
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+
+use crate::parallel;
+use crate::report::{Cell, Report};
+use crate::scanner::{self, GitRepo};
+
+/// A local branch with commits no remote has.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Branch {
+    pub name: String,
+    /// Commits reachable from the branch but from no remote-tracking branch
+    pub commits: usize,
+}
+
+/// Work in one repository that exists only on this machine.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct RepoUnpushed {
+    pub name: String,
+    pub path: PathBuf,
+    /// The repository has no remote at all, so none of its commits are pushed
+    pub no_remote: bool,
+    /// Branches with unpushed commits; empty for a repository without a remote
+    pub branches: Vec<Branch>,
+}
+
+/// Find unpushed work in every repository, in parallel, keeping input order.
+///
+/// Repositories with nothing to lose are left out, as are linked worktrees,
+/// whose branches are those of their main repository.
+pub fn collect(repos: &[GitRepo]) -> Vec<RepoUnpushed> {
+    parallel::filter_map(repos, parallel::default_workers(), |repo| {
+        check(repo).filter(|found| found.no_remote || !found.branches.is_empty())
+    })
+}
+
+/// The unpushed work in `repo`. `None` when it cannot be opened, is a linked
+/// worktree, or has no commits.
+fn check(repo: &GitRepo) -> Option<RepoUnpushed> {
+    let mut unpushed = RepoUnpushed {
+        name: repo.name.clone(),
+        path: repo.path.clone(),
+        no_remote: false,
+        branches: Vec::new(),
+    };
+    let repo = Repository::open(&repo.path).ok()?;
+    if repo.is_worktree() {
+        return None;
+    }
+    let locals: Vec<(String, git2::Oid)> = repo
+        .branches(Some(BranchType::Local))
+        .ok()?
+        .flatten()
+        .filter_map(|(branch, _)| {
+            let name = branch.name().ok().flatten()?.to_string();
+            Some((name, branch.get().target()?))
+        })
+        .collect();
+    if locals.is_empty() {
+        return None;
+    }
+    if repo.remotes().map_or(true, |r| r.is_empty()) {
+        unpushed.no_remote = true;
+        return Some(unpushed);
+    }
+
+    for (name, tip) in locals {
+        let commits = count_unpushed(&repo, tip).unwrap_or(0);
+        if commits > 0 {
+            unpushed.branches.push(Branch { name, commits });
+        }
+    }
+    unpushed.branches.sort_by(|a, b| a.name.cmp(&b.name));
+    Some(unpushed)
+}
+
+/// Commits reachable from `tip` but from no remote-tracking branch, so a branch
+/// pushed under another name, or merged into one that was pushed, counts as
+/// pushed.
+fn count_unpushed(repo: &Repository, tip: git2::Oid) -> Result<usize, git2::Error> {
+    let mut walk = repo.revwalk()?;
+    walk.push(tip)?;
+    walk.hide_glob("refs/remotes/*")?;
+    Ok(walk.count())
+}
+
+/// The unpushed branches, one row each, and the repositories without a remote.
+pub fn build_reports(found: &[RepoUnpushed]) -> Vec<Report> {
+    let home = dirs::home_dir();
+    let location = |path: &Path| Cell::new(scanner::shorten_home(path, home.as_deref()));
+
+    let branches = found
+        .iter()
+        .flat_map(|repo| {
+            repo.branches.iter().map(|branch| {
+                vec![
+                    Cell::new(repo.name.clone()),
+                    Cell::new(branch.name.clone()),
+                    Cell::new(branch.commits.to_string()),
+                    location(&repo.path),
+                ]
+            })
+        })
+        .collect();
+    let no_remote = found
+        .iter()
+        .filter(|repo| repo.no_remote)
+        .map(|repo| vec![Cell::new(repo.name.clone()), location(&repo.path)])
+        .collect();
+
+    vec![
+        Report {
+            title: "Unpushed branches".to_string(),
+            notes: Vec::new(),
+            headers: ["Repository", "Branch", "Commits", "Path"]
+                .iter()
+                .map(|h| h.to_string())
+                .collect(),
+            rows: branches,
+        },
+        Report {
+            title: "Without a remote".to_string(),
+            notes: Vec::new(),
+            headers: ["Repository", "Path"]
+                .iter()
+                .map(|h| h.to_string())
+                .collect(),
+            rows: no_remote,
+        },
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::commit;
+
+    #[test]
+    fn test_collect_counts_commits_no_remote_has() {
+        let tmp = tempfile::tempdir().unwrap();
+        let api = tmp.path().join("api");
+        let repo = Repository::init(&api).unwrap();
+        repo.remote("origin", "git@github.com:acme/api.git")
+            .unwrap();
+        let pushed = commit(&repo, "first", None);
+        repo.reference("refs/remotes/origin/main", pushed, false, "")
+            .unwrap();
+        repo.branch("merged", &repo.find_commit(pushed).unwrap(), false)
+            .unwrap();
+        commit(&repo, "second", None);
+        commit(&repo, "third", None);
+
+        let notes = tmp.path().join("notes");
+        commit(&Repository::init(&notes).unwrap(), "todo", None);
+        let empty = tmp.path().join("empty");
+        Repository::init(&empty).unwrap();
+
+        let found = collect(&[
+            GitRepo::new(api.clone()),
+            GitRepo::new(notes.clone()),
+            GitRepo::new(empty),
+        ]);
+        assert_eq!(found.len(), 2);
+        assert_eq!(found[0].name, "api");
+        assert!(!found[0].no_remote);
+        let head = repo.head().unwrap().shorthand().unwrap().to_string();
+        assert_eq!(
+            found[0].branches,
+            vec![Branch {
+                name: head,
+                commits: 2,
+            }]
+        );
+        assert_eq!(found[1].path, notes);
+        assert!(found[1].no_remote && found[1].branches.is_empty());
+
+        let reports = build_reports(&found);
+        assert_eq!(reports[0].rows.len(), 1);
+        assert_eq!(reports[1].rows[0][0].text, "notes");
+    }
+}
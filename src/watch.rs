@@ -0,0 +1,293 @@
+use notify::event::{EventKind, ModifyKind};
+use notify::{RecommendedWatcher, RecursiveMode, Watcher as _};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc;
+use std::time::{Duration, SystemTime};
+
+use crate::scanner::ScanOptions;
+
+/// How often the daemon should call [`Watcher::changed`] when filesystem
+/// events are used; it only drains a channel, so checking often is free.
+pub const EVENT_CHECK: Duration = Duration::from_secs(1);
+
+/// Notices repositories being cloned, moved, or deleted under the search
+/// paths, for `gitnav daemon`.
+///
+/// Cloning or deleting a repository creates or removes an entry in the
+/// directory holding it, so the watcher follows every directory a scan walks
+/// through, except the insides of repositories. It subscribes to filesystem
+/// events (inotify, FSEvents, ReadDirectoryChangesW) on those directories,
+/// and falls back to comparing their modification times on each
+/// [`Watcher::changed`] call when events are unavailable or polling is asked
+/// for. Edits inside repositories are not reported.
+pub struct Watcher {
+    roots: Vec<PathBuf>,
+    options: ScanOptions,
+    dirs: HashMap<PathBuf, Option<SystemTime>>,
+    events: Option<Events>,
+}
+
+/// A native watch on each directory in [`Watcher::dirs`].
+struct Events {
+    watcher: RecommendedWatcher,
+    rx: mpsc::Receiver<notify::Result<notify::Event>>,
+}
+
+impl std::fmt::Debug for Watcher {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Watcher")
+            .field("roots", &self.roots)
+            .field("dirs", &self.dirs.len())
+            .field("polling", &self.is_polling())
+            .finish()
+    }
+}
+
+impl Watcher {
+    /// Start watching `roots` down to the scan depth, skipping the
+    /// directories the scanner skips. With `poll`, or when filesystem events
+    /// cannot be set up, changes are found by polling modification times.
+    pub fn new(roots: &[String], options: ScanOptions, poll: bool) -> Self {
+        let mut watcher = Self {
+            roots: roots.iter().map(PathBuf::from).collect(),
+            options,
+            dirs: HashMap::new(),
+            events: None,
+        };
+        watcher.dirs = watcher.snapshot();
+        if !poll {
+            watcher.events = Events::new().ok();
+            watcher.subscribe(&HashMap::new());
+        }
+        watcher
+    }
+
+    /// Whether changes are found by polling modification times rather than
+    /// by filesystem events.
+    pub fn is_polling(&self) -> bool {
+        self.events.is_none()
+    }
+
+    /// Whether a repository may have appeared or disappeared since the last call.
+    pub fn changed(&mut self) -> bool {
+        let changed = match &self.events {
+            Some(events) => events.pending(),
+            None => true,
+        };
+        if !changed {
+            // Events cannot report a root that did not exist yet
+            return self
+                .roots
+                .iter()
+                .any(|root| self.dirs.get(root).is_some_and(Option::is_none) && root.exists())
+                && self.refresh();
+        }
+        self.refresh()
+    }
+
+    /// Take a new snapshot, follow its directories, and report whether it
+    /// differs from the last one.
+    fn refresh(&mut self) -> bool {
+        let snapshot = self.snapshot();
+        let old = std::mem::replace(&mut self.dirs, snapshot);
+        if self.events.is_some() {
+            self.subscribe(&old);
+            // Any event on a followed directory is a change; the snapshot
+            // may miss it when timestamps are coarse
+            return true;
+        }
+        old != self.dirs
+    }
+
+    /// Watch the directories that are new since `old` and drop the ones that
+    /// are gone, falling back to polling if a watch cannot be added.
+    fn subscribe(&mut self, old: &HashMap<PathBuf, Option<SystemTime>>) {
+        let Some(events) = self.events.as_mut() else {
+            return;
+        };
+        for dir in old.keys().filter(|dir| !self.dirs.contains_key(*dir)) {
+            let _ = events.watcher.unwatch(dir);
+        }
+        let added = self.dirs.iter().filter(|(dir, modified)| {
+            modified.is_some() && !matches!(old.get(*dir), Some(Some(_)))
+        });
+        for (dir, _) in added {
+            match events.watcher.watch(dir, RecursiveMode::NonRecursive) {
+                Ok(()) => {}
+                // Deleted since the snapshot; its parent's event covers it
+                Err(e) if matches!(e.kind, notify::ErrorKind::PathNotFound) => {}
+                // Out of watches (inotify's max_user_watches) or unsupported
+                Err(_) => {
+                    self.events = None;
+                    return;
+                }
+            }
+        }
+    }
+
+    fn snapshot(&self) -> HashMap<PathBuf, Option<SystemTime>> {
+        let mut dirs = HashMap::new();
+        for root in &self.roots {
+            // A missing root is recorded too, so creating it counts as a change
+            dirs.insert(root.clone(), modified(root));
+
+            let options = self.options.for_root(root);
+            let ignore_patterns = options.ignore_patterns.clone();
+            let walker = options.walker(root, move |entry| {
+                let is_ignored = entry
+                    .file_name()
+                    .to_str()
+                    .is_some_and(|name| ignore_patterns.iter().any(|p| p == name));
+                // Below the root, a repository's own modification time
+                // changes with every file created at its top level
+                let is_repo = entry.depth() > 0 && entry.path().join(".git").exists();
+                !is_ignored && !is_repo
+            });
+            for entry in walker.flatten() {
+                if entry.file_type().is_some_and(|t| t.is_dir()) && entry.file_name() != ".git" {
+                    dirs.insert(entry.path().to_path_buf(), modified(entry.path()));
+                }
+            }
+        }
+        dirs
+    }
+}
+
+impl Events {
+    fn new() -> notify::Result<Self> {
+        let (tx, rx) = mpsc::channel();
+        Ok(Self {
+            watcher: notify::recommended_watcher(tx)?,
+            rx,
+        })
+    }
+
+    /// Drain the queued events, reporting whether any could mean a repository
+    /// appeared or disappeared.
+    fn pending(&self) -> bool {
+        let mut changed = false;
+        for event in self.rx.try_iter() {
+            changed |= match event {
+                Ok(event) => {
+                    event.need_rescan()
+                        || matches!(
+                            event.kind,
+                            EventKind::Any
+                                | EventKind::Create(_)
+                                | EventKind::Remove(_)
+                                | EventKind::Modify(ModifyKind::Name(_) | ModifyKind::Any)
+                        )
+                }
+                // Events may have been lost
+                Err(_) => true,
+            };
+        }
+        changed
+    }
+}
+
+fn modified(path: &Path) -> Option<SystemTime> {
+    path.metadata().and_then(|m| m.modified()).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    /// Move a directory's modification time so changes are seen even on
+    /// filesystems with coarse timestamps.
+    fn touch(dir: &Path, secs_ago: u64) {
+        let time = SystemTime::now() - Duration::from_secs(secs_ago);
+        fs::File::open(dir).unwrap().set_modified(time).unwrap();
+    }
+
+    fn options() -> ScanOptions {
+        ScanOptions {
+            max_depth: 5,
+            ignore_patterns: vec!["node_modules".to_string()],
+            ..ScanOptions::default()
+        }
+    }
+
+    /// Whether `changed` reports a change within half a second, for events that
+    /// arrive asynchronously.
+    fn changes(watcher: &mut Watcher) -> bool {
+        (0..25).any(|_| {
+            std::thread::sleep(Duration::from_millis(20));
+            watcher.changed()
+        })
+    }
+
+    #[test]
+    fn test_polling_sees_new_and_deleted_repos_only() {
+        let tmp = tempfile::tempdir().unwrap();
+        let root = tmp.path().to_path_buf();
+        fs::create_dir_all(root.join("work/api/.git")).unwrap();
+        fs::create_dir_all(root.join("node_modules")).unwrap();
+        touch(&root.join("work"), 60);
+
+        let roots = vec![root.display().to_string()];
+        let mut watcher = Watcher::new(&roots, options(), true);
+        assert!(watcher.is_polling());
+        assert!(!watcher.changed());
+
+        // Work inside a repository or an ignored directory is not a change
+        fs::write(root.join("work/api/README"), "x").unwrap();
+        fs::create_dir(root.join("node_modules/left-pad")).unwrap();
+        assert!(!watcher.changed());
+
+        fs::create_dir_all(root.join("work/web/.git")).unwrap();
+        touch(&root.join("work"), 30);
+        assert!(watcher.changed());
+        assert!(!watcher.changed());
+
+        fs::remove_dir_all(root.join("work/web")).unwrap();
+        touch(&root.join("work"), 0);
+        assert!(watcher.changed());
+    }
+
+    #[test]
+    fn test_events_see_new_and_deleted_repos_only() {
+        let tmp = tempfile::tempdir().unwrap();
+        let root = tmp.path().to_path_buf();
+        fs::create_dir_all(root.join("work/api/.git")).unwrap();
+        fs::create_dir_all(root.join("node_modules")).unwrap();
+
+        let roots = vec![root.display().to_string()];
+        let mut watcher = Watcher::new(&roots, options(), false);
+        assert!(!watcher.is_polling());
+        assert!(!changes(&mut watcher));
+
+        fs::write(root.join("work/api/README"), "x").unwrap();
+        fs::create_dir(root.join("node_modules/left-pad")).unwrap();
+        assert!(!changes(&mut watcher));
+
+        // A directory that appeared is followed from then on
+        fs::create_dir_all(root.join("work/team")).unwrap();
+        assert!(changes(&mut watcher));
+        assert!(!changes(&mut watcher));
+        fs::create_dir_all(root.join("work/team/web/.git")).unwrap();
+        assert!(changes(&mut watcher));
+
+        fs::remove_dir_all(root.join("work/team/web")).unwrap();
+        assert!(changes(&mut watcher));
+    }
+
+    #[test]
+    fn test_events_see_a_root_being_created() {
+        let tmp = tempfile::tempdir().unwrap();
+        let root = tmp.path().join("src");
+
+        let roots = vec![root.display().to_string()];
+        let mut watcher = Watcher::new(&roots, options(), false);
+        assert!(!watcher.is_polling());
+        assert!(!watcher.changed());
+
+        fs::create_dir_all(root.join("api/.git")).unwrap();
+        assert!(watcher.changed());
+        fs::create_dir_all(root.join("web/.git")).unwrap();
+        assert!(changes(&mut watcher));
+    }
+}
@@ -0,0 +1,242 @@
+use anyhow::{Context, Result};
+use std::path::{Component, Path, PathBuf};
+use std::process::{Command, Stdio};
+
+use crate::template::{Part, Placeholder, Template};
+
+/// A placeholder in the `worktree.path` template.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PathField {
+    /// `{repo}`: the repository's directory name
+    Repo,
+    /// `{branch}`: the branch, with `/` replaced by `-`
+    Branch,
+}
+
+impl Placeholder for PathField {
+    const ALL: &'static [(&'static str, Self)] =
+        &[("repo", PathField::Repo), ("branch", PathField::Branch)];
+}
+
+/// A working tree of a repository, as `git worktree list` reports it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Worktree {
+    pub path: PathBuf,
+    /// Checked-out branch (short name); `None` when HEAD is detached
+    pub branch: Option<String>,
+    /// The main working tree (or bare repository) rather than a linked one
+    pub main: bool,
+}
+
+/// Every working tree of the repository at `repo`, the main one first.
+///
+/// Works from the main working tree or any linked one.
+///
+/// # Errors
+///
+/// Returns an error if git cannot be started or fails (e.g. `repo` is not a
+/// repository)
+pub fn list(repo: &Path) -> Result<Vec<Worktree>> {
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(repo)
+        .args(["worktree", "list", "--porcelain"])
+        .stdin(Stdio::null())
+        .stderr(Stdio::piped())
+        .output()
+        .context("Failed to run git")?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        anyhow::bail!(
+            "git worktree list failed in {}: {}",
+            repo.display(),
+            stderr.lines().next().unwrap_or("").trim()
+        );
+    }
+    Ok(parse(&String::from_utf8_lossy(&output.stdout)))
+}
+
+/// Where `gitnav worktree --new` puts the worktree for `branch`.
+///
+/// `template` is the `worktree.path` setting; `~` is expanded and a relative
+/// result is taken relative to `main`, the repository's main working tree, so
+/// the default `../{repo}-{branch}` places worktrees beside it.
+///
+/// # Errors
+///
+/// Returns an error if the template is invalid
+pub fn new_path(template: &str, main: &Path, branch: &str) -> Result<PathBuf> {
+    let template = Template::<PathField>::parse(template).context("Invalid worktree.path")?;
+    let repo = main
+        .file_name()
+        .map(|n| n.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    let mut path = String::new();
+    for part in template.parts() {
+        match part {
+            Part::Text(text) => path.push_str(text),
+            Part::Field(PathField::Repo) => path.push_str(&repo),
+            Part::Field(PathField::Branch) => path.push_str(&branch.replace('/', "-")),
+        }
+    }
+    let path = PathBuf::from(shellexpand::tilde(&path).to_string());
+    // Resolve `..` so the printed path reads `/src/api-fix`, not `/src/api/../api-fix`
+    let mut resolved = PathBuf::new();
+    for component in main.join(path).components() {
+        match component {
+            Component::ParentDir => {
+                resolved.pop();
+            }
+            Component::CurDir => {}
+            component => resolved.push(component),
+        }
+    }
+    Ok(resolved)
+}
+
+/// Create a worktree for `branch` at `path` with `git worktree add`.
+///
+/// A branch that exists locally, or on exactly one remote, is checked out
+/// (git creates a tracking branch for a remote one); any other name becomes a
+/// new branch from the current HEAD. git's output goes to stderr, keeping
+/// stdout for the path.
+///
+/// # Errors
+///
+/// Returns an error if git cannot be started or fails (e.g. `path` exists)
+pub fn add(repo: &Path, path: &Path, branch: &str) -> Result<()> {
+    let mut cmd = Command::new("git");
+    cmd.arg("-C").arg(repo).args(["worktree", "add"]);
+    if branch_exists(repo, branch) {
+        cmd.arg(path).arg(branch);
+    } else {
+        cmd.args(["-b", branch]).arg(path);
+    }
+    let status = cmd
+        .stdin(Stdio::null())
+        .stdout(std::io::stderr())
+        .status()
+        .context("Failed to run git")?;
+    if !status.success() {
+        anyhow::bail!("git worktree add {} failed with {}", path.display(), status);
+    }
+    Ok(())
+}
+
+/// Whether `branch` is a local branch or a remote-tracking branch of any remote.
+fn branch_exists(repo: &Path, branch: &str) -> bool {
+    let Ok(repo) = git2::Repository::open(repo) else {
+        return false;
+    };
+    if repo.find_branch(branch, git2::BranchType::Local).is_ok() {
+        return true;
+    }
+    let Ok(remotes) = repo.remotes() else {
+        return false;
+    };
+    remotes.iter().flatten().any(|remote| {
+        repo.find_branch(&format!("{}/{}", remote, branch), git2::BranchType::Remote)
+            .is_ok()
+    })
+}
+
+/// Parse `git worktree list --porcelain`: one block of `key value` lines per
+/// working tree, separated by blank lines, the main working tree first.
+fn parse(output: &str) -> Vec<Worktree> {
+    let mut worktrees: Vec<Worktree> = Vec::new();
+    for line in output.lines() {
+        let (key, value) = line.split_once(' ').unwrap_or((line, ""));
+        match key {
+            "worktree" => worktrees.push(Worktree {
+                path: PathBuf::from(value),
+                branch: None,
+                main: worktrees.is_empty(),
+            }),
+            "branch" => {
+                if let Some(worktree) = worktrees.last_mut() {
+                    let branch = value.strip_prefix("refs/heads/").unwrap_or(value);
+                    worktree.branch = Some(branch.to_string());
+                }
+            }
+            _ => {}
+        }
+    }
+    worktrees
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::commit;
+
+    #[test]
+    fn test_parse() {
+        let output = "worktree /src/api\nHEAD 1a2b\nbranch refs/heads/main\n\n\
+                      worktree /src/api-fix\nHEAD 3c4d\nbranch refs/heads/fix/login\n\n\
+                      worktree /tmp/api-old\nHEAD 5e6f\ndetached\nprunable gitdir file points to non-existent location\n\n";
+        assert_eq!(
+            parse(output),
+            vec![
+                Worktree {
+                    path: PathBuf::from("/src/api"),
+                    branch: Some("main".to_string()),
+                    main: true,
+                },
+                Worktree {
+                    path: PathBuf::from("/src/api-fix"),
+                    branch: Some("fix/login".to_string()),
+                    main: false,
+                },
+                Worktree {
+                    path: PathBuf::from("/tmp/api-old"),
+                    branch: None,
+                    main: false,
+                },
+            ]
+        );
+        assert!(parse("").is_empty());
+    }
+
+    #[test]
+    fn test_new_path() {
+        let main = Path::new("/src/api");
+        assert_eq!(
+            new_path("../{repo}-{branch}", main, "fix/login").unwrap(),
+            PathBuf::from("/src/api-fix-login")
+        );
+        assert_eq!(
+            new_path("/tmp/trees/{repo}/{branch}", main, "main").unwrap(),
+            PathBuf::from("/tmp/trees/api/main")
+        );
+        assert_eq!(
+            new_path("./trees/{branch}", main, "main").unwrap(),
+            PathBuf::from("/src/api/trees/main")
+        );
+        assert!(new_path("{name}", main, "main").is_err());
+    }
+
+    #[test]
+    fn test_add_checks_out_or_creates_branch() {
+        let tmp = tempfile::tempdir().unwrap();
+        let main = tmp.path().join("api");
+        let repo = git2::Repository::init(&main).unwrap();
+        let first = commit(&repo, "first", None);
+        repo.branch("fix", &repo.find_commit(first).unwrap(), false)
+            .unwrap();
+
+        add(&main, &tmp.path().join("api-fix"), "fix").unwrap();
+        add(&main, &tmp.path().join("api-new"), "new").unwrap();
+
+        let branches: Vec<Option<String>> = list(&main)
+            .unwrap()
+            .into_iter()
+            .filter(|w| !w.main)
+            .map(|w| w.branch)
+            .collect();
+        assert_eq!(
+            branches,
+            vec![Some("fix".to_string()), Some("new".to_string())]
+        );
+        assert!(add(&main, &tmp.path().join("api-fix"), "other").is_err());
+    }
+}
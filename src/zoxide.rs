@@ -0,0 +1,108 @@
+use anyhow::{Context, Result};
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+
+use crate::scanner::{self, GitRepo};
+
+/// Record a visit to `path` in zoxide's database (`zoxide add`).
+///
+/// # Errors
+///
+/// Returns an error if zoxide cannot be started or fails
+pub fn add(path: &Path) -> Result<()> {
+    let status = Command::new("zoxide")
+        .arg("add")
+        .arg("--")
+        .arg(path)
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .status()
+        .context("Failed to run zoxide")?;
+    if !status.success() {
+        anyhow::bail!("zoxide add failed for {} ({})", path.display(), status);
+    }
+    Ok(())
+}
+
+/// Directories in zoxide's database, highest score first (`zoxide query --list`).
+///
+/// # Errors
+///
+/// Returns an error if zoxide cannot be started
+pub fn list() -> Result<Vec<PathBuf>> {
+    let output = Command::new("zoxide")
+        .args(["query", "--list"])
+        .stdin(Stdio::null())
+        .stderr(Stdio::null())
+        .output()
+        .context("Failed to run zoxide")?;
+    // zoxide exits with an error when its database is empty, listing nothing
+    Ok(String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .map(|line| line.trim_end_matches('\r'))
+        .filter(|line| !line.is_empty())
+        .map(PathBuf::from)
+        .collect())
+}
+
+/// The repositories among `dirs`: directories with `.git` at their root, so a
+/// subdirectory zoxide learned about is not listed as a repository of its own.
+pub fn repos(dirs: &[PathBuf]) -> Vec<GitRepo> {
+    dirs.iter()
+        .filter(|dir| dir.join(".git").exists())
+        .map(|dir| GitRepo::new(dir.clone()))
+        .collect()
+}
+
+/// Add `extra` to `repos`, leaving out repositories already listed.
+///
+/// The result is sorted by name, as after a scan; callers apply `search.order`.
+pub fn merge(repos: Vec<GitRepo>, extra: Vec<GitRepo>) -> Vec<GitRepo> {
+    let mut merged = repos;
+    merged.extend(extra);
+    scanner::dedup_repos(merged)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_repos_keeps_repository_roots() {
+        let tmp = tempfile::tempdir().unwrap();
+        let api = tmp.path().join("api");
+        let worktree = tmp.path().join("api-fix");
+        let notes = tmp.path().join("notes");
+        std::fs::create_dir_all(api.join(".git")).unwrap();
+        std::fs::create_dir_all(api.join("src")).unwrap();
+        std::fs::create_dir_all(&worktree).unwrap();
+        std::fs::write(worktree.join(".git"), "gitdir: ../api/.git/worktrees/fix\n").unwrap();
+        std::fs::create_dir_all(&notes).unwrap();
+
+        let dirs = vec![
+            api.join("src"),
+            notes,
+            worktree.clone(),
+            api.clone(),
+            tmp.path().join("deleted"),
+        ];
+        let found: Vec<PathBuf> = repos(&dirs).into_iter().map(|r| r.path).collect();
+        assert_eq!(found, vec![worktree, api]);
+    }
+
+    #[test]
+    fn test_merge_skips_known_repositories() {
+        let tmp = tempfile::tempdir().unwrap();
+        let api = tmp.path().join("api");
+        let web = tmp.path().join("web");
+        std::fs::create_dir_all(&api).unwrap();
+        std::fs::create_dir_all(&web).unwrap();
+
+        let merged = merge(
+            vec![GitRepo::new(web.clone())],
+            vec![GitRepo::new(api.clone()), GitRepo::new(web.clone())],
+        );
+        let paths: Vec<PathBuf> = merged.into_iter().map(|r| r.path).collect();
+        assert_eq!(paths, vec![api, web]);
+    }
+}